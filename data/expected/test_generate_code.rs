@@ -1,6 +1,42 @@
 #![allow(clippy::manual_is_ascii_check)]
 
-use scangen::{DfaData, FindMatches, Scanner, ScannerBuilder, ScannerModeData};
+use scangen::prelude::{
+    DfaData, FindMatches, RunClassData, Scanner, ScannerDef, ScannerModeData, StartSetData,
+};
+
+const SHARED_0: &[(usize, usize)] = &[(0, 1), (1, 2)];
+const SHARED_1: &[(usize, usize)] = &[(0, 1), (1, 2), (2, 3), (3, 4), (4, 5), (5, 6), (0, 0)];
+const SHARED_2: &[(usize, usize)] = &[
+    (0, 1),
+    (1, 2),
+    (2, 3),
+    (3, 4),
+    (4, 5),
+    (5, 6),
+    (6, 7),
+    (7, 8),
+    (0, 0),
+];
+const SHARED_3: &[(usize, usize)] = &[(0, 1), (0, 0)];
+const SHARED_4: &[(usize, usize)] = &[
+    (0, 1),
+    (1, 2),
+    (2, 3),
+    (3, 4),
+    (4, 5),
+    (5, 6),
+    (6, 7),
+    (7, 8),
+    (8, 9),
+    (9, 10),
+    (10, 11),
+    (11, 12),
+    (12, 13),
+    (0, 0),
+];
+const SHARED_5: &[(usize, usize)] = &[(0, 1), (1, 2), (2, 3), (0, 0)];
+const SHARED_6: &[(usize, usize)] = &[(0, 1), (1, 2), (0, 0)];
+const SHARED_7: &[(usize, usize)] = &[(0, 1), (1, 4), (0, 0), (4, 7), (7, 8)];
 
 const DFAS: &[DfaData] = &[
     /* 0 */
@@ -11,7 +47,7 @@ const DFAS: &[DfaData] = &[
         &[(0, 2), (1, 1), (1, 1)],
     ),
     /* 1 */
-    ("[\\s--\\r\\n]+", &[1], &[(0, 1), (1, 2)], &[(2, 1), (2, 1)]),
+    ("[\\s--\\r\\n]+", &[1], SHARED_0, &[(2, 1), (2, 1)]),
     /* 2 */
     (
         "(//.*(\\r\\n|\\r|\\n))",
@@ -23,47 +59,37 @@ const DFAS: &[DfaData] = &[
     (
         "(/\\*.*?\\*/)",
         &[4],
-        &[(0, 1), (1, 2), (2, 3), (3, 5), (0, 0)],
-        &[(3, 2), (3, 4), (5, 3), (5, 1), (4, 3)],
+        &[(0, 1), (1, 3), (3, 6), (6, 7), (0, 0)],
+        &[(3, 3), (5, 1), (6, 2), (3, 4), (6, 2), (7, 1), (6, 1)],
     ),
     /* 4 */
     (
         "%start",
         &[6],
-        &[(0, 1), (1, 2), (2, 3), (3, 4), (4, 5), (5, 6), (0, 0)],
-        &[(6, 1), (7, 2), (8, 4), (8, 6), (9, 5), (10, 3)],
+        SHARED_1,
+        &[(8, 1), (9, 2), (10, 4), (10, 6), (11, 5), (12, 3)],
     ),
     /* 5 */
     (
         "%title",
         &[6],
-        &[(0, 1), (1, 2), (2, 3), (3, 4), (4, 5), (5, 6), (0, 0)],
-        &[(6, 1), (8, 3), (8, 4), (11, 2), (12, 5), (13, 6)],
+        SHARED_1,
+        &[(8, 1), (10, 3), (10, 4), (13, 2), (14, 5), (15, 6)],
     ),
     /* 6 */
     (
         "%comment",
         &[8],
+        SHARED_2,
         &[
-            (0, 1),
-            (1, 2),
-            (2, 3),
-            (3, 4),
-            (4, 5),
-            (5, 6),
-            (6, 7),
-            (7, 8),
-            (0, 0),
-        ],
-        &[
-            (6, 1),
-            (14, 2),
-            (15, 3),
-            (16, 4),
-            (16, 5),
-            (13, 6),
-            (17, 7),
-            (8, 8),
+            (8, 1),
+            (16, 2),
+            (17, 3),
+            (18, 4),
+            (18, 5),
+            (15, 6),
+            (19, 7),
+            (10, 8),
         ],
     ),
     /* 7 */
@@ -84,90 +110,60 @@ const DFAS: &[DfaData] = &[
             (0, 0),
         ],
         &[
-            (6, 1),
-            (18, 2),
-            (7, 3),
-            (13, 5),
-            (13, 10),
-            (10, 6),
-            (19, 7),
-            (8, 8),
-            (20, 9),
-            (21, 4),
+            (8, 1),
+            (20, 2),
+            (9, 3),
+            (15, 5),
+            (15, 10),
+            (12, 6),
+            (21, 7),
+            (10, 8),
+            (22, 9),
+            (23, 4),
         ],
     ),
     /* 8 */
-    ("=", &[1], &[(0, 1), (0, 0)], &[(22, 1)]),
+    ("=", &[1], SHARED_3, &[(24, 1)]),
     /* 9 */
     (
         "%grammar_type",
         &[13],
+        SHARED_4,
         &[
-            (0, 1),
-            (1, 2),
-            (2, 3),
-            (3, 4),
-            (4, 5),
-            (5, 6),
-            (6, 7),
-            (7, 8),
-            (8, 9),
-            (9, 10),
-            (10, 11),
-            (11, 12),
-            (12, 13),
-            (0, 0),
-        ],
-        &[
-            (6, 1),
-            (23, 2),
-            (10, 5),
-            (10, 8),
-            (9, 3),
-            (9, 7),
-            (16, 4),
-            (16, 6),
-            (19, 9),
-            (8, 10),
-            (20, 11),
-            (21, 12),
-            (13, 13),
+            (8, 1),
+            (25, 2),
+            (12, 5),
+            (12, 8),
+            (11, 3),
+            (11, 7),
+            (18, 4),
+            (18, 6),
+            (21, 9),
+            (10, 10),
+            (22, 11),
+            (23, 12),
+            (15, 13),
         ],
     ),
     /* 10 */
     (
         "%line_comment",
         &[13],
+        SHARED_4,
         &[
-            (0, 1),
-            (1, 2),
-            (2, 3),
-            (3, 4),
-            (4, 5),
-            (5, 6),
-            (6, 7),
-            (7, 8),
-            (8, 9),
-            (9, 10),
-            (10, 11),
-            (11, 12),
-            (12, 13),
-            (0, 0),
-        ],
-        &[
-            (6, 1),
-            (12, 2),
-            (11, 3),
-            (17, 6),
-            (17, 12),
-            (13, 4),
-            (13, 7),
-            (19, 8),
-            (14, 9),
-            (15, 11),
-            (16, 5),
-            (16, 10),
-            (8, 13),
+            (8, 1),
+            (14, 2),
+            (13, 3),
+            (19, 6),
+            (19, 12),
+            (15, 4),
+            (15, 7),
+            (21, 8),
+            (16, 9),
+            (17, 11),
+            (18, 5),
+            (18, 10),
+            (10, 13),
         ],
     ),
     /* 11 */
@@ -192,20 +188,20 @@ const DFAS: &[DfaData] = &[
             (0, 0),
         ],
         &[
-            (6, 1),
-            (24, 2),
-            (12, 3),
-            (15, 6),
-            (15, 9),
-            (14, 4),
-            (14, 7),
-            (25, 8),
-            (19, 5),
-            (16, 10),
-            (16, 11),
-            (13, 12),
-            (17, 13),
-            (8, 14),
+            (8, 1),
+            (26, 2),
+            (14, 3),
+            (17, 6),
+            (17, 9),
+            (16, 4),
+            (16, 7),
+            (27, 8),
+            (21, 5),
+            (18, 10),
+            (18, 11),
+            (15, 12),
+            (19, 13),
+            (10, 14),
         ],
     ),
     /* 12 */
@@ -233,23 +229,23 @@ const DFAS: &[DfaData] = &[
             (0, 0),
         ],
         &[
-            (6, 1),
-            (9, 2),
-            (18, 3),
-            (8, 4),
-            (15, 7),
-            (15, 15),
-            (19, 5),
-            (19, 9),
-            (17, 10),
-            (17, 11),
-            (13, 6),
-            (13, 12),
-            (26, 13),
-            (12, 14),
-            (11, 8),
-            (27, 16),
-            (27, 17),
+            (8, 1),
+            (11, 2),
+            (20, 3),
+            (10, 4),
+            (17, 7),
+            (17, 15),
+            (21, 5),
+            (21, 9),
+            (19, 10),
+            (19, 11),
+            (15, 6),
+            (15, 12),
+            (28, 13),
+            (14, 14),
+            (13, 8),
+            (29, 16),
+            (29, 17),
         ],
     ),
     /* 13 */
@@ -272,288 +268,310 @@ const DFAS: &[DfaData] = &[
             (0, 0),
         ],
         &[
-            (6, 1),
-            (9, 2),
-            (18, 3),
-            (8, 4),
-            (15, 7),
-            (15, 10),
-            (19, 5),
-            (19, 8),
-            (26, 9),
-            (7, 6),
-            (27, 11),
-            (27, 12),
+            (8, 1),
+            (11, 2),
+            (20, 3),
+            (10, 4),
+            (17, 7),
+            (17, 10),
+            (21, 5),
+            (21, 8),
+            (28, 9),
+            (9, 6),
+            (29, 11),
+            (29, 12),
         ],
     ),
     /* 14 */
-    (
-        "%on",
-        &[3],
-        &[(0, 1), (1, 2), (2, 3), (0, 0)],
-        &[(6, 1), (15, 2), (17, 3)],
-    ),
+    ("%on", &[3], SHARED_5, &[(8, 1), (17, 2), (19, 3)]),
     /* 15 */
     (
         "%enter",
         &[6],
-        &[(0, 1), (1, 2), (2, 3), (3, 4), (4, 5), (5, 6), (0, 0)],
-        &[(6, 1), (13, 3), (13, 5), (17, 4), (8, 2), (10, 6)],
+        SHARED_1,
+        &[(8, 1), (15, 3), (15, 5), (19, 4), (10, 2), (12, 6)],
     ),
     /* 16 */
-    ("%%", &[2], &[(0, 1), (1, 2), (0, 0)], &[(6, 1), (6, 2)]),
+    ("%%", &[2], SHARED_6, &[(8, 1), (8, 2)]),
     /* 17 */
-    ("::", &[2], &[(0, 1), (1, 2), (0, 0)], &[(28, 1), (28, 2)]),
+    ("::", &[2], SHARED_6, &[(30, 1), (30, 2)]),
     /* 18 */
-    (":", &[1], &[(0, 1), (0, 0)], &[(28, 1)]),
+    (":", &[1], SHARED_3, &[(30, 1)]),
     /* 19 */
-    (";", &[1], &[(0, 1), (0, 0)], &[(29, 1)]),
+    (";", &[1], SHARED_3, &[(31, 1)]),
     /* 20 */
-    ("\\|", &[1], &[(0, 1), (0, 0)], &[(30, 1)]),
+    ("\\|", &[1], SHARED_3, &[(32, 1)]),
     /* 21 */
-    ("<", &[1], &[(0, 1), (0, 0)], &[(31, 1)]),
+    ("<", &[1], SHARED_3, &[(33, 1)]),
     /* 22 */
-    (">", &[1], &[(0, 1), (0, 0)], &[(32, 1)]),
+    (">", &[1], SHARED_3, &[(34, 1)]),
     /* 23 */
     (
         "\"(\\\\.|[^\\\\])*?\"",
         &[2],
-        &[(0, 1), (1, 4), (0, 0), (4, 7), (7, 8)],
+        SHARED_7,
         &[
-            (33, 1),
-            (33, 2),
-            (34, 4),
-            (35, 3),
-            (33, 2),
-            (34, 4),
-            (35, 3),
+            (35, 1),
+            (35, 2),
+            (36, 4),
+            (37, 3),
+            (35, 2),
+            (36, 4),
+            (37, 3),
             (4, 3),
         ],
     ),
     /* 24 */
     (
         "\'(\\\\\'|[^\'])*?\'",
-        &[2],
-        &[(0, 1), (1, 4), (0, 0), (4, 7), (7, 8)],
+        &[2, 5],
+        &[(0, 1), (1, 4), (0, 0), (4, 7), (7, 10), (0, 0)],
         &[
-            (36, 1),
-            (36, 2),
-            (34, 4),
-            (37, 3),
-            (36, 2),
-            (34, 4),
-            (37, 3),
-            (36, 3),
+            (38, 1),
+            (38, 2),
+            (36, 4),
+            (39, 3),
+            (38, 2),
+            (36, 4),
+            (39, 3),
+            (38, 5),
+            (36, 4),
+            (39, 3),
         ],
     ),
     /* 25 */
     (
         "\\u{2F}(\\\\.|[^\\\\])*?\\u{2F}",
         &[2],
-        &[(0, 1), (1, 4), (0, 0), (4, 7), (7, 8)],
+        SHARED_7,
         &[
-            (38, 1),
-            (38, 2),
-            (34, 4),
-            (35, 3),
-            (38, 2),
-            (34, 4),
-            (35, 3),
+            (3, 1),
+            (3, 2),
+            (36, 4),
+            (40, 3),
+            (3, 2),
+            (36, 4),
+            (40, 3),
             (4, 3),
         ],
     ),
     /* 26 */
-    ("\\(", &[1], &[(0, 1), (0, 0)], &[(39, 1)]),
+    ("\\(", &[1], SHARED_3, &[(41, 1)]),
     /* 27 */
-    ("\\)", &[1], &[(0, 1), (0, 0)], &[(40, 1)]),
+    ("\\)", &[1], SHARED_3, &[(42, 1)]),
     /* 28 */
-    ("\\[", &[1], &[(0, 1), (0, 0)], &[(41, 1)]),
+    ("\\[", &[1], SHARED_3, &[(43, 1)]),
     /* 29 */
-    ("\\]", &[1], &[(0, 1), (0, 0)], &[(42, 1)]),
+    ("\\]", &[1], SHARED_3, &[(44, 1)]),
     /* 30 */
-    ("\\{", &[1], &[(0, 1), (0, 0)], &[(43, 1)]),
+    ("\\{", &[1], SHARED_3, &[(45, 1)]),
     /* 31 */
-    ("\\}", &[1], &[(0, 1), (0, 0)], &[(44, 1)]),
+    ("\\}", &[1], SHARED_3, &[(46, 1)]),
     /* 32 */
     (
         "[a-zA-Z_][a-zA-Z0-9_]*",
         &[1],
-        &[(0, 1), (1, 2)],
-        &[(45, 1), (46, 1)],
+        SHARED_0,
+        &[(47, 1), (48, 1)],
     ),
     /* 33 */
     (
         "%scanner",
         &[8],
+        SHARED_2,
         &[
-            (0, 1),
-            (1, 2),
-            (2, 3),
-            (3, 4),
-            (4, 5),
-            (5, 6),
-            (6, 7),
-            (7, 8),
-            (0, 0),
-        ],
-        &[
-            (6, 1),
-            (7, 2),
-            (14, 3),
-            (9, 4),
-            (17, 5),
-            (17, 6),
-            (13, 7),
-            (10, 8),
+            (8, 1),
+            (9, 2),
+            (16, 3),
+            (11, 4),
+            (19, 5),
+            (19, 6),
+            (15, 7),
+            (12, 8),
         ],
     ),
     /* 34 */
-    (",", &[1], &[(0, 1), (0, 0)], &[(47, 1)]),
+    (",", &[1], SHARED_3, &[(49, 1)]),
     /* 35 */
-    (
-        "%sc",
-        &[3],
-        &[(0, 1), (1, 2), (2, 3), (0, 0)],
-        &[(6, 1), (7, 2), (14, 3)],
-    ),
+    ("%sc", &[3], SHARED_5, &[(8, 1), (9, 2), (16, 3)]),
     /* 36 */
     (
         "%push",
         &[5],
         &[(0, 1), (1, 2), (2, 3), (3, 4), (4, 5), (0, 0)],
-        &[(6, 1), (21, 2), (18, 3), (7, 4), (48, 5)],
+        &[(8, 1), (23, 2), (20, 3), (9, 4), (50, 5)],
     ),
     /* 37 */
     (
         "%pop",
         &[4],
         &[(0, 1), (1, 2), (2, 3), (3, 4), (0, 0)],
-        &[(6, 1), (21, 3), (21, 4), (15, 2)],
+        &[(8, 1), (23, 3), (23, 4), (17, 2)],
     ),
     /* 38 */
-    ("\\^", &[1], &[(0, 1), (0, 0)], &[(49, 1)]),
+    ("\\^", &[1], SHARED_3, &[(51, 1)]),
     /* 39 */
-    (".", &[1], &[(0, 1), (0, 0)], &[(4, 1)]),
+    (".", &[1], SHARED_3, &[(4, 1)]),
 ];
 
 const MODES: &[ScannerModeData] = &[];
 
+const START_SETS: &[StartSetData] = &[/* 0 */ (
+    0xffffffffffffffffffffffffffffffff,
+    &[('\u{80}', '\u{10ffff}')],
+)];
+
+const RUN_CLASSES: &[RunClassData] = &[
+    (
+        2,
+        (
+            0x00000000000000000000000100001a00,
+            &[
+                ('\u{85}', '\u{85}'),
+                ('\u{a0}', '\u{a0}'),
+                ('\u{1680}', '\u{1680}'),
+                ('\u{2000}', '\u{200a}'),
+                ('\u{2028}', '\u{2029}'),
+                ('\u{202f}', '\u{202f}'),
+                ('\u{205f}', '\u{205f}'),
+                ('\u{3000}', '\u{3000}'),
+            ],
+        ),
+    ),
+    (48, (0x07fffffe87fffffe03ff000000000000, &[])),
+];
+
 fn matches_char_class(c: char, char_class: usize) -> bool {
     match char_class {
         /* \r */
         0 => c == '\r',
         /* \n */
         1 => c == '\n',
-        /* [\s--\r\n] */
-        2 => c.is_whitespace() && !(c == '\r' || c == '\n'),
+        /* [\t\u{b}-\u{c} \u{85}\u{a0}\u{1680}\u{2000}-\u{200a}\u{2028}-\u{2029}\u{202f}\u{205f}\u{3000}] */
+        2 => {
+            matches!(c, '\t' | '\u{b}'..='\u{c}' | ' ' | '\u{85}' | '\u{a0}' | '\u{1680}' | '\u{2000}'..='\u{200a}' | '\u{2028}'..='\u{2029}' | '\u{202f}' | '\u{205f}' | '\u{3000}')
+        }
         /* / */
         3 => c == '/',
-        /* . */
-        4 => c != '\n' && c != '\r',
+        /* [^\n\r] */
+        4 => !matches!(c, '\n' | '\r'),
+        /* [^\n\r\*] */
+        5 => !matches!(c, '\n' | '\r' | '*'),
         /* \* */
-        5 => c == '*',
+        6 => c == '*',
+        /* [^\n\r\*\/] */
+        7 => !matches!(c, '\n' | '\r' | '*' | '/'),
         /* % */
-        6 => c == '%',
+        8 => c == '%',
         /* s */
-        7 => c == 's',
+        9 => c == 's',
         /* t */
-        8 => c == 't',
+        10 => c == 't',
         /* a */
-        9 => c == 'a',
+        11 => c == 'a',
         /* r */
-        10 => c == 'r',
+        12 => c == 'r',
         /* i */
-        11 => c == 'i',
+        13 => c == 'i',
         /* l */
-        12 => c == 'l',
+        14 => c == 'l',
         /* e */
-        13 => c == 'e',
+        15 => c == 'e',
         /* c */
-        14 => c == 'c',
+        16 => c == 'c',
         /* o */
-        15 => c == 'o',
+        17 => c == 'o',
         /* m */
-        16 => c == 'm',
+        18 => c == 'm',
         /* n */
-        17 => c == 'n',
+        19 => c == 'n',
         /* u */
-        18 => c == 'u',
+        20 => c == 'u',
         /* _ */
-        19 => c == '_',
+        21 => c == '_',
         /* y */
-        20 => c == 'y',
+        22 => c == 'y',
         /* p */
-        21 => c == 'p',
+        23 => c == 'p',
         /* = */
-        22 => c == '=',
+        24 => c == '=',
         /* g */
-        23 => c == 'g',
+        25 => c == 'g',
         /* b */
-        24 => c == 'b',
+        26 => c == 'b',
         /* k */
-        25 => c == 'k',
+        27 => c == 'k',
         /* w */
-        26 => c == 'w',
+        28 => c == 'w',
         /* f */
-        27 => c == 'f',
+        29 => c == 'f',
         /* : */
-        28 => c == ':',
+        30 => c == ':',
         /* ; */
-        29 => c == ';',
+        31 => c == ';',
         /* \| */
-        30 => c == '|',
+        32 => c == '|',
         /* < */
-        31 => c == '<',
+        33 => c == '<',
         /* > */
-        32 => c == '>',
+        34 => c == '>',
         /* " */
-        33 => c == '\"',
+        35 => c == '\"',
         /* \\ */
-        34 => c == '\\',
-        /* [^\\] */
-        35 => c != '\\',
+        36 => c == '\\',
+        /* [^"\\] */
+        37 => !matches!(c, '\"' | '\\'),
         /* ' */
-        36 => c == '\'',
-        /* [^'] */
-        37 => c != '\'',
-        /* \u{2F} */
-        38 => c == '/',
+        38 => c == '\'',
+        /* [^'\\] */
+        39 => !matches!(c, '\'' | '\\'),
+        /* [^/\\] */
+        40 => !matches!(c, '/' | '\\'),
         /* \( */
-        39 => c == '(',
+        41 => c == '(',
         /* \) */
-        40 => c == ')',
+        42 => c == ')',
         /* \[ */
-        41 => c == '[',
+        43 => c == '[',
         /* \] */
-        42 => c == ']',
+        44 => c == ']',
         /* \{ */
-        43 => c == '{',
+        45 => c == '{',
         /* \} */
-        44 => c == '}',
-        /* [a-zA-Z_] */
-        45 => ('a'..='z').contains(&c) || ('A'..='Z').contains(&c) || c == '_',
-        /* [a-zA-Z0-9_] */
-        46 => {
-            ('a'..='z').contains(&c)
-                || ('A'..='Z').contains(&c)
-                || ('0'..='9').contains(&c)
-                || c == '_'
+        46 => c == '}',
+        /* [A-Z_a-z] */
+        47 => {
+            const MASK: u128 = 0x07fffffe87fffffe0000000000000000;
+            c.is_ascii() && (MASK >> (c as u32)) & 1 == 1
+        }
+        /* [0-9A-Z_a-z] */
+        48 => {
+            const MASK: u128 = 0x07fffffe87fffffe03ff000000000000;
+            c.is_ascii() && (MASK >> (c as u32)) & 1 == 1
         }
         /* , */
-        47 => c == ',',
+        49 => c == ',',
         /* h */
-        48 => c == 'h',
+        50 => c == 'h',
         /* \^ */
-        49 => c == '^',
+        51 => c == '^',
         _ => false,
     }
 }
 
+pub(crate) static SCANNER_DEF: ScannerDef = ScannerDef::new(DFAS, MODES)
+    .generated_with("0.1.0", 2)
+    .with_start_sets(START_SETS)
+    .with_run_classes(RUN_CLASSES);
+
 pub(crate) fn create_scanner() -> Scanner {
-    ScannerBuilder::new()
-        .add_dfa_data(DFAS)
-        .add_scanner_mode_data(MODES)
-        .build()
+    SCANNER_DEF.create_scanner()
+}
+
+/// Returns the scanner that is shared by all callers. It is created on first use.
+pub(crate) fn scanner() -> &'static Scanner {
+    static SCANNER: std::sync::OnceLock<Scanner> = std::sync::OnceLock::new();
+    SCANNER.get_or_init(|| SCANNER_DEF.create_scanner())
 }
 
 pub(crate) fn create_find_iter<'h>(scanner: &Scanner, input: &'h str) -> FindMatches<'h> {
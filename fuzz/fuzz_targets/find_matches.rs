@@ -0,0 +1,33 @@
+//! Scans arbitrary haystacks with the scanner of the test grammar, optionally with a maximum
+//! token length and a step budget.
+
+#![no_main]
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use scangen::TokenType;
+use scangen_fuzz::{check_matches, check_peek, scanner_with_modes, unused_match_function};
+
+#[derive(Debug, Arbitrary)]
+struct Input<'a> {
+    haystack: &'a str,
+    max_token_length: Option<u8>,
+    step_budget: Option<u8>,
+    peek: u8,
+}
+
+fuzz_target!(|input: Input<'_>| {
+    let mut scanner = scanner_with_modes().clone();
+    if let Some(max_token_length) = input.max_token_length {
+        scanner.set_max_token_length(max_token_length as usize, TokenType::new(0));
+    }
+    scanner.set_step_budget(input.step_budget.map(usize::from));
+    check_matches(
+        input.haystack,
+        scanner.find_iter(input.haystack, unused_match_function),
+    );
+    check_peek(
+        scanner.find_iter(input.haystack, unused_match_function),
+        input.peek as usize % 8 + 1,
+    );
+});
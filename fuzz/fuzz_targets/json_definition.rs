@@ -0,0 +1,21 @@
+//! Builds scanners from arbitrary JSON definitions. Invalid definitions must be rejected with an
+//! error, valid ones must scan without panicking.
+//!
+//! The definition in `data/scanner_with_modes.json` is a good seed for the corpus.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use scangen::ScannerBuilder;
+use scangen_fuzz::{check_matches, unused_match_function};
+
+const HAYSTACK: &str = "id \"a\\tb\" /* c */ 42 ä\n\r\n";
+
+fuzz_target!(|json: &str| {
+    if let Ok(scanner) = ScannerBuilder::build_from_json(json) {
+        check_matches(
+            HAYSTACK,
+            scanner.find_iter(HAYSTACK, unused_match_function),
+        );
+    }
+});
@@ -0,0 +1,94 @@
+//! Builds scanners from arbitrary but valid DFA tables and scans arbitrary haystacks with them.
+
+#![no_main]
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use scangen::{OwnedDfaData, ScannerBuilder};
+use scangen_fuzz::check_matches;
+
+const MAX_DFAS: usize = 8;
+const MAX_STATES: usize = 16;
+const CLASS_COUNT: usize = 5;
+
+#[derive(Debug, Arbitrary)]
+struct Input<'a> {
+    dfas: Vec<ArbitraryDfa>,
+    haystack: &'a str,
+}
+
+#[derive(Debug, Arbitrary)]
+struct ArbitraryDfa {
+    states: Vec<ArbitraryState>,
+}
+
+#[derive(Debug, Arbitrary)]
+struct ArbitraryState {
+    accepting: bool,
+    transitions: Vec<(u8, u8)>,
+}
+
+impl ArbitraryDfa {
+    /// Converts the DFA into valid DFA data, i.e. each state has at most one transition per
+    /// character class and all targets exist.
+    fn dfa_data(&self, index: usize) -> OwnedDfaData {
+        let states = &self.states[..self.states.len().min(MAX_STATES)];
+        let state_count = states.len().max(1);
+        let mut dfa_data = OwnedDfaData {
+            pattern: format!("dfa{}", index),
+            ..Default::default()
+        };
+        for (state, arbitrary_state) in states.iter().enumerate() {
+            if arbitrary_state.accepting && state > 0 {
+                dfa_data.accepting_states.push(state);
+            }
+            let start = dfa_data.transitions.len();
+            for (char_class, target) in &arbitrary_state.transitions {
+                let char_class = *char_class as usize % CLASS_COUNT;
+                if dfa_data.transitions[start..]
+                    .iter()
+                    .all(|(other, _)| *other != char_class)
+                {
+                    dfa_data
+                        .transitions
+                        .push((char_class, *target as usize % state_count));
+                }
+            }
+            dfa_data
+                .state_ranges
+                .push((start, dfa_data.transitions.len()));
+        }
+        if dfa_data.state_ranges.is_empty() {
+            dfa_data.state_ranges.push((0, 0));
+        }
+        dfa_data
+    }
+}
+
+/// The character classes of the arbitrary DFAs. They overlap like the classes of real grammars.
+fn matches_char_class(c: char, char_class: usize) -> bool {
+    match char_class {
+        0 => c.is_ascii_lowercase(),
+        1 => c.is_ascii_digit(),
+        2 => c.is_whitespace(),
+        3 => !c.is_ascii(),
+        _ => c != '\n',
+    }
+}
+
+fuzz_target!(|input: Input<'_>| {
+    let dfas = input
+        .dfas
+        .iter()
+        .take(MAX_DFAS)
+        .enumerate()
+        .map(|(index, dfa)| dfa.dfa_data(index))
+        .collect::<Vec<_>>();
+    let Ok(scanner) = ScannerBuilder::new().add_owned_dfa_data(dfas).build() else {
+        return;
+    };
+    check_matches(
+        input.haystack,
+        scanner.find_iter(input.haystack, matches_char_class),
+    );
+});
@@ -0,0 +1,72 @@
+//! Helpers shared by the fuzz targets of `scangen`.
+//!
+//! The targets feed arbitrary haystacks and scanner tables into [FindMatches] and assert that the
+//! runtime doesn't panic and that the matches are well-formed.
+
+use std::sync::OnceLock;
+
+use scangen::{FindMatches, Scanner, ScannerBuilder};
+
+/// Returns the scanner of the test grammar with several scanner modes, e.g. for strings and
+/// comments. It is built from its JSON definition once.
+///
+/// The DFAs of the scanner know the characters of their classes, so the match function passed to
+/// it is never called, see [unused_match_function].
+pub fn scanner_with_modes() -> &'static Scanner {
+    static SCANNER: OnceLock<Scanner> = OnceLock::new();
+    SCANNER.get_or_init(|| {
+        ScannerBuilder::build_from_json(include_str!("../data/scanner_with_modes.json"))
+            .expect("The JSON definition of the test scanner is valid")
+    })
+}
+
+/// The match function for scanners whose DFAs know the characters of their classes.
+pub fn unused_match_function(_: char, _: usize) -> bool {
+    false
+}
+
+/// Collects the matches of the given iterator and asserts that
+/// * each match lies within the haystack and starts and ends at character boundaries,
+/// * no match overlaps the previous one and
+/// * the positions of the matches increase monotonically, i.e. the iterator makes progress.
+pub fn check_matches(input: &str, find_iter: FindMatches<'_>) {
+    let mut previous: Option<(usize, usize)> = None;
+    for matched in find_iter {
+        let span = matched.span();
+        assert!(
+            input.get(matched.range()).is_some(),
+            "The match {:?} is no valid span of the haystack {:?}",
+            matched,
+            input
+        );
+        if let Some((start, end)) = previous {
+            assert!(
+                span.start >= end,
+                "The match {:?} overlaps the previous match in {:?}",
+                matched,
+                input
+            );
+            assert!(
+                (span.start, span.end) > (start, end),
+                "The match {:?} doesn't advance in {:?}",
+                matched,
+                input
+            );
+        }
+        previous = Some((span.start, span.end));
+    }
+}
+
+/// Asserts that peeking ahead yields the same matches as the subsequent calls of
+/// [FindMatches::next_match].
+pub fn check_peek(mut find_iter: FindMatches<'_>, n: usize) {
+    loop {
+        let peeked = find_iter.peek_n(n).into_vec();
+        if peeked.is_empty() {
+            break;
+        }
+        for matched in peeked {
+            assert_eq!(find_iter.next_match(), Some(matched));
+        }
+    }
+}
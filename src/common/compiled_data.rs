@@ -1,3 +1,12 @@
+use super::{ScannerModeId, TokenType};
+
+/// The version of the format of the generated data.
+///
+/// It is increased whenever the runtime interprets the generated data differently. Generated code
+/// embeds the version it was generated with and the runtime rejects data of another version at
+/// compile time.
+pub const TABLE_FORMAT_VERSION: u32 = 2;
+
 /// The data of a DFA generated as Rust code.
 pub type DfaData = (
     // The pattern that this DFA recognizes.
@@ -11,14 +20,138 @@ pub type DfaData = (
     &'static [(usize, usize)],
 );
 
+/// The owned equivalent of [DfaData].
+///
+/// It is exported from a built scanner with `Scanner::export_dfa_data` and can be used to build a
+/// scanner again with `ScannerBuilder::add_owned_dfa_data`, e.g. after it has been stored.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OwnedDfaData {
+    /// The pattern that this DFA recognizes.
+    pub pattern: String,
+    /// The states that are accepting states.
+    pub accepting_states: Vec<usize>,
+    /// The ranges of transitions in the transitions vector. The state is used as index.
+    pub state_ranges: Vec<(usize, usize)>,
+    /// The transitions of the DFA. The first usize is the char class and the second usize is the
+    /// target state.
+    pub transitions: Vec<(usize, usize)>,
+    /// The counted states of the DFA with the minimum and the optional maximum number of
+    /// repetitions of the characters they loop on. See [CountedStateData].
+    pub counted_states: Vec<(usize, usize, Option<usize>)>,
+}
+
+impl From<&DfaData> for OwnedDfaData {
+    fn from(data: &DfaData) -> Self {
+        OwnedDfaData {
+            pattern: data.0.to_string(),
+            accepting_states: data.1.to_vec(),
+            state_ranges: data.2.to_vec(),
+            transitions: data.3.to_vec(),
+            counted_states: Vec::new(),
+        }
+    }
+}
+
+/// A state of a DFA that loops on the characters of a large repetition like `\d{1,100}`,
+/// generated as Rust code.
+///
+/// Instead of a state for each repetition the DFA has a single state that loops on the repeated
+/// characters. The scanner counts the repetitions: it enters the state with the first one, stays
+/// in it only up to the maximum number of repetitions and accepts or leaves it only after the
+/// minimum number of repetitions.
+pub type CountedStateData = (
+    // The index of the DFA.
+    usize,
+    // The counted state of the DFA.
+    usize,
+    // The minimum number of repetitions.
+    usize,
+    // The maximum number of repetitions, if bounded.
+    Option<usize>,
+);
+
 /// The data of a scanner mode generated as Rust code.
 pub type ScannerModeData = (
     // The name of the scanner mode.
     &'static str,
     // The DFAs of the scanner mode bundled with their associated token type numbers.
-    &'static [(usize, usize)],
+    &'static [(usize, TokenType)],
     // The transitions between the scanner modes triggered by a token type number.
     // The entries are tuples of the token type numbers and the new scanner mode index and are
     // sorted by token type number.
-    &'static [(usize, usize)],
+    &'static [(TokenType, ScannerModeId)],
+    // The token type of the tokens that cover input no terminal of the scanner mode matches.
+    // If set, the scanner makes progress in this mode on any input.
+    Option<TokenType>,
+);
+
+/// The configuration of a counting scanner mode, e.g. for nested comments.
+///
+/// A counting mode tracks the nesting depth of its tokens. Entering the mode sets the depth to
+/// one, the opening token type increments and the closing token type decrements it. When the
+/// depth drops to zero, the scanner switches to the exit mode.
+pub type CountingModeData = (
+    // The index of the scanner mode that counts the nesting depth.
+    usize,
+    // The token type that increments the nesting depth.
+    TokenType,
+    // The token type that decrements the nesting depth.
+    TokenType,
+    // The scanner mode that is entered when the nesting depth drops to zero.
+    ScannerModeId,
+);
+
+/// A contextual keyword generated as Rust code, see [crate::Scanner::reinterpret].
+///
+/// A contextual keyword has no DFA of its own in its scanner mode. The scanner matches it as
+/// another terminal, e.g. an identifier, and the parser reinterprets the match as keyword where the
+/// grammar expects it.
+pub type ContextualKeywordData = (
+    // The index of the scanner mode in which the keyword is contextual.
+    usize,
+    // The token type of the terminal that matches the keyword, e.g. the identifier.
+    TokenType,
+    // The text of the keyword.
+    &'static str,
+    // The token type of the keyword.
+    TokenType,
+);
+
+/// A case-insensitive keyword generated as Rust code.
+///
+/// Like a contextual keyword, a case-insensitive keyword has no DFA of its own in its scanner mode.
+/// The scanner matches it as another terminal, e.g. an identifier, and returns the match with the
+/// token type of the keyword if its text equals the keyword regardless of case. The text of the
+/// match keeps its original case.
+pub type CaseInsensitiveKeywordData = (
+    // The index of the scanner mode of the keyword.
+    usize,
+    // The token type of the terminal that matches the keyword, e.g. the identifier.
+    TokenType,
+    // The text of the keyword in lowercase.
+    &'static str,
+    // The token type of the keyword.
+    TokenType,
+);
+
+/// The set of characters that can start a token in a scanner mode generated as Rust code.
+pub type StartSetData = (
+    // A bitmap of the ASCII characters that can start a token. Bit `n` is set if the character
+    // with code point `n` can start a token.
+    u128,
+    // The sorted and disjoint ranges of the non-ASCII characters that can start a token.
+    &'static [(char, char)],
+);
+
+/// The characters of a character class that a DFA loops on in an accepting state, generated as
+/// Rust code.
+///
+/// Tokens like whitespace or identifiers consist of a long run of characters of such a class. The
+/// scanner consumes the run by testing the characters against this set instead of calling the
+/// match function for each of them.
+pub type RunClassData = (
+    // The index of the character class.
+    usize,
+    // The characters of the character class.
+    StartSetData,
 );
@@ -1,17 +1,17 @@
-use super::Span;
+use super::{Span, TokenType};
 
 /// A match in the haystack.
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub struct Match {
     /// The token type number associated with the match.
-    token_type: usize,
+    token_type: TokenType,
     /// The underlying match span.
     span: Span,
 }
 
 impl Match {
     /// Create a new match.
-    pub fn new(token_type: usize, span: Span) -> Self {
+    pub fn new(token_type: TokenType, span: Span) -> Self {
         Self { token_type, span }
     }
 
@@ -53,7 +53,7 @@ impl Match {
 
     /// Get the token type of the match.
     #[inline]
-    pub fn token_type(&self) -> usize {
+    pub fn token_type(&self) -> TokenType {
         self.token_type
     }
 }
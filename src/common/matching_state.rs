@@ -15,6 +15,8 @@ where
     start_position: Option<usize>,
     // The end position of the current match
     end_position: Option<usize>,
+    // The number of repetitions of the counted repetition the current state loops on
+    repetitions: usize,
 }
 
 impl<S> MatchingState<S>
@@ -40,6 +42,20 @@ where
         self.current_state
     }
 
+    /// Set the number of repetitions of the counted repetition the current state loops on.
+    #[cfg(feature = "runtime")]
+    #[inline]
+    pub(crate) fn set_repetitions(&mut self, repetitions: usize) {
+        self.repetitions = repetitions;
+    }
+
+    /// Get the number of repetitions of the counted repetition the current state loops on.
+    #[cfg(feature = "runtime")]
+    #[inline]
+    pub(crate) fn repetitions(&self) -> usize {
+        self.repetitions
+    }
+
     /// No transition was found.
     /// See matching_state.dot for the state diagram
     #[cfg(feature = "runtime")]
@@ -87,28 +103,30 @@ where
     }
 
     /// Transition to an accepting state.
+    /// The accepted character starts at position `i` and occupies `c_len` bytes of the input.
     /// See matching_state.dot for the state diagram
     #[cfg(feature = "runtime")]
-    pub(crate) fn transition_to_accepting(&mut self, i: usize, c: char) {
+    pub(crate) fn transition_to_accepting(&mut self, i: usize, c_len: usize) {
         match self.state {
             InnerMatchingState::None => {
                 *self = MatchingState {
                     current_state: S::default(),
                     state: InnerMatchingState::Accepting,
                     start_position: Some(i),
-                    end_position: Some(i + c.len_utf8()),
+                    end_position: Some(i + c_len),
+                    repetitions: 0,
                 }
             }
             InnerMatchingState::Start => {
                 *self = MatchingState {
                     state: InnerMatchingState::Accepting,
-                    end_position: Some(i + c.len_utf8()),
+                    end_position: Some(i + c_len),
                     ..self.clone()
                 }
             }
             InnerMatchingState::Accepting => {
                 *self = MatchingState {
-                    end_position: Some(i + c.len_utf8()),
+                    end_position: Some(i + c_len),
                     ..self.clone()
                 }
             }
@@ -118,6 +136,15 @@ where
         }
     }
 
+    /// Extends the recorded match to the given end position, e.g. after a run of characters that
+    /// all loop in the current accepting state.
+    #[cfg(feature = "runtime")]
+    pub(crate) fn extend_accepting_to(&mut self, end: usize) {
+        if self.state == InnerMatchingState::Accepting {
+            self.end_position = Some(end);
+        }
+    }
+
     /// Returns true if the current state is no match.
     #[inline]
     #[cfg(feature = "runtime")]
@@ -1,11 +1,22 @@
 /// Module that provides data types for the generated code
 mod compiled_data;
-pub use compiled_data::{DfaData, ScannerModeData};
+pub use compiled_data::{
+    CaseInsensitiveKeywordData, ContextualKeywordData, CountedStateData, CountingModeData, DfaData,
+    OwnedDfaData, RunClassData, ScannerModeData, StartSetData, TABLE_FORMAT_VERSION,
+};
 
 /// Module that provides a Match type
 mod match_type;
 pub use match_type::Match;
 
+/// Module that provides a TokenType type
+mod token_type;
+pub use token_type::TokenType;
+
+/// Module that provides a ScannerModeId type
+mod scanner_mode_id;
+pub use scanner_mode_id::ScannerModeId;
+
 /// Module that provides a Span type
 mod span;
 pub use span::Span;
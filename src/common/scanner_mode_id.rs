@@ -0,0 +1,55 @@
+/// The index of a scanner mode.
+///
+/// A scanner mode id identifies a scanner mode within a scanner. It is a newtype around `usize`
+/// to prevent accidental mixing of scanner mode indices with token types or pattern indices.
+///
+/// The id alone doesn't know the name of its scanner mode, so it is displayed as its index. Use
+/// `Scanner::display_mode` to display a scanner mode by its name.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ScannerModeId(usize);
+
+impl ScannerModeId {
+    /// Create a new scanner mode id.
+    #[inline]
+    pub const fn new(index: usize) -> Self {
+        ScannerModeId(index)
+    }
+
+    /// Get the scanner mode id as usize.
+    #[inline]
+    pub const fn as_usize(&self) -> usize {
+        self.0
+    }
+}
+
+impl From<usize> for ScannerModeId {
+    fn from(index: usize) -> Self {
+        ScannerModeId::new(index)
+    }
+}
+
+impl From<ScannerModeId> for usize {
+    fn from(id: ScannerModeId) -> Self {
+        id.0
+    }
+}
+
+impl std::fmt::Display for ScannerModeId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scanner_mode_id_conversions() {
+        const MODE: ScannerModeId = ScannerModeId::new(3);
+        assert_eq!(MODE.as_usize(), 3);
+        assert_eq!(ScannerModeId::from(3), MODE);
+        assert_eq!(usize::from(MODE), 3);
+        assert_eq!(MODE.to_string(), "3");
+    }
+}
@@ -30,6 +30,45 @@ impl Span {
     pub fn range(self) -> std::ops::Range<usize> {
         self.start..self.end
     }
+
+    /// Widen the span so that it doesn't split multi-byte characters of the given input.
+    /// The span is clamped to the length of the input beforehand.
+    /// This is useful for diagnostics, because slicing the input with the resulting span never
+    /// panics.
+    pub fn widen_to_char_boundaries(self, input: &str) -> Self {
+        let mut start = self.start.min(input.len());
+        let mut end = self.end.min(input.len()).max(start);
+        while !input.is_char_boundary(start) {
+            start -= 1;
+        }
+        while !input.is_char_boundary(end) {
+            end += 1;
+        }
+        Span { start, end }
+    }
+
+    /// Widen the span so that it doesn't split extended grapheme clusters of the given input,
+    /// e.g. a character followed by combining marks.
+    /// The span is clamped to the length of the input beforehand.
+    #[cfg(feature = "unicode-segmentation")]
+    pub fn widen_to_grapheme_boundaries(self, input: &str) -> Self {
+        use unicode_segmentation::UnicodeSegmentation;
+        let Span { start, end } = self.widen_to_char_boundaries(input);
+        let mut widened = Span { start, end };
+        for (index, grapheme) in input.grapheme_indices(true) {
+            let grapheme_end = index + grapheme.len();
+            if index <= start && start < grapheme_end {
+                widened.start = index;
+            }
+            if index < end && end < grapheme_end {
+                widened.end = grapheme_end;
+            }
+            if index >= end {
+                break;
+            }
+        }
+        widened
+    }
 }
 
 impl<T> From<std::ops::Range<T>> for Span
@@ -61,3 +100,56 @@ impl std::fmt::Display for Span {
         write!(f, "{}..{}", self.start, self.end)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_widen_to_char_boundaries() {
+        let input = "aäb€c";
+        // 'ä' occupies 1..3, '€' occupies 4..7
+        assert_eq!(
+            Span::new(2, 5).widen_to_char_boundaries(input),
+            Span::new(1, 7)
+        );
+        assert_eq!(
+            Span::new(0, 1).widen_to_char_boundaries(input),
+            Span::new(0, 1)
+        );
+        assert_eq!(
+            Span::new(5, 100).widen_to_char_boundaries(input),
+            Span::new(4, 8)
+        );
+        assert_eq!(
+            Span::new(100, 200).widen_to_char_boundaries(input),
+            Span::new(8, 8)
+        );
+        let span = Span::new(2, 2).widen_to_char_boundaries(input);
+        assert_eq!(span, Span::new(1, 3));
+        assert_eq!(&input[span.range()], "ä");
+    }
+
+    #[cfg(feature = "unicode-segmentation")]
+    #[test]
+    fn test_widen_to_grapheme_boundaries() {
+        // 'e' followed by a combining acute accent (2 bytes) forms one grapheme at 1..4
+        let input = "xe\u{301}y";
+        assert_eq!(
+            Span::new(1, 2).widen_to_grapheme_boundaries(input),
+            Span::new(1, 4)
+        );
+        assert_eq!(
+            Span::new(2, 3).widen_to_grapheme_boundaries(input),
+            Span::new(1, 4)
+        );
+        assert_eq!(
+            Span::new(0, 1).widen_to_grapheme_boundaries(input),
+            Span::new(0, 1)
+        );
+        assert_eq!(
+            Span::new(4, 5).widen_to_grapheme_boundaries(input),
+            Span::new(4, 5)
+        );
+    }
+}
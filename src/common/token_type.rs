@@ -0,0 +1,52 @@
+/// The token type number associated with a match.
+///
+/// A token type identifies a terminal symbol of the scanner. It is a newtype around `usize` to
+/// prevent accidental mixing of token types with scanner mode indices or pattern indices.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TokenType(usize);
+
+impl TokenType {
+    /// Create a new token type.
+    #[inline]
+    pub const fn new(token_type: usize) -> Self {
+        TokenType(token_type)
+    }
+
+    /// Get the token type as usize.
+    #[inline]
+    pub const fn as_usize(&self) -> usize {
+        self.0
+    }
+}
+
+impl From<usize> for TokenType {
+    fn from(token_type: usize) -> Self {
+        TokenType::new(token_type)
+    }
+}
+
+impl From<TokenType> for usize {
+    fn from(token_type: TokenType) -> Self {
+        token_type.0
+    }
+}
+
+impl std::fmt::Display for TokenType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_type_conversions() {
+        const TOKEN_TYPE: TokenType = TokenType::new(42);
+        assert_eq!(TOKEN_TYPE.as_usize(), 42);
+        assert_eq!(TokenType::from(42), TOKEN_TYPE);
+        assert_eq!(usize::from(TOKEN_TYPE), 42);
+        assert_eq!(TOKEN_TYPE.to_string(), "42");
+    }
+}
@@ -0,0 +1,163 @@
+//! This module provides the absent operator for terminals.
+//!
+//! A terminal of the form `P(?~X)Y` matches the prefix `P` followed by any text that contains no
+//! match of `X` up to and including the first match of the terminator `Y`, e.g.
+//! `<<(?~\n\.\n)\n\.\n` for a heredoc that ends at the first line with a single dot. The prefix
+//! is optional. The operator is borrowed from Onigmo. It is not part of the regex syntax, so the
+//! pattern is split before parsing and the DFA is built by the concatenation of the DFAs for `P`,
+//! for the complement of `(?s:.)*X(?s:.)*` and for `Y`. Afterwards all transitions leaving
+//! accepting states are removed, so the terminal ends at the first terminator.
+
+use crate::{Result, ScanGenError};
+
+macro_rules! unsupported {
+    ($feature:expr) => {
+        ScanGenError::new($crate::ScanGenErrorKind::UnsupportedFeature(
+            $feature.to_string(),
+        ))
+    };
+}
+
+/// An absent pattern `P(?~X)Y` split into its parts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct AbsentPattern<'a> {
+    /// The prefix `P`, which may be empty.
+    pub(crate) prefix: &'a str,
+    /// The pattern `X` that must not be contained in the text after the prefix.
+    pub(crate) excluded: &'a str,
+    /// The terminator `Y`.
+    pub(crate) terminator: &'a str,
+}
+
+/// Splits a pattern of the form `P(?~X)Y` into its parts.
+/// Returns `None` if the pattern doesn't contain the absent operator.
+/// # Errors
+/// An error is returned if the absent operator is not closed, if it is nested in a group or if
+/// `X` or `Y` is empty.
+pub(crate) fn split_absent_pattern(pattern: &str) -> Result<Option<AbsentPattern<'_>>> {
+    let mut depth = 0usize;
+    let mut in_class = 0usize;
+    // The start of the absent operator and the start of its excluded pattern.
+    let mut absent = None;
+    let mut chars = pattern.char_indices().peekable();
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            }
+            '[' => {
+                in_class += 1;
+                // A closing bracket right at the start of a class is a literal.
+                chars.next_if(|(_, c)| *c == '^');
+                chars.next_if(|(_, c)| *c == ']');
+            }
+            ']' if in_class > 0 => in_class -= 1,
+            '(' if in_class == 0 && pattern[index..].starts_with("(?~") => {
+                if depth > 0 || absent.is_some() {
+                    return Err(unsupported!(format!(
+                        "The absent operator in '{}' must be used once at the top level",
+                        pattern
+                    )));
+                }
+                absent = Some((index, index + "(?~".len()));
+                depth += 1;
+            }
+            '(' if in_class == 0 => depth += 1,
+            ')' if in_class == 0 && depth > 0 => {
+                depth -= 1;
+                if let (0, Some((start, excluded_start))) = (depth, absent) {
+                    let absent_pattern = AbsentPattern {
+                        prefix: &pattern[..start],
+                        excluded: &pattern[excluded_start..index],
+                        terminator: &pattern[index + 1..],
+                    };
+                    if absent_pattern.excluded.is_empty()
+                        || absent_pattern.terminator.is_empty()
+                        || split_absent_pattern(absent_pattern.terminator)?.is_some()
+                    {
+                        return Err(unsupported!(format!(
+                            "The absent operator in '{}' needs a non-empty pattern and a terminator \
+                            without another absent operator",
+                            pattern
+                        )));
+                    }
+                    return Ok(Some(absent_pattern));
+                }
+            }
+            _ => {}
+        }
+    }
+    match absent {
+        Some(_) => Err(unsupported!(format!(
+            "The absent operator in '{}' is not closed",
+            pattern
+        ))),
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiletime::{dfa::Dfa, DfaTable, StateID};
+
+    fn split(pattern: &str) -> Option<(&str, &str, &str)> {
+        split_absent_pattern(pattern)
+            .unwrap()
+            .map(|absent| (absent.prefix, absent.excluded, absent.terminator))
+    }
+
+    #[test]
+    fn test_split_absent_pattern() {
+        assert_eq!(split("a(b)c"), None);
+        assert_eq!(split(r"a\(?~b)c"), None);
+        assert_eq!(split(r"(?~\*/)\*/"), Some(("", r"\*/", r"\*/")));
+        assert_eq!(
+            split(r"/\*(?~(a|b)[)\]]\))END"),
+            Some((r"/\*", r"(a|b)[)\]]\)", "END"))
+        );
+        assert_eq!(split("(x)(?~[])]x)y"), Some(("(x)", "[])]x", "y")));
+        assert!(split_absent_pattern("(?~a").is_err());
+        assert!(split_absent_pattern("(?~a)").is_err());
+        assert!(split_absent_pattern("(?~)a").is_err());
+        assert!(split_absent_pattern("(a(?~b)c)").is_err());
+        assert!(split_absent_pattern("(?~a)b(?~c)d").is_err());
+    }
+
+    #[test]
+    fn test_absent_operator() {
+        // Raw text up to the first `END` that must not contain a blank line.
+        let dfa = DfaTable::from_pattern(r"(?~\n\n)END").unwrap();
+        assert_eq!(dfa.patterns(), [r"(?~\n\n)END"]);
+        assert!(!dfa.accepts(""));
+        assert!(dfa.accepts("END"));
+        assert!(dfa.accepts("raw\ntext END"));
+        assert!(!dfa.accepts("raw\n\ntext END"));
+        // The terminal stops at the first terminator.
+        assert!(!dfa.accepts("raw END END"));
+        assert!(!dfa.accepts("raw EN"));
+
+        let dfa = DfaTable::from_pattern(r"/\*(?~/\*)\*/").unwrap();
+        assert!(dfa.accepts("/* a */"));
+        assert!(dfa.accepts("/**/"));
+        assert!(!dfa.accepts("/* /* a */"));
+        assert!(!dfa.accepts("/* a */ */"));
+
+        // The terminator itself may match the excluded pattern.
+        let dfa = DfaTable::from_pattern(r"<<(?~\n\.\n)\n\.\n").unwrap();
+        assert!(dfa.accepts("<<a\nb\n.\n"));
+        assert!(!dfa.accepts("<<a\n.\nb\n.\n"));
+    }
+
+    #[test]
+    fn test_absent_operator_has_no_dead_states() {
+        // After an `x` the terminal can't match anymore, so the scanner must stop there instead
+        // of reading until the end of the input.
+        let dfa = Dfa::try_from_pattern("(?~x)y").unwrap();
+        for dfa in [dfa.minimize().unwrap(), dfa] {
+            let after_a = dfa.next_state(StateID::default(), 'a').unwrap();
+            assert_eq!(dfa.next_state(StateID::default(), 'x'), None);
+            assert_eq!(dfa.next_state(after_a, 'x'), None);
+        }
+    }
+}
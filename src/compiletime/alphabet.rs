@@ -0,0 +1,105 @@
+//! This module provides a representative alphabet for a set of character classes.
+//!
+//! Instead of intersecting the character classes symbolically we sample a set of candidate
+//! characters that contains all ASCII characters, the borders of all ranges of the character
+//! classes together with their neighbors and some non-ASCII characters. The candidates are
+//! partitioned by the set of character classes they match and one representative character is
+//! chosen for each partition.
+
+use std::collections::BTreeSet;
+
+use regex_syntax::hir::ClassUnicode;
+
+use super::MatchFunction;
+
+/// Some non-ASCII characters from different unicode categories used as candidates.
+const NON_ASCII_CANDIDATES: &[char] = &[
+    '\u{a0}', 'ä', 'é', 'ß', 'π', 'Ж', '中', '٣', '\u{2028}', '€', '😀',
+];
+
+/// Returns one representative character for each set of character classes a candidate character
+/// matches. Characters that match no character class at all are not returned.
+pub(crate) fn representative_chars(match_functions: &[(ClassUnicode, MatchFunction)]) -> Vec<char> {
+    let mut signatures = BTreeSet::new();
+    let mut representatives = Vec::new();
+    for c in candidate_chars(match_functions) {
+        let signature = match_functions
+            .iter()
+            .map(|(_, f)| f.call(c))
+            .collect::<Vec<_>>();
+        if signature.iter().any(|m| *m) && signatures.insert(signature) {
+            representatives.push(c);
+        }
+    }
+    representatives
+}
+
+/// Returns the candidate characters, i.e. all ASCII characters, some non-ASCII characters and the
+/// bounds of the ranges of the character classes together with their neighbors.
+pub(crate) fn candidate_chars(match_functions: &[(ClassUnicode, MatchFunction)]) -> BTreeSet<char> {
+    let mut candidates: BTreeSet<char> = (0u8..=127).map(char::from).collect();
+    candidates.extend(NON_ASCII_CANDIDATES.iter().copied());
+    for (class, _) in match_functions {
+        collect_chars(class, &mut candidates);
+    }
+    candidates
+}
+
+/// Collects the bounds of the ranges of the given class as well as their neighbors.
+fn collect_chars(class: &ClassUnicode, chars: &mut BTreeSet<char>) {
+    for range in class.iter() {
+        add_with_neighbors(range.start(), chars);
+        add_with_neighbors(range.end(), chars);
+    }
+}
+
+fn add_with_neighbors(c: char, chars: &mut BTreeSet<char>) {
+    chars.insert(c);
+    if let Some(prev) = char::from_u32((c as u32).wrapping_sub(1)) {
+        chars.insert(prev);
+    }
+    if let Some(next) = char::from_u32(c as u32 + 1) {
+        chars.insert(next);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiletime::{character_class::single_char_class, parse_regex_syntax};
+    use regex_syntax::hir::{Class, HirKind};
+
+    fn match_functions(classes: &[&str]) -> Vec<(ClassUnicode, MatchFunction)> {
+        classes
+            .iter()
+            .map(|c| {
+                let class = match parse_regex_syntax(c).unwrap().into_kind() {
+                    HirKind::Class(Class::Unicode(class)) => class,
+                    HirKind::Literal(literal) => {
+                        single_char_class(std::str::from_utf8(&literal.0).unwrap().parse().unwrap())
+                    }
+                    kind => panic!("Unexpected HIR {:?}", kind),
+                };
+                let match_function = MatchFunction::from(&class);
+                (class, match_function)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_representative_chars() {
+        let match_functions = match_functions(&["[a-z]", "x", "[0-9]"]);
+        let chars = representative_chars(&match_functions);
+        // One character for [a-z] without x, x itself and one for [0-9]
+        assert_eq!(chars.len(), 3);
+        assert!(chars.contains(&'x'));
+    }
+
+    #[test]
+    fn test_representative_chars_non_ascii() {
+        let match_functions = match_functions(&[r"\w", "[a-z]"]);
+        let chars = representative_chars(&match_functions);
+        // One character for [a-z] and one for \w without [a-z], e.g. a digit
+        assert_eq!(chars.len(), 2);
+    }
+}
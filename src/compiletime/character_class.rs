@@ -1,20 +1,24 @@
-use regex_syntax::ast::{Ast, Position, Span};
+use regex_syntax::{
+    hir::{ClassUnicode, ClassUnicodeRange},
+    is_meta_character,
+};
 
 use super::CharClassID;
 
 /// A character class that can match a character.
-#[derive(Default, Clone)]
+///
+/// The character class is taken from the HIR of the regex syntax, i.e. it is a sorted set of
+/// non-overlapping character ranges. Flags, case folding, Perl classes and class set operations
+/// are already resolved by the translation into the HIR.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub(crate) struct CharacterClass {
     pub(crate) id: CharClassID,
-    pub(crate) ast: ComparableAst,
+    pub(crate) class: ClassUnicode,
 }
 
 impl CharacterClass {
-    pub fn new(id: CharClassID, ast: Ast) -> Self {
-        CharacterClass {
-            id,
-            ast: ComparableAst(ast),
-        }
+    pub fn new(id: CharClassID, class: ClassUnicode) -> Self {
+        CharacterClass { id, class }
     }
 
     #[inline]
@@ -23,41 +27,34 @@ impl CharacterClass {
     }
 
     #[inline]
-    pub fn ast(&self) -> &Ast {
-        &self.ast.0
+    pub fn class(&self) -> &ClassUnicode {
+        &self.class
     }
 }
 
-impl std::fmt::Debug for CharacterClass {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "CharacterClass {{ id: {:?}, ast: {:?} }}",
-            self.id, self.ast
-        )
+impl Default for CharacterClass {
+    fn default() -> Self {
+        CharacterClass {
+            id: CharClassID::default(),
+            class: ClassUnicode::empty(),
+        }
     }
 }
 
 impl std::hash::Hash for CharacterClass {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
         self.id.hash(state);
-        self.ast.hash(state);
-        // Do not hash the match function, because it is not relevant for equality.
-        // Actually it is calculated from the AST, so it would be redundant.
-    }
-}
-
-impl PartialEq for CharacterClass {
-    fn eq(&self, other: &Self) -> bool {
-        self.id == other.id && self.ast == other.ast
+        // The class doesn't implement Hash, so we hash its ranges.
+        for range in self.class.ranges() {
+            range.start().hash(state);
+            range.end().hash(state);
+        }
     }
 }
 
-impl Eq for CharacterClass {}
-
 impl PartialOrd for CharacterClass {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.id.cmp(&other.id))
+        Some(self.cmp(other))
     }
 }
 
@@ -67,50 +64,128 @@ impl Ord for CharacterClass {
     }
 }
 
-/// A comparable AST in regard of a character class.
-/// It only compares AST types that are relevant for handling of character classes.
-#[derive(Debug, Clone, Eq)]
-pub(crate) struct ComparableAst(pub(crate) Ast);
-
-impl PartialEq for ComparableAst {
-    fn eq(&self, other: &Self) -> bool {
-        match (&self.0, &other.0) {
-            (Ast::Empty(_), Ast::Empty(_))
-            | (Ast::Dot(_), Ast::Dot(_))
-            | (Ast::Literal(_), Ast::Literal(_))
-            | (Ast::ClassUnicode(_), Ast::ClassUnicode(_))
-            | (Ast::ClassPerl(_), Ast::ClassPerl(_))
-            | (Ast::ClassBracketed(_), Ast::ClassBracketed(_)) => {
-                // Compare the string representation of the ASTs.
-                // This is a workaround because the AST's implementation of PartialEq also
-                // compares the span, which is not relevant for the character class handling here.
-                self.0.to_string() == other.0.to_string()
+impl std::fmt::Display for CharacterClass {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", class_to_string(&self.class))
+    }
+}
+
+/// Creates a character class that only contains the given character.
+pub(crate) fn single_char_class(c: char) -> ClassUnicode {
+    ClassUnicode::new([ClassUnicodeRange::new(c, c)])
+}
+
+/// Splits the given character classes into disjoint character classes, so that each of the given
+/// classes is the union of some of the returned classes.
+pub(crate) fn disjoint_classes<'a, I>(classes: I) -> Vec<ClassUnicode>
+where
+    I: IntoIterator<Item = &'a ClassUnicode>,
+{
+    let mut disjoint_classes: Vec<ClassUnicode> = Vec::new();
+    for class in classes {
+        let mut rest = class.clone();
+        let mut refined_classes = Vec::with_capacity(disjoint_classes.len() + 1);
+        for class in disjoint_classes {
+            let mut intersection = class.clone();
+            intersection.intersect(&rest);
+            if intersection.ranges().is_empty() {
+                refined_classes.push(class);
+                continue;
+            }
+            let mut difference = class;
+            difference.difference(&rest);
+            rest.difference(&intersection);
+            refined_classes.push(intersection);
+            if !difference.ranges().is_empty() {
+                refined_classes.push(difference);
             }
-            _ => false,
         }
+        if !rest.ranges().is_empty() {
+            refined_classes.push(rest);
+        }
+        disjoint_classes = refined_classes;
     }
+    disjoint_classes
 }
 
-impl std::hash::Hash for ComparableAst {
-    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
-        // Hash the string representation of the AST.
-        self.0.to_string().hash(state);
+/// Returns a readable regex syntax of the given character class.
+/// Single characters are rendered without brackets and a class is rendered negated if its
+/// complement consists of fewer ranges. Control and whitespace characters are escaped.
+pub(crate) fn class_to_string(class: &ClassUnicode) -> String {
+    if let [range] = class.ranges() {
+        if range.start() == range.end() {
+            return escape_char(range.start());
+        }
+    }
+    let mut negated = class.clone();
+    negated.negate();
+    let (prefix, ranges) = if negated.ranges().len() < class.ranges().len() {
+        ("[^", negated.ranges())
+    } else {
+        ("[", class.ranges())
+    };
+    let mut result = prefix.to_string();
+    for range in ranges {
+        result.push_str(&escape_char(range.start()));
+        if range.start() != range.end() {
+            result.push('-');
+            result.push_str(&escape_char(range.end()));
+        }
     }
+    result.push(']');
+    result
 }
 
-impl Default for ComparableAst {
-    fn default() -> Self {
-        ComparableAst(Ast::Empty(Box::new(Span {
-            start: Position {
-                offset: 0,
-                line: 0,
-                column: 0,
-            },
-            end: Position {
-                offset: 0,
-                line: 0,
-                column: 0,
-            },
-        })))
+/// The labels of the character classes in the renderings of automata for users, see the
+/// `Display` implementations of [super::dfa::Dfa] and [super::nfa::Nfa].
+///
+/// Short classes are labeled with their regex syntax. Longer ones, e.g. Unicode classes like `\d`,
+/// would make the rendering unreadable and are labeled `c0`, `c1`, ... in the order of their first
+/// use instead. Their regex syntax is listed in the legend.
+#[derive(Debug, Default)]
+pub(crate) struct ClassLabels {
+    legend: Vec<String>,
+}
+
+impl ClassLabels {
+    /// The maximum number of characters of a class that is labeled with its regex syntax.
+    const MAX_INLINE_LENGTH: usize = 16;
+
+    /// Returns the label of the given class.
+    pub(crate) fn label(&mut self, class: &ClassUnicode) -> String {
+        let syntax = class_to_string(class);
+        if syntax.chars().count() <= Self::MAX_INLINE_LENGTH {
+            return syntax;
+        }
+        let index = match self.legend.iter().position(|known| *known == syntax) {
+            Some(index) => index,
+            None => {
+                self.legend.push(syntax);
+                self.legend.len() - 1
+            }
+        };
+        format!("c{}", index)
+    }
+
+    /// Writes the regex syntax of the classes that are labeled by their number, if any.
+    pub(crate) fn write_legend(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.legend.is_empty() {
+            return Ok(());
+        }
+        writeln!(f, "Character classes:")?;
+        for (index, syntax) in self.legend.iter().enumerate() {
+            writeln!(f, "  c{}: {}", index, syntax)?;
+        }
+        Ok(())
+    }
+}
+
+fn escape_char(c: char) -> String {
+    if is_meta_character(c) {
+        format!("\\{}", c)
+    } else if c.is_control() || (c.is_whitespace() && c != ' ') {
+        c.escape_default().to_string()
+    } else {
+        c.to_string()
     }
 }
@@ -0,0 +1,218 @@
+//! This module contains the report that is created during the compilation of the terminals.
+
+/// A pair of terminals whose languages intersect, i.e. there is at least one string that is
+/// accepted by both terminals.
+///
+/// The terminals are identified by their index in the generated `DFAS` slice.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TerminalConflict {
+    /// The index of the first terminal.
+    pub first: usize,
+    /// The index of the second terminal, which is always greater than the first one.
+    pub second: usize,
+    /// A shortest example string that is accepted by both terminals.
+    pub witness: String,
+}
+
+impl std::fmt::Display for TerminalConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Terminals #{} and #{} both match {:?}",
+            self.first, self.second, self.witness
+        )
+    }
+}
+
+/// A literal terminal, e.g. a keyword, whose string is also matched by another terminal of the
+/// same scanner mode, e.g. the identifier terminal.
+///
+/// Both terminals match the literal string with the same length, so the priority within the
+/// scanner mode decides which one wins. The terminals are identified by their index in the
+/// generated `DFAS` slice.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LiteralShadowing {
+    /// The name of the scanner mode in which both terminals are active.
+    pub mode: String,
+    /// The index of the literal terminal.
+    pub literal: usize,
+    /// The index of the terminal that also matches the literal string.
+    pub other: usize,
+    /// The index of the terminal that wins under the current tie-breaking policy, i.e. the
+    /// terminal that comes first in the scanner mode.
+    pub winner: usize,
+}
+
+impl LiteralShadowing {
+    /// Returns true if the literal terminal loses against the other terminal and thus can never
+    /// be matched in this scanner mode.
+    pub fn is_literal_unreachable(&self) -> bool {
+        self.winner != self.literal
+    }
+}
+
+impl std::fmt::Display for LiteralShadowing {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Mode {}: literal terminal #{} is also matched by terminal #{}, #{} wins",
+            self.mode, self.literal, self.other, self.winner
+        )
+    }
+}
+
+/// A scanner mode in which the scanner can't make progress on some input, because the mode has
+/// neither a catch-all terminal nor an error token type.
+///
+/// A mode has a catch-all if each character is matched on its own by one of its terminals, e.g.
+/// by the terminals `\r\n|\r|\n` and `.`. Otherwise input that no terminal matches is skipped
+/// silently, unless the mode declares an error token type in its [crate::ScannerModeData].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MissingCatchAll {
+    /// The name of the scanner mode.
+    pub mode: String,
+    /// A character that is matched by no terminal of the scanner mode.
+    pub example: char,
+}
+
+impl std::fmt::Display for MissingCatchAll {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Mode {}: {:?} is matched by no terminal and no error token type is declared",
+            self.mode, self.example
+        )
+    }
+}
+
+/// A terminal whose DFA has unusually many character classes or states relative to the length
+/// of its pattern.
+///
+/// This is often a sign of a mis-written pattern, e.g. the class `[.\r\n]`, which matches only a
+/// dot, a carriage return and a line feed, or a repetition of an alternative whose branches
+/// overlap. The terminal is identified by its index in the generated `DFAS` slice.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatternExplosion {
+    /// The index of the terminal.
+    pub terminal: usize,
+    /// The pattern of the terminal.
+    pub pattern: String,
+    /// The number of distinct character classes the DFA of the terminal uses.
+    pub char_classes: usize,
+    /// The number of states of the DFA of the terminal.
+    pub states: usize,
+}
+
+impl std::fmt::Display for PatternExplosion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Terminal #{} {:?} compiles to {} character classes and {} states, which is unusually \
+            many for its length",
+            self.terminal, self.pattern, self.char_classes, self.states
+        )
+    }
+}
+
+/// A terminal whose pattern is the same as the one of an earlier terminal, or that is declared
+/// as alias of an earlier terminal.
+///
+/// Both terminals share one DFA, so the alias never wins against the earlier terminal within a
+/// scanner mode. The terminals are identified by their index in the patterns, i.e. the index
+/// that is their token type in the default scanner mode. See
+/// [crate::GenerateOptions::with_duplicate_patterns] and
+/// [crate::GenerateOptions::with_terminal_aliases].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatternAlias {
+    /// The index of the terminal that repeats the pattern.
+    pub terminal: usize,
+    /// The index of the first terminal with the pattern.
+    pub alias_of: usize,
+    /// The pattern of both terminals.
+    pub pattern: String,
+    /// True if the alias is declared with [crate::GenerateOptions::with_terminal_aliases].
+    pub declared: bool,
+}
+
+impl std::fmt::Display for PatternAlias {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Terminal #{} {:?} is an alias of terminal #{}",
+            self.terminal, self.pattern, self.alias_of
+        )
+    }
+}
+
+/// The size of the generated tables.
+///
+/// The sizes are computed for the pointer width of the generating host, which is usually the
+/// same as the one of the target.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TableSize {
+    /// The number of bytes of the `DFAS` slice including the patterns and the slices it refers
+    /// to. Shared slices are counted once.
+    pub dfa_bytes: usize,
+    /// The number of match arms of all generated match functions.
+    pub match_arms: usize,
+    /// The patterns together with the number of bytes of their DFA data, in descending order of
+    /// the number of bytes. Shared slices are counted for each pattern that uses them.
+    pub pattern_bytes: Vec<(String, usize)>,
+}
+
+impl std::fmt::Display for TableSize {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} bytes of DFA data, {} match arms",
+            self.dfa_bytes, self.match_arms
+        )
+    }
+}
+
+/// The report created during the compilation of the terminals.
+///
+/// It provides information that helps grammar authors to detect problems before runtime.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CompilationReport {
+    /// The pairs of terminals whose languages intersect.
+    pub conflicts: Vec<TerminalConflict>,
+    /// The literal terminals that are also matched by other terminals of the same scanner mode.
+    pub literal_shadowings: Vec<LiteralShadowing>,
+    /// The scanner modes that have neither a catch-all terminal nor an error token type.
+    pub missing_catch_alls: Vec<MissingCatchAll>,
+    /// The terminals with unusually many character classes or states for their length.
+    pub pattern_explosions: Vec<PatternExplosion>,
+    /// The terminals whose patterns repeat the patterns of earlier terminals.
+    pub aliases: Vec<PatternAlias>,
+    /// The size of the generated tables.
+    pub table_size: TableSize,
+}
+
+impl std::fmt::Display for CompilationReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Conflicts:")?;
+        for conflict in &self.conflicts {
+            writeln!(f, "  {}", conflict)?;
+        }
+        writeln!(f, "Shadowed literals:")?;
+        for shadowing in &self.literal_shadowings {
+            writeln!(f, "  {}", shadowing)?;
+        }
+        writeln!(f, "Modes without catch-all:")?;
+        for missing_catch_all in &self.missing_catch_alls {
+            writeln!(f, "  {}", missing_catch_all)?;
+        }
+        writeln!(f, "Exploding patterns:")?;
+        for pattern_explosion in &self.pattern_explosions {
+            writeln!(f, "  {}", pattern_explosion)?;
+        }
+        writeln!(f, "Aliased patterns:")?;
+        for alias in &self.aliases {
+            writeln!(f, "  {}", alias)?;
+        }
+        writeln!(f, "Table size:")?;
+        writeln!(f, "  {}", self.table_size)?;
+        Ok(())
+    }
+}
@@ -1,16 +1,15 @@
-use regex_syntax::ast::Ast;
+use std::collections::BTreeMap;
+
+use regex_syntax::hir::ClassUnicode;
 
 #[cfg(all(feature = "runtime", not(feature = "generate")))]
 use crate::common::Span;
 use crate::{
     common::MatchingState,
-    compiletime::{
-        character_class::ComparableAst, dfa::Dfa, match_function::MatchFunction, Result,
-        ScanGenError,
-    },
+    compiletime::{dfa::Dfa, match_function::MatchFunction, nfa::RepetitionCounter, Result},
 };
 
-use super::{CharClassID, StateID};
+use super::{shared_slices::SharedSlices, CharClassID, StateID};
 
 /// A compiled DFA that can be used to match a string.
 ///
@@ -32,6 +31,9 @@ pub(crate) struct CompiledDfa {
     /// The transitions of the DFA. The indices that are relevant for a state are stored in the
     /// state_ranges vector.
     transitions: Vec<(CharClassID, StateID)>,
+    /// The counters of the counted repetitions by the state that loops on the repeated
+    /// characters.
+    counters: BTreeMap<StateID, RepetitionCounter>,
     /// The state of matching
     matching_state: MatchingState<StateID>,
 }
@@ -45,10 +47,127 @@ impl CompiledDfa {
         &self.pattern
     }
 
+    /// Returns true if the given state is an accepting state.
+    pub(crate) fn is_accepting(&self, state: StateID) -> bool {
+        self.accepting_states.contains(&state)
+    }
+
+    /// Returns true if the DFA accepts the whole given input.
+    /// The bounds of counted repetitions are respected.
+    pub(crate) fn accepts(
+        &self,
+        input: &str,
+        match_functions: &[(ClassUnicode, MatchFunction)],
+    ) -> bool {
+        input
+            .chars()
+            .try_fold((StateID::default(), 0), |(state, repetitions), c| {
+                let next_state = self.next_state(state, c, match_functions)?;
+                Some((next_state, self.count(state, repetitions, next_state)?))
+            })
+            .is_some_and(|(state, repetitions)| {
+                self.is_accepting(state)
+                    && self
+                        .counters
+                        .get(&state)
+                        .is_none_or(|counter| repetitions >= counter.min)
+            })
+    }
+
+    /// Returns the number of repetitions of a counted repetition after the transition from the
+    /// given state to the target state, or None if the bounds of the repetition forbid the
+    /// transition. The number is zero outside of counted repetitions.
+    fn count(&self, state: StateID, repetitions: usize, target_state: StateID) -> Option<usize> {
+        if let Some(counter) = self.counters.get(&state) {
+            if target_state == state {
+                return counter
+                    .max
+                    .is_none_or(|max| repetitions < max)
+                    .then(|| (repetitions + 1).min(counter.max.unwrap_or(counter.min)));
+            }
+            if repetitions < counter.min {
+                return None;
+            }
+        }
+        Some(usize::from(self.counters.contains_key(&target_state)))
+    }
+
+    /// Returns the counted states of the DFA together with the bounds of their repetitions.
+    pub(crate) fn counted_states(
+        &self,
+    ) -> impl Iterator<Item = (usize, usize, Option<usize>)> + '_ {
+        self.counters
+            .iter()
+            .map(|(state, counter)| (state.as_usize(), counter.min, counter.max))
+    }
+
+    /// Returns the target state of the transition from the given state on the given character.
+    /// The bounds of counted repetitions are not checked.
+    pub(crate) fn next_state(
+        &self,
+        state: StateID,
+        c: char,
+        match_functions: &[(ClassUnicode, MatchFunction)],
+    ) -> Option<StateID> {
+        let (start, end) = self.state_ranges[state];
+        self.transitions[start..end]
+            .iter()
+            .find(|(char_class, _)| match_functions[char_class.as_usize()].1.call(c))
+            .map(|(_, target_state)| *target_state)
+    }
+
+    /// Returns the class of the characters a match of the DFA can start with, i.e. the union of
+    /// the classes of all transitions from the start state.
+    pub(crate) fn start_class(
+        &self,
+        match_functions: &[(ClassUnicode, MatchFunction)],
+    ) -> ClassUnicode {
+        let mut start_class = ClassUnicode::empty();
+        if let Some((start, end)) = self.state_ranges.first() {
+            for (char_class, _) in &self.transitions[*start..*end] {
+                start_class.union(&match_functions[char_class.as_usize()].0);
+            }
+        }
+        start_class
+    }
+
+    /// Returns the number of states of the DFA.
+    pub(crate) fn state_count(&self) -> usize {
+        self.state_ranges.len()
+    }
+
+    /// Returns the indices of the match functions of all character classes used by the DFA.
+    pub(crate) fn char_classes(&self) -> impl Iterator<Item = usize> + '_ {
+        self.transitions
+            .iter()
+            .map(|(char_class, _)| char_class.as_usize())
+    }
+
+    /// Returns the character classes the DFA loops on in an accepting state without any other
+    /// transition, e.g. the class `\s` of the pattern `\s+`. The scanner can consume a run of
+    /// characters of such a class without stepping the DFA.
+    pub(crate) fn self_loop_classes(&self) -> impl Iterator<Item = usize> + '_ {
+        self.state_ranges
+            .iter()
+            .enumerate()
+            .filter_map(
+                |(state, (start, end))| match &self.transitions[*start..*end] {
+                    [(char_class, target)]
+                        if target.as_usize() == state
+                            && self.is_accepting(*target)
+                            && !self.counters.contains_key(target) =>
+                    {
+                        Some(char_class.as_usize())
+                    }
+                    _ => None,
+                },
+            )
+    }
+
     pub(crate) fn compile(
         &mut self,
         dfa: &Dfa,
-        match_functions: &mut Vec<(Ast, MatchFunction)>,
+        match_functions: &mut Vec<(ClassUnicode, MatchFunction)>,
     ) -> Result<()> {
         // Set the pattern
         debug_assert_eq!(dfa.pattern().len(), 1);
@@ -60,31 +179,37 @@ impl CompiledDfa {
             self.state_ranges.push((0, 0));
         }
         for (state, state_transitions) in dfa.transitions() {
+            // The character classes of the DFA are disjoint, so the classes of all transitions to
+            // the same target state can be merged into one class.
+            let mut merged_transitions: Vec<(ClassUnicode, StateID)> = Vec::new();
+            for (char_class, target_state) in state_transitions {
+                if let Some((class, _)) = merged_transitions
+                    .iter_mut()
+                    .find(|(_, target)| target == target_state)
+                {
+                    class.union(char_class.class());
+                } else {
+                    merged_transitions.push((char_class.class().clone(), *target_state));
+                }
+            }
             let start = self.transitions.len();
-            self.state_ranges[*state] = (start, start + state_transitions.len());
-            let mut transitions_for_state = state_transitions.iter().try_fold(
-                Vec::new(),
-                |mut acc, (char_class, target_state)| {
-                    // Create the match function for the character class if it does not exist
-                    if let Some(pos) = match_functions
-                        .iter()
-                        .position(|(ast, _)| ComparableAst(ast.clone()) == char_class.ast)
-                    {
-                        acc.push((pos.into(), *target_state));
-                        Ok::<Vec<(CharClassID, StateID)>, ScanGenError>(acc)
+            self.state_ranges[*state] = (start, start + merged_transitions.len());
+            for (class, target_state) in merged_transitions {
+                // Create the match function for the character class if it does not exist
+                let char_class_id =
+                    if let Some(pos) = match_functions.iter().position(|(c, _)| *c == class) {
+                        pos.into()
                     } else {
-                        let match_function: MatchFunction = char_class.ast().clone().try_into()?;
-                        let new_char_class_id = CharClassID::new(match_functions.len());
-                        match_functions.push((char_class.ast().clone(), match_function));
-                        acc.push((new_char_class_id, *target_state));
-                        Ok(acc)
-                    }
-                },
-            )?;
-            self.transitions.append(&mut transitions_for_state);
+                        let match_function = MatchFunction::from(&class);
+                        match_functions.push((class, match_function));
+                        CharClassID::new(match_functions.len() - 1)
+                    };
+                self.transitions.push((char_class_id, target_state));
+            }
         }
         // Create the accepting states vector
         self.accepting_states = dfa.accepting_states().keys().cloned().collect();
+        self.counters = dfa.counters().clone();
         Ok(())
     }
 
@@ -113,7 +238,7 @@ impl CompiledDfa {
         &mut self,
         c_pos: usize,
         c: char,
-        match_functions: &[(Ast, MatchFunction)],
+        match_functions: &[(ClassUnicode, MatchFunction)],
     ) {
         // If we already have the longest match, we can stop
         if self.matching_state.is_longest_match() {
@@ -122,7 +247,8 @@ impl CompiledDfa {
         // Get the transitions for the current state
         if let Some(next_state) = self.find_transition(c, match_functions) {
             if self.accepting_states.contains(&next_state) {
-                self.matching_state.transition_to_accepting(c_pos, c);
+                self.matching_state
+                    .transition_to_accepting(c_pos, c.len_utf8());
             } else {
                 self.matching_state.transition_to_non_accepting(c_pos);
             }
@@ -137,7 +263,7 @@ impl CompiledDfa {
     fn find_transition(
         &self,
         c: char,
-        match_functions: &[(Ast, MatchFunction)],
+        match_functions: &[(ClassUnicode, MatchFunction)],
     ) -> Option<StateID> {
         let (start, end) = self.state_ranges[self.matching_state.current_state().as_usize()];
         let transitions = &self.transitions[start..end];
@@ -155,22 +281,58 @@ impl CompiledDfa {
         !self.matching_state.is_longest_match()
     }
 
-    pub(crate) fn generate_code(&self, output: &mut dyn std::io::Write) -> Result<()> {
-        write!(output, "    (\"{}\", &[", self.pattern.escape_default())?;
-        for state in &self.accepting_states {
-            write!(output, "{}, ", state.as_usize())?;
-        }
-        write!(output, "], &[")?;
+    /// Returns the code of the slices of the generated DFA data, i.e. the accepting states, the
+    /// state ranges and the transitions, each together with the type and the number of its
+    /// elements.
+    pub(crate) fn slices(&self) -> [(&'static str, String, usize); 3] {
+        let accepting_states = self
+            .accepting_states
+            .iter()
+            .map(|state| format!("{}, ", state.as_usize()))
+            .collect::<String>();
+        let state_ranges = self
+            .state_ranges
+            .iter()
+            .map(|(start, end)| format!("({}, {}), ", start, end))
+            .collect::<String>();
+        let transitions = self
+            .transitions
+            .iter()
+            .map(|(char_class, target_state)| {
+                format!("({}, {}), ", char_class, target_state.as_usize())
+            })
+            .collect::<String>();
+        [
+            (
+                "usize",
+                format!("&[{}]", accepting_states),
+                self.accepting_states.len(),
+            ),
+            (
+                "(usize, usize)",
+                format!("&[{}]", state_ranges),
+                self.state_ranges.len(),
+            ),
+            (
+                "(usize, usize)",
+                format!("&[{}]", transitions),
+                self.transitions.len(),
+            ),
+        ]
+    }
 
-        for (start, end) in &self.state_ranges {
-            write!(output, "({}, {}), ", start, end)?;
-        }
-        write!(output, "], &[")?;
-        for (char_class, target_state) in &self.transitions {
-            write!(output, "({}, {}), ", char_class, target_state.as_usize())?;
+    /// Generates the DFA data. Slices that are shared with other DFAs are referenced by the name
+    /// of their constant.
+    pub(crate) fn generate_code(
+        &self,
+        shared_slices: &SharedSlices,
+        output: &mut dyn std::io::Write,
+    ) -> Result<()> {
+        write!(output, "    (\"{}\"", self.pattern.escape_default())?;
+        for (element_type, code, _) in self.slices() {
+            write!(output, ", {}", shared_slices.reference(element_type, &code))?;
         }
-        writeln!(output, "]),")?;
-
+        writeln!(output, "),")?;
         Ok(())
     }
 }
@@ -181,6 +343,7 @@ impl std::fmt::Debug for CompiledDfa {
             .field("accepting_states", &self.accepting_states)
             .field("state_ranges", &self.state_ranges)
             .field("transitions", &self.transitions)
+            .field("counters", &self.counters)
             .field("current_state", &self.matching_state.current_state())
             .field("matching_state", &self.matching_state)
             .finish()
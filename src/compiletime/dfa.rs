@@ -3,11 +3,21 @@
 //! The DFA is generated from the NFA using the subset construction algorithm.
 
 use itertools::Itertools;
-use std::collections::{BTreeMap, BTreeSet};
-
-use crate::Result;
-
-use super::{CharacterClass, MultiPatternNfa, PatternID, StateID};
+use regex_syntax::hir::{ClassUnicode, ClassUnicodeRange};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use tracing::trace_span;
+
+use crate::{Result, ScanGenError, ScanGenErrorKind};
+
+use super::{
+    absent::split_absent_pattern,
+    character_class::{disjoint_classes, ClassLabels},
+    errors::DfaError,
+    hir::shortest_match,
+    multi_pattern_nfa::NfaWithCharClasses,
+    nfa::RepetitionCounter,
+    parse_regex_syntax, CharClassID, CharacterClass, MultiPatternNfa, PatternID, StateID,
+};
 
 // The type definitions for the subset construction algorithm.
 pub(crate) type StateGroup = BTreeSet<StateID>;
@@ -41,6 +51,9 @@ pub(crate) struct Dfa {
     char_classes: Vec<CharacterClass>,
     // The transitions of the DFA.
     transitions: BTreeMap<StateID, BTreeMap<CharacterClass, StateID>>,
+    // The counters of the counted repetitions, keyed by the state that loops on the repeated
+    // characters. The operations that combine DFAs don't support them.
+    counters: BTreeMap<StateID, RepetitionCounter>,
 }
 
 impl Dfa {
@@ -75,14 +88,432 @@ impl Dfa {
         &self.transitions
     }
 
+    /// Get the counters of the counted repetitions by the state that loops on the repeated
+    /// characters.
+    pub(crate) fn counters(&self) -> &BTreeMap<StateID, RepetitionCounter> {
+        &self.counters
+    }
+
+    /// Removes the states that are not reachable from the start state and the states from which no
+    /// accepting state is reachable, e.g. the state of a difference after the input was accepted
+    /// by the subtrahend. The scanner would otherwise stay in such a state until the end of the
+    /// input. The start state is always kept.
+    pub(crate) fn remove_useless_states(&mut self) {
+        let mut reachable = BTreeSet::from([StateID::default()]);
+        let mut work_list = vec![StateID::default()];
+        while let Some(state_id) = work_list.pop() {
+            for target_state in self
+                .transitions
+                .get(&state_id)
+                .into_iter()
+                .flat_map(|t| t.values())
+            {
+                if reachable.insert(*target_state) {
+                    work_list.push(*target_state);
+                }
+            }
+        }
+        let mut alive: BTreeSet<StateID> = self.accepting_states.keys().copied().collect();
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (state_id, transitions) in &self.transitions {
+                if !alive.contains(state_id)
+                    && transitions
+                        .values()
+                        .any(|target_state| alive.contains(target_state))
+                {
+                    alive.insert(*state_id);
+                    changed = true;
+                }
+            }
+        }
+        let new_ids = self
+            .states
+            .iter()
+            .map(|state| state.id)
+            .filter(|state_id| {
+                *state_id == StateID::default()
+                    || (reachable.contains(state_id) && alive.contains(state_id))
+            })
+            .enumerate()
+            .map(|(new_id, state_id)| (state_id, StateID::new(new_id)))
+            .collect::<BTreeMap<_, _>>();
+        if new_ids.len() == self.states.len() {
+            return;
+        }
+        self.states = new_ids
+            .values()
+            .map(|state_id| DfaState::new(*state_id, Vec::new()))
+            .collect();
+        self.accepting_states = std::mem::take(&mut self.accepting_states)
+            .into_iter()
+            .filter_map(|(state_id, pattern_id)| Some((*new_ids.get(&state_id)?, pattern_id)))
+            .collect();
+        self.counters = std::mem::take(&mut self.counters)
+            .into_iter()
+            .filter_map(|(state_id, counter)| Some((*new_ids.get(&state_id)?, counter)))
+            .collect();
+        self.transitions = std::mem::take(&mut self.transitions)
+            .into_iter()
+            .filter_map(|(state_id, transitions)| {
+                let transitions = transitions
+                    .into_iter()
+                    .filter_map(|(char_class, target_state)| {
+                        Some((char_class, *new_ids.get(&target_state)?))
+                    })
+                    .collect::<BTreeMap<_, _>>();
+                Some((*new_ids.get(&state_id)?, transitions)).filter(|(_, t)| !t.is_empty())
+            })
+            .collect();
+    }
+
+    /// Removes all transitions leaving accepting states, i.e. the DFA stops at the first, i.e.
+    /// shortest, match.
+    /// This is used for patterns with non-greedy repetitions like `/\*.*?\*/`, which otherwise
+    /// would match over the end of the first block comment because the longest match wins.
+    pub(crate) fn remove_transitions_from_accepting_states(&mut self) {
+        for state in self.accepting_states.keys() {
+            self.transitions.remove(state);
+        }
+    }
+
+    /// Create the DFA for the pattern of a single terminal.
+    /// Patterns with a lazy repetition stop at their shortest match, see [shortest_match].
+    /// Patterns with the absent operator `P(?~X)Y` are built from the concatenation of the DFAs
+    /// for `P`, the complement of `(?s:.)*X(?s:.)*` and `Y`, see the `absent` module.
+    pub(crate) fn try_from_pattern(pattern: &str) -> Result<Self> {
+        Self::build(pattern, false)
+    }
+
+    /// Create the DFA for the pattern of a single terminal like [Dfa::try_from_pattern], but
+    /// large repetitions of a single character class, e.g. `\d{1,100}`, loop in one state whose
+    /// repetitions are counted while matching, see [Dfa::counters].
+    ///
+    /// Repetitions are only counted in greedy patterns without the absent operator. If a
+    /// repetition can't be counted in the DFA because other parts of the pattern could match the
+    /// same characters at the same time, e.g. in `a{1,20}a`, the repetitions are expanded.
+    pub(crate) fn try_from_pattern_with_counted_repetitions(pattern: &str) -> Result<Self> {
+        Self::build(pattern, true)
+    }
+
+    fn build(pattern: &str, count_repetitions: bool) -> Result<Self> {
+        if let Some(absent) = split_absent_pattern(pattern)? {
+            let prefix = Self::try_from_pattern(&format!("(?:{})", absent.prefix))?;
+            let body = Self::try_from_pattern(&format!("(?s:.)*(?:{})(?s:.)*", absent.excluded))?
+                .complement();
+            let terminator = Self::try_from_pattern(&format!("(?:{})", absent.terminator))?;
+            let mut dfa = prefix.concat(&body).concat(&terminator);
+            dfa.remove_transitions_from_accepting_states();
+            dfa.remove_useless_states();
+            dfa.pattern = vec![pattern.to_string()];
+            return Ok(dfa);
+        }
+        let shortest_match = shortest_match(&parse_regex_syntax(pattern)?)?;
+        let mut multi_pattern_nfa = MultiPatternNfa::new();
+        if count_repetitions && !shortest_match {
+            multi_pattern_nfa.add_pattern_with_counted_repetitions(pattern)?;
+        } else {
+            multi_pattern_nfa.add_pattern(pattern)?;
+        }
+        let counted = !multi_pattern_nfa.counters.is_empty();
+        let mut dfa = match Self::try_from_nfa(multi_pattern_nfa) {
+            // The repetitions that can't be counted are expanded instead
+            Err(_) if counted => return Self::try_from_pattern(pattern),
+            result => result?,
+        };
+        if shortest_match {
+            dfa.remove_transitions_from_accepting_states();
+        }
+        Ok(dfa)
+    }
+
+    /// Get the state that is reached from the given state on the given character, if any.
+    pub(crate) fn next_state(&self, state_id: StateID, c: char) -> Option<StateID> {
+        self.transitions
+            .get(&state_id)?
+            .iter()
+            .find(|(char_class, _)| {
+                char_class
+                    .class()
+                    .ranges()
+                    .iter()
+                    .any(|range| range.start() <= c && c <= range.end())
+            })
+            .map(|(_, target_state)| *target_state)
+    }
+
+    /// Create a DFA that accepts the strings of both DFAs.
+    /// The pattern of the other DFA follow the pattern of this DFA. If both DFAs accept an input,
+    /// the pattern of this DFA wins.
+    pub(crate) fn union(&self, other: &Dfa) -> Dfa {
+        self.product(other, ProductOperation::Union)
+    }
+
+    /// Create a DFA that accepts the strings that are accepted by both DFAs with the pattern of
+    /// this DFA.
+    pub(crate) fn intersect(&self, other: &Dfa) -> Dfa {
+        self.product(other, ProductOperation::Intersection)
+    }
+
+    /// Create a DFA that accepts the strings of this DFA that are not accepted by the other DFA,
+    /// e.g. identifiers without keywords.
+    pub(crate) fn difference(&self, other: &Dfa) -> Dfa {
+        self.product(other, ProductOperation::Difference)
+    }
+
+    /// Create a DFA that accepts exactly the strings this DFA does not accept.
+    /// It is meant for single-pattern DFAs, all strings are accepted with the first pattern.
+    pub(crate) fn complement(&self) -> Dfa {
+        // The characters without any character class get a class of their own, so each state can
+        // get a transition on every character. Missing transitions lead to a new sink state.
+        let mut char_classes = self.char_classes.clone();
+        let mut rest = ClassUnicode::new([ClassUnicodeRange::new('\0', char::MAX)]);
+        for char_class in &char_classes {
+            rest.difference(char_class.class());
+        }
+        if !rest.ranges().is_empty() {
+            let id = char_classes
+                .iter()
+                .map(|char_class| char_class.id().as_usize() + 1)
+                .max()
+                .unwrap_or_default();
+            char_classes.push(CharacterClass::new(CharClassID::new(id), rest));
+        }
+        let sink_state = StateID::new(self.states.len());
+        let mut dfa = Dfa {
+            states: Vec::new(),
+            pattern: self.pattern.iter().take(1).cloned().collect(),
+            accepting_states: BTreeMap::new(),
+            char_classes,
+            transitions: BTreeMap::new(),
+            counters: BTreeMap::new(),
+        };
+        for state_id in (0..=sink_state.as_usize()).map(StateID::new) {
+            dfa.states.push(DfaState::new(state_id, Vec::new()));
+            if self.pattern_id(state_id).is_none() {
+                dfa.accepting_states.insert(state_id, PatternID::default());
+            }
+            let transitions = dfa
+                .char_classes
+                .iter()
+                .map(|char_class| {
+                    let target_state = self
+                        .transitions
+                        .get(&state_id)
+                        .and_then(|transitions| transitions.get(char_class))
+                        .copied()
+                        .unwrap_or(sink_state);
+                    (char_class.clone(), target_state)
+                })
+                .collect();
+            dfa.transitions.insert(state_id, transitions);
+        }
+        dfa
+    }
+
+    /// Create a DFA that accepts a string of this DFA followed by a string of the other DFA.
+    /// It is meant for single-pattern DFAs, all strings are accepted with the first pattern of
+    /// this DFA.
+    pub(crate) fn concat(&self, other: &Dfa) -> Dfa {
+        let char_classes = disjoint_classes(
+            self.char_classes
+                .iter()
+                .chain(other.char_classes.iter())
+                .map(CharacterClass::class),
+        )
+        .into_iter()
+        .enumerate()
+        .map(|(id, class)| CharacterClass::new(CharClassID::new(id), class))
+        .collect::<Vec<_>>();
+        let mut dfa = Dfa {
+            states: Vec::new(),
+            pattern: self.pattern.iter().take(1).cloned().collect(),
+            accepting_states: BTreeMap::new(),
+            char_classes,
+            transitions: BTreeMap::new(),
+            counters: BTreeMap::new(),
+        };
+
+        // Each state of the new DFA is a state of this DFA, or `None` if this DFA has no
+        // transition on the input read so far, together with the states of the other DFA that
+        // are reached after a prefix of the input was accepted by this DFA.
+        let with_other_start = |state: Option<StateID>, mut other_states: BTreeSet<StateID>| {
+            if state.is_some_and(|state| self.pattern_id(state).is_some()) {
+                other_states.insert(StateID::default());
+            }
+            (state, other_states)
+        };
+        let start = with_other_start(Some(StateID::default()), BTreeSet::new());
+        let mut state_ids = BTreeMap::from([(start.clone(), StateID::default())]);
+        let mut state_sets = vec![start];
+        while let Some((state, other_states)) = state_sets.get(dfa.states.len()).cloned() {
+            let state_id = StateID::new(dfa.states.len());
+            dfa.states.push(DfaState::new(state_id, Vec::new()));
+            if other_states
+                .iter()
+                .any(|other_state| other.pattern_id(*other_state).is_some())
+            {
+                dfa.accepting_states.insert(state_id, PatternID::default());
+            }
+            for char_class in &dfa.char_classes {
+                let Some(c) = char_class
+                    .class()
+                    .ranges()
+                    .first()
+                    .map(|range| range.start())
+                else {
+                    continue;
+                };
+                let target = with_other_start(
+                    state.and_then(|state| self.next_state(state, c)),
+                    other_states
+                        .iter()
+                        .filter_map(|other_state| other.next_state(*other_state, c))
+                        .collect(),
+                );
+                if target.0.is_none() && target.1.is_empty() {
+                    continue;
+                }
+                let target_state = *state_ids.entry(target.clone()).or_insert_with(|| {
+                    state_sets.push(target);
+                    StateID::new(state_sets.len() - 1)
+                });
+                dfa.transitions
+                    .entry(state_id)
+                    .or_default()
+                    .insert(char_class.clone(), target_state);
+            }
+        }
+        dfa.remove_useless_states();
+        dfa
+    }
+
+    /// Returns true if the DFA accepts no string at all.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.shortest_accepted_string().is_none()
+    }
+
+    /// Returns a shortest string the DFA accepts, if any.
+    /// The states are visited in breadth-first order, so the first accepting state found is
+    /// reached by a shortest string. For each character class its smallest character is used.
+    pub(crate) fn shortest_accepted_string(&self) -> Option<String> {
+        let mut predecessors: BTreeMap<StateID, (StateID, char)> = BTreeMap::new();
+        let mut work_list = VecDeque::from([StateID::default()]);
+        while let Some(state_id) = work_list.pop_front() {
+            if self.accepting_states.contains_key(&state_id) {
+                let mut string = Vec::new();
+                let mut state_id = state_id;
+                while let Some((predecessor, c)) = predecessors.get(&state_id) {
+                    string.push(*c);
+                    state_id = *predecessor;
+                }
+                return Some(string.into_iter().rev().collect());
+            }
+            for (char_class, target_state) in self.transitions.get(&state_id).into_iter().flatten()
+            {
+                let Some(range) = char_class.class().ranges().first() else {
+                    continue;
+                };
+                if *target_state != StateID::default() && !predecessors.contains_key(target_state) {
+                    predecessors.insert(*target_state, (state_id, range.start()));
+                    work_list.push_back(*target_state);
+                }
+            }
+        }
+        None
+    }
+
+    /// Create a DFA from this and the other DFA using the product construction.
+    /// The operation decides which pairs of states are accepting and with which pattern.
+    fn product(&self, other: &Dfa, operation: ProductOperation) -> Dfa {
+        // The character classes of both DFAs are split into disjoint character classes, so that
+        // each of them leads to at most one state in each DFA.
+        let char_classes = disjoint_classes(
+            self.char_classes
+                .iter()
+                .chain(other.char_classes.iter())
+                .map(CharacterClass::class),
+        )
+        .into_iter()
+        .enumerate()
+        .map(|(id, class)| CharacterClass::new(CharClassID::new(id), class))
+        .collect::<Vec<_>>();
+        let pattern = match operation {
+            ProductOperation::Union => self.pattern.iter().chain(&other.pattern).cloned().collect(),
+            ProductOperation::Intersection | ProductOperation::Difference => self.pattern.clone(),
+        };
+        let mut dfa = Dfa {
+            states: Vec::new(),
+            pattern,
+            accepting_states: BTreeMap::new(),
+            char_classes,
+            transitions: BTreeMap::new(),
+            counters: BTreeMap::new(),
+        };
+
+        // Each state of the new DFA is a pair of states of both DFAs, where `None` denotes that
+        // the DFA has no transition on the input read so far.
+        let mut state_pairs = vec![(Some(StateID::default()), Some(StateID::default()))];
+        let mut state_ids = BTreeMap::from([(state_pairs[0], StateID::default())]);
+        while let Some(&(state, other_state)) = state_pairs.get(dfa.states.len()) {
+            let state_id = StateID::new(dfa.states.len());
+            dfa.states.push(DfaState::new(state_id, Vec::new()));
+            let pattern_id = state.and_then(|state| self.pattern_id(state));
+            let other_pattern_id = other_state.and_then(|state| other.pattern_id(state));
+            let accepted_pattern_id = match operation {
+                ProductOperation::Union => pattern_id.or_else(|| {
+                    other_pattern_id.map(|pattern_id| {
+                        PatternID::new(pattern_id.as_usize() + self.pattern.len())
+                    })
+                }),
+                ProductOperation::Intersection => pattern_id.filter(|_| other_pattern_id.is_some()),
+                ProductOperation::Difference => pattern_id.filter(|_| other_pattern_id.is_none()),
+            };
+            if let Some(pattern_id) = accepted_pattern_id {
+                dfa.accepting_states.insert(state_id, pattern_id);
+            }
+            for char_class in &dfa.char_classes {
+                let Some(c) = char_class
+                    .class()
+                    .ranges()
+                    .first()
+                    .map(|range| range.start())
+                else {
+                    continue;
+                };
+                let target_pair = (
+                    state.and_then(|state| self.next_state(state, c)),
+                    other_state.and_then(|state| other.next_state(state, c)),
+                );
+                if !operation.is_alive(target_pair) {
+                    continue;
+                }
+                let target_state = *state_ids.entry(target_pair).or_insert_with(|| {
+                    state_pairs.push(target_pair);
+                    StateID::new(state_pairs.len() - 1)
+                });
+                dfa.transitions
+                    .entry(state_id)
+                    .or_default()
+                    .insert(char_class.clone(), target_state);
+            }
+        }
+        dfa.remove_useless_states();
+        dfa
+    }
+
     /// Create a DFA from a multi-pattern NFA.
     /// The DFA is created using the subset construction algorithm.
     fn try_from_nfa(nfa: MultiPatternNfa) -> Result<Self> {
+        let _span =
+            trace_span!("subset_construction", nfa_states = nfa.nfa.states().len()).entered();
         let MultiPatternNfa {
             nfa,
             pattern,
             accepting_states,
             char_classes,
+            counters,
         } = nfa;
         let mut dfa = Dfa {
             states: Vec::new(),
@@ -90,6 +521,7 @@ impl Dfa {
             accepting_states: BTreeMap::new(),
             char_classes,
             transitions: BTreeMap::new(),
+            counters: BTreeMap::new(),
         };
         // The initial state of the DFA is the epsilon closure of the start state of the NFA.
         let start_state = nfa.epsilon_closure(StateID::default());
@@ -119,9 +551,61 @@ impl Dfa {
             }
         }
 
+        if !counters.is_empty() {
+            dfa.counters = dfa.counted_states(&nfa, &counters).ok_or_else(|| {
+                ScanGenError::new(ScanGenErrorKind::DfaError(DfaError::ConstructionError(
+                    "a counted repetition overlaps with other parts of the pattern".to_string(),
+                )))
+            })?;
+        }
+
         Ok(dfa)
     }
 
+    /// Maps the counters of the counted repetitions of the NFA to the DFA states of their loop
+    /// states. Returns None if a repetition can't be counted in the DFA.
+    ///
+    /// A repetition can be counted if its loop state is contained in only one DFA state, which
+    /// consists of the ε-closure of the loop state, and no other NFA state of this DFA state has
+    /// a transition on the characters of the loop or into the loop. Then the DFA state is entered
+    /// with the first repetition, loops on the further ones and is left on all other transitions.
+    fn counted_states(
+        &self,
+        nfa: &NfaWithCharClasses,
+        counters: &BTreeMap<StateID, RepetitionCounter>,
+    ) -> Option<BTreeMap<StateID, RepetitionCounter>> {
+        counters
+            .iter()
+            .map(|(loop_state, counter)| {
+                let mut dfa_states = self
+                    .states
+                    .iter()
+                    .filter(|state| state.nfa_states.contains(loop_state));
+                let dfa_state = dfa_states.next()?;
+                if dfa_states.next().is_some()
+                    || dfa_state.nfa_states != nfa.epsilon_closure_set([*loop_state])
+                {
+                    return None;
+                }
+                let loop_classes = nfa.states()[*loop_state]
+                    .transitions()
+                    .iter()
+                    .map(|transition| transition.chars())
+                    .collect::<Vec<_>>();
+                let separated = dfa_state
+                    .nfa_states
+                    .iter()
+                    .filter(|nfa_state| *nfa_state != loop_state)
+                    .flat_map(|nfa_state| nfa.states()[*nfa_state].transitions())
+                    .all(|transition| {
+                        transition.target_state() != *loop_state
+                            && !loop_classes.contains(&transition.chars())
+                    });
+                separated.then_some((dfa_state.id, *counter))
+            })
+            .collect()
+    }
+
     /// Add a state to the DFA if it does not already exist.
     /// The state is identified by the NFA states that constitute the DFA state.
     /// The accepting states are used to determine if the DFA state is an accepting state.
@@ -203,6 +687,7 @@ impl Dfa {
     /// The Nfa states are removed from the DFA states during minimization. They are not needed
     /// anymore after the DFA is created.
     pub fn minimize(&self) -> Result<Self> {
+        let _span = trace_span!("minimize", states = self.states.len()).entered();
         let mut partition_old = self.calculate_initial_partition();
         let mut partition_new = Partition::new();
         let mut changed = true;
@@ -213,7 +698,70 @@ impl Dfa {
             partition_old.clone_from(&partition_new);
         }
 
-        self.create_from_partition(&partition_new)
+        // The groups are numbered by their position in the partition. The group of the start
+        // state must come first, so the start state of the minimized DFA is state 0, too.
+        if let Some(start_group) = partition_new
+            .iter()
+            .position(|group| group.contains(&StateID::default()))
+        {
+            partition_new[..=start_group].rotate_right(1);
+        }
+
+        let minimized_dfa = self.create_from_partition(&partition_new)?;
+        if cfg!(debug_assertions) {
+            self.verify_minimization(&minimized_dfa, &partition_new)?;
+        }
+        Ok(minimized_dfa)
+    }
+
+    /// Checks that the minimized DFA behaves like this DFA, i.e. that the state of each group
+    /// accepts the same pattern as the states of the group and has a transition on exactly the
+    /// character classes of these states, which leads to the group of their target states.
+    fn verify_minimization(&self, minimized_dfa: &Dfa, partition: &[StateGroup]) -> Result<()> {
+        if Self::group_of(partition, StateID::default())? != StateID::default() {
+            return Err(inconsistency(
+                StateID::default(),
+                "the start state is not the first state of the minimized DFA",
+            ));
+        }
+        let no_transitions = BTreeMap::new();
+        for state in &self.states {
+            let group = Self::group_of(partition, state.id)?;
+            if minimized_dfa.pattern_id(group) != self.pattern_id(state.id) {
+                return Err(inconsistency(
+                    state.id,
+                    "the accepted pattern is not preserved",
+                ));
+            }
+            if minimized_dfa.counters.get(&group) != self.counters.get(&state.id) {
+                return Err(inconsistency(
+                    state.id,
+                    "the counter of the repetition is not preserved",
+                ));
+            }
+            let transitions = self.transitions.get(&state.id).unwrap_or(&no_transitions);
+            let minimized_transitions = minimized_dfa
+                .transitions
+                .get(&group)
+                .unwrap_or(&no_transitions);
+            if transitions.len() != minimized_transitions.len() {
+                return Err(inconsistency(
+                    state.id,
+                    "the minimized state has transitions on other character classes",
+                ));
+            }
+            for (char_class, target_state) in transitions {
+                if minimized_transitions.get(char_class)
+                    != Some(&Self::group_of(partition, *target_state)?)
+                {
+                    return Err(inconsistency(
+                        state.id,
+                        format!("the transition on {} is not preserved", char_class),
+                    ));
+                }
+            }
+        }
+        Ok(())
     }
 
     /// The start partition is created as follows:
@@ -229,7 +777,8 @@ impl Dfa {
     /// the key is the state id of the first non-accepting state.
     fn calculate_initial_partition(&self) -> Partition {
         let group_id_non_accepting_states: StateID = StateID::new(self.accepting_states.len());
-        self.states
+        let mut partition = self
+            .states
             .clone()
             .into_iter()
             .chunk_by(|state| {
@@ -247,7 +796,17 @@ impl Dfa {
                 });
                 partitions.push(state_group);
                 partitions
-            })
+            });
+        // The state of a counted repetition is distinguished from all other states by its
+        // counter.
+        for state_id in self.counters.keys() {
+            partition.iter_mut().for_each(|group| {
+                group.remove(state_id);
+            });
+            partition.push(StateGroup::from([*state_id]));
+        }
+        partition.retain(|group| !group.is_empty());
+        partition
     }
 
     /// Calculate the new partition based on the old partition.
@@ -331,6 +890,7 @@ impl Dfa {
             accepting_states: BTreeMap::new(),
             char_classes: self.char_classes.clone(),
             transitions: self.transitions.clone(),
+            counters: BTreeMap::new(),
         };
 
         for group in partition {
@@ -341,12 +901,18 @@ impl Dfa {
         }
 
         // Then renumber the states in the transitions.
-        dfa.update_transitions(partition);
+        dfa.update_transitions(partition)?;
+
+        // The counted states are not merged with other states.
+        for (state_id, counter) in &self.counters {
+            dfa.counters
+                .insert(Self::group_of(partition, *state_id)?, *counter);
+        }
 
         Ok(dfa)
     }
 
-    fn update_transitions(&mut self, partition: &[StateGroup]) {
+    fn update_transitions(&mut self, partition: &[StateGroup]) -> Result<()> {
         // Create a vector because we dont want to mess the transitins map while renumbering.
         let mut transitions = self
             .transitions
@@ -354,74 +920,133 @@ impl Dfa {
             .map(|(s, t)| (*s, t.clone()))
             .collect::<Vec<_>>();
 
-        Self::merge_transitions(partition, &mut transitions);
-        Self::renumber_states_in_transitions(partition, &mut transitions);
+        Self::merge_transitions(partition, &mut transitions)?;
+        Self::renumber_states_in_transitions(partition, &mut transitions)?;
 
         self.transitions = transitions.into_iter().collect();
+        Ok(())
     }
 
     fn merge_transitions(
         partition: &[BTreeSet<StateID>],
         transitions: &mut Vec<(StateID, BTreeMap<CharacterClass, StateID>)>,
-    ) {
+    ) -> Result<()> {
         // Remove all transitions that do not belong to the representive states of a group.
         // The representive states are the first states in the groups.
         for group in partition {
-            debug_assert!(!group.is_empty());
-            if group.len() == 1 {
+            let Some(representive_state_id) = group.first() else {
                 continue;
-            }
-            let representive_state_id = group.first().unwrap();
+            };
             for state_id in group.iter().skip(1) {
-                Self::merge_transitions_of_state(*state_id, *representive_state_id, transitions);
+                Self::merge_transitions_of_state(
+                    partition,
+                    *state_id,
+                    *representive_state_id,
+                    transitions,
+                )?;
             }
         }
+        Ok(())
     }
 
+    /// Removes the transitions of the given state, which is merged into the representative state
+    /// of its group. Both states must have transitions on the same character classes that lead
+    /// to the same groups, otherwise they are not equivalent and an error is returned.
     fn merge_transitions_of_state(
+        partition: &[StateGroup],
         state_id: StateID,
         representive_state_id: StateID,
         transitions: &mut Vec<(StateID, BTreeMap<CharacterClass, StateID>)>,
-    ) {
-        if let Some(rep_pos) = transitions
+    ) -> Result<()> {
+        let no_transitions = BTreeMap::new();
+        let transitions_of_state = match transitions.iter().position(|(s, _)| *s == state_id) {
+            Some(pos) => transitions.remove(pos).1,
+            None => BTreeMap::new(),
+        };
+        let representive_transitions = transitions
             .iter()
-            .position(|(s, _)| *s == representive_state_id)
+            .find(|(s, _)| *s == representive_state_id)
+            .map_or(&no_transitions, |(_, t)| t);
+        if !transitions_of_state
+            .keys()
+            .eq(representive_transitions.keys())
         {
-            let mut rep_trans = transitions.get_mut(rep_pos).unwrap().1.clone();
-            if let Some(pos) = transitions.iter().position(|(s, _)| *s == state_id) {
-                let (_, transitions_of_state) = transitions.get_mut(pos).unwrap();
-                for (char_class, target_state) in transitions_of_state.iter() {
-                    rep_trans.insert(char_class.clone(), *target_state);
-                }
-                // Remove the transitions of the state that is merged into the representative state.
-                transitions.remove(pos);
+            return Err(inconsistency(
+                state_id,
+                "the character classes of the transitions differ from the ones of the \
+                representative state",
+            ));
+        }
+        for ((char_class, target_state), representive_target_state) in transitions_of_state
+            .iter()
+            .zip(representive_transitions.values())
+        {
+            if Self::group_of(partition, *target_state)?
+                != Self::group_of(partition, *representive_target_state)?
+            {
+                return Err(inconsistency(
+                    state_id,
+                    format!(
+                        "the transition on {} leads to another group than the one of the \
+                        representative state",
+                        char_class
+                    ),
+                ));
             }
-            transitions[rep_pos].1 = rep_trans;
         }
+        Ok(())
     }
 
     fn renumber_states_in_transitions(
         partition: &[StateGroup],
         transitions: &mut [(StateID, BTreeMap<CharacterClass, StateID>)],
-    ) {
-        let find_group_of_state = |state_id: StateID| -> StateID {
-            for (group_id, group) in partition.iter().enumerate() {
-                if group.contains(&state_id) {
-                    return StateID::new(group_id);
-                }
-            }
-            panic!("State {} not found in partition.", state_id.as_usize());
-        };
-
+    ) -> Result<()> {
         for transition in transitions.iter_mut() {
-            transition.0 = find_group_of_state(transition.0);
+            transition.0 = Self::group_of(partition, transition.0)?;
             for target_state in transition.1.values_mut() {
-                *target_state = find_group_of_state(*target_state);
+                *target_state = Self::group_of(partition, *target_state)?;
             }
         }
+        Ok(())
+    }
+
+    /// Returns the id of the group of the partition that contains the given state.
+    fn group_of(partition: &[StateGroup], state_id: StateID) -> Result<StateID> {
+        partition
+            .iter()
+            .position(|group| group.contains(&state_id))
+            .map(StateID::new)
+            .ok_or_else(|| inconsistency(state_id, "the state is not contained in the partition"))
     }
 }
 
+/// The regular language operation of a product construction of two DFAs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ProductOperation {
+    Union,
+    Intersection,
+    Difference,
+}
+
+impl ProductOperation {
+    /// Returns true if the product of the given pair of states can still reach an accepting
+    /// state. Pairs that can not are not added to the product DFA.
+    fn is_alive(self, (state, other_state): (Option<StateID>, Option<StateID>)) -> bool {
+        match self {
+            ProductOperation::Union => state.is_some() || other_state.is_some(),
+            ProductOperation::Intersection => state.is_some() && other_state.is_some(),
+            ProductOperation::Difference => state.is_some(),
+        }
+    }
+}
+
+/// Creates the error for an inconsistency of the minimized DFA at the given state.
+fn inconsistency(state_id: StateID, message: impl Into<String>) -> ScanGenError {
+    ScanGenError::new(ScanGenErrorKind::DfaError(
+        DfaError::InconsistentMinimization(state_id.as_usize(), message.into()),
+    ))
+}
+
 impl TryFrom<MultiPatternNfa> for Dfa {
     type Error = crate::ScanGenError;
 
@@ -430,34 +1055,75 @@ impl TryFrom<MultiPatternNfa> for Dfa {
     }
 }
 
+/// Renders the DFA as a compact state table that is meant to be read by users, e.g. in
+/// documentation and error messages.
+///
+/// Each state is printed on its own line with its transitions, labeled with the character classes
+/// they match. Long classes are labeled by number and listed after the states. The start state
+/// is marked with `>` and accepting states are marked with `*`, followed by the pattern they
+/// accept. The loop states of counted repetitions show their bounds.
+///
+/// ```text
+/// DFA with 2 states for 1 pattern
+/// Patterns:
+///   #0: [a-z]+
+/// States:
+///   >  0: [a-z] -> 1
+///    * 1: [a-z] -> 1; accepts #0
+/// ```
 impl std::fmt::Display for Dfa {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        writeln!(f, "DFA")?;
+        writeln!(
+            f,
+            "DFA with {} state{} for {} pattern{}",
+            self.states.len(),
+            if self.states.len() == 1 { "" } else { "s" },
+            self.pattern.len(),
+            if self.pattern.len() == 1 { "" } else { "s" },
+        )?;
+        if !self.pattern.is_empty() {
+            writeln!(f, "Patterns:")?;
+            for (pattern_id, pattern) in self.pattern.iter().enumerate() {
+                writeln!(f, "  #{}: {}", pattern_id, pattern)?;
+            }
+        }
         writeln!(f, "States:")?;
+        let mut labels = ClassLabels::default();
+        let width = self.states.len().saturating_sub(1).to_string().len();
         for state in &self.states {
-            writeln!(f, "{:?}", state)?;
-        }
-        writeln!(f, "Pattern:")?;
-        for pattern in &self.pattern {
-            writeln!(f, "{}", pattern)?;
-        }
-        writeln!(f, "Accepting states:")?;
-        for (state_id, pattern_id) in &self.accepting_states {
-            writeln!(f, "{}: {}", state_id.as_usize(), pattern_id.as_usize())?;
-        }
-        writeln!(f, "Char classes:")?;
-        for char_class in &self.char_classes {
-            writeln!(f, "{:?}", char_class)?;
-        }
-        writeln!(f, "Transitions:")?;
-        for (source_id, targets) in &self.transitions {
-            write!(f, "{} -> ", source_id.as_usize())?;
-            for (char_class, target_id) in targets {
-                write!(f, "{}:{}", char_class.ast.0, target_id.as_usize())?;
+            let state_id = state.id();
+            let start = if state_id == StateID::default() {
+                '>'
+            } else {
+                ' '
+            };
+            let accepting = self.accepting_states.get(&state_id);
+            write!(
+                f,
+                "  {}{} {:>width$}:",
+                start,
+                if accepting.is_some() { '*' } else { ' ' },
+                state_id.as_usize(),
+            )?;
+            match self.transitions.get(&state_id) {
+                Some(targets) if !targets.is_empty() => {
+                    for (i, (char_class, target_id)) in targets.iter().enumerate() {
+                        let separator = if i == 0 { " " } else { ", " };
+                        let label = labels.label(&char_class.class);
+                        write!(f, "{}{} -> {}", separator, label, target_id.as_usize())?;
+                    }
+                }
+                _ => write!(f, " no transitions")?,
+            }
+            if let Some(counter) = self.counters.get(&state_id) {
+                write!(f, "; repeats {}", counter)?;
             }
-            writeln!(f)?
+            if let Some(pattern_id) = accepting {
+                write!(f, "; accepts #{}", pattern_id.as_usize())?;
+            }
+            writeln!(f)?;
         }
-        Ok(())
+        labels.write_legend(f)
     }
 }
 
@@ -482,7 +1148,6 @@ impl DfaState {
     }
 
     /// Get the id of the DFA state.
-    #[allow(dead_code)]
     pub fn id(&self) -> StateID {
         self.id
     }
@@ -526,6 +1191,7 @@ mod tests {
     }
 
     use super::*;
+    use proptest::prelude::*;
 
     const TERMINALS: &[&str] = &[
         "\\r\\n|\\r|\\n",
@@ -594,10 +1260,10 @@ mod tests {
             name: "parol",
             pattern: TERMINALS,
             states: 154,
-            accepting_states: 45,
-            char_classes: 50,
-            min_states: 151,
-            min_accepting_states: 45,
+            accepting_states: 48,
+            char_classes: 53,
+            min_states: 153,
+            min_accepting_states: 48,
         },
         TestData {
             name: "sym_spec",
@@ -652,6 +1318,38 @@ mod tests {
         let _ = env_logger::builder().is_test(true).try_init();
     }
 
+    #[test]
+    fn test_dfa_display() {
+        let dfa = Dfa::try_from_pattern("[a-z]+").unwrap();
+        assert_eq!(
+            dfa.to_string(),
+            "\
+DFA with 2 states for 1 pattern
+Patterns:
+  #0: [a-z]+
+States:
+  >  0: [a-z] -> 1
+   * 1: [a-z] -> 1; accepts #0
+"
+        );
+
+        let dfa = Dfa::try_from_pattern_with_counted_repetitions(r"\d{1,100}|x").unwrap();
+        let rendered = dfa.to_string();
+        assert!(rendered.starts_with(
+            "\
+DFA with 3 states for 1 pattern
+Patterns:
+  #0: \\d{1,100}|x
+States:
+  >  0: c0 -> 1, x -> 2
+   * 1: c0 -> 1; repeats 1..=100; accepts #0
+   * 2: no transitions; accepts #0
+Character classes:
+  c0: [0-9"
+        ));
+        assert_eq!(rendered.lines().count(), 9);
+    }
+
     #[test]
     fn test_dfa_from_nfa() {
         let mut multi_pattern_nfa = MultiPatternNfa::new();
@@ -689,6 +1387,55 @@ mod tests {
         assert_eq!(dfa.char_classes().len(), 3);
     }
 
+    #[test]
+    fn test_inconsistent_partition() {
+        let mut multi_pattern_nfa = MultiPatternNfa::new();
+        multi_pattern_nfa.add_pattern("in").unwrap();
+        multi_pattern_nfa.add_pattern("int").unwrap();
+        let dfa = Dfa::try_from(multi_pattern_nfa).unwrap();
+        let partition = |groups: &[&[usize]]| -> Partition {
+            groups
+                .iter()
+                .map(|group| group.iter().copied().map(StateID::new).collect())
+                .collect()
+        };
+
+        // Each state in its own group is consistent.
+        let identity = partition(&[&[0], &[1], &[2], &[3]]);
+        let minimized_dfa = dfa.create_from_partition(&identity).unwrap();
+        assert!(dfa.verify_minimization(&minimized_dfa, &identity).is_ok());
+
+        // Merging states that are not equivalent is reported instead of silently overwriting
+        // the transitions of the representative state.
+        let err = dfa
+            .create_from_partition(&partition(&[&[0, 1], &[2], &[3]]))
+            .unwrap_err();
+        assert!(matches!(
+            *err.source,
+            ScanGenErrorKind::DfaError(DfaError::InconsistentMinimization(1, _))
+        ));
+
+        // A state that is missing in the partition is reported instead of panicking.
+        let err = dfa
+            .create_from_partition(&partition(&[&[0], &[1], &[2]]))
+            .unwrap_err();
+        assert!(matches!(
+            *err.source,
+            ScanGenErrorKind::DfaError(DfaError::InconsistentMinimization(3, _))
+        ));
+
+        // A minimized DFA that lost its accepting states is detected by the verification.
+        let mut minimized_dfa = minimized_dfa;
+        minimized_dfa.accepting_states.clear();
+        let err = dfa
+            .verify_minimization(&minimized_dfa, &identity)
+            .unwrap_err();
+        assert!(matches!(
+            *err.source,
+            ScanGenErrorKind::DfaError(DfaError::InconsistentMinimization(2, _))
+        ));
+    }
+
     #[test]
     fn test_dfa_from_nfa_3() {
         let mut multi_pattern_nfa = MultiPatternNfa::new();
@@ -704,8 +1451,8 @@ mod tests {
 
         assert_eq!(dfa.states().len(), 154);
         assert_eq!(dfa.pattern().len(), 40);
-        assert_eq!(dfa.accepting_states().len(), 45);
-        assert_eq!(dfa.char_classes().len(), 50);
+        assert_eq!(dfa.accepting_states().len(), 48);
+        assert_eq!(dfa.char_classes().len(), 53);
     }
 
     #[test]
@@ -774,4 +1521,85 @@ mod tests {
             );
         }
     }
+
+    /// The characters the random regexes are built from, plus one character they don't name.
+    const ALPHABET: &[char] = &['a', 'b', 'c', 'd'];
+
+    /// Returns a strategy for small random regexes over the alphabet.
+    fn regex() -> impl Strategy<Value = String> {
+        let leaf = prop_oneof![Just("a"), Just("b"), Just("c"), Just("[ab]"), Just("[^a]"),]
+            .prop_map(String::from);
+        leaf.prop_recursive(4, 16, 2, |inner| {
+            prop_oneof![
+                (inner.clone(), inner.clone()).prop_map(|(l, r)| format!("{}{}", l, r)),
+                (inner.clone(), inner.clone()).prop_map(|(l, r)| format!("({}|{})", l, r)),
+                inner.clone().prop_map(|r| format!("({})*", r)),
+                inner.clone().prop_map(|r| format!("({})+", r)),
+                inner.prop_map(|r| format!("({})?", r)),
+            ]
+        })
+    }
+
+    /// Returns the pattern that accepts the whole input, if any.
+    fn accepted_pattern(dfa: &Dfa, input: &str) -> Option<PatternID> {
+        let mut state = StateID::default();
+        for c in input.chars() {
+            state = dfa.next_state(state, c)?;
+        }
+        dfa.pattern_id(state)
+    }
+
+    /// Asserts with a product construction over the alphabet that both DFAs accept the same
+    /// strings with the same patterns. A missing transition leads to the dead state `None`.
+    fn assert_equivalent(dfa: &Dfa, minimized_dfa: &Dfa, pattern: &str) {
+        let start = Some(StateID::default());
+        let mut visited = BTreeSet::new();
+        let mut work_list = vec![(start, start)];
+        while let Some((state, minimized_state)) = work_list.pop() {
+            if !visited.insert((state, minimized_state)) {
+                continue;
+            }
+            assert_eq!(
+                state.and_then(|state| dfa.pattern_id(state)),
+                minimized_state.and_then(|state| minimized_dfa.pattern_id(state)),
+                "states {:?} and {:?} of {:?}",
+                state,
+                minimized_state,
+                pattern
+            );
+            for c in ALPHABET {
+                work_list.push((
+                    state.and_then(|state| dfa.next_state(state, *c)),
+                    minimized_state.and_then(|state| minimized_dfa.next_state(state, *c)),
+                ));
+            }
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(256))]
+
+        #[test]
+        fn test_minimize_preserves_language(
+            pattern in regex(),
+            inputs in proptest::collection::vec("[abcd]{0,6}", 32),
+        ) {
+            // Like the DFAs of the generated scanners, each DFA matches a single pattern.
+            let mut multi_pattern_nfa = MultiPatternNfa::new();
+            prop_assume!(multi_pattern_nfa.add_pattern(&pattern).is_ok());
+            let dfa = Dfa::try_from(multi_pattern_nfa).unwrap();
+            let minimized_dfa = dfa.minimize().unwrap();
+            prop_assert!(minimized_dfa.states().len() <= dfa.states().len());
+            for input in &inputs {
+                prop_assert_eq!(
+                    accepted_pattern(&dfa, input),
+                    accepted_pattern(&minimized_dfa, input),
+                    "input {:?} of {:?}",
+                    input,
+                    pattern
+                );
+            }
+            assert_equivalent(&dfa, &minimized_dfa, &pattern);
+        }
+    }
 }
@@ -0,0 +1,237 @@
+//! This module contains a public DFA type that makes the DFA construction, minimization and union
+//! of the code generator available on their own, e.g. for experiments with regular languages.
+
+use crate::Result;
+
+use super::{dfa::Dfa, MultiPatternNfa, StateID};
+
+/// A deterministic finite automaton that accepts the strings matched by one or more regex
+/// patterns.
+///
+/// The patterns are numbered in the order they were added. If a string is matched by several
+/// patterns, the first one wins, just like the terminals of a scanner mode. Patterns with lazy
+/// repetitions stop at their shortest match and the absent operator `P(?~X)Y` is supported, like in
+/// the generated scanners.
+///
+/// ```
+/// use scangen::DfaTable;
+///
+/// let keyword = DfaTable::from_pattern("if").unwrap();
+/// let identifier = DfaTable::from_pattern("[a-z]+").unwrap();
+/// let dfa = keyword.union(&identifier).minimize().unwrap();
+/// assert_eq!(dfa.matching_pattern("if"), Some(0));
+/// assert_eq!(dfa.matching_pattern("iff"), Some(1));
+/// assert!(!dfa.accepts("if0"));
+/// ```
+#[derive(Debug)]
+pub struct DfaTable {
+    dfa: Dfa,
+}
+
+impl DfaTable {
+    /// Creates a DFA for the given regex pattern.
+    pub fn from_pattern(pattern: &str) -> Result<Self> {
+        Ok(Self {
+            dfa: Dfa::try_from_pattern(pattern)?,
+        })
+    }
+
+    /// Creates a DFA for the given regex patterns. Without any pattern the DFA accepts no string.
+    pub fn from_patterns<I, S>(patterns: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut patterns = patterns.into_iter();
+        let Some(first) = patterns.next() else {
+            return Ok(Self {
+                dfa: MultiPatternNfa::new().try_into()?,
+            });
+        };
+        patterns.try_fold(Self::from_pattern(first.as_ref())?, |table, pattern| {
+            Ok(table.union(&Self::from_pattern(pattern.as_ref())?))
+        })
+    }
+
+    /// Returns the minimal DFA that accepts the same strings with the same patterns.
+    pub fn minimize(&self) -> Result<Self> {
+        Ok(Self {
+            dfa: self.dfa.minimize()?,
+        })
+    }
+
+    /// Returns a DFA that accepts the strings of both DFAs. The patterns of the other DFA follow
+    /// the patterns of this DFA and lose against them.
+    pub fn union(&self, other: &DfaTable) -> Self {
+        Self {
+            dfa: self.dfa.union(&other.dfa),
+        }
+    }
+
+    /// Returns a DFA that accepts the strings that are accepted by both DFAs. The strings are
+    /// accepted with the patterns of this DFA.
+    pub fn intersect(&self, other: &DfaTable) -> Self {
+        Self {
+            dfa: self.dfa.intersect(&other.dfa),
+        }
+    }
+
+    /// Returns a DFA that accepts the strings of this DFA that are not accepted by the other DFA,
+    /// e.g. identifiers without keywords.
+    pub fn difference(&self, other: &DfaTable) -> Self {
+        Self {
+            dfa: self.dfa.difference(&other.dfa),
+        }
+    }
+
+    /// Returns true if the DFA accepts no string at all, e.g. if the intersection of two DFAs is
+    /// empty.
+    pub fn is_empty(&self) -> bool {
+        self.dfa.is_empty()
+    }
+
+    /// Returns a shortest string the DFA accepts, if any.
+    pub fn shortest_accepted_string(&self) -> Option<String> {
+        self.dfa.shortest_accepted_string()
+    }
+
+    /// Returns true if the whole input is matched by one of the patterns.
+    pub fn accepts(&self, input: &str) -> bool {
+        self.matching_pattern(input).is_some()
+    }
+
+    /// Returns the index of the pattern that matches the whole input, if any.
+    pub fn matching_pattern(&self, input: &str) -> Option<usize> {
+        let state = input
+            .chars()
+            .try_fold(StateID::default(), |state, c| self.dfa.next_state(state, c))?;
+        self.dfa
+            .pattern_id(state)
+            .map(|pattern_id| pattern_id.as_usize())
+    }
+
+    /// Returns the patterns of the DFA in the order of their indices.
+    pub fn patterns(&self) -> &[String] {
+        self.dfa.pattern()
+    }
+
+    /// Returns the number of states of the DFA.
+    pub fn state_count(&self) -> usize {
+        self.dfa.states().len()
+    }
+}
+
+/// Renders the DFA as a readable state table with one line per state.
+///
+/// ```
+/// use scangen::DfaTable;
+///
+/// let dfa = DfaTable::from_pattern("[a-z]+").unwrap();
+/// assert_eq!(
+///     dfa.to_string(),
+///     "\
+/// DFA with 2 states for 1 pattern
+/// Patterns:
+///   #0: [a-z]+
+/// States:
+///   >  0: [a-z] -> 1
+///    * 1: [a-z] -> 1; accepts #0
+/// "
+/// );
+/// ```
+impl std::fmt::Display for DfaTable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.dfa)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dfa_table() {
+        let dfa = DfaTable::from_patterns(["in", "int", "[a-z]+"]).unwrap();
+        assert_eq!(dfa.patterns(), ["in", "int", "[a-z]+"]);
+        assert_eq!(dfa.matching_pattern("in"), Some(0));
+        assert_eq!(dfa.matching_pattern("int"), Some(1));
+        assert_eq!(dfa.matching_pattern("i"), Some(2));
+        assert_eq!(dfa.matching_pattern("into"), Some(2));
+        assert_eq!(dfa.matching_pattern(""), None);
+        assert_eq!(dfa.matching_pattern("in1"), None);
+
+        let minimized_dfa = dfa.minimize().unwrap();
+        assert!(minimized_dfa.state_count() <= dfa.state_count());
+        for input in ["", "i", "in", "int", "into", "in1", "x", "Int"] {
+            assert_eq!(
+                minimized_dfa.matching_pattern(input),
+                dfa.matching_pattern(input),
+                "{input}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_dfa_table_union() {
+        let even = DfaTable::from_pattern("(aa)*").unwrap();
+        let odd = DfaTable::from_pattern("a(aa)*").unwrap();
+        let dfa = even.union(&odd).minimize().unwrap();
+        for n in 0..6 {
+            assert_eq!(dfa.matching_pattern(&"a".repeat(n)), Some(n % 2));
+        }
+        assert!(!dfa.accepts("b"));
+
+        let empty = DfaTable::from_patterns(Vec::<&str>::new()).unwrap();
+        assert!(!empty.accepts(""));
+        assert_eq!(empty.union(&even).matching_pattern("aa"), Some(0));
+    }
+
+    #[test]
+    fn test_dfa_table_intersect_and_difference() {
+        let identifier = DfaTable::from_pattern("[a-z][a-z0-9]*").unwrap();
+        let keywords = DfaTable::from_patterns(["if", "else", "while"]).unwrap();
+
+        let identifier_without_keywords = identifier.difference(&keywords).minimize().unwrap();
+        assert_eq!(identifier_without_keywords.patterns(), ["[a-z][a-z0-9]*"]);
+        for input in ["if", "else", "while"] {
+            assert!(!identifier_without_keywords.accepts(input), "{input}");
+        }
+        for input in ["i", "iff", "els", "while1", "x"] {
+            assert_eq!(
+                identifier_without_keywords.matching_pattern(input),
+                Some(0),
+                "{input}"
+            );
+        }
+        let shortest = identifier_without_keywords
+            .shortest_accepted_string()
+            .unwrap();
+        assert_eq!(shortest.len(), 1);
+        assert!(identifier_without_keywords.accepts(&shortest));
+
+        let keyword_identifiers = identifier.intersect(&keywords);
+        assert!(keyword_identifiers.accepts("while"));
+        assert_eq!(
+            keyword_identifiers.shortest_accepted_string().as_deref(),
+            Some("if")
+        );
+        assert!(keywords.difference(&identifier).is_empty());
+        assert!(identifier
+            .intersect(&DfaTable::from_pattern("[0-9]+").unwrap())
+            .is_empty());
+        assert_eq!(
+            DfaTable::from_pattern("a*")
+                .unwrap()
+                .shortest_accepted_string()
+                .as_deref(),
+            Some("")
+        );
+    }
+
+    #[test]
+    fn test_dfa_table_lazy_repetition() {
+        let dfa = DfaTable::from_pattern(r"/\*.*?\*/").unwrap();
+        assert!(dfa.accepts("/* a */"));
+        assert!(!dfa.accepts("/* a */ b */"));
+    }
+}
@@ -0,0 +1,479 @@
+//! This module defines terminal sets of language dialects as layers of changes to a base set and
+//! generates one scanner that serves all dialects.
+
+use crate::{
+    compiletime::{generate_code_with_options, EntryPoint, GenerateOptions},
+    Result, ScanGenError, ScanGenErrorKind, ScannerModeData, TokenType,
+};
+
+/// A change of a dialect layer to the terminals of the dialect it is based on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Change {
+    Add {
+        name: String,
+        pattern: String,
+        before: Option<String>,
+    },
+    Replace {
+        name: String,
+        pattern: String,
+    },
+    Remove {
+        name: String,
+    },
+}
+
+/// A layer of changes that defines the terminals of a dialect on top of the base terminals or of
+/// another dialect, e.g. the keywords an edition of a language adds or removes.
+///
+/// The layer is created with [DialectLayer::new] and its changes are added in fluent notation.
+/// They are applied in the order they are added.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DialectLayer {
+    name: String,
+    based_on: Option<String>,
+    changes: Vec<Change>,
+}
+
+impl DialectLayer {
+    /// Creates a new layer of the dialect with the given name that is based on the base
+    /// terminals.
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            based_on: None,
+            changes: Vec::new(),
+        }
+    }
+
+    /// Bases the layer on the dialect with the given name, which must be defined before this
+    /// one.
+    pub fn based_on(mut self, dialect: &str) -> Self {
+        self.based_on = Some(dialect.to_string());
+        self
+    }
+
+    /// Adds a terminal with the lowest priority of the dialect.
+    pub fn add(mut self, name: &str, pattern: &str) -> Self {
+        self.changes.push(Change::Add {
+            name: name.to_string(),
+            pattern: pattern.to_string(),
+            before: None,
+        });
+        self
+    }
+
+    /// Adds a terminal with a higher priority than the terminal named `before`, e.g. a keyword
+    /// that must win over the identifier.
+    pub fn add_before(mut self, before: &str, name: &str, pattern: &str) -> Self {
+        self.changes.push(Change::Add {
+            name: name.to_string(),
+            pattern: pattern.to_string(),
+            before: Some(before.to_string()),
+        });
+        self
+    }
+
+    /// Replaces the pattern of a terminal. Its priority and its token type don't change.
+    pub fn replace(mut self, name: &str, pattern: &str) -> Self {
+        self.changes.push(Change::Replace {
+            name: name.to_string(),
+            pattern: pattern.to_string(),
+        });
+        self
+    }
+
+    /// Removes a terminal from the dialect.
+    pub fn remove(mut self, name: &str) -> Self {
+        self.changes.push(Change::Remove {
+            name: name.to_string(),
+        });
+        self
+    }
+
+    /// Returns the name of the dialect.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// The terminal sets of the dialects of a language with a versioned grammar, defined by a set
+/// of base terminals and layers that add, replace or remove terminals per dialect.
+///
+/// Each terminal is identified by its name. Its token type is the same in all dialects: the base
+/// terminals get the token types in their order, terminals that are added by layers get the
+/// following ones in the order they first appear. The order of the terminals of a dialect is
+/// their priority.
+///
+/// [Dialects::generate_code] generates one scanner with a scanner mode per dialect. The modes
+/// share the DFAs of identical patterns, so the tables of a terminal exist only once regardless
+/// of how many dialects use it.
+///
+/// ```rust
+/// use scangen::{DialectLayer, Dialects};
+/// let dialects = Dialects::new(
+///     "2015",
+///     &[("Whitespace", r"\s+"), ("Fn", "fn"), ("Identifier", "[a-z]+")],
+/// )
+/// .with_layer(DialectLayer::new("2018").add_before("Identifier", "Async", "async"))
+/// .with_layer(DialectLayer::new("2021").based_on("2018").remove("Fn"));
+/// assert_eq!(dialects.dialect_names(), vec!["2015", "2018", "2021"]);
+/// assert_eq!(
+///     dialects.token_names().unwrap(),
+///     vec!["Whitespace", "Fn", "Identifier", "Async"]
+/// );
+/// let patterns = dialects
+///     .terminals("2021")
+///     .unwrap()
+///     .into_iter()
+///     .map(|(token_type, pattern)| (token_type.as_usize(), pattern))
+///     .collect::<Vec<_>>();
+/// assert_eq!(
+///     patterns,
+///     vec![(0, r"\s+".to_string()), (3, "async".to_string()), (2, "[a-z]+".to_string())]
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dialects {
+    base_name: String,
+    base: Vec<(String, String)>,
+    layers: Vec<DialectLayer>,
+}
+
+impl Dialects {
+    /// Creates the dialects from the name of the base dialect and its terminals, given as pairs
+    /// of name and pattern in the order of their priority.
+    pub fn new(base_name: &str, terminals: &[(&str, &str)]) -> Self {
+        Self {
+            base_name: base_name.to_string(),
+            base: terminals
+                .iter()
+                .map(|(name, pattern)| (name.to_string(), pattern.to_string()))
+                .collect(),
+            layers: Vec::new(),
+        }
+    }
+
+    /// Adds the layer of a dialect.
+    pub fn with_layer(mut self, layer: DialectLayer) -> Self {
+        self.layers.push(layer);
+        self
+    }
+
+    /// Returns the names of the dialects, starting with the base dialect. The dialect at index
+    /// `n` is scanned by the scanner mode `n` of the generated scanner.
+    pub fn dialect_names(&self) -> Vec<&str> {
+        std::iter::once(self.base_name.as_str())
+            .chain(self.layers.iter().map(DialectLayer::name))
+            .collect()
+    }
+
+    /// Returns the names of the terminals of all dialects, indexed by token type.
+    ///
+    /// # Errors
+    /// An error is returned if a layer can't be applied, see [Dialects::terminals].
+    pub fn token_names(&self) -> Result<Vec<String>> {
+        Ok(self.resolve()?.0)
+    }
+
+    /// Returns the token types and patterns of the terminals of the given dialect in the order
+    /// of their priority.
+    ///
+    /// # Errors
+    /// An error is returned if the dialect doesn't exist, if a dialect name or a terminal name
+    /// is defined twice, if a layer is based on an unknown or later dialect or if a change refers
+    /// to a terminal the dialect doesn't have.
+    pub fn terminals(&self, dialect: &str) -> Result<Vec<(TokenType, String)>> {
+        let (names, dialects) = self.resolve()?;
+        let (_, terminals) = dialects
+            .into_iter()
+            .find(|(name, _)| name == dialect)
+            .ok_or_else(|| invalid_dialect(dialect, "The dialect doesn't exist"))?;
+        Ok(terminals
+            .into_iter()
+            .map(|(name, pattern)| (token_type(&names, &name), pattern))
+            .collect())
+    }
+
+    /// Generates a scanner module for all dialects. The scanner has a scanner mode per dialect,
+    /// named after it, and the modes share the DFAs of identical patterns.
+    ///
+    /// In addition to the entry points selected by the options, the module contains the constant
+    /// `DIALECTS` with the names of the dialects and the constant `TOKEN_NAMES` with the names
+    /// of the terminals indexed by token type. If `create_scanner` is generated,
+    /// `create_dialect_scanner(dialect: usize)` creates a scanner for the dialect at the given
+    /// index of `DIALECTS`.
+    ///
+    /// # Errors
+    /// An error is returned if the dialects are inconsistent, see [Dialects::terminals], or if
+    /// a pattern contains unsupported syntax.
+    pub fn generate_code(
+        &self,
+        options: &GenerateOptions,
+        output: &mut dyn std::io::Write,
+    ) -> Result<()> {
+        let (names, dialects) = self.resolve()?;
+        let mut patterns: Vec<&str> = Vec::new();
+        let mut scanner_mode_data: Vec<ScannerModeData> = Vec::new();
+        for (dialect, terminals) in &dialects {
+            let dfas = terminals
+                .iter()
+                .map(|(name, pattern)| {
+                    let dfa_index = patterns
+                        .iter()
+                        .position(|known| known == pattern)
+                        .unwrap_or_else(|| {
+                            patterns.push(pattern);
+                            patterns.len() - 1
+                        });
+                    (dfa_index, token_type(&names, name))
+                })
+                .collect::<Vec<_>>();
+            // The scanner mode data of the code generation borrows for 'static like the data of
+            // generated code. The data is small and the generation runs once per build.
+            scanner_mode_data.push((String::leak(dialect.clone()), Vec::leak(dfas), &[], None));
+        }
+
+        let mut epilogue = format!(
+            r"/// The names of the dialects. The dialect at index `n` is scanned by the scanner mode `n`.
+pub(crate) const DIALECTS: &[&str] = &{:?};
+
+/// The names of the terminals of all dialects, indexed by token type.
+pub(crate) const TOKEN_NAMES: &[&str] = &{:?};
+",
+            self.dialect_names(),
+            names
+        );
+        if options.generates(EntryPoint::CreateScanner) {
+            epilogue.push_str(
+                r"
+/// Creates a scanner for the dialect at the given index of [DIALECTS].
+pub(crate) fn create_dialect_scanner(dialect: usize) -> Scanner {
+    let mut scanner = SCANNER_DEF.create_scanner();
+    scanner.set_mode(dialect.into());
+    scanner
+}
+",
+            );
+        }
+        if !options.epilogue().is_empty() {
+            epilogue.push('\n');
+            epilogue.push_str(options.epilogue());
+        }
+        generate_code_with_options(
+            &patterns,
+            &scanner_mode_data,
+            &options.clone().with_epilogue(&epilogue),
+            output,
+        )
+    }
+
+    /// Applies the layers and returns the names of all terminals, indexed by token type, along
+    /// with the terminals of each dialect.
+    #[allow(clippy::type_complexity)]
+    fn resolve(&self) -> Result<(Vec<String>, Vec<(String, Vec<(String, String)>)>)> {
+        let mut names: Vec<String> = Vec::new();
+        for (name, _) in &self.base {
+            if names.contains(name) {
+                return Err(invalid_dialect(
+                    &self.base_name,
+                    &format!("The terminal {} is defined twice", name),
+                ));
+            }
+            names.push(name.clone());
+        }
+        let mut dialects = vec![(self.base_name.clone(), self.base.clone())];
+        for layer in &self.layers {
+            if dialects.iter().any(|(name, _)| *name == layer.name) {
+                return Err(invalid_dialect(&layer.name, "The dialect is defined twice"));
+            }
+            let base_name = layer.based_on.as_ref().unwrap_or(&self.base_name);
+            let mut terminals = dialects
+                .iter()
+                .find(|(name, _)| name == base_name)
+                .map(|(_, terminals)| terminals.clone())
+                .ok_or_else(|| {
+                    invalid_dialect(
+                        &layer.name,
+                        &format!(
+                            "The dialect {} it is based on isn't defined before",
+                            base_name
+                        ),
+                    )
+                })?;
+            for change in &layer.changes {
+                apply_change(&layer.name, &mut terminals, change)?;
+                if let Change::Add { name, .. } = change {
+                    if !names.contains(name) {
+                        names.push(name.clone());
+                    }
+                }
+            }
+            dialects.push((layer.name.clone(), terminals));
+        }
+        Ok((names, dialects))
+    }
+}
+
+/// Applies a change of the layer of the given dialect to its terminals.
+fn apply_change(
+    dialect: &str,
+    terminals: &mut Vec<(String, String)>,
+    change: &Change,
+) -> Result<()> {
+    let position = |terminals: &[(String, String)], name: &str| {
+        terminals
+            .iter()
+            .position(|(terminal, _)| terminal == name)
+            .ok_or_else(|| {
+                invalid_dialect(
+                    dialect,
+                    &format!("The terminal {} doesn't exist in the dialect", name),
+                )
+            })
+    };
+    match change {
+        Change::Add {
+            name,
+            pattern,
+            before,
+        } => {
+            if terminals.iter().any(|(terminal, _)| terminal == name) {
+                return Err(invalid_dialect(
+                    dialect,
+                    &format!("The terminal {} is added twice", name),
+                ));
+            }
+            let index = match before {
+                Some(before) => position(terminals, before)?,
+                None => terminals.len(),
+            };
+            terminals.insert(index, (name.clone(), pattern.clone()));
+        }
+        Change::Replace { name, pattern } => {
+            let index = position(terminals, name)?;
+            terminals[index].1 = pattern.clone();
+        }
+        Change::Remove { name } => {
+            let index = position(terminals, name)?;
+            terminals.remove(index);
+        }
+    }
+    Ok(())
+}
+
+/// Returns the token type of the terminal with the given name.
+fn token_type(names: &[String], name: &str) -> TokenType {
+    // All terminals of the dialects are in the names.
+    TokenType::new(
+        names
+            .iter()
+            .position(|known| known == name)
+            .unwrap_or_default(),
+    )
+}
+
+fn invalid_dialect(dialect: &str, message: &str) -> ScanGenError {
+    ScanGenError::new(ScanGenErrorKind::InvalidDialect {
+        dialect: dialect.to_string(),
+        message: message.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::compiletime::try_format;
+
+    fn dialects() -> Dialects {
+        Dialects::new(
+            "2015",
+            &[
+                ("Whitespace", r"\s+"),
+                ("Fn", "fn"),
+                ("Identifier", "[a-z]+"),
+                ("Number", "[0-9]+"),
+            ],
+        )
+        .with_layer(DialectLayer::new("2018").add_before("Identifier", "Async", "async"))
+        .with_layer(
+            DialectLayer::new("2021")
+                .based_on("2018")
+                .replace("Number", "[0-9][0-9_]*")
+                .remove("Fn"),
+        )
+    }
+
+    #[test]
+    fn generate_code_for_dialect_scanner() {
+        // The generated module is compiled and used by the test below.
+        let file_name = "src/compiletime/generated/dialect_scanner.rs";
+        {
+            let mut out_file = fs::File::create(file_name).unwrap();
+            dialects()
+                .generate_code(
+                    &GenerateOptions::new()
+                        .with_scangen_module_name("crate")
+                        .with_entry_points(&[
+                            EntryPoint::CreateScanner,
+                            EntryPoint::CreateFindIter,
+                        ]),
+                    &mut out_file,
+                )
+                .unwrap();
+        }
+        try_format(file_name).unwrap();
+    }
+
+    // The generated scanner needs the runtime.
+    #[cfg(feature = "runtime")]
+    #[test]
+    fn test_dialect_scanner() {
+        use crate::compiletime::generated::dialect_scanner;
+
+        assert_eq!(dialect_scanner::DIALECTS, ["2015", "2018", "2021"]);
+        assert_eq!(
+            dialect_scanner::TOKEN_NAMES,
+            ["Whitespace", "Fn", "Identifier", "Number", "Async"]
+        );
+        // The dialects share the DFAs of identical patterns
+        assert_eq!(dialect_scanner::create_scanner().dfas().count(), 6);
+        let token_types = |dialect: usize| {
+            let scanner = dialect_scanner::create_dialect_scanner(dialect);
+            dialect_scanner::create_find_iter(&scanner, "fn async 1_0")
+                .map(|m| m.token_type().as_usize())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(token_types(0), vec![1, 0, 2, 0, 3, 3]);
+        assert_eq!(token_types(1), vec![1, 0, 4, 0, 3, 3]);
+        assert_eq!(token_types(2), vec![2, 0, 4, 0, 3]);
+    }
+
+    #[test]
+    fn test_invalid_dialects() {
+        let error = |dialects: Dialects| dialects.token_names().unwrap_err().to_string();
+        assert_eq!(
+            error(dialects().with_layer(DialectLayer::new("2024").remove("Fn").based_on("2021"))),
+            "Invalid dialect 2024: The terminal Fn doesn't exist in the dialect"
+        );
+        assert_eq!(
+            error(dialects().with_layer(DialectLayer::new("2018"))),
+            "Invalid dialect 2018: The dialect is defined twice"
+        );
+        assert_eq!(
+            error(dialects().with_layer(DialectLayer::new("2024").based_on("2027"))),
+            "Invalid dialect 2024: The dialect 2027 it is based on isn't defined before"
+        );
+        assert_eq!(
+            error(dialects().with_layer(DialectLayer::new("2024").add("Fn", "fun"))),
+            "Invalid dialect 2024: The terminal Fn is added twice"
+        );
+        assert_eq!(
+            error(Dialects::new("base", &[("A", "a"), ("A", "b")])),
+            "Invalid dialect base: The terminal A is defined twice"
+        );
+        assert!(dialects().terminals("2027").is_err());
+    }
+}
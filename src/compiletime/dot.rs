@@ -5,7 +5,7 @@ use std::io::Write;
 
 use dot_writer::{Attributes, DotWriter, RankDirection};
 
-use super::{dfa::Dfa, nfa::Nfa, MultiPatternNfa, StateID};
+use super::{character_class::class_to_string, dfa::Dfa, nfa::Nfa, MultiPatternNfa, StateID};
 
 /// Render the NFA to a graphviz dot format.
 #[allow(dead_code)]
@@ -39,17 +39,17 @@ pub(crate) fn nfa_render<W: Write>(nfa: &Nfa, label: &str, output: &mut W) {
             digraph
                 .edge(
                     source_id.clone(),
-                    &format!("node_{}", target_state.as_usize()),
+                    format!("node_{}", target_state.as_usize()),
                 )
                 .attributes()
-                .set_label(&format!("{}", transition.chars()));
+                .set_label(&class_to_string(transition.chars()));
         }
         for epsilon_transition in state.epsilon_transitions() {
             let target_state = epsilon_transition.target_state();
             digraph
                 .edge(
                     source_id.clone(),
-                    &format!("node_{}", target_state.as_usize()),
+                    format!("node_{}", target_state.as_usize()),
                 )
                 .attributes()
                 .set_label("ε");
@@ -95,14 +95,12 @@ pub(crate) fn multi_nfa_render<W: Write>(nfa: &MultiPatternNfa, label: &str, out
             digraph
                 .edge(
                     source_id.clone(),
-                    &format!("node_{}", target_state.as_usize()),
+                    format!("node_{}", target_state.as_usize()),
                 )
                 .attributes()
                 .set_label(&format!(
                     "{}:{}",
                     nfa.char_classes()[transition.chars()]
-                        .ast
-                        .0
                         .to_string()
                         .escape_default(),
                     transition.chars()
@@ -113,7 +111,7 @@ pub(crate) fn multi_nfa_render<W: Write>(nfa: &MultiPatternNfa, label: &str, out
             digraph
                 .edge(
                     source_id.clone(),
-                    &format!("node_{}", target_state.as_usize()),
+                    format!("node_{}", target_state.as_usize()),
                 )
                 .attributes()
                 .set_label("ε");
@@ -158,15 +156,13 @@ pub(crate) fn dfa_render<W: Write>(dfa: &Dfa, label: &str, output: &mut W) {
             // Label the edge with the character class used to transition to the target state.
             digraph
                 .edge(
-                    &format!("node_{}", source_id.as_usize()),
-                    &format!("node_{}", target_id.as_usize()),
+                    format!("node_{}", source_id.as_usize()),
+                    format!("node_{}", target_id.as_usize()),
                 )
                 .attributes()
                 .set_label(&format!(
                     "{}:{}",
                     dfa.char_classes()[char_id.id()]
-                        .ast
-                        .0
                         .to_string()
                         .escape_default(),
                     char_id.id()
@@ -32,6 +32,11 @@ pub enum ScanGenErrorKind {
     #[error(transparent)]
     RegexSyntaxError(#[from] regex_syntax::ast::Error),
 
+    /// An error occurred during the translation of the regex syntax into the HIR, e.g. an
+    /// unknown Unicode class.
+    #[error(transparent)]
+    RegexTranslationError(#[from] regex_syntax::hir::Error),
+
     /// A std::io error occurred.
     #[error(transparent)]
     IoError(#[from] std::io::Error),
@@ -40,17 +45,121 @@ pub enum ScanGenErrorKind {
     #[error("Unsupported regex feature: {0}")]
     UnsupportedFeature(String),
 
+    /// A bounded repetition expands to too many NFA states.
+    /// The first value is the number of states, the second value is the limit.
+    #[error("Bounded repetition expands to {0} NFA states, which exceeds the limit of {1}")]
+    RepetitionTooLarge(usize, usize),
+
+    /// The generated DFA tables exceed the size budget given in the generate options.
+    /// The patterns that contribute most are listed together with their number of bytes.
+    #[error(
+        "The generated DFA tables take {size} bytes, which exceeds the budget of {budget} bytes. \
+        The patterns that contribute most are: {}. Consider simplifying them, e.g. by replacing \
+        large Unicode classes or bounded repetitions",
+        largest_patterns(.largest)
+    )]
+    TableSizeExceeded {
+        /// The number of bytes of the DFA tables.
+        size: usize,
+        /// The size budget in bytes.
+        budget: usize,
+        /// The patterns with the most bytes of DFA data in descending order.
+        largest: Vec<(String, usize)>,
+    },
+
+    /// A feature of the generate options is assigned to a terminal that doesn't exist.
+    /// The first value is the index of the terminal, the second value is the number of patterns.
+    #[error("The terminal {0} of a feature doesn't exist, only {1} patterns are given")]
+    UnknownTerminal(usize, usize),
+
+    /// The layers of the dialects of a language can't be applied.
+    #[error("Invalid dialect {dialect}: {message}")]
+    InvalidDialect {
+        /// The name of the dialect.
+        dialect: String,
+        /// The description of the problem.
+        message: String,
+    },
+
+    /// The name of a token injected by the generate options is no Rust identifier.
+    #[error("Invalid token name: {0:?}")]
+    InvalidTokenName(String),
+
+    /// A terminal repeats the pattern of an earlier terminal and the generate options forbid
+    /// duplicate patterns, see [crate::DuplicatePatterns::Error].
+    #[error("The terminal {terminal} repeats the pattern {pattern:?} of the terminal {alias_of}")]
+    DuplicatePattern {
+        /// The index of the terminal that repeats the pattern.
+        terminal: usize,
+        /// The index of the first terminal with the pattern.
+        alias_of: usize,
+        /// The repeated pattern.
+        pattern: String,
+    },
+
+    /// A terminal alias of the generate options doesn't refer to an earlier terminal.
+    #[error(
+        "The terminal {alias} can't be an alias of the terminal {target}, which doesn't precede it"
+    )]
+    InvalidTerminalAlias {
+        /// The index of the alias terminal.
+        alias: usize,
+        /// The index of the terminal it should be an alias of.
+        target: usize,
+    },
+
+    /// A contextual keyword of the generate options can't be reinterpreted from another terminal.
+    #[error("The terminal {terminal} can't be a contextual keyword: {message}")]
+    InvalidContextualKeyword {
+        /// The index of the terminal.
+        terminal: usize,
+        /// The description of the problem.
+        message: String,
+    },
+
+    /// A case-insensitive keyword of the generate options can't be recognized by another terminal.
+    #[error("The terminal {terminal} can't be a case-insensitive keyword: {message}")]
+    InvalidCaseInsensitiveKeyword {
+        /// The index of the terminal.
+        terminal: usize,
+        /// The description of the problem.
+        message: String,
+    },
+
+    /// A scanner mode named in the generate options doesn't exist.
+    #[error("Unknown scanner mode: {0:?}")]
+    UnknownScannerMode(String),
+
+    /// The name of a crate to scaffold is no valid crate name.
+    #[error("Invalid crate name: {0:?}")]
+    InvalidCrateName(String),
+
     /// An error occurred during construction of the DFA.
     #[error(transparent)]
     DfaError(DfaError),
 }
 
+/// Formats the patterns that contribute most to the table size.
+fn largest_patterns(largest: &[(String, usize)]) -> String {
+    largest
+        .iter()
+        .map(|(pattern, bytes)| format!("{:?} ({} bytes)", pattern, bytes))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
 impl From<regex_syntax::ast::Error> for ScanGenError {
     fn from(error: regex_syntax::ast::Error) -> Self {
         ScanGenError::new(ScanGenErrorKind::RegexSyntaxError(error))
     }
 }
 
+impl From<regex_syntax::hir::Error> for ScanGenError {
+    fn from(error: regex_syntax::hir::Error) -> Self {
+        ScanGenError::new(ScanGenErrorKind::RegexTranslationError(error))
+    }
+}
+
 impl From<std::io::Error> for ScanGenError {
     fn from(error: std::io::Error) -> Self {
         ScanGenError::new(ScanGenErrorKind::IoError(error))
@@ -66,5 +175,12 @@ pub enum DfaError {
 
     /// An error occurred during the construction of a single-pattern DFA.
     #[error("Single-pattern DFA construction error: {0}")]
+    #[allow(clippy::enum_variant_names)]
     SinglePatternDfaError(String),
+
+    /// The minimized DFA is inconsistent with the original DFA.
+    /// The first value is the state of the original DFA, the second value describes the
+    /// inconsistency.
+    #[error("Inconsistent DFA minimization at state {0}: {1}")]
+    InconsistentMinimization(usize, String),
 }
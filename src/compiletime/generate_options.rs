@@ -0,0 +1,445 @@
+//! This module contains the options that control the code generation.
+
+use std::path::{Path, PathBuf};
+
+use crate::TokenType;
+
+/// An entry point of the generated scanner module.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EntryPoint {
+    /// `create_scanner() -> Scanner` creates a new scanner.
+    CreateScanner,
+    /// `create_find_iter(scanner: &Scanner, input: &str) -> FindMatches` creates an iterator over
+    /// the matches of the given scanner.
+    CreateFindIter,
+    /// `scanner() -> &'static Scanner` returns a scanner that is created on first use and shared
+    /// by all callers.
+    SharedScanner,
+    /// `tokens(input: &str) -> TokenIter` creates an iterator over the tokens, i.e. the matches
+    /// along with their text.
+    Tokens,
+    /// `tokenize(input: &str) -> Vec<Match>` returns all matches of the input and
+    /// `tokenize_into(input: &str, matches: &mut Vec<Match>)` stores them in a reusable buffer.
+    Tokenize,
+    /// `MODE_TOKEN_TYPES: &[&[TokenType]]` lists the token types of each scanner mode in
+    /// ascending order, so the position of a token type is its mode-local index, and
+    /// `mode_local_index(mode: ScannerModeId, token_type: TokenType) -> Option<usize>` looks the
+    /// index up. See [crate::ModeInfo::local_token_types].
+    ModeTokenMap,
+}
+
+/// How the generation handles a terminal whose pattern is the same as the one of an earlier
+/// terminal, see [GenerateOptions::with_duplicate_patterns].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DuplicatePatterns {
+    /// The generation fails with [crate::ScanGenErrorKind::DuplicatePattern].
+    Error,
+    /// The terminal becomes an alias like with [DuplicatePatterns::Alias] and a warning is
+    /// logged.
+    #[default]
+    Warn,
+    /// The terminal becomes an alias of the earlier terminal. Both share one DFA and the alias
+    /// never wins against the earlier terminal, while the indices of all terminals are kept. The
+    /// aliases are listed in the [crate::CompilationReport].
+    Alias,
+}
+
+impl EntryPoint {
+    /// The entry points that are generated by default.
+    pub const DEFAULT: &'static [EntryPoint] = &[
+        EntryPoint::CreateScanner,
+        EntryPoint::CreateFindIter,
+        EntryPoint::SharedScanner,
+    ];
+}
+
+/// The options of the code generation.
+///
+/// The options are created with [GenerateOptions::new] and configured in fluent notation:
+/// ```rust
+/// use scangen::{EntryPoint, GenerateOptions};
+/// let options = GenerateOptions::new()
+///     .with_scangen_module_name("crate")
+///     .with_entry_points(&[EntryPoint::Tokenize]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GenerateOptions {
+    scangen_module_name: Option<String>,
+    entry_points: Vec<EntryPoint>,
+    prologue: String,
+    epilogue: String,
+    token_profile: Vec<(TokenType, u64)>,
+    max_table_size: Option<usize>,
+    terminal_features: Vec<(usize, String)>,
+    manifest: Option<PathBuf>,
+    error_token: Option<String>,
+    auto_newline: Option<String>,
+    auto_ws: Option<String>,
+    auto_ws_off: Vec<String>,
+    duplicate_patterns: DuplicatePatterns,
+    terminal_aliases: Vec<(usize, usize)>,
+    contextual_keywords: Vec<usize>,
+    case_insensitive_keywords: Vec<usize>,
+}
+
+impl GenerateOptions {
+    /// Creates the default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the path of the `scangen` crate the generated code imports from. This can be any path
+    /// that leads to the crate, e.g. `::scangen` or `my_crate::reexports::scangen` if the crate is
+    /// re-exported. The default is `scangen`.
+    ///
+    /// The generated code imports from the `prelude` module below this path.
+    pub fn with_scangen_module_name(mut self, scangen_module_name: &str) -> Self {
+        self.scangen_module_name = Some(scangen_module_name.to_string());
+        self
+    }
+
+    /// Sets the entry points of the generated module.
+    /// The default entry points are given in [EntryPoint::DEFAULT].
+    pub fn with_entry_points(mut self, entry_points: &[EntryPoint]) -> Self {
+        self.entry_points = entry_points.to_vec();
+        self
+    }
+
+    /// Sets code that is emitted verbatim at the top of the generated file, e.g. inner attributes,
+    /// imports or type aliases. It follows the inner attributes of the generated code, so it may
+    /// contain inner attributes itself.
+    pub fn with_prologue(mut self, prologue: &str) -> Self {
+        self.prologue = prologue.to_string();
+        self
+    }
+
+    /// Sets code that is emitted verbatim at the bottom of the generated file.
+    pub fn with_epilogue(mut self, epilogue: &str) -> Self {
+        self.epilogue = epilogue.to_string();
+        self
+    }
+
+    /// Sets a token frequency profile, i.e. how often each token type occurs in a typical input.
+    ///
+    /// The DFAs of each scanner mode are then advanced in the order of descending frequency of
+    /// their token types, so the DFAs that are most likely to survive a character come first.
+    /// This doesn't change the priorities of the token types. Token types that are missing in the
+    /// profile count as never observed.
+    pub fn with_token_profile(mut self, token_profile: &[(TokenType, u64)]) -> Self {
+        self.token_profile = token_profile.to_vec();
+        self
+    }
+
+    /// Sets a budget for the size of the generated DFA tables in bytes, e.g. for embedded targets
+    /// with strict flash limits.
+    ///
+    /// The generation fails with [crate::ScanGenErrorKind::TableSizeExceeded] if the tables
+    /// exceed the budget. The error lists the patterns that contribute most to the size.
+    pub fn with_max_table_size(mut self, max_table_size: usize) -> Self {
+        self.max_table_size = Some(max_table_size);
+        self
+    }
+
+    /// Tags terminals, given by their index in the patterns, with the names of the cargo features
+    /// of the generated crate that enable them. This way one list of patterns can serve several
+    /// language dialects or editions.
+    ///
+    /// The generated code guards the terminals with `#[cfg(feature = "...")]` attributes. A
+    /// terminal with several features is enabled if any of them is enabled. The generated scanner
+    /// doesn't match disabled terminals in any scanner mode, while the token types of the other
+    /// terminals don't change. Terminals without a feature are always enabled.
+    ///
+    /// The generation fails with [crate::ScanGenErrorKind::UnknownTerminal] if an index exceeds
+    /// the patterns.
+    pub fn with_terminal_features(mut self, terminal_features: &[(usize, &str)]) -> Self {
+        self.terminal_features = terminal_features
+            .iter()
+            .map(|(terminal, feature)| (*terminal, feature.to_string()))
+            .collect();
+        self
+    }
+
+    /// Adds an error token with the given name, which covers the input that no other terminal
+    /// matches, so terminal lists don't need to repeat a catch-all pattern.
+    ///
+    /// A terminal that matches any single character is appended with the lowest priority to each
+    /// scanner mode, or to the default mode if no scanner modes are given. Its token type is one
+    /// more than the greatest token type of the scanner modes, or the number of patterns for the
+    /// default mode. It becomes the error token type of all scanner modes, so the runtime reports
+    /// it as error token, see [crate::Scanner::error_token_type]. The generated code contains a
+    /// constant of the token type with the given name, e.g. `pub(crate) const ERROR: TokenType`.
+    ///
+    /// The generation fails with [crate::ScanGenErrorKind::InvalidTokenName] if the name is no
+    /// Rust identifier.
+    pub fn with_error_token(mut self, name: &str) -> Self {
+        self.error_token = Some(name.to_string());
+        self
+    }
+
+    /// Adds a newline token with the given name that matches the line breaks `\r\n`, `\r` and
+    /// `\n`, like the newline terminal parol adds to grammars without `%auto_newline_off`, so the
+    /// terminal lists don't need to contain it.
+    ///
+    /// The terminal is prepended with the highest priority to each scanner mode, or to the
+    /// default mode if no scanner modes are given. Its token type is one more than the greatest
+    /// token type of the scanner modes, or the number of patterns for the default mode. The
+    /// generated code contains a constant of the token type with the given name, e.g.
+    /// `pub(crate) const NEWLINE: TokenType`, and the scanner knows it as its newline token type,
+    /// see [crate::Scanner::newline_token_type]. Thus the positions of
+    /// [crate::FindMatches::with_positions] start a new line after each of these line breaks.
+    ///
+    /// Whitespace terminals should exclude the line breaks, e.g. `[\s--\r\n]+`, because a longer
+    /// match of another terminal still wins over the newline terminal.
+    ///
+    /// The generation fails with [crate::ScanGenErrorKind::InvalidTokenName] if the name is no
+    /// Rust identifier.
+    pub fn with_auto_newline(mut self, name: &str) -> Self {
+        self.auto_newline = Some(name.to_string());
+        self
+    }
+
+    /// Adds a whitespace token with the given name whose matches the scanner skips, like the
+    /// whitespace parol skips in grammars without `%auto_ws_off`, so the terminal lists and the
+    /// parsers don't need to handle whitespace.
+    ///
+    /// The terminal matches `\s+`, or `[\s--\r\n]+` along with
+    /// [GenerateOptions::with_auto_newline], so the line breaks are still newline tokens. It is
+    /// appended to each scanner mode, except for the modes given to
+    /// [GenerateOptions::with_auto_ws_off], or to the default mode if no scanner modes are given.
+    /// Its token type is one more than the greatest token type of the scanner modes, or the
+    /// number of patterns for the default mode. The generated code contains a constant of the
+    /// token type with the given name, e.g. `pub(crate) const WHITESPACE: TokenType`, and the
+    /// scanner skips its matches, see [crate::Scanner::skipped_token_types].
+    ///
+    /// The generation fails with [crate::ScanGenErrorKind::InvalidTokenName] if the name is no
+    /// Rust identifier.
+    pub fn with_auto_ws(mut self, name: &str) -> Self {
+        self.auto_ws = Some(name.to_string());
+        self
+    }
+
+    /// Sets the names of the scanner modes without the whitespace token of
+    /// [GenerateOptions::with_auto_ws], like `%auto_ws_off` in a scanner state of a parol grammar,
+    /// e.g. the mode of the content of string literals.
+    ///
+    /// The generation fails with [crate::ScanGenErrorKind::UnknownScannerMode] if a mode doesn't
+    /// exist. The default mode is named `INITIAL`.
+    pub fn with_auto_ws_off(mut self, modes: &[&str]) -> Self {
+        self.auto_ws_off = modes.iter().map(|mode| mode.to_string()).collect();
+        self
+    }
+
+    /// Sets how terminals are handled whose patterns are the same as the ones of earlier
+    /// terminals. By default such a terminal becomes an alias of the earlier one and a warning is
+    /// logged, see [DuplicatePatterns].
+    ///
+    /// The terminals of the scanner modes are given by their index in the patterns regardless of
+    /// aliases, and in the default scanner mode the token type of each terminal is its index.
+    pub fn with_duplicate_patterns(mut self, duplicate_patterns: DuplicatePatterns) -> Self {
+        self.duplicate_patterns = duplicate_patterns;
+        self
+    }
+
+    /// Declares terminals as aliases of earlier terminals, given as pairs of the index of the
+    /// alias and the index of the terminal whose pattern it shares, e.g. for contextual keywords
+    /// that are identifiers in one scanner mode and keywords with their own token type in
+    /// another.
+    ///
+    /// The pattern of an alias is not compiled, so it can be empty. The alias shares the DFA of
+    /// the other terminal, and the scanner returns the token type the active scanner mode assigns
+    /// to it. Within one scanner mode the earlier terminal wins against its alias. Declared
+    /// aliases are listed in the [crate::CompilationReport] but are not subject to
+    /// [GenerateOptions::with_duplicate_patterns].
+    ///
+    /// The generation fails with [crate::ScanGenErrorKind::InvalidTerminalAlias] if a terminal is
+    /// not an alias of an earlier terminal.
+    pub fn with_terminal_aliases(mut self, terminal_aliases: &[(usize, usize)]) -> Self {
+        self.terminal_aliases = terminal_aliases.to_vec();
+        self
+    }
+
+    /// Marks terminals, given by their index in the patterns, as contextual keywords, e.g. `union`
+    /// in Rust or the many non-reserved keywords of SQL. They are matched as identifiers and the
+    /// parser reinterprets an identifier as keyword where the grammar expects it with
+    /// [crate::Scanner::reinterpret], so no scanner mode is needed for each context.
+    ///
+    /// The pattern of a contextual keyword must be a literal. In each scanner mode that contains
+    /// the keyword, the keyword is removed and the first other terminal of the mode that matches
+    /// its text, e.g. the identifier, returns it instead. The generated scanner knows which token
+    /// type the keyword has in which mode, see [crate::ContextualKeywordData].
+    ///
+    /// The generation fails with [crate::ScanGenErrorKind::InvalidContextualKeyword] if a terminal
+    /// doesn't exist, its pattern is no literal or no other terminal of a mode matches it.
+    pub fn with_contextual_keywords(mut self, contextual_keywords: &[usize]) -> Self {
+        self.contextual_keywords = contextual_keywords.to_vec();
+        self
+    }
+
+    /// Marks terminals, given by their index in the patterns, as case-insensitive keywords, e.g.
+    /// `select` in SQL. Instead of a DFA with a character class for each letter, the keywords are
+    /// looked up in a table: the scanner matches them as identifiers and returns the match with
+    /// the token type of the keyword if its text equals the keyword regardless of case. The text
+    /// of the match keeps its original case.
+    ///
+    /// The pattern of a case-insensitive keyword must be a literal. In each scanner mode that
+    /// contains the keyword, the keyword is removed and the first other terminal of the mode that
+    /// matches it in lowercase and uppercase recognizes it, see
+    /// [crate::CaseInsensitiveKeywordData].
+    ///
+    /// The generation fails with [crate::ScanGenErrorKind::InvalidCaseInsensitiveKeyword] if a
+    /// terminal doesn't exist, its pattern is no literal or no other terminal of a mode matches
+    /// it.
+    pub fn with_case_insensitive_keywords(mut self, case_insensitive_keywords: &[usize]) -> Self {
+        self.case_insensitive_keywords = case_insensitive_keywords.to_vec();
+        self
+    }
+
+    /// Sets the path of a manifest that is written along with the generated code, e.g. next to
+    /// the generated file. The manifest describes the generated scanner in JSON, so build systems
+    /// and other tools can use its metadata without parsing the generated Rust code.
+    ///
+    /// The JSON object has the following members:
+    /// * `format`: always `"scangen-manifest"`.
+    /// * `version`: the version of the schema, see [crate::MANIFEST_FORMAT_VERSION].
+    /// * `scangen_version`: the version of `scangen` that generated the scanner.
+    /// * `table_format_version`: the format of the generated tables, see
+    ///   [crate::TABLE_FORMAT_VERSION].
+    /// * `input_hash`: a hash of the patterns, the scanner modes and the options, except for the
+    ///   path of the manifest, e.g. `"fnv1a64:af63bd4c8601b7df"`. It only changes if the input
+    ///   of the generation changes.
+    /// * `tokens`: objects with the members `token_type`, `terminal` and `pattern` that map the
+    ///   token types to the indices and patterns of the terminals that produce them, in the order
+    ///   of the token types.
+    /// * `modes`: the scanner modes. Each mode is an object with the members
+    ///   * `id`: the index of the mode,
+    ///   * `name`: the name of the mode,
+    ///   * `terminals`: pairs of terminal index and token type in the order of their priority,
+    ///   * `transitions`: pairs of token type and the index of the mode it switches to,
+    ///   * `error_token_type`: the token type of unmatched input or `null`.
+    /// * `statistics`: an object with the number of `terminals`, DFA `states` and distinct
+    ///   `char_classes`, the size of the DFA tables in bytes `table_bytes` and the number of
+    ///   `match_arms` of the generated match functions.
+    pub fn with_manifest(mut self, path: impl AsRef<Path>) -> Self {
+        self.manifest = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Returns a copy of the options without the path of the manifest.
+    pub(crate) fn without_manifest(mut self) -> Self {
+        self.manifest = None;
+        self
+    }
+
+    /// Returns the code that is emitted at the top of the generated file.
+    pub fn prologue(&self) -> &str {
+        &self.prologue
+    }
+
+    /// Returns the code that is emitted at the bottom of the generated file.
+    pub fn epilogue(&self) -> &str {
+        &self.epilogue
+    }
+
+    /// Returns the path of the `scangen` crate the generated code imports from.
+    pub fn scangen_module_name(&self) -> &str {
+        self.scangen_module_name.as_deref().unwrap_or("scangen")
+    }
+
+    /// Returns the token frequency profile. It is empty if no profile was given.
+    pub fn token_profile(&self) -> &[(TokenType, u64)] {
+        &self.token_profile
+    }
+
+    /// Returns the path of the manifest that is written along with the generated code, if any.
+    pub fn manifest(&self) -> Option<&Path> {
+        self.manifest.as_deref()
+    }
+
+    /// Returns the budget for the size of the generated DFA tables in bytes, if any.
+    pub fn max_table_size(&self) -> Option<usize> {
+        self.max_table_size
+    }
+
+    /// Returns the name of the error token, if any.
+    pub fn error_token(&self) -> Option<&str> {
+        self.error_token.as_deref()
+    }
+
+    /// Returns the name of the newline token, if any.
+    pub fn auto_newline(&self) -> Option<&str> {
+        self.auto_newline.as_deref()
+    }
+
+    /// Returns the name of the whitespace token, if any.
+    pub fn auto_ws(&self) -> Option<&str> {
+        self.auto_ws.as_deref()
+    }
+
+    /// Returns the names of the scanner modes without the whitespace token.
+    pub fn auto_ws_off(&self) -> &[String] {
+        &self.auto_ws_off
+    }
+
+    /// Returns how terminals with duplicate patterns are handled.
+    pub fn duplicate_patterns(&self) -> DuplicatePatterns {
+        self.duplicate_patterns
+    }
+
+    /// Returns the declared terminal aliases as pairs of the alias and the terminal it shares the
+    /// pattern with.
+    pub fn terminal_aliases(&self) -> &[(usize, usize)] {
+        &self.terminal_aliases
+    }
+
+    /// Returns the terminals that are contextual keywords.
+    pub fn contextual_keywords(&self) -> &[usize] {
+        &self.contextual_keywords
+    }
+
+    /// Returns the terminals that are case-insensitive keywords.
+    pub fn case_insensitive_keywords(&self) -> &[usize] {
+        &self.case_insensitive_keywords
+    }
+
+    /// Returns the terminals along with the features that enable them.
+    pub fn terminal_features(&self) -> &[(usize, String)] {
+        &self.terminal_features
+    }
+
+    /// Returns the observed frequency of the given token type in the profile.
+    pub(crate) fn token_frequency(&self, token_type: TokenType) -> u64 {
+        self.token_profile
+            .iter()
+            .filter(|(profiled, _)| *profiled == token_type)
+            .map(|(_, count)| *count)
+            .sum()
+    }
+
+    /// Returns true if the given entry point is generated.
+    pub fn generates(&self, entry_point: EntryPoint) -> bool {
+        self.entry_points.contains(&entry_point)
+    }
+}
+
+impl Default for GenerateOptions {
+    fn default() -> Self {
+        Self {
+            scangen_module_name: None,
+            entry_points: EntryPoint::DEFAULT.to_vec(),
+            prologue: String::new(),
+            epilogue: String::new(),
+            token_profile: Vec::new(),
+            max_table_size: None,
+            terminal_features: Vec::new(),
+            manifest: None,
+            error_token: None,
+            auto_newline: None,
+            auto_ws: None,
+            auto_ws_off: Vec::new(),
+            duplicate_patterns: DuplicatePatterns::default(),
+            terminal_aliases: Vec::new(),
+            contextual_keywords: Vec::new(),
+            case_insensitive_keywords: Vec::new(),
+        }
+    }
+}
@@ -0,0 +1,273 @@
+#![allow(clippy::manual_is_ascii_check)]
+
+use crate::prelude::{
+    DfaData, FindMatches, RunClassData, Scanner, ScannerDef, ScannerModeData, StartSetData,
+    TokenType,
+};
+
+const SHARED_0: &[(usize, usize)] = &[(0, 1), (1, 2)];
+
+const DFAS: &[DfaData] = &[
+    /* 0 */
+    ("\\s+", &[1], SHARED_0, &[(0, 1), (0, 1)]),
+    /* 1 */
+    ("fn", &[2], &[(0, 1), (1, 2), (0, 0)], &[(1, 1), (2, 2)]),
+    /* 2 */
+    ("[a-z]+", &[1], SHARED_0, &[(3, 1), (3, 1)]),
+    /* 3 */
+    ("[0-9]+", &[1], SHARED_0, &[(4, 1), (4, 1)]),
+    /* 4 */
+    (
+        "async",
+        &[5],
+        &[(0, 1), (1, 2), (2, 3), (3, 4), (4, 5), (0, 0)],
+        &[(5, 1), (6, 2), (7, 3), (2, 4), (8, 5)],
+    ),
+    /* 5 */
+    ("[0-9][0-9_]*", &[1], SHARED_0, &[(4, 1), (9, 1)]),
+];
+
+const MODES: &[ScannerModeData] = &[
+    /* 0 */
+    (
+        "2015",
+        &[
+            (0, TokenType::new(0)),
+            (1, TokenType::new(1)),
+            (2, TokenType::new(2)),
+            (3, TokenType::new(3)),
+        ],
+        &[],
+        None,
+    ),
+    /* 1 */
+    (
+        "2018",
+        &[
+            (0, TokenType::new(0)),
+            (1, TokenType::new(1)),
+            (4, TokenType::new(4)),
+            (2, TokenType::new(2)),
+            (3, TokenType::new(3)),
+        ],
+        &[],
+        None,
+    ),
+    /* 2 */
+    (
+        "2021",
+        &[
+            (0, TokenType::new(0)),
+            (4, TokenType::new(4)),
+            (2, TokenType::new(2)),
+            (5, TokenType::new(3)),
+        ],
+        &[],
+        None,
+    ),
+];
+
+const START_SETS: &[StartSetData] = &[
+    /* 0 */
+    (
+        0x07fffffe0000000003ff000100003e00,
+        &[
+            ('\u{85}', '\u{85}'),
+            ('\u{a0}', '\u{a0}'),
+            ('\u{1680}', '\u{1680}'),
+            ('\u{2000}', '\u{200a}'),
+            ('\u{2028}', '\u{2029}'),
+            ('\u{202f}', '\u{202f}'),
+            ('\u{205f}', '\u{205f}'),
+            ('\u{3000}', '\u{3000}'),
+        ],
+    ),
+    /* 1 */
+    (
+        0x07fffffe0000000003ff000100003e00,
+        &[
+            ('\u{85}', '\u{85}'),
+            ('\u{a0}', '\u{a0}'),
+            ('\u{1680}', '\u{1680}'),
+            ('\u{2000}', '\u{200a}'),
+            ('\u{2028}', '\u{2029}'),
+            ('\u{202f}', '\u{202f}'),
+            ('\u{205f}', '\u{205f}'),
+            ('\u{3000}', '\u{3000}'),
+        ],
+    ),
+    /* 2 */
+    (
+        0x07fffffe0000000003ff000100003e00,
+        &[
+            ('\u{85}', '\u{85}'),
+            ('\u{a0}', '\u{a0}'),
+            ('\u{1680}', '\u{1680}'),
+            ('\u{2000}', '\u{200a}'),
+            ('\u{2028}', '\u{2029}'),
+            ('\u{202f}', '\u{202f}'),
+            ('\u{205f}', '\u{205f}'),
+            ('\u{3000}', '\u{3000}'),
+        ],
+    ),
+];
+
+const RUN_CLASSES: &[RunClassData] = &[
+    (
+        0,
+        (
+            0x00000000000000000000000100003e00,
+            &[
+                ('\u{85}', '\u{85}'),
+                ('\u{a0}', '\u{a0}'),
+                ('\u{1680}', '\u{1680}'),
+                ('\u{2000}', '\u{200a}'),
+                ('\u{2028}', '\u{2029}'),
+                ('\u{202f}', '\u{202f}'),
+                ('\u{205f}', '\u{205f}'),
+                ('\u{3000}', '\u{3000}'),
+            ],
+        ),
+    ),
+    (3, (0x07fffffe000000000000000000000000, &[])),
+    (4, (0x000000000000000003ff000000000000, &[])),
+    (9, (0x000000008000000003ff000000000000, &[])),
+];
+
+fn matches_char_class(c: char, char_class: usize) -> bool {
+    match char_class {
+        /* [\t-\r \u{85}\u{a0}\u{1680}\u{2000}-\u{200a}\u{2028}-\u{2029}\u{202f}\u{205f}\u{3000}] */
+        0 => {
+            matches!(c, '\t'..='\r' | ' ' | '\u{85}' | '\u{a0}' | '\u{1680}' | '\u{2000}'..='\u{200a}' | '\u{2028}'..='\u{2029}' | '\u{202f}' | '\u{205f}' | '\u{3000}')
+        }
+        /* f */
+        1 => c == 'f',
+        /* n */
+        2 => c == 'n',
+        /* [a-z] */
+        3 => ('a'..='z').contains(&c),
+        /* [0-9] */
+        4 => ('0'..='9').contains(&c),
+        /* a */
+        5 => c == 'a',
+        /* s */
+        6 => c == 's',
+        /* y */
+        7 => c == 'y',
+        /* c */
+        8 => c == 'c',
+        /* [0-9_] */
+        9 => {
+            matches!(c, '0'..='9' | '_')
+        }
+        _ => false,
+    }
+}
+
+/* 2015 */
+fn matches_char_class_0(c: char, char_class: usize) -> bool {
+    match char_class {
+        /* [\t-\r \u{85}\u{a0}\u{1680}\u{2000}-\u{200a}\u{2028}-\u{2029}\u{202f}\u{205f}\u{3000}] */
+        0 => {
+            matches!(c, '\t'..='\r' | ' ' | '\u{85}' | '\u{a0}' | '\u{1680}' | '\u{2000}'..='\u{200a}' | '\u{2028}'..='\u{2029}' | '\u{202f}' | '\u{205f}' | '\u{3000}')
+        }
+        /* f */
+        1 => c == 'f',
+        /* n */
+        2 => c == 'n',
+        /* [a-z] */
+        3 => ('a'..='z').contains(&c),
+        /* [0-9] */
+        4 => ('0'..='9').contains(&c),
+        _ => false,
+    }
+}
+
+/* 2018 */
+fn matches_char_class_1(c: char, char_class: usize) -> bool {
+    match char_class {
+        /* [\t-\r \u{85}\u{a0}\u{1680}\u{2000}-\u{200a}\u{2028}-\u{2029}\u{202f}\u{205f}\u{3000}] */
+        0 => {
+            matches!(c, '\t'..='\r' | ' ' | '\u{85}' | '\u{a0}' | '\u{1680}' | '\u{2000}'..='\u{200a}' | '\u{2028}'..='\u{2029}' | '\u{202f}' | '\u{205f}' | '\u{3000}')
+        }
+        /* f */
+        1 => c == 'f',
+        /* n */
+        2 => c == 'n',
+        /* [a-z] */
+        3 => ('a'..='z').contains(&c),
+        /* [0-9] */
+        4 => ('0'..='9').contains(&c),
+        /* a */
+        5 => c == 'a',
+        /* s */
+        6 => c == 's',
+        /* y */
+        7 => c == 'y',
+        /* c */
+        8 => c == 'c',
+        _ => false,
+    }
+}
+
+/* 2021 */
+fn matches_char_class_2(c: char, char_class: usize) -> bool {
+    match char_class {
+        /* [\t-\r \u{85}\u{a0}\u{1680}\u{2000}-\u{200a}\u{2028}-\u{2029}\u{202f}\u{205f}\u{3000}] */
+        0 => {
+            matches!(c, '\t'..='\r' | ' ' | '\u{85}' | '\u{a0}' | '\u{1680}' | '\u{2000}'..='\u{200a}' | '\u{2028}'..='\u{2029}' | '\u{202f}' | '\u{205f}' | '\u{3000}')
+        }
+        /* n */
+        2 => c == 'n',
+        /* [a-z] */
+        3 => ('a'..='z').contains(&c),
+        /* [0-9] */
+        4 => ('0'..='9').contains(&c),
+        /* a */
+        5 => c == 'a',
+        /* s */
+        6 => c == 's',
+        /* y */
+        7 => c == 'y',
+        /* c */
+        8 => c == 'c',
+        /* [0-9_] */
+        9 => {
+            matches!(c, '0'..='9' | '_')
+        }
+        _ => false,
+    }
+}
+
+const MODE_MATCH_FUNCTIONS: &[fn(char, usize) -> bool] = &[
+    matches_char_class_0,
+    matches_char_class_1,
+    matches_char_class_2,
+];
+
+pub(crate) static SCANNER_DEF: ScannerDef = ScannerDef::new(DFAS, MODES)
+    .generated_with("0.1.0", 2)
+    .with_start_sets(START_SETS)
+    .with_run_classes(RUN_CLASSES)
+    .with_mode_match_functions(MODE_MATCH_FUNCTIONS);
+
+pub(crate) fn create_scanner() -> Scanner {
+    SCANNER_DEF.create_scanner()
+}
+
+pub(crate) fn create_find_iter<'h>(scanner: &Scanner, input: &'h str) -> FindMatches<'h> {
+    scanner.find_iter(input, matches_char_class)
+}
+
+/// The names of the dialects. The dialect at index `n` is scanned by the scanner mode `n`.
+pub(crate) const DIALECTS: &[&str] = &["2015", "2018", "2021"];
+
+/// The names of the terminals of all dialects, indexed by token type.
+pub(crate) const TOKEN_NAMES: &[&str] = &["Whitespace", "Fn", "Identifier", "Number", "Async"];
+
+/// Creates a scanner for the dialect at the given index of [DIALECTS].
+pub(crate) fn create_dialect_scanner(dialect: usize) -> Scanner {
+    let mut scanner = SCANNER_DEF.create_scanner();
+    scanner.set_mode(dialect.into());
+    scanner
+}
@@ -0,0 +1,104 @@
+#![allow(clippy::manual_is_ascii_check)]
+
+use crate::prelude::{
+    DfaData, FindMatches, RunClassData, Scanner, ScannerDef, ScannerModeData, ScannerModeId,
+    StartSetData, TokenType,
+};
+
+const SHARED_0: &[(usize, usize)] = &[(0, 1), (1, 2)];
+
+const DFAS: &[DfaData] = &[
+    /* 0 */
+    ("[a-z]+", &[1], SHARED_0, &[(0, 1), (0, 1)]),
+    /* 1 */
+    ("[0-9]+", &[1], SHARED_0, &[(1, 1), (1, 1)]),
+    /* 2 */
+    ("\\s+", &[1], SHARED_0, &[(2, 1), (2, 1)]),
+    /* 3 */
+    ("(?s:.)", &[1], &[(0, 1), (0, 0)], &[(3, 1)]),
+];
+
+const MODES: &[ScannerModeData] = &[/* 0 */ (
+    "INITIAL",
+    &[
+        (0, TokenType::new(0)),
+        (1, TokenType::new(1)),
+        (2, TokenType::new(2)),
+        (3, TokenType::new(3)),
+    ],
+    &[],
+    Some(TokenType::new(3)),
+)];
+
+const START_SETS: &[StartSetData] = &[/* 0 */ (
+    0xffffffffffffffffffffffffffffffff,
+    &[('\u{80}', '\u{10ffff}')],
+)];
+
+const RUN_CLASSES: &[RunClassData] = &[
+    (0, (0x07fffffe000000000000000000000000, &[])),
+    (1, (0x000000000000000003ff000000000000, &[])),
+    (
+        2,
+        (
+            0x00000000000000000000000100003e00,
+            &[
+                ('\u{85}', '\u{85}'),
+                ('\u{a0}', '\u{a0}'),
+                ('\u{1680}', '\u{1680}'),
+                ('\u{2000}', '\u{200a}'),
+                ('\u{2028}', '\u{2029}'),
+                ('\u{202f}', '\u{202f}'),
+                ('\u{205f}', '\u{205f}'),
+                ('\u{3000}', '\u{3000}'),
+            ],
+        ),
+    ),
+];
+
+fn matches_char_class(c: char, char_class: usize) -> bool {
+    match char_class {
+        /* [a-z] */
+        0 => ('a'..='z').contains(&c),
+        /* [0-9] */
+        1 => ('0'..='9').contains(&c),
+        /* [\t-\r \u{85}\u{a0}\u{1680}\u{2000}-\u{200a}\u{2028}-\u{2029}\u{202f}\u{205f}\u{3000}] */
+        2 => {
+            matches!(c, '\t'..='\r' | ' ' | '\u{85}' | '\u{a0}' | '\u{1680}' | '\u{2000}'..='\u{200a}' | '\u{2028}'..='\u{2029}' | '\u{202f}' | '\u{205f}' | '\u{3000}')
+        }
+        /* [^] */
+        3 => true,
+        _ => false,
+    }
+}
+
+pub(crate) static SCANNER_DEF: ScannerDef = ScannerDef::new(DFAS, MODES)
+    .generated_with("0.1.0", 2)
+    .with_start_sets(START_SETS)
+    .with_run_classes(RUN_CLASSES);
+
+pub(crate) const MODE_TOKEN_TYPES: &[&[TokenType]] = &[/* 0 INITIAL */ &[
+    TokenType::new(0),
+    TokenType::new(1),
+    TokenType::new(2),
+    TokenType::new(3),
+]];
+
+/// Returns the index of the given token type among the token types of the given scanner mode.
+pub(crate) fn mode_local_index(mode: ScannerModeId, token_type: TokenType) -> Option<usize> {
+    MODE_TOKEN_TYPES
+        .get(mode.as_usize())?
+        .binary_search(&token_type)
+        .ok()
+}
+
+pub(crate) fn create_scanner() -> Scanner {
+    SCANNER_DEF.create_scanner()
+}
+
+pub(crate) fn create_find_iter<'h>(scanner: &Scanner, input: &'h str) -> FindMatches<'h> {
+    scanner.find_iter(input, matches_char_class)
+}
+
+/// The token type of the error token, which covers the input no other terminal matches.
+pub(crate) const ERROR: TokenType = TokenType::new(3);
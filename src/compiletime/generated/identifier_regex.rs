@@ -0,0 +1,65 @@
+#![allow(clippy::manual_is_ascii_check)]
+
+/* Generated by scangen 0.1.0 for the regex "[a-zA-Z_][a-zA-Z0-9_]*" */
+
+const ACCEPTING_STATES: &[usize] = &[1];
+const STATE_RANGES: &[(usize, usize)] = &[(0, 1), (1, 2)];
+const TRANSITIONS: &[(usize, usize)] = &[(0, 1), (1, 1)];
+
+fn matches_char_class(c: char, char_class: usize) -> bool {
+    match char_class {
+        /* [A-Z_a-z] */
+        0 => {
+            const MASK: u128 = 0x07fffffe87fffffe0000000000000000;
+            c.is_ascii() && (MASK >> (c as u32)) & 1 == 1
+        }
+        /* [0-9A-Z_a-z] */
+        1 => {
+            const MASK: u128 = 0x07fffffe87fffffe03ff000000000000;
+            c.is_ascii() && (MASK >> (c as u32)) & 1 == 1
+        }
+        _ => false,
+    }
+}
+
+fn next_state(state: usize, c: char) -> Option<usize> {
+    let (start, end) = STATE_RANGES.get(state).copied()?;
+    TRANSITIONS
+        .get(start..end)?
+        .iter()
+        .find(|(char_class, _)| matches_char_class(c, *char_class))
+        .map(|(_, target_state)| *target_state)
+}
+
+/// Returns the length of the longest match that starts at the beginning of the input.
+fn longest_match_len(input: &str) -> Option<usize> {
+    let mut state = 0;
+    let mut len = ACCEPTING_STATES.contains(&state).then_some(0);
+    for (i, c) in input.char_indices() {
+        let Some(next) = next_state(state, c) else {
+            break;
+        };
+        state = next;
+        if ACCEPTING_STATES.contains(&state) {
+            len = Some(i + c.len_utf8());
+        }
+    }
+    len
+}
+
+/// Returns true if the regex matches somewhere in the input.
+pub(crate) fn is_match(input: &str) -> bool {
+    find(input).is_some()
+}
+
+/// Returns the byte range of the leftmost longest match of the regex in the input.
+pub(crate) fn find(input: &str) -> Option<std::ops::Range<usize>> {
+    input
+        .char_indices()
+        .map(|(i, _)| i)
+        .chain(std::iter::once(input.len()))
+        .find_map(|start| {
+            let len = longest_match_len(input.get(start..)?)?;
+            Some(start..start + len)
+        })
+}
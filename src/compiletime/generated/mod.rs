@@ -0,0 +1,8 @@
+#[cfg(all(test, feature = "runtime"))]
+pub(crate) mod dialect_scanner;
+#[cfg(all(test, feature = "runtime"))]
+pub(crate) mod error_token_scanner;
+#[cfg(test)]
+pub(crate) mod identifier_regex;
+#[cfg(all(test, feature = "runtime"))]
+pub(crate) mod newline_scanner;
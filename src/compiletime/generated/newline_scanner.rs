@@ -0,0 +1,98 @@
+#![allow(clippy::manual_is_ascii_check)]
+
+use crate::prelude::{
+    DfaData, FindMatches, RunClassData, Scanner, ScannerDef, ScannerModeData, StartSetData,
+    TokenType,
+};
+
+const SHARED_0: &[(usize, usize)] = &[(0, 1), (1, 2)];
+
+const DFAS: &[DfaData] = &[
+    /* 0 */
+    ("[a-z]+", &[1], SHARED_0, &[(0, 1), (0, 1)]),
+    /* 1 */
+    ("[\\s--\\r\\n]+", &[1], SHARED_0, &[(1, 1), (1, 1)]),
+    /* 2 */
+    (
+        "\\r\\n|\\r|\\n",
+        &[1, 2],
+        &[(0, 2), (0, 0), (2, 3)],
+        &[(2, 2), (3, 1), (3, 1)],
+    ),
+    /* 3 */
+    ("(?s:.)", &[1], &[(0, 1), (0, 0)], &[(4, 1)]),
+];
+
+const MODES: &[ScannerModeData] = &[/* 0 */ (
+    "INITIAL",
+    &[
+        (2, TokenType::new(2)),
+        (0, TokenType::new(0)),
+        (1, TokenType::new(1)),
+        (3, TokenType::new(3)),
+    ],
+    &[],
+    Some(TokenType::new(3)),
+)];
+
+const START_SETS: &[StartSetData] = &[/* 0 */ (
+    0xffffffffffffffffffffffffffffffff,
+    &[('\u{80}', '\u{10ffff}')],
+)];
+
+const RUN_CLASSES: &[RunClassData] = &[
+    (0, (0x07fffffe000000000000000000000000, &[])),
+    (
+        1,
+        (
+            0x00000000000000000000000100001a00,
+            &[
+                ('\u{85}', '\u{85}'),
+                ('\u{a0}', '\u{a0}'),
+                ('\u{1680}', '\u{1680}'),
+                ('\u{2000}', '\u{200a}'),
+                ('\u{2028}', '\u{2029}'),
+                ('\u{202f}', '\u{202f}'),
+                ('\u{205f}', '\u{205f}'),
+                ('\u{3000}', '\u{3000}'),
+            ],
+        ),
+    ),
+];
+
+fn matches_char_class(c: char, char_class: usize) -> bool {
+    match char_class {
+        /* [a-z] */
+        0 => ('a'..='z').contains(&c),
+        /* [\t\u{b}-\u{c} \u{85}\u{a0}\u{1680}\u{2000}-\u{200a}\u{2028}-\u{2029}\u{202f}\u{205f}\u{3000}] */
+        1 => {
+            matches!(c, '\t' | '\u{b}'..='\u{c}' | ' ' | '\u{85}' | '\u{a0}' | '\u{1680}' | '\u{2000}'..='\u{200a}' | '\u{2028}'..='\u{2029}' | '\u{202f}' | '\u{205f}' | '\u{3000}')
+        }
+        /* \r */
+        2 => c == '\r',
+        /* \n */
+        3 => c == '\n',
+        /* [^] */
+        4 => true,
+        _ => false,
+    }
+}
+
+pub(crate) static SCANNER_DEF: ScannerDef = ScannerDef::new(DFAS, MODES)
+    .generated_with("0.1.0", 2)
+    .with_start_sets(START_SETS)
+    .with_run_classes(RUN_CLASSES)
+    .with_newline_token_type(TokenType::new(2));
+
+pub(crate) fn create_scanner() -> Scanner {
+    SCANNER_DEF.create_scanner()
+}
+
+pub(crate) fn create_find_iter<'h>(scanner: &Scanner, input: &'h str) -> FindMatches<'h> {
+    scanner.find_iter(input, matches_char_class)
+}
+
+/// The token type of the newline token, which matches the line breaks.
+pub(crate) const NEWLINE: TokenType = TokenType::new(2);
+/// The token type of the error token, which covers the input no other terminal matches.
+pub(crate) const ERROR: TokenType = TokenType::new(3);
@@ -1,9 +1,11 @@
 //! This module contains the source generator for the regex syntax.
 //! The source generator is used to generate code from the regex syntax.
 
-use crate::{compiletime::MultiPatternDfa, Result, ScannerModeData};
-use log::trace;
-use std::time::Instant;
+use crate::{
+    compiletime::{CompilationReport, GenerateOptions, MultiPatternDfa},
+    Result, ScannerModeData,
+};
+use tracing::info_span;
 
 /// Generate code from the regex syntax.
 /// The function returns an error if the regex syntax is invalid.
@@ -21,25 +23,182 @@ pub fn generate_code(
     scangen_module_name: Option<&str>,
     output: &mut dyn std::io::Write,
 ) -> Result<()> {
-    let now = Instant::now();
+    generate_code_with_options(
+        pattern,
+        scanner_mode_data,
+        &options_with_module_name(scangen_module_name),
+        output,
+    )
+}
+
+/// Generate code from the regex syntax with the given [GenerateOptions].
+/// The options select, among others, the entry points of the generated module.
+///
+/// # Errors
+/// An error is returned if the regex contains unsupported syntax.
+pub fn generate_code_with_options(
+    pattern: &[&str],
+    scanner_mode_data: &[ScannerModeData],
+    options: &GenerateOptions,
+    output: &mut dyn std::io::Write,
+) -> Result<()> {
+    let _span = info_span!(
+        "generate_code",
+        patterns = pattern.len(),
+        modes = scanner_mode_data.len()
+    )
+    .entered();
+
+    let mut multi_pattern_dfa = MultiPatternDfa::new();
+    multi_pattern_dfa.add_terminals(pattern, options.terminal_aliases())?;
+    let scanner_mode_data =
+        multi_pattern_dfa.resolve_aliases(scanner_mode_data, options.duplicate_patterns())?;
+    let scanner_mode_data = if options.contextual_keywords().is_empty() {
+        scanner_mode_data
+    } else {
+        multi_pattern_dfa
+            .add_contextual_keywords(options.contextual_keywords(), &scanner_mode_data)?
+    };
+    let scanner_mode_data = if options.case_insensitive_keywords().is_empty() {
+        scanner_mode_data
+    } else {
+        multi_pattern_dfa.add_case_insensitive_keywords(
+            options.case_insensitive_keywords(),
+            &scanner_mode_data,
+        )?
+    };
+
+    if options.auto_newline().is_none()
+        && options.auto_ws().is_none()
+        && options.error_token().is_none()
+    {
+        return multi_pattern_dfa.generate_code(&scanner_mode_data, options, output);
+    }
+    let mut scanner_mode_data = scanner_mode_data;
+    let mut epilogue = String::new();
+    if let Some(name) = options.auto_newline() {
+        let token_type;
+        (scanner_mode_data, token_type) =
+            multi_pattern_dfa.add_newline_token(name, &scanner_mode_data)?;
+        epilogue.push_str(&format!(
+            r"/// The token type of the newline token, which matches the line breaks.
+pub(crate) const {}: TokenType = TokenType::new({});
+",
+            name,
+            token_type.as_usize()
+        ));
+    }
+    if let Some(name) = options.auto_ws() {
+        let token_type;
+        (scanner_mode_data, token_type) = multi_pattern_dfa.add_whitespace_token(
+            name,
+            options.auto_newline().is_some(),
+            options.auto_ws_off(),
+            &scanner_mode_data,
+        )?;
+        epilogue.push_str(&format!(
+            r"/// The token type of the whitespace token, whose matches the scanner skips.
+pub(crate) const {}: TokenType = TokenType::new({});
+",
+            name,
+            token_type.as_usize()
+        ));
+    }
+    if let Some(name) = options.error_token() {
+        let token_type;
+        (scanner_mode_data, token_type) =
+            multi_pattern_dfa.add_error_token(name, &scanner_mode_data)?;
+        epilogue.push_str(&format!(
+            r"/// The token type of the error token, which covers the input no other terminal matches.
+pub(crate) const {}: TokenType = TokenType::new({});
+",
+            name,
+            token_type.as_usize()
+        ));
+    }
+    if !options.epilogue().is_empty() {
+        epilogue.push('\n');
+        epilogue.push_str(options.epilogue());
+    }
+    multi_pattern_dfa.generate_code(
+        &scanner_mode_data,
+        &options.clone().with_epilogue(&epilogue),
+        output,
+    )
+}
+
+/// Generate code from the regex syntax with the given [GenerateOptions] like
+/// [generate_code_with_options] and return it as string, e.g. to post-process, compare or embed
+/// the code without a temporary file. The code can be formatted with
+/// [crate::try_format_code].
+///
+/// # Errors
+/// An error is returned if the regex contains unsupported syntax.
+pub fn generate_code_to_string(
+    pattern: &[&str],
+    scanner_mode_data: &[ScannerModeData],
+    options: &GenerateOptions,
+) -> Result<String> {
+    let mut output = Vec::new();
+    generate_code_with_options(pattern, scanner_mode_data, options, &mut output)?;
+    // The code is assembled from strings, so it is valid UTF-8.
+    String::from_utf8(output)
+        .map_err(|error| std::io::Error::new(std::io::ErrorKind::InvalidData, error).into())
+}
+
+/// Generate code from the regex syntax and create a [CompilationReport].
+/// The arguments are the same as for [generate_code].
+///
+/// In addition to the code generation, the terminals are analyzed for conflicts, i.e. pairs of
+/// terminals that accept the same string. The report contains an example string for each
+/// conflict. Furthermore literal terminals, like keywords, that are also matched by another
+/// terminal of the same scanner mode, like the identifier, are reported along with the terminal
+/// that wins. Scanner modes that have neither a catch-all terminal nor an error token type are
+/// reported as well, and so are terminals whose DFAs have unusually many character classes or
+/// states for the length of their patterns and the size of the generated tables.
+///
+/// # Errors
+/// An error is returned if the regex contains unsupported syntax.
+pub fn generate_code_with_report(
+    pattern: &[&str],
+    scanner_mode_data: &[ScannerModeData],
+    scangen_module_name: Option<&str>,
+    output: &mut dyn std::io::Write,
+) -> Result<CompilationReport> {
+    let _span = info_span!(
+        "generate_code_with_report",
+        patterns = pattern.len(),
+        modes = scanner_mode_data.len()
+    )
+    .entered();
 
     let mut multi_pattern_dfa = MultiPatternDfa::new();
     multi_pattern_dfa.add_patterns(pattern)?;
+    let options = options_with_module_name(scangen_module_name);
+    let scanner_mode_data =
+        multi_pattern_dfa.resolve_aliases(scanner_mode_data, options.duplicate_patterns())?;
 
-    multi_pattern_dfa.generate_code(scanner_mode_data, scangen_module_name, output)?;
+    multi_pattern_dfa.generate_code(&scanner_mode_data, &options, output)?;
 
-    let elapsed_time = now.elapsed();
-    trace!(
-        "Code generation took {} milliseconds.",
-        elapsed_time.as_millis()
-    );
-    Ok(())
+    Ok(multi_pattern_dfa.report(&scanner_mode_data))
+}
+
+/// Creates the default options with the given name of the `scangen` module.
+fn options_with_module_name(scangen_module_name: Option<&str>) -> GenerateOptions {
+    match scangen_module_name {
+        Some(name) => GenerateOptions::new().with_scangen_module_name(name),
+        None => GenerateOptions::new(),
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::compiletime::rust_code_formatter::try_format;
+    use crate::compiletime::{
+        rust_code_formatter::{try_format, try_format_code},
+        DuplicatePatterns, EntryPoint,
+    };
+    use crate::{ScanGenErrorKind, ScannerModeId, TokenType};
     use regex::Regex;
     use std::fs;
 
@@ -113,4 +272,449 @@ mod tests {
             "generation result mismatch!"
         );
     }
+
+    #[test]
+    fn test_generate_code_to_string() {
+        let code = generate_code_to_string(TERMINALS, &[], &GenerateOptions::new()).unwrap();
+        let mut output = Vec::new();
+        generate_code(TERMINALS, &[], None, &mut output).unwrap();
+        assert_eq!(code.as_bytes(), output);
+
+        // The code formatted in memory equals the formatted file
+        let formatted_code = try_format_code(&code).unwrap();
+        let expected_generated_code =
+            fs::read_to_string("data/expected/test_generate_code.rs").unwrap();
+        let rx_newline: Regex = Regex::new(r"\r?\n|\r").unwrap();
+        assert_eq!(
+            rx_newline.replace_all(&expected_generated_code, "\n"),
+            rx_newline.replace_all(&formatted_code, "\n"),
+        );
+
+        // Code that rustfmt can't parse is returned unchanged
+        assert_eq!(try_format_code("fn (").unwrap(), "fn (");
+    }
+
+    #[test]
+    fn test_generate_code_with_entry_points() {
+        let mut output = Vec::new();
+        let options = GenerateOptions::new()
+            .with_scangen_module_name("crate")
+            .with_entry_points(&[EntryPoint::Tokens, EntryPoint::Tokenize]);
+        generate_code_with_options(&["a", "b"], &[], &options, &mut output).unwrap();
+        let code = String::from_utf8(output).unwrap();
+        assert!(code.contains(
+            "use crate::prelude::{DfaData, Match, RunClassData, ScannerDef, ScannerModeData, StartSetData, \
+             TokenIter};"
+        ));
+        assert!(code.contains("pub(crate) fn tokens(input: &str)"));
+        assert!(code.contains("pub(crate) fn tokenize(input: &str) -> Vec<Match>"));
+        assert!(code.contains("pub(crate) fn tokenize_into(input: &str, matches: &mut Vec<Match>)"));
+        assert!(!code.contains("fn create_scanner()"));
+        assert!(!code.contains("fn create_find_iter"));
+        assert!(!code.contains("fn scanner()"));
+        // The match function is only used by the generated entry points
+        assert!(code.contains("\nfn matches_char_class("));
+    }
+
+    #[test]
+    fn test_generate_code_without_search_entry_points() {
+        let mut output = Vec::new();
+        let options = GenerateOptions::new().with_entry_points(&[EntryPoint::CreateScanner]);
+        generate_code_with_options(&["a"], &[], &options, &mut output).unwrap();
+        let code = String::from_utf8(output).unwrap();
+        assert!(code.contains("use scangen::prelude::{DfaData, RunClassData, Scanner, ScannerDef,"));
+        assert!(code.contains("pub(crate) fn matches_char_class("));
+    }
+
+    #[test]
+    fn test_generate_code_with_max_table_size() {
+        let patterns = ["a", r"\p{L}+"];
+        let mut output = Vec::new();
+        let options = GenerateOptions::new().with_max_table_size(100);
+        let error = generate_code_with_options(&patterns, &[], &options, &mut output).unwrap_err();
+        assert!(output.is_empty());
+        match *error.source {
+            ScanGenErrorKind::TableSizeExceeded {
+                size,
+                budget,
+                ref largest,
+            } => {
+                assert!(size > budget);
+                assert_eq!(budget, 100);
+                assert_eq!(largest[0].0, r"\p{L}+");
+            }
+            ref kind => panic!("Unexpected error: {}", kind),
+        }
+        assert!(error
+            .to_string()
+            .contains(r#"The patterns that contribute most are: "\\p{L}+""#));
+
+        let options = GenerateOptions::new().with_max_table_size(10_000);
+        generate_code_with_options(&patterns, &[], &options, &mut output).unwrap();
+    }
+
+    #[test]
+    fn test_generate_code_with_prologue_and_epilogue() {
+        let mut output = Vec::new();
+        let options = GenerateOptions::new()
+            .with_prologue("#![allow(dead_code)]\ntype Alias = usize;")
+            .with_epilogue("// End of generated code");
+        generate_code_with_options(&["a"], &[], &options, &mut output).unwrap();
+        let code = String::from_utf8(output).unwrap();
+        assert!(code.starts_with(
+            "#![allow(clippy::manual_is_ascii_check)]\n#![allow(dead_code)]\ntype Alias = usize;\n"
+        ));
+        assert!(code.trim_end().ends_with("// End of generated code"));
+    }
+
+    #[test]
+    fn test_generate_code_with_terminal_features() {
+        let patterns = ["a", "async", "await", "[a-z]+"];
+        let mut output = Vec::new();
+        let options = GenerateOptions::new().with_terminal_features(&[
+            (1, "edition2018"),
+            (2, "edition2018"),
+            (2, "edition2021"),
+            (2, "edition2018"),
+        ]);
+        generate_code_with_options(&patterns, &[], &options, &mut output).unwrap();
+        let code = String::from_utf8(output).unwrap();
+        assert!(code.contains(
+            "const DISABLED_DFAS: &[usize] = &[\n    #[cfg(not(feature = \"edition2018\"))]\n    1,\n    \
+            #[cfg(not(any(feature = \"edition2018\", feature = \"edition2021\")))]\n    2,\n];"
+        ));
+        assert!(code.contains(".with_disabled_dfas(DISABLED_DFAS);"));
+
+        let options = GenerateOptions::new().with_terminal_features(&[(4, "edition2018")]);
+        let error =
+            generate_code_with_options(&patterns, &[], &options, &mut Vec::new()).unwrap_err();
+        assert!(matches!(
+            *error.source,
+            ScanGenErrorKind::UnknownTerminal(4, 4)
+        ));
+    }
+
+    #[test]
+    fn generate_code_for_error_token_scanner() {
+        // The generated module is compiled and used by the test below.
+        let file_name = "src/compiletime/generated/error_token_scanner.rs";
+        {
+            let mut out_file = fs::File::create(file_name).unwrap();
+            let options = GenerateOptions::new()
+                .with_scangen_module_name("crate")
+                .with_entry_points(&[
+                    EntryPoint::CreateScanner,
+                    EntryPoint::CreateFindIter,
+                    EntryPoint::ModeTokenMap,
+                ])
+                .with_error_token("ERROR");
+            generate_code_with_options(&["[a-z]+", "[0-9]+", r"\s+"], &[], &options, &mut out_file)
+                .unwrap();
+        }
+        try_format(file_name).unwrap();
+    }
+
+    // The generated scanner needs the runtime.
+    #[cfg(feature = "runtime")]
+    #[test]
+    fn test_error_token() {
+        use crate::compiletime::generated::error_token_scanner;
+
+        assert_eq!(error_token_scanner::ERROR, TokenType::new(3));
+        let scanner = error_token_scanner::create_scanner();
+        assert_eq!(scanner.error_token_type(), Some(error_token_scanner::ERROR));
+        let token_types = error_token_scanner::create_find_iter(&scanner, "ab ?1\n!")
+            .map(|m| m.token_type().as_usize())
+            .collect::<Vec<_>>();
+        assert_eq!(token_types, [0, 2, 3, 1, 2, 3]);
+
+        // The generated mode token map agrees with the scanner
+        let initial = ScannerModeId::new(0);
+        let mode = scanner.modes().next().unwrap();
+        assert_eq!(
+            error_token_scanner::MODE_TOKEN_TYPES,
+            [mode.local_token_types()]
+        );
+        assert_eq!(
+            error_token_scanner::mode_local_index(initial, error_token_scanner::ERROR),
+            Some(3)
+        );
+        assert_eq!(
+            error_token_scanner::mode_local_index(initial, TokenType::new(4)),
+            None
+        );
+        assert_eq!(
+            error_token_scanner::mode_local_index(ScannerModeId::new(1), TokenType::new(0)),
+            None
+        );
+    }
+
+    #[test]
+    fn generate_code_for_newline_scanner() {
+        // The generated module is compiled and used by the test below.
+        let file_name = "src/compiletime/generated/newline_scanner.rs";
+        {
+            let mut out_file = fs::File::create(file_name).unwrap();
+            let options = GenerateOptions::new()
+                .with_scangen_module_name("crate")
+                .with_entry_points(&[EntryPoint::CreateScanner, EntryPoint::CreateFindIter])
+                .with_auto_newline("NEWLINE")
+                .with_error_token("ERROR");
+            generate_code_with_options(&["[a-z]+", r"[\s--\r\n]+"], &[], &options, &mut out_file)
+                .unwrap();
+        }
+        try_format(file_name).unwrap();
+    }
+
+    // The generated scanner needs the runtime.
+    #[cfg(feature = "runtime")]
+    #[test]
+    fn test_auto_newline() {
+        use crate::compiletime::generated::newline_scanner;
+        use crate::{ColumnConfig, Position};
+
+        assert_eq!(newline_scanner::NEWLINE, TokenType::new(2));
+        assert_eq!(newline_scanner::ERROR, TokenType::new(3));
+        let scanner = newline_scanner::create_scanner();
+        assert_eq!(scanner.newline_token_type(), Some(newline_scanner::NEWLINE));
+        let matches = newline_scanner::create_find_iter(&scanner, "ab\r\n c\r!\n")
+            .with_positions(ColumnConfig::new())
+            .map(|(m, start, _)| (m.token_type().as_usize(), start))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            matches,
+            [
+                (0, Position::new(1, 1)),
+                (2, Position::new(1, 3)),
+                (1, Position::new(2, 1)),
+                (0, Position::new(2, 2)),
+                (2, Position::new(2, 3)),
+                (3, Position::new(3, 1)),
+                (2, Position::new(3, 2)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_auto_newline_with_scanner_modes() {
+        const MODES: &[ScannerModeData] = &[
+            (
+                "INITIAL",
+                &[(0, TokenType::new(1)), (1, TokenType::new(2))],
+                &[(TokenType::new(2), ScannerModeId::new(1))],
+                None,
+            ),
+            (
+                "STRING",
+                &[(2, TokenType::new(3)), (1, TokenType::new(2))],
+                &[(TokenType::new(2), ScannerModeId::new(0))],
+                None,
+            ),
+        ];
+        let options = GenerateOptions::new().with_auto_newline("Newline");
+        let code = generate_code_to_string(&["[a-z]+", "\"", "[^\"]+"], MODES, &options).unwrap();
+        // The newline terminal is prepended to each mode
+        assert!(code.contains(
+            "    (\"INITIAL\", &[\n        (3, TokenType::new(4)),\n        (0, TokenType::new(1)),"
+        ));
+        assert!(code.contains(
+            "    (\"STRING\", &[\n        (3, TokenType::new(4)),\n        (2, TokenType::new(3)),"
+        ));
+        assert!(code.contains(".with_newline_token_type(TokenType::new(4));"));
+        assert!(code.contains("pub(crate) const Newline: TokenType = TokenType::new(4);"));
+
+        // A terminal with the same pattern is no newline token without the option
+        let code = generate_code_to_string(&[r"\r\n|\r|\n"], &[], &GenerateOptions::new()).unwrap();
+        assert!(!code.contains("with_newline_token_type"));
+    }
+
+    #[test]
+    fn test_auto_ws() {
+        const MODES: &[ScannerModeData] = &[
+            (
+                "INITIAL",
+                &[(0, TokenType::new(0)), (1, TokenType::new(1))],
+                &[(TokenType::new(1), ScannerModeId::new(1))],
+                None,
+            ),
+            (
+                "STRING",
+                &[(2, TokenType::new(2)), (1, TokenType::new(1))],
+                &[(TokenType::new(1), ScannerModeId::new(0))],
+                None,
+            ),
+        ];
+        let patterns = &["[a-z]+", "\"", "[^\"]+"];
+        let options = GenerateOptions::new()
+            .with_auto_ws("Whitespace")
+            .with_auto_ws_off(&["STRING"]);
+        let code = generate_code_to_string(patterns, MODES, &options).unwrap();
+        // The whitespace terminal is appended to the modes with automatic whitespace only
+        assert!(code.contains(r#"("\\s+", "#));
+        assert!(code.contains(
+            "        (1, TokenType::new(1)),\n        (3, TokenType::new(3)),\n    ], &[\n        \
+             (TokenType::new(1), ScannerModeId::new(1)),"
+        ));
+        assert!(code.contains(
+            "        (1, TokenType::new(1)),\n    ], &[\n        \
+             (TokenType::new(1), ScannerModeId::new(0)),"
+        ));
+        assert!(code.contains(".with_skipped_token_types(&[TokenType::new(3)]);"));
+        assert!(code.contains("pub(crate) const Whitespace: TokenType = TokenType::new(3);"));
+
+        // Along with the newline token the whitespace terminal excludes the line breaks
+        let options = GenerateOptions::new()
+            .with_auto_newline("NEWLINE")
+            .with_auto_ws("WHITESPACE");
+        let code = generate_code_to_string(patterns, &[], &options).unwrap();
+        assert!(code.contains(r#"("[\\s--\\r\\n]+", "#));
+        assert!(code.contains("pub(crate) const NEWLINE: TokenType = TokenType::new(3);"));
+        assert!(code.contains("pub(crate) const WHITESPACE: TokenType = TokenType::new(4);"));
+
+        let options = GenerateOptions::new()
+            .with_auto_ws("WHITESPACE")
+            .with_auto_ws_off(&["COMMENT"]);
+        let error = generate_code_to_string(patterns, MODES, &options).unwrap_err();
+        assert!(matches!(
+            *error.source,
+            ScanGenErrorKind::UnknownScannerMode(ref name) if name == "COMMENT"
+        ));
+    }
+
+    #[test]
+    fn test_terminal_aliases() {
+        // The identifier is a property name after a dot
+        const MODES: &[ScannerModeData] = &[
+            (
+                "INITIAL",
+                &[(0, TokenType::new(0)), (1, TokenType::new(1))],
+                &[(TokenType::new(1), ScannerModeId::new(1))],
+                None,
+            ),
+            (
+                "PROPERTY",
+                &[(2, TokenType::new(2))],
+                &[(TokenType::new(2), ScannerModeId::new(0))],
+                None,
+            ),
+        ];
+        // Declared aliases are no duplicates
+        let options = GenerateOptions::new()
+            .with_terminal_aliases(&[(2, 0)])
+            .with_duplicate_patterns(DuplicatePatterns::Error);
+        let code = generate_code_to_string(&["[a-z]+", r"\.", ""], MODES, &options).unwrap();
+        // The alias shares the DFA of the identifier and has its own token type
+        assert!(!code.contains("/* 2 */"));
+        assert!(code.contains("\"PROPERTY\", &[\n        (0, TokenType::new(2)),\n    ]"));
+
+        let options = GenerateOptions::new().with_terminal_aliases(&[(0, 2)]);
+        let error = generate_code_to_string(&["", r"\.", "[a-z]+"], MODES, &options).unwrap_err();
+        assert!(matches!(
+            *error.source,
+            ScanGenErrorKind::InvalidTerminalAlias {
+                alias: 0,
+                target: 2
+            }
+        ));
+    }
+
+    #[test]
+    fn test_contextual_keywords() {
+        // `union` is a keyword in the first mode only, the second mode matches it as identifier
+        const MODES: &[ScannerModeData] = &[
+            (
+                "INITIAL",
+                &[(0, TokenType::new(0)), (1, TokenType::new(1))],
+                &[],
+                None,
+            ),
+            ("ATTRIBUTE", &[(1, TokenType::new(1))], &[], None),
+        ];
+        let options = GenerateOptions::new().with_contextual_keywords(&[0]);
+        let code = generate_code_to_string(&["union", "[a-z]+"], MODES, &options).unwrap();
+        assert!(code.contains("ContextualKeywordData, DfaData,"));
+        assert!(code.contains("\"INITIAL\", &[\n        (1, TokenType::new(1)),\n    ]"));
+        assert!(code.contains(
+            "const CONTEXTUAL_KEYWORDS: &[ContextualKeywordData] = &[\n    \
+             (0, TokenType::new(1), \"union\", TokenType::new(0)),\n];"
+        ));
+        assert!(code.contains(".with_contextual_keywords(CONTEXTUAL_KEYWORDS);"));
+
+        // The keyword needs a literal and another terminal that matches it
+        let error = generate_code_to_string(&["unions?", "[a-z]+"], MODES, &options).unwrap_err();
+        assert!(matches!(
+            *error.source,
+            ScanGenErrorKind::InvalidContextualKeyword { terminal: 0, .. }
+        ));
+        let error = generate_code_to_string(&["union", "[0-9]+"], MODES, &options).unwrap_err();
+        assert!(matches!(
+            *error.source,
+            ScanGenErrorKind::InvalidContextualKeyword { terminal: 0, .. }
+        ));
+    }
+
+    #[test]
+    fn test_case_insensitive_keywords() {
+        let options = GenerateOptions::new()
+            .with_case_insensitive_keywords(&[0, 1])
+            .with_entry_points(&[EntryPoint::ModeTokenMap]);
+        let code =
+            generate_code_to_string(&["SELECT", "from", "[a-zA-Z]+"], &[], &options).unwrap();
+        assert!(code.contains("CaseInsensitiveKeywordData, DfaData,"));
+        // Only the identifier remains in the mode and the keywords are looked up by lowercase text
+        assert!(code.contains("\"INITIAL\", &[\n        (2, TokenType::new(2)),\n    ]"));
+        assert!(code.contains(
+            "const CASE_INSENSITIVE_KEYWORDS: &[CaseInsensitiveKeywordData] = &[\n    \
+             (0, TokenType::new(2), \"select\", TokenType::new(0)),\n    \
+             (0, TokenType::new(2), \"from\", TokenType::new(1)),\n];"
+        ));
+        assert!(code.contains(".with_case_insensitive_keywords(CASE_INSENSITIVE_KEYWORDS);"));
+        // The mode still returns the token types of the keywords
+        assert!(code.contains("/* 0 INITIAL */ &[\n        TokenType::new(0),"));
+
+        // The identifier must match the keyword in any case
+        let options = GenerateOptions::new().with_case_insensitive_keywords(&[0]);
+        let error = generate_code_to_string(&["select", "[a-z]+"], &[], &options).unwrap_err();
+        assert!(matches!(
+            *error.source,
+            ScanGenErrorKind::InvalidCaseInsensitiveKeyword { terminal: 0, .. }
+        ));
+    }
+
+    #[test]
+    fn test_error_token_with_scanner_modes() {
+        const MODES: &[ScannerModeData] = &[
+            (
+                "INITIAL",
+                &[(0, TokenType::new(0)), (1, TokenType::new(1))],
+                &[(TokenType::new(1), ScannerModeId::new(1))],
+                None,
+            ),
+            (
+                "STRING",
+                &[(2, TokenType::new(2)), (1, TokenType::new(1))],
+                &[(TokenType::new(1), ScannerModeId::new(0))],
+                Some(TokenType::new(4)),
+            ),
+        ];
+        let options = GenerateOptions::new().with_error_token("Error");
+        let code = generate_code_to_string(&["[a-z]+", "\"", "[^\"]+"], MODES, &options).unwrap();
+        // The catch-all terminal is appended to each mode and replaces its error token type
+        assert!(code.contains(
+            "        (1, TokenType::new(1)),\n        (3, TokenType::new(5)),\n    ], &[\n        \
+             (TokenType::new(1), ScannerModeId::new(1)),\n    ], Some(TokenType::new(5))),"
+        ));
+        assert!(code.contains(
+            "        (1, TokenType::new(1)),\n        (3, TokenType::new(5)),\n    ], &[\n        \
+             (TokenType::new(1), ScannerModeId::new(0)),\n    ], Some(TokenType::new(5))),"
+        ));
+        assert!(code.contains("pub(crate) const Error: TokenType = TokenType::new(5);"));
+
+        let options = GenerateOptions::new().with_error_token("1st");
+        let error = generate_code_to_string(&["a"], &[], &options).unwrap_err();
+        assert!(matches!(
+            *error.source,
+            ScanGenErrorKind::InvalidTokenName(ref name) if name == "1st"
+        ));
+    }
 }
@@ -0,0 +1,435 @@
+//! This module contains a TryFrom implementation for converting the HIR to an NFA.
+
+use regex_syntax::hir::{Class, ClassUnicode, Hir, HirKind, Repetition};
+
+use crate::compiletime::{
+    character_class::single_char_class,
+    nfa::{Nfa, RepetitionCounter},
+    Result, ScanGenError,
+};
+
+/// The maximum number of NFA states a single bounded repetition may expand to.
+/// Bounded repetitions are expanded by duplicating the repeated NFA, so large bounds, especially
+/// nested ones, can explode the number of states.
+pub(crate) const MAX_REPETITION_STATES: usize = 10_000;
+
+/// The largest bound of a repetition of a single character class that is expanded. Repetitions
+/// with larger bounds, e.g. `\d{1,100}`, are matched by counting the repeated characters if the
+/// NFA is created with counted repetitions.
+pub(crate) const MAX_UNROLLED_REPETITION: u32 = 16;
+
+macro_rules! unsupported {
+    ($feature:expr) => {
+        ScanGenError::new($crate::ScanGenErrorKind::UnsupportedFeature(
+            $feature.to_string(),
+        ))
+    };
+}
+
+impl TryFrom<Hir> for Nfa {
+    type Error = ScanGenError;
+
+    fn try_from(hir: Hir) -> Result<Self> {
+        Nfa::try_from_hir(hir, false)
+    }
+}
+
+impl Nfa {
+    /// Converts the HIR to an NFA.
+    /// If `count_repetitions` is set, greedy repetitions of a single character class with a bound
+    /// above [MAX_UNROLLED_REPETITION] become counted repetitions, see
+    /// [Nfa::counted_repetition]. Otherwise all bounded repetitions are expanded.
+    pub(crate) fn try_from_hir(hir: Hir, count_repetitions: bool) -> Result<Self> {
+        let mut nfa = Nfa::new();
+        match hir.into_kind() {
+            HirKind::Empty => Ok(nfa),
+            HirKind::Literal(literal) => {
+                // A literal can consist of several characters
+                let literal = std::str::from_utf8(&literal.0)
+                    .map_err(|_| unsupported!("Literal that is not valid UTF-8"))?;
+                for c in literal.chars() {
+                    let start_state = nfa.end_state();
+                    let end_state = nfa.new_state();
+                    nfa.set_end_state(end_state);
+                    nfa.add_transition(start_state, single_char_class(c), end_state);
+                }
+                Ok(nfa)
+            }
+            HirKind::Class(Class::Unicode(class)) => {
+                let start_state = nfa.end_state();
+                let end_state = nfa.new_state();
+                nfa.set_end_state(end_state);
+                nfa.add_transition(start_state, class, end_state);
+                Ok(nfa)
+            }
+            HirKind::Class(Class::Bytes(class)) => {
+                // ASCII byte classes, e.g. `(?-u:\w)` or the empty class of `[a&&b]`, can be
+                // treated as unicode classes because the input is always valid UTF-8
+                let class = class
+                    .to_unicode_class()
+                    .ok_or_else(|| unsupported!(format!("Byte class {:?}", class)))?;
+                let start_state = nfa.end_state();
+                let end_state = nfa.new_state();
+                nfa.set_end_state(end_state);
+                nfa.add_transition(start_state, class, end_state);
+                Ok(nfa)
+            }
+            HirKind::Look(look) => Err(unsupported!(format!("Assertion {:?}", look))),
+            HirKind::Repetition(repetition) => {
+                let Repetition {
+                    min,
+                    max,
+                    greedy,
+                    sub,
+                } = repetition;
+                if count_repetitions && greedy && max.unwrap_or(min) > MAX_UNROLLED_REPETITION {
+                    if let Some(chars) = single_class(&sub) {
+                        let counter = RepetitionCounter {
+                            min: min as usize,
+                            max: max.map(|max| max as usize),
+                        };
+                        return Ok(Nfa::counted_repetition(chars, counter));
+                    }
+                }
+                let mut nfa2 = Nfa::try_from_hir(*sub, count_repetitions)?;
+                match (min, max) {
+                    (0, Some(1)) => {
+                        nfa2.zero_or_one();
+                        nfa = nfa2;
+                    }
+                    (0, None) => {
+                        nfa2.zero_or_more();
+                        nfa = nfa2;
+                    }
+                    (1, None) => {
+                        nfa2.one_or_more();
+                        nfa = nfa2;
+                    }
+                    (min, None) => {
+                        check_repetition_size(&nfa2, min, None)?;
+                        for _ in 0..min {
+                            nfa.concat(nfa2.clone());
+                        }
+                        let mut nfa_zero_or_more: Nfa = nfa2.clone();
+                        nfa_zero_or_more.zero_or_more();
+                        nfa.concat(nfa_zero_or_more);
+                    }
+                    (min, Some(max)) => {
+                        check_repetition_size(&nfa2, min, Some(max))?;
+                        for _ in 0..min {
+                            nfa.concat(nfa2.clone());
+                        }
+                        let mut nfa_zero_or_one: Nfa = nfa2.clone();
+                        nfa_zero_or_one.zero_or_one();
+                        for _ in min..max {
+                            nfa.concat(nfa_zero_or_one.clone());
+                        }
+                    }
+                }
+                Ok(nfa)
+            }
+            HirKind::Capture(capture) => Nfa::try_from_hir(*capture.sub, count_repetitions),
+            HirKind::Alternation(hirs) => {
+                for hir in hirs {
+                    let nfa2 = Nfa::try_from_hir(hir, count_repetitions)?;
+                    nfa.alternation(nfa2);
+                }
+                Ok(nfa)
+            }
+            HirKind::Concat(hirs) => {
+                for hir in hirs {
+                    let nfa2 = Nfa::try_from_hir(hir, count_repetitions)?;
+                    nfa.concat(nfa2);
+                }
+                Ok(nfa)
+            }
+        }
+    }
+}
+
+/// Returns the character class of a HIR that matches exactly one character of a class, i.e. a
+/// class or a literal of a single character.
+fn single_class(hir: &Hir) -> Option<ClassUnicode> {
+    match hir.kind() {
+        HirKind::Class(Class::Unicode(class)) => Some(class.clone()),
+        HirKind::Class(Class::Bytes(class)) => class.to_unicode_class(),
+        HirKind::Literal(literal) => {
+            let mut chars = std::str::from_utf8(&literal.0).ok()?.chars();
+            let c = chars.next()?;
+            chars.next().is_none().then(|| single_char_class(c))
+        }
+        HirKind::Capture(capture) => single_class(&capture.sub),
+        _ => None,
+    }
+}
+
+/// Returns true if the DFA of the given pattern stops at its shortest match because of a
+/// non-greedy repetition, e.g. `.*?` in `/\*.*?\*/`. The DFA is cut after its first match, see
+/// [Dfa::remove_transitions_from_accepting_states](crate::compiletime::dfa::Dfa).
+///
+/// The cut only agrees with the semantics of non-greedy repetitions if the pattern is a sequence
+/// whose only repetition is the non-greedy one, like the comments and string literals it is meant
+/// for. Other placements, e.g. `a+?b+`, are rejected.
+pub(crate) fn shortest_match(hir: &Hir) -> Result<bool> {
+    if !has_lazy_repetition(hir) {
+        return Ok(false);
+    }
+    let hir = without_captures(hir);
+    let elements = match hir.kind() {
+        HirKind::Concat(hirs) => hirs.as_slice(),
+        _ => std::slice::from_ref(hir),
+    };
+    let mut repetitions = elements.iter().filter(|element| has_repetition(element));
+    let only_repetition = match (repetitions.next().map(without_captures), repetitions.next()) {
+        (Some(element), None) => matches!(
+            element.kind(),
+            HirKind::Repetition(repetition)
+                if !repetition.greedy && !has_lazy_repetition(&repetition.sub)
+        ),
+        _ => false,
+    };
+    if only_repetition {
+        Ok(true)
+    } else {
+        Err(unsupported!(format!(
+            "Non-greedy repetition in {} that is not the only repetition of the pattern",
+            hir
+        )))
+    }
+}
+
+/// Returns true if the HIR contains a non-greedy repetition like `.*?`.
+fn has_lazy_repetition(hir: &Hir) -> bool {
+    match hir.kind() {
+        HirKind::Repetition(repetition) => {
+            !repetition.greedy || has_lazy_repetition(&repetition.sub)
+        }
+        HirKind::Capture(capture) => has_lazy_repetition(&capture.sub),
+        HirKind::Concat(hirs) | HirKind::Alternation(hirs) => hirs.iter().any(has_lazy_repetition),
+        _ => false,
+    }
+}
+
+/// Returns true if the HIR contains a repetition.
+fn has_repetition(hir: &Hir) -> bool {
+    match hir.kind() {
+        HirKind::Repetition(_) => true,
+        HirKind::Capture(capture) => has_repetition(&capture.sub),
+        HirKind::Concat(hirs) | HirKind::Alternation(hirs) => hirs.iter().any(has_repetition),
+        _ => false,
+    }
+}
+
+/// Returns the HIR within the capture groups that enclose it.
+fn without_captures(hir: &Hir) -> &Hir {
+    match hir.kind() {
+        HirKind::Capture(capture) => without_captures(&capture.sub),
+        _ => hir,
+    }
+}
+
+/// Checks that the expansion of a bounded repetition of the given NFA stays within
+/// [MAX_REPETITION_STATES].
+fn check_repetition_size(nfa: &Nfa, min: u32, max: Option<u32>) -> Result<()> {
+    let copies = match max {
+        Some(max) => max as usize,
+        // The zero-or-more part is an additional copy
+        None => min as usize + 1,
+    };
+    let states = nfa.states.len().saturating_mul(copies);
+    if states > MAX_REPETITION_STATES {
+        Err(ScanGenError::new(
+            crate::ScanGenErrorKind::RepetitionTooLarge(states, MAX_REPETITION_STATES),
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        compiletime::{GenerateOptions, MultiPatternDfa},
+        ScanGenErrorKind,
+    };
+
+    fn accepts(pattern: &str, input: &str) -> bool {
+        let mut multi_pattern_dfa = MultiPatternDfa::new();
+        multi_pattern_dfa.add_pattern(pattern).unwrap();
+        multi_pattern_dfa.dfas()[0].accepts(input, multi_pattern_dfa.match_functions())
+    }
+
+    #[test]
+    fn test_bounded_repetition_of_group() {
+        assert!(!accepts("(ab){2,4}", "ab"));
+        assert!(accepts("(ab){2,4}", "abab"));
+        assert!(accepts("(ab){2,4}", "ababab"));
+        assert!(accepts("(ab){2,4}", "abababab"));
+        assert!(!accepts("(ab){2,4}", "ababababab"));
+        assert!(!accepts("(ab){2,4}", "aba"));
+    }
+
+    #[test]
+    fn test_exact_repetition_of_class() {
+        assert!(accepts("[a-z]{8}", "abcdefgh"));
+        assert!(!accepts("[a-z]{8}", "abcdefg"));
+        assert!(!accepts("[a-z]{8}", "abcdefghi"));
+    }
+
+    #[test]
+    fn test_repetition_of_alternation() {
+        assert!(accepts("(a|bc){0,2}d", "d"));
+        assert!(accepts("(a|bc){0,2}d", "bcad"));
+        assert!(!accepts("(a|bc){0,2}d", "abcad"));
+        assert!(accepts("(a|bc){2,}", "abcbc"));
+        assert!(!accepts("(a|bc){2,}", "bc"));
+    }
+
+    #[test]
+    fn test_nested_bounded_repetition() {
+        assert!(accepts("((a{1,2})b){2}", "abaab"));
+        assert!(!accepts("((a{1,2})b){2}", "abaaab"));
+        assert!(accepts("(a{2,3}){2,3}", "aaaa"));
+        assert!(accepts("(a{2,3}){2,3}", "aaaaaaaaa"));
+        assert!(!accepts("(a{2,3}){2,3}", "aaa"));
+        assert!(!accepts("(a{2,3}){2,3}", "aaaaaaaaaa"));
+    }
+
+    fn counted_states(pattern: &str) -> usize {
+        let mut multi_pattern_dfa = MultiPatternDfa::new();
+        multi_pattern_dfa.add_pattern(pattern).unwrap();
+        multi_pattern_dfa.dfas()[0].counted_states().count()
+    }
+
+    #[test]
+    fn test_counted_repetition() {
+        assert_eq!(counted_states(r"\d{1,100}"), 1);
+        assert!(accepts(r"\d{1,100}", "7"));
+        assert!(accepts(r"\d{1,100}", &"7".repeat(100)));
+        assert!(!accepts(r"\d{1,100}", &"7".repeat(101)));
+        assert!(!accepts(r"\d{1,100}", ""));
+
+        assert_eq!(counted_states("a{20,}"), 1);
+        assert!(!accepts("a{20,}", &"a".repeat(19)));
+        assert!(accepts("a{20,}", &"a".repeat(20)));
+        assert!(accepts("a{20,}", &"a".repeat(50)));
+
+        assert_eq!(counted_states("(?:[0-9]{17,18}x)+"), 1);
+        let number = "1".repeat(17);
+        assert!(accepts(
+            "(?:[0-9]{17,18}x)+",
+            &format!("{}x{}1x", number, number)
+        ));
+        assert!(!accepts("(?:[0-9]{17,18}x)+", &format!("{}x1x", number)));
+
+        // Small bounds and lazy repetitions are expanded
+        assert_eq!(counted_states("[a-z]{8}"), 0);
+        assert_eq!(counted_states("/\\*.{0,100}?\\*/"), 0);
+    }
+
+    #[test]
+    fn test_overlapping_counted_repetition_is_expanded() {
+        // The characters after the repetition could continue it, so it can't be counted
+        assert_eq!(counted_states("a{1,20}a"), 0);
+        assert!(!accepts("a{1,20}a", "a"));
+        assert!(accepts("a{1,20}a", "aa"));
+        assert!(accepts("a{1,20}a", &"a".repeat(21)));
+        assert!(!accepts("a{1,20}a", &"a".repeat(22)));
+
+        // The next repetition of the group starts while the counted one could continue
+        assert_eq!(counted_states("(?:[0-9]{17,20})+"), 0);
+        assert!(!accepts("(?:[0-9]{17,20})+", &"1".repeat(16)));
+        assert!(accepts("(?:[0-9]{17,20})+", &"1".repeat(40)));
+    }
+
+    #[test]
+    fn test_repetition_size_limit() {
+        let mut multi_pattern_dfa = MultiPatternDfa::new();
+        let error = multi_pattern_dfa
+            .add_pattern("((ab){100}){100}")
+            .unwrap_err();
+        assert!(matches!(
+            *error.source,
+            ScanGenErrorKind::RepetitionTooLarge(_, _)
+        ));
+    }
+
+    #[test]
+    fn test_case_insensitive_group() {
+        assert!(accepts("(?i:select|from)", "SeLeCt"));
+        assert!(accepts("(?i:select|from)", "FROM"));
+        assert!(accepts("(?i:a)b", "Ab"));
+        assert!(!accepts("(?i:a)b", "AB"));
+        assert!(accepts("(?i:[a-c]x)", "BX"));
+        assert!(!accepts("(?i:[a-c]x)", "DX"));
+        assert!(accepts("(?i:[^a])", "b"));
+        assert!(!accepts("(?i:[^a])", "A"));
+    }
+
+    #[test]
+    fn test_nested_case_sensitive_group() {
+        assert!(accepts("(?i:a(?-i:b))", "Ab"));
+        assert!(!accepts("(?i:a(?-i:b))", "AB"));
+    }
+
+    #[test]
+    fn test_overlapping_classes() {
+        assert!(accepts("(a|b)*abb", "babb"));
+        assert!(accepts("[ab]*abb", "ababb"));
+        assert!(!accepts("[ab]*abb", "abab"));
+        assert!(accepts("[a-z]*x", "xyzx"));
+    }
+
+    #[test]
+    fn test_lazy_repetition() {
+        assert!(accepts(r"/\*.*?\*/", "/* a */"));
+        assert!(!accepts(r"/\*.*?\*/", "/* a */ b */"));
+        assert!(accepts(r"/\*.*\*/", "/* a */ b */"));
+        assert!(accepts(r#""(\\.|[^\\])*?""#, r#""a\"b""#));
+        assert!(!accepts(r#""(\\.|[^\\])*?""#, r#""a" "b""#));
+        // Only the sole repetition of a sequence may be non-greedy
+        for pattern in ["a+?b+", "a*b*?c", "(a*?|b)c"] {
+            let mut multi_pattern_dfa = MultiPatternDfa::new();
+            let error = multi_pattern_dfa.add_pattern(pattern).unwrap_err();
+            assert!(matches!(
+                *error.source,
+                ScanGenErrorKind::UnsupportedFeature(_)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_other_flags() {
+        assert!(!accepts(".", "\n"));
+        assert!(accepts("(?s:.)", "\n"));
+        assert!(accepts("(?i)a+(?-i)b+", "aAbb"));
+        assert!(!accepts("(?i)a+(?-i)b+", "aAbB"));
+        assert!(accepts(r"(?-u:\w)+", "a_1"));
+        assert!(!accepts(r"(?-u:\w)+", "ä"));
+    }
+
+    #[test]
+    fn test_unsupported_features() {
+        for pattern in ["^a", "a$", r"\ba"] {
+            let mut multi_pattern_dfa = MultiPatternDfa::new();
+            let error = multi_pattern_dfa.add_pattern(pattern).unwrap_err();
+            assert!(matches!(
+                *error.source,
+                ScanGenErrorKind::UnsupportedFeature(_)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_case_insensitive_code_generation() {
+        let mut multi_pattern_dfa = MultiPatternDfa::new();
+        multi_pattern_dfa.add_pattern("(?i:if)").unwrap();
+        let mut output = Vec::new();
+        multi_pattern_dfa
+            .generate_code(&[], &GenerateOptions::default(), &mut output)
+            .unwrap();
+        let code = String::from_utf8(output).unwrap();
+        assert!(code.contains("matches!(c, 'I' | 'i')"));
+        assert!(code.contains("matches!(c, 'F' | 'f')"));
+    }
+}
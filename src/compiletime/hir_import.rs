@@ -0,0 +1,85 @@
+//! This module converts regex HIRs back into scangen terminals.
+//!
+//! Projects that are standardized on `regex-automata` usually hold their patterns as
+//! [`regex_syntax::hir::Hir`] values, e.g. to build a `regex_automata::meta::Regex` with
+//! `build_many_from_hir`. These HIRs can be printed back into the regex syntax and used as
+//! terminals. HIRs are already translated, i.e. flags like `(?i)` and Perl classes like `\d` are
+//! resolved into character classes. Anchors and look-around assertions are not supported by
+//! scangen and result in an error.
+
+use regex_syntax::hir::Hir;
+
+use crate::{
+    compiletime::{generate_code, parse_regex_syntax, Result},
+    ScannerModeData,
+};
+
+/// Converts the given HIRs into terminals in the regex syntax that can be used with
+/// [generate_code].
+/// # Errors
+/// An error is returned if a printed HIR can't be parsed again.
+pub fn terminals_from_hirs(hirs: &[Hir]) -> Result<Vec<String>> {
+    hirs.iter()
+        .map(|hir| {
+            let terminal = hir.to_string();
+            parse_regex_syntax(&terminal)?;
+            Ok(terminal)
+        })
+        .collect()
+}
+
+/// Generate code from the given HIRs.
+/// The HIR at index i corresponds to the terminal with index i. The other arguments are the same
+/// as for [generate_code].
+/// # Errors
+/// An error is returned if a HIR contains unsupported features, like anchors.
+pub fn generate_code_from_hirs(
+    hirs: &[Hir],
+    scanner_mode_data: &[ScannerModeData],
+    scangen_module_name: Option<&str>,
+    output: &mut dyn std::io::Write,
+) -> Result<()> {
+    let terminals = terminals_from_hirs(hirs)?;
+    let terminals = terminals.iter().map(String::as_str).collect::<Vec<_>>();
+    generate_code(&terminals, scanner_mode_data, scangen_module_name, output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{compiletime::MultiPatternDfa, ScanGenErrorKind};
+    use regex_syntax::Parser;
+
+    fn hir(pattern: &str) -> Hir {
+        Parser::new().parse(pattern).unwrap()
+    }
+
+    #[test]
+    fn test_terminals_from_hirs() {
+        let terminals = terminals_from_hirs(&[hir("(?i:if)"), hir("[0-9]+")]).unwrap();
+        let mut multi_pattern_dfa = MultiPatternDfa::new();
+        multi_pattern_dfa.add_patterns(&terminals).unwrap();
+        let dfas = multi_pattern_dfa.dfas();
+        let match_functions = multi_pattern_dfa.match_functions();
+        assert!(dfas[0].accepts("iF", match_functions));
+        assert!(!dfas[0].accepts("ig", match_functions));
+        assert!(dfas[1].accepts("4711", match_functions));
+    }
+
+    #[test]
+    fn test_generate_code_from_hirs() {
+        let mut output = Vec::new();
+        generate_code_from_hirs(&[hir(r"[a-z]+"), hir(r"\s")], &[], None, &mut output).unwrap();
+        assert!(String::from_utf8(output).unwrap().contains("const DFAS"));
+    }
+
+    #[test]
+    fn test_unsupported_hir() {
+        let mut output = Vec::new();
+        let error = generate_code_from_hirs(&[hir(r"^a")], &[], None, &mut output).unwrap_err();
+        assert!(matches!(
+            *error.source,
+            ScanGenErrorKind::UnsupportedFeature(_)
+        ));
+    }
+}
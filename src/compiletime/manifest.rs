@@ -0,0 +1,295 @@
+//! This module contains the manifest of a generated scanner, i.e. a JSON file with the metadata of
+//! the scanner, so build systems and other tools don't need to parse the generated Rust code.
+
+use std::{collections::BTreeSet, fmt::Write};
+
+use crate::{ScannerModeData, TABLE_FORMAT_VERSION};
+
+use super::{GenerateOptions, MultiPatternDfa};
+
+/// The version of the JSON schema of the generation manifest.
+/// See [GenerateOptions::with_manifest] for the schema.
+pub const MANIFEST_FORMAT_VERSION: u32 = 1;
+
+/// A scanner mode as it is written to the manifest, see [manifest].
+type ManifestMode<'a> = (
+    &'a str,
+    Vec<(usize, usize)>,
+    Vec<(usize, usize)>,
+    Option<usize>,
+);
+
+/// Creates the manifest of the scanner that is generated from the given DFAs, scanner modes and
+/// options. See [GenerateOptions::with_manifest] for the schema.
+pub(crate) fn manifest(
+    multi_pattern_dfa: &MultiPatternDfa,
+    scanner_mode_data: &[ScannerModeData],
+    options: &GenerateOptions,
+) -> String {
+    let dfas = multi_pattern_dfa.dfas();
+    // The scanner modes with their pairs of terminal and token type, their transitions as pairs
+    // of token type and mode, and their error token type. Without scanner modes the scanner has
+    // a default mode in which the token type of each terminal is its index.
+    let modes: Vec<ManifestMode> = if scanner_mode_data.is_empty() {
+        vec![(
+            "INITIAL",
+            (0..dfas.len()).map(|index| (index, index)).collect(),
+            Vec::new(),
+            None,
+        )]
+    } else {
+        scanner_mode_data
+            .iter()
+            .map(|(name, terminals, transitions, error_token_type)| {
+                (
+                    *name,
+                    terminals
+                        .iter()
+                        .map(|(terminal, token_type)| (*terminal, token_type.as_usize()))
+                        .collect(),
+                    transitions
+                        .iter()
+                        .map(|(token_type, mode)| (token_type.as_usize(), mode.as_usize()))
+                        .collect(),
+                    error_token_type.map(|token_type| token_type.as_usize()),
+                )
+            })
+            .collect()
+    };
+
+    let mut out = String::new();
+    out.push_str("{\n");
+    out.push_str("  \"format\": \"scangen-manifest\",\n");
+    let _ = writeln!(out, "  \"version\": {},", MANIFEST_FORMAT_VERSION);
+    let _ = writeln!(
+        out,
+        "  \"scangen_version\": \"{}\",",
+        env!("CARGO_PKG_VERSION")
+    );
+    let _ = writeln!(out, "  \"table_format_version\": {},", TABLE_FORMAT_VERSION);
+    let _ = writeln!(
+        out,
+        "  \"input_hash\": \"fnv1a64:{:016x}\",",
+        input_hash(multi_pattern_dfa, scanner_mode_data, options)
+    );
+
+    // The token types along with the terminals they are matched by, in the order of the token
+    // types.
+    let tokens = modes
+        .iter()
+        .flat_map(|(_, terminals, _, _)| terminals.iter())
+        .map(|(terminal, token_type)| (*token_type, *terminal))
+        .collect::<BTreeSet<_>>();
+    out.push_str("  \"tokens\": [");
+    for (index, (token_type, terminal)) in tokens.iter().enumerate() {
+        let _ = write!(
+            out,
+            "{}\n    {{\"token_type\": {}, \"terminal\": {}, \"pattern\": ",
+            if index == 0 { "" } else { "," },
+            token_type,
+            terminal
+        );
+        let pattern = dfas.get(*terminal).map_or("", |dfa| dfa.pattern());
+        write_string(&mut out, pattern);
+        out.push('}');
+    }
+    out.push_str(if tokens.is_empty() {
+        "],\n"
+    } else {
+        "\n  ],\n"
+    });
+
+    out.push_str("  \"modes\": [");
+    for (index, (name, terminals, transitions, error_token_type)) in modes.iter().enumerate() {
+        let _ = write!(
+            out,
+            "{}\n    {{\"id\": {}, \"name\": ",
+            if index == 0 { "" } else { "," },
+            index
+        );
+        write_string(&mut out, name);
+        out.push_str(", \"terminals\": ");
+        write_pairs(&mut out, terminals);
+        out.push_str(", \"transitions\": ");
+        write_pairs(&mut out, transitions);
+        match error_token_type {
+            Some(token_type) => {
+                let _ = write!(out, ", \"error_token_type\": {}}}", token_type);
+            }
+            None => out.push_str(", \"error_token_type\": null}"),
+        }
+    }
+    out.push_str("\n  ],\n");
+
+    let table_size = multi_pattern_dfa.table_size(scanner_mode_data);
+    let _ = writeln!(
+        out,
+        "  \"statistics\": {{\"terminals\": {}, \"states\": {}, \"char_classes\": {}, \
+         \"table_bytes\": {}, \"match_arms\": {}}}",
+        dfas.len(),
+        dfas.iter().map(|dfa| dfa.state_count()).sum::<usize>(),
+        multi_pattern_dfa.match_functions().len(),
+        table_size.dfa_bytes,
+        table_size.match_arms
+    );
+    out.push_str("}\n");
+    out
+}
+
+/// Calculates the hash of the input of the generation, i.e. the patterns, the scanner modes and
+/// the options, except for the path of the manifest itself.
+///
+/// The FNV-1a hash is used because, unlike the hasher of the standard library, it is stable
+/// across Rust versions and platforms, so build systems can compare it with stored hashes.
+fn input_hash(
+    multi_pattern_dfa: &MultiPatternDfa,
+    scanner_mode_data: &[ScannerModeData],
+    options: &GenerateOptions,
+) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    let mut input = String::new();
+    for dfa in multi_pattern_dfa.dfas() {
+        input.push_str(dfa.pattern());
+        input.push('\0');
+    }
+    for (name, terminals, transitions, error_token_type) in scanner_mode_data {
+        let _ = write!(
+            input,
+            "{}\0{:?}\0{:?}\0{:?}\0",
+            name, terminals, transitions, error_token_type
+        );
+    }
+    let _ = write!(input, "{:?}", options.clone().without_manifest());
+    input.bytes().fold(OFFSET_BASIS, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(PRIME)
+    })
+}
+
+/// Writes the given string as JSON string literal.
+fn write_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/// Writes the given pairs of numbers as JSON array of arrays with two elements.
+fn write_pairs(out: &mut String, pairs: &[(usize, usize)]) {
+    out.push('[');
+    for (index, (first, second)) in pairs.iter().enumerate() {
+        if index > 0 {
+            out.push_str(", ");
+        }
+        let _ = write!(out, "[{}, {}]", first, second);
+    }
+    out.push(']');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{generate_code_with_options, EntryPoint, ScannerModeId, TokenType};
+
+    const MODES: &[ScannerModeData] = &[
+        (
+            "INITIAL",
+            &[
+                (0, TokenType::new(1)),
+                (1, TokenType::new(2)),
+                (2, TokenType::new(3)),
+            ],
+            &[(TokenType::new(3), ScannerModeId::new(1))],
+            None,
+        ),
+        (
+            "STRING",
+            &[(3, TokenType::new(4)), (2, TokenType::new(3))],
+            &[(TokenType::new(3), ScannerModeId::new(0))],
+            Some(TokenType::new(5)),
+        ),
+    ];
+
+    const PATTERNS: &[&str] = &["if", "[a-z]+", "\"", "[^\"]+"];
+
+    // Generates the scanner with the given options and returns the manifest.
+    fn generated_manifest(name: &str, options: GenerateOptions) -> String {
+        let path =
+            std::env::temp_dir().join(format!("scangen_{}_{}.json", name, std::process::id()));
+        let options = options.with_manifest(&path);
+        generate_code_with_options(PATTERNS, MODES, &options, &mut Vec::new()).unwrap();
+        let manifest = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        manifest
+    }
+
+    #[test]
+    fn test_manifest() {
+        let manifest = generated_manifest("manifest", GenerateOptions::new());
+        let lines = manifest.lines().collect::<Vec<_>>();
+        assert_eq!(
+            lines[..5],
+            [
+                "{",
+                "  \"format\": \"scangen-manifest\",",
+                "  \"version\": 1,",
+                &format!("  \"scangen_version\": \"{}\",", env!("CARGO_PKG_VERSION")),
+                &format!("  \"table_format_version\": {},", TABLE_FORMAT_VERSION),
+            ]
+        );
+        assert!(lines[5].starts_with("  \"input_hash\": \"fnv1a64:"));
+        assert_eq!(
+            lines[6..16],
+            [
+                "  \"tokens\": [",
+                "    {\"token_type\": 1, \"terminal\": 0, \"pattern\": \"if\"},",
+                "    {\"token_type\": 2, \"terminal\": 1, \"pattern\": \"[a-z]+\"},",
+                "    {\"token_type\": 3, \"terminal\": 2, \"pattern\": \"\\\"\"},",
+                "    {\"token_type\": 4, \"terminal\": 3, \"pattern\": \"[^\\\"]+\"}",
+                "  ],",
+                "  \"modes\": [",
+                "    {\"id\": 0, \"name\": \"INITIAL\", \"terminals\": [[0, 1], [1, 2], [2, 3]], \
+                 \"transitions\": [[3, 1]], \"error_token_type\": null},",
+                "    {\"id\": 1, \"name\": \"STRING\", \"terminals\": [[3, 4], [2, 3]], \
+                 \"transitions\": [[3, 0]], \"error_token_type\": 5}",
+                "  ],",
+            ]
+        );
+        // The size of the tables depends on the size of pointers
+        assert!(lines[16].starts_with(
+            "  \"statistics\": {\"terminals\": 4, \"states\": 9, \"char_classes\": 5, \
+             \"table_bytes\": "
+        ));
+        assert_eq!(lines[17..], ["}"]);
+    }
+
+    #[test]
+    fn test_manifest_input_hash() {
+        let input_hash = |manifest: &str| manifest.lines().nth(5).unwrap().to_string();
+        let manifest = generated_manifest("input_hash", GenerateOptions::new());
+        // The path of the manifest is not part of the input
+        assert_eq!(
+            input_hash(&generated_manifest("other_path", GenerateOptions::new())),
+            input_hash(&manifest)
+        );
+        assert_ne!(
+            input_hash(&generated_manifest(
+                "other_options",
+                GenerateOptions::new().with_entry_points(&[EntryPoint::Tokenize])
+            )),
+            input_hash(&manifest)
+        );
+    }
+}
@@ -1,20 +1,9 @@
-use regex_syntax::ast::{
-    Ast, ClassAscii, ClassAsciiKind, ClassBracketed, ClassPerl, ClassPerlKind, ClassSet,
-    ClassSetBinaryOp, ClassSetBinaryOpKind, ClassSetItem, ClassSetRange, ClassSetUnion,
-    ClassUnicode,
-    ClassUnicodeKind::{Named, NamedValue, OneLetter},
-    Literal,
-};
-
-use crate::{Result, ScanGenError};
-
-macro_rules! unsupported {
-    ($feature:expr) => {
-        ScanGenError::new($crate::ScanGenErrorKind::UnsupportedFeature(
-            $feature.to_string(),
-        ))
-    };
-}
+use regex_syntax::hir::ClassUnicode;
+
+use super::character_class::class_to_string;
+#[cfg(test)]
+use super::character_class::single_char_class;
+use crate::Result;
 
 /// A function that takes a character and returns a boolean.
 pub(crate) struct MatchFunction(pub(crate) Box<dyn Fn(char) -> bool + 'static>);
@@ -34,380 +23,110 @@ impl MatchFunction {
         (self.0)(c)
     }
 
-    fn try_from_class_set(set: ClassSet) -> Result<Self> {
-        let negated = false;
-        match &set {
-            ClassSet::Item(item) => Self::try_from_set_item(item.clone(), negated),
-            ClassSet::BinaryOp(bin_op) => Self::try_from_binary_op(bin_op.clone(), negated),
-        }
-    }
-
-    fn try_from_class_unicode(unicode: ClassUnicode) -> Result<Self> {
-        let negated = unicode.is_negated();
-        let kind = unicode.kind.clone();
-        let match_function = match kind {
-            OneLetter(ch) => {
-                match ch {
-                    // Unicode class for Letters
-                    'L' => MatchFunction::new(|ch| ch.is_alphabetic()),
-                    // Unicode class for Numbers
-                    'N' => MatchFunction::new(|ch| ch.is_numeric()),
-                    // Unicode class for Whitespace
-                    'Z' => MatchFunction::new(|ch| ch.is_whitespace()),
-                    // Unicode class for Punctuation
-                    // Attention: Only ASCII based punctuation is supported
-                    'P' => MatchFunction::new(|ch| ch.is_ascii_punctuation()),
-                    // Unicode class for Control characters
-                    'C' => MatchFunction::new(|ch| ch.is_control()),
-                    _ => return Err(unsupported!(format!("{:#?}", unicode))),
-                }
-            }
-            Named(_) | NamedValue { .. } => {
-                // Actually no support for named classes and named values
-                // We need to ensure that this is not a match even if it is negated
-                let no_match = negated;
-                MatchFunction::new(move |_| no_match)
-            }
-        };
-        Ok(if unicode.is_negated() {
-            MatchFunction::new(move |ch| !match_function.call(ch))
-        } else {
-            match_function
-        })
-    }
-
-    fn try_from_class_perl(perl: ClassPerl) -> Result<Self> {
-        let ClassPerl { negated, kind, .. } = perl;
-        let match_function = match kind {
-            ClassPerlKind::Digit => MatchFunction::new(|ch| ch.is_numeric()),
-            ClassPerlKind::Space => MatchFunction::new(|ch| ch.is_whitespace()),
-            ClassPerlKind::Word => MatchFunction::new(|ch| ch.is_alphanumeric()),
-        };
-        Ok(if negated {
-            MatchFunction::new(move |ch| !match_function.call(ch))
-        } else {
-            match_function
-        })
-    }
-
-    fn try_from_class_bracketed(bracketed: ClassBracketed) -> Result<Self> {
-        let negated = bracketed.negated;
-        match &bracketed.kind {
-            ClassSet::Item(item) => Self::try_from_set_item(item.clone(), negated),
-            ClassSet::BinaryOp(bin_op) => Self::try_from_binary_op(bin_op.clone(), negated),
-        }
-    }
-
-    // Match one of the set items, i.e.
-    fn try_from_class_set_union(union: ClassSetUnion) -> Result<Self> {
-        union
-            .items
-            .iter()
-            .try_fold(MatchFunction::new(|_| false), |acc, s| {
-                Self::try_from_set_item(s.clone(), false)
-                    .map(|f| MatchFunction::new(move |ch| acc.call(ch) || f.call(ch)))
-            })
-    }
-
-    fn try_from_binary_op(bin_op: ClassSetBinaryOp, negated: bool) -> Result<Self> {
-        let ClassSetBinaryOp { kind, lhs, rhs, .. } = bin_op;
-        let lhs = Self::try_from_class_set(*lhs)?;
-        let rhs = Self::try_from_class_set(*rhs)?;
-        let match_function = match kind {
-            ClassSetBinaryOpKind::Intersection => {
-                MatchFunction::new(move |ch| lhs.call(ch) && rhs.call(ch))
-            }
-            ClassSetBinaryOpKind::Difference => {
-                MatchFunction::new(move |ch| lhs.call(ch) && !rhs.call(ch))
-            }
-            ClassSetBinaryOpKind::SymmetricDifference => {
-                MatchFunction::new(move |ch| lhs.call(ch) != rhs.call(ch))
-            }
-        };
-        Ok(if negated {
-            MatchFunction::new(move |ch| !match_function.call(ch))
-        } else {
-            match_function
-        })
-    }
-
-    fn try_from_set_item(item: ClassSetItem, negated: bool) -> Result<Self> {
-        let match_function = match item {
-            ClassSetItem::Empty(_) => MatchFunction::new(|_| false),
-            ClassSetItem::Literal(ref l) => {
-                let Literal { c, .. } = *l;
-                MatchFunction::new(move |ch| ch == c)
-            }
-            ClassSetItem::Range(ref r) => {
-                let ClassSetRange {
-                    ref start, ref end, ..
-                } = *r;
-                let start = start.c;
-                let end = end.c;
-                MatchFunction::new(move |ch| start <= ch && ch <= end)
-            }
-            ClassSetItem::Ascii(ref a) => {
-                let ClassAscii {
-                    ref kind, negated, ..
-                } = *a;
-                let match_function = match kind {
-                    ClassAsciiKind::Alnum => MatchFunction::new(|ch| ch.is_alphanumeric()),
-                    ClassAsciiKind::Alpha => MatchFunction::new(|ch| ch.is_alphabetic()),
-                    ClassAsciiKind::Ascii => MatchFunction::new(|ch| ch.is_ascii()),
-                    ClassAsciiKind::Blank => MatchFunction::new(|ch| ch.is_ascii_whitespace()),
-                    ClassAsciiKind::Cntrl => MatchFunction::new(|ch| ch.is_ascii_control()),
-                    ClassAsciiKind::Digit => MatchFunction::new(|ch| ch.is_numeric()),
-                    ClassAsciiKind::Graph => MatchFunction::new(|ch| ch.is_ascii_graphic()),
-                    ClassAsciiKind::Lower => MatchFunction::new(|ch| ch.is_lowercase()),
-                    ClassAsciiKind::Print => MatchFunction::new(|ch| ch.is_ascii_graphic()),
-                    ClassAsciiKind::Punct => MatchFunction::new(|ch| ch.is_ascii_punctuation()),
-                    ClassAsciiKind::Space => MatchFunction::new(|ch| ch.is_whitespace()),
-                    ClassAsciiKind::Upper => MatchFunction::new(|ch| ch.is_uppercase()),
-                    ClassAsciiKind::Word => MatchFunction::new(|ch| ch.is_alphanumeric()),
-                    ClassAsciiKind::Xdigit => MatchFunction::new(|ch| ch.is_ascii_hexdigit()),
-                };
-                if negated {
-                    MatchFunction::new(move |ch| !match_function.call(ch))
-                } else {
-                    match_function
-                }
-            }
-            ClassSetItem::Unicode(ref c) => Self::try_from_class_unicode(c.clone())?,
-            ClassSetItem::Perl(ref c) => Self::try_from_class_perl(c.clone())?,
-            ClassSetItem::Bracketed(ref c) => Self::try_from_class_bracketed(*c.clone())?,
-            ClassSetItem::Union(ref c) => Self::try_from_class_set_union(c.clone())?,
-        };
-        Ok(if negated {
-            MatchFunction::new(move |ch| !match_function.call(ch))
-        } else {
-            match_function
-        })
-    }
-
+    /// Generates the match arm of the `matches_char_class` function for the given character class.
     pub(crate) fn generate_code(
-        ast: &Ast,
+        class: &ClassUnicode,
         match_function_index: usize,
         output: &mut dyn std::io::Write,
     ) -> Result<()> {
-        // Add code generation here
-        writeln!(output, "        /* {} */", ast)?;
+        // A `*/` in the class would terminate the comment prematurely
+        writeln!(
+            output,
+            "        /* {} */",
+            class_to_string(class).replace("*/", "*\\/")
+        )?;
         writeln!(output, "        {} => {{", match_function_index)?;
-        match ast {
-            Ast::Empty(_) => write!(output, "            true")?,
-            Ast::Dot(_) => write!(output, "            c != '\\n' && c != '\\r'")?,
-            Ast::Literal(ref l) => {
-                let Literal { c, .. } = **l;
-                write!(output, "            c == '{}'", c.escape_default())?
-            }
-            Ast::ClassUnicode(ref c) => {
-                Self::generate_code_from_class_unicode(c, output)?;
-            }
-            Ast::ClassPerl(ref c) => {
-                Self::generate_code_from_class_perl(c, output)?;
-            }
-            Ast::ClassBracketed(ref c) => {
-                Self::generate_code_from_class_bracketed(c, output)?;
-            }
-            _ => return Err(unsupported!(format!("{:#?}", ast))),
-        }
+        write!(output, "            ")?;
+        Self::generate_condition(class, output)?;
         writeln!(output)?;
         writeln!(output, "        }},")?;
         Ok(())
     }
 
-    fn generate_code_from_class_set(set: &ClassSet, output: &mut dyn std::io::Write) -> Result<()> {
-        let negated = false;
-        match set {
-            ClassSet::Item(item) => {
-                Self::generate_code_from_set_item(item.clone(), negated, output)
-            }
-            ClassSet::BinaryOp(bin_op) => {
-                Self::generate_code_from_binary_op(bin_op.clone(), negated, output)
-            }
-        }
-    }
-
-    fn generate_code_from_class_unicode(
-        c: &ClassUnicode,
-        output: &mut dyn std::io::Write,
-    ) -> Result<()> {
-        let ClassUnicode { negated, kind, .. } = c;
-        write!(output, "            ")?;
-        if *negated {
-            write!(output, "!")?;
-        }
-        match kind {
-            Named(_) | NamedValue { .. } => {
-                // Actually no support for named classes and named values
-                // We need to ensure that this is not a match even if it is negated
-                let no_match = *negated;
-                write!(output, "{}", no_match)?;
-            }
-            OneLetter(ch) => match ch {
-                // Unicode class for Letters
-                'L' => write!(output, "c.is_alphabetic()")?,
-                // Unicode class for Numbers
-                'N' => write!(output, "c.is_numeric()")?,
-                // Unicode class for Whitespace
-                'Z' => write!(output, "c.is_whitespace()")?,
-                // Unicode class for Punctuation
-                // Attention: Only ASCII based punctuation is supported
-                'P' => write!(output, "c.is_ascii_punctuation()")?,
-                // Unicode class for Control characters
-                'C' => write!(output, "c.is_control()")?,
-                _ => return Err(unsupported!(format!("{:#?}", c))),
-            },
-        }
-        Ok(())
-    }
-
-    fn generate_code_from_class_perl(
-        perl: &ClassPerl,
-        output: &mut dyn std::io::Write,
-    ) -> Result<()> {
-        let ClassPerl { negated, kind, .. } = perl;
-        write!(output, "            ")?;
-        if *negated {
-            write!(output, "!")?;
+    /// Generates a boolean expression that checks if the character `c` is in the class.
+    /// If the complement of the class consists of less ranges, the negated complement is checked,
+    /// e.g. `c != '\n'` instead of two ranges.
+    /// Classes of more than two ranges that only contain ASCII characters are checked with a
+    /// bitmask.
+    fn generate_condition(class: &ClassUnicode, output: &mut dyn std::io::Write) -> Result<()> {
+        let mut complement = class.clone();
+        complement.negate();
+        if class.ranges().is_empty() {
+            write!(output, "false")?;
+        } else if class.ranges().len() > 2 && class.ranges().iter().all(|r| r.end().is_ascii()) {
+            Self::generate_ascii_mask_condition(class, output)?;
+        } else if complement.ranges().is_empty() {
+            write!(output, "true")?;
+        } else if complement.ranges().len() < class.ranges().len() {
+            Self::generate_ranges_condition(&complement, true, output)?;
+        } else {
+            Self::generate_ranges_condition(class, false, output)?;
         }
-        match kind {
-            ClassPerlKind::Digit => write!(output, "c.is_numeric()")?,
-            ClassPerlKind::Space => write!(output, "c.is_whitespace()")?,
-            ClassPerlKind::Word => write!(output, "c.is_alphanumeric()")?,
-        };
         Ok(())
     }
 
-    fn generate_code_from_class_bracketed(
-        bracketed: &ClassBracketed,
+    /// Generates a check against a `u128` bitmask in which bit `n` is set if the character with
+    /// code point `n` is in the class. The class must only contain ASCII characters.
+    fn generate_ascii_mask_condition(
+        class: &ClassUnicode,
         output: &mut dyn std::io::Write,
     ) -> Result<()> {
-        let ClassBracketed { negated, kind, .. } = bracketed;
-        match kind {
-            ClassSet::Item(item) => {
-                Self::generate_code_from_set_item(item.clone(), *negated, output)
-            }
-            ClassSet::BinaryOp(bin_op) => {
-                Self::generate_code_from_binary_op(bin_op.clone(), *negated, output)
-            }
-        }
-    }
-
-    fn generate_code_from_class_set_union(
-        union: &ClassSetUnion,
-        negated: bool,
-        output: &mut dyn std::io::Write,
-    ) -> Result<()> {
-        let num_items = union.items.len();
-        if negated {
-            write!(output, "!(")?;
-        }
-        union.items.iter().enumerate().try_for_each(|(i, s)| {
-            Self::generate_code_from_set_item(s.clone(), false, output)?;
-            if i < num_items - 1 {
-                write!(output, " || ")?;
-            }
-            Ok::<(), ScanGenError>(())
-        })?;
-        if negated {
-            write!(output, ")")?;
-        }
+        let mask = class
+            .ranges()
+            .iter()
+            .flat_map(|range| range.start()..=range.end())
+            .fold(0u128, |mask, c| mask | (1 << c as u32));
+        writeln!(output, "const MASK: u128 = 0x{:032x};", mask)?;
+        write!(
+            output,
+            "            c.is_ascii() && (MASK >> (c as u32)) & 1 == 1"
+        )?;
         Ok(())
     }
 
-    fn generate_code_from_set_item(
-        item: ClassSetItem,
+    fn generate_ranges_condition(
+        class: &ClassUnicode,
         negated: bool,
         output: &mut dyn std::io::Write,
     ) -> Result<()> {
-        match item {
-            ClassSetItem::Empty(_) => write!(output, "false")?,
-            ClassSetItem::Literal(ref l) => {
-                let Literal { c, .. } = *l;
-                if negated {
-                    write!(output, "c != '{}'", c.escape_default())?
-                } else {
-                    write!(output, "c == '{}'", c.escape_default())?
-                }
-            }
-            ClassSetItem::Range(ref r) => {
-                let ClassSetRange {
-                    ref start, ref end, ..
-                } = *r;
-                let start = start.c;
-                let end = end.c;
+        let ranges = class.ranges();
+        if let [range] = ranges {
+            if range.start() == range.end() {
+                let op = if negated { "!=" } else { "==" };
+                write!(output, "c {} '{}'", op, range.start().escape_default())?;
+            } else {
                 if negated {
-                    write!(output, "!")?
+                    write!(output, "!")?;
                 }
-                write!(output, "('{}'..='{}').contains(&c)", start, end)?
-            }
-            ClassSetItem::Ascii(ref a) => {
-                let ClassAscii {
-                    ref kind, negated, ..
-                } = *a;
-                let match_function = match kind {
-                    ClassAsciiKind::Alnum => "c.is_alphanumeric()",
-                    ClassAsciiKind::Alpha => "c.is_alphabetic()",
-                    ClassAsciiKind::Ascii => "c.is_ascii()",
-                    ClassAsciiKind::Blank => "c.is_ascii_whitespace()",
-                    ClassAsciiKind::Cntrl => "c.is_ascii_control()",
-                    ClassAsciiKind::Digit => "c.is_numeric()",
-                    ClassAsciiKind::Graph => "c.is_ascii_graphic()",
-                    ClassAsciiKind::Lower => "c.is_lowercase()",
-                    ClassAsciiKind::Print => "c.is_ascii_graphic()",
-                    ClassAsciiKind::Punct => "c.is_ascii_punctuation()",
-                    ClassAsciiKind::Space => "c.is_whitespace()",
-                    ClassAsciiKind::Upper => "c.is_uppercase()",
-                    ClassAsciiKind::Word => "c.is_alphanumeric()",
-                    ClassAsciiKind::Xdigit => "c.is_ascii_hexdigit()",
-                };
-                if negated {
-                    write!(output, "!")?
-                }
-                write!(output, "{}", match_function)?
-            }
-            ClassSetItem::Unicode(ref c) => Self::generate_code_from_class_unicode(c, output)?,
-            ClassSetItem::Perl(ref c) => Self::generate_code_from_class_perl(c, output)?,
-            ClassSetItem::Bracketed(ref c) => {
-                Self::generate_code_from_class_bracketed(c, output)?;
-            }
-            ClassSetItem::Union(ref c) => {
-                Self::generate_code_from_class_set_union(c, negated, output)?;
+                write!(
+                    output,
+                    "('{}'..='{}').contains(&c)",
+                    range.start().escape_default(),
+                    range.end().escape_default()
+                )?;
             }
+            return Ok(());
         }
-        Ok(())
-    }
-
-    fn generate_code_from_binary_op(
-        clone: ClassSetBinaryOp,
-        negated: bool,
-        output: &mut dyn std::io::Write,
-    ) -> Result<()> {
-        let ClassSetBinaryOp { kind, lhs, rhs, .. } = clone;
         if negated {
-            write!(output, "!(")?;
+            write!(output, "!")?;
         }
-        match kind {
-            ClassSetBinaryOpKind::Intersection => {
-                Self::generate_code_from_class_set(&lhs, output)?;
-                write!(output, " && ")?;
-                Self::generate_code_from_class_set(&rhs, output)?;
+        write!(output, "matches!(c, ")?;
+        for (i, range) in ranges.iter().enumerate() {
+            if i > 0 {
+                write!(output, " | ")?;
             }
-            ClassSetBinaryOpKind::Difference => {
-                Self::generate_code_from_class_set(&lhs, output)?;
-                write!(output, " && !(")?;
-                Self::generate_code_from_class_set(&rhs, output)?;
-                write!(output, ")")?;
+            if range.start() == range.end() {
+                write!(output, "'{}'", range.start().escape_default())?;
+            } else {
+                write!(
+                    output,
+                    "'{}'..='{}'",
+                    range.start().escape_default(),
+                    range.end().escape_default()
+                )?;
             }
-            ClassSetBinaryOpKind::SymmetricDifference => {
-                Self::generate_code_from_class_set(&lhs, output)?;
-                write!(output, " != ")?;
-                Self::generate_code_from_class_set(&rhs, output)?;
-            }
-        };
-        if negated {
-            write!(output, ")")?;
         }
+        write!(output, ")")?;
         Ok(())
     }
 }
@@ -418,60 +137,130 @@ impl std::fmt::Debug for MatchFunction {
     }
 }
 
-impl TryFrom<Ast> for MatchFunction {
-    type Error = ScanGenError;
-
-    fn try_from(ast: Ast) -> Result<Self> {
-        let match_function = match ast {
-            Ast::Empty(_) => {
-                // An empty AST matches everything.
-                MatchFunction::new(|_| true)
-            }
-            Ast::Dot(_) => {
-                // A dot AST matches any character except newline.
-                MatchFunction::new(|ch| ch != '\n' && ch != '\r')
-            }
-            Ast::Literal(ref l) => {
-                // A literal AST matches a single character.
-                let Literal { c, .. } = **l;
-                MatchFunction::new(move |ch| ch == c)
-            }
-            Ast::ClassUnicode(ref c) => Self::try_from_class_unicode(*c.clone())?,
-            Ast::ClassPerl(ref c) => Self::try_from_class_perl(*c.clone())?,
-            Ast::ClassBracketed(ref c) => Self::try_from_class_bracketed(*c.clone())?,
-            _ => return Err(unsupported!(format!("{:#?}", ast))),
-        };
-        Ok(match_function)
+impl From<&ClassUnicode> for MatchFunction {
+    fn from(class: &ClassUnicode) -> Self {
+        let ranges = class
+            .ranges()
+            .iter()
+            .map(|r| (r.start(), r.end()))
+            .collect::<Vec<_>>();
+        MatchFunction::new(move |c| {
+            ranges
+                .binary_search_by(|(start, end)| {
+                    if *end < c {
+                        std::cmp::Ordering::Less
+                    } else if *start > c {
+                        std::cmp::Ordering::Greater
+                    } else {
+                        std::cmp::Ordering::Equal
+                    }
+                })
+                .is_ok()
+        })
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use regex_syntax::ast::parse::Parser;
+    use crate::compiletime::parse_regex_syntax;
+    use regex_syntax::hir::{Class, HirKind};
+
+    fn class(pattern: &str) -> ClassUnicode {
+        match parse_regex_syntax(pattern).unwrap().into_kind() {
+            HirKind::Class(Class::Unicode(class)) => class,
+            HirKind::Literal(literal) => {
+                let c = std::str::from_utf8(&literal.0)
+                    .unwrap()
+                    .chars()
+                    .next()
+                    .unwrap();
+                single_char_class(c)
+            }
+            HirKind::Class(Class::Bytes(class)) => class.to_unicode_class().unwrap(),
+            kind => panic!("Not a character class: {:?}", kind),
+        }
+    }
+
+    fn match_function(pattern: &str) -> MatchFunction {
+        MatchFunction::from(&class(pattern))
+    }
 
     #[test]
     fn test_match_function_unicode_class() {
-        let ast = Parser::new().parse(r"\pL").unwrap();
-        let match_function = MatchFunction::try_from(ast).unwrap();
+        let match_function = match_function(r"\pL");
         assert!(match_function.call('a'));
         assert!(match_function.call('A'));
         assert!(!match_function.call('1'));
         assert!(!match_function.call(' '));
     }
 
+    #[test]
+    fn test_match_function_unicode_script_class() {
+        let f = match_function(r"\p{Greek}");
+        assert!(f.call('π'));
+        assert!(f.call('Ω'));
+        assert!(!f.call('a'));
+
+        let f = match_function(r"\p{Script=Cyrillic}");
+        assert!(f.call('Ж'));
+        assert!(!f.call('π'));
+
+        let f = match_function(r"\P{Greek}");
+        assert!(!f.call('π'));
+        assert!(f.call('a'));
+
+        let f = match_function(r"\p{Script!=Greek}");
+        assert!(!f.call('π'));
+        assert!(f.call('a'));
+    }
+
+    #[test]
+    fn test_match_function_unicode_block_class() {
+        let f = match_function(r"\p{Block=Greek}");
+        assert!(f.call('π'));
+        // The Coptic letters of the block aren't part of the Greek script
+        assert!(f.call('\u{3e2}'));
+        assert!(!f.call('\u{1f00}'));
+        assert!(!f.call('a'));
+    }
+
+    #[test]
+    fn test_generate_code_unicode_script_class() {
+        let mut output = Vec::new();
+        MatchFunction::generate_code(&class(r"\p{Greek}"), 0, &mut output).unwrap();
+        let code = String::from_utf8(output).unwrap();
+        assert!(code.contains("matches!(c, '\\u{370}'..='\\u{373}' | "));
+    }
+
+    #[test]
+    fn test_generate_code_ascii_mask() {
+        let mut output = Vec::new();
+        MatchFunction::generate_code(&class(r"[A-Z_a-z]"), 0, &mut output).unwrap();
+        let code = String::from_utf8(output).unwrap();
+        let mask = (0x3ffffffu128 << b'A') | (1 << b'_') | (0x3ffffffu128 << b'a');
+        assert!(code.contains(&format!("const MASK: u128 = 0x{:032x};", mask)));
+        assert!(code.contains("c.is_ascii() && (MASK >> (c as u32)) & 1 == 1"));
+
+        // Few ranges and non-ASCII classes are still checked by comparisons
+        let mut output = Vec::new();
+        MatchFunction::generate_code(&class(r"[a-z_]"), 0, &mut output).unwrap();
+        assert!(!String::from_utf8(output).unwrap().contains("MASK"));
+        let mut output = Vec::new();
+        MatchFunction::generate_code(&class(r"[0-9a-zä]"), 0, &mut output).unwrap();
+        assert!(!String::from_utf8(output).unwrap().contains("MASK"));
+    }
+
     #[test]
     fn test_match_function_perl_class() {
-        let ast = Parser::new().parse(r"\d").unwrap();
-        let match_function = MatchFunction::try_from(ast).unwrap();
+        let match_function = match_function(r"\d");
         assert!(match_function.call('1'));
         assert!(!match_function.call('a'));
     }
 
     #[test]
     fn test_match_function_bracketed_class() {
-        let ast = Parser::new().parse(r"[a-z]").unwrap();
-        let match_function = MatchFunction::try_from(ast).unwrap();
+        let match_function = match_function(r"[a-z]");
         assert!(match_function.call('a'));
         assert!(match_function.call('z'));
         assert!(!match_function.call('A'));
@@ -481,8 +270,7 @@ mod tests {
     #[test]
     fn test_match_function_binary_op_class_intersection() {
         // Intersection (matching x or y)
-        let ast = Parser::new().parse(r"[a-y&&xyz]").unwrap();
-        let match_function = MatchFunction::try_from(ast).unwrap();
+        let match_function = match_function(r"[a-y&&xyz]");
         assert!(match_function.call('x'));
         assert!(match_function.call('y'));
         assert!(!match_function.call('a'));
@@ -491,8 +279,7 @@ mod tests {
 
     #[test]
     fn test_match_function_union_class() {
-        let ast = Parser::new().parse(r"[0-9a-z]").unwrap();
-        let match_function = MatchFunction::try_from(ast).unwrap();
+        let match_function = match_function(r"[0-9a-z]");
         assert!(match_function.call('a'));
         assert!(match_function.call('z'));
         assert!(match_function.call('0'));
@@ -502,8 +289,7 @@ mod tests {
 
     #[test]
     fn test_match_function_negated_bracketed_class() {
-        let ast = Parser::new().parse(r"[^a-z]").unwrap();
-        let match_function = MatchFunction::try_from(ast).unwrap();
+        let match_function = match_function(r"[^a-z]");
         assert!(!match_function.call('a'));
         assert!(!match_function.call('z'));
         assert!(match_function.call('A'));
@@ -512,8 +298,7 @@ mod tests {
 
     #[test]
     fn test_match_function_negated_binary_op_class() {
-        let ast = Parser::new().parse(r"[a-z&&[^aeiou]]").unwrap();
-        let match_function = MatchFunction::try_from(ast).unwrap();
+        let match_function = match_function(r"[a-z&&[^aeiou]]");
         assert!(!match_function.call('a'));
         assert!(!match_function.call('e'));
         assert!(match_function.call('z'));
@@ -523,11 +308,10 @@ mod tests {
     // [[:alpha:]]   ASCII character class ([A-Za-z])
     #[test]
     fn test_match_function_ascci_class() {
-        let ast = Parser::new().parse(r"[[:alpha:]]").unwrap();
-        let match_function = MatchFunction::try_from(ast).unwrap();
+        let match_function = match_function(r"[[:alpha:]]");
         assert!(match_function.call('a'));
         assert!(match_function.call('A'));
-        assert!(match_function.call('ä'));
+        assert!(!match_function.call('ä'));
         assert!(!match_function.call('1'));
         assert!(!match_function.call(' '));
     }
@@ -535,21 +319,10 @@ mod tests {
     // [[:^alpha:]]  Negated ASCII character class ([^A-Za-z])
     #[test]
     fn test_match_function_negated_ascii_class() {
-        let ast = Parser::new().parse(r"[^[:alpha:]]").unwrap();
-        let match_function = MatchFunction::try_from(ast).unwrap();
+        let match_function = match_function(r"[^[:alpha:]]");
         assert!(!match_function.call('a'));
         assert!(!match_function.call('A'));
-        assert!(!match_function.call('ä'));
-        assert!(match_function.call('1'));
-        assert!(match_function.call(' '));
-    }
-
-    #[test]
-    fn test_match_function_empty() {
-        let ast = Parser::new().parse(r"").unwrap();
-        let match_function = MatchFunction::try_from(ast).unwrap();
-        assert!(match_function.call('a'));
-        assert!(match_function.call('A'));
+        assert!(match_function.call('ä'));
         assert!(match_function.call('1'));
         assert!(match_function.call(' '));
     }
@@ -557,8 +330,7 @@ mod tests {
     // [x[^xyz]]     Nested/grouping character class (matching any character except y and z)
     #[test]
     fn test_nested_classes() {
-        let ast = Parser::new().parse(r"[x[^xyz]]").unwrap();
-        let match_function = MatchFunction::try_from(ast).unwrap();
+        let match_function = match_function(r"[x[^xyz]]");
         assert!(match_function.call('a'));
         assert!(match_function.call('x'));
         assert!(match_function.call('1'));
@@ -570,8 +342,7 @@ mod tests {
     // [0-9&&[^4]]   Subtraction using intersection and negation (matching 0-9 except 4)
     #[test]
     fn test_subtraction() {
-        let ast = Parser::new().parse(r"[0-9&&[^4]]").unwrap();
-        let match_function = MatchFunction::try_from(ast).unwrap();
+        let match_function = match_function(r"[0-9&&[^4]]");
         assert!(match_function.call('0'));
         assert!(match_function.call('9'));
         assert!(!match_function.call('4'));
@@ -581,8 +352,7 @@ mod tests {
     // [0-9--4]      Direct subtraction (matching 0-9 except 4)
     #[test]
     fn test_direct_subtraction() {
-        let ast = Parser::new().parse(r"[0-9--4]").unwrap();
-        let match_function = MatchFunction::try_from(ast).unwrap();
+        let match_function = match_function(r"[0-9--4]");
         assert!(match_function.call('0'));
         assert!(match_function.call('9'));
         assert!(!match_function.call('4'));
@@ -592,8 +362,7 @@ mod tests {
     // [a-g~~b-h]    Symmetric difference (matching `a` and `h` only)
     #[test]
     fn test_symmetric_difference() {
-        let ast = Parser::new().parse(r"[a-g~~b-h]").unwrap();
-        let match_function = MatchFunction::try_from(ast).unwrap();
+        let match_function = match_function(r"[a-g~~b-h]");
         assert!(match_function.call('a'));
         assert!(match_function.call('h'));
         assert!(!match_function.call('b'));
@@ -603,8 +372,7 @@ mod tests {
     // [\[\]]        Escaping in character classes (matching [ or ])
     #[test]
     fn test_escaping() {
-        let ast = Parser::new().parse(r"[\[\]]").unwrap();
-        let match_function = MatchFunction::try_from(ast).unwrap();
+        let match_function = match_function(r"[\[\]]");
         assert!(match_function.call('['));
         assert!(match_function.call(']'));
         assert!(!match_function.call('a'));
@@ -614,8 +382,7 @@ mod tests {
     // [a&&b]        An empty character class matching nothing
     #[test]
     fn test_empty_intersection() {
-        let ast = Parser::new().parse(r"[a&&b]").unwrap();
-        let match_function = MatchFunction::try_from(ast).unwrap();
+        let match_function = match_function(r"[a&&b]");
         assert!(!match_function.call('a'));
         assert!(!match_function.call('b'));
         assert!(!match_function.call('1'));
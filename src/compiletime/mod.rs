@@ -8,18 +8,62 @@ pub(crate) use ids::{CharClassID, PatternID, StateID};
 
 /// The parser module contains the regex syntax parser.
 mod parser;
-pub(crate) use parser::parse_regex_syntax;
+pub use parser::parse_regex_syntax;
 
 /// The generator module contains the code generator.
 /// The code generator generates code from the regex syntax.
 mod generator;
-pub use generator::generate_code;
+pub use generator::{
+    generate_code, generate_code_to_string, generate_code_with_options, generate_code_with_report,
+};
+
+/// Module that generates a standalone module for a single regex
+mod regex_code;
+pub use regex_code::generate_regex_code;
+
+/// Module that validates single patterns
+mod pattern_info;
+pub use pattern_info::{validate_pattern, PatternInfo};
+
+/// Module that derives maximal munch tests from the terminals
+mod munch_tests;
+pub use munch_tests::generate_munch_tests;
+
+/// Module that layers the terminal sets of language dialects
+mod dialects;
+pub use dialects::{DialectLayer, Dialects};
+
+/// Module that describes generated scanners in a JSON manifest
+mod manifest;
+pub use manifest::MANIFEST_FORMAT_VERSION;
+
+/// Module that scaffolds a crate with a generated scanner
+mod scaffold;
+pub use scaffold::scaffold_lexer;
+
+/// Module with generated code that is used in tests
+#[cfg(test)]
+mod generated;
+
+/// Module with the options of the code generation
+mod generate_options;
+pub use generate_options::{DuplicatePatterns, EntryPoint, GenerateOptions};
+
+/// Module that converts regex HIRs into terminals
+mod hir_import;
+pub use hir_import::{generate_code_from_hirs, terminals_from_hirs};
 
 /// The nfa module contains the NFA implementation.
 mod nfa;
 
-/// The module containing the conversions from Ast to Nfa
-mod ast;
+/// The module containing the conversions from Hir to Nfa
+mod hir;
+
+/// Module that provides the absent operator for terminals
+mod absent;
+
+/// Module that optimizes the HIR of a pattern before the NFA construction
+mod optimizer;
 
 /// Module with conversion to graphviz dot format
 mod dot;
@@ -45,9 +89,29 @@ pub(crate) use match_function::MatchFunction;
 /// Module that provides types related to DFA
 mod dfa;
 
+/// Module that provides a public DFA type for standalone use of the DFA algorithms
+mod dfa_table;
+pub use dfa_table::DfaTable;
+
 /// Module that provides types related to compiled DFAs
 mod compiled_dfa;
 
+/// Module that deduplicates identical slices of the generated DFA data
+mod shared_slices;
+
+/// Module that provides the compilation report
+mod compilation_report;
+pub use compilation_report::{
+    CompilationReport, LiteralShadowing, MissingCatchAll, PatternAlias, PatternExplosion,
+    TableSize, TerminalConflict,
+};
+
+/// Module that provides a representative alphabet for character classes.
+mod alphabet;
+
+/// Module that provides the ranges of the unicode blocks
+mod unicode_blocks;
+
 /// Module that provides code formatting
 mod rust_code_formatter;
-pub use rust_code_formatter::try_format;
+pub use rust_code_formatter::{try_format, try_format_code};
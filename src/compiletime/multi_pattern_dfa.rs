@@ -1,8 +1,39 @@
-use regex_syntax::ast::Ast;
+use std::collections::{btree_map::Entry, BTreeMap, BTreeSet, HashSet, VecDeque};
 
-use crate::{Result, ScanGenError, ScanGenErrorKind, ScannerModeData};
+use regex_syntax::hir::{ClassUnicode, Hir, HirKind};
+use tracing::{debug, debug_span, trace_span, warn};
 
-use super::{compiled_dfa::CompiledDfa, dfa::Dfa, MatchFunction, MultiPatternNfa};
+use crate::{
+    Result, ScanGenError, ScanGenErrorKind, ScannerModeData, TokenType, TABLE_FORMAT_VERSION,
+};
+
+use super::{
+    alphabet::{candidate_chars, representative_chars},
+    compiled_dfa::CompiledDfa,
+    dfa::Dfa,
+    manifest::manifest,
+    parse_regex_syntax,
+    shared_slices::{slice_bytes, SharedSlices},
+    CompilationReport, DuplicatePatterns, EntryPoint, GenerateOptions, LiteralShadowing,
+    MatchFunction, MissingCatchAll, PatternAlias, PatternExplosion, StateID, TableSize,
+    TerminalConflict,
+};
+
+/// The number of patterns that are listed when the tables exceed the size budget.
+const MAX_REPORTED_PATTERNS: usize = 5;
+
+/// A pattern is reported as exploding if its DFA has more character classes or states than this
+/// factor times the number of characters of the pattern...
+const EXPLOSION_FACTOR: usize = 4;
+/// ...and than this minimum, so short patterns with a few states aren't reported.
+const MIN_EXPLOSION_SIZE: usize = 64;
+
+// A state of the product automaton of two DFAs.
+type StatePair = (StateID, StateID);
+
+/// A keyword without a DFA of its own in a scanner mode as index of the scanner mode, token type
+/// of the terminal that matches it, text and token type of the keyword.
+type Keyword = (usize, TokenType, String, TokenType);
 
 macro_rules! unsupported {
     ($feature:expr) => {
@@ -12,6 +43,21 @@ macro_rules! unsupported {
     };
 }
 
+/// The pattern of the catch-all terminal of the error token, see
+/// [GenerateOptions::with_error_token]. It matches any single character.
+const ERROR_TOKEN_PATTERN: &str = "(?s:.)";
+
+/// The pattern of the newline terminal, see [GenerateOptions::with_auto_newline]. It matches the
+/// same line breaks as the newline terminals of parol grammars.
+const NEWLINE_TOKEN_PATTERN: &str = r"\r\n|\r|\n";
+
+/// The pattern of the whitespace terminal, see [GenerateOptions::with_auto_ws].
+const WHITESPACE_TOKEN_PATTERN: &str = r"\s+";
+
+/// The pattern of the whitespace terminal along with the newline terminal, which matches the line
+/// breaks instead.
+const WHITESPACE_TOKEN_PATTERN_WITHOUT_LINE_BREAKS: &str = r"[\s--\r\n]+";
+
 /// The `MultiPatternDfa` struct represents a multi-pattern DFA.
 /// The `MultiPatternDfa` struct can be used to match multiple pattern in parallel.
 #[derive(Default)]
@@ -19,7 +65,17 @@ pub(crate) struct MultiPatternDfa {
     /// The DFAs that are used to match the pattern. Each DFA is used to match a single pattern.
     dfas: Vec<CompiledDfa>,
     /// The match functions shared by all DFAs.
-    match_functions: Vec<(Ast, MatchFunction)>,
+    match_functions: Vec<(ClassUnicode, MatchFunction)>,
+    /// The index of the DFA of each terminal added by [MultiPatternDfa::add_patterns]. Aliases
+    /// share the DFA of the first terminal with their pattern.
+    terminal_dfas: Vec<usize>,
+    /// The terminals added by [MultiPatternDfa::add_patterns] that repeat the pattern of an
+    /// earlier terminal.
+    aliases: Vec<PatternAlias>,
+    /// The contextual keywords.
+    contextual_keywords: Vec<Keyword>,
+    /// The case-insensitive keywords like the contextual keywords, with lowercase text.
+    case_insensitive_keywords: Vec<Keyword>,
 }
 
 impl MultiPatternDfa {
@@ -33,6 +89,11 @@ impl MultiPatternDfa {
         &self.dfas
     }
 
+    /// Returns the match functions shared by all DFAs.
+    pub(crate) fn match_functions(&self) -> &[(ClassUnicode, MatchFunction)] {
+        &self.match_functions
+    }
+
     /// Add a pattern to the multi-pattern DFA.
     pub fn add_pattern<S>(&mut self, pattern: S) -> Result<()>
     where
@@ -44,16 +105,28 @@ impl MultiPatternDfa {
             return Ok(());
         }
 
-        let mut multi_pattern_nfa = MultiPatternNfa::new();
-        multi_pattern_nfa.add_pattern(pattern.as_ref())?;
+        let _span = debug_span!(
+            "pattern",
+            terminal = self.dfas.len(),
+            pattern = pattern.as_ref()
+        )
+        .entered();
 
-        // Convert the multi-pattern NFA to a DFA and minimize it.
-        let dfa: Dfa = multi_pattern_nfa.try_into()?;
+        // Convert the pattern to a DFA and minimize it. Large repetitions of a character class
+        // are counted by the scanner instead of being expanded.
+        let dfa = Dfa::try_from_pattern_with_counted_repetitions(pattern.as_ref())?;
         let minimzed_dfa = dfa.minimize()?;
 
         // Compile the minimized DFA.
         let mut compiled_dfa = CompiledDfa::new();
-        compiled_dfa.compile(&minimzed_dfa, &mut self.match_functions)?;
+        trace_span!("compile")
+            .in_scope(|| compiled_dfa.compile(&minimzed_dfa, &mut self.match_functions))?;
+        debug!(
+            states = dfa.states().len(),
+            minimized_states = minimzed_dfa.states().len(),
+            char_classes = minimzed_dfa.char_classes().len(),
+            "pattern compiled"
+        );
 
         // Add the compiled DFA to the list of DFAs.
         self.dfas.push(compiled_dfa);
@@ -63,11 +136,65 @@ impl MultiPatternDfa {
 
     /// Add multiple pattern to the multi-pattern DFA.
     pub fn add_patterns<I, S>(&mut self, pattern: I) -> Result<()>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.add_terminals(pattern, &[])
+    }
+
+    /// Adds the patterns of the terminals like [MultiPatternDfa::add_patterns], except for the
+    /// given declared aliases, see [GenerateOptions::with_terminal_aliases]. The pairs consist of
+    /// the index of the alias and the index of the terminal whose DFA it shares.
+    pub(crate) fn add_terminals<I, S>(
+        &mut self,
+        pattern: I,
+        terminal_aliases: &[(usize, usize)],
+    ) -> Result<()>
     where
         I: IntoIterator<Item = S>,
         S: AsRef<str>,
     {
         for (index, pattern) in pattern.into_iter().enumerate() {
+            if let Some((alias, target)) =
+                terminal_aliases.iter().find(|(alias, _)| *alias == index)
+            {
+                let (Some(dfa_index), true) = (self.terminal_dfas.get(*target), target < alias)
+                else {
+                    return Err(ScanGenError::new(ScanGenErrorKind::InvalidTerminalAlias {
+                        alias: *alias,
+                        target: *target,
+                    }));
+                };
+                let dfa_index = *dfa_index;
+                self.aliases.push(PatternAlias {
+                    terminal: index,
+                    alias_of: *target,
+                    pattern: self.dfas[dfa_index].pattern().to_string(),
+                    declared: true,
+                });
+                self.terminal_dfas.push(dfa_index);
+                continue;
+            }
+            if let Some(dfa_index) = self
+                .dfas
+                .iter()
+                .position(|dfa| dfa.pattern() == pattern.as_ref())
+            {
+                self.aliases.push(PatternAlias {
+                    terminal: self.terminal_dfas.len(),
+                    alias_of: self
+                        .terminal_dfas
+                        .iter()
+                        .position(|terminal_dfa| *terminal_dfa == dfa_index)
+                        .unwrap_or(dfa_index),
+                    pattern: pattern.as_ref().to_string(),
+                    declared: false,
+                });
+                self.terminal_dfas.push(dfa_index);
+                continue;
+            }
+            self.terminal_dfas.push(self.dfas.len());
             let result = self.add_pattern(pattern.as_ref()).map(|_| ());
             if let Err(ScanGenError { source }) = &result {
                 match &**source {
@@ -87,26 +214,632 @@ impl MultiPatternDfa {
         Ok(())
     }
 
+    /// Handles the terminals that repeat the pattern of an earlier terminal as configured, see
+    /// [DuplicatePatterns]. Returns the scanner modes in which the terminals, given by their index
+    /// in the patterns, are replaced by the indices of their DFAs. An alias is dropped from a
+    /// scanner mode that contains the terminal it aliases, because it can never win against it.
+    ///
+    /// Without aliases the scanner modes are returned unchanged, otherwise the default mode is
+    /// made explicit, so the token type of each terminal is still its index.
+    pub(crate) fn resolve_aliases(
+        &self,
+        scanner_mode_data: &[ScannerModeData],
+        duplicate_patterns: DuplicatePatterns,
+    ) -> Result<Vec<ScannerModeData>> {
+        if self.aliases.is_empty() {
+            return Ok(scanner_mode_data.to_vec());
+        }
+        let mut duplicates = self.aliases.iter().filter(|alias| !alias.declared);
+        match (duplicate_patterns, duplicates.next()) {
+            (_, None) | (DuplicatePatterns::Alias, _) => (),
+            (DuplicatePatterns::Error, Some(alias)) => {
+                return Err(ScanGenError::new(ScanGenErrorKind::DuplicatePattern {
+                    terminal: alias.terminal,
+                    alias_of: alias.alias_of,
+                    pattern: alias.pattern.clone(),
+                }));
+            }
+            (DuplicatePatterns::Warn, Some(alias)) => {
+                for alias in std::iter::once(alias).chain(duplicates) {
+                    warn!("{}", alias);
+                }
+            }
+        }
+        let terminal_modes = if scanner_mode_data.is_empty() {
+            let terminals = (0..self.terminal_dfas.len())
+                .map(|terminal| (terminal, TokenType::new(terminal)))
+                .collect::<Vec<_>>();
+            vec![("INITIAL", &*Vec::leak(terminals), &[][..], None)]
+        } else {
+            scanner_mode_data.to_vec()
+        };
+        // The scanner mode data borrows for 'static like the data of generated code. The data is
+        // small and the generation runs once per build.
+        Ok(terminal_modes
+            .into_iter()
+            .map(|(name, terminals, transitions, error_token_type)| {
+                let mut dfas: Vec<(usize, TokenType)> = Vec::with_capacity(terminals.len());
+                for (terminal, token_type) in terminals {
+                    let dfa_index = self
+                        .terminal_dfas
+                        .get(*terminal)
+                        .copied()
+                        .unwrap_or(*terminal);
+                    if dfas.iter().all(|(index, _)| *index != dfa_index) {
+                        dfas.push((dfa_index, *token_type));
+                    }
+                }
+                (name, &*Vec::leak(dfas), transitions, error_token_type)
+            })
+            .collect())
+    }
+
+    /// Makes the given terminals contextual keywords, see
+    /// [GenerateOptions::with_contextual_keywords]. Returns the scanner modes without the
+    /// keywords.
+    pub(crate) fn add_contextual_keywords(
+        &mut self,
+        terminals: &[usize],
+        scanner_mode_data: &[ScannerModeData],
+    ) -> Result<Vec<ScannerModeData>> {
+        let (modes, keywords) =
+            self.replace_keywords(terminals, scanner_mode_data, false, |terminal, message| {
+                ScanGenErrorKind::InvalidContextualKeyword { terminal, message }
+            })?;
+        self.contextual_keywords.extend(keywords);
+        Ok(modes)
+    }
+
+    /// Makes the given terminals case-insensitive keywords, see
+    /// [GenerateOptions::with_case_insensitive_keywords]. Returns the scanner modes without the
+    /// keywords.
+    pub(crate) fn add_case_insensitive_keywords(
+        &mut self,
+        terminals: &[usize],
+        scanner_mode_data: &[ScannerModeData],
+    ) -> Result<Vec<ScannerModeData>> {
+        let (modes, keywords) =
+            self.replace_keywords(terminals, scanner_mode_data, true, |terminal, message| {
+                ScanGenErrorKind::InvalidCaseInsensitiveKeyword { terminal, message }
+            })?;
+        self.case_insensitive_keywords.extend(keywords);
+        Ok(modes)
+    }
+
+    /// Removes the keywords of the given terminals from the scanner modes and returns the modes
+    /// along with the keywords.
+    ///
+    /// The text of case-insensitive keywords is lowercase and the other terminal must match it in
+    /// lowercase and in uppercase.
+    fn replace_keywords<E>(
+        &self,
+        terminals: &[usize],
+        scanner_mode_data: &[ScannerModeData],
+        case_insensitive: bool,
+        invalid: E,
+    ) -> Result<(Vec<ScannerModeData>, Vec<Keyword>)>
+    where
+        E: Fn(usize, String) -> ScanGenErrorKind,
+    {
+        let invalid =
+            |terminal: usize, message: String| ScanGenError::new(invalid(terminal, message));
+        let mut texts = Vec::with_capacity(terminals.len());
+        for terminal in terminals {
+            let dfa_index = if self.terminal_dfas.is_empty() {
+                *terminal
+            } else {
+                self.terminal_dfas
+                    .get(*terminal)
+                    .copied()
+                    .unwrap_or(usize::MAX)
+            };
+            let pattern = self
+                .dfas
+                .get(dfa_index)
+                .ok_or_else(|| invalid(*terminal, "The terminal doesn't exist".to_string()))?
+                .pattern();
+            let text = literal_string(&parse_regex_syntax(pattern)?).ok_or_else(|| {
+                invalid(
+                    *terminal,
+                    format!("The pattern {:?} is no literal", pattern),
+                )
+            })?;
+            let text = if case_insensitive {
+                text.to_lowercase()
+            } else {
+                text
+            };
+            texts.push((dfa_index, text));
+        }
+
+        // The scanner mode data borrows for 'static like the data of generated code. The data is
+        // small and the generation runs once per build.
+        let mut modes = Vec::with_capacity(scanner_mode_data.len().max(1));
+        let mut keywords = Vec::new();
+        for (mode_index, (name, dfas, transitions, error_token_type)) in self
+            .explicit_modes(scanner_mode_data)
+            .into_iter()
+            .enumerate()
+        {
+            let (replaced, others): (Vec<_>, Vec<_>) = dfas
+                .iter()
+                .partition(|(dfa_index, _)| texts.iter().any(|(keyword, _)| keyword == dfa_index));
+            for (dfa_index, keyword_token_type) in replaced {
+                let Some((terminal, text)) = terminals
+                    .iter()
+                    .zip(&texts)
+                    .find(|(_, (keyword, _))| *keyword == dfa_index)
+                    .map(|(terminal, (_, text))| (*terminal, text))
+                else {
+                    continue;
+                };
+                let matches = |other: usize| {
+                    let dfa = &self.dfas[other];
+                    dfa.accepts(text, &self.match_functions)
+                        && (!case_insensitive
+                            || dfa.accepts(&text.to_uppercase(), &self.match_functions))
+                };
+                let (_, token_type) = others
+                    .iter()
+                    .find(|(other, _)| matches(*other))
+                    .ok_or_else(|| {
+                        invalid(
+                            terminal,
+                            format!("No other terminal of the scanner mode {} matches it", name),
+                        )
+                    })?;
+                keywords.push((mode_index, *token_type, text.clone(), keyword_token_type));
+            }
+            modes.push((name, &*Vec::leak(others), transitions, error_token_type));
+        }
+        Ok((modes, keywords))
+    }
+
+    /// Adds the catch-all terminal of the error token with the given name, see
+    /// [GenerateOptions::with_error_token]. Returns the scanner modes in which the terminal is
+    /// appended with the lowest priority and whose error token type is the one of the error
+    /// token, as well as this token type.
+    pub(crate) fn add_error_token(
+        &mut self,
+        name: &str,
+        scanner_mode_data: &[ScannerModeData],
+    ) -> Result<(Vec<ScannerModeData>, TokenType)> {
+        validate_token_name(name)?;
+        let scanner_mode_data = self.explicit_modes(scanner_mode_data);
+        let dfa_index = self.add_injected_pattern(ERROR_TOKEN_PATTERN)?;
+        let token_type = next_token_type(&scanner_mode_data);
+        let modes = scanner_mode_data
+            .iter()
+            .map(|(name, dfas, transitions, _)| {
+                let mut dfas = dfas.to_vec();
+                if dfas.iter().all(|(index, _)| *index != dfa_index) {
+                    dfas.push((dfa_index, token_type));
+                }
+                (*name, &*Vec::leak(dfas), *transitions, Some(token_type))
+            })
+            .collect();
+        Ok((modes, token_type))
+    }
+
+    /// Adds the newline terminal with the given name, see [GenerateOptions::with_auto_newline].
+    /// Returns the scanner modes in which the terminal is prepended with the highest priority, as
+    /// well as its token type.
+    pub(crate) fn add_newline_token(
+        &mut self,
+        name: &str,
+        scanner_mode_data: &[ScannerModeData],
+    ) -> Result<(Vec<ScannerModeData>, TokenType)> {
+        validate_token_name(name)?;
+        let scanner_mode_data = self.explicit_modes(scanner_mode_data);
+        let dfa_index = self.add_injected_pattern(NEWLINE_TOKEN_PATTERN)?;
+        let token_type = next_token_type(&scanner_mode_data);
+        let modes = scanner_mode_data
+            .iter()
+            .map(|(name, dfas, transitions, error_token_type)| {
+                let mut dfas = dfas.to_vec();
+                if dfas.iter().all(|(index, _)| *index != dfa_index) {
+                    dfas.insert(0, (dfa_index, token_type));
+                }
+                (*name, &*Vec::leak(dfas), *transitions, *error_token_type)
+            })
+            .collect();
+        Ok((modes, token_type))
+    }
+
+    /// Adds the whitespace terminal with the given name, see [GenerateOptions::with_auto_ws].
+    /// Returns the scanner modes in which the terminal is appended, except for the given modes
+    /// without automatic whitespace, as well as its token type.
+    ///
+    /// If the line breaks are excluded, the whitespace terminal doesn't match them, so they are
+    /// left to the newline terminal.
+    pub(crate) fn add_whitespace_token(
+        &mut self,
+        name: &str,
+        exclude_line_breaks: bool,
+        auto_ws_off: &[String],
+        scanner_mode_data: &[ScannerModeData],
+    ) -> Result<(Vec<ScannerModeData>, TokenType)> {
+        validate_token_name(name)?;
+        let scanner_mode_data = self.explicit_modes(scanner_mode_data);
+        if let Some(unknown) = auto_ws_off
+            .iter()
+            .find(|off| scanner_mode_data.iter().all(|(name, ..)| name != off))
+        {
+            return Err(ScanGenError::new(ScanGenErrorKind::UnknownScannerMode(
+                unknown.clone(),
+            )));
+        }
+        let dfa_index = self.add_injected_pattern(if exclude_line_breaks {
+            WHITESPACE_TOKEN_PATTERN_WITHOUT_LINE_BREAKS
+        } else {
+            WHITESPACE_TOKEN_PATTERN
+        })?;
+        let token_type = next_token_type(&scanner_mode_data);
+        let modes = scanner_mode_data
+            .iter()
+            .map(|(name, dfas, transitions, error_token_type)| {
+                let mut dfas = dfas.to_vec();
+                if !auto_ws_off.iter().any(|off| off == name)
+                    && dfas.iter().all(|(index, _)| *index != dfa_index)
+                {
+                    dfas.push((dfa_index, token_type));
+                }
+                (*name, &*Vec::leak(dfas), *transitions, *error_token_type)
+            })
+            .collect();
+        Ok((modes, token_type))
+    }
+
+    /// Returns the given scanner modes, or the default mode if no scanner modes are given. In the
+    /// default mode the token type of each terminal is its index.
+    ///
+    /// The scanner mode data borrows for 'static like the data of generated code. The data is
+    /// small and the generation runs once per build.
+    fn explicit_modes(&self, scanner_mode_data: &[ScannerModeData]) -> Vec<ScannerModeData> {
+        if !scanner_mode_data.is_empty() {
+            return scanner_mode_data.to_vec();
+        }
+        let dfas = (0..self.dfas.len())
+            .map(|index| (index, TokenType::new(index)))
+            .collect::<Vec<_>>();
+        vec![("INITIAL", Vec::leak(dfas), &[], None)]
+    }
+
+    /// Returns the token type of the terminal with the given injected pattern in the first
+    /// scanner mode that contains it, if the terminal has been added by one of the generate
+    /// options.
+    fn injected_token_type(
+        &self,
+        pattern: &str,
+        scanner_mode_data: &[ScannerModeData],
+    ) -> Option<TokenType> {
+        let dfa_index = self.dfas.iter().position(|dfa| dfa.pattern() == pattern)?;
+        scanner_mode_data
+            .iter()
+            .flat_map(|(_, dfas, _, _)| dfas.iter())
+            .find(|(index, _)| *index == dfa_index)
+            .map(|(_, token_type)| *token_type)
+    }
+
+    /// Adds a pattern that the generator injects and returns the index of its DFA, which is the
+    /// one of an identical terminal if there is one.
+    fn add_injected_pattern(&mut self, pattern: &str) -> Result<usize> {
+        let pattern_count = self.dfas.len();
+        self.add_pattern(pattern)?;
+        Ok(self
+            .dfas
+            .iter()
+            .position(|dfa| dfa.pattern() == pattern)
+            .unwrap_or(pattern_count))
+    }
+
+    /// Creates the compilation report for the DFAs added so far.
+    /// If no scanner mode data is given, the default mode with all DFAs is assumed.
+    pub(crate) fn report(&self, scanner_mode_data: &[ScannerModeData]) -> CompilationReport {
+        CompilationReport {
+            conflicts: self.conflicts(),
+            literal_shadowings: self.literal_shadowings(scanner_mode_data),
+            missing_catch_alls: self.missing_catch_alls(scanner_mode_data),
+            pattern_explosions: self.pattern_explosions(),
+            aliases: self.aliases.clone(),
+            table_size: self.table_size(scanner_mode_data),
+        }
+    }
+
+    /// Returns the name and the DFA indices of each scanner mode in the order of their priority.
+    /// If no scanner mode data is given, the default mode with all DFAs is returned.
+    fn mode_dfas<'a>(
+        &self,
+        scanner_mode_data: &'a [ScannerModeData],
+    ) -> Vec<(&'a str, Vec<usize>)> {
+        if scanner_mode_data.is_empty() {
+            vec![("INITIAL", (0..self.dfas.len()).collect())]
+        } else {
+            scanner_mode_data
+                .iter()
+                .map(|(name, dfas, _, _)| (*name, dfas.iter().map(|(i, _)| *i).collect()))
+                .collect()
+        }
+    }
+
+    /// Finds the scanner modes without an error token type in which at least one character is
+    /// not matched on its own by any terminal, i.e. the scanner can't make progress on it.
+    pub(crate) fn missing_catch_alls(
+        &self,
+        scanner_mode_data: &[ScannerModeData],
+    ) -> Vec<MissingCatchAll> {
+        let candidates = candidate_chars(&self.match_functions);
+        let mut buffer = [0u8; 4];
+        self.mode_dfas(scanner_mode_data)
+            .into_iter()
+            .zip(
+                scanner_mode_data
+                    .iter()
+                    .map(|mode| mode.3)
+                    .chain(std::iter::repeat(None)),
+            )
+            .filter(|(_, error_token_type)| error_token_type.is_none())
+            .filter_map(|((mode, dfa_indices), _)| {
+                candidates
+                    .iter()
+                    .find(|c| {
+                        let text = c.encode_utf8(&mut buffer);
+                        !dfa_indices
+                            .iter()
+                            .any(|i| self.dfas[*i].accepts(text, &self.match_functions))
+                    })
+                    .map(|example| MissingCatchAll {
+                        mode: mode.to_string(),
+                        example: *example,
+                    })
+            })
+            .collect()
+    }
+
+    /// Finds the terminals whose DFAs have unusually many character classes or states relative to
+    /// the length of their patterns.
+    pub(crate) fn pattern_explosions(&self) -> Vec<PatternExplosion> {
+        self.dfas
+            .iter()
+            .enumerate()
+            .filter_map(|(terminal, dfa)| {
+                let limit =
+                    (dfa.pattern().chars().count() * EXPLOSION_FACTOR).max(MIN_EXPLOSION_SIZE);
+                let char_classes = dfa.char_classes().collect::<BTreeSet<_>>().len();
+                let states = dfa.state_count();
+                (char_classes > limit || states > limit).then(|| PatternExplosion {
+                    terminal,
+                    pattern: dfa.pattern().to_string(),
+                    char_classes,
+                    states,
+                })
+            })
+            .collect()
+    }
+
+    /// Finds all literal terminals whose string is also matched by another terminal of the same
+    /// scanner mode.
+    pub(crate) fn literal_shadowings(
+        &self,
+        scanner_mode_data: &[ScannerModeData],
+    ) -> Vec<LiteralShadowing> {
+        let literals = self
+            .dfas
+            .iter()
+            .map(|dfa| {
+                parse_regex_syntax(dfa.pattern())
+                    .ok()
+                    .and_then(|hir| literal_string(&hir))
+            })
+            .collect::<Vec<_>>();
+        let mut shadowings = Vec::new();
+        for (mode, dfa_indices) in self.mode_dfas(scanner_mode_data) {
+            for (literal_priority, literal) in dfa_indices.iter().enumerate() {
+                let Some(literal_string) = &literals[*literal] else {
+                    continue;
+                };
+                for (other_priority, other) in dfa_indices.iter().enumerate() {
+                    if other != literal
+                        && self.dfas[*other].accepts(literal_string, &self.match_functions)
+                    {
+                        shadowings.push(LiteralShadowing {
+                            mode: mode.to_string(),
+                            literal: *literal,
+                            other: *other,
+                            winner: if literal_priority < other_priority {
+                                *literal
+                            } else {
+                                *other
+                            },
+                        });
+                    }
+                }
+            }
+        }
+        shadowings
+    }
+
+    /// Finds all pairs of DFAs whose languages intersect.
+    /// For each pair the product automaton is searched breadth-first for a pair of accepting
+    /// states, which yields a shortest witness string that is accepted by both DFAs.
+    pub(crate) fn conflicts(&self) -> Vec<TerminalConflict> {
+        let alphabet = representative_chars(&self.match_functions);
+        let mut conflicts = Vec::new();
+        for (first, dfa1) in self.dfas.iter().enumerate() {
+            for (second, dfa2) in self.dfas.iter().enumerate().skip(first + 1) {
+                if let Some(witness) = self.find_witness(dfa1, dfa2, &alphabet) {
+                    conflicts.push(TerminalConflict {
+                        first,
+                        second,
+                        witness,
+                    });
+                }
+            }
+        }
+        conflicts
+    }
+
+    /// Searches the product automaton of the two DFAs for a string accepted by both.
+    /// The bounds of counted repetitions are ignored, so a conflict of a terminal with a counted
+    /// repetition may only exist for inputs that exceed its bounds.
+    fn find_witness(
+        &self,
+        dfa1: &CompiledDfa,
+        dfa2: &CompiledDfa,
+        alphabet: &[char],
+    ) -> Option<String> {
+        let start = (StateID::default(), StateID::default());
+        // Maps each visited state pair to its predecessor and the character leading to it.
+        let mut visited: BTreeMap<StatePair, Option<(StatePair, char)>> = BTreeMap::new();
+        visited.insert(start, None);
+        let mut queue = VecDeque::from([start]);
+        while let Some(pair) = queue.pop_front() {
+            if dfa1.is_accepting(pair.0) && dfa2.is_accepting(pair.1) {
+                let mut witness = Vec::new();
+                let mut current = pair;
+                while let Some(Some((predecessor, c))) = visited.get(&current) {
+                    witness.push(*c);
+                    current = *predecessor;
+                }
+                return Some(witness.into_iter().rev().collect());
+            }
+            for c in alphabet {
+                let next1 = dfa1.next_state(pair.0, *c, &self.match_functions);
+                let next2 = dfa2.next_state(pair.1, *c, &self.match_functions);
+                if let (Some(next1), Some(next2)) = (next1, next2) {
+                    let next = (next1, next2);
+                    if let Entry::Vacant(e) = visited.entry(next) {
+                        e.insert(Some((pair, *c)));
+                        queue.push_back(next);
+                    }
+                }
+            }
+        }
+        None
+    }
+
     pub(crate) fn generate_code(
         &self,
         scanner_mode_data: &[ScannerModeData],
-        scangen_module_name: Option<&str>,
+        options: &GenerateOptions,
         output: &mut dyn std::io::Write,
     ) -> Result<()> {
-        let scangen_module_name: &str = scangen_module_name.unwrap_or("scangen");
+        let _span = debug_span!(
+            "codegen",
+            dfas = self.dfas.len(),
+            modes = scanner_mode_data.len()
+        )
+        .entered();
+        for missing_catch_all in self.missing_catch_alls(scanner_mode_data) {
+            warn!("{}", missing_catch_all);
+        }
+        for pattern_explosion in self.pattern_explosions() {
+            warn!("{}", pattern_explosion);
+        }
+        if let Some(budget) = options.max_table_size() {
+            let table_size = self.table_size(scanner_mode_data);
+            if table_size.dfa_bytes > budget {
+                return Err(ScanGenError::new(ScanGenErrorKind::TableSizeExceeded {
+                    size: table_size.dfa_bytes,
+                    budget,
+                    largest: table_size
+                        .pattern_bytes
+                        .into_iter()
+                        .take(MAX_REPORTED_PATTERNS)
+                        .collect(),
+                }));
+            }
+        }
+        if let Some((terminal, _)) = options
+            .terminal_features()
+            .iter()
+            .find(|(terminal, _)| *terminal >= self.dfas.len())
+        {
+            return Err(ScanGenError::new(ScanGenErrorKind::UnknownTerminal(
+                *terminal,
+                self.dfas.len(),
+            )));
+        }
+        // The token type is only referenced by the generated scanner modes and the scanner mode id
+        // only by their transitions, unless the mode token map is generated.
+        let token_type_import = if options.generates(EntryPoint::ModeTokenMap) {
+            ", ScannerModeId, TokenType"
+        } else if scanner_mode_data.is_empty() {
+            ""
+        } else if scanner_mode_data
+            .iter()
+            .all(|(_, _, transitions, _)| transitions.is_empty())
+        {
+            ", TokenType"
+        } else {
+            ", ScannerModeId, TokenType"
+        };
+        // Only the scanners with counted repetitions reference the counted states.
+        let counted_states = self.counted_states();
+        let counted_state_import = if counted_states.is_empty() {
+            ""
+        } else {
+            "CountedStateData, "
+        };
+        let contextual_keyword_import = if self.contextual_keywords.is_empty() {
+            ""
+        } else {
+            "ContextualKeywordData, "
+        };
+        let case_insensitive_keyword_import = if self.case_insensitive_keywords.is_empty() {
+            ""
+        } else {
+            "CaseInsensitiveKeywordData, "
+        };
+        let find_matches_import = if options.generates(EntryPoint::CreateFindIter) {
+            " FindMatches,"
+        } else {
+            ""
+        };
+        let match_import = if options.generates(EntryPoint::Tokenize) {
+            " Match,"
+        } else {
+            ""
+        };
+        let scanner_import = if options.generates(EntryPoint::CreateScanner)
+            || options.generates(EntryPoint::CreateFindIter)
+            || options.generates(EntryPoint::SharedScanner)
+        {
+            " Scanner,"
+        } else {
+            ""
+        };
+        writeln!(output, "#![allow(clippy::manual_is_ascii_check)]")?;
+        if !options.prologue().is_empty() {
+            writeln!(output, "{}", options.prologue())?;
+        }
         writeln!(
             output,
-            r"#![allow(clippy::manual_is_ascii_check)]
-
- use {}::{{DfaData, FindMatches, Scanner, ScannerBuilder, ScannerModeData}};
+            r"
+ use {}::prelude::{{{}{}{}DfaData,{}{} RunClassData,{} ScannerDef, ScannerModeData, StartSetData{}{}}};
  
  ",
-            scangen_module_name
+            options.scangen_module_name(),
+            case_insensitive_keyword_import,
+            contextual_keyword_import,
+            counted_state_import,
+            find_matches_import,
+            match_import,
+            scanner_import,
+            token_type_import,
+            if options.generates(EntryPoint::Tokens) {
+                ", TokenIter"
+            } else {
+                ""
+            }
         )?;
+        let shared_slices = self.shared_slices();
+        shared_slices.generate_code(output)?;
+
         writeln!(output, "const DFAS: &[DfaData] = &[")?;
         for (index, dfa) in self.dfas.iter().enumerate() {
             writeln!(output, "    /* {} */ ", index)?;
-            dfa.generate_code(output)?;
+            dfa.generate_code(&shared_slices, output)?;
         }
         writeln!(output, "];")?;
         writeln!(output)?;
@@ -116,49 +849,646 @@ impl MultiPatternDfa {
             writeln!(output, "    /* {} */ ", index)?;
             writeln!(output, "    (\"{}\", &[", mode.0)?;
             for (dfa_index, token_type) in mode.1.iter() {
-                writeln!(output, "        ({}, {}),", dfa_index, token_type)?;
+                writeln!(
+                    output,
+                    "        ({}, TokenType::new({})),",
+                    dfa_index, token_type
+                )?;
             }
-            writeln!(output, "    ], &[").unwrap();
+            writeln!(output, "    ], &[")?;
             for (token_type, new_mode) in mode.2.iter() {
-                writeln!(output, "        ({}, {}),", token_type, new_mode)?;
+                writeln!(
+                    output,
+                    "        (TokenType::new({}), ScannerModeId::new({})),",
+                    token_type, new_mode
+                )?;
+            }
+            match mode.3 {
+                Some(error_token_type) => writeln!(
+                    output,
+                    "    ], Some(TokenType::new({}))),",
+                    error_token_type
+                )?,
+                None => writeln!(output, "    ], None),")?,
             }
-            writeln!(output, "    ]),")?;
         }
         writeln!(output, "];")?;
         writeln!(output)?;
 
+        self.generate_start_sets(scanner_mode_data, output)?;
+        self.generate_run_classes(output)?;
+        if !counted_states.is_empty() {
+            Self::generate_counted_states(&counted_states, output)?;
+        }
+
+        let disabled_dfas = !options.terminal_features().is_empty();
+        if disabled_dfas {
+            Self::generate_disabled_dfas(options, output)?;
+        }
+        if !self.contextual_keywords.is_empty() {
+            Self::generate_keywords(
+                "CONTEXTUAL_KEYWORDS: &[ContextualKeywordData]",
+                &self.contextual_keywords,
+                output,
+            )?;
+        }
+        if !self.case_insensitive_keywords.is_empty() {
+            Self::generate_keywords(
+                "CASE_INSENSITIVE_KEYWORDS: &[CaseInsensitiveKeywordData]",
+                &self.case_insensitive_keywords,
+                output,
+            )?;
+        }
+
+        // Without a generated entry point that searches, the callers need the match function to
+        // call `Scanner::find_iter` themselves.
+        let searches = options.generates(EntryPoint::CreateFindIter)
+            || options.generates(EntryPoint::Tokens)
+            || options.generates(EntryPoint::Tokenize);
         writeln!(
             output,
-            "fn matches_char_class(c: char, char_class: usize) -> bool {{"
+            "{}fn matches_char_class(c: char, char_class: usize) -> bool {{",
+            if searches { "" } else { "pub(crate) " }
         )?;
         writeln!(output, "    match char_class {{")?;
         self.match_functions
             .iter()
             .enumerate()
-            .try_for_each(|(i, (ast, _))| -> Result<()> {
-                MatchFunction::generate_code(ast, i, output)?;
+            .try_for_each(|(i, (class, _))| -> Result<()> {
+                MatchFunction::generate_code(class, i, output)?;
                 Ok(())
             })?;
         writeln!(output, "        _ => false,")?;
         writeln!(output, "    }}")?;
+        writeln!(output, "}}")?;
+        writeln!(output)?;
+
+        // The newline terminal of the auto newline option tells the runtime which line breaks end
+        // a line.
+        let newline_token_type = options
+            .auto_newline()
+            .and_then(|_| self.injected_token_type(NEWLINE_TOKEN_PATTERN, scanner_mode_data));
+        // The whitespace terminal of the auto whitespace option is skipped by the runtime.
+        let whitespace_token_type = options.auto_ws().and_then(|_| {
+            let pattern = if options.auto_newline().is_some() {
+                WHITESPACE_TOKEN_PATTERN_WITHOUT_LINE_BREAKS
+            } else {
+                WHITESPACE_TOKEN_PATTERN
+            };
+            self.injected_token_type(pattern, scanner_mode_data)
+        });
+
+        // With more than one scanner mode each mode gets its own match function that only
+        // contains the character classes used by the DFAs of the mode.
+        let mode_match_functions = scanner_mode_data.len() > 1;
+        if mode_match_functions {
+            self.generate_mode_match_functions(scanner_mode_data, output)?;
+        }
+
+        // A token profile orders the evaluation of the DFAs by the frequency of their tokens.
+        let evaluation_orders = !options.token_profile().is_empty();
+        if evaluation_orders {
+            self.generate_evaluation_orders(scanner_mode_data, options, output)?;
+        }
+
         writeln!(
             output,
-            r"}}
+            r#"pub(crate) static SCANNER_DEF: ScannerDef = ScannerDef::new(DFAS, MODES)
+    .generated_with("{}", {})
+    .with_start_sets(START_SETS)
+    .with_run_classes(RUN_CLASSES){}{}{}{}{}{}{}{};
+"#,
+            env!("CARGO_PKG_VERSION"),
+            TABLE_FORMAT_VERSION,
+            if counted_states.is_empty() {
+                ""
+            } else {
+                ".with_counted_states(COUNTED_STATES)"
+            },
+            if mode_match_functions {
+                ".with_mode_match_functions(MODE_MATCH_FUNCTIONS)"
+            } else {
+                ""
+            },
+            if evaluation_orders {
+                ".with_evaluation_orders(EVALUATION_ORDERS)"
+            } else {
+                ""
+            },
+            if disabled_dfas {
+                ".with_disabled_dfas(DISABLED_DFAS)"
+            } else {
+                ""
+            },
+            newline_token_type.map_or(String::new(), |token_type| format!(
+                ".with_newline_token_type(TokenType::new({}))",
+                token_type.as_usize()
+            )),
+            whitespace_token_type.map_or(String::new(), |token_type| format!(
+                ".with_skipped_token_types(&[TokenType::new({})])",
+                token_type.as_usize()
+            )),
+            if self.contextual_keywords.is_empty() {
+                ""
+            } else {
+                ".with_contextual_keywords(CONTEXTUAL_KEYWORDS)"
+            },
+            if self.case_insensitive_keywords.is_empty() {
+                ""
+            } else {
+                ".with_case_insensitive_keywords(CASE_INSENSITIVE_KEYWORDS)"
+            }
+        )?;
+        if options.generates(EntryPoint::ModeTokenMap) {
+            self.generate_mode_token_map(scanner_mode_data, output)?;
+        }
+        Self::generate_entry_points(options, output)?;
+        if !options.epilogue().is_empty() {
+            writeln!(output, "{}", options.epilogue())?;
+        }
+        if let Some(path) = options.manifest() {
+            std::fs::write(path, manifest(self, scanner_mode_data, options))?;
+        }
+        Ok(())
+    }
+    /// Generates the entry points of the generated module that are selected in the options.
+    fn generate_entry_points(
+        options: &GenerateOptions,
+        output: &mut dyn std::io::Write,
+    ) -> Result<()> {
+        if options.generates(EntryPoint::CreateScanner) {
+            writeln!(
+                output,
+                r"pub(crate) fn create_scanner() -> Scanner {{
+    SCANNER_DEF.create_scanner()
+}}
+"
+            )?;
+        }
+        if options.generates(EntryPoint::SharedScanner) {
+            writeln!(
+                output,
+                r"/// Returns the scanner that is shared by all callers. It is created on first use.
+pub(crate) fn scanner() -> &'static Scanner {{
+    static SCANNER: std::sync::OnceLock<Scanner> = std::sync::OnceLock::new();
+    SCANNER.get_or_init(|| SCANNER_DEF.create_scanner())
+}}
+"
+            )?;
+        }
+        if options.generates(EntryPoint::CreateFindIter) {
+            writeln!(
+                output,
+                r"pub(crate) fn create_find_iter<'h>(scanner: &Scanner, input: &'h str) -> FindMatches<'h> {{
+    scanner.find_iter(input, matches_char_class)
+}}
+"
+            )?;
+        }
+        if options.generates(EntryPoint::Tokens) {
+            writeln!(
+                output,
+                r"pub(crate) fn tokens(input: &str) -> TokenIter<'_> {{
+    SCANNER_DEF
+        .create_scanner()
+        .token_iter(input, matches_char_class)
+}}
+"
+            )?;
+        }
+        if options.generates(EntryPoint::Tokenize) {
+            writeln!(
+                output,
+                r"pub(crate) fn tokenize(input: &str) -> Vec<Match> {{
+    SCANNER_DEF
+        .create_scanner()
+        .find_iter(input, matches_char_class)
+        .collect()
+}}
 
-pub(crate) fn create_scanner() -> Scanner {{
-    ScannerBuilder::new()
-        .add_dfa_data(DFAS)
-        .add_scanner_mode_data(MODES)
-        .build()
+/// Stores all matches of the input in the given buffer, which is cleared beforehand.
+pub(crate) fn tokenize_into(input: &str, matches: &mut Vec<Match>) {{
+    SCANNER_DEF
+        .create_scanner()
+        .scan_into(input, matches_char_class, matches)
 }}
+"
+            )?;
+        }
+        Ok(())
+    }
 
-pub(crate) fn create_find_iter<'h>(scanner: &Scanner, input: &'h str) -> FindMatches<'h> {{
-    scanner.find_iter(input, matches_char_class)
+    /// Generates the token types of each scanner mode in ascending order along with the function
+    /// that maps a token type to its mode-local index, see [EntryPoint::ModeTokenMap]. The token
+    /// types are the same as the ones of [crate::ModeInfo::local_token_types].
+    fn generate_mode_token_map(
+        &self,
+        scanner_mode_data: &[ScannerModeData],
+        output: &mut dyn std::io::Write,
+    ) -> Result<()> {
+        writeln!(
+            output,
+            "pub(crate) const MODE_TOKEN_TYPES: &[&[TokenType]] = &["
+        )?;
+        for (index, (name, dfas, _, error_token_type)) in
+            self.explicit_modes(scanner_mode_data).iter().enumerate()
+        {
+            // The case-insensitive keywords are returned by the mode without a DFA of their own.
+            let token_types = dfas
+                .iter()
+                .map(|(_, token_type)| *token_type)
+                .chain(*error_token_type)
+                .chain(
+                    self.case_insensitive_keywords
+                        .iter()
+                        .filter(|(mode_index, ..)| *mode_index == index)
+                        .map(|(.., keyword_token_type)| *keyword_token_type),
+                )
+                .collect::<BTreeSet<_>>();
+            writeln!(output, "    /* {} {} */ &[", index, name)?;
+            for token_type in token_types {
+                writeln!(output, "        TokenType::new({}),", token_type.as_usize())?;
+            }
+            writeln!(output, "    ],")?;
+        }
+        writeln!(output, "];")?;
+        writeln!(
+            output,
+            r"
+/// Returns the index of the given token type among the token types of the given scanner mode.
+pub(crate) fn mode_local_index(mode: ScannerModeId, token_type: TokenType) -> Option<usize> {{
+    MODE_TOKEN_TYPES
+        .get(mode.as_usize())?
+        .binary_search(&token_type)
+        .ok()
 }}
 "
         )?;
         Ok(())
     }
+
+    /// Returns the indices of the character classes used by the DFAs of the given scanner mode.
+    fn mode_char_classes(&self, mode: &ScannerModeData) -> BTreeSet<usize> {
+        mode.1
+            .iter()
+            .flat_map(|(dfa_index, _)| self.dfas[*dfa_index].char_classes())
+            .collect()
+    }
+
+    /// Collects the slices of the DFA data, so identical slices can be shared.
+    fn shared_slices(&self) -> SharedSlices {
+        let mut shared_slices = SharedSlices::new();
+        for dfa in &self.dfas {
+            for (element_type, code, len) in dfa.slices() {
+                shared_slices.add(element_type, code, len);
+            }
+        }
+        shared_slices
+    }
+
+    /// Returns the size of the generated tables, i.e. of the DFA data and the match functions.
+    pub(crate) fn table_size(&self, scanner_mode_data: &[ScannerModeData]) -> TableSize {
+        let word = std::mem::size_of::<usize>();
+        let shared_slices = self.shared_slices();
+        let mut counted_shared = HashSet::new();
+        let mut dfa_bytes = 0;
+        let mut pattern_bytes = Vec::with_capacity(self.dfas.len());
+        for dfa in &self.dfas {
+            // The tuple of the DFA data consists of the pattern and three slices, i.e. four fat
+            // pointers.
+            let mut bytes = 8 * word + dfa.pattern().len();
+            dfa_bytes += bytes;
+            for (element_type, code, len) in dfa.slices() {
+                let slice_bytes = slice_bytes(element_type, len);
+                bytes += slice_bytes;
+                if !shared_slices.is_shared(element_type, &code)
+                    || counted_shared.insert((element_type, code))
+                {
+                    dfa_bytes += slice_bytes;
+                }
+            }
+            pattern_bytes.push((dfa.pattern().to_string(), bytes));
+        }
+        pattern_bytes.sort_by_key(|(_, bytes)| std::cmp::Reverse(*bytes));
+        let mode_match_arms = if scanner_mode_data.len() > 1 {
+            scanner_mode_data
+                .iter()
+                .map(|mode| self.mode_char_classes(mode).len())
+                .sum()
+        } else {
+            0
+        };
+        TableSize {
+            dfa_bytes,
+            match_arms: self.match_functions.len() + mode_match_arms,
+            pattern_bytes,
+        }
+    }
+
+    /// Generates one match function per scanner mode that only contains the match arms of the
+    /// character classes used by the DFAs of the mode, as well as the slice of these functions.
+    fn generate_mode_match_functions(
+        &self,
+        scanner_mode_data: &[ScannerModeData],
+        output: &mut dyn std::io::Write,
+    ) -> Result<()> {
+        for (index, mode) in scanner_mode_data.iter().enumerate() {
+            let char_classes = self.mode_char_classes(mode);
+            writeln!(output, "/* {} */", mode.0)?;
+            writeln!(
+                output,
+                "fn matches_char_class_{}(c: char, char_class: usize) -> bool {{",
+                index
+            )?;
+            writeln!(output, "    match char_class {{")?;
+            for char_class in char_classes {
+                MatchFunction::generate_code(
+                    &self.match_functions[char_class].0,
+                    char_class,
+                    output,
+                )?;
+            }
+            writeln!(output, "        _ => false,")?;
+            writeln!(output, "    }}")?;
+            writeln!(output, "}}")?;
+            writeln!(output)?;
+        }
+        writeln!(
+            output,
+            "const MODE_MATCH_FUNCTIONS: &[fn(char, usize) -> bool] = &[{}];",
+            (0..scanner_mode_data.len())
+                .map(|index| format!("matches_char_class_{}", index))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )?;
+        writeln!(output)?;
+        Ok(())
+    }
+
+    /// Returns the classes of the characters that can start a token in each scanner mode.
+    /// If no scanner mode data is given, the default mode with all DFAs is assumed.
+    fn start_classes(&self, scanner_mode_data: &[ScannerModeData]) -> Vec<ClassUnicode> {
+        let modes: Vec<Vec<usize>> = if scanner_mode_data.is_empty() {
+            vec![(0..self.dfas.len()).collect()]
+        } else {
+            scanner_mode_data
+                .iter()
+                .map(|mode| mode.1.iter().map(|(dfa_index, _)| *dfa_index).collect())
+                .collect()
+        };
+        modes
+            .iter()
+            .map(|dfa_indices| {
+                let mut class = ClassUnicode::empty();
+                for dfa_index in dfa_indices {
+                    class.union(&self.dfas[*dfa_index].start_class(&self.match_functions));
+                }
+                class
+            })
+            .collect()
+    }
+
+    /// Generates the start sets of the scanner modes, i.e. an ASCII bitmap and the non-ASCII
+    /// ranges of the characters that can start a token.
+    fn generate_start_sets(
+        &self,
+        scanner_mode_data: &[ScannerModeData],
+        output: &mut dyn std::io::Write,
+    ) -> Result<()> {
+        writeln!(output, "const START_SETS: &[StartSetData] = &[")?;
+        for (index, class) in self.start_classes(scanner_mode_data).iter().enumerate() {
+            writeln!(output, "    /* {} */ {},", index, char_set_code(class))?;
+        }
+        writeln!(output, "];")?;
+        writeln!(output)?;
+        Ok(())
+    }
+
+    /// Returns the evaluation order of the DFAs of each scanner mode, i.e. the indices of the DFAs
+    /// within the mode sorted by descending frequency of their token types in the token profile.
+    /// DFAs with equal frequencies keep their order.
+    /// If no scanner mode data is given, the default mode with all DFAs is assumed.
+    fn evaluation_orders(
+        &self,
+        scanner_mode_data: &[ScannerModeData],
+        options: &GenerateOptions,
+    ) -> Vec<Vec<usize>> {
+        let modes: Vec<Vec<TokenType>> = if scanner_mode_data.is_empty() {
+            vec![(0..self.dfas.len()).map(TokenType::new).collect()]
+        } else {
+            scanner_mode_data
+                .iter()
+                .map(|mode| mode.1.iter().map(|(_, token_type)| *token_type).collect())
+                .collect()
+        };
+        modes
+            .iter()
+            .map(|token_types| {
+                let mut order = (0..token_types.len()).collect::<Vec<_>>();
+                order.sort_by_key(|index| {
+                    std::cmp::Reverse(options.token_frequency(token_types[*index]))
+                });
+                order
+            })
+            .collect()
+    }
+
+    /// Generates the evaluation orders of the scanner modes.
+    fn generate_evaluation_orders(
+        &self,
+        scanner_mode_data: &[ScannerModeData],
+        options: &GenerateOptions,
+        output: &mut dyn std::io::Write,
+    ) -> Result<()> {
+        writeln!(output, "const EVALUATION_ORDERS: &[&[usize]] = &[")?;
+        for (index, order) in self
+            .evaluation_orders(scanner_mode_data, options)
+            .iter()
+            .enumerate()
+        {
+            writeln!(
+                output,
+                "    /* {} */ &[{}],",
+                index,
+                order
+                    .iter()
+                    .map(|dfa_index| dfa_index.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )?;
+        }
+        writeln!(output, "];")?;
+        writeln!(output)?;
+        Ok(())
+    }
+
+    /// Generates the indices of the DFAs whose terminals are disabled because none of their
+    /// features is enabled in the generated crate.
+    fn generate_disabled_dfas(
+        options: &GenerateOptions,
+        output: &mut dyn std::io::Write,
+    ) -> Result<()> {
+        let mut features: BTreeMap<usize, Vec<&str>> = BTreeMap::new();
+        for (terminal, feature) in options.terminal_features() {
+            let terminal_features = features.entry(*terminal).or_default();
+            if !terminal_features.contains(&feature.as_str()) {
+                terminal_features.push(feature);
+            }
+        }
+        writeln!(output, "const DISABLED_DFAS: &[usize] = &[")?;
+        for (terminal, terminal_features) in features {
+            let predicates = terminal_features
+                .iter()
+                .map(|feature| format!("feature = {:?}", feature))
+                .collect::<Vec<_>>();
+            match predicates.as_slice() {
+                [predicate] => writeln!(output, "    #[cfg(not({}))]", predicate)?,
+                _ => writeln!(output, "    #[cfg(not(any({})))]", predicates.join(", "))?,
+            }
+            writeln!(output, "    {},", terminal)?;
+        }
+        writeln!(output, "];")?;
+        writeln!(output)?;
+        Ok(())
+    }
+
+    /// Generates the constant of the given name and type with the contextual keywords as
+    /// [crate::ContextualKeywordData] or the case-insensitive keywords as
+    /// [crate::CaseInsensitiveKeywordData].
+    fn generate_keywords(
+        constant: &str,
+        keywords: &[Keyword],
+        output: &mut dyn std::io::Write,
+    ) -> Result<()> {
+        writeln!(output, "const {} = &[", constant)?;
+        for (mode_index, token_type, text, keyword_token_type) in keywords {
+            writeln!(
+                output,
+                "    ({}, TokenType::new({}), {:?}, TokenType::new({})),",
+                mode_index, token_type, text, keyword_token_type
+            )?;
+        }
+        writeln!(output, "];")?;
+        writeln!(output)?;
+        Ok(())
+    }
+
+    /// Returns the counted states of all DFAs as [crate::CountedStateData], i.e. the index of the
+    /// DFA, the counted state and the bounds of its repetition.
+    fn counted_states(&self) -> Vec<(usize, usize, usize, Option<usize>)> {
+        self.dfas
+            .iter()
+            .enumerate()
+            .flat_map(|(index, dfa)| {
+                dfa.counted_states()
+                    .map(move |(state, min, max)| (index, state, min, max))
+            })
+            .collect()
+    }
+
+    /// Generates the counted states of the DFAs, so the scanner checks the bounds of the
+    /// repetitions that loop in them.
+    fn generate_counted_states(
+        counted_states: &[(usize, usize, usize, Option<usize>)],
+        output: &mut dyn std::io::Write,
+    ) -> Result<()> {
+        writeln!(output, "const COUNTED_STATES: &[CountedStateData] = &[")?;
+        for counted_state in counted_states {
+            writeln!(output, "    {:?},", counted_state)?;
+        }
+        writeln!(output, "];")?;
+        writeln!(output)?;
+        Ok(())
+    }
+
+    /// Returns the indices of the character classes any DFA loops on in an accepting state
+    /// without any other transition, in ascending order.
+    fn run_classes(&self) -> BTreeSet<usize> {
+        self.dfas
+            .iter()
+            .flat_map(CompiledDfa::self_loop_classes)
+            .collect()
+    }
+
+    /// Generates the run classes, i.e. the characters of the classes the DFAs loop on, so the
+    /// scanner can consume runs of them, e.g. whitespace or identifiers, with a bitmap test.
+    fn generate_run_classes(&self, output: &mut dyn std::io::Write) -> Result<()> {
+        writeln!(output, "const RUN_CLASSES: &[RunClassData] = &[")?;
+        for char_class in self.run_classes() {
+            writeln!(
+                output,
+                "    ({}, {}),",
+                char_class,
+                char_set_code(&self.match_functions[char_class].0)
+            )?;
+        }
+        writeln!(output, "];")?;
+        writeln!(output)?;
+        Ok(())
+    }
+}
+
+/// Checks that the given name of an injected token is a Rust identifier, so it can be used as
+/// name of the constant of its token type.
+fn validate_token_name(name: &str) -> Result<()> {
+    let mut chars = name.chars();
+    if !chars.next().is_some_and(|c| c.is_alphabetic() || c == '_')
+        || !chars.all(|c| c.is_alphanumeric() || c == '_')
+        || name == "_"
+    {
+        return Err(ScanGenError::new(ScanGenErrorKind::InvalidTokenName(
+            name.to_string(),
+        )));
+    }
+    Ok(())
+}
+
+/// Returns the token type that is one more than the greatest token type of the given scanner
+/// modes.
+fn next_token_type(scanner_mode_data: &[ScannerModeData]) -> TokenType {
+    TokenType::new(
+        scanner_mode_data
+            .iter()
+            .flat_map(|(_, dfas, transitions, error_token_type)| {
+                dfas.iter()
+                    .map(|(_, token_type)| *token_type)
+                    .chain(transitions.iter().map(|(token_type, _)| *token_type))
+                    .chain(*error_token_type)
+            })
+            .map(|token_type| token_type.as_usize() + 1)
+            .max()
+            .unwrap_or_default(),
+    )
+}
+
+/// Returns the code of the given class as [crate::StartSetData], i.e. an ASCII bitmap and the
+/// non-ASCII ranges of the class.
+fn char_set_code(class: &ClassUnicode) -> String {
+    let mut ascii = 0u128;
+    let mut ranges = Vec::new();
+    for range in class.ranges() {
+        for c in range.start()..=range.end().min('\x7f') {
+            ascii |= 1 << c as u32;
+        }
+        if !range.end().is_ascii() {
+            ranges.push(format!(
+                "('{}', '{}')",
+                range.start().max('\u{80}').escape_default(),
+                range.end().escape_default()
+            ));
+        }
+    }
+    format!("(0x{:032x}, &[{}])", ascii, ranges.join(", "))
+}
+
+/// Returns the string matched by the given HIR if the HIR only consists of literals.
+fn literal_string(hir: &Hir) -> Option<String> {
+    match hir.kind() {
+        HirKind::Literal(l) => std::str::from_utf8(&l.0).ok().map(str::to_string),
+        HirKind::Capture(c) => literal_string(&c.sub),
+        HirKind::Concat(hirs) => hirs.iter().map(literal_string).collect(),
+        _ => None,
+    }
 }
 
 impl std::fmt::Debug for MultiPatternDfa {
@@ -166,3 +1496,406 @@ impl std::fmt::Debug for MultiPatternDfa {
         write!(f, "MultiPatternDfa {{ dfas: {:?} }}", self.dfas)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use regex_syntax::hir::ClassUnicodeRange;
+
+    use crate::{compiletime::character_class::single_char_class, TokenType};
+
+    #[test]
+    fn test_conflicts() {
+        let mut multi_pattern_dfa = MultiPatternDfa::new();
+        multi_pattern_dfa
+            .add_patterns(["in", "int", "[a-z]+", r"\d+", "."])
+            .unwrap();
+        let report = multi_pattern_dfa.report(&[]);
+        assert_eq!(
+            report.conflicts,
+            vec![
+                TerminalConflict {
+                    first: 0,
+                    second: 2,
+                    witness: "in".to_string()
+                },
+                TerminalConflict {
+                    first: 1,
+                    second: 2,
+                    witness: "int".to_string()
+                },
+                TerminalConflict {
+                    first: 2,
+                    second: 4,
+                    witness: "a".to_string()
+                },
+                TerminalConflict {
+                    first: 3,
+                    second: 4,
+                    witness: "0".to_string()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_resolve_aliases() {
+        let mut multi_pattern_dfa = MultiPatternDfa::new();
+        multi_pattern_dfa
+            .add_patterns(["a", "[a-z]+", "a", "b"])
+            .unwrap();
+        assert_eq!(multi_pattern_dfa.dfas().len(), 3);
+        let alias = PatternAlias {
+            terminal: 2,
+            alias_of: 0,
+            pattern: "a".to_string(),
+            declared: false,
+        };
+        assert_eq!(
+            multi_pattern_dfa.report(&[]).aliases,
+            std::slice::from_ref(&alias)
+        );
+        assert_eq!(
+            alias.to_string(),
+            "Terminal #2 \"a\" is an alias of terminal #0"
+        );
+
+        // The token types of the default mode are still the indices of the terminals
+        let modes = multi_pattern_dfa
+            .resolve_aliases(&[], DuplicatePatterns::Alias)
+            .unwrap();
+        assert_eq!(
+            modes,
+            [(
+                "INITIAL",
+                &[
+                    (0, TokenType::new(0)),
+                    (1, TokenType::new(1)),
+                    (2, TokenType::new(3))
+                ][..],
+                &[][..],
+                None
+            )]
+        );
+
+        // The terminals of the scanner modes are mapped to their DFAs
+        const MODES: &[ScannerModeData] = &[(
+            "INITIAL",
+            &[(2, TokenType::new(5)), (3, TokenType::new(6))],
+            &[],
+            None,
+        )];
+        let modes = multi_pattern_dfa
+            .resolve_aliases(MODES, DuplicatePatterns::Warn)
+            .unwrap();
+        assert_eq!(modes[0].1, [(0, TokenType::new(5)), (2, TokenType::new(6))]);
+
+        let error = multi_pattern_dfa
+            .resolve_aliases(MODES, DuplicatePatterns::Error)
+            .unwrap_err();
+        assert!(matches!(
+            *error.source,
+            ScanGenErrorKind::DuplicatePattern {
+                terminal: 2,
+                alias_of: 0,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_absent_operator() {
+        let mut multi_pattern_dfa = MultiPatternDfa::new();
+        multi_pattern_dfa
+            .add_patterns([r"<<(?~\n\.\n)\n\.\n", "(?~a"])
+            .unwrap_err();
+        multi_pattern_dfa
+            .add_patterns([r"<<(?~\n\.\n)\n\.\n"])
+            .unwrap();
+        let dfa = &multi_pattern_dfa.dfas()[0];
+        assert_eq!(dfa.pattern(), r"<<(?~\n\.\n)\n\.\n");
+        let match_functions = multi_pattern_dfa.match_functions();
+        assert!(dfa.accepts("<<a\nb\n.\n", match_functions));
+        assert!(!dfa.accepts("<<a\n.\nb\n.\n", match_functions));
+    }
+
+    #[test]
+    fn test_literal_shadowings() {
+        let mut multi_pattern_dfa = MultiPatternDfa::new();
+        multi_pattern_dfa
+            .add_patterns(["if", "[a-z]+", "(e)lse", "%on"])
+            .unwrap();
+        const MODES: &[ScannerModeData] = &[(
+            "INITIAL",
+            &[
+                (0, TokenType::new(0)),
+                (1, TokenType::new(1)),
+                (2, TokenType::new(2)),
+                (3, TokenType::new(3)),
+            ],
+            &[],
+            None,
+        )];
+        let shadowings = multi_pattern_dfa.literal_shadowings(MODES);
+        assert_eq!(
+            shadowings,
+            vec![
+                LiteralShadowing {
+                    mode: "INITIAL".to_string(),
+                    literal: 0,
+                    other: 1,
+                    winner: 0,
+                },
+                LiteralShadowing {
+                    mode: "INITIAL".to_string(),
+                    literal: 2,
+                    other: 1,
+                    winner: 1,
+                },
+            ]
+        );
+        assert!(!shadowings[0].is_literal_unreachable());
+        assert!(shadowings[1].is_literal_unreachable());
+    }
+
+    #[test]
+    fn test_missing_catch_alls() {
+        let mut multi_pattern_dfa = MultiPatternDfa::new();
+        multi_pattern_dfa
+            .add_patterns(["\\r\\n|\\r|\\n", "[a-z]+", "."])
+            .unwrap();
+        const MODES: &[ScannerModeData] = &[
+            (
+                "INITIAL",
+                &[
+                    (0, TokenType::new(0)),
+                    (1, TokenType::new(1)),
+                    (2, TokenType::new(2)),
+                ],
+                &[],
+                None,
+            ),
+            ("WORDS", &[(1, TokenType::new(1))], &[], None),
+            (
+                "CHECKED",
+                &[(1, TokenType::new(1))],
+                &[],
+                Some(TokenType::new(2)),
+            ),
+        ];
+        let missing_catch_alls = multi_pattern_dfa.missing_catch_alls(MODES);
+        assert_eq!(missing_catch_alls.len(), 1);
+        assert_eq!(missing_catch_alls[0].mode, "WORDS");
+        assert!(!missing_catch_alls[0].example.is_ascii_lowercase());
+        // The default mode contains the catch-all
+        assert!(multi_pattern_dfa.missing_catch_alls(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_pattern_explosions() {
+        let mut multi_pattern_dfa = MultiPatternDfa::new();
+        multi_pattern_dfa
+            .add_patterns([
+                "[a-z]+",
+                "[ab]*a[ab][ab][ab][ab][ab][ab][ab]",
+                "[0-9]{1,20}",
+            ])
+            .unwrap();
+        let pattern_explosions = multi_pattern_dfa.pattern_explosions();
+        assert_eq!(pattern_explosions.len(), 1);
+        assert_eq!(pattern_explosions[0].terminal, 1);
+        // The DFA has to remember the last eight characters
+        assert_eq!(pattern_explosions[0].states, 256);
+        assert_eq!(pattern_explosions[0].char_classes, 2);
+    }
+
+    #[test]
+    fn test_start_sets() {
+        let mut multi_pattern_dfa = MultiPatternDfa::new();
+        multi_pattern_dfa.add_patterns(["if", r"\d+", "π"]).unwrap();
+        const MODES: &[ScannerModeData] = &[
+            (
+                "INITIAL",
+                &[(0, TokenType::new(0)), (1, TokenType::new(1))],
+                &[],
+                None,
+            ),
+            ("GREEK", &[(2, TokenType::new(2))], &[], None),
+        ];
+        let start_classes = multi_pattern_dfa.start_classes(MODES);
+        assert_eq!(start_classes.len(), 2);
+        assert_eq!(start_classes[1], single_char_class('π'));
+        // The default mode contains all DFAs
+        assert_eq!(multi_pattern_dfa.start_classes(&[]).len(), 1);
+
+        let mut output = Vec::new();
+        multi_pattern_dfa
+            .generate_start_sets(MODES, &mut output)
+            .unwrap();
+        let code = String::from_utf8(output).unwrap();
+        let initial = (1u128 << b'i') | (0x3ffu128 << b'0');
+        assert!(code.contains(&format!("/* 0 */ (0x{:032x}, &[", initial)));
+        assert!(code.contains(
+            "/* 1 */ (0x00000000000000000000000000000000, &[('\\u{3c0}', '\\u{3c0}')]),"
+        ));
+    }
+
+    #[test]
+    fn test_mode_match_functions() {
+        let mut multi_pattern_dfa = MultiPatternDfa::new();
+        multi_pattern_dfa.add_patterns(["a", "b"]).unwrap();
+        const MODES: &[ScannerModeData] = &[
+            ("A", &[(0, TokenType::new(0))], &[], None),
+            ("B", &[(1, TokenType::new(1))], &[], None),
+        ];
+        let mut output = Vec::new();
+        multi_pattern_dfa
+            .generate_mode_match_functions(MODES, &mut output)
+            .unwrap();
+        let code = String::from_utf8(output).unwrap();
+        let (mode_a, mode_b) = code.split_once("/* B */").unwrap();
+        assert!(mode_a.contains("c == 'a'"));
+        assert!(!mode_a.contains("c == 'b'"));
+        assert!(mode_b.contains("c == 'b'"));
+        assert!(!mode_b.contains("c == 'a'"));
+        assert!(code.contains(
+            "const MODE_MATCH_FUNCTIONS: &[fn(char, usize) -> bool] = \
+             &[matches_char_class_0, matches_char_class_1];"
+        ));
+    }
+
+    #[test]
+    fn test_run_classes() {
+        let mut multi_pattern_dfa = MultiPatternDfa::new();
+        multi_pattern_dfa
+            .add_patterns([r"[ \t]+", r"[a-z][a-z0-9]*", "ab", r"[0-9]+\.[0-9]+"])
+            .unwrap();
+        let run_classes = multi_pattern_dfa
+            .run_classes()
+            .into_iter()
+            .map(|char_class| multi_pattern_dfa.match_functions()[char_class].0.clone())
+            .collect::<Vec<_>>();
+        // The digits before the dot are no run class because the state has another transition
+        assert_eq!(run_classes.len(), 3);
+        for expected in [
+            ClassUnicode::new([
+                ClassUnicodeRange::new('\t', '\t'),
+                ClassUnicodeRange::new(' ', ' '),
+            ]),
+            ClassUnicode::new([
+                ClassUnicodeRange::new('0', '9'),
+                ClassUnicodeRange::new('a', 'z'),
+            ]),
+            ClassUnicode::new([ClassUnicodeRange::new('0', '9')]),
+        ] {
+            assert!(run_classes.contains(&expected));
+        }
+
+        let mut output = Vec::new();
+        multi_pattern_dfa.generate_run_classes(&mut output).unwrap();
+        let code = String::from_utf8(output).unwrap();
+        assert!(code.starts_with("const RUN_CLASSES: &[RunClassData] = &["));
+        assert!(code.contains(&format!(
+            ", (0x{:032x}, &[])),",
+            (1u128 << b' ') | (1 << b'\t')
+        )));
+    }
+
+    #[test]
+    fn test_table_size() {
+        let mut multi_pattern_dfa = MultiPatternDfa::new();
+        multi_pattern_dfa
+            .add_patterns(["a", "b", "[a-z]+"])
+            .unwrap();
+        let word = std::mem::size_of::<usize>();
+        let table_size = multi_pattern_dfa.table_size(&[]);
+        assert_eq!(
+            table_size.pattern_bytes,
+            vec![
+                ("[a-z]+".to_string(), 17 * word + 6),
+                ("a".to_string(), 15 * word + 1),
+                ("b".to_string(), 15 * word + 1),
+            ]
+        );
+        // The state ranges of "a" and "b" are shared and thus counted once
+        assert_eq!(table_size.dfa_bytes, 47 * word + 8 - 4 * word);
+        assert_eq!(
+            table_size.match_arms,
+            multi_pattern_dfa.match_functions().len()
+        );
+    }
+
+    #[test]
+    fn test_evaluation_orders() {
+        let mut multi_pattern_dfa = MultiPatternDfa::new();
+        multi_pattern_dfa
+            .add_patterns(["if", "[a-z]+", r"\s+", "."])
+            .unwrap();
+        const MODES: &[ScannerModeData] = &[
+            (
+                "INITIAL",
+                &[
+                    (0, TokenType::new(1)),
+                    (1, TokenType::new(2)),
+                    (2, TokenType::new(3)),
+                    (3, TokenType::new(4)),
+                ],
+                &[],
+                None,
+            ),
+            (
+                "WORDS",
+                &[(1, TokenType::new(2)), (0, TokenType::new(1))],
+                &[],
+                None,
+            ),
+        ];
+        let options = GenerateOptions::new().with_token_profile(&[
+            (TokenType::new(3), 500),
+            (TokenType::new(2), 300),
+            (TokenType::new(1), 300),
+        ]);
+        assert_eq!(
+            multi_pattern_dfa.evaluation_orders(MODES, &options),
+            vec![vec![2, 0, 1, 3], vec![0, 1]]
+        );
+        // In the default mode the token types are the indices of the DFAs
+        assert_eq!(
+            multi_pattern_dfa.evaluation_orders(&[], &options),
+            vec![vec![3, 1, 2, 0]]
+        );
+
+        let mut output = Vec::new();
+        multi_pattern_dfa
+            .generate_code(MODES, &options, &mut output)
+            .unwrap();
+        let code = String::from_utf8(output).unwrap();
+        assert!(code.contains("    /* 0 */ &[2, 0, 1, 3],\n    /* 1 */ &[0, 1],\n"));
+        assert!(code.contains(".with_evaluation_orders(EVALUATION_ORDERS);"));
+    }
+
+    #[test]
+    fn test_counted_states() {
+        let mut multi_pattern_dfa = MultiPatternDfa::new();
+        multi_pattern_dfa
+            .add_patterns([r"[a-z]+", r"\d{1,100}"])
+            .unwrap();
+        assert_eq!(
+            multi_pattern_dfa.counted_states(),
+            vec![(1, 1, 1, Some(100))]
+        );
+        // The counted state is no run class, the scanner has to count its characters
+        assert_eq!(multi_pattern_dfa.run_classes().len(), 1);
+
+        let mut output = Vec::new();
+        multi_pattern_dfa
+            .generate_code(&[], &GenerateOptions::new(), &mut output)
+            .unwrap();
+        let code = String::from_utf8(output).unwrap();
+        assert!(code.contains("prelude::{CountedStateData, DfaData,"));
+        assert!(code.contains(
+            "const COUNTED_STATES: &[CountedStateData] = &[\n    (1, 1, 1, Some(100)),\n];"
+        ));
+        assert!(code.contains(".with_counted_states(COUNTED_STATES)"));
+    }
+}
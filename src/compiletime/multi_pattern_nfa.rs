@@ -1,10 +1,12 @@
 use std::collections::BTreeMap;
 
+use tracing::trace_span;
+
 use crate::{Result, ScanGenError, ScanGenErrorKind};
 
 use super::{
-    character_class::ComparableAst,
-    nfa::{EpsilonTransition, Nfa},
+    nfa::{EpsilonTransition, Nfa, RepetitionCounter},
+    optimizer::optimize,
     parse_regex_syntax, CharClassID, CharacterClass, PatternID, StateID,
 };
 
@@ -23,6 +25,7 @@ pub(crate) struct MultiPatternNfa {
     pub(crate) pattern: Vec<String>,
     pub(crate) accepting_states: BTreeMap<StateID, PatternID>,
     pub(crate) char_classes: Vec<CharacterClass>,
+    pub(crate) counters: BTreeMap<StateID, RepetitionCounter>,
 }
 
 impl MultiPatternNfa {
@@ -33,6 +36,7 @@ impl MultiPatternNfa {
             pattern: Vec::new(),
             accepting_states: BTreeMap::new(),
             char_classes: Vec::new(),
+            counters: BTreeMap::new(),
         }
     }
 
@@ -58,6 +62,19 @@ impl MultiPatternNfa {
 
     /// Add a pattern to the multi-pattern NFA.
     pub fn add_pattern(&mut self, pattern: &str) -> Result<PatternID> {
+        self.add_pattern_with(pattern, false)
+    }
+
+    /// Add a pattern to the multi-pattern NFA whose large repetitions of a single character class
+    /// are counted instead of expanded, see [Nfa::try_from_hir].
+    pub(crate) fn add_pattern_with_counted_repetitions(
+        &mut self,
+        pattern: &str,
+    ) -> Result<PatternID> {
+        self.add_pattern_with(pattern, true)
+    }
+
+    fn add_pattern_with(&mut self, pattern: &str, count_repetitions: bool) -> Result<PatternID> {
         if let Some(id) = self.pattern.iter().position(|p| p == pattern) {
             // If the pattern already exists, return the terminal id
             // Not sure if this should rather be an error
@@ -65,8 +82,11 @@ impl MultiPatternNfa {
         }
 
         let pattern_id = PatternID::new(self.pattern.len());
-        let mut nfa: Nfa = parse_regex_syntax(pattern)?.try_into()?;
+        let hir = optimize(parse_regex_syntax(pattern)?);
+        let _span = trace_span!("nfa", pattern, count_repetitions).entered();
+        let mut nfa = Nfa::try_from_hir(hir, count_repetitions)?;
         nfa.set_pattern(pattern);
+        nfa.make_classes_disjoint();
         self.pattern.push(pattern.to_string());
 
         // Shift the state ids of the given NFA
@@ -81,7 +101,8 @@ impl MultiPatternNfa {
         self.nfa
             .add_epsilon_transition(StateID::default(), nfa.start_state());
 
-        // Move the states of the given NFA to the own NFA
+        // Move the states and the counters of the given NFA to the own NFA
+        self.counters.append(&mut nfa.counters);
         self.nfa.append(&mut self.char_classes, nfa);
 
         Ok(pattern_id)
@@ -189,7 +210,7 @@ impl NfaWithCharClasses {
             state.transitions().iter().for_each(|transition| {
                 if let Some(class_id) = char_classes
                     .iter()
-                    .position(|c| c.ast == ComparableAst(transition.chars().clone()))
+                    .position(|c| c.class == *transition.chars())
                 {
                     new_state.transitions.push(MultiNfaTransition {
                         chars: CharClassID::new(class_id),
@@ -363,7 +384,7 @@ mod tests {
             multi_pattern_nfa.accepting_states(),
             &[
                 (StateID::new(11), PatternID::new(0)),
-                (StateID::new(25), PatternID::new(1))
+                (StateID::new(19), PatternID::new(1))
             ]
             .iter()
             .cloned()
@@ -0,0 +1,340 @@
+//! This module derives a maximal munch test suite from the compiled DFAs of the terminals.
+
+use std::collections::{btree_map::Entry, BTreeMap, BTreeSet, VecDeque};
+
+use regex_syntax::hir::ClassUnicode;
+
+use crate::{Result, ScannerModeData, ScannerModeId, TokenType};
+
+use super::{
+    alphabet::representative_chars, compiled_dfa::CompiledDfa, DuplicatePatterns, MatchFunction,
+    MultiPatternDfa, StateID,
+};
+
+/// A pair of terminals of a scanner mode where a string accepted by the shorter terminal is a
+/// proper prefix of a string accepted by the longer terminal, e.g. `in` and `int`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct PrefixOverlap {
+    /// The index of the scanner mode.
+    mode: usize,
+    /// The index of the terminal that accepts the prefix.
+    shorter: usize,
+    /// The index of the terminal that accepts the extended string.
+    longer: usize,
+    /// A shortest string accepted by the shorter terminal.
+    prefix: String,
+    /// A shortest non-empty extension of the prefix that the longer terminal accepts.
+    extension: String,
+}
+
+// A state of the product automaton of two DFAs.
+type StatePair = (StateID, StateID);
+
+/// A scanner mode given by its name, its DFAs and token types in the order of their priority
+/// and its transitions.
+type Mode<'a> = (
+    &'a str,
+    Vec<(usize, TokenType)>,
+    &'a [(TokenType, ScannerModeId)],
+);
+
+/// Generates a test module that checks the tie-breaking of the generated scanner on adversarial
+/// boundary inputs, i.e. on the maximal munch cases of the terminals.
+///
+/// For each pair of terminals of a scanner mode where a string of one terminal is a proper
+/// prefix of a string of the other one, like `%sc` and `%scanner` or `in` and `int`, the prefix
+/// and the extended string are scanned in that mode. The expected tokens are derived from the
+/// compiled DFAs, so the suite records the current tie-breaking and detects when a change of the
+/// terminals or of `scangen` changes it. Inputs that contain characters no terminal matches are
+/// left out.
+///
+/// The arguments are the same as for [crate::generate_code]. `scanner_module` is the path of
+/// the module generated from them, e.g. `crate::lexer`. The generated tests use its entry points
+/// `create_scanner` and `create_find_iter`, so the test module should be included with
+/// `#[cfg(test)]`.
+///
+/// # Errors
+/// An error is returned if a pattern contains unsupported syntax.
+pub fn generate_munch_tests(
+    pattern: &[&str],
+    scanner_mode_data: &[ScannerModeData],
+    scanner_module: &str,
+    output: &mut dyn std::io::Write,
+) -> Result<()> {
+    let mut multi_pattern_dfa = MultiPatternDfa::new();
+    multi_pattern_dfa.add_patterns(pattern)?;
+    let scanner_mode_data =
+        multi_pattern_dfa.resolve_aliases(scanner_mode_data, DuplicatePatterns::Alias)?;
+    let modes = modes(&multi_pattern_dfa, &scanner_mode_data);
+
+    writeln!(
+        output,
+        "//! Maximal munch tests generated by scangen from the terminals of `{}`.",
+        scanner_module
+    )?;
+    writeln!(output)?;
+    writeln!(
+        output,
+        "use {}::{{create_find_iter, create_scanner}};",
+        scanner_module
+    )?;
+    writeln!(output)?;
+    writeln!(
+        output,
+        "/// An input with the scanner mode it is scanned in and the expected tokens as pairs of \
+        token type and text."
+    )?;
+    writeln!(
+        output,
+        "type Case = (&'static str, usize, &'static [(usize, &'static str)]);"
+    )?;
+    writeln!(output)?;
+    writeln!(output, "const CASES: &[Case] = &[")?;
+    let mut generated = BTreeSet::new();
+    for overlap in prefix_overlaps(&multi_pattern_dfa, &modes) {
+        let extended = format!("{}{}", overlap.prefix, overlap.extension);
+        let cases = [overlap.prefix.as_str(), extended.as_str()]
+            .into_iter()
+            .filter_map(|input| {
+                let tokens = tokenize(&multi_pattern_dfa, &modes, overlap.mode, input)?;
+                generated
+                    .insert((overlap.mode, input.to_string()))
+                    .then_some((input, tokens))
+            })
+            .collect::<Vec<_>>();
+        if cases.is_empty() {
+            continue;
+        }
+        writeln!(
+            output,
+            "    // Mode {}: {:?} of terminal #{} is a prefix of {:?} of terminal #{}",
+            modes[overlap.mode].0, overlap.prefix, overlap.shorter, extended, overlap.longer
+        )?;
+        for (input, tokens) in cases {
+            writeln!(
+                output,
+                "    ({:?}, {}, &[{}]),",
+                input,
+                overlap.mode,
+                tokens
+                    .iter()
+                    .map(|(token_type, text)| format!("({}, {:?})", token_type, text))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )?;
+        }
+    }
+    writeln!(output, "];")?;
+    writeln!(
+        output,
+        r#"
+#[test]
+fn test_maximal_munch() {{
+    for (input, mode, expected) in CASES {{
+        let mut scanner = create_scanner();
+        scanner.set_mode((*mode).into());
+        let tokens = create_find_iter(&scanner, input)
+            .map(|m| (m.token_type().as_usize(), &input[m.range()]))
+            .collect::<Vec<_>>();
+        assert_eq!(tokens, *expected, "Input {{:?}} in scanner mode {{}}", input, mode);
+    }}
+}}"#
+    )?;
+    Ok(())
+}
+
+/// Returns the scanner modes. If no scanner mode data is given, the default mode with all DFAs
+/// is returned, in which the token type of each DFA is its index.
+fn modes<'a>(
+    multi_pattern_dfa: &MultiPatternDfa,
+    scanner_mode_data: &'a [ScannerModeData],
+) -> Vec<Mode<'a>> {
+    if scanner_mode_data.is_empty() {
+        let dfas = (0..multi_pattern_dfa.dfas().len())
+            .map(|index| (index, TokenType::new(index)))
+            .collect();
+        vec![("INITIAL", dfas, &[])]
+    } else {
+        scanner_mode_data
+            .iter()
+            .map(|(name, dfas, transitions, _)| (*name, dfas.to_vec(), *transitions))
+            .collect()
+    }
+}
+
+/// Finds the prefix overlaps of all pairs of different terminals of each scanner mode.
+fn prefix_overlaps(multi_pattern_dfa: &MultiPatternDfa, modes: &[Mode]) -> Vec<PrefixOverlap> {
+    let match_functions = multi_pattern_dfa.match_functions();
+    let alphabet = representative_chars(match_functions);
+    let dfas = multi_pattern_dfa.dfas();
+    let mut overlaps = Vec::new();
+    for (mode, (_, mode_dfas, _)) in modes.iter().enumerate() {
+        for (shorter, _) in mode_dfas {
+            for (longer, _) in mode_dfas {
+                if shorter == longer {
+                    continue;
+                }
+                if let Some((prefix, extension)) =
+                    find_prefix_overlap(&dfas[*shorter], &dfas[*longer], &alphabet, match_functions)
+                {
+                    overlaps.push(PrefixOverlap {
+                        mode,
+                        shorter: *shorter,
+                        longer: *longer,
+                        prefix,
+                        extension,
+                    });
+                }
+            }
+        }
+    }
+    overlaps
+}
+
+/// Searches the product automaton of the two DFAs breadth-first for a shortest string that the
+/// first DFA accepts and the second one can extend to a longer string it accepts. Returns the
+/// string and a shortest extension.
+fn find_prefix_overlap(
+    shorter: &CompiledDfa,
+    longer: &CompiledDfa,
+    alphabet: &[char],
+    match_functions: &[(ClassUnicode, MatchFunction)],
+) -> Option<(String, String)> {
+    let start = (StateID::default(), StateID::default());
+    // Maps each visited state pair to its predecessor and the character leading to it.
+    let mut visited: BTreeMap<StatePair, Option<(StatePair, char)>> = BTreeMap::new();
+    visited.insert(start, None);
+    let mut queue = VecDeque::from([start]);
+    while let Some(pair) = queue.pop_front() {
+        if pair != start && shorter.is_accepting(pair.0) {
+            if let Some(extension) = shortest_extension(longer, pair.1, alphabet, match_functions) {
+                let mut prefix = Vec::new();
+                let mut current = pair;
+                while let Some(Some((predecessor, c))) = visited.get(&current) {
+                    prefix.push(*c);
+                    current = *predecessor;
+                }
+                return Some((prefix.into_iter().rev().collect(), extension));
+            }
+        }
+        for c in alphabet {
+            let next1 = shorter.next_state(pair.0, *c, match_functions);
+            let next2 = longer.next_state(pair.1, *c, match_functions);
+            if let (Some(next1), Some(next2)) = (next1, next2) {
+                let next = (next1, next2);
+                if let Entry::Vacant(e) = visited.entry(next) {
+                    e.insert(Some((pair, *c)));
+                    queue.push_back(next);
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Returns a shortest non-empty string that leads the DFA from the given state to an accepting
+/// state.
+fn shortest_extension(
+    dfa: &CompiledDfa,
+    state: StateID,
+    alphabet: &[char],
+    match_functions: &[(ClassUnicode, MatchFunction)],
+) -> Option<String> {
+    let mut visited: BTreeMap<StateID, (Option<StateID>, char)> = BTreeMap::new();
+    let mut queue = VecDeque::new();
+    for c in alphabet {
+        if let Some(next) = dfa.next_state(state, *c, match_functions) {
+            if let Entry::Vacant(e) = visited.entry(next) {
+                e.insert((None, *c));
+                queue.push_back(next);
+            }
+        }
+    }
+    while let Some(current) = queue.pop_front() {
+        if dfa.is_accepting(current) {
+            let mut extension = Vec::new();
+            let mut step = Some(current);
+            while let Some((predecessor, c)) = step.and_then(|state| visited.get(&state)) {
+                extension.push(*c);
+                step = *predecessor;
+            }
+            return Some(extension.into_iter().rev().collect());
+        }
+        for c in alphabet {
+            if let Some(next) = dfa.next_state(current, *c, match_functions) {
+                if let Entry::Vacant(e) = visited.entry(next) {
+                    e.insert((Some(current), *c));
+                    queue.push_back(next);
+                }
+            }
+        }
+    }
+    None
+}
+
+/// Tokenizes the input like the generated scanner, starting in the given scanner mode: the
+/// longest match wins and ties are broken by the priority of the terminals in the mode. Mode
+/// transitions are followed. Returns None if a character of the input isn't matched.
+fn tokenize<'h>(
+    multi_pattern_dfa: &MultiPatternDfa,
+    modes: &[Mode],
+    mut mode: usize,
+    input: &'h str,
+) -> Option<Vec<(usize, &'h str)>> {
+    let dfas = multi_pattern_dfa.dfas();
+    let match_functions = multi_pattern_dfa.match_functions();
+    let mut tokens = Vec::new();
+    let mut rest = input;
+    while !rest.is_empty() {
+        let (_, mode_dfas, transitions) = modes.get(mode)?;
+        let boundaries = rest
+            .char_indices()
+            .map(|(index, _)| index)
+            .skip(1)
+            .chain(std::iter::once(rest.len()));
+        let (len, token_type) = boundaries
+            .flat_map(|len| {
+                mode_dfas
+                    .iter()
+                    .filter(move |(dfa, _)| dfas[*dfa].accepts(&rest[..len], match_functions))
+                    .map(move |(_, token_type)| (len, *token_type))
+            })
+            // The first terminal in the mode wins among the longest matches.
+            .fold(
+                None,
+                |longest: Option<(usize, TokenType)>, (len, token_type)| match longest {
+                    Some((longest_len, _)) if longest_len >= len => longest,
+                    _ => Some((len, token_type)),
+                },
+            )?;
+        tokens.push((token_type.as_usize(), &rest[..len]));
+        rest = &rest[len..];
+        if let Some((_, target)) = transitions.iter().find(|(term, _)| *term == token_type) {
+            mode = target.as_usize();
+        }
+    }
+    Some(tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_munch_tests() {
+        let patterns = ["in", "int", "[a-z]+", r"\s+", "%sc", "%scanner"];
+        let mut output = Vec::new();
+        generate_munch_tests(&patterns, &[], "crate::lexer", &mut output).unwrap();
+        let code = String::from_utf8(output).unwrap();
+        assert!(code.contains("use crate::lexer::{create_find_iter, create_scanner};"));
+        assert!(code.contains(
+            "    // Mode INITIAL: \"in\" of terminal #0 is a prefix of \"int\" of terminal #1\n    \
+            (\"in\", 0, &[(0, \"in\")]),\n    (\"int\", 0, &[(1, \"int\")]),\n"
+        ));
+        assert!(code.contains("    (\"%sc\", 0, &[(4, \"%sc\")]),\n"));
+        assert!(code.contains("    (\"%scanner\", 0, &[(5, \"%scanner\")]),\n"));
+        // The identifier extends `in` as well, but the case is generated only once
+        assert_eq!(code.matches("(\"int\", 0,").count(), 1);
+        assert!(code.contains("fn test_maximal_munch()"));
+    }
+}
@@ -2,11 +2,14 @@
 //! The NFA is used to represent the regex syntax as a finite automaton.
 //! The NFA is later converted to a DFA (Deterministic Finite Automaton) for matching strings.
 
-use std::vec;
+use std::{collections::BTreeMap, vec};
 
-use regex_syntax::ast::Ast;
+use regex_syntax::hir::ClassUnicode;
 
-use super::StateID;
+use super::{
+    character_class::{disjoint_classes, ClassLabels},
+    StateID,
+};
 
 #[derive(Debug, Clone, Default)]
 pub(crate) struct Nfa {
@@ -16,6 +19,28 @@ pub(crate) struct Nfa {
     pub(crate) start_state: StateID,
     // Used during NFA construction
     pub(crate) end_state: StateID,
+    // The counters of the counted repetitions, keyed by the state that loops on the repeated
+    // character class
+    pub(crate) counters: BTreeMap<StateID, RepetitionCounter>,
+}
+
+/// The bounds of a repetition of a single character class that is matched by counting the
+/// repeated characters instead of expanding the NFA, see [Nfa::counted_repetition].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct RepetitionCounter {
+    /// The minimum number of repetitions.
+    pub(crate) min: usize,
+    /// The maximum number of repetitions, if bounded.
+    pub(crate) max: Option<usize>,
+}
+
+impl std::fmt::Display for RepetitionCounter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.max {
+            Some(max) => write!(f, "{}..={}", self.min, max),
+            None => write!(f, "{}..", self.min),
+        }
+    }
 }
 
 impl Nfa {
@@ -25,7 +50,28 @@ impl Nfa {
             states: vec![NfaState::default()],
             start_state: StateID::default(),
             end_state: StateID::default(),
+            counters: BTreeMap::new(),
+        }
+    }
+
+    /// Creates the NFA of a repetition of the given character class, which loops on the class
+    /// in a single state instead of duplicating the NFA of the class for each repetition.
+    /// The bounds of the repetition are checked by counting the characters of the loop while
+    /// matching.
+    pub(crate) fn counted_repetition(chars: ClassUnicode, counter: RepetitionCounter) -> Self {
+        let mut nfa = Nfa::new();
+        let start_state = nfa.start_state();
+        let loop_state = nfa.new_state();
+        let end_state = nfa.new_state();
+        nfa.add_transition(start_state, chars.clone(), loop_state);
+        nfa.add_transition(loop_state, chars, loop_state);
+        nfa.add_epsilon_transition(loop_state, end_state);
+        if counter.min == 0 {
+            nfa.add_epsilon_transition(start_state, end_state);
         }
+        nfa.set_end_state(end_state);
+        nfa.counters.insert(loop_state, counter);
+        nfa
     }
 
     // Returns true if the NFA is empty, i.e. no states and no transitions have been added.
@@ -69,7 +115,12 @@ impl Nfa {
         self.end_state = state;
     }
 
-    pub(crate) fn add_transition(&mut self, from: StateID, chars: Ast, target_state: StateID) {
+    pub(crate) fn add_transition(
+        &mut self,
+        from: StateID,
+        chars: ClassUnicode,
+        target_state: StateID,
+    ) {
         self.states[from].transitions.push(NfaTransition {
             chars,
             target_state,
@@ -95,6 +146,10 @@ impl Nfa {
         }
         self.start_state = StateID::new(self.start_state.as_usize() + offset);
         self.end_state = StateID::new(self.end_state.as_usize() + offset);
+        self.counters = std::mem::take(&mut self.counters)
+            .into_iter()
+            .map(|(state, counter)| (StateID::new(state.as_usize() + offset), counter))
+            .collect();
         (self.start_state, self.end_state)
     }
 
@@ -106,6 +161,7 @@ impl Nfa {
             self.set_start_state(nfa.start_state);
             self.set_end_state(nfa.end_state);
             self.states = nfa.states;
+            self.counters = nfa.counters;
             return;
         }
 
@@ -128,6 +184,7 @@ impl Nfa {
             self.set_start_state(nfa.start_state);
             self.set_end_state(nfa.end_state);
             self.states = nfa.states;
+            self.counters = nfa.counters;
             return;
         }
 
@@ -209,6 +266,7 @@ impl Nfa {
     /// Move the states of the given NFA to the current NFA and thereby consume the NFA.
     pub(crate) fn append(&mut self, mut nfa: Nfa) {
         self.states.append(nfa.states.as_mut());
+        self.counters.append(&mut nfa.counters);
         // Check the index constraints
         debug_assert!(self
             .states
@@ -216,6 +274,107 @@ impl Nfa {
             .enumerate()
             .all(|(i, s)| s.id().as_usize() == i));
     }
+
+    /// Splits the character classes of all transitions into disjoint classes.
+    /// The subset construction treats each character class as a distinct input symbol, so a
+    /// character must not be matched by more than one class, e.g. the classes `[ab]` and `a` of
+    /// the pattern `(a|b)*abb` are replaced by the classes `a` and `b`.
+    pub(crate) fn make_classes_disjoint(&mut self) {
+        let disjoint_classes = disjoint_classes(
+            self.states
+                .iter()
+                .flat_map(|s| s.transitions.iter())
+                .map(|transition| &transition.chars),
+        );
+        for state in self.states.iter_mut() {
+            state.transitions = state
+                .transitions
+                .iter()
+                .flat_map(|transition| {
+                    disjoint_classes
+                        .iter()
+                        .filter(|class| {
+                            let mut intersection = (*class).clone();
+                            intersection.intersect(&transition.chars);
+                            !intersection.ranges().is_empty()
+                        })
+                        .map(|class| NfaTransition {
+                            chars: class.clone(),
+                            target_state: transition.target_state,
+                        })
+                })
+                .collect();
+        }
+    }
+}
+
+/// Renders the NFA as a compact state table that is meant to be read by users, e.g. in
+/// documentation and error messages.
+///
+/// Each state is printed on its own line with its transitions, labeled with the character classes
+/// they match or `ε` for epsilon transitions. Long classes are labeled by number and listed after
+/// the states. The start state is marked with `>` and the end state is marked with `*`.
+///
+/// ```text
+/// NFA with 6 states for pattern a*b
+///      0: a -> 1
+///      1: ε -> 3, ε -> 0
+///   >  2: ε -> 0, ε -> 1
+///      3: ε -> 4
+///      4: b -> 5
+///    * 5: no transitions
+/// ```
+impl std::fmt::Display for Nfa {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "NFA with {} state{}",
+            self.states.len(),
+            if self.states.len() == 1 { "" } else { "s" },
+        )?;
+        if self.pattern.is_empty() {
+            writeln!(f)?;
+        } else {
+            writeln!(f, " for pattern {}", self.pattern)?;
+        }
+        let mut labels = ClassLabels::default();
+        let width = self.states.len().saturating_sub(1).to_string().len();
+        for state in &self.states {
+            let state_id = state.id();
+            write!(
+                f,
+                "  {}{} {:>width$}:",
+                if state_id == self.start_state {
+                    '>'
+                } else {
+                    ' '
+                },
+                if state_id == self.end_state { '*' } else { ' ' },
+                state_id.as_usize(),
+            )?;
+            let transitions = state
+                .epsilon_transitions()
+                .iter()
+                .map(|epsilon| ("ε".to_string(), epsilon.target_state()))
+                .chain(state.transitions().iter().map(|transition| {
+                    (labels.label(transition.chars()), transition.target_state())
+                }));
+            let mut empty = true;
+            for (label, target_id) in transitions {
+                let separator = if empty { " " } else { ", " };
+                write!(f, "{}{} -> {}", separator, label, target_id.as_usize())?;
+                empty = false;
+            }
+            if empty {
+                write!(f, " no transitions")?;
+            }
+            if let Some(counter) = self.counters.get(&state_id) {
+                write!(f, "; repeats {}", counter)?;
+            }
+            writeln!(f)?;
+        }
+        labels.write_legend(f)
+    }
 }
 
 #[derive(Debug, Clone, Default)]
@@ -265,9 +424,9 @@ impl NfaState {
 
 #[derive(Debug, Clone)]
 pub(crate) struct NfaTransition {
-    // This can be a Literal or a CharacterClass
+    // The character class of the transition, single characters are represented as a class, too
     // We will later generate a predicate from this that determines if a character matches this transition
-    chars: Ast,
+    chars: ClassUnicode,
     // The next state to transition to
     target_state: StateID,
 }
@@ -277,7 +436,7 @@ impl NfaTransition {
         self.target_state
     }
 
-    pub(crate) fn chars(&self) -> &Ast {
+    pub(crate) fn chars(&self) -> &ClassUnicode {
         &self.chars
     }
 }
@@ -314,12 +473,12 @@ mod tests {
     }
 
     #[test]
-    fn test_nfa_from_ast() {
-        // Create an example AST
-        let ast = crate::compiletime::parse_regex_syntax("a").unwrap();
+    fn test_nfa_from_hir() {
+        // Create an example HIR
+        let hir = crate::compiletime::parse_regex_syntax("a").unwrap();
 
-        // Convert the AST to an NFA
-        let nfa: Nfa = ast.try_into().unwrap();
+        // Convert the HIR to an NFA
+        let nfa: Nfa = hir.try_into().unwrap();
 
         // Add assertions here to validate the NFA
         assert_eq!(nfa.states.len(), 2);
@@ -328,8 +487,44 @@ mod tests {
     }
 
     #[test]
-    fn test_nfa_from_ast_concat() {
-        // Create an example AST and convert the AST to an NFA
+    fn test_nfa_display() {
+        let mut nfa: Nfa = crate::compiletime::parse_regex_syntax("a*b")
+            .unwrap()
+            .try_into()
+            .unwrap();
+        nfa.pattern = "a*b".to_string();
+        assert_eq!(
+            nfa.to_string(),
+            "\
+NFA with 6 states for pattern a*b
+     0: a -> 1
+     1: ε -> 3, ε -> 0
+  >  2: ε -> 0, ε -> 1
+     3: ε -> 4
+     4: b -> 5
+   * 5: no transitions
+"
+        );
+
+        // Long classes are labeled by number
+        let nfa: Nfa = crate::compiletime::parse_regex_syntax(r"\w")
+            .unwrap()
+            .try_into()
+            .unwrap();
+        let rendered = nfa.to_string();
+        assert!(rendered.starts_with(
+            "\
+NFA with 2 states
+  >  0: c0 -> 1
+   * 1: no transitions
+Character classes:
+  c0: [0-9A-Z_a-z"
+        ));
+    }
+
+    #[test]
+    fn test_nfa_from_hir_concat() {
+        // Create an example HIR and convert the HIR to an NFA
         let nfa: Nfa = crate::compiletime::parse_regex_syntax("ab")
             .unwrap()
             .try_into()
@@ -338,9 +533,9 @@ mod tests {
         nfa_render_to!(&nfa, "ab");
 
         // Add assertions here to validate the NFA
-        assert_eq!(nfa.states.len(), 4);
+        assert_eq!(nfa.states.len(), 3);
         assert_eq!(nfa.start_state.as_usize(), 0);
-        assert_eq!(nfa.end_state.as_usize(), 3);
+        assert_eq!(nfa.end_state.as_usize(), 2);
     }
 
     #[test]
@@ -385,7 +580,7 @@ mod tests {
 
     #[test]
     fn test_nfa_repetition() {
-        // Create an example AST and convert the AST to an NFA
+        // Create an example HIR and convert the HIR to an NFA
         let mut nfa: Nfa = crate::compiletime::parse_regex_syntax("a")
             .unwrap()
             .try_into()
@@ -402,7 +597,7 @@ mod tests {
 
     #[test]
     fn test_nfa_zero_or_one() {
-        // Create an example AST and convert the AST to an NFA
+        // Create an example HIR and convert the HIR to an NFA
         let mut nfa: Nfa = crate::compiletime::parse_regex_syntax("a")
             .unwrap()
             .try_into()
@@ -419,7 +614,7 @@ mod tests {
 
     #[test]
     fn test_nfa_one_or_more() {
-        // Create an example AST and convert the AST to an NFA
+        // Create an example HIR and convert the HIR to an NFA
         let mut nfa: Nfa = crate::compiletime::parse_regex_syntax("a")
             .unwrap()
             .try_into()
@@ -436,7 +631,7 @@ mod tests {
 
     #[test]
     fn test_nfa_zero_or_more() {
-        // Create an example AST and convert the AST to an NFA
+        // Create an example HIR and convert the HIR to an NFA
         let mut nfa: Nfa = crate::compiletime::parse_regex_syntax("a")
             .unwrap()
             .try_into()
@@ -453,7 +648,7 @@ mod tests {
 
     #[test]
     fn test_complex_nfa() {
-        // Create an example AST and convert the AST to an NFA
+        // Create an example HIR and convert the HIR to an NFA
         let nfa: Nfa = crate::compiletime::parse_regex_syntax("(a|b)*abb")
             .unwrap()
             .try_into()
@@ -462,14 +657,14 @@ mod tests {
         nfa_render_to!(&nfa, "complex");
 
         // Add assertions here to validate the NFA
-        assert_eq!(nfa.states.len(), 14);
-        assert_eq!(nfa.start_state.as_usize(), 6);
-        assert_eq!(nfa.end_state.as_usize(), 13);
+        assert_eq!(nfa.states.len(), 8);
+        assert_eq!(nfa.start_state.as_usize(), 2);
+        assert_eq!(nfa.end_state.as_usize(), 7);
     }
 
     #[test]
     fn test_nfa_offset_states() {
-        // Create an example AST and convert the AST to an NFA
+        // Create an example HIR and convert the HIR to an NFA
         let mut nfa: Nfa = crate::compiletime::parse_regex_syntax("a")
             .unwrap()
             .try_into()
@@ -484,7 +679,7 @@ mod tests {
 
     #[test]
     fn test_nfa_repetition_at_least() {
-        // Create an example AST and convert the AST to an NFA
+        // Create an example HIR and convert the HIR to an NFA
         let nfa: Nfa = crate::compiletime::parse_regex_syntax("a{3,}")
             .unwrap()
             .try_into()
@@ -500,7 +695,7 @@ mod tests {
 
     #[test]
     fn test_nfa_repetition_bounded() {
-        // Create an example AST and convert the AST to an NFA
+        // Create an example HIR and convert the HIR to an NFA
         let nfa: Nfa = crate::compiletime::parse_regex_syntax("a{3,5}")
             .unwrap()
             .try_into()
@@ -517,7 +712,7 @@ mod tests {
     // Ascii character class are not yet implemented
     #[test]
     fn test_character_class_expression() {
-        // Create an example AST and convert the AST to an NFA
+        // Create an example HIR and convert the HIR to an NFA
         let nfa: Nfa = crate::compiletime::parse_regex_syntax(r"[[:digit:]]")
             .unwrap()
             .try_into()
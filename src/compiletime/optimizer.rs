@@ -0,0 +1,129 @@
+//! This module contains an optimization pass over the HIR of a pattern that is applied before the
+//! NFA construction.
+//!
+//! Alternations are factored into a trie, i.e. common prefixes of the alternatives are extracted,
+//! so that e.g. `%start|%scanner|%sc` becomes `%s(tart|c(anner)?)`. This reduces the number of NFA
+//! and DFA states considerably for keyword-heavy grammars.
+//! Because the scanner is only interested in the language of a pattern and not in the order of
+//! the alternatives, alternatives can be grouped regardless of their position.
+
+use regex_syntax::hir::{Hir, HirKind};
+
+/// Optimizes the given HIR by factoring all alternations into tries.
+pub(crate) fn optimize(hir: Hir) -> Hir {
+    match hir.kind() {
+        HirKind::Alternation(_)
+        | HirKind::Concat(_)
+        | HirKind::Repetition(_)
+        | HirKind::Capture(_) => (),
+        _ => return hir,
+    }
+    match hir.into_kind() {
+        HirKind::Alternation(hirs) => {
+            factor(hirs.into_iter().map(|hir| atoms(optimize(hir))).collect())
+        }
+        HirKind::Concat(hirs) => Hir::concat(hirs.into_iter().map(optimize).collect()),
+        HirKind::Repetition(mut repetition) => {
+            repetition.sub = Box::new(optimize(*repetition.sub));
+            Hir::repetition(repetition)
+        }
+        HirKind::Capture(mut capture) => {
+            capture.sub = Box::new(optimize(*capture.sub));
+            Hir::capture(capture)
+        }
+        _ => unreachable!(),
+    }
+}
+
+/// Splits the given HIR into the sequence of its atoms, i.e. the items of a concatenation where
+/// literals are split into single characters.
+fn atoms(hir: Hir) -> Vec<Hir> {
+    match hir.kind() {
+        HirKind::Literal(literal) => match std::str::from_utf8(&literal.0) {
+            Ok(s) => s
+                .chars()
+                .map(|c| Hir::literal(c.to_string().into_bytes()))
+                .collect(),
+            Err(_) => vec![hir],
+        },
+        HirKind::Concat(_) => match hir.into_kind() {
+            HirKind::Concat(hirs) => hirs.into_iter().flat_map(atoms).collect(),
+            _ => unreachable!(),
+        },
+        _ => vec![hir],
+    }
+}
+
+/// Builds a trie from the given alternatives, each given as sequence of atoms.
+/// Alternatives that start with the same atom are grouped and their remaining atoms are factored
+/// recursively.
+fn factor(alternatives: Vec<Vec<Hir>>) -> Hir {
+    let mut groups: Vec<(Hir, Vec<Vec<Hir>>)> = Vec::new();
+    let mut matches_empty = false;
+    for mut alternative in alternatives {
+        if alternative.is_empty() {
+            matches_empty = true;
+            continue;
+        }
+        let first = alternative.remove(0);
+        if let Some((_, tails)) = groups.iter_mut().find(|(atom, _)| *atom == first) {
+            tails.push(alternative);
+        } else {
+            groups.push((first, vec![alternative]));
+        }
+    }
+    let mut factored = Vec::with_capacity(groups.len() + 1);
+    for (first, mut tails) in groups {
+        if tails.len() == 1 {
+            let mut concat = vec![first];
+            concat.append(&mut tails[0]);
+            factored.push(Hir::concat(concat));
+        } else {
+            factored.push(Hir::concat(vec![first, factor(tails)]));
+        }
+    }
+    if matches_empty {
+        factored.push(Hir::empty());
+    }
+    Hir::alternation(factored)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiletime::{nfa::Nfa, parse_regex_syntax, MultiPatternDfa};
+
+    fn nfa_states(hir: Hir) -> usize {
+        let nfa: Nfa = hir.try_into().unwrap();
+        nfa.states().len()
+    }
+
+    #[test]
+    fn test_factor_keywords() {
+        let hir = parse_regex_syntax("%start|%scanner|%sc").unwrap();
+        let optimized = optimize(hir.clone());
+        assert!(nfa_states(optimized) < nfa_states(hir));
+    }
+
+    #[test]
+    fn test_factor_class_prefix() {
+        let hir = parse_regex_syntax("[a-z]x|[a-z]yz|[a-z]yw").unwrap();
+        let optimized = optimize(hir.clone());
+        assert!(nfa_states(optimized) < nfa_states(hir));
+    }
+
+    #[test]
+    fn test_optimized_language() {
+        let pattern = "(%start|%scanner|%sc)+|if|in|int|i";
+        let mut multi_pattern_dfa = MultiPatternDfa::new();
+        multi_pattern_dfa.add_pattern(pattern).unwrap();
+        let dfa = &multi_pattern_dfa.dfas()[0];
+        let match_functions = multi_pattern_dfa.match_functions();
+        for input in ["%start", "%sc%scanner", "if", "in", "int", "i"] {
+            assert!(dfa.accepts(input, match_functions), "{}", input);
+        }
+        for input in ["%s", "%scan", "", "inn", "%start%"] {
+            assert!(!dfa.accepts(input, match_functions), "{}", input);
+        }
+    }
+}
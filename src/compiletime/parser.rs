@@ -1,33 +1,116 @@
 //! This module contains the parser for the regex syntax.
-//! The parser is used to parse the regex syntax into an abstract syntax tree (AST).
-//! We use the `regex_syntax` crate to parse the regex syntax, although we will only support a
-//! subset of the regex syntax.
+//! The parser is used to parse the regex syntax into the high-level intermediate representation
+//! (HIR) of the `regex_syntax` crate.
+//! The HIR already resolves flags, case folding, Perl and Unicode classes as well as class set
+//! operations into plain character ranges. We will only support a subset of the HIR, though.
 
 use crate::Result;
-use log::trace;
-use std::time::Instant;
+use tracing::trace_span;
 
-use regex_syntax::ast::{parse::Parser, Ast};
+use regex_syntax::{
+    ast::{self, parse::Parser, Ast, ClassSetItem, ClassUnicode, ClassUnicodeKind, Span, Visitor},
+    hir::translate::TranslatorBuilder,
+    hir::Hir,
+};
 
-/// Parse the regex syntax into an abstract syntax tree (AST).
+use super::unicode_blocks::block_range;
+
+/// Parse the regex syntax into the high-level intermediate representation (HIR).
 /// The function returns an error if the regex syntax is invalid.
+///
+/// The dot `.` doesn't match `\r` and `\n` unless the flag `s` is set.
+/// Unicode block classes like `\p{Block=Greek}` are replaced by the range of the block, because
+/// the unicode data of the `regex_syntax` crate contains no blocks.
 /// # Arguments
 /// * `input` - A string slice that holds the regex syntax.
 /// # Returns
-/// An `Ast` that represents the abstract syntax tree of the regex syntax.
+/// A `Hir` that represents the regex syntax.
 /// # Errors
 /// An error is returned if the regex syntax is invalid.
-pub fn parse_regex_syntax(input: &str) -> Result<Ast> {
-    let now = Instant::now();
-    match Parser::new().parse(input) {
-        Ok(syntax_tree) => {
-            let elapsed_time = now.elapsed();
-            trace!("Parsing took {} milliseconds.", elapsed_time.as_millis());
-            Ok(syntax_tree)
+pub fn parse_regex_syntax(input: &str) -> Result<Hir> {
+    let _span = trace_span!("parse", pattern = input).entered();
+    let ast = Parser::new().parse(input)?;
+    let blocks = ast::visit(&ast, UnicodeBlocks::default())?;
+    let (pattern, ast) = if blocks.is_empty() {
+        (input.to_string(), ast)
+    } else {
+        let pattern = replace_unicode_blocks(input, &blocks);
+        let ast = Parser::new().parse(&pattern)?;
+        (pattern, ast)
+    };
+    let hir = TranslatorBuilder::new()
+        .crlf(true)
+        .build()
+        .translate(&pattern, &ast)?;
+    Ok(hir)
+}
+
+/// Replaces the unicode block classes at the given spans by bracketed classes of their ranges.
+fn replace_unicode_blocks(input: &str, blocks: &[(Span, String)]) -> String {
+    let mut pattern = String::with_capacity(input.len());
+    let mut end = 0;
+    for (span, class) in blocks {
+        pattern.push_str(&input[end..span.start.offset]);
+        pattern.push_str(class);
+        end = span.end.offset;
+    }
+    pattern.push_str(&input[end..]);
+    pattern
+}
+
+/// A visitor that collects the unicode block classes together with the bracketed classes of
+/// their ranges, in the order of their occurrence.
+/// Unknown blocks are kept, so the translation reports them like other unknown unicode classes.
+#[derive(Default)]
+struct UnicodeBlocks(Vec<(Span, String)>);
+
+impl UnicodeBlocks {
+    fn collect(&mut self, class: &ClassUnicode) {
+        if let ClassUnicodeKind::NamedValue { name, value, .. } = &class.kind {
+            let name = name.replace([' ', '_', '-'], "").to_lowercase();
+            if name != "block" && name != "blk" {
+                return;
+            }
+            if let Some((first, last)) = block_range(value) {
+                let negation = if class.is_negated() { "^" } else { "" };
+                self.0.push((
+                    class.span,
+                    format!(
+                        "[{}\\x{{{:X}}}-\\x{{{:X}}}]",
+                        negation, first as u32, last as u32
+                    ),
+                ));
+            }
         }
-        Err(e) => Err(e.into()),
     }
 }
+
+impl Visitor for UnicodeBlocks {
+    type Output = Vec<(Span, String)>;
+    type Err = ast::Error;
+
+    fn finish(self) -> std::result::Result<Self::Output, Self::Err> {
+        Ok(self.0)
+    }
+
+    fn visit_pre(&mut self, ast: &Ast) -> std::result::Result<(), Self::Err> {
+        if let Ast::ClassUnicode(class) = ast {
+            self.collect(class);
+        }
+        Ok(())
+    }
+
+    fn visit_class_set_item_pre(
+        &mut self,
+        item: &ClassSetItem,
+    ) -> std::result::Result<(), Self::Err> {
+        if let ClassSetItem::Unicode(class) = item {
+            self.collect(class);
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::error::Error;
@@ -40,10 +123,9 @@ mod tests {
     fn test_parse_regex_syntax_valid() {
         // Valid regex syntax
         let input = r"\d";
-        let ast = parse_regex_syntax(input).unwrap();
-        // Add assertions here to validate the AST
-        assert_eq!(format!("{:?}", ast),
-            "ClassPerl(ClassPerl { span: Span(Position(o: 0, l: 1, c: 1), Position(o: 2, l: 1, c: 3)), kind: Digit, negated: false })");
+        let hir = parse_regex_syntax(input).unwrap();
+        // The Perl class is resolved into the ranges of the Unicode class Nd
+        assert!(hir.to_string().starts_with("[0-9"));
     }
 
     #[test]
@@ -74,6 +156,29 @@ error: unclosed character class"#
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_parse_regex_syntax_unicode_block() {
+        let greek = parse_regex_syntax(r"[\x{370}-\x{3FF}]").unwrap();
+        assert_eq!(parse_regex_syntax(r"\p{Block=Greek}").unwrap(), greek);
+        assert_eq!(
+            parse_regex_syntax(r"\p{blk = Greek and Coptic}").unwrap(),
+            greek
+        );
+        assert_eq!(
+            parse_regex_syntax(r"[a\p{Block=Cyrillic}]").unwrap(),
+            parse_regex_syntax(r"[a\x{400}-\x{4FF}]").unwrap()
+        );
+        assert_eq!(
+            parse_regex_syntax(r"\P{Block=Greek}x\p{Block!=Greek}").unwrap(),
+            parse_regex_syntax(r"[^\x{370}-\x{3FF}]x[^\x{370}-\x{3FF}]").unwrap()
+        );
+        // Unknown blocks are reported by the translation
+        assert!(matches!(
+            parse_regex_syntax(r"\p{Block=NoSuchBlock}"),
+            Err(ref e) if matches!(e, ScanGenError{ source } if matches!(**source, ScanGenErrorKind::RegexTranslationError(_)))
+        ));
+    }
+
     // This may hinder the use of the regex_syntax crate because it does not support lookaround
     // assertions. We'll have to evaluate if we can live with this limitation.
     #[test]
@@ -0,0 +1,170 @@
+//! This module validates single patterns, e.g. for grammar editors that check terminals while
+//! they are typed, before the scanner is generated.
+
+use regex_syntax::hir::{Class, ClassUnicode, Hir, HirKind};
+
+use crate::{Result, ScanGenError, ScanGenErrorKind};
+
+use super::{
+    absent::split_absent_pattern,
+    character_class::{disjoint_classes, single_char_class},
+    parse_regex_syntax, DfaTable,
+};
+
+/// The information about a single pattern that is returned by [validate_pattern].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatternInfo {
+    /// The descriptions of the features of the pattern that scangen doesn't support, e.g.
+    /// anchors and look-around assertions. The pattern can be used as terminal if this is empty.
+    pub unsupported_features: Vec<String>,
+    /// The number of disjoint character classes the characters of the pattern are split into.
+    /// Each of them becomes an arm of the generated match function.
+    pub char_classes: usize,
+    /// True if the pattern matches the empty string. The scanner never returns empty matches, so
+    /// such a terminal only matches its non-empty strings.
+    pub nullable: bool,
+}
+
+impl PatternInfo {
+    /// Returns true if the pattern can be used as terminal.
+    pub fn is_supported(&self) -> bool {
+        self.unsupported_features.is_empty()
+    }
+}
+
+/// Validates a single pattern without generating a scanner, e.g. to check the terminals of a
+/// grammar interactively.
+///
+/// Unlike the generation, the validation doesn't stop at the first unsupported feature but lists
+/// all of them in the returned [PatternInfo].
+///
+/// ```
+/// use scangen::validate_pattern;
+///
+/// let info = validate_pattern("[a-z]*").unwrap();
+/// assert!(info.is_supported());
+/// assert!(info.nullable);
+///
+/// let info = validate_pattern(r"^\d+\b").unwrap();
+/// assert_eq!(info.unsupported_features.len(), 2);
+/// ```
+///
+/// # Errors
+/// An error is returned if the pattern is no valid regex syntax.
+pub fn validate_pattern(pattern: &str) -> Result<PatternInfo> {
+    let (hirs, nullable_parts) = match split_absent_pattern(pattern) {
+        Ok(Some(absent)) => {
+            let prefix = parse_regex_syntax(absent.prefix)?;
+            let excluded = parse_regex_syntax(absent.excluded)?;
+            let terminator = parse_regex_syntax(absent.terminator)?;
+            // The text between the prefix and the terminator can be empty.
+            let nullable = is_nullable(&prefix) && is_nullable(&terminator);
+            (vec![prefix, excluded, terminator], nullable)
+        }
+        Ok(None) => {
+            let hir = parse_regex_syntax(pattern)?;
+            let nullable = is_nullable(&hir);
+            (vec![hir], nullable)
+        }
+        Err(ScanGenError { source }) => match *source {
+            ScanGenErrorKind::UnsupportedFeature(feature) => {
+                return Ok(PatternInfo {
+                    unsupported_features: vec![feature],
+                    char_classes: 0,
+                    nullable: false,
+                });
+            }
+            source => return Err(ScanGenError::new(source)),
+        },
+    };
+
+    let mut unsupported_features = Vec::new();
+    let mut classes = Vec::new();
+    for hir in &hirs {
+        collect(hir, &mut unsupported_features, &mut classes);
+    }
+    // The DFA of a supported pattern knows exactly whether it accepts the empty string.
+    let nullable = if unsupported_features.is_empty() {
+        DfaTable::from_pattern(pattern)?.accepts("")
+    } else {
+        nullable_parts
+    };
+    Ok(PatternInfo {
+        unsupported_features,
+        char_classes: disjoint_classes(&classes).len(),
+        nullable,
+    })
+}
+
+/// Returns true if the given HIR matches the empty string.
+fn is_nullable(hir: &Hir) -> bool {
+    hir.properties().minimum_len() == Some(0)
+}
+
+/// Collects the unsupported features and the character classes of the given HIR.
+fn collect(hir: &Hir, unsupported_features: &mut Vec<String>, classes: &mut Vec<ClassUnicode>) {
+    match hir.kind() {
+        HirKind::Empty => (),
+        HirKind::Literal(literal) => match std::str::from_utf8(&literal.0) {
+            Ok(literal) => classes.extend(literal.chars().map(single_char_class)),
+            Err(_) => unsupported_features.push("Literal that is not valid UTF-8".to_string()),
+        },
+        HirKind::Class(Class::Unicode(class)) => classes.push(class.clone()),
+        HirKind::Class(Class::Bytes(class)) => match class.to_unicode_class() {
+            Some(class) => classes.push(class),
+            None => unsupported_features.push(format!("Byte class {:?}", class)),
+        },
+        HirKind::Look(look) => unsupported_features.push(format!("Assertion {:?}", look)),
+        HirKind::Repetition(repetition) => collect(&repetition.sub, unsupported_features, classes),
+        HirKind::Capture(capture) => collect(&capture.sub, unsupported_features, classes),
+        HirKind::Concat(hirs) | HirKind::Alternation(hirs) => {
+            for hir in hirs {
+                collect(hir, unsupported_features, classes);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_pattern() {
+        assert_eq!(
+            validate_pattern("[a-z][a-z0-9]*").unwrap(),
+            PatternInfo {
+                unsupported_features: Vec::new(),
+                char_classes: 2,
+                nullable: false,
+            }
+        );
+        assert_eq!(
+            validate_pattern("if|[a-z]+").unwrap(),
+            PatternInfo {
+                unsupported_features: Vec::new(),
+                char_classes: 3,
+                nullable: false,
+            }
+        );
+        assert!(validate_pattern("(ab)?").unwrap().nullable);
+
+        let info = validate_pattern(r"^a\b").unwrap();
+        assert!(!info.is_supported());
+        assert_eq!(
+            info.unsupported_features,
+            ["Assertion Start", "Assertion WordUnicode"]
+        );
+        assert_eq!(info.char_classes, 1);
+
+        // The parts of an absent pattern are validated on their own
+        let info = validate_pattern(r"/\*(?~\*/)\*/").unwrap();
+        assert!(info.is_supported());
+        assert!(!info.nullable);
+        let info = validate_pattern("a(?~b)(?~c)").unwrap();
+        assert_eq!(info.unsupported_features.len(), 1);
+
+        // Invalid syntax is an error
+        assert!(validate_pattern("a(").is_err());
+    }
+}
@@ -0,0 +1,158 @@
+//! This module generates a standalone module for a single regex.
+//!
+//! The generated module contains the tables of the minimized DFA of the regex, its match function
+//! and the functions `is_match` and `find`. It doesn't depend on the `scangen` crate, so users
+//! that only need one precompiled regex don't need the scanner machinery.
+
+use crate::{compiletime::dfa::Dfa, Result};
+
+use super::{compiled_dfa::CompiledDfa, MatchFunction};
+
+/// Generate a standalone module that matches the given regex.
+///
+/// The generated module provides the functions
+/// * `is_match(input: &str) -> bool`, which returns true if the regex matches somewhere in the
+///   input, and
+/// * `find(input: &str) -> Option<std::ops::Range<usize>>`, which returns the byte range of the
+///   leftmost longest match.
+///
+/// Unlike the code generated by [crate::generate_code], the module doesn't use the `scangen`
+/// crate, so no runtime dependency has to be added.
+///
+/// # Errors
+/// An error is returned if the regex contains unsupported syntax.
+pub fn generate_regex_code(pattern: &str, output: &mut dyn std::io::Write) -> Result<()> {
+    let dfa = Dfa::try_from_pattern(pattern)?.minimize()?;
+    let mut match_functions = Vec::new();
+    let mut compiled_dfa = CompiledDfa::new();
+    compiled_dfa.compile(&dfa, &mut match_functions)?;
+    let [accepting_states, state_ranges, transitions] = compiled_dfa.slices();
+
+    writeln!(output, "#![allow(clippy::manual_is_ascii_check)]")?;
+    writeln!(output)?;
+    // A `*/` in the pattern would terminate the comment prematurely
+    writeln!(
+        output,
+        "/* Generated by scangen {} for the regex {:?} */",
+        env!("CARGO_PKG_VERSION"),
+        pattern.replace("*/", "*\\/")
+    )?;
+    writeln!(output)?;
+    writeln!(
+        output,
+        "const ACCEPTING_STATES: &[{}] = {};",
+        accepting_states.0, accepting_states.1
+    )?;
+    writeln!(
+        output,
+        "const STATE_RANGES: &[{}] = {};",
+        state_ranges.0, state_ranges.1
+    )?;
+    writeln!(
+        output,
+        "const TRANSITIONS: &[{}] = {};",
+        transitions.0, transitions.1
+    )?;
+    writeln!(output)?;
+
+    writeln!(
+        output,
+        "fn matches_char_class(c: char, char_class: usize) -> bool {{"
+    )?;
+    writeln!(output, "    match char_class {{")?;
+    for (i, (class, _)) in match_functions.iter().enumerate() {
+        MatchFunction::generate_code(class, i, output)?;
+    }
+    writeln!(output, "        _ => false,")?;
+    writeln!(output, "    }}")?;
+    writeln!(output, "}}")?;
+    writeln!(output)?;
+
+    writeln!(
+        output,
+        r"fn next_state(state: usize, c: char) -> Option<usize> {{
+    let (start, end) = STATE_RANGES.get(state).copied()?;
+    TRANSITIONS
+        .get(start..end)?
+        .iter()
+        .find(|(char_class, _)| matches_char_class(c, *char_class))
+        .map(|(_, target_state)| *target_state)
+}}
+
+/// Returns the length of the longest match that starts at the beginning of the input.
+fn longest_match_len(input: &str) -> Option<usize> {{
+    let mut state = 0;
+    let mut len = ACCEPTING_STATES.contains(&state).then_some(0);
+    for (i, c) in input.char_indices() {{
+        let Some(next) = next_state(state, c) else {{
+            break;
+        }};
+        state = next;
+        if ACCEPTING_STATES.contains(&state) {{
+            len = Some(i + c.len_utf8());
+        }}
+    }}
+    len
+}}
+
+/// Returns true if the regex matches somewhere in the input.
+pub(crate) fn is_match(input: &str) -> bool {{
+    find(input).is_some()
+}}
+
+/// Returns the byte range of the leftmost longest match of the regex in the input.
+pub(crate) fn find(input: &str) -> Option<std::ops::Range<usize>> {{
+    input
+        .char_indices()
+        .map(|(i, _)| i)
+        .chain(std::iter::once(input.len()))
+        .find_map(|start| {{
+            let len = longest_match_len(input.get(start..)?)?;
+            Some(start..start + len)
+        }})
+}}"
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::compiletime::{generated::identifier_regex, try_format};
+
+    use super::*;
+
+    #[test]
+    fn generate_code_for_identifier_regex() {
+        // The generated module is compiled and used by the test below.
+        let file_name = "src/compiletime/generated/identifier_regex.rs";
+        {
+            let mut out_file = fs::File::create(file_name).unwrap();
+            generate_regex_code("[a-zA-Z_][a-zA-Z0-9_]*", &mut out_file).unwrap();
+        }
+        try_format(file_name).unwrap();
+    }
+
+    #[test]
+    fn test_identifier_regex() {
+        assert!(identifier_regex::is_match("  foo1 bar"));
+        assert!(!identifier_regex::is_match("12 + 3"));
+        assert_eq!(identifier_regex::find("  foo1 bar"), Some(2..6));
+        assert_eq!(identifier_regex::find("1ä_x"), Some(3..5));
+        assert_eq!(identifier_regex::find(""), None);
+    }
+
+    #[test]
+    fn test_generate_regex_code() {
+        let mut output = Vec::new();
+        generate_regex_code(r"/\*.*?\*/", &mut output).unwrap();
+        let code = String::from_utf8(output).unwrap();
+        assert!(code.contains(r#"for the regex "/\\*.*?\\*\\/" */"#));
+        assert!(code.contains("pub(crate) fn is_match(input: &str) -> bool"));
+        assert!(code.contains("pub(crate) fn find(input: &str) -> Option<std::ops::Range<usize>>"));
+        assert!(!code.contains("scangen::"));
+
+        assert!(generate_regex_code("a(", &mut Vec::new()).is_err());
+    }
+}
@@ -1,5 +1,6 @@
+use std::io::Write;
 use std::path::Path;
-use std::process::Command;
+use std::process::{Command, Stdio};
 
 use crate::Result;
 
@@ -14,3 +15,36 @@ where
         .status()?;
     Ok(())
 }
+
+/// Tries to format the given source code in memory, e.g. the code returned by
+/// [crate::generate_code_to_string].
+/// The code is returned unchanged if rustfmt fails to format it.
+///
+/// # Errors
+/// An error is returned if rustfmt can't be run.
+pub fn try_format_code(code: &str) -> Result<String> {
+    let mut rustfmt = Command::new("rustfmt")
+        .args(["--edition", "2021"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()?;
+    // The code is written from another thread, because rustfmt may block on writing its output
+    // while we are still writing the input.
+    let mut stdin = rustfmt.stdin.take();
+    let output = std::thread::scope(|scope| {
+        let writer = scope.spawn(move || match stdin.as_mut() {
+            Some(stdin) => stdin.write_all(code.as_bytes()),
+            None => Ok(()),
+        });
+        let output = rustfmt.wait_with_output();
+        // If rustfmt exits early, e.g. on a syntax error, the input may be incomplete, which is
+        // detected by the exit status below.
+        let _ = writer.join();
+        output
+    })?;
+    match String::from_utf8(output.stdout) {
+        Ok(formatted) if output.status.success() => Ok(formatted),
+        _ => Ok(code.to_string()),
+    }
+}
@@ -0,0 +1,228 @@
+//! This module scaffolds a crate with a scanner that is generated ahead of time.
+
+use std::{fs, path::Path};
+
+use crate::{generate_code, try_format, Result, ScanGenError, ScanGenErrorKind};
+
+/// The patterns of the scaffolded crate. Each line that is neither empty nor a comment is the
+/// pattern of the next token type.
+const PATTERNS: &str = r"// Each line is the pattern of a token type, starting with token type 0.
+// Empty lines and lines that start with `//` are ignored.
+\r\n|\r|\n
+[\s--\r\n]+
+[a-zA-Z_][a-zA-Z0-9_]*
+0|[1-9][0-9]*
+[-+*/=;()]
+.
+";
+
+const CARGO_TOML: &str = r#"[package]
+name = "{crate_name}"
+version = "0.1.0"
+edition = "2021"
+
+[dependencies]
+scangen = { version = "{scangen_version}", default-features = false, features = ["runtime"] }
+
+[build-dependencies]
+scangen = "{scangen_version}"
+"#;
+
+const BUILD_RS: &str = r#"//! Generates the scanner module `src/lexer.rs` from the patterns in `patterns.txt`.
+
+use std::fs;
+
+fn main() {
+    println!("cargo:rerun-if-changed=patterns.txt");
+    let patterns = fs::read_to_string("patterns.txt").expect("Failed to read patterns.txt");
+    let patterns = patterns
+        .lines()
+        .filter(|line| !line.is_empty() && !line.starts_with("//"))
+        .collect::<Vec<_>>();
+    let mut code = Vec::new();
+    scangen::generate_code(&patterns, &[], None, &mut code).expect("Failed to generate code");
+    fs::write("src/lexer.rs", code).expect("Failed to write src/lexer.rs");
+    // Formatting is optional, the generated code compiles either way.
+    let _ = scangen::try_format("src/lexer.rs");
+}
+"#;
+
+const LIB_RS: &str = r#"//! The lexer of `{crate_name}`.
+//!
+//! The token types are defined by the patterns in `patterns.txt`. The build script regenerates
+//! the scanner module `src/lexer.rs` whenever they change.
+
+use scangen::{FindMatches, Scanner, TokenType};
+
+mod lexer;
+
+/// The token types in the order of the patterns in `patterns.txt`.
+pub mod token_types {
+    use scangen::TokenType;
+
+    /// A line break.
+    pub const NEWLINE: TokenType = TokenType::new(0);
+    /// Whitespace other than line breaks.
+    pub const WHITESPACE: TokenType = TokenType::new(1);
+    /// An identifier.
+    pub const IDENTIFIER: TokenType = TokenType::new(2);
+    /// A decimal number.
+    pub const NUMBER: TokenType = TokenType::new(3);
+    /// An operator or a punctuation character.
+    pub const OPERATOR: TokenType = TokenType::new(4);
+    /// Any other character.
+    pub const ERROR: TokenType = TokenType::new(5);
+}
+
+/// Creates a new scanner, e.g. to configure it before it is used.
+pub fn create_scanner() -> Scanner {
+    lexer::create_scanner()
+}
+
+/// Returns an iterator over the tokens of the input.
+pub fn tokenize(input: &str) -> FindMatches<'_> {
+    lexer::create_find_iter(lexer::scanner(), input)
+}
+
+/// Returns the token types of the input without whitespace and line breaks.
+pub fn significant_token_types(input: &str) -> Vec<TokenType> {
+    tokenize(input)
+        .map(|m| m.token_type())
+        .filter(|token_type| {
+            *token_type != token_types::NEWLINE && *token_type != token_types::WHITESPACE
+        })
+        .collect()
+}
+"#;
+
+const TESTS_RS: &str = r#"use {crate_ident}::{significant_token_types, token_types::*, tokenize};
+
+#[test]
+fn test_tokenize() {
+    let input = "x = 42;\n";
+    let tokens = tokenize(input)
+        .map(|m| (m.token_type(), &input[m.range()]))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        tokens,
+        vec![
+            (IDENTIFIER, "x"),
+            (WHITESPACE, " "),
+            (OPERATOR, "="),
+            (WHITESPACE, " "),
+            (NUMBER, "42"),
+            (OPERATOR, ";"),
+            (NEWLINE, "\n"),
+        ]
+    );
+}
+
+#[test]
+fn test_unknown_characters() {
+    assert_eq!(
+        significant_token_types("a # 1"),
+        vec![IDENTIFIER, ERROR, NUMBER]
+    );
+}
+"#;
+
+const GITIGNORE: &str = "/target\nCargo.lock\n";
+
+/// Creates a new crate with a lexer in the given directory, which must not exist yet. This is
+/// what `scangen new-lexer` does.
+///
+/// The crate consists of
+/// * `patterns.txt` with one pattern per token type,
+/// * a build script that generates the scanner module `src/lexer.rs` from the patterns whenever
+///   they change,
+/// * the generated module itself, so the crate can be inspected before it is built,
+/// * `src/lib.rs` with the token types and a `tokenize` function and
+/// * example tests in `tests/lexer.rs`.
+///
+/// # Errors
+/// An error is returned if the name is no valid crate name, if the directory already exists or
+/// if the files can't be written.
+pub fn scaffold_lexer<T>(crate_name: &str, directory: T) -> Result<()>
+where
+    T: AsRef<Path>,
+{
+    let valid_start = crate_name
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic());
+    if !valid_start
+        || !crate_name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(ScanGenError::new(ScanGenErrorKind::InvalidCrateName(
+            crate_name.to_string(),
+        )));
+    }
+    let crate_ident = crate_name.replace('-', "_");
+    let instantiate = |template: &str| {
+        template
+            .replace("{crate_name}", crate_name)
+            .replace("{crate_ident}", &crate_ident)
+            .replace("{scangen_version}", env!("CARGO_PKG_VERSION"))
+    };
+
+    let directory = directory.as_ref();
+    fs::create_dir(directory)?;
+    fs::create_dir(directory.join("src"))?;
+    fs::create_dir(directory.join("tests"))?;
+    fs::write(directory.join("Cargo.toml"), instantiate(CARGO_TOML))?;
+    fs::write(directory.join(".gitignore"), GITIGNORE)?;
+    fs::write(directory.join("patterns.txt"), PATTERNS)?;
+    fs::write(directory.join("build.rs"), BUILD_RS)?;
+    fs::write(directory.join("src/lib.rs"), instantiate(LIB_RS))?;
+    fs::write(directory.join("tests/lexer.rs"), instantiate(TESTS_RS))?;
+
+    let lexer = directory.join("src/lexer.rs");
+    let mut out_file = fs::File::create(&lexer)?;
+    generate_code(&patterns(PATTERNS), &[], None, &mut out_file)?;
+    // Formatting is optional, the build script formats the module again if rustfmt is installed.
+    let _ = try_format(&lexer);
+    Ok(())
+}
+
+/// Returns the patterns of the lines that are neither empty nor a comment. The build script of
+/// the scaffolded crate reads the patterns the same way.
+fn patterns(text: &str) -> Vec<&str> {
+    text.lines()
+        .filter(|line| !line.is_empty() && !line.starts_with("//"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scaffold_lexer() {
+        let directory =
+            std::env::temp_dir().join(format!("scangen_scaffold_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&directory);
+        scaffold_lexer("my-lexer", &directory).unwrap();
+        let cargo_toml = fs::read_to_string(directory.join("Cargo.toml")).unwrap();
+        assert!(cargo_toml.contains("name = \"my-lexer\""));
+        assert!(cargo_toml.contains(&format!("version = \"{}\"", env!("CARGO_PKG_VERSION"))));
+        let tests = fs::read_to_string(directory.join("tests/lexer.rs")).unwrap();
+        assert!(tests.starts_with("use my_lexer::"));
+        let lexer = fs::read_to_string(directory.join("src/lexer.rs")).unwrap();
+        assert!(lexer.contains("fn create_find_iter"));
+        assert_eq!(patterns(PATTERNS).len(), 6);
+        assert!(directory.join("build.rs").exists());
+
+        // The directory must not exist yet
+        assert!(scaffold_lexer("my-lexer", &directory).is_err());
+        fs::remove_dir_all(&directory).unwrap();
+
+        for invalid in ["", "1lexer", "my lexer", "lexer!"] {
+            assert!(matches!(
+                *scaffold_lexer(invalid, &directory).unwrap_err().source,
+                ScanGenErrorKind::InvalidCrateName(_)
+            ));
+        }
+    }
+}
@@ -0,0 +1,175 @@
+//! This module deduplicates identical slices of the generated DFA data.
+//!
+//! Grammars with many similar literals like `%on`, `%sc` or `%push` yield DFAs with identical
+//! accepting states and state ranges, and sometimes even identical transitions. The slices are
+//! hashed structurally by their generated code, and every slice that is used more than once is
+//! emitted as a constant that is referenced by all DFAs using it.
+//! Only whole slices are shared. Slices that merely have a common prefix or suffix, e.g. the
+//! transitions of keywords with the same ending, are emitted separately.
+
+use std::{cell::OnceCell, collections::HashMap};
+
+use super::Result;
+
+/// Slices with fewer elements are not shared because the reference to the constant wouldn't be
+/// shorter than the slice itself.
+const MIN_SHARED_ELEMENTS: usize = 2;
+
+/// The slices of the generated DFA data.
+#[derive(Debug, Default)]
+pub(crate) struct SharedSlices {
+    /// The slices in the order of their first occurrence together with their element type and the
+    /// number of their occurrences.
+    slices: Vec<(&'static str, String, usize)>,
+    /// The index into the slices vector by element type and code.
+    index: HashMap<(&'static str, String), usize>,
+    /// The number of the constant of each slice, i.e. the number of shared slices before it.
+    /// It is computed once the slices are complete.
+    numbers: OnceCell<Vec<usize>>,
+}
+
+impl SharedSlices {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an occurrence of the slice with the given element type, code and number of elements.
+    pub(crate) fn add(&mut self, element_type: &'static str, code: String, len: usize) {
+        if len < MIN_SHARED_ELEMENTS {
+            return;
+        }
+        self.numbers.take();
+        match self.index.get(&(element_type, code.clone())) {
+            Some(i) => self.slices[*i].2 += 1,
+            None => {
+                self.index
+                    .insert((element_type, code.clone()), self.slices.len());
+                self.slices.push((element_type, code, 1));
+            }
+        }
+    }
+
+    /// Returns true if the given slice is emitted as a constant that is shared by several DFAs.
+    pub(crate) fn is_shared(&self, element_type: &'static str, code: &str) -> bool {
+        matches!(
+            self.index.get(&(element_type, code.to_string())),
+            Some(i) if self.slices[*i].2 > 1
+        )
+    }
+
+    /// Returns the name of the constant of the given slice if it is shared, otherwise the code
+    /// of the slice itself.
+    pub(crate) fn reference(&self, element_type: &'static str, code: &str) -> String {
+        match self.index.get(&(element_type, code.to_string())) {
+            Some(i) if self.slices[*i].2 > 1 => self.name(*i),
+            _ => code.to_string(),
+        }
+    }
+
+    /// Generates the constants of all shared slices.
+    pub(crate) fn generate_code(&self, output: &mut dyn std::io::Write) -> Result<()> {
+        let mut any_shared = false;
+        for (i, (element_type, code, count)) in self.slices.iter().enumerate() {
+            if *count > 1 {
+                writeln!(
+                    output,
+                    "const {}: &[{}] = {};",
+                    self.name(i),
+                    element_type,
+                    code
+                )?;
+                any_shared = true;
+            }
+        }
+        if any_shared {
+            writeln!(output)?;
+        }
+        Ok(())
+    }
+
+    /// The shared slices are numbered consecutively in the order of their first occurrence.
+    fn name(&self, i: usize) -> String {
+        let numbers = self.numbers.get_or_init(|| {
+            let mut number = 0;
+            self.slices
+                .iter()
+                .map(|(_, _, count)| {
+                    let current = number;
+                    if *count > 1 {
+                        number += 1;
+                    }
+                    current
+                })
+                .collect()
+        });
+        format!("SHARED_{}", numbers[i])
+    }
+}
+
+/// Returns the number of bytes of the given number of elements of a slice.
+/// Each element is either a `usize` or a pair of them.
+pub(crate) fn slice_bytes(element_type: &'static str, len: usize) -> usize {
+    let word = std::mem::size_of::<usize>();
+    if element_type == "usize" {
+        len * word
+    } else {
+        len * 2 * word
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiletime::{GenerateOptions, MultiPatternDfa};
+
+    #[test]
+    fn test_shared_slices() {
+        let mut shared_slices = SharedSlices::new();
+        shared_slices.add("usize", "&[1, 2, ]".to_string(), 2);
+        shared_slices.add("(usize, usize)", "&[(0, 1), (0, 0), ]".to_string(), 2);
+        shared_slices.add("usize", "&[1, 2, ]".to_string(), 2);
+        shared_slices.add("usize", "&[3, ]".to_string(), 1);
+        shared_slices.add("usize", "&[3, ]".to_string(), 1);
+        assert_eq!(shared_slices.reference("usize", "&[1, 2, ]"), "SHARED_0");
+        assert_eq!(
+            shared_slices.reference("(usize, usize)", "&[(0, 1), (0, 0), ]"),
+            "&[(0, 1), (0, 0), ]"
+        );
+        assert_eq!(shared_slices.reference("usize", "&[3, ]"), "&[3, ]");
+
+        // A slice of two pairs is shared, too, and numbered by its first occurrence
+        shared_slices.add("(usize, usize)", "&[(0, 1), (0, 0), ]".to_string(), 2);
+        assert_eq!(
+            shared_slices.reference("(usize, usize)", "&[(0, 1), (0, 0), ]"),
+            "SHARED_1"
+        );
+
+        let mut output = Vec::new();
+        shared_slices.generate_code(&mut output).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "const SHARED_0: &[usize] = &[1, 2, ];\n\
+             const SHARED_1: &[(usize, usize)] = &[(0, 1), (0, 0), ];\n\n"
+        );
+        assert_eq!(
+            slice_bytes("(usize, usize)", 2),
+            4 * std::mem::size_of::<usize>()
+        );
+    }
+
+    #[test]
+    fn test_shared_state_ranges_of_keywords() {
+        let mut multi_pattern_dfa = MultiPatternDfa::new();
+        multi_pattern_dfa
+            .add_patterns(["%on", "%sc", "%push", "%pop"])
+            .unwrap();
+        let mut output = Vec::new();
+        multi_pattern_dfa
+            .generate_code(&[], &GenerateOptions::default(), &mut output)
+            .unwrap();
+        let code = String::from_utf8(output).unwrap();
+        assert!(code
+            .contains("const SHARED_0: &[(usize, usize)] = &[(0, 1), (1, 2), (2, 3), (0, 0), ];"));
+        assert_eq!(code.matches("SHARED_0").count(), 3);
+    }
+}
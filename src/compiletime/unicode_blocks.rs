@@ -0,0 +1,860 @@
+//! This module provides the ranges of the unicode blocks.
+//!
+//! The unicode data of the `regex_syntax` crate contains no blocks, so classes like
+//! `\p{Block=Greek}` are resolved with the table of this module before the pattern is translated.
+//! The table is taken from `Blocks.txt` of the Unicode Character Database 14.0.0.
+
+/// The unicode blocks with their aliases and their first and last character.
+const BLOCKS: &[(&str, &[&str], char, char)] = &[
+    ("Basic Latin", &["ascii"], '\u{0}', '\u{7F}'),
+    (
+        "Latin-1 Supplement",
+        &["latin1", "latin1sup"],
+        '\u{80}',
+        '\u{FF}',
+    ),
+    ("Latin Extended-A", &["latinexta"], '\u{100}', '\u{17F}'),
+    ("Latin Extended-B", &["latinextb"], '\u{180}', '\u{24F}'),
+    ("IPA Extensions", &["ipaext"], '\u{250}', '\u{2AF}'),
+    (
+        "Spacing Modifier Letters",
+        &["modifierletters"],
+        '\u{2B0}',
+        '\u{2FF}',
+    ),
+    (
+        "Combining Diacritical Marks",
+        &["diacriticals"],
+        '\u{300}',
+        '\u{36F}',
+    ),
+    ("Greek and Coptic", &["greek"], '\u{370}', '\u{3FF}'),
+    ("Cyrillic", &[], '\u{400}', '\u{4FF}'),
+    (
+        "Cyrillic Supplement",
+        &["cyrillicsup", "cyrillicsupplementary"],
+        '\u{500}',
+        '\u{52F}',
+    ),
+    ("Armenian", &[], '\u{530}', '\u{58F}'),
+    ("Hebrew", &[], '\u{590}', '\u{5FF}'),
+    ("Arabic", &[], '\u{600}', '\u{6FF}'),
+    ("Syriac", &[], '\u{700}', '\u{74F}'),
+    ("Arabic Supplement", &["arabicsup"], '\u{750}', '\u{77F}'),
+    ("Thaana", &[], '\u{780}', '\u{7BF}'),
+    ("NKo", &[], '\u{7C0}', '\u{7FF}'),
+    ("Samaritan", &[], '\u{800}', '\u{83F}'),
+    ("Mandaic", &[], '\u{840}', '\u{85F}'),
+    ("Syriac Supplement", &["syriacsup"], '\u{860}', '\u{86F}'),
+    ("Arabic Extended-B", &["arabicextb"], '\u{870}', '\u{89F}'),
+    ("Arabic Extended-A", &["arabicexta"], '\u{8A0}', '\u{8FF}'),
+    ("Devanagari", &[], '\u{900}', '\u{97F}'),
+    ("Bengali", &[], '\u{980}', '\u{9FF}'),
+    ("Gurmukhi", &[], '\u{A00}', '\u{A7F}'),
+    ("Gujarati", &[], '\u{A80}', '\u{AFF}'),
+    ("Oriya", &[], '\u{B00}', '\u{B7F}'),
+    ("Tamil", &[], '\u{B80}', '\u{BFF}'),
+    ("Telugu", &[], '\u{C00}', '\u{C7F}'),
+    ("Kannada", &[], '\u{C80}', '\u{CFF}'),
+    ("Malayalam", &[], '\u{D00}', '\u{D7F}'),
+    ("Sinhala", &[], '\u{D80}', '\u{DFF}'),
+    ("Thai", &[], '\u{E00}', '\u{E7F}'),
+    ("Lao", &[], '\u{E80}', '\u{EFF}'),
+    ("Tibetan", &[], '\u{F00}', '\u{FFF}'),
+    ("Myanmar", &[], '\u{1000}', '\u{109F}'),
+    ("Georgian", &[], '\u{10A0}', '\u{10FF}'),
+    ("Hangul Jamo", &["jamo"], '\u{1100}', '\u{11FF}'),
+    ("Ethiopic", &[], '\u{1200}', '\u{137F}'),
+    (
+        "Ethiopic Supplement",
+        &["ethiopicsup"],
+        '\u{1380}',
+        '\u{139F}',
+    ),
+    ("Cherokee", &[], '\u{13A0}', '\u{13FF}'),
+    (
+        "Unified Canadian Aboriginal Syllabics",
+        &["canadiansyllabics", "ucas"],
+        '\u{1400}',
+        '\u{167F}',
+    ),
+    ("Ogham", &[], '\u{1680}', '\u{169F}'),
+    ("Runic", &[], '\u{16A0}', '\u{16FF}'),
+    ("Tagalog", &[], '\u{1700}', '\u{171F}'),
+    ("Hanunoo", &[], '\u{1720}', '\u{173F}'),
+    ("Buhid", &[], '\u{1740}', '\u{175F}'),
+    ("Tagbanwa", &[], '\u{1760}', '\u{177F}'),
+    ("Khmer", &[], '\u{1780}', '\u{17FF}'),
+    ("Mongolian", &[], '\u{1800}', '\u{18AF}'),
+    (
+        "Unified Canadian Aboriginal Syllabics Extended",
+        &["ucasext"],
+        '\u{18B0}',
+        '\u{18FF}',
+    ),
+    ("Limbu", &[], '\u{1900}', '\u{194F}'),
+    ("Tai Le", &[], '\u{1950}', '\u{197F}'),
+    ("New Tai Lue", &[], '\u{1980}', '\u{19DF}'),
+    ("Khmer Symbols", &[], '\u{19E0}', '\u{19FF}'),
+    ("Buginese", &[], '\u{1A00}', '\u{1A1F}'),
+    ("Tai Tham", &[], '\u{1A20}', '\u{1AAF}'),
+    (
+        "Combining Diacritical Marks Extended",
+        &["diacriticalsext"],
+        '\u{1AB0}',
+        '\u{1AFF}',
+    ),
+    ("Balinese", &[], '\u{1B00}', '\u{1B7F}'),
+    ("Sundanese", &[], '\u{1B80}', '\u{1BBF}'),
+    ("Batak", &[], '\u{1BC0}', '\u{1BFF}'),
+    ("Lepcha", &[], '\u{1C00}', '\u{1C4F}'),
+    ("Ol Chiki", &[], '\u{1C50}', '\u{1C7F}'),
+    (
+        "Cyrillic Extended-C",
+        &["cyrillicextc"],
+        '\u{1C80}',
+        '\u{1C8F}',
+    ),
+    (
+        "Georgian Extended",
+        &["georgianext"],
+        '\u{1C90}',
+        '\u{1CBF}',
+    ),
+    (
+        "Sundanese Supplement",
+        &["sundanesesup"],
+        '\u{1CC0}',
+        '\u{1CCF}',
+    ),
+    ("Vedic Extensions", &["vedicext"], '\u{1CD0}', '\u{1CFF}'),
+    (
+        "Phonetic Extensions",
+        &["phoneticext"],
+        '\u{1D00}',
+        '\u{1D7F}',
+    ),
+    (
+        "Phonetic Extensions Supplement",
+        &["phoneticextsup"],
+        '\u{1D80}',
+        '\u{1DBF}',
+    ),
+    (
+        "Combining Diacritical Marks Supplement",
+        &["diacriticalssup"],
+        '\u{1DC0}',
+        '\u{1DFF}',
+    ),
+    (
+        "Latin Extended Additional",
+        &["latinextadditional"],
+        '\u{1E00}',
+        '\u{1EFF}',
+    ),
+    ("Greek Extended", &["greekext"], '\u{1F00}', '\u{1FFF}'),
+    (
+        "General Punctuation",
+        &["punctuation"],
+        '\u{2000}',
+        '\u{206F}',
+    ),
+    (
+        "Superscripts and Subscripts",
+        &["superandsub"],
+        '\u{2070}',
+        '\u{209F}',
+    ),
+    ("Currency Symbols", &[], '\u{20A0}', '\u{20CF}'),
+    (
+        "Combining Diacritical Marks for Symbols",
+        &["combiningmarksforsymbols", "diacriticalsforsymbols"],
+        '\u{20D0}',
+        '\u{20FF}',
+    ),
+    ("Letterlike Symbols", &[], '\u{2100}', '\u{214F}'),
+    ("Number Forms", &[], '\u{2150}', '\u{218F}'),
+    ("Arrows", &[], '\u{2190}', '\u{21FF}'),
+    (
+        "Mathematical Operators",
+        &["mathoperators"],
+        '\u{2200}',
+        '\u{22FF}',
+    ),
+    (
+        "Miscellaneous Technical",
+        &["misctechnical"],
+        '\u{2300}',
+        '\u{23FF}',
+    ),
+    ("Control Pictures", &[], '\u{2400}', '\u{243F}'),
+    (
+        "Optical Character Recognition",
+        &["ocr"],
+        '\u{2440}',
+        '\u{245F}',
+    ),
+    (
+        "Enclosed Alphanumerics",
+        &["enclosedalphanum"],
+        '\u{2460}',
+        '\u{24FF}',
+    ),
+    ("Box Drawing", &[], '\u{2500}', '\u{257F}'),
+    ("Block Elements", &[], '\u{2580}', '\u{259F}'),
+    ("Geometric Shapes", &[], '\u{25A0}', '\u{25FF}'),
+    (
+        "Miscellaneous Symbols",
+        &["miscsymbols"],
+        '\u{2600}',
+        '\u{26FF}',
+    ),
+    ("Dingbats", &[], '\u{2700}', '\u{27BF}'),
+    (
+        "Miscellaneous Mathematical Symbols-A",
+        &["miscmathsymbolsa"],
+        '\u{27C0}',
+        '\u{27EF}',
+    ),
+    (
+        "Supplemental Arrows-A",
+        &["suparrowsa"],
+        '\u{27F0}',
+        '\u{27FF}',
+    ),
+    ("Braille Patterns", &["braille"], '\u{2800}', '\u{28FF}'),
+    (
+        "Supplemental Arrows-B",
+        &["suparrowsb"],
+        '\u{2900}',
+        '\u{297F}',
+    ),
+    (
+        "Miscellaneous Mathematical Symbols-B",
+        &["miscmathsymbolsb"],
+        '\u{2980}',
+        '\u{29FF}',
+    ),
+    (
+        "Supplemental Mathematical Operators",
+        &["supmathoperators"],
+        '\u{2A00}',
+        '\u{2AFF}',
+    ),
+    (
+        "Miscellaneous Symbols and Arrows",
+        &["miscarrows"],
+        '\u{2B00}',
+        '\u{2BFF}',
+    ),
+    ("Glagolitic", &[], '\u{2C00}', '\u{2C5F}'),
+    ("Latin Extended-C", &["latinextc"], '\u{2C60}', '\u{2C7F}'),
+    ("Coptic", &[], '\u{2C80}', '\u{2CFF}'),
+    (
+        "Georgian Supplement",
+        &["georgiansup"],
+        '\u{2D00}',
+        '\u{2D2F}',
+    ),
+    ("Tifinagh", &[], '\u{2D30}', '\u{2D7F}'),
+    (
+        "Ethiopic Extended",
+        &["ethiopicext"],
+        '\u{2D80}',
+        '\u{2DDF}',
+    ),
+    (
+        "Cyrillic Extended-A",
+        &["cyrillicexta"],
+        '\u{2DE0}',
+        '\u{2DFF}',
+    ),
+    (
+        "Supplemental Punctuation",
+        &["suppunctuation"],
+        '\u{2E00}',
+        '\u{2E7F}',
+    ),
+    (
+        "CJK Radicals Supplement",
+        &["cjkradicalssup"],
+        '\u{2E80}',
+        '\u{2EFF}',
+    ),
+    ("Kangxi Radicals", &["kangxi"], '\u{2F00}', '\u{2FDF}'),
+    (
+        "Ideographic Description Characters",
+        &["idc"],
+        '\u{2FF0}',
+        '\u{2FFF}',
+    ),
+    (
+        "CJK Symbols and Punctuation",
+        &["cjksymbols"],
+        '\u{3000}',
+        '\u{303F}',
+    ),
+    ("Hiragana", &[], '\u{3040}', '\u{309F}'),
+    ("Katakana", &[], '\u{30A0}', '\u{30FF}'),
+    ("Bopomofo", &[], '\u{3100}', '\u{312F}'),
+    (
+        "Hangul Compatibility Jamo",
+        &["compatjamo"],
+        '\u{3130}',
+        '\u{318F}',
+    ),
+    ("Kanbun", &[], '\u{3190}', '\u{319F}'),
+    (
+        "Bopomofo Extended",
+        &["bopomofoext"],
+        '\u{31A0}',
+        '\u{31BF}',
+    ),
+    ("CJK Strokes", &[], '\u{31C0}', '\u{31EF}'),
+    (
+        "Katakana Phonetic Extensions",
+        &["katakanaext"],
+        '\u{31F0}',
+        '\u{31FF}',
+    ),
+    (
+        "Enclosed CJK Letters and Months",
+        &["enclosedcjk"],
+        '\u{3200}',
+        '\u{32FF}',
+    ),
+    ("CJK Compatibility", &["cjkcompat"], '\u{3300}', '\u{33FF}'),
+    (
+        "CJK Unified Ideographs Extension A",
+        &["cjkexta"],
+        '\u{3400}',
+        '\u{4DBF}',
+    ),
+    (
+        "Yijing Hexagram Symbols",
+        &["yijing"],
+        '\u{4DC0}',
+        '\u{4DFF}',
+    ),
+    ("CJK Unified Ideographs", &["cjk"], '\u{4E00}', '\u{9FFF}'),
+    ("Yi Syllables", &[], '\u{A000}', '\u{A48F}'),
+    ("Yi Radicals", &[], '\u{A490}', '\u{A4CF}'),
+    ("Lisu", &[], '\u{A4D0}', '\u{A4FF}'),
+    ("Vai", &[], '\u{A500}', '\u{A63F}'),
+    (
+        "Cyrillic Extended-B",
+        &["cyrillicextb"],
+        '\u{A640}',
+        '\u{A69F}',
+    ),
+    ("Bamum", &[], '\u{A6A0}', '\u{A6FF}'),
+    ("Modifier Tone Letters", &[], '\u{A700}', '\u{A71F}'),
+    ("Latin Extended-D", &["latinextd"], '\u{A720}', '\u{A7FF}'),
+    ("Syloti Nagri", &[], '\u{A800}', '\u{A82F}'),
+    (
+        "Common Indic Number Forms",
+        &["indicnumberforms"],
+        '\u{A830}',
+        '\u{A83F}',
+    ),
+    ("Phags-pa", &[], '\u{A840}', '\u{A87F}'),
+    ("Saurashtra", &[], '\u{A880}', '\u{A8DF}'),
+    (
+        "Devanagari Extended",
+        &["devanagariext"],
+        '\u{A8E0}',
+        '\u{A8FF}',
+    ),
+    ("Kayah Li", &[], '\u{A900}', '\u{A92F}'),
+    ("Rejang", &[], '\u{A930}', '\u{A95F}'),
+    (
+        "Hangul Jamo Extended-A",
+        &["jamoexta"],
+        '\u{A960}',
+        '\u{A97F}',
+    ),
+    ("Javanese", &[], '\u{A980}', '\u{A9DF}'),
+    (
+        "Myanmar Extended-B",
+        &["myanmarextb"],
+        '\u{A9E0}',
+        '\u{A9FF}',
+    ),
+    ("Cham", &[], '\u{AA00}', '\u{AA5F}'),
+    (
+        "Myanmar Extended-A",
+        &["myanmarexta"],
+        '\u{AA60}',
+        '\u{AA7F}',
+    ),
+    ("Tai Viet", &[], '\u{AA80}', '\u{AADF}'),
+    (
+        "Meetei Mayek Extensions",
+        &["meeteimayekext"],
+        '\u{AAE0}',
+        '\u{AAFF}',
+    ),
+    (
+        "Ethiopic Extended-A",
+        &["ethiopicexta"],
+        '\u{AB00}',
+        '\u{AB2F}',
+    ),
+    ("Latin Extended-E", &["latinexte"], '\u{AB30}', '\u{AB6F}'),
+    (
+        "Cherokee Supplement",
+        &["cherokeesup"],
+        '\u{AB70}',
+        '\u{ABBF}',
+    ),
+    ("Meetei Mayek", &[], '\u{ABC0}', '\u{ABFF}'),
+    ("Hangul Syllables", &["hangul"], '\u{AC00}', '\u{D7AF}'),
+    (
+        "Hangul Jamo Extended-B",
+        &["jamoextb"],
+        '\u{D7B0}',
+        '\u{D7FF}',
+    ),
+    (
+        "Private Use Area",
+        &["privateuse", "pua"],
+        '\u{E000}',
+        '\u{F8FF}',
+    ),
+    (
+        "CJK Compatibility Ideographs",
+        &["cjkcompatideographs"],
+        '\u{F900}',
+        '\u{FAFF}',
+    ),
+    (
+        "Alphabetic Presentation Forms",
+        &["alphabeticpf"],
+        '\u{FB00}',
+        '\u{FB4F}',
+    ),
+    (
+        "Arabic Presentation Forms-A",
+        &["arabicpfa"],
+        '\u{FB50}',
+        '\u{FDFF}',
+    ),
+    ("Variation Selectors", &["vs"], '\u{FE00}', '\u{FE0F}'),
+    ("Vertical Forms", &[], '\u{FE10}', '\u{FE1F}'),
+    (
+        "Combining Half Marks",
+        &["halfmarks"],
+        '\u{FE20}',
+        '\u{FE2F}',
+    ),
+    (
+        "CJK Compatibility Forms",
+        &["cjkcompatforms"],
+        '\u{FE30}',
+        '\u{FE4F}',
+    ),
+    (
+        "Small Form Variants",
+        &["smallforms"],
+        '\u{FE50}',
+        '\u{FE6F}',
+    ),
+    (
+        "Arabic Presentation Forms-B",
+        &["arabicpfb"],
+        '\u{FE70}',
+        '\u{FEFF}',
+    ),
+    (
+        "Halfwidth and Fullwidth Forms",
+        &["halfandfullforms"],
+        '\u{FF00}',
+        '\u{FFEF}',
+    ),
+    ("Specials", &[], '\u{FFF0}', '\u{FFFF}'),
+    ("Linear B Syllabary", &[], '\u{10000}', '\u{1007F}'),
+    ("Linear B Ideograms", &[], '\u{10080}', '\u{100FF}'),
+    ("Aegean Numbers", &[], '\u{10100}', '\u{1013F}'),
+    ("Ancient Greek Numbers", &[], '\u{10140}', '\u{1018F}'),
+    ("Ancient Symbols", &[], '\u{10190}', '\u{101CF}'),
+    ("Phaistos Disc", &["phaistos"], '\u{101D0}', '\u{101FF}'),
+    ("Lycian", &[], '\u{10280}', '\u{1029F}'),
+    ("Carian", &[], '\u{102A0}', '\u{102DF}'),
+    ("Coptic Epact Numbers", &[], '\u{102E0}', '\u{102FF}'),
+    ("Old Italic", &[], '\u{10300}', '\u{1032F}'),
+    ("Gothic", &[], '\u{10330}', '\u{1034F}'),
+    ("Old Permic", &[], '\u{10350}', '\u{1037F}'),
+    ("Ugaritic", &[], '\u{10380}', '\u{1039F}'),
+    ("Old Persian", &[], '\u{103A0}', '\u{103DF}'),
+    ("Deseret", &[], '\u{10400}', '\u{1044F}'),
+    ("Shavian", &[], '\u{10450}', '\u{1047F}'),
+    ("Osmanya", &[], '\u{10480}', '\u{104AF}'),
+    ("Osage", &[], '\u{104B0}', '\u{104FF}'),
+    ("Elbasan", &[], '\u{10500}', '\u{1052F}'),
+    ("Caucasian Albanian", &[], '\u{10530}', '\u{1056F}'),
+    ("Vithkuqi", &[], '\u{10570}', '\u{105BF}'),
+    ("Linear A", &[], '\u{10600}', '\u{1077F}'),
+    ("Latin Extended-F", &["latinextf"], '\u{10780}', '\u{107BF}'),
+    ("Cypriot Syllabary", &[], '\u{10800}', '\u{1083F}'),
+    ("Imperial Aramaic", &[], '\u{10840}', '\u{1085F}'),
+    ("Palmyrene", &[], '\u{10860}', '\u{1087F}'),
+    ("Nabataean", &[], '\u{10880}', '\u{108AF}'),
+    ("Hatran", &[], '\u{108E0}', '\u{108FF}'),
+    ("Phoenician", &[], '\u{10900}', '\u{1091F}'),
+    ("Lydian", &[], '\u{10920}', '\u{1093F}'),
+    ("Meroitic Hieroglyphs", &[], '\u{10980}', '\u{1099F}'),
+    ("Meroitic Cursive", &[], '\u{109A0}', '\u{109FF}'),
+    ("Kharoshthi", &[], '\u{10A00}', '\u{10A5F}'),
+    ("Old South Arabian", &[], '\u{10A60}', '\u{10A7F}'),
+    ("Old North Arabian", &[], '\u{10A80}', '\u{10A9F}'),
+    ("Manichaean", &[], '\u{10AC0}', '\u{10AFF}'),
+    ("Avestan", &[], '\u{10B00}', '\u{10B3F}'),
+    ("Inscriptional Parthian", &[], '\u{10B40}', '\u{10B5F}'),
+    ("Inscriptional Pahlavi", &[], '\u{10B60}', '\u{10B7F}'),
+    ("Psalter Pahlavi", &[], '\u{10B80}', '\u{10BAF}'),
+    ("Old Turkic", &[], '\u{10C00}', '\u{10C4F}'),
+    ("Old Hungarian", &[], '\u{10C80}', '\u{10CFF}'),
+    ("Hanifi Rohingya", &[], '\u{10D00}', '\u{10D3F}'),
+    ("Rumi Numeral Symbols", &["rumi"], '\u{10E60}', '\u{10E7F}'),
+    ("Yezidi", &[], '\u{10E80}', '\u{10EBF}'),
+    ("Old Sogdian", &[], '\u{10F00}', '\u{10F2F}'),
+    ("Sogdian", &[], '\u{10F30}', '\u{10F6F}'),
+    ("Old Uyghur", &[], '\u{10F70}', '\u{10FAF}'),
+    ("Chorasmian", &[], '\u{10FB0}', '\u{10FDF}'),
+    ("Elymaic", &[], '\u{10FE0}', '\u{10FFF}'),
+    ("Brahmi", &[], '\u{11000}', '\u{1107F}'),
+    ("Kaithi", &[], '\u{11080}', '\u{110CF}'),
+    ("Sora Sompeng", &[], '\u{110D0}', '\u{110FF}'),
+    ("Chakma", &[], '\u{11100}', '\u{1114F}'),
+    ("Mahajani", &[], '\u{11150}', '\u{1117F}'),
+    ("Sharada", &[], '\u{11180}', '\u{111DF}'),
+    ("Sinhala Archaic Numbers", &[], '\u{111E0}', '\u{111FF}'),
+    ("Khojki", &[], '\u{11200}', '\u{1124F}'),
+    ("Multani", &[], '\u{11280}', '\u{112AF}'),
+    ("Khudawadi", &[], '\u{112B0}', '\u{112FF}'),
+    ("Grantha", &[], '\u{11300}', '\u{1137F}'),
+    ("Newa", &[], '\u{11400}', '\u{1147F}'),
+    ("Tirhuta", &[], '\u{11480}', '\u{114DF}'),
+    ("Siddham", &[], '\u{11580}', '\u{115FF}'),
+    ("Modi", &[], '\u{11600}', '\u{1165F}'),
+    (
+        "Mongolian Supplement",
+        &["mongoliansup"],
+        '\u{11660}',
+        '\u{1167F}',
+    ),
+    ("Takri", &[], '\u{11680}', '\u{116CF}'),
+    ("Ahom", &[], '\u{11700}', '\u{1174F}'),
+    ("Dogra", &[], '\u{11800}', '\u{1184F}'),
+    ("Warang Citi", &[], '\u{118A0}', '\u{118FF}'),
+    ("Dives Akuru", &[], '\u{11900}', '\u{1195F}'),
+    ("Nandinagari", &[], '\u{119A0}', '\u{119FF}'),
+    ("Zanabazar Square", &[], '\u{11A00}', '\u{11A4F}'),
+    ("Soyombo", &[], '\u{11A50}', '\u{11AAF}'),
+    (
+        "Unified Canadian Aboriginal Syllabics Extended-A",
+        &["ucasexta"],
+        '\u{11AB0}',
+        '\u{11ABF}',
+    ),
+    ("Pau Cin Hau", &[], '\u{11AC0}', '\u{11AFF}'),
+    ("Bhaiksuki", &[], '\u{11C00}', '\u{11C6F}'),
+    ("Marchen", &[], '\u{11C70}', '\u{11CBF}'),
+    ("Masaram Gondi", &[], '\u{11D00}', '\u{11D5F}'),
+    ("Gunjala Gondi", &[], '\u{11D60}', '\u{11DAF}'),
+    ("Makasar", &[], '\u{11EE0}', '\u{11EFF}'),
+    ("Lisu Supplement", &["lisusup"], '\u{11FB0}', '\u{11FBF}'),
+    ("Tamil Supplement", &["tamilsup"], '\u{11FC0}', '\u{11FFF}'),
+    ("Cuneiform", &[], '\u{12000}', '\u{123FF}'),
+    (
+        "Cuneiform Numbers and Punctuation",
+        &["cuneiformnumbers"],
+        '\u{12400}',
+        '\u{1247F}',
+    ),
+    ("Early Dynastic Cuneiform", &[], '\u{12480}', '\u{1254F}'),
+    ("Cypro-Minoan", &[], '\u{12F90}', '\u{12FFF}'),
+    ("Egyptian Hieroglyphs", &[], '\u{13000}', '\u{1342F}'),
+    (
+        "Egyptian Hieroglyph Format Controls",
+        &[],
+        '\u{13430}',
+        '\u{1343F}',
+    ),
+    ("Anatolian Hieroglyphs", &[], '\u{14400}', '\u{1467F}'),
+    ("Bamum Supplement", &["bamumsup"], '\u{16800}', '\u{16A3F}'),
+    ("Mro", &[], '\u{16A40}', '\u{16A6F}'),
+    ("Tangsa", &[], '\u{16A70}', '\u{16ACF}'),
+    ("Bassa Vah", &[], '\u{16AD0}', '\u{16AFF}'),
+    ("Pahawh Hmong", &[], '\u{16B00}', '\u{16B8F}'),
+    ("Medefaidrin", &[], '\u{16E40}', '\u{16E9F}'),
+    ("Miao", &[], '\u{16F00}', '\u{16F9F}'),
+    (
+        "Ideographic Symbols and Punctuation",
+        &["ideographicsymbols"],
+        '\u{16FE0}',
+        '\u{16FFF}',
+    ),
+    ("Tangut", &[], '\u{17000}', '\u{187FF}'),
+    ("Tangut Components", &[], '\u{18800}', '\u{18AFF}'),
+    ("Khitan Small Script", &[], '\u{18B00}', '\u{18CFF}'),
+    (
+        "Tangut Supplement",
+        &["tangutsup"],
+        '\u{18D00}',
+        '\u{18D7F}',
+    ),
+    ("Kana Extended-B", &["kanaextb"], '\u{1AFF0}', '\u{1AFFF}'),
+    ("Kana Supplement", &["kanasup"], '\u{1B000}', '\u{1B0FF}'),
+    ("Kana Extended-A", &["kanaexta"], '\u{1B100}', '\u{1B12F}'),
+    (
+        "Small Kana Extension",
+        &["smallkanaext"],
+        '\u{1B130}',
+        '\u{1B16F}',
+    ),
+    ("Nushu", &[], '\u{1B170}', '\u{1B2FF}'),
+    ("Duployan", &[], '\u{1BC00}', '\u{1BC9F}'),
+    ("Shorthand Format Controls", &[], '\u{1BCA0}', '\u{1BCAF}'),
+    (
+        "Znamenny Musical Notation",
+        &["znamennymusic"],
+        '\u{1CF00}',
+        '\u{1CFCF}',
+    ),
+    (
+        "Byzantine Musical Symbols",
+        &["byzantinemusic"],
+        '\u{1D000}',
+        '\u{1D0FF}',
+    ),
+    ("Musical Symbols", &["music"], '\u{1D100}', '\u{1D1FF}'),
+    (
+        "Ancient Greek Musical Notation",
+        &["ancientgreekmusic"],
+        '\u{1D200}',
+        '\u{1D24F}',
+    ),
+    ("Mayan Numerals", &[], '\u{1D2E0}', '\u{1D2FF}'),
+    (
+        "Tai Xuan Jing Symbols",
+        &["taixuanjing"],
+        '\u{1D300}',
+        '\u{1D35F}',
+    ),
+    (
+        "Counting Rod Numerals",
+        &["countingrod"],
+        '\u{1D360}',
+        '\u{1D37F}',
+    ),
+    (
+        "Mathematical Alphanumeric Symbols",
+        &["mathalphanum"],
+        '\u{1D400}',
+        '\u{1D7FF}',
+    ),
+    ("Sutton SignWriting", &[], '\u{1D800}', '\u{1DAAF}'),
+    ("Latin Extended-G", &["latinextg"], '\u{1DF00}', '\u{1DFFF}'),
+    (
+        "Glagolitic Supplement",
+        &["glagoliticsup"],
+        '\u{1E000}',
+        '\u{1E02F}',
+    ),
+    ("Nyiakeng Puachue Hmong", &[], '\u{1E100}', '\u{1E14F}'),
+    ("Toto", &[], '\u{1E290}', '\u{1E2BF}'),
+    ("Wancho", &[], '\u{1E2C0}', '\u{1E2FF}'),
+    (
+        "Ethiopic Extended-B",
+        &["ethiopicextb"],
+        '\u{1E7E0}',
+        '\u{1E7FF}',
+    ),
+    ("Mende Kikakui", &[], '\u{1E800}', '\u{1E8DF}'),
+    ("Adlam", &[], '\u{1E900}', '\u{1E95F}'),
+    ("Indic Siyaq Numbers", &[], '\u{1EC70}', '\u{1ECBF}'),
+    ("Ottoman Siyaq Numbers", &[], '\u{1ED00}', '\u{1ED4F}'),
+    (
+        "Arabic Mathematical Alphabetic Symbols",
+        &["arabicmath"],
+        '\u{1EE00}',
+        '\u{1EEFF}',
+    ),
+    ("Mahjong Tiles", &["mahjong"], '\u{1F000}', '\u{1F02F}'),
+    ("Domino Tiles", &["domino"], '\u{1F030}', '\u{1F09F}'),
+    ("Playing Cards", &[], '\u{1F0A0}', '\u{1F0FF}'),
+    (
+        "Enclosed Alphanumeric Supplement",
+        &["enclosedalphanumsup"],
+        '\u{1F100}',
+        '\u{1F1FF}',
+    ),
+    (
+        "Enclosed Ideographic Supplement",
+        &["enclosedideographicsup"],
+        '\u{1F200}',
+        '\u{1F2FF}',
+    ),
+    (
+        "Miscellaneous Symbols and Pictographs",
+        &["miscpictographs"],
+        '\u{1F300}',
+        '\u{1F5FF}',
+    ),
+    ("Emoticons", &[], '\u{1F600}', '\u{1F64F}'),
+    ("Ornamental Dingbats", &[], '\u{1F650}', '\u{1F67F}'),
+    (
+        "Transport and Map Symbols",
+        &["transportandmap"],
+        '\u{1F680}',
+        '\u{1F6FF}',
+    ),
+    (
+        "Alchemical Symbols",
+        &["alchemical"],
+        '\u{1F700}',
+        '\u{1F77F}',
+    ),
+    (
+        "Geometric Shapes Extended",
+        &["geometricshapesext"],
+        '\u{1F780}',
+        '\u{1F7FF}',
+    ),
+    (
+        "Supplemental Arrows-C",
+        &["suparrowsc"],
+        '\u{1F800}',
+        '\u{1F8FF}',
+    ),
+    (
+        "Supplemental Symbols and Pictographs",
+        &["supsymbolsandpictographs"],
+        '\u{1F900}',
+        '\u{1F9FF}',
+    ),
+    ("Chess Symbols", &[], '\u{1FA00}', '\u{1FA6F}'),
+    (
+        "Symbols and Pictographs Extended-A",
+        &["symbolsandpictographsexta"],
+        '\u{1FA70}',
+        '\u{1FAFF}',
+    ),
+    (
+        "Symbols for Legacy Computing",
+        &[],
+        '\u{1FB00}',
+        '\u{1FBFF}',
+    ),
+    (
+        "CJK Unified Ideographs Extension B",
+        &["cjkextb"],
+        '\u{20000}',
+        '\u{2A6DF}',
+    ),
+    (
+        "CJK Unified Ideographs Extension C",
+        &["cjkextc"],
+        '\u{2A700}',
+        '\u{2B73F}',
+    ),
+    (
+        "CJK Unified Ideographs Extension D",
+        &["cjkextd"],
+        '\u{2B740}',
+        '\u{2B81F}',
+    ),
+    (
+        "CJK Unified Ideographs Extension E",
+        &["cjkexte"],
+        '\u{2B820}',
+        '\u{2CEAF}',
+    ),
+    (
+        "CJK Unified Ideographs Extension F",
+        &["cjkextf"],
+        '\u{2CEB0}',
+        '\u{2EBEF}',
+    ),
+    (
+        "CJK Compatibility Ideographs Supplement",
+        &["cjkcompatideographssup"],
+        '\u{2F800}',
+        '\u{2FA1F}',
+    ),
+    (
+        "CJK Unified Ideographs Extension G",
+        &["cjkextg"],
+        '\u{30000}',
+        '\u{3134F}',
+    ),
+    ("Tags", &[], '\u{E0000}', '\u{E007F}'),
+    (
+        "Variation Selectors Supplement",
+        &["vssup"],
+        '\u{E0100}',
+        '\u{E01EF}',
+    ),
+    (
+        "Supplementary Private Use Area-A",
+        &["suppuaa"],
+        '\u{F0000}',
+        '\u{FFFFF}',
+    ),
+    (
+        "Supplementary Private Use Area-B",
+        &["suppuab"],
+        '\u{100000}',
+        '\u{10FFFF}',
+    ),
+];
+
+/// Returns the range of the unicode block with the given name.
+/// As required by the Unicode standard, the names are compared ignoring case, whitespace,
+/// underscores, hyphens and a leading `is`.
+pub(crate) fn block_range(name: &str) -> Option<(char, char)> {
+    let name = normalize(name);
+    BLOCKS
+        .iter()
+        .find(|(block, aliases, _, _)| {
+            normalize(block) == name || aliases.iter().any(|alias| *alias == name)
+        })
+        .map(|(_, _, first, last)| (*first, *last))
+}
+
+fn normalize(name: &str) -> String {
+    let name = name
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '_' && *c != '-')
+        .collect::<String>()
+        .to_lowercase();
+    match name.strip_prefix("is") {
+        Some(stripped) => stripped.to_string(),
+        None => name,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_block_range() {
+        assert_eq!(
+            block_range("Greek and Coptic"),
+            Some(('\u{370}', '\u{3ff}'))
+        );
+        assert_eq!(
+            block_range("greek_and_coptic"),
+            Some(('\u{370}', '\u{3ff}'))
+        );
+        assert_eq!(
+            block_range("Latin-1 Supplement"),
+            Some(('\u{80}', '\u{ff}'))
+        );
+        assert_eq!(block_range("Greek"), Some(('\u{370}', '\u{3ff}')));
+        assert_eq!(block_range("isCyrillic"), Some(('\u{400}', '\u{4ff}')));
+        assert_eq!(block_range("NoSuchBlock"), None);
+    }
+}
@@ -0,0 +1,135 @@
+//! A scanner for comma separated values with quoted fields as defined in RFC 4180.
+//!
+//! A quoted field is scanned as an opening [QUOTE], the [QUOTED_TEXT] and [ESCAPED_QUOTE]s of its
+//! content and a closing [QUOTE]. The scanner switches into the scanner mode `QUOTED` for the
+//! content, so commas and line breaks within a quoted field are part of the field.
+//!
+//! ```rust
+//! use scangen::examples::csv;
+//!
+//! let input = "name,quote\nAda,\"Say \"\"hi\"\", then go\"\n";
+//! let texts = csv::find_iter(input)
+//!     .tokens()
+//!     .filter(|token| token.token_type == csv::FIELD || token.token_type == csv::QUOTED_TEXT)
+//!     .map(|token| token.text)
+//!     .collect::<Vec<_>>();
+//! assert_eq!(texts, ["name", "quote", "Ada", "Say ", "hi", ", then go"]);
+//! ```
+
+use crate::{FindMatches, Scanner, ScannerModeData, ScannerModeId, TokenType};
+
+use super::generated::csv_scanner;
+
+/// The terminals of the scanner. The index of a terminal is its token type.
+pub const TERMINALS: &[&str] = &[
+    /* 0 */ r"\r\n|\r|\n", // Newline
+    /* 1 */ r",", // Comma
+    /* 2 */ r#"[^,"\r\n]+"#, // Field
+    /* 3 */ r#"""#, // Quote
+    /* 4 */ r#""""#, // Escaped quote
+    /* 5 */ r#"[^"]+"#, // Quoted text
+];
+
+/// The scanner modes of the scanner.
+pub const MODES: &[ScannerModeData] = &[
+    (
+        "INITIAL",
+        &[
+            (0, TokenType::new(0)), // Newline
+            (1, TokenType::new(1)), // Comma
+            (2, TokenType::new(2)), // Field
+            (3, TokenType::new(3)), // Quote
+        ],
+        &[
+            (TokenType::new(3), ScannerModeId::new(1)), // Token "Quote" -> Mode "QUOTED"
+        ],
+        None,
+    ),
+    (
+        "QUOTED",
+        &[
+            (3, TokenType::new(3)), // Quote
+            (4, TokenType::new(4)), // Escaped quote
+            (5, TokenType::new(5)), // Quoted text
+        ],
+        &[
+            (TokenType::new(3), ScannerModeId::new(0)), // Token "Quote" -> Mode "INITIAL"
+        ],
+        None,
+    ),
+];
+
+/// A line break that ends a record.
+pub const NEWLINE: TokenType = TokenType::new(0);
+/// The comma that separates two fields.
+pub const COMMA: TokenType = TokenType::new(1);
+/// The text of a field without quotes.
+pub const FIELD: TokenType = TokenType::new(2);
+/// The quote that opens or closes a quoted field.
+pub const QUOTE: TokenType = TokenType::new(3);
+/// Two quotes within a quoted field that stand for one quote.
+pub const ESCAPED_QUOTE: TokenType = TokenType::new(4);
+/// Text within a quoted field, which may contain commas and line breaks.
+pub const QUOTED_TEXT: TokenType = TokenType::new(5);
+
+/// Creates a new scanner for comma separated values.
+pub fn create_scanner() -> Scanner {
+    csv_scanner::create_scanner()
+}
+
+/// Returns the scanner for comma separated values that is shared by all callers. It is created
+/// on first use.
+pub fn scanner() -> &'static Scanner {
+    csv_scanner::scanner()
+}
+
+/// Returns an iterator over the tokens of the given text.
+pub fn find_iter(input: &str) -> FindMatches<'_> {
+    csv_scanner::create_find_iter(scanner(), input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "generate")]
+    fn generate_code_for_csv_scanner() {
+        let file_name = "src/examples/generated/csv_scanner.rs";
+        {
+            let mut out_file = std::fs::File::create(file_name).expect("Failed to create file");
+            crate::generate_code(TERMINALS, MODES, Some("crate"), &mut out_file)
+                .expect("Failed to generate code");
+        }
+        crate::try_format(file_name).expect("Failed to format the generated code");
+    }
+
+    #[test]
+    fn test_csv() {
+        let input = "a,,\"\"\r\n\"x\"\"\",\"1,\n2\"";
+        let tokens = find_iter(input)
+            .tokens()
+            .map(|token| (token.token_type, token.text))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            tokens,
+            vec![
+                (FIELD, "a"),
+                (COMMA, ","),
+                (COMMA, ","),
+                (QUOTE, "\""),
+                (QUOTE, "\""),
+                (NEWLINE, "\r\n"),
+                (QUOTE, "\""),
+                (QUOTED_TEXT, "x"),
+                (ESCAPED_QUOTE, "\"\""),
+                (QUOTE, "\""),
+                (COMMA, ","),
+                (QUOTE, "\""),
+                (QUOTED_TEXT, "1,\n2"),
+                (QUOTE, "\""),
+            ]
+        );
+        assert!(crate::verify_lossless(input, &find_iter(input).collect::<Vec<_>>()).is_ok());
+    }
+}
@@ -0,0 +1,154 @@
+#![allow(clippy::manual_is_ascii_check)]
+
+use crate::prelude::{
+    DfaData, FindMatches, RunClassData, Scanner, ScannerDef, ScannerModeData, ScannerModeId,
+    StartSetData, TokenType,
+};
+
+const SHARED_0: &[(usize, usize)] = &[(0, 1), (0, 0)];
+const SHARED_1: &[(usize, usize)] = &[(0, 1), (1, 2)];
+
+const DFAS: &[DfaData] = &[
+    /* 0 */
+    (
+        "\\r\\n|\\r|\\n",
+        &[1, 2],
+        &[(0, 2), (0, 0), (2, 3)],
+        &[(0, 2), (1, 1), (1, 1)],
+    ),
+    /* 1 */
+    (",", &[1], SHARED_0, &[(2, 1)]),
+    /* 2 */
+    ("[^,\"\\r\\n]+", &[1], SHARED_1, &[(3, 1), (3, 1)]),
+    /* 3 */
+    ("\"", &[1], SHARED_0, &[(4, 1)]),
+    /* 4 */
+    ("\"\"", &[2], &[(0, 1), (1, 2), (0, 0)], &[(4, 1), (4, 2)]),
+    /* 5 */
+    ("[^\"]+", &[1], SHARED_1, &[(5, 1), (5, 1)]),
+];
+
+const MODES: &[ScannerModeData] = &[
+    /* 0 */
+    (
+        "INITIAL",
+        &[
+            (0, TokenType::new(0)),
+            (1, TokenType::new(1)),
+            (2, TokenType::new(2)),
+            (3, TokenType::new(3)),
+        ],
+        &[(TokenType::new(3), ScannerModeId::new(1))],
+        None,
+    ),
+    /* 1 */
+    (
+        "QUOTED",
+        &[
+            (3, TokenType::new(3)),
+            (4, TokenType::new(4)),
+            (5, TokenType::new(5)),
+        ],
+        &[(TokenType::new(3), ScannerModeId::new(0))],
+        None,
+    ),
+];
+
+const START_SETS: &[StartSetData] = &[
+    /* 0 */
+    (
+        0xffffffffffffffffffffffffffffffff,
+        &[('\u{80}', '\u{10ffff}')],
+    ),
+    /* 1 */
+    (
+        0xffffffffffffffffffffffffffffffff,
+        &[('\u{80}', '\u{10ffff}')],
+    ),
+];
+
+const RUN_CLASSES: &[RunClassData] = &[
+    (
+        3,
+        (
+            0xffffffffffffffffffffeffbffffdbff,
+            &[('\u{80}', '\u{10ffff}')],
+        ),
+    ),
+    (
+        5,
+        (
+            0xfffffffffffffffffffffffbffffffff,
+            &[('\u{80}', '\u{10ffff}')],
+        ),
+    ),
+];
+
+fn matches_char_class(c: char, char_class: usize) -> bool {
+    match char_class {
+        /* \r */
+        0 => c == '\r',
+        /* \n */
+        1 => c == '\n',
+        /* , */
+        2 => c == ',',
+        /* [^\n\r",] */
+        3 => !matches!(c, '\n' | '\r' | '\"' | ','),
+        /* " */
+        4 => c == '\"',
+        /* [^"] */
+        5 => c != '\"',
+        _ => false,
+    }
+}
+
+/* INITIAL */
+fn matches_char_class_0(c: char, char_class: usize) -> bool {
+    match char_class {
+        /* \r */
+        0 => c == '\r',
+        /* \n */
+        1 => c == '\n',
+        /* , */
+        2 => c == ',',
+        /* [^\n\r",] */
+        3 => !matches!(c, '\n' | '\r' | '\"' | ','),
+        /* " */
+        4 => c == '\"',
+        _ => false,
+    }
+}
+
+/* QUOTED */
+fn matches_char_class_1(c: char, char_class: usize) -> bool {
+    match char_class {
+        /* " */
+        4 => c == '\"',
+        /* [^"] */
+        5 => c != '\"',
+        _ => false,
+    }
+}
+
+const MODE_MATCH_FUNCTIONS: &[fn(char, usize) -> bool] =
+    &[matches_char_class_0, matches_char_class_1];
+
+pub(crate) static SCANNER_DEF: ScannerDef = ScannerDef::new(DFAS, MODES)
+    .generated_with("0.1.0", 2)
+    .with_start_sets(START_SETS)
+    .with_run_classes(RUN_CLASSES)
+    .with_mode_match_functions(MODE_MATCH_FUNCTIONS);
+
+pub(crate) fn create_scanner() -> Scanner {
+    SCANNER_DEF.create_scanner()
+}
+
+/// Returns the scanner that is shared by all callers. It is created on first use.
+pub(crate) fn scanner() -> &'static Scanner {
+    static SCANNER: std::sync::OnceLock<Scanner> = std::sync::OnceLock::new();
+    SCANNER.get_or_init(|| SCANNER_DEF.create_scanner())
+}
+
+pub(crate) fn create_find_iter<'h>(scanner: &Scanner, input: &'h str) -> FindMatches<'h> {
+    scanner.find_iter(input, matches_char_class)
+}
@@ -0,0 +1,241 @@
+#![allow(clippy::manual_is_ascii_check)]
+
+use crate::prelude::{
+    DfaData, FindMatches, RunClassData, Scanner, ScannerDef, ScannerModeData, ScannerModeId,
+    StartSetData, TokenType,
+};
+
+const SHARED_0: &[(usize, usize)] = &[(0, 1), (1, 2)];
+const SHARED_1: &[(usize, usize)] = &[(0, 1), (1, 3), (3, 5)];
+const SHARED_2: &[(usize, usize)] = &[(0, 1), (0, 0)];
+
+const DFAS: &[DfaData] = &[
+    /* 0 */
+    (
+        "\\r\\n|\\r|\\n",
+        &[1, 2],
+        &[(0, 2), (0, 0), (2, 3)],
+        &[(0, 2), (1, 1), (1, 1)],
+    ),
+    /* 1 */
+    ("[ \\t]+", &[1], SHARED_0, &[(2, 1), (2, 1)]),
+    /* 2 */
+    ("[;#][^\\r\\n]*", &[1], SHARED_0, &[(3, 1), (4, 1)]),
+    /* 3 */
+    (
+        "\\[[^\\]\\r\\n]*\\]",
+        &[2],
+        &[(0, 1), (1, 3), (0, 0)],
+        &[(5, 1), (6, 1), (7, 2)],
+    ),
+    /* 4 */
+    (
+        "[^\\s=;#\\[](?:[^=\\r\\n]*[^\\s=])?",
+        &[1],
+        SHARED_1,
+        &[(8, 1), (9, 1), (10, 2), (9, 1), (10, 2)],
+    ),
+    /* 5 */
+    ("=", &[1], SHARED_2, &[(11, 1)]),
+    /* 6 */
+    (
+        "[^\\s;#](?:[^\\r\\n;#]*[^\\s;#])?",
+        &[1],
+        SHARED_1,
+        &[(12, 1), (12, 1), (10, 2), (12, 1), (10, 2)],
+    ),
+    /* 7 */
+    (".", &[1], SHARED_2, &[(4, 1)]),
+];
+
+const MODES: &[ScannerModeData] = &[
+    /* 0 */
+    (
+        "INITIAL",
+        &[
+            (0, TokenType::new(0)),
+            (1, TokenType::new(1)),
+            (2, TokenType::new(2)),
+            (3, TokenType::new(3)),
+            (4, TokenType::new(4)),
+            (5, TokenType::new(5)),
+            (7, TokenType::new(7)),
+        ],
+        &[(TokenType::new(5), ScannerModeId::new(1))],
+        None,
+    ),
+    /* 1 */
+    (
+        "VALUE",
+        &[
+            (0, TokenType::new(0)),
+            (1, TokenType::new(1)),
+            (2, TokenType::new(2)),
+            (6, TokenType::new(6)),
+            (7, TokenType::new(7)),
+        ],
+        &[(TokenType::new(0), ScannerModeId::new(0))],
+        None,
+    ),
+];
+
+const START_SETS: &[StartSetData] = &[
+    /* 0 */
+    (
+        0xffffffffffffffffffffffffffffffff,
+        &[('\u{80}', '\u{10ffff}')],
+    ),
+    /* 1 */
+    (
+        0xffffffffffffffffffffffffffffffff,
+        &[('\u{80}', '\u{10ffff}')],
+    ),
+];
+
+const RUN_CLASSES: &[RunClassData] = &[
+    (2, (0x00000000000000000000000100000200, &[])),
+    (
+        4,
+        (
+            0xffffffffffffffffffffffffffffdbff,
+            &[('\u{80}', '\u{10ffff}')],
+        ),
+    ),
+];
+
+fn matches_char_class(c: char, char_class: usize) -> bool {
+    match char_class {
+        /* \r */
+        0 => c == '\r',
+        /* \n */
+        1 => c == '\n',
+        /* [\t ] */
+        2 => {
+            matches!(c, '\t' | ' ')
+        }
+        /* [\#;] */
+        3 => {
+            matches!(c, '#' | ';')
+        }
+        /* [^\n\r] */
+        4 => !matches!(c, '\n' | '\r'),
+        /* \[ */
+        5 => c == '[',
+        /* [^\n\r\]] */
+        6 => !matches!(c, '\n' | '\r' | ']'),
+        /* \] */
+        7 => c == ']',
+        /* [^\t-\r \#;=\[\u{85}\u{a0}\u{1680}\u{2000}-\u{200a}\u{2028}-\u{2029}\u{202f}\u{205f}\u{3000}] */
+        8 => {
+            !matches!(c, '\t'..='\r' | ' ' | '#' | ';' | '=' | '[' | '\u{85}' | '\u{a0}' | '\u{1680}' | '\u{2000}'..='\u{200a}' | '\u{2028}'..='\u{2029}' | '\u{202f}' | '\u{205f}' | '\u{3000}')
+        }
+        /* [^\t-\r =\u{85}\u{a0}\u{1680}\u{2000}-\u{200a}\u{2028}-\u{2029}\u{202f}\u{205f}\u{3000}] */
+        9 => {
+            !matches!(c, '\t'..='\r' | ' ' | '=' | '\u{85}' | '\u{a0}' | '\u{1680}' | '\u{2000}'..='\u{200a}' | '\u{2028}'..='\u{2029}' | '\u{202f}' | '\u{205f}' | '\u{3000}')
+        }
+        /* [\t\u{b}-\u{c} \u{85}\u{a0}\u{1680}\u{2000}-\u{200a}\u{2028}-\u{2029}\u{202f}\u{205f}\u{3000}] */
+        10 => {
+            matches!(c, '\t' | '\u{b}'..='\u{c}' | ' ' | '\u{85}' | '\u{a0}' | '\u{1680}' | '\u{2000}'..='\u{200a}' | '\u{2028}'..='\u{2029}' | '\u{202f}' | '\u{205f}' | '\u{3000}')
+        }
+        /* = */
+        11 => c == '=',
+        /* [^\t-\r \#;\u{85}\u{a0}\u{1680}\u{2000}-\u{200a}\u{2028}-\u{2029}\u{202f}\u{205f}\u{3000}] */
+        12 => {
+            !matches!(c, '\t'..='\r' | ' ' | '#' | ';' | '\u{85}' | '\u{a0}' | '\u{1680}' | '\u{2000}'..='\u{200a}' | '\u{2028}'..='\u{2029}' | '\u{202f}' | '\u{205f}' | '\u{3000}')
+        }
+        _ => false,
+    }
+}
+
+/* INITIAL */
+fn matches_char_class_0(c: char, char_class: usize) -> bool {
+    match char_class {
+        /* \r */
+        0 => c == '\r',
+        /* \n */
+        1 => c == '\n',
+        /* [\t ] */
+        2 => {
+            matches!(c, '\t' | ' ')
+        }
+        /* [\#;] */
+        3 => {
+            matches!(c, '#' | ';')
+        }
+        /* [^\n\r] */
+        4 => !matches!(c, '\n' | '\r'),
+        /* \[ */
+        5 => c == '[',
+        /* [^\n\r\]] */
+        6 => !matches!(c, '\n' | '\r' | ']'),
+        /* \] */
+        7 => c == ']',
+        /* [^\t-\r \#;=\[\u{85}\u{a0}\u{1680}\u{2000}-\u{200a}\u{2028}-\u{2029}\u{202f}\u{205f}\u{3000}] */
+        8 => {
+            !matches!(c, '\t'..='\r' | ' ' | '#' | ';' | '=' | '[' | '\u{85}' | '\u{a0}' | '\u{1680}' | '\u{2000}'..='\u{200a}' | '\u{2028}'..='\u{2029}' | '\u{202f}' | '\u{205f}' | '\u{3000}')
+        }
+        /* [^\t-\r =\u{85}\u{a0}\u{1680}\u{2000}-\u{200a}\u{2028}-\u{2029}\u{202f}\u{205f}\u{3000}] */
+        9 => {
+            !matches!(c, '\t'..='\r' | ' ' | '=' | '\u{85}' | '\u{a0}' | '\u{1680}' | '\u{2000}'..='\u{200a}' | '\u{2028}'..='\u{2029}' | '\u{202f}' | '\u{205f}' | '\u{3000}')
+        }
+        /* [\t\u{b}-\u{c} \u{85}\u{a0}\u{1680}\u{2000}-\u{200a}\u{2028}-\u{2029}\u{202f}\u{205f}\u{3000}] */
+        10 => {
+            matches!(c, '\t' | '\u{b}'..='\u{c}' | ' ' | '\u{85}' | '\u{a0}' | '\u{1680}' | '\u{2000}'..='\u{200a}' | '\u{2028}'..='\u{2029}' | '\u{202f}' | '\u{205f}' | '\u{3000}')
+        }
+        /* = */
+        11 => c == '=',
+        _ => false,
+    }
+}
+
+/* VALUE */
+fn matches_char_class_1(c: char, char_class: usize) -> bool {
+    match char_class {
+        /* \r */
+        0 => c == '\r',
+        /* \n */
+        1 => c == '\n',
+        /* [\t ] */
+        2 => {
+            matches!(c, '\t' | ' ')
+        }
+        /* [\#;] */
+        3 => {
+            matches!(c, '#' | ';')
+        }
+        /* [^\n\r] */
+        4 => !matches!(c, '\n' | '\r'),
+        /* [\t\u{b}-\u{c} \u{85}\u{a0}\u{1680}\u{2000}-\u{200a}\u{2028}-\u{2029}\u{202f}\u{205f}\u{3000}] */
+        10 => {
+            matches!(c, '\t' | '\u{b}'..='\u{c}' | ' ' | '\u{85}' | '\u{a0}' | '\u{1680}' | '\u{2000}'..='\u{200a}' | '\u{2028}'..='\u{2029}' | '\u{202f}' | '\u{205f}' | '\u{3000}')
+        }
+        /* [^\t-\r \#;\u{85}\u{a0}\u{1680}\u{2000}-\u{200a}\u{2028}-\u{2029}\u{202f}\u{205f}\u{3000}] */
+        12 => {
+            !matches!(c, '\t'..='\r' | ' ' | '#' | ';' | '\u{85}' | '\u{a0}' | '\u{1680}' | '\u{2000}'..='\u{200a}' | '\u{2028}'..='\u{2029}' | '\u{202f}' | '\u{205f}' | '\u{3000}')
+        }
+        _ => false,
+    }
+}
+
+const MODE_MATCH_FUNCTIONS: &[fn(char, usize) -> bool] =
+    &[matches_char_class_0, matches_char_class_1];
+
+pub(crate) static SCANNER_DEF: ScannerDef = ScannerDef::new(DFAS, MODES)
+    .generated_with("0.1.0", 2)
+    .with_start_sets(START_SETS)
+    .with_run_classes(RUN_CLASSES)
+    .with_mode_match_functions(MODE_MATCH_FUNCTIONS);
+
+pub(crate) fn create_scanner() -> Scanner {
+    SCANNER_DEF.create_scanner()
+}
+
+/// Returns the scanner that is shared by all callers. It is created on first use.
+pub(crate) fn scanner() -> &'static Scanner {
+    static SCANNER: std::sync::OnceLock<Scanner> = std::sync::OnceLock::new();
+    SCANNER.get_or_init(|| SCANNER_DEF.create_scanner())
+}
+
+pub(crate) fn create_find_iter<'h>(scanner: &Scanner, input: &'h str) -> FindMatches<'h> {
+    scanner.find_iter(input, matches_char_class)
+}
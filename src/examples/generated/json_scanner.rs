@@ -0,0 +1,234 @@
+#![allow(clippy::manual_is_ascii_check)]
+
+use crate::prelude::{
+    DfaData, FindMatches, RunClassData, Scanner, ScannerDef, ScannerModeData, StartSetData,
+};
+
+const SHARED_0: &[(usize, usize)] = &[(0, 1), (0, 0)];
+const SHARED_1: &[(usize, usize)] = &[(0, 1), (1, 2), (2, 3), (3, 4), (0, 0)];
+
+const DFAS: &[DfaData] = &[
+    /* 0 */
+    (
+        "\\r\\n|\\r|\\n",
+        &[1, 2],
+        &[(0, 2), (0, 0), (2, 3)],
+        &[(0, 2), (1, 1), (1, 1)],
+    ),
+    /* 1 */
+    ("[ \\t]+", &[1], &[(0, 1), (1, 2)], &[(2, 1), (2, 1)]),
+    /* 2 */
+    ("\\{", &[1], SHARED_0, &[(3, 1)]),
+    /* 3 */
+    ("\\}", &[1], SHARED_0, &[(4, 1)]),
+    /* 4 */
+    ("\\[", &[1], SHARED_0, &[(5, 1)]),
+    /* 5 */
+    ("\\]", &[1], SHARED_0, &[(6, 1)]),
+    /* 6 */
+    (":", &[1], SHARED_0, &[(7, 1)]),
+    /* 7 */
+    (",", &[1], SHARED_0, &[(8, 1)]),
+    /* 8 */
+    (
+        "\"(?:[^\"\\\\\\u{0}-\\u{1f}]|\\\\[\"\\\\/bfnrt]|\\\\u[0-9a-fA-F]{4})*\"",
+        &[2],
+        &[
+            (0, 1),
+            (1, 4),
+            (0, 0),
+            (4, 7),
+            (7, 9),
+            (9, 10),
+            (10, 11),
+            (11, 12),
+            (12, 13),
+        ],
+        &[
+            (9, 1),
+            (9, 2),
+            (10, 3),
+            (11, 4),
+            (9, 2),
+            (10, 3),
+            (11, 4),
+            (12, 3),
+            (13, 8),
+            (14, 3),
+            (14, 5),
+            (14, 6),
+            (14, 7),
+        ],
+    ),
+    /* 9 */
+    (
+        "-?(?:0|[1-9][0-9]*)(?:\\.[0-9]+)?(?:[eE][+-]?[0-9]+)?",
+        &[2, 3, 7, 8],
+        &[
+            (0, 3),
+            (3, 5),
+            (5, 8),
+            (8, 10),
+            (10, 12),
+            (12, 13),
+            (13, 14),
+            (14, 15),
+            (15, 17),
+        ],
+        &[
+            (15, 1),
+            (16, 3),
+            (17, 2),
+            (16, 3),
+            (17, 2),
+            (18, 2),
+            (19, 6),
+            (20, 4),
+            (19, 6),
+            (20, 4),
+            (21, 5),
+            (18, 7),
+            (18, 7),
+            (18, 8),
+            (18, 7),
+            (18, 8),
+            (20, 4),
+        ],
+    ),
+    /* 10 */
+    (
+        "true",
+        &[4],
+        SHARED_1,
+        &[(22, 1), (23, 2), (13, 3), (24, 4)],
+    ),
+    /* 11 */
+    (
+        "false",
+        &[5],
+        &[(0, 1), (1, 2), (2, 3), (3, 4), (4, 5), (0, 0)],
+        &[(25, 1), (26, 2), (27, 3), (28, 4), (24, 5)],
+    ),
+    /* 12 */
+    (
+        "null",
+        &[4],
+        SHARED_1,
+        &[(29, 1), (13, 2), (27, 3), (27, 4)],
+    ),
+    /* 13 */
+    (".", &[1], SHARED_0, &[(30, 1)]),
+];
+
+const MODES: &[ScannerModeData] = &[];
+
+const START_SETS: &[StartSetData] = &[/* 0 */ (
+    0xffffffffffffffffffffffffffffffff,
+    &[('\u{80}', '\u{10ffff}')],
+)];
+
+const RUN_CLASSES: &[RunClassData] = &[
+    (2, (0x00000000000000000000000100000200, &[])),
+    (18, (0x000000000000000003ff000000000000, &[])),
+];
+
+fn matches_char_class(c: char, char_class: usize) -> bool {
+    match char_class {
+        /* \r */
+        0 => c == '\r',
+        /* \n */
+        1 => c == '\n',
+        /* [\t ] */
+        2 => {
+            matches!(c, '\t' | ' ')
+        }
+        /* \{ */
+        3 => c == '{',
+        /* \} */
+        4 => c == '}',
+        /* \[ */
+        5 => c == '[',
+        /* \] */
+        6 => c == ']',
+        /* : */
+        7 => c == ':',
+        /* , */
+        8 => c == ',',
+        /* " */
+        9 => c == '\"',
+        /* [ -!\#-\[\]-􏿿] */
+        10 => {
+            matches!(c, ' '..='!' | '#'..='[' | ']'..='\u{10ffff}')
+        }
+        /* \\ */
+        11 => c == '\\',
+        /* ["/\\bfnrt] */
+        12 => {
+            const MASK: u128 = 0x00144044100000000000800400000000;
+            c.is_ascii() && (MASK >> (c as u32)) & 1 == 1
+        }
+        /* u */
+        13 => c == 'u',
+        /* [0-9A-Fa-f] */
+        14 => {
+            const MASK: u128 = 0x0000007e0000007e03ff000000000000;
+            c.is_ascii() && (MASK >> (c as u32)) & 1 == 1
+        }
+        /* \- */
+        15 => c == '-',
+        /* 0 */
+        16 => c == '0',
+        /* [1-9] */
+        17 => ('1'..='9').contains(&c),
+        /* [0-9] */
+        18 => ('0'..='9').contains(&c),
+        /* \. */
+        19 => c == '.',
+        /* [Ee] */
+        20 => {
+            matches!(c, 'E' | 'e')
+        }
+        /* [\+\-] */
+        21 => {
+            matches!(c, '+' | '-')
+        }
+        /* t */
+        22 => c == 't',
+        /* r */
+        23 => c == 'r',
+        /* e */
+        24 => c == 'e',
+        /* f */
+        25 => c == 'f',
+        /* a */
+        26 => c == 'a',
+        /* l */
+        27 => c == 'l',
+        /* s */
+        28 => c == 's',
+        /* n */
+        29 => c == 'n',
+        /* [^\n\r] */
+        30 => !matches!(c, '\n' | '\r'),
+        _ => false,
+    }
+}
+
+pub(crate) static SCANNER_DEF: ScannerDef = ScannerDef::new(DFAS, MODES)
+    .generated_with("0.1.0", 2)
+    .with_start_sets(START_SETS)
+    .with_run_classes(RUN_CLASSES);
+
+pub(crate) fn create_scanner() -> Scanner {
+    SCANNER_DEF.create_scanner()
+}
+
+/// Returns the scanner that is shared by all callers. It is created on first use.
+pub(crate) fn scanner() -> &'static Scanner {
+    static SCANNER: std::sync::OnceLock<Scanner> = std::sync::OnceLock::new();
+    SCANNER.get_or_init(|| SCANNER_DEF.create_scanner())
+}
+
+pub(crate) fn create_find_iter<'h>(scanner: &Scanner, input: &'h str) -> FindMatches<'h> {
+    scanner.find_iter(input, matches_char_class)
+}
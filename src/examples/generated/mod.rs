@@ -0,0 +1,8 @@
+#[cfg(feature = "examples-json")]
+pub(crate) mod json_scanner;
+
+#[cfg(feature = "examples-csv")]
+pub(crate) mod csv_scanner;
+
+#[cfg(feature = "examples-ini")]
+pub(crate) mod ini_scanner;
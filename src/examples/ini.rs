@@ -0,0 +1,149 @@
+//! A scanner for INI files with sections, key-value pairs and comments.
+//!
+//! Keys and values don't include the whitespace around them. After the [EQUALS] of a key-value
+//! pair, the scanner switches into the scanner mode `VALUE` until the end of the line, so a value
+//! may contain `=` and `[`.
+//!
+//! ```rust
+//! use scangen::examples::ini;
+//!
+//! let input = "[server]\nhost = example.org ; the host\nurl=a=b\n";
+//! let pairs = ini::find_iter(input)
+//!     .tokens()
+//!     .filter(|token| token.token_type == ini::KEY || token.token_type == ini::VALUE)
+//!     .map(|token| token.text)
+//!     .collect::<Vec<_>>();
+//! assert_eq!(pairs, ["host", "example.org", "url", "a=b"]);
+//! ```
+
+use crate::{FindMatches, Scanner, ScannerModeData, ScannerModeId, TokenType};
+
+use super::generated::ini_scanner;
+
+/// The terminals of the scanner. The index of a terminal is its token type.
+pub const TERMINALS: &[&str] = &[
+    /* 0 */ r"\r\n|\r|\n", // Newline
+    /* 1 */ r"[ \t]+", // Whitespace
+    /* 2 */ r"[;#][^\r\n]*", // Comment
+    /* 3 */ r"\[[^\]\r\n]*\]", // Section
+    /* 4 */ r"[^\s=;#\[](?:[^=\r\n]*[^\s=])?", // Key
+    /* 5 */ r"=", // Equals
+    /* 6 */ r"[^\s;#](?:[^\r\n;#]*[^\s;#])?", // Value
+    /* 7 */ r".", // Error
+];
+
+/// The scanner modes of the scanner.
+pub const MODES: &[ScannerModeData] = &[
+    (
+        "INITIAL",
+        &[
+            (0, TokenType::new(0)), // Newline
+            (1, TokenType::new(1)), // Whitespace
+            (2, TokenType::new(2)), // Comment
+            (3, TokenType::new(3)), // Section
+            (4, TokenType::new(4)), // Key
+            (5, TokenType::new(5)), // Equals
+            (7, TokenType::new(7)), // Error
+        ],
+        &[
+            (TokenType::new(5), ScannerModeId::new(1)), // Token "Equals" -> Mode "VALUE"
+        ],
+        None,
+    ),
+    (
+        "VALUE",
+        &[
+            (0, TokenType::new(0)), // Newline
+            (1, TokenType::new(1)), // Whitespace
+            (2, TokenType::new(2)), // Comment
+            (6, TokenType::new(6)), // Value
+            (7, TokenType::new(7)), // Error
+        ],
+        &[
+            (TokenType::new(0), ScannerModeId::new(0)), // Token "Newline" -> Mode "INITIAL"
+        ],
+        None,
+    ),
+];
+
+/// A line break.
+pub const NEWLINE: TokenType = TokenType::new(0);
+/// Spaces and tabs.
+pub const WHITESPACE: TokenType = TokenType::new(1);
+/// A comment that starts with `;` or `#` and ends at the end of the line.
+pub const COMMENT: TokenType = TokenType::new(2);
+/// A section header including its brackets, e.g. `[server]`.
+pub const SECTION: TokenType = TokenType::new(3);
+/// The key of a key-value pair.
+pub const KEY: TokenType = TokenType::new(4);
+/// The `=` between a key and its value.
+pub const EQUALS: TokenType = TokenType::new(5);
+/// The value of a key-value pair.
+pub const VALUE: TokenType = TokenType::new(6);
+/// Any other character, e.g. the `[` of an unterminated section header.
+pub const ERROR: TokenType = TokenType::new(7);
+
+/// Creates a new scanner for INI files.
+pub fn create_scanner() -> Scanner {
+    ini_scanner::create_scanner()
+}
+
+/// Returns the scanner for INI files that is shared by all callers. It is created on first use.
+pub fn scanner() -> &'static Scanner {
+    ini_scanner::scanner()
+}
+
+/// Returns an iterator over the tokens of the given INI file.
+pub fn find_iter(input: &str) -> FindMatches<'_> {
+    ini_scanner::create_find_iter(scanner(), input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "generate")]
+    fn generate_code_for_ini_scanner() {
+        let file_name = "src/examples/generated/ini_scanner.rs";
+        {
+            let mut out_file = std::fs::File::create(file_name).expect("Failed to create file");
+            crate::generate_code(TERMINALS, MODES, Some("crate"), &mut out_file)
+                .expect("Failed to generate code");
+        }
+        crate::try_format(file_name).expect("Failed to format the generated code");
+    }
+
+    #[test]
+    fn test_ini() {
+        let input = "# Settings\n[user data]\nfull name = Ada L. # inline\n\tpath=[a=b]\n[open";
+        let tokens = find_iter(input)
+            .tokens()
+            .map(|token| (token.token_type, token.text))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            tokens,
+            vec![
+                (COMMENT, "# Settings"),
+                (NEWLINE, "\n"),
+                (SECTION, "[user data]"),
+                (NEWLINE, "\n"),
+                (KEY, "full name"),
+                (WHITESPACE, " "),
+                (EQUALS, "="),
+                (WHITESPACE, " "),
+                (VALUE, "Ada L."),
+                (WHITESPACE, " "),
+                (COMMENT, "# inline"),
+                (NEWLINE, "\n"),
+                (WHITESPACE, "\t"),
+                (KEY, "path"),
+                (EQUALS, "="),
+                (VALUE, "[a=b]"),
+                (NEWLINE, "\n"),
+                (ERROR, "["),
+                (KEY, "open"),
+            ]
+        );
+    }
+}
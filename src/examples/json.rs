@@ -0,0 +1,141 @@
+//! A scanner for JSON as defined in RFC 8259.
+//!
+//! ```rust
+//! use scangen::examples::json;
+//!
+//! let token_types = json::find_iter(r#"{"id": [1, true]}"#)
+//!     .map(|m| m.token_type())
+//!     .filter(|token_type| *token_type != json::WHITESPACE)
+//!     .collect::<Vec<_>>();
+//! assert_eq!(
+//!     token_types,
+//!     [
+//!         json::LEFT_BRACE,
+//!         json::STRING,
+//!         json::COLON,
+//!         json::LEFT_BRACKET,
+//!         json::NUMBER,
+//!         json::COMMA,
+//!         json::TRUE,
+//!         json::RIGHT_BRACKET,
+//!         json::RIGHT_BRACE,
+//!     ]
+//! );
+//! ```
+
+use crate::{FindMatches, Scanner, TokenType};
+
+use super::generated::json_scanner;
+
+/// The terminals of the scanner. The index of a terminal is its token type.
+pub const TERMINALS: &[&str] = &[
+    /* 0 */ r"\r\n|\r|\n", // Newline
+    /* 1 */ r"[ \t]+", // Whitespace
+    /* 2 */ r"\{", // Left brace
+    /* 3 */ r"\}", // Right brace
+    /* 4 */ r"\[", // Left bracket
+    /* 5 */ r"\]", // Right bracket
+    /* 6 */ r":", // Colon
+    /* 7 */ r",", // Comma
+    /* 8 */ r#""(?:[^"\\\u{0}-\u{1f}]|\\["\\/bfnrt]|\\u[0-9a-fA-F]{4})*""#, // String
+    /* 9 */ r"-?(?:0|[1-9][0-9]*)(?:\.[0-9]+)?(?:[eE][+-]?[0-9]+)?", // Number
+    /* 10 */ r"true", // True
+    /* 11 */ r"false", // False
+    /* 12 */ r"null", // Null
+    /* 13 */ r".", // Error
+];
+
+/// A line break.
+pub const NEWLINE: TokenType = TokenType::new(0);
+/// Spaces and tabs.
+pub const WHITESPACE: TokenType = TokenType::new(1);
+/// `{`
+pub const LEFT_BRACE: TokenType = TokenType::new(2);
+/// `}`
+pub const RIGHT_BRACE: TokenType = TokenType::new(3);
+/// `[`
+pub const LEFT_BRACKET: TokenType = TokenType::new(4);
+/// `]`
+pub const RIGHT_BRACKET: TokenType = TokenType::new(5);
+/// `:`
+pub const COLON: TokenType = TokenType::new(6);
+/// `,`
+pub const COMMA: TokenType = TokenType::new(7);
+/// A string including its quotes. Escape sequences are not decoded.
+pub const STRING: TokenType = TokenType::new(8);
+/// A number.
+pub const NUMBER: TokenType = TokenType::new(9);
+/// `true`
+pub const TRUE: TokenType = TokenType::new(10);
+/// `false`
+pub const FALSE: TokenType = TokenType::new(11);
+/// `null`
+pub const NULL: TokenType = TokenType::new(12);
+/// Any other character, e.g. a control character or an unterminated string.
+pub const ERROR: TokenType = TokenType::new(13);
+
+/// Creates a new scanner for JSON.
+pub fn create_scanner() -> Scanner {
+    json_scanner::create_scanner()
+}
+
+/// Returns the scanner for JSON that is shared by all callers. It is created on first use.
+pub fn scanner() -> &'static Scanner {
+    json_scanner::scanner()
+}
+
+/// Returns an iterator over the tokens of the given JSON text.
+pub fn find_iter(input: &str) -> FindMatches<'_> {
+    json_scanner::create_find_iter(scanner(), input)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "generate")]
+    fn generate_code_for_json_scanner() {
+        let file_name = "src/examples/generated/json_scanner.rs";
+        {
+            let mut out_file = std::fs::File::create(file_name).expect("Failed to create file");
+            crate::generate_code(TERMINALS, &[], Some("crate"), &mut out_file)
+                .expect("Failed to generate code");
+        }
+        crate::try_format(file_name).expect("Failed to format the generated code");
+    }
+
+    #[test]
+    fn test_json() {
+        let input = "{\"a\\\"\\u00e4\": [-1.5e3, false, null],\n\"\u{1}\"}";
+        let tokens = find_iter(input)
+            .tokens()
+            .map(|token| (token.token_type, token.text))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            tokens,
+            vec![
+                (LEFT_BRACE, "{"),
+                (STRING, "\"a\\\"\\u00e4\""),
+                (COLON, ":"),
+                (WHITESPACE, " "),
+                (LEFT_BRACKET, "["),
+                (NUMBER, "-1.5e3"),
+                (COMMA, ","),
+                (WHITESPACE, " "),
+                (FALSE, "false"),
+                (COMMA, ","),
+                (WHITESPACE, " "),
+                (NULL, "null"),
+                (RIGHT_BRACKET, "]"),
+                (COMMA, ","),
+                (NEWLINE, "\n"),
+                // A control character is not allowed within a string
+                (ERROR, "\""),
+                (ERROR, "\u{1}"),
+                (ERROR, "\""),
+                (RIGHT_BRACE, "}"),
+            ]
+        );
+    }
+}
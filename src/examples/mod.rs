@@ -0,0 +1,21 @@
+//! Ready-made scanners for common formats.
+//!
+//! Each scanner is enabled by its own feature:
+//! - `examples-json`: [json] for JSON as defined in RFC 8259.
+//! - `examples-csv`: [csv] for comma separated values with quoted fields as defined in RFC 4180.
+//! - `examples-ini`: [ini] for INI files with sections, key-value pairs and comments.
+//!
+//! The scanners are generated ahead of time from the public terminals and scanner modes of their
+//! modules. They can be used as they are or serve as a starting point for own scanners: copy the
+//! terminals, change them and generate the code with [crate::generate_code].
+
+#[cfg(feature = "examples-json")]
+pub mod json;
+
+#[cfg(feature = "examples-csv")]
+pub mod csv;
+
+#[cfg(feature = "examples-ini")]
+pub mod ini;
+
+mod generated;
@@ -10,6 +10,12 @@
 //!   from a regex syntax.
 //! - `runtime`: This feature enables the runtime module which can be used to scan text for matches.
 //!
+//! The feature `peek`, which is enabled by default, adds the peek operations of `FindMatches`,
+//! like `peek_n`, to the runtime. Minimal builds can leave it out.
+//!
+//! The optional features `examples-json`, `examples-csv` and `examples-ini` add ready-made
+//! scanners for these formats to the module `examples`.
+//!
 //! To use only the runtime feature, use the following in your `Cargo.toml`:
 //! ```toml
 //! [dependencies]
@@ -54,19 +60,56 @@
 
 /// Module with common types and functions
 mod common;
-pub use common::{DfaData, Match, ScannerModeData, Span};
+pub use common::{
+    CaseInsensitiveKeywordData, ContextualKeywordData, CountedStateData, CountingModeData, DfaData,
+    Match, OwnedDfaData, RunClassData, ScannerModeData, ScannerModeId, Span, StartSetData,
+    TokenType, TABLE_FORMAT_VERSION,
+};
 
 /// Compiletime module
 #[cfg(feature = "generate")]
 mod compiletime;
 #[cfg(feature = "generate")]
-pub use compiletime::{generate_code, try_format, Result, ScanGenError, ScanGenErrorKind};
+pub use compiletime::{
+    generate_code, generate_code_from_hirs, generate_code_to_string, generate_code_with_options,
+    generate_code_with_report, generate_munch_tests, generate_regex_code, parse_regex_syntax,
+    scaffold_lexer, terminals_from_hirs, try_format, try_format_code, validate_pattern,
+    CompilationReport, DfaTable, DialectLayer, Dialects, DuplicatePatterns, EntryPoint,
+    GenerateOptions, LiteralShadowing, MissingCatchAll, PatternAlias, PatternExplosion,
+    PatternInfo, Result, ScanGenError, ScanGenErrorKind, TableSize, TerminalConflict,
+    MANIFEST_FORMAT_VERSION,
+};
 
 /// Runtime module
 #[cfg(feature = "runtime")]
 mod runtime;
 #[cfg(feature = "runtime")]
+#[allow(deprecated)]
 pub use runtime::{
-    Dfa, FindMatches, PeekResult, Scanner, ScannerBuilder, ScannerBuilderWithScannerModes,
-    ScannerBuilderWithsDfas, ScannerBuilderWithsDfasAndScannerModes, ScannerMode,
+    decode_escape, diff_scanners, verify_lossless, Ambiguity, AttachTrivia, BehaviorDifference,
+    BuildError, CharSource, ColumnConfig, Completion, ConversionError, CookRule, CookRules,
+    CookedTokens, DecodedCharIndices, DecodedString, Dfa, Encoding, FindDecodedMatches,
+    FindMatches, FindOutcome, FindSegmentedMatches, FoldStrings, InternTokens, InternedMatch,
+    Interner, LineGroups, LineIndex, LosslessViolation, MatchKind, ModeInfo, OwnedToken,
+    PatternChange, Position, Positions, ReloadableScanner, Scanner, ScannerBuilder,
+    ScannerBuilderWithScannerModes, ScannerBuilderWithsDfas,
+    ScannerBuilderWithsDfasAndScannerModes, ScannerComposer, ScannerDef, ScannerDiff, ScannerMode,
+    ScannerRegistry, SegmentedCharIndices, SourceFile, SourceId, SourceMap, SourceMatch,
+    StateCountChange, StringInterner, StringItem, StringTokenTypes, Symbol, Token, TokenIter,
+    TokenRenumbering, TokenValueConverters, TokenWithTrivia, TriviaAttachment, TypedToken,
+    ValueConverter, JSON_FORMAT_VERSION,
 };
+#[cfg(feature = "peek")]
+pub use runtime::{PeekIntoResult, PeekResult};
+
+/// Ready-made scanners for common formats
+#[cfg(any(
+    feature = "examples-json",
+    feature = "examples-csv",
+    feature = "examples-ini"
+))]
+pub mod examples;
+
+/// The stable prelude for the generated code
+#[cfg(feature = "runtime")]
+pub mod prelude;
@@ -0,0 +1,46 @@
+//! The command line interface of `scangen`.
+//!
+//! ```text
+//! scangen new-lexer <name> [<directory>]
+//! ```
+//!
+//! `new-lexer` scaffolds a crate with a lexer that is generated ahead of time from a file of
+//! patterns, see [scangen::scaffold_lexer]. The directory defaults to the name of the crate.
+
+use std::process::ExitCode;
+
+const USAGE: &str = "Usage: scangen new-lexer <name> [<directory>]";
+
+fn main() -> ExitCode {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    match args
+        .iter()
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .as_slice()
+    {
+        ["new-lexer", name] => new_lexer(name, name),
+        ["new-lexer", name, directory] => new_lexer(name, directory),
+        ["--help" | "-h" | "help"] => {
+            println!("{}", USAGE);
+            ExitCode::SUCCESS
+        }
+        _ => {
+            eprintln!("{}", USAGE);
+            ExitCode::from(2)
+        }
+    }
+}
+
+fn new_lexer(name: &str, directory: &str) -> ExitCode {
+    match scangen::scaffold_lexer(name, directory) {
+        Ok(()) => {
+            println!("Created lexer crate `{}` in {}", name, directory);
+            ExitCode::SUCCESS
+        }
+        Err(error) => {
+            eprintln!("Error: {}", error);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,14 @@
+//! The prelude with all items the generated code uses.
+//!
+//! Generated files import from this module only. Its items are kept stable across versions of
+//! `scangen`, so files generated with an older version still compile with a newer runtime.
+//!
+//! ```rust
+//! use scangen::prelude::{DfaData, Scanner, ScannerDef, ScannerModeData};
+//! ```
+
+pub use crate::{
+    CaseInsensitiveKeywordData, ContextualKeywordData, CountedStateData, DfaData, FindMatches,
+    Match, RunClassData, Scanner, ScannerDef, ScannerModeData, ScannerModeId, StartSetData,
+    TokenIter, TokenType,
+};
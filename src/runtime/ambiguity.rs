@@ -0,0 +1,33 @@
+use crate::{Match, TokenType};
+
+/// An ambiguity recorded by the scanner.
+///
+/// An ambiguity occurs if more than one pattern matched the same longest span. The match with the
+/// highest priority, i.e. the pattern that comes first in the current scanner mode, wins. The
+/// patterns that lost are recorded as shadowed token types.
+///
+/// Ambiguities are only recorded if the recording has been enabled with
+/// [crate::Scanner::set_record_ambiguities].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ambiguity {
+    /// The match that won the tie.
+    pub matched: Match,
+    /// The token types of the patterns that matched the same span but lost the tie.
+    pub shadowed: Vec<TokenType>,
+}
+
+impl std::fmt::Display for Ambiguity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Token type {} at {} shadows token type(s) {}",
+            self.matched.token_type(),
+            self.matched.span(),
+            self.shadowed
+                .iter()
+                .map(|t| t.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    }
+}
@@ -0,0 +1,102 @@
+use crate::ScannerModeId;
+
+/// An error that occurs if a scanner is built from inconsistent data.
+///
+/// It is returned by [crate::ScannerBuilder::build].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BuildError {
+    /// No DFA data has been added to the scanner builder.
+    MissingDfas,
+    /// A scanner mode references a DFA that doesn't exist.
+    UnknownDfa {
+        /// The name of the scanner mode.
+        mode: String,
+        /// The index of the DFA referenced by the scanner mode.
+        dfa_index: usize,
+        /// The number of DFAs that have been added.
+        dfa_count: usize,
+    },
+    /// A scanner mode has a transition to a scanner mode that doesn't exist.
+    UnknownScannerMode {
+        /// The name of the scanner mode with the transition.
+        mode: String,
+        /// The target of the transition.
+        target: ScannerModeId,
+        /// The number of scanner modes that have been added.
+        mode_count: usize,
+    },
+    /// The JSON definition of a scanner is malformed or doesn't follow the schema.
+    InvalidJson {
+        /// The description of the problem.
+        message: String,
+    },
+    /// The evaluation order of a scanner mode is no permutation of the indices of its DFAs.
+    InvalidEvaluationOrder {
+        /// The name of the scanner mode.
+        mode: String,
+    },
+    /// A counted state references a DFA or a state that doesn't exist, or its minimum number of
+    /// repetitions exceeds the maximum.
+    InvalidCountedState {
+        /// The index of the DFA.
+        dfa_index: usize,
+        /// The counted state.
+        state: usize,
+    },
+    /// A disabled DFA doesn't exist.
+    UnknownDisabledDfa {
+        /// The index of the disabled DFA.
+        dfa_index: usize,
+        /// The number of DFAs that have been added.
+        dfa_count: usize,
+    },
+}
+
+impl std::fmt::Display for BuildError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BuildError::MissingDfas => write!(f, "No DFA data has been added to the scanner"),
+            BuildError::UnknownDfa {
+                mode,
+                dfa_index,
+                dfa_count,
+            } => write!(
+                f,
+                "Scanner mode {} references the unknown DFA {}, only {} DFAs are given",
+                mode, dfa_index, dfa_count
+            ),
+            BuildError::UnknownScannerMode {
+                mode,
+                target,
+                mode_count,
+            } => write!(
+                f,
+                "Scanner mode {} switches to the unknown scanner mode {}, only {} modes are given",
+                mode, target, mode_count
+            ),
+            BuildError::InvalidJson { message } => {
+                write!(f, "Invalid JSON definition of the scanner: {}", message)
+            }
+            BuildError::InvalidEvaluationOrder { mode } => write!(
+                f,
+                "The evaluation order of scanner mode {} is no permutation of its DFAs",
+                mode
+            ),
+            BuildError::InvalidCountedState { dfa_index, state } => write!(
+                f,
+                "The counted state {} of DFA {} doesn't exist or has inconsistent bounds",
+                state, dfa_index
+            ),
+            BuildError::UnknownDisabledDfa {
+                dfa_index,
+                dfa_count,
+            } => write!(
+                f,
+                "The disabled DFA {} doesn't exist, only {} DFAs are given",
+                dfa_index, dfa_count
+            ),
+        }
+    }
+}
+
+impl std::error::Error for BuildError {}
@@ -0,0 +1,16 @@
+use crate::TokenType;
+
+/// A pattern of the current scanner mode that is still alive after the scanner consumed a given
+/// input from its start, i.e. a possible completion of the input.
+///
+/// Completions are returned by [crate::Scanner::longest_prefix], e.g. for the auto-completion of
+/// keywords and operators in an editor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Completion {
+    /// The token type of the pattern.
+    pub token_type: TokenType,
+    /// True if the pattern already matches the whole input.
+    pub is_match: bool,
+    /// True if the pattern can also match longer inputs that start with the input.
+    pub can_continue: bool,
+}
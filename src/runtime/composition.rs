@@ -0,0 +1,214 @@
+use crate::{ScannerModeId, TokenType};
+
+use super::{BuildError, Scanner, ScannerMode};
+
+/// Composes several scanners into one scanner, e.g. a scanner of a template language with a
+/// scanner of the language embedded into it.
+///
+/// The scanner modes of an embedded scanner are appended to the modes of the composed scanner.
+/// Their names are prefixed with a namespace, e.g. `js::INITIAL`, and their token types are
+/// shifted by an offset, so the token types of the scanners don't collide. The DFAs are shared
+/// with the scanners they come from.
+///
+/// Each scanner uses its own match function. Therefore, each scanner mode of the composed scanner
+/// is bound to the match function of the scanner it comes from, and the match function given to
+/// the search of the composed scanner is not used.
+///
+/// The scanners are connected by transitions that switch from a mode of one scanner to a mode of
+/// another one, see [ScannerComposer::add_transition].
+#[derive(Debug, Clone)]
+pub struct ScannerComposer {
+    scanner: Scanner,
+}
+
+impl ScannerComposer {
+    /// Starts the composition with the base scanner and its match function.
+    /// The modes of the base scanner keep their names, token types and mode ids.
+    pub fn new(base: Scanner, matches_char_class: fn(char, usize) -> bool) -> Self {
+        let mut scanner = base;
+        for mode in &mut scanner.scanner_modes {
+            mode.matches_char_class.get_or_insert(matches_char_class);
+        }
+        Self { scanner }
+    }
+
+    /// Embeds the scanner with its match function into the composed scanner.
+    /// The names of its modes are prefixed with `namespace::` and `token_type_offset` is added to
+    /// its token types.
+    ///
+    /// Returns the id of the first mode of the embedded scanner in the composed scanner. The ids
+    /// of its other modes follow in their original order.
+    pub fn embed(
+        &mut self,
+        namespace: &str,
+        scanner: &Scanner,
+        matches_char_class: fn(char, usize) -> bool,
+        token_type_offset: usize,
+    ) -> ScannerModeId {
+        let mode_offset = self.scanner.scanner_modes.len();
+        let shift =
+            |token_type: TokenType| TokenType::new(token_type.as_usize() + token_type_offset);
+        self.scanner.dfas.extend(scanner.dfas.iter().cloned());
+        for mode in &scanner.scanner_modes {
+            self.scanner.scanner_modes.push(ScannerMode {
+                name: format!("{}::{}", namespace, mode.name),
+                dfas: mode
+                    .dfas
+                    .iter()
+                    .map(|dfa| dfa.with_token_type(shift(dfa.token_type())))
+                    .collect(),
+                transitions: mode
+                    .transitions
+                    .iter()
+                    .map(|(token_type, target)| {
+                        (
+                            shift(*token_type),
+                            ScannerModeId::new(target.as_usize() + mode_offset),
+                        )
+                    })
+                    .collect(),
+                start_set: mode.start_set.clone(),
+                run_sets: mode.run_sets.clone(),
+                counting: mode.counting.map(|(open, close, exit)| {
+                    (
+                        shift(open),
+                        shift(close),
+                        ScannerModeId::new(exit.as_usize() + mode_offset),
+                    )
+                }),
+                evaluation_order: mode.evaluation_order.clone(),
+                error_token_type: mode.error_token_type.map(shift),
+                matches_char_class: Some(mode.matches_char_class.unwrap_or(matches_char_class)),
+                generation: 0,
+                active_dfas: Vec::new(),
+                matched_dfas: Vec::new(),
+            });
+        }
+        ScannerModeId::new(mode_offset)
+    }
+
+    /// Adds a transition from one mode of the composed scanner to another one that is triggered
+    /// by the given token type. An existing transition of the mode on this token type is
+    /// replaced.
+    ///
+    /// # Errors
+    /// Returns [BuildError::UnknownScannerMode] if one of the modes doesn't exist.
+    pub fn add_transition(
+        &mut self,
+        from: ScannerModeId,
+        token_type: TokenType,
+        to: ScannerModeId,
+    ) -> Result<&mut Self, BuildError> {
+        let mode_count = self.scanner.scanner_modes.len();
+        if to.as_usize() >= mode_count {
+            return Err(BuildError::UnknownScannerMode {
+                mode: self.scanner.display_mode(from).to_string(),
+                target: to,
+                mode_count,
+            });
+        }
+        let Some(mode) = self.scanner.scanner_modes.get_mut(from.as_usize()) else {
+            return Err(BuildError::UnknownScannerMode {
+                mode: from.to_string(),
+                target: from,
+                mode_count,
+            });
+        };
+        match mode
+            .transitions
+            .binary_search_by_key(&token_type, |(token_type, _)| *token_type)
+        {
+            Ok(index) => {
+                if let Some((_, target)) = mode.transitions.get_mut(index) {
+                    *target = to;
+                }
+            }
+            Err(index) => mode.transitions.insert(index, (token_type, to)),
+        }
+        Ok(self)
+    }
+
+    /// Returns the composed scanner.
+    pub fn build(self) -> Scanner {
+        self.scanner
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DfaData, Match, ScannerBuilder};
+
+    const BASE_DFAS: &[DfaData] = &[
+        ("a", &[1], &[(0, 1), (1, 1)], &[(0, 1)]),
+        ("<", &[1], &[(0, 1), (1, 1)], &[(1, 1)]),
+    ];
+
+    const EMBEDDED_DFAS: &[DfaData] = &[
+        ("b", &[1], &[(0, 1), (1, 1)], &[(0, 1)]),
+        (">", &[1], &[(0, 1), (1, 1)], &[(1, 1)]),
+    ];
+
+    fn base_matches_char_class(c: char, char_class: usize) -> bool {
+        match char_class {
+            0 => c == 'a',
+            1 => c == '<',
+            _ => false,
+        }
+    }
+
+    fn embedded_matches_char_class(c: char, char_class: usize) -> bool {
+        match char_class {
+            0 => c == 'b',
+            1 => c == '>',
+            _ => false,
+        }
+    }
+
+    #[test]
+    fn test_compose_scanners() {
+        let base = ScannerBuilder::new()
+            .add_dfa_data(BASE_DFAS)
+            .build()
+            .unwrap();
+        let embedded = ScannerBuilder::new()
+            .add_dfa_data(EMBEDDED_DFAS)
+            .build()
+            .unwrap();
+        let mut composer = ScannerComposer::new(base, base_matches_char_class);
+        let embedded_mode = composer.embed("emb", &embedded, embedded_matches_char_class, 10);
+        assert_eq!(embedded_mode, ScannerModeId::new(1));
+        composer
+            .add_transition(ScannerModeId::new(0), TokenType::new(1), embedded_mode)
+            .unwrap()
+            .add_transition(embedded_mode, TokenType::new(11), ScannerModeId::new(0))
+            .unwrap();
+        let scanner = composer.build();
+        assert_eq!(scanner.mode_name(embedded_mode), Some("emb::INITIAL"));
+        assert_eq!(scanner.dfas().count(), 4);
+
+        // The match function of the search is not used by any mode
+        let token_types = scanner
+            .find_iter("a<b>a", |_, _| unreachable!())
+            .map(|m: Match| m.token_type().as_usize())
+            .collect::<Vec<_>>();
+        assert_eq!(token_types, vec![0, 1, 10, 11, 0]);
+    }
+
+    #[test]
+    fn test_transition_to_unknown_mode() {
+        let base = ScannerBuilder::new()
+            .add_dfa_data(BASE_DFAS)
+            .build()
+            .unwrap();
+        let mut composer = ScannerComposer::new(base, base_matches_char_class);
+        assert!(matches!(
+            composer.add_transition(
+                ScannerModeId::new(0),
+                TokenType::new(1),
+                ScannerModeId::new(1)
+            ),
+            Err(BuildError::UnknownScannerMode { mode_count: 1, .. })
+        ));
+    }
+}
@@ -0,0 +1,353 @@
+use std::collections::VecDeque;
+
+use crate::{ScannerModeId, Span, TokenType};
+
+use super::{Token, TokenIter};
+
+/// A rule of [CookRules] that turns raw tokens into cooked tokens.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CookRule {
+    /// Merges a run of adjacent tokens of the given token types, e.g. the pieces of a string
+    /// literal, into one token of the result token type.
+    Merge {
+        /// The token types of the tokens that are merged.
+        token_types: Vec<TokenType>,
+        /// The token type of the merged token.
+        result: TokenType,
+    },
+    /// Fuses adjacent tokens of the given sequence of token types, e.g. `>` `>`, into one token
+    /// of the result token type, e.g. `>>`.
+    Fuse {
+        /// The token types of the tokens that are fused, in the order they appear.
+        sequence: Vec<TokenType>,
+        /// The token type of the fused token.
+        result: TokenType,
+    },
+    /// Splits a token of the given token type, e.g. `>>`, into tokens of the given token types and
+    /// lengths in bytes, e.g. `>` and `>`. A token is only split if the lengths of the parts add
+    /// up to its length and each part ends on a character boundary.
+    Split {
+        /// The token type of the tokens that are split.
+        token_type: TokenType,
+        /// The token types and lengths in bytes of the parts.
+        parts: Vec<(TokenType, usize)>,
+    },
+}
+
+/// The scanner modes in which a [CookRule] applies.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ModeCondition {
+    Any,
+    In(Vec<ScannerModeId>),
+    NotIn(Vec<ScannerModeId>),
+}
+
+impl ModeCondition {
+    fn holds(&self, mode: ScannerModeId) -> bool {
+        match self {
+            ModeCondition::Any => true,
+            ModeCondition::In(modes) => modes.contains(&mode),
+            ModeCondition::NotIn(modes) => !modes.contains(&mode),
+        }
+    }
+}
+
+/// The rules of the second level of scanning that turn the raw tokens found by the DFAs into the
+/// cooked tokens a parser consumes. They cover cases the DFAs alone can't express cleanly, e.g.
+/// to fuse `>` `>` into `>>` only outside of the scanner mode of generic arguments.
+///
+/// The rules are applied in the order they are added. The first rule that applies to the next raw
+/// token wins, and the cooked tokens are not cooked again. A rule applies only if all raw tokens
+/// it consumes were found in a scanner mode allowed by [CookRules::only_in_modes] or
+/// [CookRules::except_in_modes].
+///
+/// ```
+/// use scangen::{CookRules, ScannerModeId, TokenType};
+///
+/// const GT: TokenType = TokenType::new(1);
+/// const SHR: TokenType = TokenType::new(2);
+/// const GENERICS: ScannerModeId = ScannerModeId::new(1);
+///
+/// let rules = CookRules::new()
+///     .fuse(&[GT, GT], SHR)
+///     .except_in_modes(&[GENERICS])
+///     .split(SHR, &[(GT, 1), (GT, 1)])
+///     .only_in_modes(&[GENERICS]);
+/// assert_eq!(rules.rules().count(), 2);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CookRules {
+    rules: Vec<(CookRule, ModeCondition)>,
+}
+
+impl CookRules {
+    /// Creates an empty set of rules.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the given rule that applies in all scanner modes.
+    pub fn with_rule(mut self, rule: CookRule) -> Self {
+        self.rules.push((rule, ModeCondition::Any));
+        self
+    }
+
+    /// Adds a [CookRule::Merge] rule.
+    pub fn merge(self, token_types: &[TokenType], result: TokenType) -> Self {
+        self.with_rule(CookRule::Merge {
+            token_types: token_types.to_vec(),
+            result,
+        })
+    }
+
+    /// Adds a [CookRule::Fuse] rule.
+    pub fn fuse(self, sequence: &[TokenType], result: TokenType) -> Self {
+        self.with_rule(CookRule::Fuse {
+            sequence: sequence.to_vec(),
+            result,
+        })
+    }
+
+    /// Adds a [CookRule::Split] rule.
+    pub fn split(self, token_type: TokenType, parts: &[(TokenType, usize)]) -> Self {
+        self.with_rule(CookRule::Split {
+            token_type,
+            parts: parts.to_vec(),
+        })
+    }
+
+    /// Restricts the last added rule to the raw tokens found in the given scanner modes.
+    pub fn only_in_modes(mut self, modes: &[ScannerModeId]) -> Self {
+        if let Some((_, condition)) = self.rules.last_mut() {
+            *condition = ModeCondition::In(modes.to_vec());
+        }
+        self
+    }
+
+    /// Restricts the last added rule to the raw tokens found outside of the given scanner modes.
+    pub fn except_in_modes(mut self, modes: &[ScannerModeId]) -> Self {
+        if let Some((_, condition)) = self.rules.last_mut() {
+            *condition = ModeCondition::NotIn(modes.to_vec());
+        }
+        self
+    }
+
+    /// Returns the rules in the order they are applied.
+    pub fn rules(&self) -> impl Iterator<Item = &CookRule> {
+        self.rules.iter().map(|(rule, _)| rule)
+    }
+}
+
+/// An iterator adapter that turns raw tokens into cooked tokens with the given [CookRules].
+///
+/// Tokens no rule applies to are passed through. The merged and fused tokens span the raw tokens
+/// they are made of, and their text is the text of this span in the haystack.
+///
+/// This iterator can be created with [crate::TokenIter::cook].
+#[derive(Debug)]
+pub struct CookedTokens<'h> {
+    input: &'h str,
+    tokens: TokenIter<'h>,
+    rules: CookRules,
+    // The raw tokens read ahead along with the scanner mode they were found in.
+    pending: VecDeque<(Token<'h>, ScannerModeId)>,
+    // The remaining parts of the last split token.
+    parts: VecDeque<Token<'h>>,
+}
+
+impl<'h> CookedTokens<'h> {
+    /// Creates a new `CookedTokens` iterator adapter over the tokens of the given haystack.
+    pub(crate) fn new(input: &'h str, tokens: TokenIter<'h>, rules: CookRules) -> Self {
+        Self {
+            input,
+            tokens,
+            rules,
+            pending: VecDeque::new(),
+            parts: VecDeque::new(),
+        }
+    }
+
+    /// Returns the raw token at the given position of the lookahead, reading ahead as needed.
+    fn raw_token(
+        tokens: &mut TokenIter<'h>,
+        pending: &mut VecDeque<(Token<'h>, ScannerModeId)>,
+        index: usize,
+    ) -> Option<(Token<'h>, ScannerModeId)> {
+        while pending.len() <= index {
+            // A transition is executed after the token that triggers it is found, so the current
+            // mode is the one the next token is found in.
+            let mode = tokens.find_matches().scanner().current_mode();
+            let token = tokens.next()?;
+            pending.push_back((token, mode));
+        }
+        pending.get(index).copied()
+    }
+
+    /// Returns the number of raw tokens the given rule consumes from the start of the lookahead,
+    /// or None if it doesn't apply.
+    fn apply(
+        tokens: &mut TokenIter<'h>,
+        pending: &mut VecDeque<(Token<'h>, ScannerModeId)>,
+        rule: &CookRule,
+        condition: &ModeCondition,
+    ) -> Option<usize> {
+        let mut end = None;
+        let mut accepts = |index: usize, token_types: &[TokenType]| {
+            let (token, mode) = Self::raw_token(tokens, pending, index)?;
+            let adjacent = end.is_none_or(|end| end == token.span.start);
+            end = Some(token.span.end);
+            (adjacent && token_types.contains(&token.token_type) && condition.holds(mode))
+                .then_some(())
+        };
+        match rule {
+            CookRule::Merge { token_types, .. } => {
+                accepts(0, token_types)?;
+                let mut count = 1;
+                while accepts(count, token_types).is_some() {
+                    count += 1;
+                }
+                Some(count)
+            }
+            CookRule::Fuse { sequence, .. } => {
+                for (index, token_type) in sequence.iter().enumerate() {
+                    accepts(index, std::slice::from_ref(token_type))?;
+                }
+                Some(sequence.len()).filter(|count| *count > 0)
+            }
+            CookRule::Split { token_type, .. } => {
+                accepts(0, std::slice::from_ref(token_type))?;
+                Some(1)
+            }
+        }
+    }
+
+    /// Returns the parts of the given token, or None if there are none or they don't cover it.
+    fn split(&self, token: Token<'h>, parts: &[(TokenType, usize)]) -> Option<Vec<Token<'h>>> {
+        let mut start = token.span.start;
+        let tokens = parts
+            .iter()
+            .map(|(token_type, len)| {
+                let span = Span::new(start, start.checked_add(*len)?);
+                start = span.end;
+                Some(Token {
+                    token_type: *token_type,
+                    span,
+                    text: self.input.get(span.range())?,
+                })
+            })
+            .collect::<Option<Vec<_>>>()?;
+        (start == token.span.end && !tokens.is_empty()).then_some(tokens)
+    }
+}
+
+impl<'h> Iterator for CookedTokens<'h> {
+    type Item = Token<'h>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(part) = self.parts.pop_front() {
+            return Some(part);
+        }
+        let (first, _) = Self::raw_token(&mut self.tokens, &mut self.pending, 0)?;
+        for (rule, condition) in &self.rules.rules {
+            let Some(count) = Self::apply(&mut self.tokens, &mut self.pending, rule, condition)
+            else {
+                continue;
+            };
+            let result = match rule {
+                CookRule::Merge { result, .. } | CookRule::Fuse { result, .. } => *result,
+                CookRule::Split { parts, .. } => {
+                    let Some(parts) = self.split(first, parts) else {
+                        continue;
+                    };
+                    self.pending.pop_front();
+                    self.parts.extend(parts);
+                    return self.parts.pop_front();
+                }
+            };
+            let (last, _) = self.pending.drain(..count).next_back()?;
+            let span = Span::new(first.span.start, last.span.end);
+            return Some(Token {
+                token_type: result,
+                span,
+                text: self.input.get(span.range()).unwrap_or_default(),
+            });
+        }
+        self.pending.pop_front().map(|(token, _)| token)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::generated::scanner_with_modes;
+
+    // The token types of the identifier, escape, string content, quote and any other character of
+    // the scanner with modes and two token types for the cooked tokens.
+    const IDENTIFIER: TokenType = TokenType::new(4);
+    const ESCAPE: TokenType = TokenType::new(5);
+    const CONTENT: TokenType = TokenType::new(7);
+    const QUOTE: TokenType = TokenType::new(8);
+    const OTHER: TokenType = TokenType::new(9);
+    const SHR: TokenType = TokenType::new(10);
+    const STRING: TokenType = TokenType::new(11);
+
+    fn cook(input: &str, rules: CookRules) -> Vec<(TokenType, &str)> {
+        let scanner = scanner_with_modes::create_scanner();
+        scanner_with_modes::create_find_iter(&scanner, input)
+            .tokens()
+            .cook(rules)
+            .filter(|token| !token.text.trim().is_empty())
+            .map(|token| (token.token_type, token.text))
+            .collect()
+    }
+
+    #[test]
+    fn test_fuse() {
+        let rules = CookRules::new().fuse(&[OTHER, OTHER], SHR);
+        assert_eq!(
+            cook("a>>b > >", rules.clone()),
+            vec![
+                (IDENTIFIER, "a"),
+                (SHR, ">>"),
+                (IDENTIFIER, "b"),
+                (OTHER, ">"),
+                (OTHER, ">"),
+            ]
+        );
+        // The rule doesn't apply in the initial mode
+        let rules = rules.except_in_modes(&[ScannerModeId::new(0)]);
+        assert_eq!(
+            cook("a>>", rules),
+            vec![(IDENTIFIER, "a"), (OTHER, ">"), (OTHER, ">")]
+        );
+    }
+
+    #[test]
+    fn test_merge() {
+        let rules = CookRules::new()
+            .merge(&[CONTENT, ESCAPE], STRING)
+            .only_in_modes(&[ScannerModeId::new(1)]);
+        assert_eq!(
+            cook(r#"x "a\tb" "c""#, rules),
+            vec![
+                (IDENTIFIER, "x"),
+                (QUOTE, "\""),
+                (STRING, r"a\tb"),
+                (QUOTE, "\""),
+                (QUOTE, "\""),
+                (STRING, "c"),
+                (QUOTE, "\""),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_split() {
+        let rules = CookRules::new().split(IDENTIFIER, &[(IDENTIFIER, 1), (OTHER, 1)]);
+        // Only the identifiers whose length matches the parts are split
+        assert_eq!(
+            cook("ab abc", rules),
+            vec![(IDENTIFIER, "a"), (OTHER, "b"), (IDENTIFIER, "abc")]
+        );
+    }
+}
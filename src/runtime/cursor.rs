@@ -0,0 +1,186 @@
+use super::{CharSource, SeekCharSource};
+
+/// A cursor over the characters of a UTF-8 input and their byte offsets.
+///
+/// In contrast to [`std::str::CharIndices`] the cursor decodes the UTF-8 sequences itself, is
+/// `Copy` and can be positioned at any character boundary in O(1). This way the iterators over the
+/// matches can hand out the current position to the scanner and jump behind a match without
+/// iterating over its characters again.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Utf8Cursor<'h> {
+    input: &'h [u8],
+    position: usize,
+}
+
+impl<'h> Utf8Cursor<'h> {
+    /// Creates a new cursor at the start of the given input.
+    pub(crate) fn new(input: &'h str) -> Self {
+        Utf8Cursor {
+            input: input.as_bytes(),
+            position: 0,
+        }
+    }
+
+    /// Returns the byte offset of the next character.
+    #[inline]
+    pub(crate) fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Moves the cursor to the given byte offset which must be a character boundary of the input.
+    #[inline]
+    pub(crate) fn set_position(&mut self, position: usize) {
+        debug_assert!(position <= self.input.len());
+        self.position = position;
+    }
+
+    /// Returns true if the cursor is at the end of the input.
+    #[inline]
+    pub(crate) fn is_at_end(&self) -> bool {
+        self.position >= self.input.len()
+    }
+}
+
+impl Iterator for Utf8Cursor<'_> {
+    type Item = (usize, char);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let position = self.position;
+        let first = *self.input.get(position)?;
+        // The input is valid UTF-8, so the sequence is complete and the length can be derived
+        // from the first byte.
+        let (code_point, len) = match first {
+            0x00..=0x7F => (first as u32, 1),
+            0xC0..=0xDF => (first as u32 & 0x1F, 2),
+            0xE0..=0xEF => (first as u32 & 0x0F, 3),
+            _ => (first as u32 & 0x07, 4),
+        };
+        let code_point = self
+            .input
+            .get(position + 1..position + len)
+            .unwrap_or_default()
+            .iter()
+            .fold(code_point, |code_point, byte| {
+                (code_point << 6) | (*byte as u32 & 0x3F)
+            });
+        self.position += len;
+        Some((
+            position,
+            char::from_u32(code_point).unwrap_or(char::REPLACEMENT_CHARACTER),
+        ))
+    }
+}
+
+impl SeekCharSource for Utf8Cursor<'_> {
+    #[inline]
+    fn offset(&self) -> usize {
+        Utf8Cursor::position(self)
+    }
+
+    #[inline]
+    fn seek(&mut self, offset: usize) {
+        Utf8Cursor::set_position(self, offset);
+    }
+
+    #[inline]
+    fn is_at_end(&self) -> bool {
+        Utf8Cursor::is_at_end(self)
+    }
+}
+
+impl CharSource for Utf8Cursor<'_> {
+    #[inline]
+    fn encoded_len(&self, c: char) -> usize {
+        c.len_utf8()
+    }
+
+    /// Consumes a run like the provided method, but checks ASCII characters without decoding
+    /// them. Eight bytes at a time are tested for being ASCII with a single word operation, so
+    /// long runs of e.g. whitespace or identifier characters are consumed in one tight loop.
+    fn skip_run<P>(&mut self, limit: usize, mut predicate: P) -> Option<usize>
+    where
+        P: FnMut(char) -> bool,
+    {
+        const NON_ASCII_BITS: u64 = 0x8080_8080_8080_8080;
+        let mut end = None;
+        loop {
+            while let Some(chunk) = self
+                .input
+                .get(self.position..self.position + 8)
+                .filter(|_| self.position + 8 <= limit)
+            {
+                let word = u64::from_ne_bytes(chunk.try_into().unwrap_or([0x80; 8]));
+                if word & NON_ASCII_BITS != 0 {
+                    break;
+                }
+                match chunk.iter().position(|b| !predicate(char::from(*b))) {
+                    Some(len) => {
+                        self.position += len;
+                        return if len > 0 { Some(self.position) } else { end };
+                    }
+                    None => {
+                        self.position += 8;
+                        end = Some(self.position);
+                    }
+                }
+            }
+            let mut lookahead = *self;
+            match lookahead.next() {
+                Some((i, c)) if i + c.len_utf8() <= limit && predicate(c) => {
+                    *self = lookahead;
+                    end = Some(self.position);
+                }
+                _ => return end,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_utf8_cursor() {
+        let input = "aä€😀b";
+        let mut cursor = Utf8Cursor::new(input);
+        assert_eq!(
+            cursor.collect::<Vec<_>>(),
+            input.char_indices().collect::<Vec<_>>()
+        );
+        cursor.set_position(3);
+        assert_eq!(cursor.next(), Some((3, '€')));
+        assert_eq!(cursor.position(), 6);
+        cursor.set_position(input.len());
+        assert!(cursor.is_at_end());
+        assert_eq!(cursor.next(), None);
+    }
+
+    #[test]
+    fn test_skip_run() {
+        let input = "  \t        ä  x";
+        let mut cursor = Utf8Cursor::new(input);
+        assert_eq!(cursor.skip_run(usize::MAX, char::is_whitespace), Some(11));
+        assert_eq!(cursor.skip_run(usize::MAX, char::is_whitespace), None);
+        assert_eq!(cursor.position(), 11);
+        assert_eq!(cursor.skip_run(usize::MAX, |c| c != 'x'), Some(15));
+        assert_eq!(cursor.next(), Some((15, 'x')));
+        assert_eq!(cursor.skip_run(usize::MAX, |_| true), None);
+
+        // The run ends at the limit, even within a character
+        let mut cursor = Utf8Cursor::new(input);
+        assert_eq!(cursor.skip_run(10, |_| true), Some(10));
+        assert_eq!(cursor.skip_run(12, |_| true), Some(11));
+        assert_eq!(cursor.skip_run(12, |_| true), None);
+        assert_eq!(cursor.next(), Some((11, 'ä')));
+
+        // The provided method of the trait yields the same result
+        let mut char_indices = input.char_indices();
+        assert_eq!(
+            char_indices.skip_run(usize::MAX, char::is_whitespace),
+            Some(11)
+        );
+        assert_eq!(char_indices.next(), Some((11, 'ä')));
+    }
+}
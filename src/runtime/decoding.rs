@@ -0,0 +1,608 @@
+//! This module provides adapters that decode non-UTF-8 inputs on the fly.
+//!
+//! The scanner is character oriented and usually works on a `&str`. Files from legacy systems are
+//! often encoded in UTF-16 or Latin-1. Instead of converting them into a `String` beforehand, the
+//! adapters of this module decode the characters while scanning. The spans of the matches are
+//! reported in byte offsets of the original input.
+//!
+//! Optionally the encoding can be detected from a byte order mark, which is skipped before
+//! scanning.
+//!
+//! Invalid sequences are decoded as replacement characters. Optionally they are reported as error
+//! tokens instead, e.g. by log pipelines that scan raw bytes without a separate validation pass.
+
+use crate::{common::Match, TokenType};
+
+use super::{FindOutcome, Scanner};
+
+/// A source of characters along with their byte offsets in the original input.
+///
+/// The scanner can search any character source. [`std::str::CharIndices`] is the character
+/// source for UTF-8 input, [`DecodedCharIndices`] is the character source for the other supported
+/// encodings.
+pub trait CharSource: Iterator<Item = (usize, char)> + Clone {
+    /// Returns the number of bytes the given character occupies in the original input.
+    /// The character is the one returned by the last call to `next`.
+    fn encoded_len(&self, c: char) -> usize;
+
+    /// Consumes the longest run of characters that satisfy the given predicate and end at most at
+    /// the byte offset `limit`. Returns the byte offset after the run, or None if the next
+    /// character doesn't belong to the run.
+    fn skip_run<P>(&mut self, limit: usize, mut predicate: P) -> Option<usize>
+    where
+        P: FnMut(char) -> bool,
+    {
+        let mut end = None;
+        let mut lookahead = self.clone();
+        while let Some((i, c)) = lookahead.next() {
+            if i + lookahead.encoded_len(c) > limit || !predicate(c) {
+                break;
+            }
+            end = Some(i + lookahead.encoded_len(c));
+            *self = lookahead.clone();
+        }
+        end
+    }
+}
+
+/// A character source that can be moved to a byte offset of the input, so the scanner can drive
+/// the search for the next match over it, see [Scanner::find_next].
+pub(crate) trait SeekCharSource: CharSource {
+    /// Returns the byte offset of the next character.
+    fn offset(&self) -> usize;
+
+    /// Moves the character source to the given byte offset, which must be a character boundary.
+    fn seek(&mut self, offset: usize);
+
+    /// Returns true if no character follows.
+    fn is_at_end(&self) -> bool {
+        self.clone().next().is_none()
+    }
+}
+
+impl CharSource for std::str::CharIndices<'_> {
+    #[inline]
+    fn encoded_len(&self, c: char) -> usize {
+        c.len_utf8()
+    }
+}
+
+/// The encodings supported by [`DecodedCharIndices`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    /// UTF-8, for byte inputs that may contain invalid sequences or a byte order mark
+    Utf8,
+    /// UTF-16, little endian
+    Utf16Le,
+    /// UTF-16, big endian
+    Utf16Be,
+    /// ISO-8859-1, i.e. each byte is the unicode code point of the character
+    Latin1,
+}
+
+impl Encoding {
+    /// Detects a byte order mark at the start of the input.
+    /// Returns the encoding indicated by the byte order mark along with its length in bytes, or
+    /// `None` if the input doesn't start with a byte order mark.
+    pub fn detect_bom(input: &[u8]) -> Option<(Encoding, usize)> {
+        match input {
+            [0xEF, 0xBB, 0xBF, ..] => Some((Encoding::Utf8, 3)),
+            [0xFF, 0xFE, ..] => Some((Encoding::Utf16Le, 2)),
+            [0xFE, 0xFF, ..] => Some((Encoding::Utf16Be, 2)),
+            _ => None,
+        }
+    }
+}
+
+/// An iterator over the decoded characters of a byte input and their byte offsets.
+///
+/// Invalid code units, like unpaired surrogates or a trailing odd byte in UTF-16, are decoded as
+/// the replacement character `U+FFFD`, so the scanner never stops on malformed input.
+#[derive(Debug, Clone)]
+pub struct DecodedCharIndices<'h> {
+    input: &'h [u8],
+    position: usize,
+    encoding: Encoding,
+    // The length of the last decoded character in the input
+    last_len: usize,
+    // If true, the iterator ends before an invalid sequence
+    stop_at_invalid: bool,
+}
+
+impl<'h> DecodedCharIndices<'h> {
+    /// Creates a new iterator over the characters of the given input.
+    pub fn new(input: &'h [u8], encoding: Encoding) -> Self {
+        DecodedCharIndices {
+            input,
+            position: 0,
+            encoding,
+            last_len: 0,
+            stop_at_invalid: false,
+        }
+    }
+
+    /// Creates a new iterator that detects the encoding from the byte order mark of the input.
+    /// The byte order mark is skipped, so the offsets still refer to the original input.
+    /// If the input has no byte order mark, the given default encoding is used.
+    pub fn with_bom_detection(input: &'h [u8], default_encoding: Encoding) -> Self {
+        let (encoding, position) = Encoding::detect_bom(input).unwrap_or((default_encoding, 0));
+        DecodedCharIndices {
+            input,
+            position,
+            encoding,
+            last_len: 0,
+            stop_at_invalid: false,
+        }
+    }
+
+    /// Returns the encoding of the input.
+    #[inline]
+    pub fn encoding(&self) -> Encoding {
+        self.encoding
+    }
+
+    /// Moves the iterator to the given byte offset which must be the start of an encoded
+    /// character.
+    #[inline]
+    pub(crate) fn set_position(&mut self, position: usize) {
+        debug_assert!(position <= self.input.len());
+        self.position = position;
+    }
+
+    /// Makes the iterator end before an invalid sequence, so the scanner doesn't include its
+    /// replacement character in a token.
+    pub(crate) fn set_stop_at_invalid(&mut self, stop_at_invalid: bool) {
+        self.stop_at_invalid = stop_at_invalid;
+    }
+
+    /// Returns the length in bytes of the invalid sequence at the current position, if any.
+    pub(crate) fn invalid_len(&self) -> Option<usize> {
+        let (c, len) = self.decode()?;
+        self.is_invalid(c, len).then_some(len)
+    }
+
+    // A replacement character is only valid if it is encoded in the input itself.
+    fn is_invalid(&self, c: char, len: usize) -> bool {
+        if c != char::REPLACEMENT_CHARACTER {
+            return false;
+        }
+        match self.encoding {
+            Encoding::Utf8 => {
+                self.input.get(self.position..self.position + len) != Some(&[0xEF, 0xBF, 0xBD])
+            }
+            Encoding::Utf16Le | Encoding::Utf16Be => {
+                len != 2 || self.code_unit_at(self.position) != Some(0xFFFD)
+            }
+            Encoding::Latin1 => false,
+        }
+    }
+
+    fn decode(&self) -> Option<(char, usize)> {
+        let first = *self.input.get(self.position)?;
+        Some(match self.encoding {
+            Encoding::Utf8 => self.decode_utf8(),
+            Encoding::Latin1 => (first as char, 1),
+            Encoding::Utf16Le | Encoding::Utf16Be => self.decode_utf16(),
+        })
+    }
+
+    fn code_unit_at(&self, position: usize) -> Option<u16> {
+        let bytes: [u8; 2] = self.input.get(position..position + 2)?.try_into().ok()?;
+        Some(match self.encoding {
+            Encoding::Utf16Be => u16::from_be_bytes(bytes),
+            _ => u16::from_le_bytes(bytes),
+        })
+    }
+
+    fn decode_utf8(&self) -> (char, usize) {
+        let end = (self.position + 4).min(self.input.len());
+        let bytes = self.input.get(self.position..end).unwrap_or_default();
+        let valid = match std::str::from_utf8(bytes) {
+            Ok(valid) => valid,
+            Err(e) if e.valid_up_to() > 0 => {
+                // The prefix up to the reported position is valid UTF-8
+                bytes
+                    .get(..e.valid_up_to())
+                    .and_then(|valid| std::str::from_utf8(valid).ok())
+                    .unwrap_or_default()
+            }
+            Err(e) => {
+                return (
+                    char::REPLACEMENT_CHARACTER,
+                    e.error_len().unwrap_or(bytes.len()),
+                )
+            }
+        };
+        let c = valid.chars().next().unwrap_or(char::REPLACEMENT_CHARACTER);
+        (c, c.len_utf8())
+    }
+
+    fn decode_utf16(&self) -> (char, usize) {
+        let Some(unit) = self.code_unit_at(self.position) else {
+            // A trailing odd byte
+            return (char::REPLACEMENT_CHARACTER, 1);
+        };
+        match unit {
+            0xD800..=0xDBFF => match self.code_unit_at(self.position + 2) {
+                Some(low @ 0xDC00..=0xDFFF) => {
+                    let code_point =
+                        0x10000 + (((unit as u32) - 0xD800) << 10) + ((low as u32) - 0xDC00);
+                    (
+                        char::from_u32(code_point).unwrap_or(char::REPLACEMENT_CHARACTER),
+                        4,
+                    )
+                }
+                _ => (char::REPLACEMENT_CHARACTER, 2),
+            },
+            0xDC00..=0xDFFF => (char::REPLACEMENT_CHARACTER, 2),
+            _ => (
+                char::from_u32(unit as u32).unwrap_or(char::REPLACEMENT_CHARACTER),
+                2,
+            ),
+        }
+    }
+}
+
+impl Iterator for DecodedCharIndices<'_> {
+    type Item = (usize, char);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (c, len) = self.decode()?;
+        if self.stop_at_invalid && self.is_invalid(c, len) {
+            return None;
+        }
+        let position = self.position;
+        self.position += len;
+        self.last_len = len;
+        Some((position, c))
+    }
+}
+
+impl CharSource for DecodedCharIndices<'_> {
+    #[inline]
+    fn encoded_len(&self, _c: char) -> usize {
+        // Invalid sequences are decoded as replacement characters, so the length can't be
+        // derived from the character itself.
+        self.last_len
+    }
+}
+
+impl SeekCharSource for DecodedCharIndices<'_> {
+    #[inline]
+    fn offset(&self) -> usize {
+        self.position
+    }
+
+    #[inline]
+    fn seek(&mut self, offset: usize) {
+        DecodedCharIndices::set_position(self, offset);
+    }
+}
+
+/// An iterator over all non-overlapping matches in a decoded byte input.
+///
+/// The iterator yields a [`Match`] value until no more matches could be found. The spans of the
+/// matches are byte offsets in the original input.
+///
+/// This iterator can be created with the [`Scanner::find_iter_decoded`] and the
+/// [`Scanner::find_iter_lossy`] methods.
+#[derive(Debug)]
+pub struct FindDecodedMatches<'h> {
+    scanner: Scanner,
+    char_indices: DecodedCharIndices<'h>,
+    matches_char_class: fn(char, usize) -> bool,
+    // The token type of the matches that cover invalid sequences
+    invalid_token_type: Option<TokenType>,
+}
+
+impl<'h> FindDecodedMatches<'h> {
+    /// Creates a new `FindDecodedMatches` iterator.
+    pub fn new(
+        scanner: Scanner,
+        input: &'h [u8],
+        encoding: Encoding,
+        matches_char_class: fn(char, usize) -> bool,
+    ) -> Self {
+        FindDecodedMatches {
+            scanner,
+            char_indices: DecodedCharIndices::new(input, encoding),
+            matches_char_class,
+            invalid_token_type: None,
+        }
+    }
+
+    /// Creates a new `FindDecodedMatches` iterator that detects the encoding from the byte order
+    /// mark of the input and skips it. See [`DecodedCharIndices::with_bom_detection`].
+    pub fn with_bom_detection(
+        scanner: Scanner,
+        input: &'h [u8],
+        default_encoding: Encoding,
+        matches_char_class: fn(char, usize) -> bool,
+    ) -> Self {
+        FindDecodedMatches {
+            scanner,
+            char_indices: DecodedCharIndices::with_bom_detection(input, default_encoding),
+            matches_char_class,
+            invalid_token_type: None,
+        }
+    }
+
+    /// Returns the encoding of the input.
+    #[inline]
+    pub fn encoding(&self) -> Encoding {
+        self.char_indices.encoding()
+    }
+
+    /// Returns the scanner used by the iterator.
+    #[inline]
+    pub fn scanner(&self) -> &Scanner {
+        &self.scanner
+    }
+
+    /// Sets the token type of the matches that cover invalid sequences of the input.
+    /// By default invalid sequences are decoded as replacement characters and scanned like any
+    /// other character. If a token type is set, each run of invalid sequences is reported as a
+    /// match of this type instead and never becomes part of another token.
+    pub fn set_invalid_token_type(&mut self, token_type: Option<TokenType>) {
+        self.invalid_token_type = token_type;
+        self.char_indices.set_stop_at_invalid(token_type.is_some());
+    }
+
+    /// Returns the token type of the matches that cover invalid sequences, if set.
+    #[inline]
+    pub fn invalid_token_type(&self) -> Option<TokenType> {
+        self.invalid_token_type
+    }
+
+    /// Returns the next match in the input.
+    ///
+    /// If no match is found, `None` is returned.
+    /// Like [`super::FindMatches::next_match`], matches of the skipped token types are passed
+    /// over, unmatched input is reported as a match of the [`Scanner::error_token_type`] if there
+    /// is one, and `None` is returned if the step budget of the scanner is exhausted or if more
+    /// input is needed.
+    pub fn next_match(&mut self) -> Option<Match> {
+        loop {
+            if let Some(matched) = self.invalid_match() {
+                return Some(matched);
+            }
+            match self
+                .scanner
+                .find_next(&mut self.char_indices, self.matches_char_class)
+            {
+                FindOutcome::Found(matched) => return Some(matched),
+                // The character source also ends before an invalid sequence.
+                FindOutcome::NotFound
+                    if self.invalid_token_type.is_some()
+                        && self.char_indices.invalid_len().is_some() => {}
+                _ => return None,
+            }
+        }
+    }
+
+    // Returns a match of the invalid token type that covers the run of invalid sequences at the
+    // current position, if any.
+    fn invalid_match(&mut self) -> Option<Match> {
+        let token_type = self.invalid_token_type?;
+        let start = self.char_indices.position;
+        let mut end = start;
+        while let Some(len) = self.char_indices.invalid_len() {
+            end += len;
+            self.char_indices.set_position(end);
+        }
+        (end > start).then(|| Match::new(token_type, (start..end).into()))
+    }
+}
+
+impl Iterator for FindDecodedMatches<'_> {
+    type Item = Match;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_match()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::generated::scanner_with_modes::{create_find_iter, create_scanner};
+
+    fn utf16(input: &str, big_endian: bool) -> Vec<u8> {
+        input
+            .encode_utf16()
+            .flat_map(|u| {
+                if big_endian {
+                    u.to_be_bytes()
+                } else {
+                    u.to_le_bytes()
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_decoded_char_indices() {
+        let input = utf16("aπ😀", false);
+        let chars = DecodedCharIndices::new(&input, Encoding::Utf16Le).collect::<Vec<_>>();
+        assert_eq!(chars, vec![(0, 'a'), (2, 'π'), (4, '😀')]);
+
+        let input = utf16("aπ😀", true);
+        let chars = DecodedCharIndices::new(&input, Encoding::Utf16Be).collect::<Vec<_>>();
+        assert_eq!(chars, vec![(0, 'a'), (2, 'π'), (4, '😀')]);
+
+        let input = [b'a', 0xE4, b'b'];
+        let chars = DecodedCharIndices::new(&input, Encoding::Latin1).collect::<Vec<_>>();
+        assert_eq!(chars, vec![(0, 'a'), (1, 'ä'), (2, 'b')]);
+    }
+
+    #[test]
+    fn test_invalid_utf16() {
+        // An unpaired high surrogate followed by 'a' and a trailing odd byte
+        let input = [0x00, 0xD8, b'a', 0x00, 0x01];
+        let chars = DecodedCharIndices::new(&input, Encoding::Utf16Le).collect::<Vec<_>>();
+        assert_eq!(
+            chars,
+            vec![
+                (0, char::REPLACEMENT_CHARACTER),
+                (2, 'a'),
+                (4, char::REPLACEMENT_CHARACTER)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invalid_utf8() {
+        let input = [b'a', 0xFF, 0xE2, 0x82, b'b', 0xE2, 0x82, 0xAC];
+        let chars = DecodedCharIndices::new(&input, Encoding::Utf8).collect::<Vec<_>>();
+        assert_eq!(
+            chars,
+            vec![
+                (0, 'a'),
+                (1, char::REPLACEMENT_CHARACTER),
+                (2, char::REPLACEMENT_CHARACTER),
+                (4, 'b'),
+                (5, '€')
+            ]
+        );
+    }
+
+    #[test]
+    fn test_bom_detection() {
+        assert_eq!(
+            Encoding::detect_bom(&[0xEF, 0xBB, 0xBF, b'a']),
+            Some((Encoding::Utf8, 3))
+        );
+        assert_eq!(
+            Encoding::detect_bom(&[0xFF, 0xFE, b'a', 0]),
+            Some((Encoding::Utf16Le, 2))
+        );
+        assert_eq!(
+            Encoding::detect_bom(&[0xFE, 0xFF, 0, b'a']),
+            Some((Encoding::Utf16Be, 2))
+        );
+        assert_eq!(Encoding::detect_bom(b"abc"), None);
+
+        let mut input = vec![0xFE, 0xFF];
+        input.extend(utf16("x yz", true));
+        let scanner = create_scanner();
+        let matches_char_class = create_find_iter(&scanner, "").matches_char_class();
+        let find_iter =
+            scanner.find_iter_with_bom_detection(&input, Encoding::Latin1, matches_char_class);
+        assert_eq!(find_iter.encoding(), Encoding::Utf16Be);
+        let spans = find_iter.map(|m| m.span()).collect::<Vec<_>>();
+        // The spans are offsets in the original input including the byte order mark.
+        assert_eq!(spans.first().map(|s| s.start), Some(2));
+        assert_eq!(spans.last().map(|s| s.end), Some(input.len()));
+
+        let chars =
+            DecodedCharIndices::with_bom_detection(b"ab", Encoding::Latin1).collect::<Vec<_>>();
+        assert_eq!(chars, vec![(0, 'a'), (1, 'b')]);
+    }
+
+    #[test]
+    fn test_find_iter_decoded() {
+        let text = "x ä yz";
+        let expected = create_find_iter(&create_scanner(), text)
+            .map(|m| (m.token_type(), text[m.span().range()].to_string()))
+            .collect::<Vec<_>>();
+
+        for encoding in [
+            Encoding::Utf8,
+            Encoding::Utf16Le,
+            Encoding::Utf16Be,
+            Encoding::Latin1,
+        ] {
+            let input = match encoding {
+                Encoding::Utf8 => text.as_bytes().to_vec(),
+                Encoding::Utf16Le => utf16(text, false),
+                Encoding::Utf16Be => utf16(text, true),
+                Encoding::Latin1 => text.chars().map(|c| c as u8).collect(),
+            };
+            let width = match encoding {
+                Encoding::Utf16Le | Encoding::Utf16Be => 2,
+                _ => 1,
+            };
+            let scanner = create_scanner();
+            let matches_char_class = create_find_iter(&scanner, "").matches_char_class();
+            let matches = scanner
+                .find_iter_decoded(&input, encoding, matches_char_class)
+                .collect::<Vec<_>>();
+            let decoded = matches
+                .iter()
+                .map(|m| {
+                    let span = m.span();
+                    assert_eq!(span.start % width, 0);
+                    let text = DecodedCharIndices::new(&input[span.range()], encoding)
+                        .map(|(_, c)| c)
+                        .collect::<String>();
+                    (m.token_type(), text)
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(decoded, expected, "{:?}", encoding);
+        }
+    }
+
+    #[test]
+    fn test_find_iter_lossy() {
+        let input = b"ab\xFFcd x\xEF\xBF\xBDy\n\xE2\x82\xE2";
+        let scanner = create_scanner();
+        let matches_char_class = create_find_iter(&scanner, "").matches_char_class();
+        let spans = |find_iter: FindDecodedMatches| {
+            find_iter
+                .map(|m| (m.token_type(), m.span().range()))
+                .collect::<Vec<_>>()
+        };
+        // The error token type 9 matches any character, including replacement characters.
+        assert_eq!(
+            spans(scanner.find_iter_lossy(input, matches_char_class)),
+            vec![
+                (4.into(), 0..2),
+                (9.into(), 2..3),
+                (4.into(), 3..5),
+                (1.into(), 5..6),
+                (4.into(), 6..7),
+                (9.into(), 7..10),
+                (4.into(), 10..11),
+                (0.into(), 11..12),
+                (9.into(), 12..14),
+                (9.into(), 14..15)
+            ]
+        );
+
+        // Invalid sequences are reported as runs of the invalid token type. The encoded
+        // replacement character is valid input.
+        let mut find_iter = scanner.find_iter_lossy(input, matches_char_class);
+        find_iter.set_invalid_token_type(Some(10.into()));
+        assert_eq!(
+            spans(find_iter),
+            vec![
+                (4.into(), 0..2),
+                (10.into(), 2..3),
+                (4.into(), 3..5),
+                (1.into(), 5..6),
+                (4.into(), 6..7),
+                (9.into(), 7..10),
+                (4.into(), 10..11),
+                (0.into(), 11..12),
+                (10.into(), 12..15)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_find_iter_decoded_skipped_token_types() {
+        let mut scanner = create_scanner();
+        scanner.set_skipped_token_types([TokenType::new(1)]);
+        let matches_char_class = create_find_iter(&scanner, "").matches_char_class();
+        let matches = scanner
+            .find_iter_lossy(b"ab cd", matches_char_class)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            matches,
+            vec![
+                Match::new(4.into(), (0usize..2).into()),
+                Match::new(4.into(), (3usize..5).into()),
+            ]
+        );
+    }
+}
@@ -1,131 +1,519 @@
-use crate::{
-    common::{MatchingState, Span},
-    DfaData, Match,
+use std::{borrow::Cow, sync::Arc};
+
+use crate::{common::MatchingState, DfaData, Match, OwnedDfaData, TokenType};
+
+use super::{
+    json::{write_array, write_pair, write_string},
+    ranges_contain,
 };
 
+/// The characters of the character classes as sorted and disjoint ranges, indexed by character
+/// class.
+pub(crate) type CharClasses = Arc<[Vec<(char, char)>]>;
+
+/// The maximum number of states of a DFA whose transitions on ASCII characters are tabulated by
+/// [crate::Scanner::prepare]. The table has 128 entries per state.
+const MAX_TABULATED_STATES: usize = 4096;
+
 /// Runtime version of a DFA.
+///
+/// A DFA created from generated [DfaData] borrows the static tables of the generated code, so
+/// creating a scanner doesn't copy them.
 #[derive(Debug, Clone)]
 pub struct Dfa {
     /// The pattern that this DFA recognizes.
-    pub pattern: String,
+    pub pattern: Cow<'static, str>,
     /// The states that are accepting states.
-    pub accepting_states: Vec<usize>,
+    pub accepting_states: Cow<'static, [usize]>,
     /// The ranges of transitions for each state.
-    pub state_ranges: Vec<(usize, usize)>,
+    pub state_ranges: Cow<'static, [(usize, usize)]>,
     /// The transitions for each state.
-    pub transitions: Vec<(usize, usize)>,
+    pub transitions: Cow<'static, [(usize, usize)]>,
+    /// The counted states with the bounds of the repetitions they loop on.
+    pub(crate) counted_states: Vec<(usize, usize, Option<usize>)>,
+    /// The characters of the character classes. If set, e.g. for a DFA loaded from JSON, the DFA
+    /// doesn't use the match function.
+    pub(crate) char_classes: Option<CharClasses>,
+    /// The target states of the transitions on ASCII characters plus one, indexed by
+    /// `state * 128 + c`. Zero means no transition. It is computed by [Dfa::prepared].
+    pub(crate) ascii_transitions: Option<Arc<[u32]>>,
     /// The current matching state of the DFA.
     pub(crate) matching_state: MatchingState<usize>,
 }
 
 impl Dfa {
+    /// Returns the pattern that this DFA recognizes.
+    #[inline]
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    /// Returns the number of states of the DFA. The start state is state 0.
+    #[inline]
+    pub fn state_count(&self) -> usize {
+        self.state_ranges.len()
+    }
+
+    /// Returns the accepting states of the DFA.
+    #[inline]
+    pub fn accepting_states(&self) -> &[usize] {
+        &self.accepting_states
+    }
+
+    /// Returns true if the given state is an accepting state.
+    #[inline]
+    pub fn is_accepting(&self, state: usize) -> bool {
+        self.accepting_states.contains(&state)
+    }
+
+    /// Returns the counted states of the DFA with the minimum and the optional maximum number of
+    /// repetitions of the characters they loop on, see [crate::CountedStateData].
+    #[inline]
+    pub fn counted_states(&self) -> &[(usize, usize, Option<usize>)] {
+        &self.counted_states
+    }
+
+    /// Returns the bounds of the repetition the given state loops on if it is a counted state.
+    #[inline]
+    fn counter(&self, state: usize) -> Option<(usize, Option<usize>)> {
+        self.counted_states
+            .iter()
+            .find(|(counted_state, _, _)| *counted_state == state)
+            .map(|(_, min, max)| (*min, *max))
+    }
+
+    /// Returns true if the given state is an accepting state after the given number of
+    /// repetitions of the counted repetition it loops on.
+    #[inline]
+    pub(crate) fn is_accepting_after(&self, state: usize, repetitions: usize) -> bool {
+        self.is_accepting(state)
+            && self
+                .counter(state)
+                .is_none_or(|(min, _)| repetitions >= min)
+    }
+
+    /// Returns true if the given state has a transition the bounds of its counted repetition
+    /// allow after the given number of repetitions.
+    pub(crate) fn can_continue(&self, state: usize, repetitions: usize) -> bool {
+        match self.counter(state) {
+            Some((_, Some(max))) if repetitions >= max => self
+                .transitions_of(state)
+                .iter()
+                .any(|(_, target)| *target != state),
+            _ => !self.transitions_of(state).is_empty(),
+        }
+    }
+
+    /// Returns the transitions of the given state as pairs of character class and target state.
+    /// The character classes are the ones passed to the match function.
+    /// A state that doesn't exist has no transitions.
+    pub fn transitions_of(&self, state: usize) -> &[(usize, usize)] {
+        self.state_ranges
+            .get(state)
+            .and_then(|(start, end)| self.transitions.get(*start..*end))
+            .unwrap_or_default()
+    }
+
+    /// Returns the character class of the given state if it is an accepting state whose only
+    /// transition is a loop on this class, e.g. the state after the first character of `\s+` or
+    /// `[a-z][a-z0-9]*`. The DFA stays in such a state for a whole run of characters of the class.
+    pub(crate) fn self_loop_class(&self, state: usize) -> Option<usize> {
+        match self.transitions_of(state) {
+            [(char_class, target)]
+                if *target == state
+                    && self.is_accepting(state)
+                    && self.counter(state).is_none() =>
+            {
+                Some(*char_class)
+            }
+            _ => None,
+        }
+    }
+
+    /// Exports the DFA as JSON object with the pattern, the accepting states and the transitions
+    /// of each state as pairs of character class and target state, e.g.
+    /// `{"pattern":"a+","accepting_states":[1],"states":[[[0,1]],[[0,1]]]}`.
+    ///
+    /// DFAs with counted states have the additional member `counted_states` with the counted
+    /// states and the bounds of their repetitions, e.g. `[[1,1,100]]`, where `null` stands for
+    /// an unbounded maximum.
+    ///
+    /// The character classes are the indices passed to the match function. Use
+    /// [crate::Scanner::definition_to_json] to export the characters of the classes, too.
+    pub fn to_json(&self) -> String {
+        let mut out = String::new();
+        self.write_json(&mut out, |char_class| char_class);
+        out
+    }
+
+    /// Writes the DFA as JSON object. The character classes are mapped by `map_class`.
+    pub(crate) fn write_json(&self, out: &mut String, map_class: impl Fn(usize) -> usize) {
+        out.push_str("{\"pattern\":");
+        write_string(out, &self.pattern);
+        out.push_str(",\"accepting_states\":");
+        write_array(out, self.accepting_states.iter(), |out, state| {
+            out.push_str(&state.to_string())
+        });
+        out.push_str(",\"states\":");
+        write_array(out, 0..self.state_count(), |out, state| {
+            write_array(
+                out,
+                self.transitions_of(state),
+                |out, (char_class, target)| write_pair(out, (map_class(*char_class), *target)),
+            )
+        });
+        if !self.counted_states.is_empty() {
+            out.push_str(",\"counted_states\":");
+            write_array(out, self.counted_states.iter(), |out, (state, min, max)| {
+                out.push_str(&format!("[{},{},", state, min));
+                match max {
+                    Some(max) => out.push_str(&format!("{}]", max)),
+                    None => out.push_str("null]"),
+                }
+            });
+        }
+        out.push('}');
+    }
+
+    /// Returns the target state of the transition from the given state on the given character,
+    /// or None if the DFA has no such transition.
+    /// The bounds of the repetitions of counted states are not checked.
+    ///
+    /// # Panics
+    /// Panics if the state doesn't exist.
+    pub fn next_state(
+        &self,
+        state: usize,
+        c: char,
+        matches_char_class: fn(char, usize) -> bool,
+    ) -> Option<usize> {
+        self.find_transition(state, c, matches_char_class)
+    }
+
     /// Advances the DFA by one character.
     pub fn advance(&mut self, c_pos: usize, c: char, matches_char_class: fn(char, usize) -> bool) {
+        self.advance_encoded(c_pos, c, c.len_utf8(), matches_char_class);
+    }
+
+    /// Advances the DFA by one character that occupies `c_len` bytes in the input.
+    /// This is used for inputs that are not UTF-8 encoded, where the spans of the matches are
+    /// reported in offsets of the original input.
+    pub(crate) fn advance_encoded(
+        &mut self,
+        c_pos: usize,
+        c: char,
+        c_len: usize,
+        matches_char_class: fn(char, usize) -> bool,
+    ) {
+        let mut matching_state = std::mem::take(&mut self.matching_state);
+        self.advance_matching_state(&mut matching_state, c_pos, c, c_len, matches_char_class);
+        self.matching_state = matching_state;
+    }
+
+    /// Advances the given matching state by one character.
+    /// The DFA itself is not modified which allows to share it between several scanner modes.
+    pub(crate) fn advance_matching_state(
+        &self,
+        matching_state: &mut MatchingState<usize>,
+        c_pos: usize,
+        c: char,
+        c_len: usize,
+        matches_char_class: fn(char, usize) -> bool,
+    ) {
         // If we already have the longest match, we can stop
-        if self.matching_state.is_longest_match() {
+        if matching_state.is_longest_match() {
             return;
         }
         // Get the transitions for the current state
-        if let Some(next_state) = self.find_transition(c, matches_char_class) {
-            if self.accepting_states.contains(&next_state) {
-                self.matching_state.transition_to_accepting(c_pos, c);
+        if let Some((next_state, repetitions)) = self.next_counted_state(
+            (matching_state.current_state(), matching_state.repetitions()),
+            c,
+            matches_char_class,
+        ) {
+            if self.is_accepting_after(next_state, repetitions) {
+                matching_state.transition_to_accepting(c_pos, c_len);
             } else {
-                self.matching_state.transition_to_non_accepting(c_pos);
+                matching_state.transition_to_non_accepting(c_pos);
             }
-            self.matching_state.set_current_state(next_state);
+            matching_state.set_current_state(next_state);
+            matching_state.set_repetitions(repetitions);
         } else {
-            self.matching_state.no_transition();
+            matching_state.no_transition();
         }
     }
 
-    /// Finds the next state of the DFA.
-    fn find_transition(
+    /// Returns the target state of the transition from the given state on the given character
+    /// together with the number of repetitions of a counted state after the transition, or None
+    /// if the DFA has no such transition or the bounds of the counted repetition forbid it.
+    /// The number of repetitions is zero outside of counted states.
+    pub(crate) fn next_counted_state(
         &self,
+        (state, repetitions): (usize, usize),
         c: char,
         matches_char_class: fn(char, usize) -> bool,
-    ) -> Option<usize> {
-        let (start, end) = self.state_ranges[self.matching_state.current_state()];
-        for i in start..end {
-            let (char_class, target_state) = &self.transitions[i];
-            if matches_char_class(c, *char_class) {
-                return Some(*target_state);
+    ) -> Option<(usize, usize)> {
+        let next_state = self.find_transition(state, c, matches_char_class)?;
+        if self.counted_states.is_empty() {
+            return Some((next_state, 0));
+        }
+        if let Some((min, max)) = self.counter(state) {
+            if next_state == state {
+                // Without a maximum the number of repetitions only matters up to the minimum
+                return max
+                    .is_none_or(|max| repetitions < max)
+                    .then(|| (next_state, (repetitions + 1).min(max.unwrap_or(min))));
+            }
+            if repetitions < min {
+                return None;
             }
         }
-        None
+        Some((next_state, usize::from(self.counter(next_state).is_some())))
     }
 
-    #[inline]
-    pub(crate) fn reset(&mut self) {
-        self.matching_state = MatchingState::new();
+    /// Returns a copy of the DFA with a table of its transitions on ASCII characters, which
+    /// replaces the search through the transitions of a state by a single lookup. The match
+    /// function is only called to build the table. DFAs with too many states are copied as is.
+    pub(crate) fn prepared(&self, matches_char_class: fn(char, usize) -> bool) -> Self {
+        let mut dfa = self.clone();
+        if self.state_count() <= MAX_TABULATED_STATES {
+            dfa.ascii_transitions = (0..self.state_count())
+                .flat_map(|state| (0..128u8).map(move |b| (state, char::from(b))))
+                .map(
+                    |(state, c)| match self.find_transition(state, c, matches_char_class) {
+                        Some(target) => u32::try_from(target + 1).ok(),
+                        None => Some(0),
+                    },
+                )
+                .collect();
+        }
+        dfa
     }
 
-    /// Returns true if the search should continue on the next character if the automaton has ever
-    /// been in the matching state Start.
-    #[inline]
-    pub(crate) fn search_for_longer_match(&self) -> bool {
-        !self.matching_state.is_longest_match() && !self.matching_state.is_no_match()
+    /// Returns the characters the tokens of the DFA can start with, or None if the DFA doesn't
+    /// know the characters of its classes.
+    pub(crate) fn start_chars(&self) -> Option<Vec<(char, char)>> {
+        let char_classes = self.char_classes.as_ref()?;
+        Some(
+            self.transitions_of(0)
+                .iter()
+                .filter_map(|(char_class, _)| char_classes.get(*char_class))
+                .flatten()
+                .copied()
+                .collect(),
+        )
+    }
+
+    /// Returns a copy of the DFA that uses the equal table of character classes of the given
+    /// pool instead of its own. If the pool has no equal table, the table is added to it.
+    pub(crate) fn with_shared_char_classes(&self, pool: &mut Vec<CharClasses>) -> Dfa {
+        let mut dfa = self.clone();
+        if let Some(char_classes) = &self.char_classes {
+            match pool.iter().find(|shared| ***shared == **char_classes) {
+                Some(shared) => dfa.char_classes = Some(Arc::clone(shared)),
+                None => pool.push(Arc::clone(char_classes)),
+            }
+        }
+        dfa
+    }
+
+    /// Finds the next state of the DFA.
+    fn find_transition(
+        &self,
+        current_state: usize,
+        c: char,
+        matches_char_class: fn(char, usize) -> bool,
+    ) -> Option<usize> {
+        if let Some(ascii_transitions) = &self.ascii_transitions {
+            if c.is_ascii() {
+                return ascii_transitions
+                    .get(current_state.saturating_mul(128).saturating_add(c as usize))
+                    .and_then(|target| target.checked_sub(1))
+                    .map(|target| target as usize);
+            }
+        }
+        self.transitions_of(current_state)
+            .iter()
+            .find(|(char_class, _)| self.matches_char_class(c, *char_class, matches_char_class))
+            .map(|(_, target_state)| *target_state)
     }
 
-    /// Returns the current match.
+    /// Returns true if the given character belongs to the given character class. The match
+    /// function is only called if the DFA doesn't know the characters of its classes itself.
     #[inline]
-    pub(crate) fn current_match(&self) -> Option<Span> {
-        self.matching_state.last_match()
+    pub(crate) fn matches_char_class(
+        &self,
+        c: char,
+        char_class: usize,
+        matches_char_class: fn(char, usize) -> bool,
+    ) -> bool {
+        match &self.char_classes {
+            Some(char_classes) => char_classes
+                .get(char_class)
+                .is_some_and(|ranges| ranges_contain(ranges, c)),
+            None => matches_char_class(c, char_class),
+        }
     }
 }
 
 impl From<&DfaData> for Dfa {
     fn from(data: &DfaData) -> Self {
         Dfa {
-            pattern: data.0.to_owned(),
-            accepting_states: data.1.to_vec(),
-            state_ranges: data.2.to_vec(),
-            transitions: data.3.to_vec(),
+            pattern: Cow::Borrowed(data.0),
+            accepting_states: Cow::Borrowed(data.1),
+            state_ranges: Cow::Borrowed(data.2),
+            transitions: Cow::Borrowed(data.3),
+            counted_states: Vec::new(),
+            char_classes: None,
+            ascii_transitions: None,
             matching_state: MatchingState::new(),
         }
     }
 }
 
+impl From<OwnedDfaData> for Dfa {
+    fn from(data: OwnedDfaData) -> Self {
+        Dfa {
+            pattern: Cow::Owned(data.pattern),
+            accepting_states: Cow::Owned(data.accepting_states),
+            state_ranges: Cow::Owned(data.state_ranges),
+            transitions: Cow::Owned(data.transitions),
+            counted_states: data.counted_states,
+            char_classes: None,
+            ascii_transitions: None,
+            matching_state: MatchingState::new(),
+        }
+    }
+}
+
+impl From<&Dfa> for OwnedDfaData {
+    fn from(dfa: &Dfa) -> Self {
+        OwnedDfaData {
+            pattern: dfa.pattern.to_string(),
+            accepting_states: dfa.accepting_states.to_vec(),
+            state_ranges: dfa.state_ranges.to_vec(),
+            transitions: dfa.transitions.to_vec(),
+            counted_states: dfa.counted_states.clone(),
+        }
+    }
+}
+
 /// A DFA bundled with its associated token type number.
 /// This struct is used to allow different token type number for the same pattern, i.e. Dfas, in
 /// different scanner modes.
 ///
 /// You could imagine to have differnt patterns for, e.g. a Comment in different scanner modes, but
 /// you want to have the same token type number for all of them.
+///
+/// The DFA is shared with the scanner and all other scanner modes that use the same pattern. Only
+/// the matching state is owned by this struct.
+///
+/// The matching state is versioned by the generation of the search it belongs to. A matching state
+/// of an older generation is treated as reset, so starting a new search doesn't need to touch
+/// every DFA.
 #[derive(Debug, Clone)]
 pub(crate) struct DfaWithTokenType {
-    dfa: Dfa,
-    token_type: usize,
+    dfa: Arc<Dfa>,
+    token_type: TokenType,
+    generation: usize,
+    matching_state: MatchingState<usize>,
 }
 
 impl DfaWithTokenType {
     /// Creates a new DFA with its associated token type number.
-    pub(crate) fn new(dfa: Dfa, token_type: usize) -> Self {
-        Self { dfa, token_type }
+    pub(crate) fn new(dfa: Arc<Dfa>, token_type: TokenType) -> Self {
+        Self {
+            dfa,
+            token_type,
+            generation: 0,
+            matching_state: MatchingState::new(),
+        }
+    }
+
+    /// Creates a new DFA with its associated token type number that shares the DFA with this one.
+    pub(crate) fn with_token_type(&self, token_type: TokenType) -> Self {
+        Self::new(Arc::clone(&self.dfa), token_type)
     }
 
-    /// Returns the current match.
+    /// Returns true if the given character belongs to the given character class of the DFA.
     #[inline]
-    pub(crate) fn current_match(&self) -> Option<Match> {
+    pub(crate) fn matches_char_class(
+        &self,
+        c: char,
+        char_class: usize,
+        matches_char_class: fn(char, usize) -> bool,
+    ) -> bool {
         self.dfa
-            .current_match()
-            .map(|span| Match::new(self.token_type, span))
+            .matches_char_class(c, char_class, matches_char_class)
     }
 
-    /// Resets the DFA.
+    /// Returns the DFA that is shared with the scanner.
     #[inline]
-    pub(crate) fn reset(&mut self) {
-        self.dfa.reset();
+    pub(crate) fn dfa(&self) -> &Arc<Dfa> {
+        &self.dfa
     }
 
-    /// Advances the DFA by one character.
+    /// Replaces the DFA, e.g. by a prepared copy of it.
+    pub(crate) fn set_dfa(&mut self, dfa: Arc<Dfa>) {
+        self.dfa = dfa;
+    }
+
+    /// Returns the token type associated with the DFA.
+    #[inline]
+    pub(crate) fn token_type(&self) -> TokenType {
+        self.token_type
+    }
+
+    /// Returns the current match of the search with the given generation.
+    #[inline]
+    pub(crate) fn current_match(&self, generation: usize) -> Option<Match> {
+        if self.generation != generation {
+            return None;
+        }
+        self.matching_state
+            .last_match()
+            .map(|span| Match::new(self.token_type, span))
+    }
+
+    /// Advances the DFA by one character that occupies `c_len` bytes in the input.
+    /// If the matching state belongs to an older search, it is reset first.
     #[inline]
     pub(crate) fn advance(
         &mut self,
+        generation: usize,
         c_pos: usize,
         c: char,
+        c_len: usize,
         matches_char_class: fn(char, usize) -> bool,
     ) {
-        self.dfa.advance(c_pos, c, matches_char_class);
+        if self.generation != generation {
+            self.generation = generation;
+            self.matching_state = MatchingState::new();
+        }
+        self.dfa.advance_matching_state(
+            &mut self.matching_state,
+            c_pos,
+            c,
+            c_len,
+            matches_char_class,
+        );
+    }
+
+    /// Returns the character class the DFA loops on in its current state, if the current state of
+    /// the search with the given generation is an accepting state with a single self-loop.
+    #[inline]
+    pub(crate) fn self_loop_class(&self, generation: usize) -> Option<usize> {
+        if self.generation != generation || self.matching_state.is_longest_match() {
+            return None;
+        }
+        self.dfa
+            .self_loop_class(self.matching_state.current_state())
+    }
+
+    /// Extends the current match to the given end position after a run of characters of the
+    /// self-loop class was consumed.
+    #[inline]
+    pub(crate) fn extend_match_to(&mut self, end: usize) {
+        self.matching_state.extend_accepting_to(end);
     }
 
     /// Returns true if the search should continue on the next character if the automaton has ever
@@ -134,6 +522,45 @@ impl DfaWithTokenType {
     /// match.
     #[inline]
     pub(crate) fn search_for_longer_match(&self) -> bool {
-        self.dfa.search_for_longer_match()
+        !self.matching_state.is_longest_match() && !self.matching_state.is_no_match()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The DFA of the pattern `ab*`
+    const DFA: DfaData = ("ab*", &[1], &[(0, 1), (1, 2)], &[(0, 1), (1, 1)]);
+
+    fn matches_char_class(c: char, char_class: usize) -> bool {
+        match char_class {
+            0 => c == 'a',
+            1 => c == 'b',
+            _ => false,
+        }
+    }
+
+    #[test]
+    fn test_introspection() {
+        let dfa = Dfa::from(&DFA);
+        assert_eq!(dfa.pattern(), "ab*");
+        assert_eq!(dfa.state_count(), 2);
+        assert_eq!(dfa.accepting_states(), &[1]);
+        assert!(!dfa.is_accepting(0));
+        assert!(dfa.is_accepting(1));
+        assert_eq!(dfa.transitions_of(0), &[(0, 1)]);
+        assert!(dfa.transitions_of(7).is_empty());
+        assert_eq!(dfa.next_state(0, 'a', matches_char_class), Some(1));
+        assert_eq!(dfa.next_state(0, 'b', matches_char_class), None);
+        assert_eq!(dfa.next_state(1, 'b', matches_char_class), Some(1));
+    }
+
+    #[test]
+    fn test_to_json() {
+        assert_eq!(
+            Dfa::from(&DFA).to_json(),
+            r#"{"pattern":"ab*","accepting_states":[1],"states":[[[0,1]],[[1,1]]]}"#
+        );
     }
 }
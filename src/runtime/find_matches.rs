@@ -1,10 +1,16 @@
 use std::char;
 
-use crate::common::Match;
+#[cfg(feature = "peek")]
+use crate::ScannerModeId;
+use crate::{common::Match, TokenType};
 
-use super::Scanner;
+use super::{
+    AttachTrivia, ColumnConfig, FindOutcome, LineGroups, LineIndex, Positions, Scanner, TokenIter,
+    TriviaAttachment, Utf8Cursor,
+};
 
 /// The result of a peek operation.
+#[cfg(feature = "peek")]
 #[derive(Debug, PartialEq)]
 pub enum PeekResult {
     /// The peek operation found a n matches.
@@ -12,13 +18,87 @@ pub enum PeekResult {
     /// The peek operation found less than n matches because the end of the haystack was reached.
     MatchesReachedEnd(Vec<Match>),
     /// The peek operation found less than n matches because the last token type would have
-    /// triggered a mode switch. The matches are returned  along with the index of the new mode that
+    /// triggered a mode switch. The matches are returned  along with the id of the new mode that
     /// would be switched to on the last match.
-    MatchesReachedModeSwitch((Vec<Match>, usize)),
+    MatchesReachedModeSwitch((Vec<Match>, ScannerModeId)),
     /// The peek operation found no matches.
     NotFound,
 }
 
+#[cfg(feature = "peek")]
+impl PeekResult {
+    /// Returns the matches found by the peek operation.
+    /// If no matches were found, an empty slice is returned.
+    pub fn matches(&self) -> &[Match] {
+        match self {
+            PeekResult::Matches(matches)
+            | PeekResult::MatchesReachedEnd(matches)
+            | PeekResult::MatchesReachedModeSwitch((matches, _)) => matches,
+            PeekResult::NotFound => &[],
+        }
+    }
+
+    /// Returns the number of matches found by the peek operation.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.matches().len()
+    }
+
+    /// Returns true if the peek operation found no matches.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.matches().is_empty()
+    }
+
+    /// Converts the peek result into a vector of the matches found.
+    pub fn into_vec(self) -> Vec<Match> {
+        match self {
+            PeekResult::Matches(matches)
+            | PeekResult::MatchesReachedEnd(matches)
+            | PeekResult::MatchesReachedModeSwitch((matches, _)) => matches,
+            PeekResult::NotFound => Vec::new(),
+        }
+    }
+}
+
+#[cfg(feature = "peek")]
+/// The result of a peek operation into a buffer provided by the caller.
+/// The variants mirror the ones of [PeekResult] but only carry the number of matches written into
+/// the buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PeekIntoResult {
+    /// The peek operation filled the whole buffer.
+    Matches(usize),
+    /// The peek operation found less matches than the buffer can hold because the end of the
+    /// haystack was reached.
+    MatchesReachedEnd(usize),
+    /// The peek operation found less matches than the buffer can hold because the last token type
+    /// would have triggered a mode switch to the given mode.
+    MatchesReachedModeSwitch(usize, ScannerModeId),
+    /// The peek operation found no matches.
+    NotFound,
+}
+
+#[cfg(feature = "peek")]
+impl PeekIntoResult {
+    /// Returns the number of matches written into the buffer.
+    #[inline]
+    pub fn len(&self) -> usize {
+        match self {
+            PeekIntoResult::Matches(len)
+            | PeekIntoResult::MatchesReachedEnd(len)
+            | PeekIntoResult::MatchesReachedModeSwitch(len, _) => *len,
+            PeekIntoResult::NotFound => 0,
+        }
+    }
+
+    /// Returns true if the peek operation found no matches.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
 /// An iterator over all non-overlapping matches.
 ///
 /// The iterator yields a [`Match`] value until no more matches could be found.
@@ -29,8 +109,15 @@ pub enum PeekResult {
 #[derive(Debug)]
 pub struct FindMatches<'h> {
     scanner: Scanner,
-    char_indices: std::str::CharIndices<'h>,
+    input: &'h str,
+    cursor: Utf8Cursor<'h>,
     matches_char_class: fn(char, usize) -> bool,
+    // The matches found by the last peek operation. They are used by `consume_peeked` to commit
+    // peeked matches without scanning the same region again.
+    #[cfg(feature = "peek")]
+    peeked: Vec<Match>,
+    // The token types that are skipped by `peek_n_significant`.
+    trivia: Vec<TokenType>,
 }
 
 impl<'h> FindMatches<'h> {
@@ -42,8 +129,88 @@ impl<'h> FindMatches<'h> {
     ) -> Self {
         FindMatches {
             scanner,
-            char_indices: input.char_indices(),
+            input,
+            cursor: Utf8Cursor::new(input),
             matches_char_class,
+            #[cfg(feature = "peek")]
+            peeked: Vec::new(),
+            trivia: Vec::new(),
+        }
+    }
+
+    /// Moves the start of the search to the given byte offset, e.g. to resume scanning at
+    /// [FindMatches::offset] after more input was appended.
+    ///
+    /// An offset that is not a character boundary of the input, e.g. beyond its end, moves the
+    /// start to the end of the input, so the iterator yields no matches.
+    pub fn start_at(mut self, position: usize) -> Self {
+        if self.input.is_char_boundary(position) {
+            self.cursor.set_position(position);
+        } else {
+            self.cursor.set_position(self.input.len());
+        }
+        self
+    }
+
+    /// Returns the byte offset in the input where the next search starts.
+    #[inline]
+    pub fn offset(&self) -> usize {
+        self.cursor.position()
+    }
+
+    /// Returns the scanner used by the iterator.
+    #[inline]
+    pub fn scanner(&self) -> &Scanner {
+        &self.scanner
+    }
+
+    /// Returns the scanner used by the iterator, e.g. to reuse its scratch space for the next
+    /// search.
+    pub(crate) fn into_scanner(self) -> Scanner {
+        self.scanner
+    }
+
+    /// Returns the match function of the generated scanner.
+    #[cfg(test)]
+    pub(crate) fn matches_char_class(&self) -> fn(char, usize) -> bool {
+        self.matches_char_class
+    }
+
+    /// Returns an iterator adapter that attaches the matches of the trivia token types configured
+    /// with [FindMatches::set_trivia] to the significant matches. See [AttachTrivia].
+    pub fn attach_trivia(self, attachment: TriviaAttachment) -> AttachTrivia<Self> {
+        let trivia = self.trivia.clone();
+        AttachTrivia::new(self, trivia, attachment)
+    }
+
+    /// Returns an iterator over the remaining matches as [Token](super::Token)s along with their
+    /// text.
+    pub fn tokens(self) -> TokenIter<'h> {
+        let input = self.input;
+        TokenIter::new(input, self)
+    }
+
+    /// Returns an iterator adapter that groups the remaining matches by the source line they
+    /// start on. See [LineGroups].
+    pub fn by_lines(self) -> LineGroups<'h, Self> {
+        let input = self.input;
+        LineGroups::new(input, self)
+    }
+
+    /// Returns an iterator adapter that yields the remaining matches along with the line and
+    /// column positions of their start and end. The columns are counted as configured by the
+    /// given [ColumnConfig]. See [Positions].
+    ///
+    /// If the scanner has a newline token type, see [Scanner::newline_token_type], the line breaks
+    /// `\r\n`, `\r` and `\n` that the newline terminal matches all end a line.
+    pub fn with_positions(self, config: ColumnConfig) -> Positions<'h, Self> {
+        let input = self.input;
+        if self.scanner.newline_token_type().is_some() {
+            let line_index =
+                LineIndex::with_universal_line_breaks(input).with_column_config(config);
+            Positions::with_line_index(line_index, self)
+        } else {
+            Positions::new(input, self, config)
         }
     }
 
@@ -52,26 +219,70 @@ impl<'h> FindMatches<'h> {
     /// If no match is found, `None` is returned.
     ///
     /// The function calls the `find_from` method of the scanner to find the next match.
-    /// If a match is found, the function advances the char_indices iterator to the end of the match.
-    /// If no match is found, the function repeatedly advances the char_indices iterator by one
-    /// and tries again until a match is found or the iterator is exhausted.
+    /// If a match is found, the function moves the cursor to the end of the match.
+    /// If no match is found, the function advances the cursor to the next
+    /// character that can start a token in the current scanner mode and tries again until a match
+    /// is found or the iterator is exhausted.
+    ///
+    /// If the step budget of the scanner is exhausted or if the scanner needs more input, `None`
+    /// is returned as well. Use [FindMatches::try_next_match] to distinguish these cases.
+    ///
+    /// Matches of the token types the scanner skips are not returned, see
+    /// [Scanner::set_skipped_token_types].
     #[inline]
     pub fn next_match(&mut self) -> Option<Match> {
-        let mut result;
-        loop {
-            result = self
-                .scanner
-                .find_from(self.char_indices.clone(), self.matches_char_class);
-            if let Some(matched) = result {
-                self.advance_beyond_match(matched);
-                break;
-            } else if self.char_indices.next().is_none() {
-                break;
-            }
+        self.try_next_match().matched()
+    }
+
+    /// Returns the next match in the haystack as [FindOutcome].
+    ///
+    /// In contrast to [FindMatches::next_match], an exhausted step budget of the scanner is
+    /// reported as [FindOutcome::BudgetExhausted] and the end of partial input as
+    /// [FindOutcome::NeedMoreInput]. With [MatchKind::TokenAnchored] and `error_on_gap` set, input
+    /// no token matches is reported as [FindOutcome::Gap]. The position of the iterator is not
+    /// changed in these cases.
+    pub fn try_next_match(&mut self) -> FindOutcome {
+        #[cfg(feature = "peek")]
+        self.peeked.clear();
+        self.scanner
+            .find_next(&mut self.cursor, self.matches_char_class)
+    }
+
+    /// Sets the token types that are considered trivia, e.g. whitespace and comments.
+    /// They are skipped by [FindMatches::peek_n_significant].
+    pub fn set_trivia<I>(&mut self, trivia: I)
+    where
+        I: IntoIterator<Item = TokenType>,
+    {
+        self.trivia = trivia.into_iter().collect();
+    }
+
+    /// Returns the token types that are considered trivia.
+    #[inline]
+    pub fn trivia(&self) -> &[TokenType] {
+        &self.trivia
+    }
+
+    // Move the cursor to the end of the match.
+    #[cfg(feature = "peek")]
+    #[inline]
+    fn advance_beyond_match(&mut self, matched: Match) {
+        Self::move_cursor_beyond_match(&mut self.cursor, matched);
+    }
+
+    /// Moves the given cursor to the end of the given match.
+    #[cfg(feature = "peek")]
+    #[inline]
+    fn move_cursor_beyond_match(cursor: &mut Utf8Cursor, matched: Match) {
+        if !matched.is_empty() {
+            cursor.set_position(matched.span().end);
         }
-        result
     }
+}
 
+// The peek operations are only available with the feature `peek`, which is enabled by default.
+#[cfg(feature = "peek")]
+impl FindMatches<'_> {
     /// Peeks n matches ahead without consuming the matches.
     /// The function returns [PeekResult].
     ///
@@ -80,27 +291,55 @@ impl<'h> FindMatches<'h> {
     /// change the state of the scanner as well as to aviod a mix of tokens from different modes
     /// being returned.
     pub fn peek_n(&mut self, n: usize) -> PeekResult {
-        let mut char_indices = self.char_indices.clone();
-        let mut matches = Vec::with_capacity(n);
-        let mut mode_switch = false;
-        let mut new_mode = 0;
-        for _ in 0..n {
-            let result = self
-                .scanner
-                .peek_from(char_indices.clone(), self.matches_char_class);
-            if let Some(matched) = result {
-                matches.push(matched);
-                Self::advance_char_indices_beyond_match(&mut char_indices, matched);
-                if let Some(mode) = self.scanner.has_transition(matched.token_type()) {
-                    mode_switch = true;
-                    new_mode = mode;
-                    break;
-                }
-            } else {
-                break;
-            }
+        self.peek_n_skipping(n, |_| false)
+    }
+
+    /// Peeks n significant matches ahead without consuming the matches.
+    /// The function returns [PeekResult].
+    ///
+    /// In contrast to [FindMatches::peek_n], matches of the trivia token types configured with
+    /// [FindMatches::set_trivia] are skipped and not counted, i.e. the result contains up to n
+    /// matches that are not trivia. A trivia match that would trigger a mode switch is returned
+    /// nevertheless, because the peek operation stops there.
+    pub fn peek_n_significant(&mut self, n: usize) -> PeekResult {
+        let trivia = std::mem::take(&mut self.trivia);
+        let result = self.peek_n_skipping(n, |token_type| trivia.contains(&token_type));
+        self.trivia = trivia;
+        result
+    }
+
+    /// Peeks ahead without consuming the matches and without allocating, for parsers that peek
+    /// on every decision.
+    /// The buffer is filled with as many matches as it can hold. The function returns
+    /// [PeekIntoResult] with the number of matches written into the buffer.
+    ///
+    /// Like [FindMatches::peek_n], the peek operation stops at the end of the haystack or when a
+    /// mode switch is triggered by the last match. The matches can be consumed with
+    /// [FindMatches::consume_peeked].
+    pub fn peek_into(&mut self, buffer: &mut [Match]) -> PeekIntoResult {
+        let mode_switch = self.peek_skipping(buffer.len(), |_| false);
+        let len = self.peeked.len();
+        for (slot, matched) in buffer.iter_mut().zip(&self.peeked) {
+            *slot = *matched;
         }
-        if mode_switch {
+        if let Some(new_mode) = mode_switch {
+            PeekIntoResult::MatchesReachedModeSwitch(len, new_mode)
+        } else if len == buffer.len() {
+            PeekIntoResult::Matches(len)
+        } else if len == 0 {
+            PeekIntoResult::NotFound
+        } else {
+            PeekIntoResult::MatchesReachedEnd(len)
+        }
+    }
+
+    fn peek_n_skipping<F>(&mut self, n: usize, skip: F) -> PeekResult
+    where
+        F: Fn(TokenType) -> bool,
+    {
+        let mode_switch = self.peek_skipping(n, skip);
+        let matches = self.peeked.clone();
+        if let Some(new_mode) = mode_switch {
             PeekResult::MatchesReachedModeSwitch((matches, new_mode))
         } else if matches.len() == n {
             PeekResult::Matches(matches)
@@ -111,33 +350,54 @@ impl<'h> FindMatches<'h> {
         }
     }
 
-    // Advance the char_indices iterator to the end of the match.
-    #[inline]
-    fn advance_beyond_match(&mut self, matched: Match) {
-        if matched.is_empty() {
-            return;
-        }
-        let end = matched.span().end;
-        for (i, c) in self.char_indices.by_ref() {
-            if i + c.len_utf8() >= end {
-                // Stop at the end of the match.
-                break;
+    /// Peeks up to n matches ahead that are not skipped and stores them in `self.peeked`, whose
+    /// allocation is reused between the calls.
+    /// Returns the new scanner mode if the last match would trigger a mode switch.
+    fn peek_skipping<F>(&mut self, n: usize, skip: F) -> Option<ScannerModeId>
+    where
+        F: Fn(TokenType) -> bool,
+    {
+        let mut cursor = self.cursor;
+        let mut nesting_depth = self.scanner.nesting_depth;
+        self.peeked.clear();
+        while self.peeked.len() < n {
+            let matched = self.scanner.peek_from(cursor, self.matches_char_class)?;
+            Self::move_cursor_beyond_match(&mut cursor, matched);
+            if let Some(mode) = self
+                .scanner
+                .mode_switch(matched.token_type(), &mut nesting_depth)
+            {
+                self.peeked.push(matched);
+                return Some(mode);
+            }
+            if !skip(matched.token_type()) && !self.scanner.is_skipped(matched.token_type()) {
+                self.peeked.push(matched);
             }
         }
+        None
     }
 
-    /// Advances the given char_indices iterator to the end of the given match.
-    fn advance_char_indices_beyond_match(char_indices: &mut std::str::CharIndices, matched: Match) {
-        if matched.is_empty() {
-            return;
+    /// Consumes the first `n` matches found by the last call to [FindMatches::peek_n] or
+    /// [FindMatches::peek_n_significant].
+    /// The function returns the number of matches actually consumed, which is less than `n` if the
+    /// last peek operation found less matches. Trivia skipped by the peek operation in between
+    /// the consumed matches is consumed as well.
+    ///
+    /// The consumed matches are not scanned again. If the last consumed match triggers a mode
+    /// switch, the mode switch is executed as if the match had been found by
+    /// [FindMatches::next_match].
+    /// The remaining peeked matches are kept and can be consumed by a subsequent call.
+    pub fn consume_peeked(&mut self, n: usize) -> usize {
+        let n = n.min(self.peeked.len());
+        if n == 0 {
+            return 0;
         }
-        let end = matched.span().end;
-        for (i, c) in char_indices {
-            if i + c.len_utf8() >= end {
-                // Stop at the end of the match.
-                break;
-            }
+        let consumed = self.peeked.drain(..n).collect::<Vec<_>>();
+        for matched in consumed {
+            self.advance_beyond_match(matched);
+            self.scanner.execute_possible_mode_switch(Some(matched));
         }
+        n
     }
 }
 
@@ -153,9 +413,12 @@ impl Iterator for FindMatches<'_> {
 mod tests {
 
     use crate::{
-        common::ScannerModeData, generate_code, runtime::generated::scanner_with_modes, try_format,
-        Match, PeekResult,
+        common::ScannerModeData, generate_code, generate_munch_tests,
+        runtime::generated::scanner_with_modes, try_format, DfaData, FindOutcome, Match, MatchKind,
+        ScannerBuilder, ScannerModeId, TokenType,
     };
+    #[cfg(feature = "peek")]
+    use crate::{PeekIntoResult, PeekResult};
     use std::fs;
 
     const TERMINALS: &[&str] = &[
@@ -177,38 +440,42 @@ mod tests {
             "INITIAL",
             // Tokens that are valid in this mode
             &[
-                (0, 0), // Newline
-                (1, 1), // Whitespace
-                (2, 2), // Line comment
-                (3, 3), // Block comment
-                (4, 4), // Identifier
-                (8, 8), // String delimiter
-                (9, 9), // Error
+                (0, TokenType::new(0)), // Newline
+                (1, TokenType::new(1)), // Whitespace
+                (2, TokenType::new(2)), // Line comment
+                (3, TokenType::new(3)), // Block comment
+                (4, TokenType::new(4)), // Identifier
+                (8, TokenType::new(8)), // String delimiter
+                (9, TokenType::new(9)), // Error
             ],
             // Transitions to other modes
             &[
-                (8, 1), // Token "String delimiter" -> Mode "STRING"
+                (TokenType::new(8), ScannerModeId::new(1)), // Token "String delimiter" -> Mode "STRING"
             ],
+            // No error token type, the error token (9) is part of the mode
+            None,
         ),
         (
             // Mode name
             "STRING",
             // Tokens that are valid in this mode
             &[
-                (0, 0), // Newline
-                (1, 1), // Whitespace
-                (2, 2), // Line comment
-                (3, 3), // Block comment
-                (5, 5), // Escape sequence
-                (6, 6), // Line continuation
-                (7, 7), // String content
-                (8, 8), // String delimiter
-                (9, 9), // Error
+                (0, TokenType::new(0)), // Newline
+                (1, TokenType::new(1)), // Whitespace
+                (2, TokenType::new(2)), // Line comment
+                (3, TokenType::new(3)), // Block comment
+                (5, TokenType::new(5)), // Escape sequence
+                (6, TokenType::new(6)), // Line continuation
+                (7, TokenType::new(7)), // String content
+                (8, TokenType::new(8)), // String delimiter
+                (9, TokenType::new(9)), // Error
             ],
             // Transitions to other modes
             &[
-                (8, 0), // Token "String delimiter" -> Mode "INITIAL"
+                (TokenType::new(8), ScannerModeId::new(0)), // Token "String delimiter" -> Mode "INITIAL"
             ],
+            // No error token type, the error token (9) is part of the mode
+            None,
         ),
     ];
 
@@ -238,6 +505,24 @@ Id2
     }
 
     #[test]
+    fn generate_munch_tests_for_scanner_with_modes() {
+        // The generated tests are compiled and run along with the tests of this crate.
+        let file_name = "src/runtime/generated/scanner_with_modes_munch.rs";
+        {
+            let mut out_file = fs::File::create(file_name).expect("Failed to create file");
+            generate_munch_tests(
+                TERMINALS,
+                MODES,
+                "crate::runtime::generated::scanner_with_modes",
+                &mut out_file,
+            )
+            .expect("Failed to generate the maximal munch tests");
+        }
+        try_format(file_name).expect("Failed to format the generated code");
+    }
+
+    #[test]
+    #[cfg(feature = "peek")]
     fn test_peek_n() {
         let scanner = scanner_with_modes::create_scanner();
         let mut find_iter = scanner_with_modes::create_find_iter(&scanner, INPUT);
@@ -245,8 +530,8 @@ Id2
         assert_eq!(
             peeked,
             PeekResult::Matches(vec![
-                Match::new(0, (0usize..1).into()),
-                Match::new(4, (1usize..4).into()),
+                Match::new(0.into(), (0usize..1).into()),
+                Match::new(4.into(), (1usize..4).into()),
             ])
         );
         let peeked = find_iter.peek_n(4);
@@ -254,12 +539,12 @@ Id2
             peeked,
             PeekResult::MatchesReachedModeSwitch((
                 vec![
-                    Match::new(0, (0usize..1).into()),
-                    Match::new(4, (1usize..4).into()),
-                    Match::new(0, (4usize..5).into()),
-                    Match::new(8, (5usize..6).into()),
+                    Match::new(0.into(), (0usize..1).into()),
+                    Match::new(4.into(), (1usize..4).into()),
+                    Match::new(0.into(), (4usize..5).into()),
+                    Match::new(8.into(), (5usize..6).into()),
                 ],
-                1
+                ScannerModeId::new(1)
             ))
         );
         let peeked = find_iter.peek_n(5);
@@ -267,12 +552,12 @@ Id2
             peeked,
             PeekResult::MatchesReachedModeSwitch((
                 vec![
-                    Match::new(0, (0usize..1).into()),
-                    Match::new(4, (1usize..4).into()),
-                    Match::new(0, (4usize..5).into()),
-                    Match::new(8, (5usize..6).into()),
+                    Match::new(0.into(), (0usize..1).into()),
+                    Match::new(4.into(), (1usize..4).into()),
+                    Match::new(0.into(), (4usize..5).into()),
+                    Match::new(8.into(), (5usize..6).into()),
                 ],
-                1
+                ScannerModeId::new(1)
             ))
         );
         let _ = find_iter.by_ref().take(7).collect::<Vec<_>>();
@@ -280,30 +565,143 @@ Id2
         assert_eq!(
             peeked,
             PeekResult::MatchesReachedEnd(vec![
-                Match::new(4, (17usize..20).into()),
-                Match::new(0, (20usize..21).into()),
+                Match::new(4.into(), (17usize..20).into()),
+                Match::new(0.into(), (20usize..21).into()),
             ])
         );
     }
 
     #[test]
-    fn test_find_iter() {
+    fn test_shared_scanner() {
+        let scanner = scanner_with_modes::scanner();
+        assert!(std::ptr::eq(scanner, scanner_with_modes::scanner()));
+        let find_iter = scanner_with_modes::create_find_iter(scanner, INPUT);
+        let expected =
+            scanner_with_modes::create_find_iter(&scanner_with_modes::create_scanner(), INPUT);
+        assert!(find_iter.eq(expected));
+    }
+
+    #[test]
+    #[cfg(feature = "peek")]
+    fn test_peek_into() {
+        let scanner = scanner_with_modes::create_scanner();
+        let mut find_iter = scanner_with_modes::create_find_iter(&scanner, INPUT);
+        let mut buffer = [Match::new(0.into(), (0usize..0).into()); 5];
+        assert_eq!(
+            find_iter.peek_into(&mut buffer[..2]),
+            PeekIntoResult::Matches(2)
+        );
+        assert_eq!(
+            find_iter.peek_into(&mut buffer),
+            PeekIntoResult::MatchesReachedModeSwitch(4, ScannerModeId::new(1))
+        );
+        assert_eq!(
+            buffer[..4],
+            [
+                Match::new(0.into(), (0usize..1).into()),
+                Match::new(4.into(), (1usize..4).into()),
+                Match::new(0.into(), (4usize..5).into()),
+                Match::new(8.into(), (5usize..6).into()),
+            ]
+        );
+        assert_eq!(find_iter.consume_peeked(4), 4);
+        assert_eq!(find_iter.scanner().current_mode(), ScannerModeId::new(1));
+    }
+
+    #[test]
+    #[cfg(feature = "peek")]
+    fn test_consume_peeked() {
+        let scanner = scanner_with_modes::create_scanner();
+        let mut find_iter = scanner_with_modes::create_find_iter(&scanner, INPUT);
+        let peeked = find_iter.peek_n(4);
+        assert_eq!(peeked.len(), 4);
+        assert_eq!(
+            peeked.matches()[1],
+            Match::new(4.into(), (1usize..4).into())
+        );
+        assert_eq!(find_iter.consume_peeked(2), 2);
+        assert_eq!(find_iter.consume_peeked(5), 2);
+        assert_eq!(find_iter.consume_peeked(1), 0);
+        // The consumed string delimiter switched the scanner to mode "STRING".
+        assert_eq!(
+            find_iter.next(),
+            Some(Match::new(7.into(), (6usize..15).into()))
+        );
+        assert_eq!(find_iter.peek_n(0).into_vec(), Vec::new());
+    }
+
+    #[test]
+    #[cfg(feature = "peek")]
+    fn test_peek_n_significant() {
+        let scanner = scanner_with_modes::create_scanner();
+        let mut find_iter = scanner_with_modes::create_find_iter(&scanner, INPUT);
+        // Newlines are trivia
+        find_iter.set_trivia([TokenType::new(0)]);
+        assert_eq!(find_iter.trivia(), &[TokenType::new(0)]);
+        let peeked = find_iter.peek_n_significant(2);
+        assert_eq!(
+            peeked,
+            PeekResult::MatchesReachedModeSwitch((
+                vec![
+                    Match::new(4.into(), (1usize..4).into()),
+                    Match::new(8.into(), (5usize..6).into()),
+                ],
+                ScannerModeId::new(1)
+            ))
+        );
+        let peeked = find_iter.peek_n_significant(1);
+        assert_eq!(
+            peeked,
+            PeekResult::Matches(vec![Match::new(4.into(), (1usize..4).into())])
+        );
+    }
+
+    #[test]
+    fn test_skipped_token_types() {
         let mut scanner = scanner_with_modes::create_scanner();
-        let find_iter = scanner_with_modes::create_find_iter(&mut scanner, INPUT);
+        // Newlines are skipped, the string delimiters switch the mode nevertheless
+        scanner.set_skipped_token_types([TokenType::new(0), TokenType::new(8)]);
+        assert_eq!(
+            scanner.skipped_token_types(),
+            &[TokenType::new(0), TokenType::new(8)]
+        );
+        let matches = scanner_with_modes::create_find_iter(&scanner, INPUT).collect::<Vec<_>>();
+        assert_eq!(
+            matches,
+            vec![
+                Match::new(4.into(), (1usize..4).into()),
+                Match::new(7.into(), (6usize..15).into()),
+                Match::new(4.into(), (17usize..20).into()),
+            ]
+        );
+        #[cfg(feature = "peek")]
+        {
+            let mut find_iter = scanner_with_modes::create_find_iter(&scanner, INPUT);
+            assert_eq!(
+                find_iter.peek_n(1),
+                PeekResult::Matches(vec![Match::new(4.into(), (1usize..4).into())])
+            );
+        }
+    }
+
+    #[test]
+    fn test_find_iter() {
+        let scanner = scanner_with_modes::create_scanner();
+        let find_iter = scanner_with_modes::create_find_iter(&scanner, INPUT);
         let matches: Vec<Match> = find_iter.collect();
         assert_eq!(matches.len(), 9);
         assert_eq!(
             matches,
             vec![
-                Match::new(0, (0usize..1).into()),
-                Match::new(4, (1usize..4).into()),
-                Match::new(0, (4usize..5).into()),
-                Match::new(8, (5usize..6).into()),
-                Match::new(7, (6usize..15).into()),
-                Match::new(8, (15usize..16).into()),
-                Match::new(0, (16usize..17).into()),
-                Match::new(4, (17usize..20).into()),
-                Match::new(0, (20usize..21).into()),
+                Match::new(0.into(), (0usize..1).into()),
+                Match::new(4.into(), (1usize..4).into()),
+                Match::new(0.into(), (4usize..5).into()),
+                Match::new(8.into(), (5usize..6).into()),
+                Match::new(7.into(), (6usize..15).into()),
+                Match::new(8.into(), (15usize..16).into()),
+                Match::new(0.into(), (16usize..17).into()),
+                Match::new(4.into(), (17usize..20).into()),
+                Match::new(0.into(), (20usize..21).into()),
             ]
         );
         assert_eq!(
@@ -327,4 +725,157 @@ Id2
             ]
         );
     }
+
+    #[test]
+    fn test_step_budget() {
+        let mut scanner = scanner_with_modes::create_scanner();
+        scanner.set_step_budget(Some(3));
+        let mut find_iter = scanner_with_modes::create_find_iter(&scanner, "ab abcdef");
+        assert_eq!(
+            find_iter.try_next_match(),
+            FindOutcome::Found(Match::new(4.into(), (0usize..2).into()))
+        );
+        assert_eq!(
+            find_iter.try_next_match(),
+            FindOutcome::Found(Match::new(1.into(), (2usize..3).into()))
+        );
+        // The identifier is longer than the budget allows
+        assert_eq!(find_iter.try_next_match(), FindOutcome::BudgetExhausted);
+        // The position is not changed
+        assert_eq!(find_iter.try_next_match(), FindOutcome::BudgetExhausted);
+        assert_eq!(find_iter.next(), None);
+    }
+
+    #[test]
+    fn test_partial_input() {
+        let mut scanner = scanner_with_modes::create_scanner();
+        scanner.set_partial_input(true);
+        let mut find_iter = scanner_with_modes::create_find_iter(&scanner, "ab ab");
+        assert_eq!(
+            find_iter.try_next_match(),
+            FindOutcome::Found(Match::new(4.into(), (0usize..2).into()))
+        );
+        assert_eq!(
+            find_iter.try_next_match(),
+            FindOutcome::Found(Match::new(1.into(), (2usize..3).into()))
+        );
+        // The identifier at the end could be continued by the next input
+        assert_eq!(find_iter.try_next_match(), FindOutcome::NeedMoreInput);
+        assert_eq!(find_iter.offset(), 3);
+        assert_eq!(find_iter.next(), None);
+
+        // Resume the search at the same offset after more input was received
+        let offset = find_iter.offset();
+        let mut scanner = find_iter.scanner().clone();
+        scanner.set_partial_input(false);
+        let mut find_iter =
+            scanner_with_modes::create_find_iter(&scanner, "ab abc d").start_at(offset);
+        assert_eq!(
+            find_iter.try_next_match(),
+            FindOutcome::Found(Match::new(4.into(), (3usize..6).into()))
+        );
+        assert_eq!(find_iter.offset(), 6);
+
+        // An invalid offset ends the search
+        let mut find_iter = scanner_with_modes::create_find_iter(&scanner, "äb").start_at(1);
+        assert_eq!(find_iter.offset(), 3);
+        assert_eq!(find_iter.next(), None);
+        let find_iter = scanner_with_modes::create_find_iter(&scanner, "ab").start_at(5);
+        assert_eq!(find_iter.offset(), 2);
+    }
+
+    #[test]
+    fn test_token_anchored() {
+        fn matches_char_class(c: char, _char_class: usize) -> bool {
+            c.is_ascii_digit()
+        }
+        const DFAS: &[DfaData] = &[("[0-9]+", &[1], &[(0, 1), (1, 2)], &[(0, 1), (0, 1)])];
+        let mut scanner = ScannerBuilder::new().add_dfa_data(DFAS).build().unwrap();
+        let spans = |scanner: &crate::Scanner| {
+            scanner
+                .find_iter("12 34", matches_char_class)
+                .map(|m| m.span().range())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(spans(&scanner), vec![0..2, 3..5]);
+
+        // The iteration ends at the gap
+        scanner.set_match_kind(MatchKind::TokenAnchored {
+            error_on_gap: false,
+        });
+        assert_eq!(spans(&scanner), vec![0..2]);
+        let mut find_iter = scanner.find_iter("12 34", matches_char_class);
+        assert!(find_iter.next().is_some());
+        assert_eq!(find_iter.try_next_match(), FindOutcome::NotFound);
+        assert_eq!(find_iter.offset(), 2);
+
+        // The gap is reported
+        scanner.set_match_kind(MatchKind::TokenAnchored { error_on_gap: true });
+        let mut find_iter = scanner.find_iter("12 34", matches_char_class);
+        assert!(find_iter.next().is_some());
+        assert_eq!(find_iter.try_next_match(), FindOutcome::Gap(2));
+        assert_eq!(find_iter.try_next_match(), FindOutcome::Gap(2));
+        assert_eq!(find_iter.offset(), 2);
+        let mut find_iter = scanner.find_iter("12", matches_char_class);
+        assert!(find_iter.next().is_some());
+        assert_eq!(find_iter.try_next_match(), FindOutcome::NotFound);
+
+        // Input covered by the unmatched token type is no gap
+        scanner.set_unmatched_token_type(Some(TokenType::new(1)));
+        assert_eq!(spans(&scanner), vec![0..2, 2..3, 3..5]);
+    }
+
+    #[test]
+    fn test_arbitrary_input_does_not_panic() {
+        const CHARS: &[char] = &[
+            'a',
+            'Z',
+            '0',
+            '9',
+            '_',
+            ' ',
+            '\t',
+            '\n',
+            '\r',
+            '"',
+            '\\',
+            '/',
+            '*',
+            '.',
+            'ä',
+            '€',
+            '\u{10348}',
+            '\u{feff}',
+            '\0',
+        ];
+        // A simple xorshift generator makes the inputs reproducible.
+        let mut state = 0x2545_f491_4f6c_dd1du64;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+        for round in 0..200 {
+            let len = (next() % 64) as usize;
+            let input = (0..len)
+                .map(|_| CHARS[(next() % CHARS.len() as u64) as usize])
+                .collect::<String>();
+            let mut scanner = scanner_with_modes::create_scanner();
+            if round % 2 == 1 {
+                scanner.set_max_token_length(3, TokenType::new(0));
+                scanner.set_step_budget(Some(40));
+            }
+            let mut end = 0;
+            for matched in scanner_with_modes::create_find_iter(&scanner, &input) {
+                assert!(matched.start() >= end, "Overlapping match in {:?}", input);
+                assert!(
+                    input.get(matched.range()).is_some(),
+                    "Invalid span in {:?}",
+                    input
+                );
+                end = matched.span().end;
+            }
+        }
+    }
 }
@@ -0,0 +1,63 @@
+use crate::Match;
+
+/// The outcome of a search that is restricted by a step budget or that runs on partial input.
+///
+/// A step budget can be set with [crate::Scanner::set_step_budget]. It limits the number of
+/// characters examined by a single search. Interactive tools can use it to keep latency bounds
+/// even on pathological inputs.
+///
+/// If the input is marked as partial with [crate::Scanner::set_partial_input], a search that
+/// reaches the end of the input while a longer match is still possible reports
+/// [FindOutcome::NeedMoreInput] instead of the best match so far.
+///
+/// With [crate::MatchKind::TokenAnchored] input no token matches can be reported as
+/// [FindOutcome::Gap] instead of being skipped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FindOutcome {
+    /// A match was found.
+    Found(Match),
+    /// No match was found.
+    NotFound,
+    /// The search was aborted because the step budget was exhausted before the search finished.
+    BudgetExhausted,
+    /// The search reached the end of the partial input while a longer match was still possible.
+    NeedMoreInput,
+    /// No token matches at the given byte offset although the matches must be contiguous, see
+    /// [crate::MatchKind::TokenAnchored].
+    Gap(usize),
+}
+
+impl FindOutcome {
+    /// Returns the match if one was found.
+    #[inline]
+    pub fn matched(self) -> Option<Match> {
+        match self {
+            FindOutcome::Found(matched) => Some(matched),
+            FindOutcome::NotFound
+            | FindOutcome::BudgetExhausted
+            | FindOutcome::NeedMoreInput
+            | FindOutcome::Gap(_) => None,
+        }
+    }
+
+    /// Returns true if the search was aborted because the step budget was exhausted.
+    #[inline]
+    pub fn is_budget_exhausted(&self) -> bool {
+        matches!(self, FindOutcome::BudgetExhausted)
+    }
+
+    /// Returns true if the search needs more input to decide on the match.
+    #[inline]
+    pub fn is_need_more_input(&self) -> bool {
+        matches!(self, FindOutcome::NeedMoreInput)
+    }
+
+    /// Returns the byte offset of the gap if no token matches where the previous match ended.
+    #[inline]
+    pub fn gap(&self) -> Option<usize> {
+        match self {
+            FindOutcome::Gap(offset) => Some(*offset),
+            _ => None,
+        }
+    }
+}
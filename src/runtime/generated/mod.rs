@@ -1,2 +1,4 @@
 #[cfg(test)]
 pub(crate) mod scanner_with_modes;
+#[cfg(test)]
+mod scanner_with_modes_munch;
@@ -0,0 +1,61 @@
+//! Maximal munch tests generated by scangen from the terminals of `crate::runtime::generated::scanner_with_modes`.
+
+use crate::runtime::generated::scanner_with_modes::{create_find_iter, create_scanner};
+
+/// An input with the scanner mode it is scanned in and the expected tokens as pairs of token type and text.
+type Case = (&'static str, usize, &'static [(usize, &'static str)]);
+
+const CASES: &[Case] = &[
+    // Mode INITIAL: "\t" of terminal #9 is a prefix of "\t\t" of terminal #1
+    ("\t", 0, &[(1, "\t")]),
+    ("\t\t", 0, &[(1, "\t\t")]),
+    // Mode INITIAL: "/" of terminal #9 is a prefix of "//\\n" of terminal #2
+    ("/", 0, &[(9, "/")]),
+    ("//\\n", 0, &[(2, "//\\n")]),
+    // Mode INITIAL: "/" of terminal #9 is a prefix of "/**/" of terminal #3
+    ("/**/", 0, &[(3, "/**/")]),
+    // Mode INITIAL: "A" of terminal #9 is a prefix of "A0" of terminal #4
+    ("A", 0, &[(4, "A")]),
+    ("A0", 0, &[(4, "A0")]),
+    // Mode STRING: "\n" of terminal #0 is a prefix of "\n\0" of terminal #7
+    ("\n", 1, &[(0, "\n")]),
+    ("\n\0", 1, &[(7, "\n\0")]),
+    // Mode STRING: "\t" of terminal #1 is a prefix of "\t\0" of terminal #7
+    ("\t", 1, &[(1, "\t")]),
+    ("\t\0", 1, &[(7, "\t\0")]),
+    // Mode STRING: "/**/" of terminal #3 is a prefix of "/**/\0" of terminal #7
+    ("/**/", 1, &[(3, "/**/")]),
+    ("/**/\0", 1, &[(7, "/**/\0")]),
+    // Mode STRING: "\r" of terminal #7 is a prefix of "\r\n" of terminal #0
+    ("\r", 1, &[(0, "\r")]),
+    ("\r\n", 1, &[(0, "\r\n")]),
+    // Mode STRING: "\t" of terminal #7 is a prefix of "\t\t" of terminal #1
+    ("\t\t", 1, &[(1, "\t\t")]),
+    // Mode STRING: "/" of terminal #7 is a prefix of "//\\n" of terminal #2
+    ("/", 1, &[(7, "/")]),
+    ("//\\n", 1, &[(2, "//\\n")]),
+    // Mode STRING: "\\" of terminal #9 is a prefix of "\\\"" of terminal #5
+    ("\\", 1, &[(9, "\\")]),
+    ("\\\"", 1, &[(5, "\\\"")]),
+    // Mode STRING: "\\" of terminal #9 is a prefix of "\\\n" of terminal #6
+    ("\\\n", 1, &[(6, "\\\n")]),
+    // Mode STRING: "\0" of terminal #9 is a prefix of "\0\0" of terminal #7
+    ("\0", 1, &[(7, "\0")]),
+    ("\0\0", 1, &[(7, "\0\0")]),
+];
+
+#[test]
+fn test_maximal_munch() {
+    for (input, mode, expected) in CASES {
+        let mut scanner = create_scanner();
+        scanner.set_mode((*mode).into());
+        let tokens = create_find_iter(&scanner, input)
+            .map(|m| (m.token_type().as_usize(), &input[m.range()]))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            tokens, *expected,
+            "Input {:?} in scanner mode {}",
+            input, mode
+        );
+    }
+}
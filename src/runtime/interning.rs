@@ -0,0 +1,200 @@
+use std::collections::HashMap;
+
+use crate::{Match, TokenType};
+
+use super::Token;
+
+/// The id of a text interned by an [Interner].
+///
+/// It is a newtype around `usize` to prevent accidental mixing of symbols with other indices.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Symbol(usize);
+
+impl Symbol {
+    /// Create a new symbol.
+    #[inline]
+    pub const fn new(index: usize) -> Self {
+        Symbol(index)
+    }
+
+    /// Get the symbol as usize.
+    #[inline]
+    pub const fn as_usize(&self) -> usize {
+        self.0
+    }
+}
+
+impl std::fmt::Display for Symbol {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// An interner maps equal texts to the same [Symbol], so a compiler can compare and store
+/// identifiers and strings without allocating a string for each token.
+///
+/// Implement it for the symbol table of a compiler to let [InternTokens] fill it directly, or use
+/// the built-in [StringInterner].
+pub trait Interner {
+    /// Returns the symbol of the given text. A text that isn't interned yet gets a new symbol.
+    fn intern(&mut self, text: &str) -> Symbol;
+
+    /// Returns the text of the given symbol, if it was returned by this interner.
+    fn resolve(&self, symbol: Symbol) -> Option<&str>;
+}
+
+/// A simple [Interner] that numbers the distinct texts in the order they are interned.
+#[derive(Debug, Clone, Default)]
+pub struct StringInterner {
+    symbols: HashMap<Box<str>, Symbol>,
+    texts: Vec<Box<str>>,
+}
+
+impl StringInterner {
+    /// Creates an empty interner.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of distinct texts interned so far.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.texts.len()
+    }
+
+    /// Returns true if no text has been interned yet.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.texts.is_empty()
+    }
+}
+
+impl Interner for StringInterner {
+    fn intern(&mut self, text: &str) -> Symbol {
+        if let Some(symbol) = self.symbols.get(text) {
+            return *symbol;
+        }
+        let symbol = Symbol::new(self.texts.len());
+        self.texts.push(text.into());
+        self.symbols.insert(text.into(), symbol);
+        symbol
+    }
+
+    fn resolve(&self, symbol: Symbol) -> Option<&str> {
+        self.texts.get(symbol.as_usize()).map(|text| &**text)
+    }
+}
+
+/// A match along with the symbol of its interned text.
+///
+/// The symbol is `None` if the token type of the match isn't interned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct InternedMatch {
+    /// The match of the token.
+    pub matched: Match,
+    /// The symbol of the text of the token.
+    pub symbol: Option<Symbol>,
+}
+
+/// An iterator adapter that interns the text of the tokens of the given token types, e.g. of
+/// identifiers and string contents, and yields the matches along with their symbols as
+/// [InternedMatch]es. The texts of other tokens are not interned.
+///
+/// This iterator can be created with [crate::TokenIter::intern].
+pub struct InternTokens<'i, I> {
+    tokens: I,
+    interner: &'i mut dyn Interner,
+    token_types: Vec<TokenType>,
+}
+
+impl<'h, 'i, I> InternTokens<'i, I>
+where
+    I: Iterator<Item = Token<'h>>,
+{
+    /// Creates a new `InternTokens` iterator adapter over the given tokens.
+    pub fn new(tokens: I, interner: &'i mut dyn Interner, token_types: &[TokenType]) -> Self {
+        Self {
+            tokens,
+            interner,
+            token_types: token_types.to_vec(),
+        }
+    }
+}
+
+impl<I> std::fmt::Debug for InternTokens<'_, I>
+where
+    I: std::fmt::Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("InternTokens")
+            .field("tokens", &self.tokens)
+            .field("token_types", &self.token_types)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'h, I> Iterator for InternTokens<'_, I>
+where
+    I: Iterator<Item = Token<'h>>,
+{
+    type Item = InternedMatch;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let token = self.tokens.next()?;
+        let symbol = self
+            .token_types
+            .contains(&token.token_type)
+            .then(|| self.interner.intern(token.text));
+        Some(InternedMatch {
+            matched: token.as_match(),
+            symbol,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::generated::scanner_with_modes;
+
+    #[test]
+    fn test_string_interner() {
+        let mut interner = StringInterner::new();
+        assert!(interner.is_empty());
+        let a = interner.intern("a");
+        let b = interner.intern("b");
+        assert_ne!(a, b);
+        assert_eq!(interner.intern("a"), a);
+        assert_eq!(interner.len(), 2);
+        assert_eq!(interner.resolve(b), Some("b"));
+        assert_eq!(interner.resolve(Symbol::new(2)), None);
+    }
+
+    #[test]
+    fn test_intern_tokens() {
+        let scanner = scanner_with_modes::create_scanner();
+        let input = "x y x";
+        let identifier = scanner_with_modes::create_find_iter(&scanner, input)
+            .tokens()
+            .next()
+            .unwrap()
+            .token_type;
+        let mut interner = StringInterner::new();
+        let matches = scanner_with_modes::create_find_iter(&scanner, input)
+            .tokens()
+            .intern(&mut interner, &[identifier])
+            .filter(|interned| interned.matched.token_type() == identifier)
+            .map(|interned| interned.symbol)
+            .collect::<Vec<_>>();
+        // Equal texts share their symbol
+        assert_eq!(
+            matches,
+            vec![
+                Some(Symbol::new(0)),
+                Some(Symbol::new(1)),
+                Some(Symbol::new(0))
+            ]
+        );
+        assert_eq!(interner.resolve(Symbol::new(1)), Some("y"));
+    }
+}
@@ -0,0 +1,944 @@
+//! Helpers to write and read the JSON definition of scanners.
+//!
+//! The runtime has no dependencies, so the few JSON constructs the definition consists of are
+//! written and parsed by hand.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Write,
+    sync::Arc,
+};
+
+use crate::{ScannerModeId, TokenType};
+
+use super::{dfa::CharClasses, BuildError, Dfa, DfaWithTokenType, Scanner, ScannerMode};
+
+/// The version of the JSON schema of scanner definitions.
+/// See [crate::Scanner::definition_to_json] for the schema.
+pub const JSON_FORMAT_VERSION: u32 = 1;
+
+/// Writes the given string as JSON string literal.
+pub(crate) fn write_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/// Writes the given items as JSON array. Each item is written by `write_item`.
+pub(crate) fn write_array<T>(
+    out: &mut String,
+    items: impl IntoIterator<Item = T>,
+    mut write_item: impl FnMut(&mut String, T),
+) {
+    out.push('[');
+    for (index, item) in items.into_iter().enumerate() {
+        if index > 0 {
+            out.push(',');
+        }
+        write_item(out, item);
+    }
+    out.push(']');
+}
+
+/// Writes the given pair of numbers as JSON array with two elements.
+pub(crate) fn write_pair(out: &mut String, (first, second): (usize, usize)) {
+    let _ = write!(out, "[{},{}]", first, second);
+}
+
+/// Writes the definition of the given scanner as JSON object.
+/// See [crate::Scanner::definition_to_json] for the schema.
+pub(crate) fn scanner_definition(
+    scanner: &Scanner,
+    matches_char_class: fn(char, usize) -> bool,
+) -> String {
+    // Each DFA uses the match function of the first scanner mode it belongs to. The match
+    // functions are identified by the mode they belong to, None stands for the given one.
+    let mut dfa_sources = vec![None; scanner.dfas.len()];
+    for (mode_index, mode) in scanner.scanner_modes.iter().enumerate() {
+        let source = mode
+            .matches_char_class
+            .map(|function| (Some(mode_index), function));
+        for dfa in &mode.dfas {
+            if let Some(dfa_index) = scanner
+                .dfas
+                .iter()
+                .position(|other| Arc::ptr_eq(other, dfa.dfa()))
+            {
+                if let Some(dfa_source) = dfa_sources.get_mut(dfa_index) {
+                    dfa_source.get_or_insert(source.unwrap_or((None, matches_char_class)));
+                }
+            }
+        }
+    }
+
+    // The character classes of all DFAs are numbered anew, so classes of different match
+    // functions don't collide and equal classes are exported only once.
+    let mut classes: Vec<Vec<(char, char)>> = Vec::new();
+    let mut class_ids: BTreeMap<Vec<(char, char)>, usize> = BTreeMap::new();
+    let mut computed: HashMap<(SourceKey, usize), usize> = HashMap::new();
+    let class_maps = scanner
+        .dfas
+        .iter()
+        .zip(&dfa_sources)
+        .enumerate()
+        .map(|(dfa_index, (dfa, source))| {
+            let (source, function) = source.unwrap_or((None, matches_char_class));
+            // The classes of a DFA that knows its characters don't depend on a match function.
+            let source = match &dfa.char_classes {
+                Some(_) => SourceKey::Dfa(dfa_index),
+                None => SourceKey::Mode(source),
+            };
+            let mut class_map = HashMap::new();
+            for (char_class, _) in dfa.transitions.iter() {
+                let id = *computed.entry((source, *char_class)).or_insert_with(|| {
+                    let ranges = match &dfa.char_classes {
+                        Some(char_classes) => {
+                            char_classes.get(*char_class).cloned().unwrap_or_default()
+                        }
+                        None => class_ranges(function, *char_class),
+                    };
+                    *class_ids.entry(ranges.clone()).or_insert_with(|| {
+                        classes.push(ranges);
+                        classes.len() - 1
+                    })
+                });
+                class_map.insert(*char_class, id);
+            }
+            class_map
+        })
+        .collect::<Vec<_>>();
+
+    let mut out = String::new();
+    let _ = write!(
+        out,
+        "{{\"format\":\"scangen\",\"version\":{},\"classes\":",
+        JSON_FORMAT_VERSION
+    );
+    write_array(&mut out, &classes, |out, ranges| {
+        write_array(out, ranges, |out, (start, end)| {
+            write_pair(out, (*start as usize, *end as usize))
+        })
+    });
+    out.push_str(",\"dfas\":");
+    write_array(
+        &mut out,
+        scanner.dfas.iter().zip(&class_maps),
+        |out, (dfa, class_map)| dfa.write_json(out, |char_class| class_map[&char_class]),
+    );
+    out.push_str(",\"modes\":");
+    write_array(&mut out, &scanner.scanner_modes, |out, mode| {
+        out.push_str("{\"name\":");
+        write_string(out, &mode.name);
+        out.push_str(",\"dfas\":");
+        write_array(out, &mode.dfas, |out, dfa| {
+            let dfa_index = scanner
+                .dfas
+                .iter()
+                .position(|other| Arc::ptr_eq(other, dfa.dfa()))
+                .unwrap_or_default();
+            write_pair(out, (dfa_index, dfa.token_type().as_usize()))
+        });
+        out.push_str(",\"transitions\":");
+        write_array(out, &mode.transitions, |out, (token_type, target)| {
+            write_pair(out, (token_type.as_usize(), target.as_usize()))
+        });
+        out.push_str(",\"error_token_type\":");
+        match mode.error_token_type {
+            Some(token_type) => out.push_str(&token_type.to_string()),
+            None => out.push_str("null"),
+        }
+        out.push_str(",\"counting\":");
+        match mode.counting {
+            Some((open, close, exit)) => {
+                let _ = write!(
+                    out,
+                    "{{\"open\":{},\"close\":{},\"exit\":{}}}",
+                    open, close, exit
+                );
+            }
+            None => out.push_str("null"),
+        }
+        out.push('}');
+    });
+    out.push('}');
+    out
+}
+
+/// Identifies where the characters of a class come from when a scanner definition is written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum SourceKey {
+    /// The match function of the mode with the given index or the given one for None.
+    Mode(Option<usize>),
+    /// The classes the DFA with the given index knows itself.
+    Dfa(usize),
+}
+
+/// Returns the ranges of the characters the match function accepts for the given class.
+/// All characters are tested, so this takes a while for each class.
+fn class_ranges(
+    matches_char_class: fn(char, usize) -> bool,
+    char_class: usize,
+) -> Vec<(char, char)> {
+    let mut ranges: Vec<(char, char)> = Vec::new();
+    for c in '\0'..=char::MAX {
+        if matches_char_class(c, char_class) {
+            match ranges.last_mut() {
+                Some((_, end)) if successor(*end) == Some(c) => *end = c,
+                _ => ranges.push((c, c)),
+            }
+        }
+    }
+    ranges
+}
+
+/// Returns the next character, skipping the surrogate code points.
+fn successor(c: char) -> Option<char> {
+    match c {
+        '\u{d7ff}' => Some('\u{e000}'),
+        _ => char::from_u32(c as u32 + 1),
+    }
+}
+
+/// Creates a scanner from its definition in JSON.
+/// See [crate::Scanner::definition_to_json] for the schema.
+pub(crate) fn scanner_from_definition(json: &str) -> Result<Scanner, BuildError> {
+    let definition = Parser::new(json).parse()?;
+    if definition.member("format")?.as_str()? != "scangen" {
+        return Err(invalid("The member format must be \"scangen\""));
+    }
+    let version = definition.member("version")?.as_usize()?;
+    if version != JSON_FORMAT_VERSION as usize {
+        return Err(invalid(format!(
+            "Version {} is not supported, expected version {}",
+            version, JSON_FORMAT_VERSION
+        )));
+    }
+
+    let char_classes = definition
+        .member("classes")?
+        .as_array()?
+        .iter()
+        .map(char_class)
+        .collect::<Result<Vec<_>, _>>()?;
+    let class_count = char_classes.len();
+    let char_classes: CharClasses = char_classes.into();
+    let dfas = definition
+        .member("dfas")?
+        .as_array()?
+        .iter()
+        .map(|dfa| self::dfa(dfa, &char_classes, class_count).map(Arc::new))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let modes = definition.member("modes")?.as_array()?;
+    let scanner_modes = modes
+        .iter()
+        .map(|mode| scanner_mode(mode, &dfas, modes.len()))
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut scanner = Scanner::new(dfas, scanner_modes);
+    if scanner.scanner_modes.is_empty() {
+        super::ScannerBuilder::create_default_mode(&mut scanner);
+    }
+    Ok(scanner)
+}
+
+/// Converts a character class given as array of pairs of code points into sorted and disjoint
+/// ranges of characters.
+fn char_class(value: &JsonValue) -> Result<Vec<(char, char)>, BuildError> {
+    let mut ranges = value
+        .as_array()?
+        .iter()
+        .map(|range| {
+            let (start, end) = range.as_pair()?;
+            let start = code_point(start)?;
+            let end = code_point(end)?;
+            if start > end {
+                return Err(invalid(format!(
+                    "The range {:?}..={:?} of a character class is empty",
+                    start, end
+                )));
+            }
+            Ok((start, end))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    ranges.sort_unstable();
+    let mut merged: Vec<(char, char)> = Vec::with_capacity(ranges.len());
+    for (start, end) in ranges {
+        match merged.last_mut() {
+            Some((_, last_end)) if start <= *last_end || successor(*last_end) == Some(start) => {
+                *last_end = (*last_end).max(end)
+            }
+            _ => merged.push((start, end)),
+        }
+    }
+    Ok(merged)
+}
+
+fn code_point(value: usize) -> Result<char, BuildError> {
+    u32::try_from(value)
+        .ok()
+        .and_then(char::from_u32)
+        .ok_or_else(|| invalid(format!("{} is no valid code point", value)))
+}
+
+/// Converts a DFA object into a DFA that knows the characters of its classes.
+fn dfa(
+    value: &JsonValue,
+    char_classes: &CharClasses,
+    class_count: usize,
+) -> Result<Dfa, BuildError> {
+    let pattern = value.member("pattern")?.as_str()?.to_string();
+    let states = value.member("states")?.as_array()?;
+    if states.is_empty() {
+        return Err(invalid(format!("The DFA {} has no states", pattern)));
+    }
+    let check_state = |state: usize| {
+        if state < states.len() {
+            Ok(state)
+        } else {
+            Err(invalid(format!(
+                "The DFA {} references the unknown state {}",
+                pattern, state
+            )))
+        }
+    };
+    let accepting_states = value
+        .member("accepting_states")?
+        .as_array()?
+        .iter()
+        .map(|state| check_state(state.as_usize()?))
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut state_ranges = Vec::with_capacity(states.len());
+    let mut transitions = Vec::new();
+    for state in states {
+        let start = transitions.len();
+        for transition in state.as_array()? {
+            let (char_class, target) = transition.as_pair()?;
+            if char_class >= class_count {
+                return Err(invalid(format!(
+                    "The DFA {} references the unknown character class {}",
+                    pattern, char_class
+                )));
+            }
+            transitions.push((char_class, check_state(target)?));
+        }
+        state_ranges.push((start, transitions.len()));
+    }
+    let counted_states = match value.optional_member("counted_states") {
+        Some(counted_states) => counted_states
+            .as_array()?
+            .iter()
+            .map(|counted_state| counted_state_of(counted_state, check_state))
+            .collect::<Result<Vec<_>, _>>()?,
+        None => Vec::new(),
+    };
+    let mut dfa = Dfa::from(crate::OwnedDfaData {
+        pattern,
+        accepting_states,
+        state_ranges,
+        transitions,
+        counted_states,
+    });
+    dfa.char_classes = Some(Arc::clone(char_classes));
+    Ok(dfa)
+}
+
+/// Converts an array of a counted state, the minimum and the optional maximum number of
+/// repetitions into a counted state.
+fn counted_state_of(
+    value: &JsonValue,
+    check_state: impl Fn(usize) -> Result<usize, BuildError>,
+) -> Result<(usize, usize, Option<usize>), BuildError> {
+    match value.as_array()? {
+        [state, min, max] => {
+            let max = match max {
+                JsonValue::Null => None,
+                max => Some(max.as_usize()?),
+            };
+            Ok((check_state(state.as_usize()?)?, min.as_usize()?, max))
+        }
+        items => Err(invalid(format!(
+            "Expected a counted state with its bounds, found an array with {} elements",
+            items.len()
+        ))),
+    }
+}
+
+/// Converts a mode object into a scanner mode.
+fn scanner_mode(
+    value: &JsonValue,
+    dfas: &[Arc<Dfa>],
+    mode_count: usize,
+) -> Result<ScannerMode, BuildError> {
+    let name = value.member("name")?.as_str()?.to_string();
+    let check_mode = |target: usize| {
+        if target < mode_count {
+            Ok(ScannerModeId::new(target))
+        } else {
+            Err(BuildError::UnknownScannerMode {
+                mode: name.clone(),
+                target: ScannerModeId::new(target),
+                mode_count,
+            })
+        }
+    };
+    let mode_dfas = value
+        .member("dfas")?
+        .as_array()?
+        .iter()
+        .map(|pair| {
+            let (dfa_index, token_type) = pair.as_pair()?;
+            let dfa = dfas.get(dfa_index).ok_or_else(|| BuildError::UnknownDfa {
+                mode: name.clone(),
+                dfa_index,
+                dfa_count: dfas.len(),
+            })?;
+            Ok(DfaWithTokenType::new(
+                Arc::clone(dfa),
+                TokenType::new(token_type),
+            ))
+        })
+        .collect::<Result<Vec<_>, BuildError>>()?;
+    let transitions = match value.optional_member("transitions") {
+        Some(transitions) => transitions
+            .as_array()?
+            .iter()
+            .map(|pair| {
+                let (token_type, target) = pair.as_pair()?;
+                Ok((TokenType::new(token_type), check_mode(target)?))
+            })
+            .collect::<Result<Vec<_>, BuildError>>()?,
+        None => Vec::new(),
+    };
+    let error_token_type = match value.optional_member("error_token_type") {
+        Some(token_type) => Some(TokenType::new(token_type.as_usize()?)),
+        None => None,
+    };
+    let counting = match value.optional_member("counting") {
+        Some(counting) => Some((
+            TokenType::new(counting.member("open")?.as_usize()?),
+            TokenType::new(counting.member("close")?.as_usize()?),
+            check_mode(counting.member("exit")?.as_usize()?)?,
+        )),
+        None => None,
+    };
+    let mut mode = ScannerMode::from_parts(name, mode_dfas, transitions, error_token_type);
+    mode.counting = counting;
+    Ok(mode)
+}
+
+fn invalid(message: impl Into<String>) -> BuildError {
+    BuildError::InvalidJson {
+        message: message.into(),
+    }
+}
+
+/// A parsed JSON value.
+#[derive(Debug, Clone, PartialEq)]
+enum JsonValue {
+    Null,
+    Bool(bool),
+    /// A number along with its value if it is a non-negative integer.
+    Number(Option<usize>),
+    String(String),
+    Array(Vec<JsonValue>),
+    Object(Vec<(String, JsonValue)>),
+}
+
+impl JsonValue {
+    fn kind(&self) -> &'static str {
+        match self {
+            JsonValue::Null => "null",
+            JsonValue::Bool(_) => "a boolean",
+            JsonValue::Number(_) => "a number",
+            JsonValue::String(_) => "a string",
+            JsonValue::Array(_) => "an array",
+            JsonValue::Object(_) => "an object",
+        }
+    }
+
+    /// Returns the member with the given name of an object that is not null.
+    fn optional_member(&self, name: &str) -> Option<&JsonValue> {
+        match self {
+            JsonValue::Object(members) => members
+                .iter()
+                .find(|(member, _)| member == name)
+                .map(|(_, value)| value)
+                .filter(|value| **value != JsonValue::Null),
+            _ => None,
+        }
+    }
+
+    fn member(&self, name: &str) -> Result<&JsonValue, BuildError> {
+        match self {
+            JsonValue::Object(_) => self
+                .optional_member(name)
+                .ok_or_else(|| invalid(format!("The member {} is missing", name))),
+            _ => Err(invalid(format!(
+                "Expected an object with the member {}, found {}",
+                name,
+                self.kind()
+            ))),
+        }
+    }
+
+    fn as_array(&self) -> Result<&[JsonValue], BuildError> {
+        match self {
+            JsonValue::Array(items) => Ok(items),
+            _ => Err(invalid(format!("Expected an array, found {}", self.kind()))),
+        }
+    }
+
+    fn as_str(&self) -> Result<&str, BuildError> {
+        match self {
+            JsonValue::String(s) => Ok(s),
+            _ => Err(invalid(format!("Expected a string, found {}", self.kind()))),
+        }
+    }
+
+    fn as_usize(&self) -> Result<usize, BuildError> {
+        match self {
+            JsonValue::Number(Some(n)) => Ok(*n),
+            _ => Err(invalid(format!(
+                "Expected a non-negative integer, found {}",
+                self.kind()
+            ))),
+        }
+    }
+
+    fn as_pair(&self) -> Result<(usize, usize), BuildError> {
+        match self.as_array()? {
+            [first, second] => Ok((first.as_usize()?, second.as_usize()?)),
+            items => Err(invalid(format!(
+                "Expected a pair of integers, found an array with {} elements",
+                items.len()
+            ))),
+        }
+    }
+}
+
+/// The maximum nesting depth of arrays and objects the JSON parser accepts. The definitions of
+/// scanners nest only a few levels, and the limit keeps the recursive descent from overflowing
+/// the stack on malicious input.
+const MAX_NESTING_DEPTH: usize = 64;
+
+/// A recursive descent parser for JSON.
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(input: &'a str) -> Self {
+        Parser {
+            input,
+            pos: 0,
+            depth: 0,
+        }
+    }
+
+    /// Parses the whole input as a single JSON value.
+    fn parse(mut self) -> Result<JsonValue, BuildError> {
+        let value = self.value()?;
+        self.skip_whitespace();
+        if self.pos < self.input.len() {
+            return Err(self.error("Unexpected characters after the JSON value"));
+        }
+        Ok(value)
+    }
+
+    fn error(&self, message: &str) -> BuildError {
+        invalid(format!("{} at offset {}", message, self.pos))
+    }
+
+    /// Returns the input that is not parsed yet.
+    fn rest(&self) -> &str {
+        self.input.get(self.pos..).unwrap_or_default()
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.input.as_bytes().get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, expected: u8) -> Result<(), BuildError> {
+        self.skip_whitespace();
+        if self.peek() == Some(expected) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(self.error(&format!("Expected '{}'", expected as char)))
+        }
+    }
+
+    fn keyword(&mut self, keyword: &str, value: JsonValue) -> Result<JsonValue, BuildError> {
+        if self.rest().starts_with(keyword) {
+            self.pos += keyword.len();
+            Ok(value)
+        } else {
+            Err(self.error("Expected a JSON value"))
+        }
+    }
+
+    fn value(&mut self) -> Result<JsonValue, BuildError> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(b'{') => self.nested(Self::object),
+            Some(b'[') => self.nested(Self::array),
+            Some(b'"') => self.string().map(JsonValue::String),
+            Some(b'-' | b'0'..=b'9') => self.number(),
+            Some(b't') => self.keyword("true", JsonValue::Bool(true)),
+            Some(b'f') => self.keyword("false", JsonValue::Bool(false)),
+            Some(b'n') => self.keyword("null", JsonValue::Null),
+            _ => Err(self.error("Expected a JSON value")),
+        }
+    }
+
+    /// Parses an array or object with the given function unless the maximum nesting depth is
+    /// exceeded.
+    fn nested(
+        &mut self,
+        parse: fn(&mut Self) -> Result<JsonValue, BuildError>,
+    ) -> Result<JsonValue, BuildError> {
+        if self.depth >= MAX_NESTING_DEPTH {
+            return Err(self.error(&format!(
+                "The JSON value is nested deeper than {} levels",
+                MAX_NESTING_DEPTH
+            )));
+        }
+        self.depth += 1;
+        let value = parse(self);
+        self.depth -= 1;
+        value
+    }
+
+    fn object(&mut self) -> Result<JsonValue, BuildError> {
+        self.expect(b'{')?;
+        let mut members = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some(b'}') {
+            self.pos += 1;
+            return Ok(JsonValue::Object(members));
+        }
+        loop {
+            self.skip_whitespace();
+            let name = self.string()?;
+            self.expect(b':')?;
+            members.push((name, self.value()?));
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b'}') => {
+                    self.pos += 1;
+                    return Ok(JsonValue::Object(members));
+                }
+                _ => return Err(self.error("Expected ',' or '}'")),
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<JsonValue, BuildError> {
+        self.expect(b'[')?;
+        let mut items = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some(b']') {
+            self.pos += 1;
+            return Ok(JsonValue::Array(items));
+        }
+        loop {
+            items.push(self.value()?);
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b']') => {
+                    self.pos += 1;
+                    return Ok(JsonValue::Array(items));
+                }
+                _ => return Err(self.error("Expected ',' or ']'")),
+            }
+        }
+    }
+
+    fn number(&mut self) -> Result<JsonValue, BuildError> {
+        let start = self.pos;
+        while matches!(
+            self.peek(),
+            Some(b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9')
+        ) {
+            self.pos += 1;
+        }
+        let text = self.input.get(start..self.pos).unwrap_or_default();
+        if text.parse::<f64>().is_err() {
+            return Err(self.error("Invalid number"));
+        }
+        Ok(JsonValue::Number(text.parse::<usize>().ok()))
+    }
+
+    fn string(&mut self) -> Result<String, BuildError> {
+        self.expect(b'"')?;
+        let mut value = String::new();
+        loop {
+            let Some(c) = self.rest().chars().next() else {
+                return Err(self.error("Unterminated string"));
+            };
+            self.pos += c.len_utf8();
+            match c {
+                '"' => return Ok(value),
+                '\\' => {
+                    let escape = self
+                        .peek()
+                        .ok_or_else(|| self.error("Unterminated string"))?;
+                    self.pos += 1;
+                    match escape {
+                        b'"' => value.push('"'),
+                        b'\\' => value.push('\\'),
+                        b'/' => value.push('/'),
+                        b'b' => value.push('\u{8}'),
+                        b'f' => value.push('\u{c}'),
+                        b'n' => value.push('\n'),
+                        b'r' => value.push('\r'),
+                        b't' => value.push('\t'),
+                        b'u' => value.push(self.unicode_escape()?),
+                        _ => return Err(self.error("Invalid escape sequence")),
+                    }
+                }
+                c if (c as u32) < 0x20 => {
+                    return Err(self.error("Unescaped control character in string"))
+                }
+                c => value.push(c),
+            }
+        }
+    }
+
+    /// Parses the hex digits of a `\\u` escape, including a following low surrogate.
+    fn unicode_escape(&mut self) -> Result<char, BuildError> {
+        let high = self.hex4()?;
+        let code_point = if (0xd800..0xdc00).contains(&high) {
+            if !self.rest().starts_with("\\u") {
+                return Err(self.error("Missing low surrogate"));
+            }
+            self.pos += 2;
+            let low = self.hex4()?;
+            if !(0xdc00..0xe000).contains(&low) {
+                return Err(self.error("Invalid low surrogate"));
+            }
+            0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00)
+        } else {
+            high
+        };
+        char::from_u32(code_point).ok_or_else(|| self.error("Invalid unicode escape"))
+    }
+
+    fn hex4(&mut self) -> Result<u32, BuildError> {
+        let digits = self
+            .input
+            .get(self.pos..self.pos + 4)
+            .ok_or_else(|| self.error("Invalid unicode escape"))?;
+        if !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(self.error("Invalid unicode escape"));
+        }
+        let value =
+            u32::from_str_radix(digits, 16).map_err(|_| self.error("Invalid unicode escape"))?;
+        self.pos += 4;
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{runtime::generated::scanner_with_modes, ScannerBuilder};
+
+    #[test]
+    fn test_write_string() {
+        let mut out = String::new();
+        write_string(&mut out, "a\"b\\c\n\u{1}π");
+        assert_eq!(out, r#""a\"b\\c\n\u0001π""#);
+    }
+
+    #[test]
+    fn test_write_array() {
+        let mut out = String::new();
+        write_array(&mut out, [(0, 1), (2, 3)], write_pair);
+        assert_eq!(out, "[[0,1],[2,3]]");
+        out.clear();
+        write_array(&mut out, std::iter::empty::<(usize, usize)>(), write_pair);
+        assert_eq!(out, "[]");
+    }
+
+    #[test]
+    fn test_scanner_definition() {
+        use crate::{DfaData, ScannerBuilder, ScannerModeData, ScannerModeId, TokenType};
+        const DFAS: &[DfaData] = &[
+            ("a+", &[1], &[(0, 1), (1, 2)], &[(0, 1), (0, 1)]),
+            ("[0-9]", &[1], &[(0, 1), (0, 0)], &[(1, 1)]),
+        ];
+        const MODES: &[ScannerModeData] = &[
+            (
+                "INITIAL",
+                &[(1, TokenType::new(1)), (0, TokenType::new(0))],
+                &[(TokenType::new(1), ScannerModeId::new(1))],
+                None,
+            ),
+            (
+                "DIGITS",
+                &[(1, TokenType::new(1))],
+                &[],
+                Some(TokenType::new(2)),
+            ),
+        ];
+        fn matches_char_class(c: char, char_class: usize) -> bool {
+            match char_class {
+                0 => c == 'a',
+                1 => c.is_ascii_digit(),
+                _ => false,
+            }
+        }
+        let scanner = ScannerBuilder::new()
+            .add_dfa_data(DFAS)
+            .add_scanner_mode_data(MODES)
+            .build()
+            .unwrap();
+        assert_eq!(
+            scanner.definition_to_json(matches_char_class),
+            concat!(
+                r#"{"format":"scangen","version":1,"classes":[[[97,97]],[[48,57]]],"dfas":["#,
+                r#"{"pattern":"a+","accepting_states":[1],"states":[[[0,1]],[[0,1]]]},"#,
+                r#"{"pattern":"[0-9]","accepting_states":[1],"states":[[[1,1]],[]]}],"#,
+                r#""modes":[{"name":"INITIAL","dfas":[[1,1],[0,0]],"transitions":[[1,1]],"#,
+                r#""error_token_type":null,"counting":null},"#,
+                r#"{"name":"DIGITS","dfas":[[1,1]],"transitions":[],"error_token_type":2,"#,
+                r#""counting":null}]}"#
+            )
+        );
+    }
+
+    #[test]
+    fn test_parse_json() {
+        let value =
+            Parser::new(r#" {"a": [1, -2, 3.5, true, null], "b": "x\u00e4\ud83d\ude00\n"} "#)
+                .parse()
+                .unwrap();
+        assert_eq!(
+            value,
+            JsonValue::Object(vec![
+                (
+                    "a".to_string(),
+                    JsonValue::Array(vec![
+                        JsonValue::Number(Some(1)),
+                        JsonValue::Number(None),
+                        JsonValue::Number(None),
+                        JsonValue::Bool(true),
+                        JsonValue::Null,
+                    ])
+                ),
+                ("b".to_string(), JsonValue::String("xä😀\n".to_string())),
+            ])
+        );
+        for malformed in ["", "[1,]", "{\"a\" 1}", "\"abc", "[1] 2", "\"\\u12\""] {
+            assert!(
+                matches!(
+                    Parser::new(malformed).parse(),
+                    Err(BuildError::InvalidJson { .. })
+                ),
+                "{}",
+                malformed
+            );
+        }
+    }
+
+    #[test]
+    fn test_scanner_from_definition() {
+        let scanner = scanner_with_modes::create_scanner();
+        let json = scanner.definition_to_json(|_, _| unreachable!());
+        let imported = ScannerBuilder::build_from_json(&json).unwrap();
+        // The imported DFAs know the characters of their classes
+        assert_eq!(imported.definition_to_json(|_, _| unreachable!()), json);
+        let input = "id \"a\\tb\" /* c */ ä\n";
+        assert_eq!(
+            imported
+                .find_iter(input, |_, _| unreachable!())
+                .collect::<Vec<_>>(),
+            scanner_with_modes::create_find_iter(&scanner, input).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_invalid_scanner_definition() {
+        let definition = |dfas: &str, modes: &str| {
+            format!(
+                r#"{{"format":"scangen","version":1,"classes":[[[97,97]]],"dfas":{},"modes":{}}}"#,
+                dfas, modes
+            )
+        };
+        let error = |json: &str| ScannerBuilder::build_from_json(json).unwrap_err();
+        let dfa = r#"[{"pattern":"a","accepting_states":[1],"states":[[[0,1]],[]]}]"#;
+        assert!(ScannerBuilder::build_from_json(&definition(dfa, "[]")).is_ok());
+        assert_eq!(
+            error(&definition(
+                r#"[{"pattern":"a","accepting_states":[1],"states":[[[0,2]],[]]}]"#,
+                "[]"
+            ))
+            .to_string(),
+            "Invalid JSON definition of the scanner: The DFA a references the unknown state 2"
+        );
+        assert!(matches!(
+            error(&definition(
+                r#"[{"pattern":"a","accepting_states":[1],"states":[[[1,1]],[]]}]"#,
+                "[]"
+            )),
+            BuildError::InvalidJson { .. }
+        ));
+        assert!(matches!(
+            error(&definition(dfa, r#"[{"name":"INITIAL","dfas":[[1,0]]}]"#)),
+            BuildError::UnknownDfa { dfa_index: 1, .. }
+        ));
+        assert!(matches!(
+            error(&definition(
+                dfa,
+                r#"[{"name":"INITIAL","dfas":[[0,0]],"transitions":[[0,1]]}]"#
+            )),
+            BuildError::UnknownScannerMode { mode_count: 1, .. }
+        ));
+        assert!(matches!(
+            error(&definition(dfa, "{}")),
+            BuildError::InvalidJson { .. }
+        ));
+        assert!(matches!(
+            error(&definition(dfa, "[]").replace("\"version\":1", "\"version\":2")),
+            BuildError::InvalidJson { .. }
+        ));
+    }
+
+    #[test]
+    fn test_nesting_depth() {
+        // Deeply nested input is rejected instead of overflowing the stack
+        for json in ["[".repeat(1_000_000), "{\"a\":".repeat(1_000_000)] {
+            assert!(matches!(
+                ScannerBuilder::build_from_json(&json),
+                Err(BuildError::InvalidJson { .. })
+            ));
+        }
+        let nested = format!("{}{}", "[".repeat(64), "]".repeat(64));
+        assert!(Parser::new(&nested).parse().is_ok());
+        let nested = format!("{}{}", "[".repeat(65), "]".repeat(65));
+        assert!(Parser::new(&nested).parse().is_err());
+    }
+}
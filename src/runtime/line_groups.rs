@@ -0,0 +1,121 @@
+use std::iter::Peekable;
+
+use crate::Match;
+
+/// An iterator adapter that groups matches by the source line they start on.
+///
+/// The iterator yields the 1-based line number along with all matches that start on this line.
+/// Lines without any match are skipped. A match that spans several lines, e.g. a block comment, is
+/// grouped into the line it starts on.
+///
+/// The matches are expected in ascending order of their start positions, as they are yielded by
+/// [crate::FindMatches].
+///
+/// This adapter can be created with [crate::FindMatches::by_lines] or [LineGroups::new].
+#[derive(Debug)]
+pub struct LineGroups<'h, I>
+where
+    I: Iterator<Item = Match>,
+{
+    input: &'h str,
+    matches: Peekable<I>,
+    // The line number of the position below
+    line: usize,
+    // The position up to which the newlines are counted
+    position: usize,
+}
+
+impl<'h, I> LineGroups<'h, I>
+where
+    I: Iterator<Item = Match>,
+{
+    /// Creates a new adapter that groups the given matches found in the given input by lines.
+    pub fn new<M>(input: &'h str, matches: M) -> Self
+    where
+        M: IntoIterator<IntoIter = I>,
+    {
+        LineGroups {
+            input,
+            matches: matches.into_iter().peekable(),
+            line: 1,
+            position: 0,
+        }
+    }
+
+    /// Advances the line counter to the given position and returns the line number.
+    fn line_at(&mut self, position: usize) -> usize {
+        let position = position.min(self.input.len()).max(self.position);
+        self.line += self
+            .input
+            .as_bytes()
+            .get(self.position..position)
+            .unwrap_or_default()
+            .iter()
+            .filter(|b| **b == b'\n')
+            .count();
+        self.position = position;
+        self.line
+    }
+}
+
+impl<I> Iterator for LineGroups<'_, I>
+where
+    I: Iterator<Item = Match>,
+{
+    type Item = (usize, Vec<Match>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let first = self.matches.next()?;
+        let line = self.line_at(first.start());
+        let mut matches = vec![first];
+        while let Some(next) = self.matches.peek() {
+            let start = next.start();
+            if self.line_at(start) != line {
+                break;
+            }
+            matches.extend(self.matches.next());
+        }
+        Some((line, matches))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::generated::scanner_with_modes::{create_find_iter, create_scanner};
+
+    #[test]
+    fn test_line_groups() {
+        let input = "a b\n\n  c \"x\ny\" d\ne";
+        let scanner = create_scanner();
+        let lines = create_find_iter(&scanner, input)
+            .by_lines()
+            .map(|(line, matches)| {
+                (
+                    line,
+                    matches
+                        .iter()
+                        .map(|m| &input[m.span().range()])
+                        .collect::<Vec<_>>(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            lines,
+            vec![
+                (1, vec!["a", " ", "b", "\n"]),
+                (2, vec!["\n"]),
+                // The string content spans two lines and is grouped into the line it starts on
+                (3, vec!["  ", "c", " ", "\"", "x\ny"]),
+                (4, vec!["\"", " ", "d", "\n"]),
+                (5, vec!["e"]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_line_groups_empty() {
+        let mut groups = LineGroups::new("", Vec::<Match>::new());
+        assert_eq!(groups.next(), None);
+    }
+}
@@ -0,0 +1,207 @@
+use crate::Span;
+
+use super::{ColumnConfig, Position};
+
+/// An index of the lines of a haystack that maps byte offsets to line and column positions and
+/// vice versa.
+///
+/// The index is built once from the offsets of the line feeds in the haystack. The line of an
+/// offset is then found by a binary search in O(log n). Only the characters of this line are
+/// examined to count the column, as configured by the [ColumnConfig].
+///
+/// ```rust
+/// use scangen::{LineIndex, Position};
+/// let index = LineIndex::new("let a;\nlet b;");
+/// assert_eq!(index.position(11), Position::new(2, 5));
+/// assert_eq!(index.offset(Position::new(2, 5)), Some(11));
+/// ```
+#[derive(Debug, Clone)]
+pub struct LineIndex<'h> {
+    input: &'h str,
+    // The byte offsets of the starts of the lines in ascending order
+    line_starts: Vec<usize>,
+    // True if a carriage return ends a line, too
+    universal_line_breaks: bool,
+    config: ColumnConfig,
+}
+
+impl<'h> LineIndex<'h> {
+    /// Creates the line index of the given haystack. Each character counts as one column.
+    pub fn new(input: &'h str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(
+                input
+                    .bytes()
+                    .enumerate()
+                    .filter(|(_, b)| *b == b'\n')
+                    .map(|(offset, _)| offset + 1),
+            )
+            .collect();
+        LineIndex {
+            input,
+            line_starts,
+            universal_line_breaks: false,
+            config: ColumnConfig::new(),
+        }
+    }
+
+    /// Creates the line index of the given haystack in which `\r\n`, `\r` and `\n` end a line,
+    /// i.e. the line breaks that the newline terminal of generated scanners matches, see
+    /// [crate::Scanner::newline_token_type].
+    pub fn with_universal_line_breaks(input: &'h str) -> Self {
+        let bytes = input.as_bytes();
+        let line_starts = std::iter::once(0)
+            .chain(
+                bytes
+                    .iter()
+                    .enumerate()
+                    .filter(|(offset, b)| {
+                        **b == b'\n' || (**b == b'\r' && bytes.get(offset + 1) != Some(&b'\n'))
+                    })
+                    .map(|(offset, _)| offset + 1),
+            )
+            .collect();
+        LineIndex {
+            input,
+            line_starts,
+            universal_line_breaks: true,
+            config: ColumnConfig::new(),
+        }
+    }
+
+    /// Sets how the columns are counted.
+    pub fn with_column_config(mut self, config: ColumnConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Returns the number of lines. A haystack without line feeds has one line.
+    pub fn line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+
+    /// Returns the 1-based number of the line that contains the given byte offset.
+    /// Offsets beyond the end of the haystack belong to the last line.
+    pub fn line_of(&self, offset: usize) -> usize {
+        self.line_starts.partition_point(|start| *start <= offset)
+    }
+
+    /// Returns the span of the given 1-based line without its line break, if the line exists.
+    pub fn line_span(&self, line: usize) -> Option<Span> {
+        let start = *self.line_starts.get(line.checked_sub(1)?)?;
+        let end = self.line_starts.get(line).map_or(self.input.len(), |next| {
+            let crlf = self
+                .input
+                .get(..*next)
+                .is_some_and(|before| before.ends_with("\r\n"));
+            if self.universal_line_breaks && crlf {
+                next - 2
+            } else {
+                next - 1
+            }
+        });
+        Some(Span::new(start, end))
+    }
+
+    /// Returns the position of the given byte offset.
+    /// The offset is clamped to the haystack and moved back to the start of its character.
+    pub fn position(&self, offset: usize) -> Position {
+        let mut offset = offset.min(self.input.len());
+        while !self.input.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        let line = self.line_of(offset);
+        let line_start = self
+            .line_starts
+            .get(line.saturating_sub(1))
+            .copied()
+            .unwrap_or_default();
+        self.config.advance_str(
+            Position::new(line, 1),
+            self.input.get(line_start..offset).unwrap_or_default(),
+        )
+    }
+
+    /// Returns the positions of the start and the end of the given span.
+    pub fn span_positions(&self, span: Span) -> (Position, Position) {
+        (self.position(span.start), self.position(span.end))
+    }
+
+    /// Returns the byte offset of the given position.
+    ///
+    /// If the column lies within a character that occupies several columns, e.g. a tab, the
+    /// offset of this character is returned. The column after the last character of a line is
+    /// the offset of its line feed. None is returned if the line doesn't exist or the column lies
+    /// beyond the end of the line.
+    pub fn offset(&self, position: Position) -> Option<usize> {
+        let line_span = self.line_span(position.line)?;
+        let mut column = 1;
+        for (offset, c) in self.input.get(line_span.range())?.char_indices() {
+            let next_column = self.config.advance(column, c);
+            if position.column < next_column {
+                return (position.column >= column).then_some(line_span.start + offset);
+            }
+            column = next_column;
+        }
+        (position.column == column).then_some(line_span.end)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_index() {
+        let index = LineIndex::new("ab\n\n\tc\n");
+        assert_eq!(index.line_count(), 4);
+        assert_eq!(index.line_of(0), 1);
+        assert_eq!(index.line_of(2), 1);
+        assert_eq!(index.line_of(3), 2);
+        assert_eq!(index.line_of(100), 4);
+        assert_eq!(index.line_span(1), Some(Span::new(0, 2)));
+        assert_eq!(index.line_span(2), Some(Span::new(3, 3)));
+        assert_eq!(index.line_span(4), Some(Span::new(7, 7)));
+        assert_eq!(index.line_span(0), None);
+        assert_eq!(index.line_span(5), None);
+        assert_eq!(index.position(5), Position::new(3, 2));
+        assert_eq!(
+            index.span_positions(Span::new(1, 3)),
+            (Position::new(1, 2), Position::new(2, 1))
+        );
+    }
+
+    #[test]
+    fn test_universal_line_breaks() {
+        let input = "a\r\nb\rc\n\r\nd";
+        let index = LineIndex::with_universal_line_breaks(input);
+        assert_eq!(index.line_count(), 5);
+        assert_eq!(index.line_span(1), Some(Span::new(0, 1)));
+        assert_eq!(index.line_span(2), Some(Span::new(3, 4)));
+        assert_eq!(index.line_span(3), Some(Span::new(5, 6)));
+        assert_eq!(index.line_span(4), Some(Span::new(7, 7)));
+        assert_eq!(index.line_span(5), Some(Span::new(9, 10)));
+        assert_eq!(index.position(5), Position::new(3, 1));
+        assert_eq!(index.position(9), Position::new(5, 1));
+        assert_eq!(index.offset(Position::new(2, 2)), Some(4));
+        // Only the line feed ends a line of the default index
+        assert_eq!(LineIndex::new(input).line_count(), 4);
+    }
+
+    #[test]
+    fn test_offset_of_position() {
+        let input = "a\tb\näx";
+        let index = LineIndex::new(input).with_column_config(ColumnConfig::new().with_tab_stops(4));
+        assert_eq!(index.position(2), Position::new(1, 5));
+        assert_eq!(index.offset(Position::new(1, 5)), Some(2));
+        // A column within the tab maps to the tab
+        assert_eq!(index.offset(Position::new(1, 3)), Some(1));
+        assert_eq!(index.offset(Position::new(1, 6)), Some(3));
+        assert_eq!(index.offset(Position::new(1, 7)), None);
+        assert_eq!(index.offset(Position::new(2, 2)), Some(6));
+        assert_eq!(index.offset(Position::new(3, 1)), None);
+        for offset in input.char_indices().map(|(offset, _)| offset) {
+            assert_eq!(index.offset(index.position(offset)), Some(offset));
+        }
+    }
+}
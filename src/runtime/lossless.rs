@@ -0,0 +1,121 @@
+use crate::{Match, Span};
+
+/// A violation of the lossless property of a token stream found by [verify_lossless].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LosslessViolation {
+    /// The part of the input is not covered by any token.
+    Gap(Span),
+    /// The part of the input is covered by more than one token.
+    Overlap(Span),
+    /// The span of a token lies outside of the input or splits a character.
+    InvalidSpan(Span),
+}
+
+impl std::fmt::Display for LosslessViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LosslessViolation::Gap(span) => {
+                write!(f, "Input at {} is not covered by a token", span)
+            }
+            LosslessViolation::Overlap(span) => {
+                write!(f, "Input at {} is covered by more than one token", span)
+            }
+            LosslessViolation::InvalidSpan(span) => write!(f, "Invalid token span {}", span),
+        }
+    }
+}
+
+impl std::error::Error for LosslessViolation {}
+
+/// Verifies that the concatenation of the spans of the given tokens reconstructs the input
+/// exactly, i.e. the tokens are ordered, don't overlap and leave no gaps.
+/// Trivia and error tokens have to be part of the tokens for this to hold.
+///
+/// The first violation found is returned as error.
+/// A scanner with an unmatched token type (see [crate::Scanner::set_unmatched_token_type])
+/// produces token streams that always pass this check.
+pub fn verify_lossless(input: &str, tokens: &[Match]) -> Result<(), LosslessViolation> {
+    let mut position = 0;
+    for token in tokens {
+        let span = token.span();
+        if span.end < span.start
+            || !input.is_char_boundary(span.start)
+            || !input.is_char_boundary(span.end)
+        {
+            return Err(LosslessViolation::InvalidSpan(span));
+        }
+        if span.start > position {
+            return Err(LosslessViolation::Gap(Span::new(position, span.start)));
+        }
+        if span.start < position {
+            return Err(LosslessViolation::Overlap(Span::new(
+                span.start,
+                position.min(span.end),
+            )));
+        }
+        position = span.end;
+    }
+    if position < input.len() {
+        return Err(LosslessViolation::Gap(Span::new(position, input.len())));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        runtime::generated::scanner_with_modes::{create_find_iter, create_scanner},
+        TokenType,
+    };
+
+    #[test]
+    fn test_verify_lossless() {
+        let tokens = [
+            Match::new(0.into(), (0usize..2).into()),
+            Match::new(1.into(), (2usize..4).into()),
+        ];
+        assert_eq!(verify_lossless("abcd", &tokens), Ok(()));
+        assert_eq!(
+            verify_lossless("abcde", &tokens),
+            Err(LosslessViolation::Gap(Span::new(4, 5)))
+        );
+        assert_eq!(
+            verify_lossless("abcd", &tokens[1..]),
+            Err(LosslessViolation::Gap(Span::new(0, 2)))
+        );
+        let overlapping = [
+            Match::new(0.into(), (0usize..3).into()),
+            Match::new(1.into(), (2usize..4).into()),
+        ];
+        assert_eq!(
+            verify_lossless("abcd", &overlapping),
+            Err(LosslessViolation::Overlap(Span::new(2, 3)))
+        );
+        let splitting = [Match::new(0.into(), (0usize..1).into())];
+        assert_eq!(
+            verify_lossless("ä", &splitting),
+            Err(LosslessViolation::InvalidSpan(Span::new(0, 1)))
+        );
+    }
+
+    #[test]
+    fn test_unmatched_token_type() {
+        let input = "a \\\\ b";
+        let mut scanner = create_scanner();
+        // Without the error token, the backslashes are not matched in mode INITIAL
+        scanner.scanner_modes[0]
+            .dfas
+            .retain(|dfa| dfa.token_type() != TokenType::new(9));
+        let tokens = create_find_iter(&scanner, input).collect::<Vec<_>>();
+        assert!(matches!(
+            verify_lossless(input, &tokens),
+            Err(LosslessViolation::Gap(_))
+        ));
+
+        scanner.set_unmatched_token_type(Some(TokenType::new(99)));
+        let tokens = create_find_iter(&scanner, input).collect::<Vec<_>>();
+        assert_eq!(verify_lossless(input, &tokens), Ok(()));
+        assert!(tokens.contains(&Match::new(99.into(), (2usize..4).into())));
+    }
+}
@@ -0,0 +1,29 @@
+/// Defines how the matches of an iteration relate to each other.
+///
+/// It can be set with [crate::Scanner::set_match_kind].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MatchKind {
+    /// Input that no token matches is skipped. The next match starts at the next position where a
+    /// token matches. This is the default.
+    #[default]
+    Unanchored,
+    /// Each match starts exactly where the previous one ended, i.e. the tokens cover the input
+    /// without gaps. Parsers that treat skipped input as a syntax error want this strictness.
+    ///
+    /// If no token matches at the current position, the iteration ends there instead of skipping
+    /// the input. If `error_on_gap` is true, [crate::FindMatches::try_next_match] reports the gap
+    /// as [crate::FindOutcome::Gap]. Input covered by the error token type of the scanner mode or
+    /// by the [crate::Scanner::unmatched_token_type] is no gap.
+    TokenAnchored {
+        /// Report a gap as [crate::FindOutcome::Gap] instead of [crate::FindOutcome::NotFound].
+        error_on_gap: bool,
+    },
+}
+
+impl MatchKind {
+    /// Returns true if each match must start where the previous one ended.
+    #[inline]
+    pub fn is_token_anchored(&self) -> bool {
+        matches!(self, MatchKind::TokenAnchored { .. })
+    }
+}
@@ -1,3 +1,31 @@
+// The runtime must not panic on any input, so services can tokenize untrusted input. Functions
+// that panic on misuse of their API document it and allow the lint explicitly.
+#![cfg_attr(
+    not(test),
+    deny(
+        clippy::panic,
+        clippy::unwrap_used,
+        clippy::expect_used,
+        clippy::indexing_slicing,
+        clippy::string_slice,
+        clippy::unreachable,
+        clippy::todo,
+        clippy::unimplemented
+    )
+)]
+
+mod ambiguity;
+pub use ambiguity::Ambiguity;
+
+mod completion;
+pub use completion::Completion;
+
+mod find_outcome;
+pub use find_outcome::FindOutcome;
+
+mod match_kind;
+pub use match_kind::MatchKind;
+
 mod dfa;
 pub use dfa::Dfa;
 pub(crate) use dfa::DfaWithTokenType;
@@ -5,17 +33,94 @@ pub(crate) use dfa::DfaWithTokenType;
 mod scanner;
 pub use scanner::Scanner;
 
+mod build_error;
+pub use build_error::BuildError;
+
 mod scanner_builder;
+#[allow(deprecated)]
 pub use scanner_builder::{
     ScannerBuilder, ScannerBuilderWithScannerModes, ScannerBuilderWithsDfas,
     ScannerBuilderWithsDfasAndScannerModes,
 };
 
+mod composition;
+pub use composition::ScannerComposer;
+
+mod scanner_def;
+pub use scanner_def::ScannerDef;
+
 mod scanner_mode;
-pub use scanner_mode::ScannerMode;
+pub use scanner_mode::{ModeInfo, ScannerMode};
+
+mod start_set;
+pub(crate) use start_set::{ranges_contain, StartSet};
 
 mod find_matches;
-pub use find_matches::{FindMatches, PeekResult};
+pub use find_matches::FindMatches;
+#[cfg(feature = "peek")]
+pub use find_matches::{PeekIntoResult, PeekResult};
+
+mod token;
+pub use token::{OwnedToken, Token, TokenIter};
+
+mod token_value;
+pub use token_value::{ConversionError, TokenValueConverters, TypedToken, ValueConverter};
+
+mod string_decoding;
+pub use string_decoding::{
+    decode_escape, DecodedString, FoldStrings, StringItem, StringTokenTypes,
+};
+
+mod cooking;
+pub use cooking::{CookRule, CookRules, CookedTokens};
+
+mod interning;
+pub use interning::{InternTokens, InternedMatch, Interner, StringInterner, Symbol};
+
+mod lossless;
+pub use lossless::{verify_lossless, LosslessViolation};
+
+mod scanner_diff;
+pub use scanner_diff::{
+    diff_scanners, BehaviorDifference, PatternChange, ScannerDiff, StateCountChange,
+    TokenRenumbering,
+};
+
+mod trivia;
+pub use trivia::{AttachTrivia, TokenWithTrivia, TriviaAttachment};
+
+mod line_groups;
+pub use line_groups::LineGroups;
+
+mod positions;
+pub use positions::{ColumnConfig, Position, Positions};
+
+mod line_index;
+pub use line_index::LineIndex;
+
+mod source_map;
+pub use source_map::{SourceFile, SourceId, SourceMap, SourceMatch};
+
+mod json;
+pub use json::JSON_FORMAT_VERSION;
+
+mod spec;
+
+mod reloadable;
+pub use reloadable::ReloadableScanner;
+
+mod registry;
+pub use registry::ScannerRegistry;
+
+mod cursor;
+pub(crate) use cursor::Utf8Cursor;
+
+mod decoding;
+pub(crate) use decoding::SeekCharSource;
+pub use decoding::{CharSource, DecodedCharIndices, Encoding, FindDecodedMatches};
+
+mod segments;
+pub use segments::{FindSegmentedMatches, SegmentedCharIndices};
 
 #[cfg(test)]
 mod generated;
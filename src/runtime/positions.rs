@@ -0,0 +1,230 @@
+use crate::Match;
+
+use super::LineIndex;
+
+/// A position in the haystack given as 1-based line and column numbers.
+///
+/// How the column is counted is determined by a [ColumnConfig].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Position {
+    /// The 1-based line number.
+    pub line: usize,
+    /// The 1-based column number.
+    pub column: usize,
+}
+
+impl Position {
+    /// Creates a new position.
+    pub fn new(line: usize, column: usize) -> Self {
+        Position { line, column }
+    }
+}
+
+impl Default for Position {
+    fn default() -> Self {
+        Position { line: 1, column: 1 }
+    }
+}
+
+impl std::fmt::Display for Position {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+/// The configuration of how columns are counted.
+///
+/// Compilers and editors disagree on the columns of tabs and wide characters. By default each
+/// character, including a tab, occupies one column. With tab stops a tab advances the column to
+/// the next tab stop. With the feature `unicode-width` the display width of the characters can be
+/// used instead, i.e. east asian wide characters occupy two columns and combining marks none.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ColumnConfig {
+    tab_width: usize,
+    unicode_width: bool,
+}
+
+impl ColumnConfig {
+    /// Creates a configuration that counts each character as one column.
+    pub const fn new() -> Self {
+        ColumnConfig {
+            tab_width: 1,
+            unicode_width: false,
+        }
+    }
+
+    /// Sets the distance between two tab stops. A tab advances the column to the next tab stop.
+    /// A width of zero is treated as one.
+    pub const fn with_tab_stops(mut self, tab_width: usize) -> Self {
+        self.tab_width = if tab_width == 0 { 1 } else { tab_width };
+        self
+    }
+
+    /// Counts the display width of the characters instead of the characters themselves.
+    #[cfg(feature = "unicode-width")]
+    pub const fn with_unicode_width(mut self, unicode_width: bool) -> Self {
+        self.unicode_width = unicode_width;
+        self
+    }
+
+    /// Returns the distance between two tab stops.
+    pub fn tab_width(&self) -> usize {
+        self.tab_width
+    }
+
+    /// Returns the column after the given character that starts at the given column.
+    pub fn advance(&self, column: usize, c: char) -> usize {
+        match c {
+            '\t' => (column - 1) / self.tab_width * self.tab_width + self.tab_width + 1,
+            _ => column + self.char_width(c),
+        }
+    }
+
+    /// Returns the position after the given text that starts at the given position.
+    /// A line feed starts a new line.
+    pub fn advance_str(&self, position: Position, text: &str) -> Position {
+        text.chars().fold(position, |position, c| match c {
+            '\n' => Position::new(position.line + 1, 1),
+            _ => Position::new(position.line, self.advance(position.column, c)),
+        })
+    }
+
+    #[cfg(feature = "unicode-width")]
+    fn char_width(&self, c: char) -> usize {
+        if self.unicode_width {
+            unicode_width::UnicodeWidthChar::width(c).unwrap_or(0)
+        } else {
+            1
+        }
+    }
+
+    #[cfg(not(feature = "unicode-width"))]
+    fn char_width(&self, _c: char) -> usize {
+        1
+    }
+}
+
+impl Default for ColumnConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// An iterator adapter that yields the matches along with the positions of their start and end.
+///
+/// The matches are expected in ascending order of their start positions, as they are yielded by
+/// [crate::FindMatches]. The positions are looked up in a [LineIndex] of the haystack.
+///
+/// This adapter can be created with [crate::FindMatches::with_positions] or [Positions::new].
+#[derive(Debug)]
+pub struct Positions<'h, I>
+where
+    I: Iterator<Item = Match>,
+{
+    line_index: LineIndex<'h>,
+    matches: I,
+}
+
+impl<'h, I> Positions<'h, I>
+where
+    I: Iterator<Item = Match>,
+{
+    /// Creates a new adapter over the given matches found in the given input.
+    pub fn new<M>(input: &'h str, matches: M, config: ColumnConfig) -> Self
+    where
+        M: IntoIterator<IntoIter = I>,
+    {
+        Positions {
+            line_index: LineIndex::new(input).with_column_config(config),
+            matches: matches.into_iter(),
+        }
+    }
+
+    /// Creates a new adapter over the given matches whose positions are looked up in the given
+    /// line index, e.g. one created with [LineIndex::with_universal_line_breaks].
+    pub fn with_line_index<M>(line_index: LineIndex<'h>, matches: M) -> Self
+    where
+        M: IntoIterator<IntoIter = I>,
+    {
+        Positions {
+            line_index,
+            matches: matches.into_iter(),
+        }
+    }
+
+    /// Returns the line index the positions are looked up in.
+    pub fn line_index(&self) -> &LineIndex<'h> {
+        &self.line_index
+    }
+}
+
+impl<I> Iterator for Positions<'_, I>
+where
+    I: Iterator<Item = Match>,
+{
+    type Item = (Match, Position, Position);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let matched = self.matches.next()?;
+        let (start, end) = self.line_index.span_positions(matched.span());
+        Some((matched, start, end))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::generated::scanner_with_modes::{create_find_iter, create_scanner};
+
+    #[test]
+    fn test_tab_stops() {
+        let config = ColumnConfig::new().with_tab_stops(4);
+        assert_eq!(config.advance(1, '\t'), 5);
+        assert_eq!(config.advance(3, '\t'), 5);
+        assert_eq!(config.advance(5, '\t'), 9);
+        assert_eq!(
+            config.advance_str(Position::default(), "a\tb\n\tc"),
+            Position::new(2, 6)
+        );
+        assert_eq!(
+            ColumnConfig::new().advance_str(Position::default(), "a\tb"),
+            Position::new(1, 4)
+        );
+    }
+
+    #[cfg(feature = "unicode-width")]
+    #[test]
+    fn test_unicode_width() {
+        let config = ColumnConfig::new().with_unicode_width(true);
+        assert_eq!(
+            config.advance_str(Position::default(), "中文a"),
+            Position::new(1, 6)
+        );
+        // A combining mark occupies no column
+        assert_eq!(
+            config.advance_str(Position::default(), "e\u{301}"),
+            Position::new(1, 2)
+        );
+    }
+
+    #[test]
+    fn test_positions() {
+        let input = "a\tb\n  c";
+        let scanner = create_scanner();
+        let positions = create_find_iter(&scanner, input)
+            .with_positions(ColumnConfig::new().with_tab_stops(8))
+            .map(|(matched, start, end)| (&input[matched.range()], start, end))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            positions,
+            vec![
+                ("a", Position::new(1, 1), Position::new(1, 2)),
+                ("\t", Position::new(1, 2), Position::new(1, 9)),
+                ("b", Position::new(1, 9), Position::new(1, 10)),
+                ("\n", Position::new(1, 10), Position::new(2, 1)),
+                ("  ", Position::new(2, 1), Position::new(2, 3)),
+                ("c", Position::new(2, 3), Position::new(2, 4)),
+            ]
+        );
+    }
+}
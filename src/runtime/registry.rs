@@ -0,0 +1,255 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, PoisonError, RwLock},
+};
+
+use super::{dfa::CharClasses, BuildError, FindMatches, Scanner, ScannerBuilder};
+
+/// A registered scanner along with the match function its searches use.
+#[derive(Debug)]
+struct Entry {
+    scanner: Arc<Scanner>,
+    matches_char_class: fn(char, usize) -> bool,
+}
+
+#[derive(Debug, Default)]
+struct Registry {
+    /// The versions of the scanners by name.
+    scanners: HashMap<String, BTreeMap<u32, Entry>>,
+    /// The tables of character classes that are shared by the registered scanners.
+    char_classes: Vec<CharClasses>,
+}
+
+impl Registry {
+    /// Releases the tables of character classes that only the registry itself holds, i.e. that
+    /// belonged to scanners which were removed and aren't in use anymore.
+    fn release_unused_char_classes(&mut self) {
+        self.char_classes
+            .retain(|char_classes| Arc::strong_count(char_classes) > 1);
+    }
+}
+
+/// A registry of named and versioned scanners, e.g. one per log format, for services that
+/// tokenize many formats concurrently.
+///
+/// The registry can be shared between threads. Lookups return the registered scanner as an
+/// [Arc], so searches don't hold a lock and scanners can be registered or removed while others
+/// are in use. Scanners that are loaded from JSON share equal tables of character classes, so
+/// versions of the same format don't keep copies of them.
+///
+/// ```
+/// use scangen::ScannerRegistry;
+///
+/// // A definition as exported by `Scanner::definition_to_json` with one token of the
+/// // characters `a-z`
+/// let json = r#"{"format":"scangen","version":1,"classes":[[[97,122]]],
+///     "dfas":[{"pattern":"[a-z]+","accepting_states":[1],"states":[[[0,1]],[[0,1]]]}],
+///     "modes":[{"name":"INITIAL","dfas":[[0,0]],"transitions":[],
+///     "error_token_type":null,"counting":null}]}"#;
+/// let registry = ScannerRegistry::new();
+/// registry.register_json("words", 1, json).unwrap();
+/// assert_eq!(registry.latest_version("words"), Some(1));
+/// let matches = registry.find_iter("words", None, "ab 12").unwrap();
+/// assert_eq!(matches.map(|m| m.range()).collect::<Vec<_>>(), vec![0..2]);
+/// ```
+#[derive(Debug, Default)]
+pub struct ScannerRegistry {
+    registry: RwLock<Registry>,
+}
+
+impl ScannerRegistry {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the scanner under the given name and version. The match function is used by
+    /// the searches of generated scanners, scanners loaded from JSON don't need it.
+    /// Returns the scanner that was registered under the same name and version before, if any.
+    pub fn register(
+        &self,
+        name: &str,
+        version: u32,
+        mut scanner: Scanner,
+        matches_char_class: fn(char, usize) -> bool,
+    ) -> Option<Arc<Scanner>> {
+        let mut registry = self.write();
+        registry.release_unused_char_classes();
+        scanner.share_char_classes(&mut registry.char_classes);
+        let entry = Entry {
+            scanner: Arc::new(scanner),
+            matches_char_class,
+        };
+        registry
+            .scanners
+            .entry(name.to_string())
+            .or_default()
+            .insert(version, entry)
+            .map(|entry| entry.scanner)
+    }
+
+    /// Builds a scanner from its definition in JSON, see [ScannerBuilder::build_from_json], and
+    /// registers it under the given name and version.
+    ///
+    /// # Errors
+    /// Returns the errors of [ScannerBuilder::build_from_json]. The registry isn't changed in
+    /// this case.
+    pub fn register_json(&self, name: &str, version: u32, json: &str) -> Result<(), BuildError> {
+        let scanner = ScannerBuilder::build_from_json(json)?;
+        // The DFAs of the scanner know the characters of their classes.
+        self.register(name, version, scanner, |_, _| false);
+        Ok(())
+    }
+
+    /// Removes the scanner with the given name and version and returns it. Searches that use it
+    /// aren't affected.
+    pub fn remove(&self, name: &str, version: u32) -> Option<Arc<Scanner>> {
+        let mut registry = self.write();
+        let versions = registry.scanners.get_mut(name)?;
+        let entry = versions.remove(&version)?;
+        if versions.is_empty() {
+            registry.scanners.remove(name);
+        }
+        registry.release_unused_char_classes();
+        Some(entry.scanner)
+    }
+
+    /// Returns the scanner with the given name and version.
+    pub fn get(&self, name: &str, version: u32) -> Option<Arc<Scanner>> {
+        self.read_entry(name, Some(version), |entry| Arc::clone(&entry.scanner))
+    }
+
+    /// Returns the scanner with the given name and the highest version.
+    pub fn latest(&self, name: &str) -> Option<Arc<Scanner>> {
+        self.read_entry(name, None, |entry| Arc::clone(&entry.scanner))
+    }
+
+    /// Returns the highest version of the scanner with the given name.
+    pub fn latest_version(&self, name: &str) -> Option<u32> {
+        self.read()
+            .scanners
+            .get(name)
+            .and_then(|versions| versions.keys().next_back().copied())
+    }
+
+    /// Returns the versions of the scanner with the given name in ascending order.
+    pub fn versions(&self, name: &str) -> Vec<u32> {
+        self.read()
+            .scanners
+            .get(name)
+            .map(|versions| versions.keys().copied().collect())
+            .unwrap_or_default()
+    }
+
+    /// Returns the names of the registered scanners in ascending order.
+    pub fn names(&self) -> Vec<String> {
+        let mut names = self.read().scanners.keys().cloned().collect::<Vec<_>>();
+        names.sort();
+        names
+    }
+
+    /// Returns the number of distinct tables of character classes that are used by the
+    /// scanners of the registry.
+    pub fn char_class_table_count(&self) -> usize {
+        self.read()
+            .char_classes
+            .iter()
+            .filter(|char_classes| Arc::strong_count(char_classes) > 1)
+            .count()
+    }
+
+    /// Returns an iterator over all non-overlapping matches of the scanner with the given name
+    /// and version, see [Scanner::find_iter]. If no version is given, the highest one is used.
+    /// Returns None if no such scanner is registered.
+    pub fn find_iter<'h>(
+        &self,
+        name: &str,
+        version: Option<u32>,
+        input: &'h str,
+    ) -> Option<FindMatches<'h>> {
+        self.read_entry(name, version, |entry| {
+            entry.scanner.find_iter(input, entry.matches_char_class)
+        })
+    }
+
+    /// Applies the function to the entry with the given name and version, or the highest version
+    /// if none is given.
+    fn read_entry<R>(
+        &self,
+        name: &str,
+        version: Option<u32>,
+        f: impl FnOnce(&Entry) -> R,
+    ) -> Option<R> {
+        let registry = self.read();
+        let versions = registry.scanners.get(name)?;
+        let entry = match version {
+            Some(version) => versions.get(&version),
+            None => versions.values().next_back(),
+        }?;
+        Some(f(entry))
+    }
+
+    // The lock only guards the maps of the registry, which are never left in an inconsistent
+    // state, so a poisoned lock can be used safely.
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, Registry> {
+        self.registry.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn write(&self) -> std::sync::RwLockWriteGuard<'_, Registry> {
+        self.registry
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::generated::scanner_with_modes;
+
+    #[test]
+    fn test_scanner_registry() {
+        let scanner = scanner_with_modes::create_scanner();
+        let json = scanner.definition_to_json(|_, _| unreachable!());
+        let input = "id \"a\" 1";
+        let expected = scanner_with_modes::create_find_iter(&scanner, input).collect::<Vec<_>>();
+
+        let registry = ScannerRegistry::new();
+        registry.register_json("modes", 2, &json).unwrap();
+        registry.register_json("modes", 1, &json).unwrap();
+        assert!(registry.register_json("modes", 3, "{}").is_err());
+        assert_eq!(registry.versions("modes"), vec![1, 2]);
+        assert_eq!(registry.latest_version("modes"), Some(2));
+        // Both versions share their table of character classes
+        assert_eq!(registry.char_class_table_count(), 1);
+        let (first, second) = (
+            registry.get("modes", 1).unwrap(),
+            registry.latest("modes").unwrap(),
+        );
+        assert!(Arc::ptr_eq(
+            first.dfas[0].char_classes.as_ref().unwrap(),
+            second.dfas[0].char_classes.as_ref().unwrap()
+        ));
+        drop((first, second));
+
+        // A generated scanner uses its match function
+        registry.register("generated", 1, scanner.clone(), |_, _| false);
+        assert_eq!(registry.names(), vec!["generated", "modes"]);
+        assert_eq!(
+            registry
+                .find_iter("modes", Some(1), input)
+                .unwrap()
+                .collect::<Vec<_>>(),
+            expected
+        );
+        assert!(registry.find_iter("modes", Some(3), input).is_none());
+        assert!(registry.find_iter("unknown", None, input).is_none());
+
+        assert!(registry.remove("modes", 1).is_some());
+        assert_eq!(registry.char_class_table_count(), 1);
+        assert!(registry.remove("modes", 2).is_some());
+        assert_eq!(registry.char_class_table_count(), 0);
+        assert_eq!(registry.names(), vec!["generated"]);
+        assert!(registry.remove("modes", 2).is_none());
+    }
+}
@@ -0,0 +1,130 @@
+use std::sync::{Arc, PoisonError, RwLock};
+
+use super::{BuildError, FindMatches, Scanner, ScannerBuilder};
+
+/// A scanner whose definition can be replaced while it is in use, e.g. by long-running services
+/// that load updated token definitions without a restart.
+///
+/// The scanner is swapped atomically. Each search takes a snapshot of the scanner that is current
+/// when the search starts, so iterators that are in flight keep the old definition until they
+/// are dropped, while new searches use the new one.
+///
+/// ```
+/// use scangen::{ReloadableScanner, ScannerBuilder};
+///
+/// // Definitions as exported by `Scanner::definition_to_json`, with one token of the characters
+/// // `a-z` and `0-9` respectively.
+/// let definition = |first: u32, last: u32| {
+///     format!(
+///         r#"{{"format":"scangen","version":1,"classes":[[[{first},{last}]]],
+///             "dfas":[{{"pattern":"","accepting_states":[1],"states":[[[0,1]],[[0,1]]]}}],
+///             "modes":[{{"name":"INITIAL","dfas":[[0,0]],"transitions":[],
+///             "error_token_type":null,"counting":null}}]}}"#
+///     )
+/// };
+/// let words = ScannerBuilder::build_from_json(&definition('a' as u32, 'z' as u32)).unwrap();
+/// let reloadable = ReloadableScanner::new(words, |_, _| false);
+/// let in_flight = reloadable.find_iter("ab 12");
+///
+/// reloadable.reload_from_json(&definition('0' as u32, '9' as u32)).unwrap();
+/// // The iterator that was created before keeps the old definition
+/// assert_eq!(in_flight.map(|m| m.range()).collect::<Vec<_>>(), vec![0..2]);
+/// assert_eq!(reloadable.find_iter("ab 12").map(|m| m.range()).collect::<Vec<_>>(), vec![3..5]);
+/// ```
+#[derive(Debug)]
+pub struct ReloadableScanner {
+    scanner: RwLock<Arc<Scanner>>,
+    matches_char_class: fn(char, usize) -> bool,
+}
+
+impl ReloadableScanner {
+    /// Creates a new reloadable scanner with the given initial scanner. The match function is
+    /// used by the searches of generated scanners. Scanners that are loaded from JSON don't need
+    /// it because their DFAs know the characters of their classes.
+    pub fn new(scanner: Scanner, matches_char_class: fn(char, usize) -> bool) -> Self {
+        ReloadableScanner {
+            scanner: RwLock::new(Arc::new(scanner)),
+            matches_char_class,
+        }
+    }
+
+    /// Returns the scanner that is current at the time of the call. Replacing the scanner later
+    /// doesn't affect the returned one.
+    pub fn current(&self) -> Arc<Scanner> {
+        // The lock only guards the swap of an `Arc`, which can't leave the scanner in an
+        // inconsistent state, so a poisoned lock can be used safely.
+        self.scanner
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Replaces the scanner and returns the previous one. Searches that already started keep
+    /// the previous scanner.
+    pub fn replace(&self, scanner: Scanner) -> Arc<Scanner> {
+        let mut current = self.scanner.write().unwrap_or_else(PoisonError::into_inner);
+        std::mem::replace(&mut *current, Arc::new(scanner))
+    }
+
+    /// Builds a scanner from its definition in JSON, see [ScannerBuilder::build_from_json], and
+    /// replaces the current scanner with it.
+    ///
+    /// # Errors
+    /// Returns the errors of [ScannerBuilder::build_from_json]. The current scanner is kept in
+    /// this case.
+    pub fn reload_from_json(&self, json: &str) -> Result<(), BuildError> {
+        let scanner = ScannerBuilder::build_from_json(json)?;
+        self.replace(scanner);
+        Ok(())
+    }
+
+    /// Returns an iterator over all non-overlapping matches of the current scanner, see
+    /// [Scanner::find_iter]. The iterator keeps using this scanner if it is replaced meanwhile.
+    pub fn find_iter<'h>(&self, input: &'h str) -> FindMatches<'h> {
+        self.current().find_iter(input, self.matches_char_class)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::generated::scanner_with_modes;
+
+    #[test]
+    fn test_reloadable_scanner() {
+        let scanner = scanner_with_modes::create_scanner();
+        let json = scanner.definition_to_json(|_, _| unreachable!());
+        let imported = ScannerBuilder::build_from_json(&json).unwrap();
+        let reloadable = ReloadableScanner::new(imported, |_, _| unreachable!());
+        let input = "id \"a\" 1";
+        let expected = scanner_with_modes::create_find_iter(&scanner, input).collect::<Vec<_>>();
+        let mut in_flight = reloadable.find_iter(input);
+        let first = in_flight.next();
+
+        let words_json = r#"{"format":"scangen","version":1,"classes":[[[97,122]]],
+            "dfas":[{"pattern":"[a-z]+","accepting_states":[1],"states":[[[0,1]],[[0,1]]]}],
+            "modes":[{"name":"INITIAL","dfas":[[0,0]],"transitions":[],
+            "error_token_type":null,"counting":null}]}"#;
+        let previous = reloadable.replace(ScannerBuilder::build_from_json(words_json).unwrap());
+        assert_eq!(previous.mode_name(0.into()), Some("INITIAL"));
+
+        // The iterator that was created before the swap keeps the old definition
+        assert_eq!(
+            first.into_iter().chain(in_flight).collect::<Vec<_>>(),
+            expected
+        );
+        assert_eq!(
+            reloadable
+                .find_iter(input)
+                .map(|m| m.range())
+                .collect::<Vec<_>>(),
+            vec![0..2, 4..5]
+        );
+
+        // A definition that fails to load keeps the current scanner
+        assert!(reloadable.reload_from_json("{}").is_err());
+        assert_eq!(reloadable.find_iter(input).count(), 2);
+        reloadable.reload_from_json(&json).unwrap();
+        assert_eq!(reloadable.find_iter(input).collect::<Vec<_>>(), expected);
+    }
+}
@@ -1,6 +1,15 @@
-use crate::common::Match;
+use std::{collections::HashMap, ops::Range, sync::Arc};
 
-use super::{Dfa, FindMatches, ScannerMode};
+use crate::{
+    common::Match, CaseInsensitiveKeywordData, ContextualKeywordData, OwnedDfaData, ScannerModeId,
+    Span, TokenType,
+};
+
+use super::{
+    dfa::CharClasses, json, spec, Ambiguity, CharSource, Completion, Dfa, Encoding,
+    FindDecodedMatches, FindMatches, FindOutcome, FindSegmentedMatches, MatchKind, ModeInfo,
+    ScannerMode, SeekCharSource, Token, TokenIter,
+};
 
 /// A Scanner.
 /// It consists of multiple DFAs that are used to search for matches.
@@ -18,17 +27,84 @@ use super::{Dfa, FindMatches, ScannerMode};
 /// The default mode contains all DFAs and assigns incrementing token type numbers to them.
 /// The default mode is named `INITIAL`.
 ///
+/// # Tie-breaking
+/// If more than one DFA matches, the scanner applies the following rules in this order:
+/// 1. The match with the lowest start position wins.
+/// 2. The longest match wins.
+/// 3. If several matches have the same span, the match of the DFA that comes first in the current
+///    scanner mode wins. In the default mode this is the pattern with the lowest index.
+///
+/// Use [Scanner::priority] to query the priority of a token type in the current mode.
+/// To debug grammars, recording of ties can be enabled with [Scanner::set_record_ambiguities].
 #[derive(Debug, Clone)]
 pub struct Scanner {
     /// The DFAs that are used to search for matches.
-    pub(crate) dfas: Vec<Dfa>,
+    /// They are shared with the scanner modes, so each DFA exists only once in memory.
+    pub(crate) dfas: Vec<Arc<Dfa>>,
     /// The scanner modes that are used to search for matches.
     pub(crate) scanner_modes: Vec<ScannerMode>,
     /// The current scanner mode.
-    pub(crate) current_mode: usize,
+    pub(crate) current_mode: ScannerModeId,
+    /// If true, ambiguities are recorded during the search.
+    pub(crate) record_ambiguities: bool,
+    /// The ambiguities recorded so far.
+    pub(crate) ambiguities: Vec<Ambiguity>,
+    /// The maximum length of a token in bytes along with the token type of the error token that
+    /// is emitted if the maximum length is exceeded.
+    pub(crate) max_token_length: Option<(usize, TokenType)>,
+    /// The maximum number of characters examined by a single search.
+    pub(crate) step_budget: Option<usize>,
+    /// If true, the end of the input is not the end of the text, e.g. in a REPL.
+    pub(crate) partial_input: bool,
+    /// Defines whether matches must be contiguous.
+    pub(crate) match_kind: MatchKind,
+    /// The token type of the tokens that cover input no pattern matches.
+    pub(crate) unmatched_token_type: Option<TokenType>,
+    /// The token type of the newline terminal, if the scanner has one.
+    pub(crate) newline_token_type: Option<TokenType>,
+    /// The token types of the matches that the iterators skip, e.g. of the whitespace terminal.
+    pub(crate) skipped_token_types: Vec<TokenType>,
+    /// The contextual keywords of the scanner modes, see [Scanner::reinterpret].
+    pub(crate) contextual_keywords: Vec<ContextualKeywordData>,
+    /// The case-insensitive keywords of the scanner modes, see [CaseInsensitiveKeywordData].
+    pub(crate) case_insensitive_keywords: Vec<CaseInsensitiveKeywordData>,
+    /// The nesting depth of the current counting mode.
+    pub(crate) nesting_depth: usize,
 }
 
 impl Scanner {
+    /// Creates a new scanner from the given DFAs and scanner modes.
+    /// The nesting depth starts at one if the initial scanner mode is a counting mode.
+    pub(crate) fn new(dfas: Vec<Arc<Dfa>>, scanner_modes: Vec<ScannerMode>) -> Self {
+        let mut scanner = Scanner {
+            dfas,
+            scanner_modes,
+            current_mode: ScannerModeId::default(),
+            record_ambiguities: false,
+            ambiguities: Vec::new(),
+            max_token_length: None,
+            step_budget: None,
+            partial_input: false,
+            match_kind: MatchKind::Unanchored,
+            unmatched_token_type: None,
+            newline_token_type: None,
+            skipped_token_types: Vec::new(),
+            contextual_keywords: Vec::new(),
+            case_insensitive_keywords: Vec::new(),
+            nesting_depth: 0,
+        };
+        scanner.nesting_depth = scanner.initial_nesting_depth(scanner.current_mode);
+        scanner
+    }
+
+    /// Returns the nesting depth a scanner mode starts at, i.e. one for a counting mode and zero
+    /// otherwise.
+    pub(crate) fn initial_nesting_depth(&self, mode: ScannerModeId) -> usize {
+        self.scanner_modes
+            .get(mode.as_usize())
+            .map_or(0, |mode| usize::from(mode.counting.is_some()))
+    }
+
     /// Returns an iterator over all non-overlapping matches.
     /// The iterator yields a [`Match`] value until no more matches could be found.
     pub fn find_iter<'h>(
@@ -39,122 +115,972 @@ impl Scanner {
         FindMatches::new(self.clone(), input, matches_char_class)
     }
 
+    /// Returns an iterator over all non-overlapping matches as [crate::Token]s, i.e. along with
+    /// the text they cover.
+    pub fn token_iter<'h>(
+        &self,
+        input: &'h str,
+        matches_char_class: fn(char, usize) -> bool,
+    ) -> TokenIter<'h> {
+        self.find_iter(input, matches_char_class).tokens()
+    }
+
+    /// Scans each of the given inputs and returns the matches of each input.
+    ///
+    /// All inputs share the tables of this scanner and the scratch space of the search is
+    /// allocated only once, which suits workloads like the ingestion of many documents by a
+    /// search engine. Each input is scanned from the current scanner mode of this scanner.
+    pub fn scan_many<'h, I>(
+        &self,
+        inputs: I,
+        matches_char_class: fn(char, usize) -> bool,
+    ) -> Vec<Vec<Match>>
+    where
+        I: IntoIterator<Item = &'h str>,
+    {
+        let mut results = Vec::new();
+        self.scan_many_into(inputs, matches_char_class, &mut results);
+        results
+    }
+
+    /// Scans each of the given inputs like [Scanner::scan_many] and stores the matches of the
+    /// n-th input in the n-th of the given buffers.
+    ///
+    /// The buffers are cleared, not freed, before they are filled, so a high-throughput pipeline
+    /// that passes the same buffers again avoids the allocation of the match vectors for each
+    /// document. Missing buffers are added and superfluous ones are removed.
+    pub fn scan_many_into<'h, I>(
+        &self,
+        inputs: I,
+        matches_char_class: fn(char, usize) -> bool,
+        buffers: &mut Vec<Vec<Match>>,
+    ) where
+        I: IntoIterator<Item = &'h str>,
+    {
+        let mut scanner = self.clone();
+        let mut count = 0;
+        for input in inputs {
+            scanner.current_mode = self.current_mode;
+            scanner.nesting_depth = self.nesting_depth;
+            scanner.ambiguities.clone_from(&self.ambiguities);
+            if buffers.len() == count {
+                buffers.push(Vec::new());
+            }
+            let Some(buffer) = buffers.get_mut(count) else {
+                break;
+            };
+            buffer.clear();
+            let mut find_iter = FindMatches::new(scanner, input, matches_char_class);
+            buffer.extend(find_iter.by_ref());
+            scanner = find_iter.into_scanner();
+            count += 1;
+        }
+        buffers.truncate(count);
+    }
+
+    /// Scans the input and stores its matches in the given buffer, which is cleared beforehand.
+    /// Passing the same buffer again avoids the allocation of a new match vector.
+    pub fn scan_into(
+        &self,
+        input: &str,
+        matches_char_class: fn(char, usize) -> bool,
+        buffer: &mut Vec<Match>,
+    ) {
+        buffer.clear();
+        buffer.extend(self.find_iter(input, matches_char_class));
+    }
+
+    /// Scans the given inputs like [Scanner::scan_many] on the given number of threads.
+    /// The matches are returned in the order of the inputs.
+    pub fn scan_many_parallel(
+        &self,
+        inputs: &[&str],
+        matches_char_class: fn(char, usize) -> bool,
+        threads: usize,
+    ) -> Vec<Vec<Match>> {
+        let chunk_size = inputs.len().div_ceil(threads.max(1)).max(1);
+        std::thread::scope(|scope| {
+            let handles = inputs
+                .chunks(chunk_size)
+                .map(|chunk| {
+                    scope.spawn(move || self.scan_many(chunk.iter().copied(), matches_char_class))
+                })
+                .collect::<Vec<_>>();
+            handles
+                .into_iter()
+                // A scanner thread only panics if the match function panics, so the panic is
+                // passed on to the caller.
+                .flat_map(|handle| {
+                    handle
+                        .join()
+                        .unwrap_or_else(|payload| std::panic::resume_unwind(payload))
+                })
+                .collect()
+        })
+    }
+
+    /// Returns an iterator over all non-overlapping matches in the given region of the input.
+    /// The search starts in the given scanner mode, e.g. the mode of an embedded language, and
+    /// ends at the end of the region. The spans of the matches are byte offsets in the whole
+    /// input, so the matches of several regions can be stitched together.
+    ///
+    /// A region whose bounds are not character boundaries of the input yields no matches.
+    pub fn find_iter_in_region<'h>(
+        &self,
+        input: &'h str,
+        region: Range<usize>,
+        mode: ScannerModeId,
+        matches_char_class: fn(char, usize) -> bool,
+    ) -> FindMatches<'h> {
+        let mut scanner = self.clone();
+        scanner.set_mode(mode);
+        let start = if input.is_char_boundary(region.start) {
+            region.start
+        } else {
+            region.end
+        };
+        let input = input.get(..region.end).unwrap_or_default();
+        FindMatches::new(scanner, input, matches_char_class).start_at(start)
+    }
+
+    /// Returns an iterator over the matches of all given regions of the input, each scanned with
+    /// [Scanner::find_iter_in_region] in its own scanner mode. The matches are yielded in the
+    /// order of the regions, e.g. the code fences of a Markdown document or the visible lines of
+    /// an editor.
+    pub fn find_iter_in_regions<'h>(
+        &self,
+        input: &'h str,
+        regions: Vec<(Range<usize>, ScannerModeId)>,
+        matches_char_class: fn(char, usize) -> bool,
+    ) -> impl Iterator<Item = Match> + 'h {
+        let scanner = self.clone();
+        regions.into_iter().flat_map(move |(region, mode)| {
+            scanner.find_iter_in_region(input, region, mode, matches_char_class)
+        })
+    }
+
+    /// Creates a new `FindDecodedMatches` iterator for an input in the given encoding.
+    /// The characters are decoded on the fly and the spans of the matches are byte offsets in the
+    /// given input.
+    pub fn find_iter_decoded<'h>(
+        &self,
+        input: &'h [u8],
+        encoding: Encoding,
+        matches_char_class: fn(char, usize) -> bool,
+    ) -> FindDecodedMatches<'h> {
+        FindDecodedMatches::new(self.clone(), input, encoding, matches_char_class)
+    }
+
+    /// Creates a new `FindDecodedMatches` iterator for UTF-8 input that may contain invalid
+    /// sequences, e.g. raw log data. Invalid sequences are decoded as replacement characters and
+    /// the spans of the matches are byte offsets in the given input, so no validation or copy is
+    /// needed before scanning. Use [FindDecodedMatches::set_invalid_token_type] to report invalid
+    /// sequences as error tokens instead.
+    pub fn find_iter_lossy<'h>(
+        &self,
+        input: &'h [u8],
+        matches_char_class: fn(char, usize) -> bool,
+    ) -> FindDecodedMatches<'h> {
+        FindDecodedMatches::new(self.clone(), input, Encoding::Utf8, matches_char_class)
+    }
+
+    /// Creates a new `FindDecodedMatches` iterator that detects the encoding of the input from its
+    /// byte order mark and skips it. If the input has no byte order mark, the given default
+    /// encoding is used.
+    /// The spans of the matches are byte offsets in the given input, i.e. they include the length
+    /// of the byte order mark.
+    pub fn find_iter_with_bom_detection<'h>(
+        &self,
+        input: &'h [u8],
+        default_encoding: Encoding,
+        matches_char_class: fn(char, usize) -> bool,
+    ) -> FindDecodedMatches<'h> {
+        FindDecodedMatches::with_bom_detection(
+            self.clone(),
+            input,
+            default_encoding,
+            matches_char_class,
+        )
+    }
+
+    /// Creates a new `FindSegmentedMatches` iterator for a text stored in several segments, e.g.
+    /// the chunks of a rope. The segments are scanned as if they were concatenated and the spans
+    /// of the matches are byte offsets in the concatenated text.
+    pub fn find_iter_segmented<'h, S>(
+        &self,
+        segments: S,
+        matches_char_class: fn(char, usize) -> bool,
+    ) -> FindSegmentedMatches<'h, S::IntoIter>
+    where
+        S: IntoIterator<Item = &'h str>,
+        S::IntoIter: Clone,
+    {
+        FindSegmentedMatches::new(self.clone(), segments, matches_char_class)
+    }
+
     /// Executes a leftmost search and returns the first match that is found, if one exists.
-    /// It starts the search at the position of the given CharIndices iterator.
+    /// It starts the search at the position of the given character source, e.g. a CharIndices
+    /// iterator.
     /// During the search, all DFAs are advanced in parallel by one character at a time.
-    pub fn find_from(
+    ///
+    /// If a step budget is set and exhausted, `None` is returned. Use [Scanner::try_find_from] to
+    /// distinguish this case from a failed search.
+    pub fn find_from<S: CharSource>(
         &mut self,
-        char_indices: std::str::CharIndices,
+        char_indices: S,
         matches_char_class: fn(char, usize) -> bool,
     ) -> Option<Match> {
-        let current_mode = &mut self.scanner_modes[self.current_mode];
-        for dfa in current_mode.dfas.iter_mut() {
-            dfa.reset();
-        }
-
-        // All indices of the DFAs that are still active.
-        let mut active_dfas = (0..current_mode.dfas.len()).collect::<Vec<_>>();
+        self.try_find_from(char_indices, matches_char_class)
+            .matched()
+    }
 
-        for (i, c) in char_indices {
-            for dfa_index in &active_dfas {
-                current_mode.dfas[*dfa_index].advance(i, c, matches_char_class);
+    /// Executes a leftmost search like [Scanner::find_from] and returns a [FindOutcome].
+    /// If a step budget is set with [Scanner::set_step_budget] and the search examines more
+    /// characters than the budget allows, the search is aborted with
+    /// [FindOutcome::BudgetExhausted].
+    pub fn try_find_from<S: CharSource>(
+        &mut self,
+        char_indices: S,
+        matches_char_class: fn(char, usize) -> bool,
+    ) -> FindOutcome {
+        let outcome = self.search(char_indices, matches_char_class);
+        if let FindOutcome::Found(matched) = outcome {
+            if self.record_ambiguities {
+                self.record_ambiguity(Some(matched));
             }
+            self.execute_possible_mode_switch(Some(matched));
+        }
+        outcome
+    }
 
-            // We remove all DFAs from `active_dfas` that finished or did not find a match so far.
-            active_dfas.retain(|&dfa_index| current_mode.dfas[dfa_index].search_for_longer_match());
+    /// Finds the next match from the position of the given character source and moves the
+    /// character source behind it. All iterators over the matches of the scanner are driven by
+    /// this function, so they treat skipped token types and unmatched input alike.
+    ///
+    /// Matches of the skipped token types are passed over, see [Scanner::set_skipped_token_types].
+    /// Input that no token matches is reported as a match of the [Scanner::error_token_type] if
+    /// there is one. Otherwise it is reported as [FindOutcome::Gap] with [MatchKind::TokenAnchored]
+    /// and `error_on_gap` set, and skipped with [MatchKind::Leftmost]. The character source isn't
+    /// moved if the step budget is exhausted or more input is needed.
+    pub(crate) fn find_next<S: SeekCharSource>(
+        &mut self,
+        char_indices: &mut S,
+        matches_char_class: fn(char, usize) -> bool,
+    ) -> FindOutcome {
+        loop {
+            match self.try_find_from(char_indices.clone(), matches_char_class) {
+                FindOutcome::Found(matched) => {
+                    if !matched.is_empty() {
+                        char_indices.seek(matched.span().end);
+                    }
+                    if self.is_skipped(matched.token_type()) {
+                        continue;
+                    }
+                    return FindOutcome::Found(matched);
+                }
+                outcome @ (FindOutcome::BudgetExhausted
+                | FindOutcome::NeedMoreInput
+                | FindOutcome::Gap(_)) => return outcome,
+                FindOutcome::NotFound => {
+                    if let Some(token_type) = self.error_token_type() {
+                        return match self.skip_unmatched(char_indices, matches_char_class) {
+                            Some(span) => FindOutcome::Found(Match::new(token_type, span)),
+                            None => FindOutcome::NotFound,
+                        };
+                    }
+                    if let MatchKind::TokenAnchored { error_on_gap } = self.match_kind() {
+                        return if error_on_gap && !char_indices.is_at_end() {
+                            FindOutcome::Gap(char_indices.offset())
+                        } else {
+                            FindOutcome::NotFound
+                        };
+                    }
+                    if char_indices.next().is_none() {
+                        return FindOutcome::NotFound;
+                    }
+                    self.skip_to_possible_start(char_indices);
+                }
+            }
+        }
+    }
 
-            // If all DFAs have finished, we can stop the search.
-            if active_dfas.is_empty() {
+    // Skips all characters up to the next position where a match starts and returns the span of
+    // the skipped characters. Returns None if the end of the input is already reached.
+    fn skip_unmatched<S: SeekCharSource>(
+        &mut self,
+        char_indices: &mut S,
+        matches_char_class: fn(char, usize) -> bool,
+    ) -> Option<Span> {
+        let (start, _) = char_indices.next()?;
+        loop {
+            self.skip_to_possible_start(char_indices);
+            if char_indices.is_at_end()
+                || self
+                    .peek_from(char_indices.clone(), matches_char_class)
+                    .is_some()
+            {
                 break;
             }
+            char_indices.next();
         }
+        Some(Span::new(start, char_indices.offset()))
+    }
 
-        let current_match = self.find_first_longest_match();
-        self.execute_possible_mode_switch(current_match);
-        current_match
+    /// Advances the character source to the next position whose character can start a token in
+    /// the current scanner mode. The skipped positions are rejected by the start set of the mode
+    /// without advancing any DFA.
+    pub(crate) fn skip_to_possible_start<S: CharSource>(&self, char_indices: &mut S) {
+        let Some(current_mode) = self.mode() else {
+            return;
+        };
+        let mut lookahead = char_indices.clone();
+        while let Some((_, c)) = lookahead.next() {
+            if current_mode.can_start_with(c) {
+                break;
+            }
+            *char_indices = lookahead.clone();
+        }
     }
 
-    /// This function is used by [super::find_matches::FindMatches::peek_n].
+    /// This function is used by the peek operations of [super::find_matches::FindMatches] and to
+    /// find the end of unmatched input.
     ///
     /// Executes a leftmost search and returns the first match that is found, if one exists.
     /// It starts the search at the position of the given CharIndices iterator.
     /// In contrast to `find_from`, this method does not execute a mode switch if a transition is
     /// defined for the token type found.
-    pub(crate) fn peek_from(
+    pub(crate) fn peek_from<S: CharSource>(
         &mut self,
-        char_indices: std::str::CharIndices,
+        char_indices: S,
         matches_char_class: fn(char, usize) -> bool,
     ) -> Option<Match> {
-        let current_mode = &mut self.scanner_modes[self.current_mode];
-        for dfa in current_mode.dfas.iter_mut() {
-            dfa.reset();
+        self.search(char_indices, matches_char_class).matched()
+    }
+
+    /// Searches the first longest match of the current mode, see [Scanner::search_dfas].
+    /// A match of a case-insensitive keyword gets the token type of the keyword.
+    fn search<S: CharSource>(
+        &mut self,
+        char_indices: S,
+        matches_char_class: fn(char, usize) -> bool,
+    ) -> FindOutcome {
+        if self.case_insensitive_keywords.is_empty() {
+            return self.search_dfas(char_indices, matches_char_class);
         }
+        match self.search_dfas(char_indices.clone(), matches_char_class) {
+            FindOutcome::Found(matched) => {
+                FindOutcome::Found(self.recognize_keyword(char_indices, matched))
+            }
+            outcome => outcome,
+        }
+    }
+
+    /// Returns the match with the token type of the case-insensitive keyword of the current mode
+    /// whose text it has regardless of case. Otherwise the match is returned unchanged.
+    /// The given character source starts at or before the match.
+    fn recognize_keyword<S: CharSource>(&self, char_indices: S, matched: Match) -> Match {
+        let current_mode = self.current_mode.as_usize();
+        self.case_insensitive_keywords
+            .iter()
+            .filter(|(mode_index, token_type, ..)| {
+                *mode_index == current_mode && *token_type == matched.token_type()
+            })
+            .find(|(_, _, text, _)| {
+                char_indices
+                    .clone()
+                    .skip_while(|(i, _)| *i < matched.start())
+                    .take_while(|(i, _)| *i < matched.end())
+                    .flat_map(|(_, c)| c.to_lowercase())
+                    .eq(text.chars())
+            })
+            .map_or(matched, |(.., keyword_token_type)| {
+                Match::new(*keyword_token_type, matched.span())
+            })
+    }
 
-        // All indices of the DFAs that are still active.
-        let mut active_dfas = (0..current_mode.dfas.len()).collect::<Vec<_>>();
+    /// Advances all DFAs of the current mode in parallel until all of them finished and returns
+    /// the first longest match.
+    /// If a maximum token length is set and a match would exceed it, the search is terminated
+    /// early and a match of the error token type is returned that spans the maximum length.
+    /// If a step budget is set, the search is aborted when the budget is exhausted.
+    /// On partial input the search needs more input if a DFA is still active at the end.
+    fn search_dfas<S: CharSource>(
+        &mut self,
+        mut char_indices: S,
+        matches_char_class: fn(char, usize) -> bool,
+    ) -> FindOutcome {
+        let Some(current_mode) = self.scanner_modes.get_mut(self.current_mode.as_usize()) else {
+            return FindOutcome::NotFound;
+        };
+        // Starting a new generation resets the matching states of all DFAs at once.
+        current_mode.generation = current_mode.generation.wrapping_add(1);
+        let ScannerMode {
+            dfas,
+            start_set,
+            run_sets,
+            evaluation_order,
+            matches_char_class: mode_matches_char_class,
+            generation,
+            active_dfas,
+            matched_dfas,
+            ..
+        } = current_mode;
+        let generation = *generation;
+        let matches_char_class = mode_matches_char_class.unwrap_or(matches_char_class);
+        matched_dfas.clear();
+        active_dfas.clear();
+        let mut start = None;
+        let mut steps = 0;
 
-        for (i, c) in char_indices {
-            for dfa_index in &active_dfas {
-                current_mode.dfas[*dfa_index].advance(i, c, matches_char_class);
+        while let Some((i, c)) = char_indices.next() {
+            if self.step_budget.is_some_and(|budget| steps >= budget) {
+                return FindOutcome::BudgetExhausted;
+            }
+            steps += 1;
+            if steps == 1 {
+                // A token must start with a character of the start set of the mode. Otherwise we
+                // can reject the position without advancing any DFA.
+                if start_set
+                    .as_ref()
+                    .is_some_and(|start_set| !start_set.contains(c))
+                {
+                    return FindOutcome::NotFound;
+                }
+                if evaluation_order.is_empty() {
+                    active_dfas.extend(0..dfas.len());
+                } else {
+                    active_dfas.extend_from_slice(evaluation_order);
+                }
+            }
+            let c_len = char_indices.encoded_len(c);
+            for dfa_index in active_dfas.iter() {
+                if let Some(dfa) = dfas.get_mut(*dfa_index) {
+                    dfa.advance(generation, i, c, c_len, matches_char_class);
+                }
             }
 
-            // We remove all DFAs from `active_dfas` that finished.
-            active_dfas.retain(|&dfa_index| current_mode.dfas[dfa_index].search_for_longer_match());
+            // We remove all DFAs from `active_dfas` that finished or did not find a match so far.
+            // The finished DFAs hold a match.
+            active_dfas.retain(|&dfa_index| {
+                let Some(dfa) = dfas.get(dfa_index) else {
+                    return false;
+                };
+                let active = dfa.search_for_longer_match();
+                if !active && dfa.current_match(generation).is_some() {
+                    matched_dfas.push(dfa_index);
+                }
+                active
+            });
+
+            // If the token gets too long, we terminate the search with an error token that ends
+            // before the current character, i.e. after at most `max_len` bytes. The error token
+            // contains at least one character to ensure progress.
+            if let Some((max_len, error_token_type)) = self.max_token_length {
+                let start = *start.get_or_insert(i);
+                if i + c_len - start > max_len
+                    && (!active_dfas.is_empty()
+                        || matched_dfas.iter().any(|dfa_index| {
+                            dfas.get(*dfa_index)
+                                .and_then(|dfa| dfa.current_match(generation))
+                                .is_some_and(|m| m.span().end > start + max_len)
+                        }))
+                {
+                    let end = if i > start { i } else { i + c_len };
+                    return FindOutcome::Found(Match::new(error_token_type, (start..end).into()));
+                }
+            }
 
             // If all DFAs have finished, we can stop the search.
             if active_dfas.is_empty() {
                 break;
             }
+
+            // Run-length fast path: if the only active DFA loops on a single character class,
+            // e.g. within whitespace or an identifier, the rest of the run is consumed without
+            // stepping the DFA character by character. If the characters of the class are known,
+            // they are tested against the run set instead of calling the match function.
+            // With a maximum token length the run ends at the limit, so that the next character
+            // is checked against it.
+            if let [dfa_index] = active_dfas.as_slice() {
+                if self.step_budget.is_none() {
+                    if let Some(dfa) = dfas.get_mut(*dfa_index) {
+                        if let Some(char_class) = dfa.self_loop_class(generation) {
+                            let limit = self
+                                .max_token_length
+                                .zip(start)
+                                .map_or(usize::MAX, |((max_len, _), start)| start + max_len);
+                            let end = match run_sets.get(char_class) {
+                                Some(Some(run_set)) => {
+                                    char_indices.skip_run(limit, |c| run_set.contains(c))
+                                }
+                                _ => char_indices.skip_run(limit, |c| {
+                                    dfa.matches_char_class(c, char_class, matches_char_class)
+                                }),
+                            };
+                            if let Some(end) = end {
+                                dfa.extend_match_to(end);
+                            }
+                        }
+                    }
+                }
+            }
         }
 
-        self.find_first_longest_match()
+        // On partial input a DFA that is still active at the end could find a longer match in
+        // the text that follows.
+        if self.partial_input && !active_dfas.is_empty() {
+            return FindOutcome::NeedMoreInput;
+        }
+
+        // DFAs that are still active at the end of the input may hold a match, too.
+        matched_dfas.extend(active_dfas.iter().filter(|dfa_index| {
+            dfas.get(**dfa_index)
+                .is_some_and(|dfa| dfa.current_match(generation).is_some())
+        }));
+        matched_dfas.sort_unstable();
+
+        match self.find_first_longest_match() {
+            Some(matched) => FindOutcome::Found(matched),
+            None => FindOutcome::NotFound,
+        }
     }
 
     /// We evaluate the matches of the DFAs in ascending order to prioritize the matches with the
     /// lowest index.
     /// We find the pattern with the lowest start position and the longest length.
+    /// Because a later match only replaces the current one if it starts earlier or is strictly
+    /// longer, the first DFA in the mode wins on equal spans.
     fn find_first_longest_match(&mut self) -> Option<Match> {
         let mut current_match: Option<Match> = None;
-        {
-            let current_mode = &self.scanner_modes[self.current_mode];
-            for dfa in current_mode.dfas.iter() {
-                if let Some(dfa_match) = dfa.current_match() {
-                    if current_match.is_none()
-                        || dfa_match.start() < current_match.unwrap().start()
-                        || dfa_match.start() == current_match.unwrap().start()
-                            && dfa_match.len() > current_match.unwrap().span().len()
-                    {
-                        // We have a match and we continue the look for a longer match.
-                        current_match = Some(dfa_match);
-                    }
-                }
+        for dfa_match in self.mode()?.current_matches() {
+            if current_match.is_none_or(|current_match| {
+                dfa_match.start() < current_match.start()
+                    || dfa_match.start() == current_match.start()
+                        && dfa_match.len() > current_match.span().len()
+            }) {
+                // We have a match and we continue the look for a longer match.
+                current_match = Some(dfa_match);
             }
         }
         current_match
     }
 
-    /// Executes a possible mode switch if a transition is defined for the token type found.
+    /// Records an ambiguity if other DFAs of the current mode matched the same span as the
+    /// winning match.
+    fn record_ambiguity(&mut self, current_match: Option<Match>) {
+        if let Some((matched, current_mode)) = current_match.zip(self.mode()) {
+            let shadowed = current_mode
+                .current_matches()
+                .filter(|m| m.span() == matched.span() && m.token_type() != matched.token_type())
+                .map(|m| m.token_type())
+                .collect::<Vec<_>>();
+            if !shadowed.is_empty() {
+                self.ambiguities.push(Ambiguity { matched, shadowed });
+            }
+        }
+    }
+
+    /// Returns the patterns of the current scanner mode that are still alive after consuming the
+    /// whole input from its start, i.e. the possible completions of the input, in the order of
+    /// their priority. Each completion tells whether the pattern already matches the input and
+    /// whether it can match longer inputs.
+    ///
+    /// An empty input yields all patterns of the current scanner mode. The scanner mode is not
+    /// changed and no matching state of the scanner is touched.
+    pub fn longest_prefix(
+        &self,
+        input: &str,
+        matches_char_class: fn(char, usize) -> bool,
+    ) -> Vec<Completion> {
+        let Some(mode) = self.mode() else {
+            return Vec::new();
+        };
+        let matches_char_class = mode.matches_char_class.unwrap_or(matches_char_class);
+        mode.dfas
+            .iter()
+            .filter_map(|dfa| {
+                let (state, repetitions) = input.chars().try_fold((0, 0), |state, c| {
+                    dfa.dfa().next_counted_state(state, c, matches_char_class)
+                })?;
+                Some(Completion {
+                    token_type: dfa.token_type(),
+                    is_match: dfa.dfa().is_accepting_after(state, repetitions),
+                    can_continue: dfa.dfa().can_continue(state, repetitions),
+                })
+            })
+            .collect()
+    }
+
+    /// Returns information about the scanner modes of the scanner in the order of their ids, e.g.
+    /// their names, tokens and transitions.
+    pub fn modes(&self) -> impl Iterator<Item = ModeInfo<'_>> {
+        self.scanner_modes
+            .iter()
+            .enumerate()
+            .map(|(id, mode)| ModeInfo::new(ScannerModeId::new(id), mode))
+    }
+
+    /// Returns the DFAs of the scanner in the order of the DFA data they were built from.
+    pub fn dfas(&self) -> impl Iterator<Item = &Dfa> {
+        self.dfas.iter().map(|dfa| dfa.as_ref())
+    }
+
+    /// Exports the data of the DFAs of the scanner in the order of the DFA data they were built
+    /// from. A scanner can be built from the exported data with
+    /// [crate::ScannerBuilder::add_owned_dfa_data].
+    pub fn export_dfa_data(&self) -> Vec<OwnedDfaData> {
+        self.dfas().map(OwnedDfaData::from).collect()
+    }
+
+    /// Exports the definition of the scanner, i.e. its DFAs, character classes and scanner modes,
+    /// as JSON, so external tools like visualizers, verifiers or runtimes in other languages can
+    /// consume the automata.
+    ///
+    /// The characters of the character classes are found by testing every character with the
+    /// match function, which takes a moment for each class. Scanner modes with their own match
+    /// function, e.g. of a composed scanner, use it instead of the given one.
+    ///
+    /// The JSON object has the following members:
+    /// * `format`: always `"scangen"`.
+    /// * `version`: the version of the schema, see [crate::JSON_FORMAT_VERSION].
+    /// * `classes`: the character classes. Each class is an array of inclusive ranges of
+    ///   characters given as pairs of code points, e.g. `[[48,57],[97,122]]`.
+    /// * `dfas`: the DFAs as exported by [Dfa::to_json]. The character classes of their
+    ///   transitions are indices into `classes`. State 0 is the start state.
+    /// * `modes`: the scanner modes. Each mode is an object with the members
+    ///   * `name`: the name of the mode,
+    ///   * `dfas`: pairs of DFA index and token type in the order of their priority,
+    ///   * `transitions`: pairs of token type and the index of the mode it switches to,
+    ///   * `error_token_type`: the token type of unmatched input or `null`,
+    ///   * `counting`: `null` or an object with the token types `open` and `close` and the mode
+    ///     `exit` of a counting mode, see [crate::CountingModeData].
+    pub fn definition_to_json(&self, matches_char_class: fn(char, usize) -> bool) -> String {
+        json::scanner_definition(self, matches_char_class)
+    }
+
+    /// Returns the specification of the scanner, i.e. the settings that differ from the defaults
+    /// and the scanner modes with their tokens in the order of their priority, their transitions,
+    /// their error token types and their counting settings. It allows to log and audit the
+    /// effective configuration of a scanner at runtime, e.g.
+    /// ```text
+    /// %scanner INITIAL {
+    ///     %token 0 Newline "\\r\\n|\\r|\\n"
+    ///     %token 1 String "\""
+    ///     %on 1 String %enter STRING
+    /// }
+    ///
+    /// %scanner STRING {
+    ///     %token 2 "[^\"]+"
+    ///     %token 1 String "\""
+    ///     %on 1 String %enter INITIAL
+    /// }
+    /// ```
+    /// The token types are named by the given names, which are indexed by token type. Token types
+    /// without a name are given by their numbers only. The patterns are quoted like Rust strings.
+    /// The [std::fmt::Display] implementation of the scanner prints the same without names.
+    pub fn to_spec_string(&self, token_names: &[&str]) -> String {
+        let mut out = String::new();
+        // Writing into a String doesn't fail.
+        let _ = spec::write_spec(self, token_names, &mut out);
+        out
+    }
+
+    /// Returns the priority of the given token type in the current scanner mode.
+    /// A lower value means a higher priority, i.e. the token type wins a tie against all token
+    /// types with a higher value.
+    /// If the token type is not part of the current scanner mode, None is returned.
+    pub fn priority(&self, token_type: TokenType) -> Option<usize> {
+        self.mode()?
+            .dfas
+            .iter()
+            .position(|dfa| dfa.token_type() == token_type)
+    }
+
+    /// Sets the maximum length of a token in bytes.
+    /// If a token would get longer, the scanner terminates the match after at most `max_len` bytes
+    /// and emits a match of the given error token type instead. Scanning continues behind the error
+    /// token, so an overlong token is reported as a sequence of error tokens of `max_len` bytes
+    /// followed by the match of its remainder.
+    /// This protects scanners of untrusted input against unbounded latency on pathological
+    /// inputs, e.g. a huge identifier or a never terminated block comment.
+    pub fn set_max_token_length(&mut self, max_len: usize, error_token_type: TokenType) {
+        self.max_token_length = Some((max_len, error_token_type));
+    }
+
+    /// Removes the maximum length of a token, i.e. tokens can have any length.
+    pub fn unset_max_token_length(&mut self) {
+        self.max_token_length = None;
+    }
+
+    /// Returns the maximum length of a token in bytes, if set.
     #[inline]
-    fn execute_possible_mode_switch(&mut self, current_match: Option<Match>) {
-        let current_mode = &self.scanner_modes[self.current_mode];
+    pub fn max_token_length(&self) -> Option<usize> {
+        self.max_token_length.map(|(max_len, _)| max_len)
+    }
+
+    /// Sets the token type of the tokens that cover input no pattern matches.
+    /// By default such input is skipped. If a token type is set, [FindMatches] emits a token of
+    /// this type for each run of unmatched characters instead. This makes the token stream
+    /// lossless, which can be checked with [crate::verify_lossless].
+    pub fn set_unmatched_token_type(&mut self, token_type: Option<TokenType>) {
+        self.unmatched_token_type = token_type;
+    }
+
+    /// Sets the token type of the newline terminal, i.e. of the tokens that match `\r\n`, `\r`
+    /// or `\n`. Generated scanners set it if they are generated with the auto newline option.
+    ///
+    /// With a newline token type, [FindMatches::with_positions] treats all these line breaks as
+    /// line boundaries, like the newline tokens do, instead of only the line feed.
+    pub fn set_newline_token_type(&mut self, token_type: Option<TokenType>) {
+        self.newline_token_type = token_type;
+    }
+
+    /// Returns the token type of the newline terminal, if set.
+    #[inline]
+    pub fn newline_token_type(&self) -> Option<TokenType> {
+        self.newline_token_type
+    }
+
+    /// Sets the token types of the matches that the iterators over matches skip, i.e. don't return
+    /// from [FindMatches::next_match], the peek operations, [FindSegmentedMatches] and
+    /// [FindDecodedMatches]. Generated scanners set the token type of the whitespace terminal if
+    /// they are generated with the auto whitespace option.
+    ///
+    /// Mode switches of skipped matches are executed nevertheless.
+    pub fn set_skipped_token_types<I>(&mut self, token_types: I)
+    where
+        I: IntoIterator<Item = TokenType>,
+    {
+        self.skipped_token_types = token_types.into_iter().collect();
+    }
+
+    /// Returns the token types of the matches that [FindMatches] skips.
+    #[inline]
+    pub fn skipped_token_types(&self) -> &[TokenType] {
+        &self.skipped_token_types
+    }
+
+    /// Reinterprets the given token as contextual keyword of the given scanner mode, e.g. an
+    /// identifier `union` as the keyword `union` where the grammar expects it. Returns the token
+    /// type of the keyword if the token has the token type of the terminal that matches the
+    /// keyword in the mode and its text is the keyword, otherwise `None`.
+    ///
+    /// Contextual keywords avoid scanner modes for each context in which a word is a keyword.
+    /// Generated scanners have them if they are generated with
+    /// `GenerateOptions::with_contextual_keywords`, see [crate::ContextualKeywordData].
+    pub fn reinterpret(&self, token: &Token, mode: ScannerModeId) -> Option<TokenType> {
+        self.contextual_keywords
+            .iter()
+            .find(|(mode_index, token_type, text, _)| {
+                *mode_index == mode.as_usize()
+                    && *token_type == token.token_type
+                    && *text == token.text
+            })
+            .map(|(_, _, _, keyword)| *keyword)
+    }
+
+    /// Returns the token type of the tokens that cover input no pattern matches, if set.
+    #[inline]
+    pub fn unmatched_token_type(&self) -> Option<TokenType> {
+        self.unmatched_token_type
+    }
+
+    /// Returns the token type of the tokens that cover input no pattern of the current scanner
+    /// mode matches. This is the error token type of the current mode if the mode declares one
+    /// (see [crate::ScannerModeData]), otherwise the [Scanner::unmatched_token_type].
+    #[inline]
+    pub fn error_token_type(&self) -> Option<TokenType> {
+        self.mode()
+            .and_then(|mode| mode.error_token_type)
+            .or(self.unmatched_token_type)
+    }
+
+    /// Sets the step budget, i.e. the maximum number of characters examined by a single search.
+    /// If the budget is exhausted, [Scanner::try_find_from] returns
+    /// [FindOutcome::BudgetExhausted]. `None` removes the budget.
+    pub fn set_step_budget(&mut self, budget: Option<usize>) {
+        self.step_budget = budget;
+    }
+
+    /// Returns the step budget, if set.
+    #[inline]
+    pub fn step_budget(&self) -> Option<usize> {
+        self.step_budget
+    }
+
+    /// Marks the input as partial, e.g. the lines a REPL or an interactive terminal received so
+    /// far. If a search reaches the end of partial input while a longer match is still possible,
+    /// [Scanner::try_find_from] returns [FindOutcome::NeedMoreInput] instead of the best match so
+    /// far. The caller can then append more input and resume the search at the same offset, see
+    /// [FindMatches::offset] and [FindMatches::start_at]. By default the input is complete.
+    pub fn set_partial_input(&mut self, partial_input: bool) {
+        self.partial_input = partial_input;
+    }
+
+    /// Returns true if the input is marked as partial.
+    #[inline]
+    pub fn is_partial_input(&self) -> bool {
+        self.partial_input
+    }
+
+    /// Sets the [MatchKind] of the iterators over matches. With [MatchKind::TokenAnchored] each
+    /// match must start where the previous one ended and the iteration ends at input no token
+    /// matches instead of skipping it. By default such input is skipped.
+    pub fn set_match_kind(&mut self, match_kind: MatchKind) {
+        self.match_kind = match_kind;
+    }
+
+    /// Returns the [MatchKind] of the iterators over matches.
+    #[inline]
+    pub fn match_kind(&self) -> MatchKind {
+        self.match_kind
+    }
+
+    /// Performs the one-time precomputations that speed up long-running scanners at the cost of
+    /// memory and of the time spent here. Building a scanner stays cheap without them.
+    ///
+    /// * The transitions of each DFA on ASCII characters are tabulated, so a step on an ASCII
+    ///   character is a single lookup.
+    /// * Scanner modes whose DFAs know the characters of their classes, e.g. a scanner built with
+    ///   [crate::ScannerBuilder::build_from_json], get a start set and run sets unless they have
+    ///   them already.
+    /// * The scratch space of the searches is allocated.
+    ///
+    /// The tables are built with the match function of each scanner mode if it has one and with
+    /// the given match function otherwise. It must be the one passed to the searches later.
+    /// Calling this method more than once does no harm.
+    pub fn prepare(&mut self, matches_char_class: fn(char, usize) -> bool) {
+        // The DFAs are shared between the scanner and its modes and are prepared only once. The
+        // original DFAs are kept alive, so their addresses stay unique while they are keys.
+        let mut prepared: HashMap<*const Dfa, (Arc<Dfa>, Arc<Dfa>)> = HashMap::new();
+        for mode in &mut self.scanner_modes {
+            let matches_char_class = mode.matches_char_class.unwrap_or(matches_char_class);
+            for dfa in &mut mode.dfas {
+                let (_, prepared_dfa) =
+                    prepared.entry(Arc::as_ptr(dfa.dfa())).or_insert_with(|| {
+                        (
+                            Arc::clone(dfa.dfa()),
+                            Arc::new(dfa.dfa().prepared(matches_char_class)),
+                        )
+                    });
+                dfa.set_dfa(Arc::clone(prepared_dfa));
+            }
+            mode.prepare();
+        }
+        for dfa in &mut self.dfas {
+            *dfa = match prepared.get(&Arc::as_ptr(dfa)) {
+                Some((_, prepared_dfa)) => Arc::clone(prepared_dfa),
+                None => Arc::new(dfa.prepared(matches_char_class)),
+            };
+        }
+    }
+
+    /// Replaces the tables of character classes of the DFAs by equal tables of the given pool,
+    /// so scanners that are loaded from similar definitions share them. Tables that aren't in
+    /// the pool yet are added to it.
+    pub(crate) fn share_char_classes(&mut self, pool: &mut Vec<CharClasses>) {
+        // Like in `prepare`, DFAs that are shared between the modes are copied only once.
+        let mut shared: HashMap<*const Dfa, (Arc<Dfa>, Arc<Dfa>)> = HashMap::new();
+        let mut share = |dfa: &Arc<Dfa>| {
+            let (_, shared_dfa) = shared.entry(Arc::as_ptr(dfa)).or_insert_with(|| {
+                (
+                    Arc::clone(dfa),
+                    Arc::new(dfa.with_shared_char_classes(pool)),
+                )
+            });
+            Arc::clone(shared_dfa)
+        };
+        for mode in &mut self.scanner_modes {
+            for dfa in &mut mode.dfas {
+                let shared_dfa = share(dfa.dfa());
+                dfa.set_dfa(shared_dfa);
+            }
+        }
+        for dfa in &mut self.dfas {
+            *dfa = share(dfa);
+        }
+    }
+
+    /// Enables or disables the recording of ambiguities.
+    /// An ambiguity is recorded if more than one pattern matched the same longest span.
+    /// Recording costs an additional pass over the DFAs for each match and is intended for
+    /// debugging grammars.
+    pub fn set_record_ambiguities(&mut self, record: bool) {
+        self.record_ambiguities = record;
+    }
+
+    /// Returns the ambiguities recorded so far.
+    pub fn ambiguities(&self) -> &[Ambiguity] {
+        &self.ambiguities
+    }
+
+    /// Returns true if matches of the given token type are skipped by the iterators over matches.
+    #[inline]
+    pub(crate) fn is_skipped(&self, token_type: TokenType) -> bool {
+        self.skipped_token_types.contains(&token_type)
+    }
+
+    /// Executes a possible mode switch if a transition is defined for the token type found or
+    /// if the token type closes the outermost nesting level of a counting mode.
+    #[inline]
+    pub(crate) fn execute_possible_mode_switch(&mut self, current_match: Option<Match>) {
         if let Some(current_match) = current_match.as_ref() {
-            // We perform a scanner mode switch if a transition is defined for the token type found.
-            if let Some(next_mode) = current_mode.has_transition(current_match.token_type()) {
+            let mut nesting_depth = self.nesting_depth;
+            if let Some(next_mode) =
+                self.mode_switch(current_match.token_type(), &mut nesting_depth)
+            {
                 self.current_mode = next_mode;
             }
+            self.nesting_depth = nesting_depth;
+        }
+    }
+
+    /// Returns the scanner mode a token of the given type switches to, if any, and updates the
+    /// given nesting depth of a counting mode accordingly.
+    /// The scanner itself is not changed, which allows to simulate the mode switches when peeking.
+    pub(crate) fn mode_switch(
+        &self,
+        token_type: TokenType,
+        nesting_depth: &mut usize,
+    ) -> Option<ScannerModeId> {
+        let current_mode = self.mode()?;
+        if let Some((open, close, exit)) = current_mode.counting {
+            if token_type == open {
+                *nesting_depth += 1;
+                return None;
+            }
+            if token_type == close {
+                *nesting_depth = nesting_depth.saturating_sub(1);
+                return (*nesting_depth == 0).then_some(exit);
+            }
         }
+        let next_mode = current_mode.has_transition(token_type)?;
+        // The token that enters a counting mode opens the first nesting level.
+        *nesting_depth = self.initial_nesting_depth(next_mode);
+        Some(next_mode)
     }
 
-    /// Returns the number of the next scanner mode if a transition is defined for the token type.
+    /// Returns the id of the next scanner mode if a transition is defined for the token type.
     /// If no transition is defined, None returned.
-    pub fn has_transition(&self, token_type: usize) -> Option<usize> {
-        self.scanner_modes[self.current_mode].has_transition(token_type)
+    pub fn has_transition(&self, token_type: TokenType) -> Option<ScannerModeId> {
+        self.mode()?.has_transition(token_type)
     }
 
-    /// Returns the name of the scanner mode with the given index.
-    /// If the index is out of bounds, None is returned.
-    pub fn mode_name(&self, index: usize) -> Option<&str> {
-        self.scanner_modes.get(index).map(|mode| mode.name())
+    /// Returns the name of the scanner mode with the given id.
+    /// If the id is out of bounds, None is returned.
+    pub fn mode_name(&self, id: ScannerModeId) -> Option<&str> {
+        self.scanner_modes
+            .get(id.as_usize())
+            .map(|mode| mode.name())
+    }
+
+    /// Returns a value that displays the scanner mode with the given id by its name, e.g. in
+    /// diagnostics. If the id is out of bounds, the mode is displayed by its index.
+    pub fn display_mode(&self, id: ScannerModeId) -> impl std::fmt::Display + '_ {
+        ModeDisplay {
+            id,
+            name: self.mode_name(id),
+        }
     }
 
     /// Sets the current scanner mode.
@@ -162,12 +1088,796 @@ impl Scanner {
     /// A parser can explicitly set the scanner mode to switch to a different set of DFAs.
     /// Usually, the scanner mode is changed by the scanner itself based on the transitions defined
     /// in the scanner mode.
-    pub fn set_mode(&mut self, mode: usize) {
+    ///
+    /// Setting a counting mode starts at nesting depth one, setting any other mode resets the
+    /// nesting depth to zero.
+    /// In an unknown scanner mode the scanner finds no matches.
+    pub fn set_mode(&mut self, mode: ScannerModeId) {
+        debug_assert!(
+            mode.as_usize() < self.scanner_modes.len(),
+            "Unknown scanner mode {}",
+            mode
+        );
         self.current_mode = mode;
+        self.nesting_depth = self.initial_nesting_depth(mode);
+    }
+
+    /// Returns the nesting depth of the current counting mode. It is zero if the current scanner
+    /// mode is no counting mode.
+    pub fn nesting_depth(&self) -> usize {
+        self.nesting_depth
     }
 
     /// Returns the current scanner mode.
-    pub fn current_mode(&self) -> usize {
+    pub fn current_mode(&self) -> ScannerModeId {
         self.current_mode
     }
+
+    /// Returns the data of the current scanner mode, which only doesn't exist if an unknown mode
+    /// was set with [Scanner::set_mode].
+    #[inline]
+    fn mode(&self) -> Option<&ScannerMode> {
+        self.scanner_modes.get(self.current_mode.as_usize())
+    }
+}
+
+impl std::fmt::Display for Scanner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        spec::write_spec(self, &[], f)
+    }
+}
+
+/// Displays a scanner mode by its name, see [Scanner::display_mode].
+struct ModeDisplay<'s> {
+    id: ScannerModeId,
+    name: Option<&'s str>,
+}
+
+impl std::fmt::Display for ModeDisplay<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.name {
+            Some(name) => write!(f, "{}", name),
+            None => write!(f, "{}", self.id),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        runtime::generated::scanner_with_modes, Ambiguity, Completion, CountingModeData, DfaData,
+        Match, RunClassData, ScannerBuilder, ScannerModeData, ScannerModeId, StartSetData, Token,
+        TokenType,
+    };
+
+    #[test]
+    fn test_longest_prefix() {
+        let scanner = scanner_with_modes::create_scanner();
+        let matches_char_class =
+            scanner_with_modes::create_find_iter(&scanner, "").matches_char_class();
+        let completion = |token_type, is_match, can_continue| Completion {
+            token_type: TokenType::new(token_type),
+            is_match,
+            can_continue,
+        };
+
+        // Line comment, block comment and the error token.
+        assert_eq!(
+            scanner.longest_prefix("/", matches_char_class),
+            vec![
+                completion(2, false, true),
+                completion(3, false, true),
+                completion(9, true, false),
+            ]
+        );
+        assert_eq!(
+            scanner.longest_prefix("/*", matches_char_class),
+            vec![completion(3, false, true)]
+        );
+        assert_eq!(
+            scanner.longest_prefix("ab", matches_char_class),
+            vec![completion(4, true, true)]
+        );
+        assert_eq!(scanner.longest_prefix("a-", matches_char_class), vec![]);
+        assert_eq!(scanner.longest_prefix("", matches_char_class).len(), 7);
+    }
+
+    #[test]
+    fn test_tie_breaking() {
+        let mut scanner = scanner_with_modes::create_scanner();
+        // In mode INITIAL the identifier (4) comes before the error token (9).
+        assert_eq!(scanner.priority(TokenType::new(4)), Some(4));
+        assert_eq!(scanner.priority(TokenType::new(9)), Some(6));
+        assert_eq!(scanner.priority(TokenType::new(7)), None);
+
+        scanner.set_record_ambiguities(true);
+        let mut find_iter = scanner_with_modes::create_find_iter(&scanner, "x yz");
+        assert_eq!(
+            find_iter.next(),
+            Some(Match::new(4.into(), (0usize..1).into()))
+        );
+        // The longer identifier does not tie with the error token.
+        assert_eq!(find_iter.by_ref().count(), 2);
+        assert_eq!(
+            find_iter.scanner().ambiguities(),
+            &[
+                Ambiguity {
+                    matched: Match::new(4.into(), (0usize..1).into()),
+                    shadowed: vec![TokenType::new(9)],
+                },
+                Ambiguity {
+                    matched: Match::new(1.into(), (1usize..2).into()),
+                    shadowed: vec![TokenType::new(9)],
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn test_max_token_length() {
+        let mut scanner = scanner_with_modes::create_scanner();
+        scanner.set_max_token_length(3, TokenType::new(9));
+        assert_eq!(scanner.max_token_length(), Some(3));
+        let find_iter = scanner_with_modes::create_find_iter(&scanner, "abc abcdefg xy");
+        let matches = find_iter
+            .filter(|m| m.token_type() != TokenType::new(1))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            matches,
+            vec![
+                // An identifier with the maximum length is accepted
+                Match::new(4.into(), (0usize..3).into()),
+                // Longer identifiers are split into error tokens and the remainder
+                Match::new(9.into(), (4usize..7).into()),
+                Match::new(9.into(), (7usize..10).into()),
+                Match::new(4.into(), (10usize..11).into()),
+                Match::new(4.into(), (12usize..14).into()),
+            ]
+        );
+
+        // The error token ends at the maximum length, also if the run-length fast path consumes
+        // the token
+        scanner.set_max_token_length(10, TokenType::new(10));
+        let input = format!("{}+xy", "a".repeat(50));
+        let find_iter = scanner_with_modes::create_find_iter(&scanner, &input);
+        let matches = find_iter.collect::<Vec<_>>();
+        assert_eq!(matches.len(), 7);
+        assert_eq!(matches[0], Match::new(10.into(), (0usize..10).into()));
+        assert_eq!(matches[3], Match::new(10.into(), (30usize..40).into()));
+        assert_eq!(
+            matches[4..],
+            [
+                // The remainder has the maximum length and is accepted
+                Match::new(4.into(), (40usize..50).into()),
+                Match::new(9.into(), (50usize..51).into()),
+                Match::new(4.into(), (51usize..53).into()),
+            ]
+        );
+
+        scanner.unset_max_token_length();
+        assert_eq!(scanner.max_token_length(), None);
+        let find_iter = scanner_with_modes::create_find_iter(&scanner, "abcdefg");
+        assert_eq!(
+            find_iter.collect::<Vec<_>>(),
+            vec![Match::new(4.into(), (0usize..7).into())]
+        );
+    }
+
+    #[test]
+    fn test_start_set() {
+        const DFAS: &[DfaData] = &[("a", &[1], &[(0, 1), (0, 0)], &[(0, 1)])];
+        const START_SETS: &[StartSetData] = &[(1 << b'a', &[])];
+        // Characters outside the start set must never reach a DFA
+        fn matches_char_class(c: char, _char_class: usize) -> bool {
+            assert_eq!(c, 'a');
+            true
+        }
+        let scanner = ScannerBuilder::new()
+            .add_dfa_data(DFAS)
+            .add_start_set_data(START_SETS)
+            .build()
+            .unwrap();
+        let find_iter = scanner.find_iter("xaäa", matches_char_class);
+        assert_eq!(
+            find_iter.collect::<Vec<_>>(),
+            vec![
+                Match::new(0.into(), (1usize..2).into()),
+                Match::new(0.into(), (4usize..5).into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_prepare() {
+        const INPUT: &str = "Id1 \"1. String\"\n/* comment */ 123 ä_x \"a\\tb\"";
+        let scanner = scanner_with_modes::create_scanner();
+        let expected = scanner_with_modes::create_find_iter(&scanner, INPUT).collect::<Vec<_>>();
+
+        // All modes of the generated scanner have their own match functions
+        let mut prepared = scanner.clone();
+        prepared.prepare(|_, _| unreachable!());
+        assert!(prepared
+            .dfas
+            .iter()
+            .all(|dfa| dfa.ascii_transitions.is_some()));
+        assert!(prepared
+            .scanner_modes
+            .iter()
+            .all(|mode| mode.dfas.iter().all(|dfa| prepared
+                .dfas
+                .iter()
+                .any(|shared| Arc::ptr_eq(shared, dfa.dfa())))));
+        assert_eq!(
+            scanner_with_modes::create_find_iter(&prepared, INPUT).collect::<Vec<_>>(),
+            expected
+        );
+
+        // The modes of an imported scanner get start sets and run sets
+        let json = scanner.definition_to_json(|_, _| unreachable!());
+        let mut imported = ScannerBuilder::build_from_json(&json).unwrap();
+        assert!(imported.scanner_modes[0].start_set.is_none());
+        imported.prepare(|_, _| unreachable!());
+        imported.prepare(|_, _| unreachable!());
+        assert!(imported
+            .scanner_modes
+            .iter()
+            .all(|mode| mode.start_set.is_some() && !mode.run_sets.is_empty()));
+        assert_eq!(
+            imported
+                .find_iter(INPUT, |_, _| unreachable!())
+                .collect::<Vec<_>>(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_modes() {
+        let scanner = scanner_with_modes::create_scanner();
+        let modes = scanner.modes().collect::<Vec<_>>();
+        assert_eq!(
+            modes.iter().map(|mode| mode.name()).collect::<Vec<_>>(),
+            vec!["INITIAL", "STRING"]
+        );
+        let string = modes[1];
+        assert_eq!(string.id(), ScannerModeId::new(1));
+        assert_eq!(scanner.display_mode(string.id()).to_string(), "STRING");
+        assert_eq!(scanner.display_mode(ScannerModeId::new(5)).to_string(), "5");
+        assert_eq!(
+            string
+                .token_types()
+                .map(|token_type| token_type.as_usize())
+                .collect::<Vec<_>>(),
+            vec![0, 1, 2, 3, 5, 6, 7, 8, 9]
+        );
+        assert_eq!(string.patterns().nth(7), Some("\\u{22}"));
+        assert_eq!(
+            string.transitions(),
+            &[(TokenType::new(8), ScannerModeId::new(0))]
+        );
+        assert_eq!(string.error_token_type(), None);
+        assert_eq!(string.counting(), None);
+        // The mode-local indices number the token types of each mode densely
+        assert_eq!(modes[0].local_token_types().len(), 7);
+        assert_eq!(modes[0].local_index(TokenType::new(8)), Some(5));
+        assert_eq!(string.local_index(TokenType::new(5)), Some(4));
+        assert_eq!(string.local_index(TokenType::new(4)), None);
+        // The DFAs are the ones of the scanner
+        let (_, dfa) = string.tokens().next().unwrap();
+        assert!(scanner.dfas().any(|shared| std::ptr::eq(shared, dfa)));
+    }
+
+    #[test]
+    fn test_to_spec_string() {
+        const DFAS: &[DfaData] = &[
+            ("\\(", &[1], &[(0, 1), (1, 1)], &[(0, 1)]),
+            ("\\)", &[1], &[(0, 1), (1, 1)], &[(1, 1)]),
+            ("a", &[1], &[(0, 1), (1, 1)], &[(2, 1)]),
+        ];
+        const MODES: &[ScannerModeData] = &[
+            (
+                "INITIAL",
+                &[(2, TokenType::new(2)), (0, TokenType::new(0))],
+                &[(TokenType::new(0), ScannerModeId::new(1))],
+                None,
+            ),
+            (
+                "COMMENT",
+                &[(0, TokenType::new(0)), (1, TokenType::new(1))],
+                &[],
+                Some(TokenType::new(3)),
+            ),
+        ];
+        let mut scanner = ScannerBuilder::new()
+            .add_dfa_data(DFAS)
+            .add_scanner_mode_data(MODES)
+            .add_counting_mode_data(&[(
+                1,
+                TokenType::new(0),
+                TokenType::new(1),
+                ScannerModeId::new(0),
+            )])
+            .build()
+            .unwrap();
+        assert_eq!(
+            scanner.to_string(),
+            concat!(
+                "%scanner INITIAL {\n",
+                "    %token 2 \"a\"\n",
+                "    %token 0 \"\\\\(\"\n",
+                "    %on 0 %enter COMMENT\n",
+                "}\n",
+                "\n",
+                "%scanner COMMENT {\n",
+                "    %token 0 \"\\\\(\"\n",
+                "    %token 1 \"\\\\)\"\n",
+                "    %error 3\n",
+                "    %counting 0, 1 %exit INITIAL\n",
+                "}\n",
+            )
+        );
+
+        scanner.set_max_token_length(10, TokenType::new(3));
+        let spec = scanner.to_spec_string(&["Open", "Close", "A", "Error"]);
+        assert!(spec.starts_with("%max_token_length 10 %error 3 Error\n\n%scanner INITIAL {\n"));
+        assert!(spec.contains("    %token 2 A \"a\"\n"));
+        assert!(spec.contains("    %on 0 Open %enter COMMENT\n"));
+        assert!(spec.contains("    %counting 0 Open, 1 Close %exit INITIAL\n"));
+    }
+
+    #[test]
+    fn test_scanner_modes_before_dfas() {
+        const DFAS: &[DfaData] = &[("a", &[1], &[(0, 1), (0, 0)], &[(0, 1)])];
+        const MODES: &[ScannerModeData] = &[("INITIAL", &[(0, TokenType::new(5))], &[], None)];
+        fn matches_char_class(c: char, _char_class: usize) -> bool {
+            c == 'a'
+        }
+        let scanner = ScannerBuilder::new()
+            .add_scanner_mode_data(MODES)
+            .add_dfa_data(DFAS)
+            .build()
+            .unwrap();
+        assert_eq!(scanner.scanner_modes[0].dfas.len(), 1);
+        assert_eq!(
+            scanner.dfas().map(|dfa| dfa.pattern()).collect::<Vec<_>>(),
+            vec!["a"]
+        );
+        let find_iter = scanner.find_iter("a", matches_char_class);
+        assert_eq!(
+            find_iter.collect::<Vec<_>>(),
+            vec![Match::new(5.into(), (0usize..1).into())]
+        );
+    }
+
+    #[test]
+    fn test_find_iter_in_regions() {
+        const DFAS: &[DfaData] = &[
+            ("a", &[1], &[(0, 1), (1, 1)], &[(0, 1)]),
+            ("b", &[1], &[(0, 1), (1, 1)], &[(1, 1)]),
+        ];
+        const MODES: &[ScannerModeData] = &[
+            ("A", &[(0, TokenType::new(0))], &[], None),
+            ("B", &[(1, TokenType::new(1))], &[], None),
+        ];
+        fn matches_char_class(c: char, char_class: usize) -> bool {
+            match char_class {
+                0 => c == 'a',
+                1 => c == 'b',
+                _ => false,
+            }
+        }
+        let scanner = ScannerBuilder::new()
+            .add_dfa_data(DFAS)
+            .add_scanner_mode_data(MODES)
+            .build()
+            .unwrap();
+        let input = "ab bb ab";
+        // The region ends before the last 'b' of the input
+        let matches = scanner
+            .find_iter_in_region(input, 3..5, ScannerModeId::new(1), matches_char_class)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            matches,
+            vec![
+                Match::new(1.into(), (3usize..4).into()),
+                Match::new(1.into(), (4usize..5).into()),
+            ]
+        );
+        let matches = scanner
+            .find_iter_in_regions(
+                input,
+                vec![(0..2, ScannerModeId::new(0)), (3..8, ScannerModeId::new(1))],
+                matches_char_class,
+            )
+            .map(|m| m.start())
+            .collect::<Vec<_>>();
+        assert_eq!(matches, vec![0, 3, 4, 7]);
+        // Regions with invalid bounds yield no matches
+        let matches = scanner
+            .find_iter_in_regions(
+                "äb",
+                vec![
+                    (0..9, ScannerModeId::new(1)),
+                    (1..3, ScannerModeId::new(1)),
+                    (std::ops::Range { start: 3, end: 2 }, ScannerModeId::new(1)),
+                    (2..3, ScannerModeId::new(1)),
+                ],
+                matches_char_class,
+            )
+            .collect::<Vec<_>>();
+        assert_eq!(matches, vec![Match::new(1.into(), (2usize..3).into())]);
+        // The mode of the scanner itself is not changed
+        assert_eq!(scanner.current_mode(), ScannerModeId::new(0));
+    }
+
+    #[test]
+    fn test_skip_unmatched_input() {
+        const DFAS: &[DfaData] = &[("a", &[1], &[(0, 1), (0, 0)], &[(0, 1)])];
+        const START_SETS: &[StartSetData] = &[(1 << b'a', &[])];
+        fn matches_char_class(c: char, _char_class: usize) -> bool {
+            assert_eq!(c, 'a');
+            true
+        }
+        let mut scanner = ScannerBuilder::new()
+            .add_dfa_data(DFAS)
+            .add_start_set_data(START_SETS)
+            .build()
+            .unwrap();
+        scanner.set_unmatched_token_type(Some(TokenType::new(99)));
+        let find_iter = scanner.find_iter("xyzaäbaa", matches_char_class);
+        assert_eq!(
+            find_iter.collect::<Vec<_>>(),
+            vec![
+                Match::new(99.into(), (0usize..3).into()),
+                Match::new(0.into(), (3usize..4).into()),
+                Match::new(99.into(), (4usize..7).into()),
+                Match::new(0.into(), (7usize..8).into()),
+                Match::new(0.into(), (8usize..9).into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_counting_mode() {
+        const DFAS: &[DfaData] = &[
+            ("\\(", &[1], &[(0, 1), (1, 1)], &[(0, 1)]),
+            ("\\)", &[1], &[(0, 1), (1, 1)], &[(1, 1)]),
+            ("x", &[1], &[(0, 1), (1, 1)], &[(2, 1)]),
+            ("a", &[1], &[(0, 1), (1, 1)], &[(3, 1)]),
+        ];
+        const MODES: &[ScannerModeData] = &[
+            (
+                "INITIAL",
+                &[(0, TokenType::new(0)), (3, TokenType::new(3))],
+                &[(TokenType::new(0), ScannerModeId::new(1))],
+                None,
+            ),
+            (
+                "COMMENT",
+                &[
+                    (0, TokenType::new(0)),
+                    (1, TokenType::new(1)),
+                    (2, TokenType::new(2)),
+                ],
+                &[],
+                None,
+            ),
+        ];
+        const COUNTING_MODES: &[CountingModeData] = &[(
+            1,
+            TokenType::new(0),
+            TokenType::new(1),
+            ScannerModeId::new(0),
+        )];
+        fn matches_char_class(c: char, char_class: usize) -> bool {
+            match char_class {
+                0 => c == '(',
+                1 => c == ')',
+                2 => c == 'x',
+                3 => c == 'a',
+                _ => false,
+            }
+        }
+        let scanner = ScannerBuilder::new()
+            .add_dfa_data(DFAS)
+            .add_scanner_mode_data(MODES)
+            .add_counting_mode_data(COUNTING_MODES)
+            .build()
+            .unwrap();
+
+        // The comment is only left after the outermost closing parenthesis
+        let mut find_iter = scanner.find_iter("a(x(x)x)a", matches_char_class);
+        let token_types = find_iter
+            .by_ref()
+            .map(|m| m.token_type().as_usize())
+            .collect::<Vec<_>>();
+        assert_eq!(token_types, vec![3, 0, 2, 0, 2, 1, 2, 1, 3]);
+        assert_eq!(find_iter.scanner().current_mode(), ScannerModeId::new(0));
+
+        // Peeking simulates the nesting depth without changing it
+        #[cfg(feature = "peek")]
+        {
+            let mut find_iter = scanner.find_iter("(x(x))a", matches_char_class);
+            find_iter.next();
+            assert_eq!(find_iter.scanner().nesting_depth(), 1);
+            assert!(matches!(
+                find_iter.peek_n(10),
+                crate::PeekResult::MatchesReachedModeSwitch((matches, mode))
+                    if matches.len() == 5 && mode == ScannerModeId::new(0)
+            ));
+            assert_eq!(find_iter.scanner().nesting_depth(), 1);
+        }
+
+        // Only setting a counting mode starts a nesting level
+        let mut scanner = scanner;
+        assert_eq!(scanner.nesting_depth(), 0);
+        scanner.set_mode(ScannerModeId::new(1));
+        assert_eq!(scanner.nesting_depth(), 1);
+        scanner.set_mode(ScannerModeId::new(0));
+        assert_eq!(scanner.nesting_depth(), 0);
+
+        // A counting initial mode starts at nesting depth one
+        let scanner = ScannerBuilder::new()
+            .add_dfa_data(DFAS)
+            .add_scanner_mode_data(MODES)
+            .add_counting_mode_data(&[(
+                0,
+                TokenType::new(3),
+                TokenType::new(0),
+                ScannerModeId::new(1),
+            )])
+            .build()
+            .unwrap();
+        assert_eq!(scanner.nesting_depth(), 1);
+        let mut find_iter = scanner.find_iter("a((x", matches_char_class);
+        let token_types = find_iter
+            .by_ref()
+            .map(|m| m.token_type().as_usize())
+            .collect::<Vec<_>>();
+        assert_eq!(token_types, vec![3, 0, 0, 2]);
+        assert_eq!(find_iter.scanner().current_mode(), ScannerModeId::new(1));
+        assert_eq!(find_iter.scanner().nesting_depth(), 0);
+
+        // Counting modes must reference existing modes
+        assert!(ScannerBuilder::new()
+            .add_dfa_data(DFAS)
+            .add_scanner_mode_data(MODES)
+            .add_counting_mode_data(&[(
+                2,
+                TokenType::new(0),
+                TokenType::new(1),
+                ScannerModeId::new(0)
+            )])
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn test_error_token_type_of_mode() {
+        const DFAS: &[DfaData] = &[
+            ("a", &[1], &[(0, 1), (1, 1)], &[(0, 1)]),
+            ("b", &[1], &[(0, 1), (1, 1)], &[(1, 1)]),
+        ];
+        const MODES: &[ScannerModeData] = &[
+            (
+                "A",
+                &[(0, TokenType::new(0)), (1, TokenType::new(1))],
+                &[(TokenType::new(1), ScannerModeId::new(1))],
+                Some(TokenType::new(7)),
+            ),
+            ("B", &[(0, TokenType::new(0))], &[], None),
+        ];
+        fn matches_char_class(c: char, char_class: usize) -> bool {
+            match char_class {
+                0 => c == 'a',
+                1 => c == 'b',
+                _ => false,
+            }
+        }
+        let mut scanner = ScannerBuilder::new()
+            .add_dfa_data(DFAS)
+            .add_scanner_mode_data(MODES)
+            .build()
+            .unwrap();
+        assert_eq!(scanner.error_token_type(), Some(TokenType::new(7)));
+        // Unmatched input is covered by the error token type of mode A and skipped in mode B
+        let find_iter = scanner.find_iter("axxbxa", matches_char_class);
+        assert_eq!(
+            find_iter.collect::<Vec<_>>(),
+            vec![
+                Match::new(0.into(), (0usize..1).into()),
+                Match::new(7.into(), (1usize..3).into()),
+                Match::new(1.into(), (3usize..4).into()),
+                Match::new(0.into(), (5usize..6).into()),
+            ]
+        );
+        // Segmented and decoded input is scanned alike
+        let expected = scanner
+            .find_iter("axxbxa", matches_char_class)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            scanner
+                .find_iter_segmented(["ax", "xbxa"], matches_char_class)
+                .collect::<Vec<_>>(),
+            expected
+        );
+        assert_eq!(
+            scanner
+                .find_iter_lossy(b"axxbxa", matches_char_class)
+                .collect::<Vec<_>>(),
+            expected
+        );
+
+        // The unmatched token type of the scanner applies to modes without error token type
+        scanner.set_unmatched_token_type(Some(TokenType::new(99)));
+        let find_iter = scanner.find_iter("bxa", matches_char_class);
+        assert_eq!(
+            find_iter.collect::<Vec<_>>(),
+            vec![
+                Match::new(1.into(), (0usize..1).into()),
+                Match::new(99.into(), (1usize..2).into()),
+                Match::new(0.into(), (2usize..3).into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_scan_many() {
+        let scanner = scanner_with_modes::create_scanner();
+        let inputs = ["a b", "\"s", "c", ""];
+        let expected = inputs
+            .iter()
+            .map(|input| scanner_with_modes::create_find_iter(&scanner, input).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        // The mode switch at the end of the second input doesn't affect the third one
+        assert_eq!(scanner.scan_many(inputs, |_, _| unreachable!()), expected);
+        assert_eq!(
+            scanner.scan_many_parallel(&inputs, |_, _| unreachable!(), 3),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_scan_into_buffers() {
+        let scanner = scanner_with_modes::create_scanner();
+        let mut buffers = (0..3).map(|_| Vec::with_capacity(64)).collect::<Vec<_>>();
+        let capacity = buffers[0].capacity();
+        scanner.scan_many_into(["a b", "c"], |_, _| unreachable!(), &mut buffers);
+        assert_eq!(buffers.len(), 2);
+        assert_eq!(buffers[0].len(), 3);
+        assert_eq!(buffers[1].len(), 1);
+        // The buffers are reused, not reallocated
+        assert_eq!(buffers[0].capacity(), capacity);
+
+        let mut buffer = std::mem::take(&mut buffers[0]);
+        scanner.scan_into("d", |_, _| unreachable!(), &mut buffer);
+        assert_eq!(buffer, vec![Match::new(4.into(), (0usize..1).into())]);
+        assert_eq!(buffer.capacity(), capacity);
+    }
+
+    #[test]
+    fn test_run_length_fast_path() {
+        let scanner = scanner_with_modes::create_scanner();
+        // The runs of whitespace and identifier characters contain non-ASCII characters and are
+        // longer than a word of eight bytes
+        let input = "ab_cdefghijklä9 \t\u{a0}          x\nyz";
+        let find_iter = scanner_with_modes::create_find_iter(&scanner, input);
+        assert_eq!(
+            find_iter.collect::<Vec<_>>(),
+            vec![
+                Match::new(4.into(), (0usize..16).into()),
+                Match::new(1.into(), (16usize..30).into()),
+                Match::new(4.into(), (30usize..31).into()),
+                Match::new(0.into(), (31usize..32).into()),
+                Match::new(4.into(), (32usize..34).into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_run_class_data() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        static CALLS: AtomicUsize = AtomicUsize::new(0);
+        fn matches_char_class(c: char, _char_class: usize) -> bool {
+            CALLS.fetch_add(1, Ordering::Relaxed);
+            c == 'a'
+        }
+        // The DFA of the pattern `a+`
+        const DFAS: &[DfaData] = &[("a+", &[1], &[(0, 1), (1, 2)], &[(0, 1), (0, 1)])];
+        const RUN_CLASSES: &[RunClassData] = &[(0, (1 << b'a', &[]))];
+        let scanner = ScannerBuilder::new()
+            .add_dfa_data(DFAS)
+            .add_run_class_data(RUN_CLASSES)
+            .build()
+            .unwrap();
+        let matches = scanner
+            .find_iter("aaaaaaaaaab", matches_char_class)
+            .collect::<Vec<_>>();
+        assert_eq!(matches, vec![Match::new(0.into(), (0usize..10).into())]);
+        // Only the first character is tested by the match function, the run is consumed with the
+        // run set. The `b` is tested by the search that ends the match and by the next search.
+        assert_eq!(CALLS.load(Ordering::Relaxed), 3);
+    }
+
+    #[test]
+    fn test_evaluation_order() {
+        const DFAS: &[DfaData] = &[
+            ("a", &[1], &[(0, 1), (0, 0)], &[(0, 1)]),
+            ("a+", &[1], &[(0, 1), (1, 2)], &[(0, 1), (0, 1)]),
+        ];
+        let scanner = ScannerBuilder::new()
+            .add_dfa_data(DFAS)
+            .add_evaluation_orders(&[&[1, 0]])
+            .build()
+            .unwrap();
+        let matches = scanner
+            .find_iter("aaa", |c, _| c == 'a')
+            .chain(scanner.find_iter("a", |c, _| c == 'a'))
+            .collect::<Vec<_>>();
+        // The order of evaluation doesn't change the priorities
+        assert_eq!(
+            matches,
+            vec![
+                Match::new(1.into(), (0usize..3).into()),
+                Match::new(0.into(), (0usize..1).into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_reinterpret() {
+        // The DFA of the pattern `a+`, the keyword `aa` is contextual in the first mode only
+        const DFAS: &[DfaData] = &[("a+", &[1], &[(0, 1), (1, 2)], &[(0, 1), (0, 1)])];
+        const MODES: &[ScannerModeData] = &[
+            ("INITIAL", &[(0, TokenType::new(0))], &[], None),
+            ("OTHER", &[(0, TokenType::new(0))], &[], None),
+        ];
+        let scanner = ScannerBuilder::new()
+            .add_dfa_data(DFAS)
+            .add_scanner_mode_data(MODES)
+            .add_contextual_keyword_data(&[(0, TokenType::new(0), "aa", TokenType::new(1))])
+            .build()
+            .unwrap();
+        let token = |input| {
+            let ma = scanner.find_iter(input, |c, _| c == 'a').next().unwrap();
+            Token::new(ma, input)
+        };
+        assert_eq!(
+            scanner.reinterpret(&token("aa"), ScannerModeId::new(0)),
+            Some(TokenType::new(1))
+        );
+        assert_eq!(
+            scanner.reinterpret(&token("aa"), ScannerModeId::new(1)),
+            None
+        );
+        assert_eq!(
+            scanner.reinterpret(&token("aaa"), ScannerModeId::new(0)),
+            None
+        );
+
+        assert!(ScannerBuilder::new()
+            .add_dfa_data(DFAS)
+            .add_scanner_mode_data(MODES)
+            .add_contextual_keyword_data(&[(2, TokenType::new(0), "aa", TokenType::new(1))])
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn test_case_insensitive_keywords() {
+        // The DFA of the pattern `a+` where the character class matches `a` and `A`
+        const DFAS: &[DfaData] = &[("a+", &[1], &[(0, 1), (1, 2)], &[(0, 1), (0, 1)])];
+        let scanner = ScannerBuilder::new()
+            .add_dfa_data(DFAS)
+            .add_case_insensitive_keyword_data(&[(0, TokenType::new(0), "aa", TokenType::new(1))])
+            .build()
+            .unwrap();
+        let matches = scanner
+            .token_iter("aA a AAA Aa", |c, _| c.eq_ignore_ascii_case(&'a'))
+            .map(|token| (token.token_type.as_usize(), token.text))
+            .collect::<Vec<_>>();
+        // The keywords keep their original text
+        assert_eq!(matches, vec![(1, "aA"), (0, "a"), (0, "AAA"), (1, "Aa")]);
+    }
 }
@@ -1,26 +1,28 @@
-use crate::{DfaData, ScannerModeData};
+use std::sync::Arc;
 
-use super::{Dfa, DfaWithTokenType, Scanner, ScannerMode};
+use crate::{
+    CaseInsensitiveKeywordData, ContextualKeywordData, CountedStateData, CountingModeData, DfaData,
+    OwnedDfaData, RunClassData, ScannerModeData, ScannerModeId, StartSetData, TokenType,
+};
+
+use super::{json, BuildError, Dfa, DfaWithTokenType, Scanner, ScannerMode, StartSet};
 
 /// A scanner builder is used to build a scanner.
 ///
-/// Here are the steps to build a scanner with a scanner builder:
-///
-/// [ScannerBuilder] -> [ScannerBuilder::add_scanner_mode_data] -> [ScannerBuilderWithScannerModes]
-///
-/// [ScannerBuilder] -> [ScannerBuilder::add_dfa_data] -> [ScannerBuilderWithsDfas]
-///
-/// [ScannerBuilderWithsDfas] -> [ScannerBuilderWithsDfas::add_scanner_mode_data] -> [ScannerBuilderWithsDfasAndScannerModes]
-///
-/// [ScannerBuilderWithsDfas] -> [ScannerBuilderWithsDfas::build] -> [Scanner] with default mode
-///
-/// [ScannerBuilderWithScannerModes] -> [ScannerBuilderWithScannerModes::add_dfa_data] -> [ScannerBuilderWithsDfasAndScannerModes]
+/// The DFA data is mandatory, all other data is optional and can be added in any order:
+/// * [ScannerBuilder::add_dfa_data] adds the DFAs.
+/// * [ScannerBuilder::add_scanner_mode_data] adds the scanner modes. If no scanner modes are
+///   added, a default mode is created that contains all DFAs.
+/// * [ScannerBuilder::add_start_set_data] adds the start sets of the scanner modes.
+/// * [ScannerBuilder::add_run_class_data] adds the characters of the classes the DFAs loop on.
+/// * [ScannerBuilder::add_mode_match_functions] adds the match functions of the scanner modes.
+/// * [ScannerBuilder::add_counting_mode_data] turns scanner modes into counting modes.
+/// * [ScannerBuilder::add_evaluation_orders] sets the order in which the DFAs of the scanner
+///   modes are advanced.
+/// * [ScannerBuilder::add_disabled_dfas] removes DFAs from all scanner modes.
 ///
-/// [ScannerBuilderWithsDfasAndScannerModes] -> [ScannerBuilderWithsDfasAndScannerModes::build] -> [Scanner] with configured modes
-///
-/// This way it is guaranteed that the scanner is built with all necessary data.
-/// The build method is the only way to build the scanner from the scanner builder.
-/// It is unfailable and returns a scanner directly instead of a Result.
+/// The data is validated by [ScannerBuilder::build], which returns a [BuildError] if the data is
+/// inconsistent, e.g. if a scanner mode references a DFA that doesn't exist.
 ///
 /// It is advised to use the fluent notation to build the scanner, like this:
 /// ```rust
@@ -30,142 +32,569 @@ use super::{Dfa, DfaWithTokenType, Scanner, ScannerMode};
 /// let mut scanner = ScannerBuilder::new()
 ///     .add_dfa_data(DFAS)
 ///     .add_scanner_mode_data(MODES)
-///     .build();
+///     .build()
+///     .expect("Invalid scanner data");
 /// ```
+#[derive(Debug, Default, Clone)]
+pub struct ScannerBuilder {
+    dfas: Option<Vec<Dfa>>,
+    scanner_mode_data: Vec<ScannerModeData>,
+    start_set_data: Vec<StartSetData>,
+    run_class_data: Vec<RunClassData>,
+    mode_match_functions: Vec<fn(char, usize) -> bool>,
+    counting_mode_data: Vec<CountingModeData>,
+    evaluation_orders: Vec<&'static [usize]>,
+    counted_state_data: Vec<CountedStateData>,
+    disabled_dfas: Vec<usize>,
+    contextual_keyword_data: Vec<ContextualKeywordData>,
+    case_insensitive_keyword_data: Vec<CaseInsensitiveKeywordData>,
+}
 
-///
-#[derive(Debug, Default)]
-pub struct ScannerBuilder {}
+/// The scanner builder after DFA data has been added.
+#[deprecated(note = "Use ScannerBuilder instead")]
+pub type ScannerBuilderWithsDfas = ScannerBuilder;
+
+/// The scanner builder after scanner mode data has been added.
+#[deprecated(note = "Use ScannerBuilder instead")]
+pub type ScannerBuilderWithScannerModes = ScannerBuilder;
+
+/// The scanner builder after DFA data and scanner mode data have been added.
+#[deprecated(note = "Use ScannerBuilder instead")]
+pub type ScannerBuilderWithsDfasAndScannerModes = ScannerBuilder;
 
 impl ScannerBuilder {
     /// Creates a new scanner builder.
     pub fn new() -> Self {
-        Self {}
+        Self::default()
+    }
+
+    /// Adds DFA data to the scanner builder.
+    /// The tables of the generated DFA data are borrowed, not copied.
+    pub fn add_dfa_data(mut self, dfa_data: &[DfaData]) -> Self {
+        self.dfas = Some(dfa_data.iter().map(Dfa::from).collect());
+        self
+    }
+
+    /// Adds owned DFA data to the scanner builder, e.g. data exported with
+    /// [Scanner::export_dfa_data]. It replaces DFA data added before.
+    pub fn add_owned_dfa_data(mut self, dfa_data: Vec<OwnedDfaData>) -> Self {
+        self.dfas = Some(dfa_data.into_iter().map(Dfa::from).collect());
+        self
     }
 
     /// Adds scanner mode data to the scanner builder.
-    /// Creates a ScannerBuilderWithScannerModes
-    pub fn add_scanner_mode_data(
-        self,
-        scanner_mode_data: &[ScannerModeData],
-    ) -> ScannerBuilderWithScannerModes {
-        let mut scanner_modes = Vec::new();
-        for mode in scanner_mode_data {
-            let scanner_mode = ScannerMode::new(&[], mode);
-            scanner_modes.push(scanner_mode);
+    pub fn add_scanner_mode_data(mut self, scanner_mode_data: &[ScannerModeData]) -> Self {
+        self.scanner_mode_data = scanner_mode_data.to_vec();
+        self
+    }
+
+    /// Adds the start sets of the scanner modes to the scanner builder.
+    /// The start set at index `n` belongs to the scanner mode at index `n`.
+    pub fn add_start_set_data(mut self, start_set_data: &[StartSetData]) -> Self {
+        self.start_set_data = start_set_data.to_vec();
+        self
+    }
+
+    /// Adds the characters of the character classes the DFAs loop on to the scanner builder.
+    /// The scanner consumes runs of these characters, e.g. within whitespace or identifiers,
+    /// without calling the match function for each character.
+    pub fn add_run_class_data(mut self, run_class_data: &[RunClassData]) -> Self {
+        self.run_class_data = run_class_data.to_vec();
+        self
+    }
+
+    /// Adds the match functions of the scanner modes to the scanner builder.
+    /// The match function at index `n` belongs to the scanner mode at index `n` and is used
+    /// instead of the match function given to the search while the mode is active.
+    pub fn add_mode_match_functions(mut self, match_functions: &[fn(char, usize) -> bool]) -> Self {
+        self.mode_match_functions = match_functions.to_vec();
+        self
+    }
+
+    /// Adds the configuration of counting scanner modes to the scanner builder.
+    /// A counting mode tracks the nesting depth of its tokens, e.g. of nested comments, and is
+    /// left when the depth drops to zero. See [CountingModeData].
+    pub fn add_counting_mode_data(mut self, counting_mode_data: &[CountingModeData]) -> Self {
+        self.counting_mode_data = counting_mode_data.to_vec();
+        self
+    }
+
+    /// Adds the counted states of the DFAs to the scanner builder, i.e. the states that loop on
+    /// the characters of a large repetition whose bounds are checked by counting the repetitions.
+    /// See [CountedStateData].
+    pub fn add_counted_state_data(mut self, counted_state_data: &[CountedStateData]) -> Self {
+        self.counted_state_data = counted_state_data.to_vec();
+        self
+    }
+
+    /// Adds the evaluation orders of the scanner modes to the scanner builder.
+    /// The evaluation order at index `n` belongs to the scanner mode at index `n` and lists the
+    /// indices of the DFAs of the mode in the order they are advanced during a search. It
+    /// doesn't change the priorities of the DFAs.
+    pub fn add_evaluation_orders(mut self, evaluation_orders: &[&'static [usize]]) -> Self {
+        self.evaluation_orders = evaluation_orders.to_vec();
+        self
+    }
+
+    /// Adds the indices of DFAs that are removed from all scanner modes, e.g. the terminals of a
+    /// language dialect that isn't enabled. The generated code disables the terminals whose
+    /// features are disabled, see [crate::GenerateOptions::with_terminal_features].
+    ///
+    /// The token types of the remaining DFAs don't change.
+    pub fn add_disabled_dfas(mut self, disabled_dfas: &[usize]) -> Self {
+        self.disabled_dfas = disabled_dfas.to_vec();
+        self
+    }
+
+    /// Adds the contextual keywords of the scanner modes to the scanner builder, see
+    /// [ContextualKeywordData] and [Scanner::reinterpret].
+    pub fn add_contextual_keyword_data(
+        mut self,
+        contextual_keyword_data: &[ContextualKeywordData],
+    ) -> Self {
+        self.contextual_keyword_data = contextual_keyword_data.to_vec();
+        self
+    }
+
+    /// Adds the case-insensitive keywords of the scanner modes to the scanner builder, see
+    /// [CaseInsensitiveKeywordData].
+    pub fn add_case_insensitive_keyword_data(
+        mut self,
+        case_insensitive_keyword_data: &[CaseInsensitiveKeywordData],
+    ) -> Self {
+        self.case_insensitive_keyword_data = case_insensitive_keyword_data.to_vec();
+        self
+    }
+
+    /// Builds the scanner from the scanner builder.
+    ///
+    /// # Errors
+    /// Returns a [BuildError] if no DFA data has been added or if a scanner mode references a DFA
+    /// or a scanner mode that doesn't exist, if an evaluation order is no permutation of the
+    /// DFAs of its scanner mode, if a counted state doesn't exist or if a disabled DFA doesn't
+    /// exist.
+    pub fn build(self) -> Result<Scanner, BuildError> {
+        let mut dfas = self.dfas.ok_or(BuildError::MissingDfas)?;
+        Self::apply_counted_states(&mut dfas, &self.counted_state_data)?;
+        self.scanner_mode_data.iter().try_for_each(|mode| {
+            Self::validate_mode(mode, dfas.len(), self.scanner_mode_data.len())
+        })?;
+        let dfas: Vec<Arc<Dfa>> = dfas.into_iter().map(Arc::new).collect();
+        let scanner_modes = self
+            .scanner_mode_data
+            .iter()
+            .map(|mode| ScannerMode::new(&dfas, mode))
+            .collect();
+        let mut scanner = Scanner::new(dfas, scanner_modes);
+        if scanner.scanner_modes.is_empty() {
+            Self::create_default_mode(&mut scanner);
         }
+        Self::apply_start_sets(&mut scanner, &self.start_set_data);
+        Self::apply_run_classes(&mut scanner, &self.run_class_data);
+        Self::apply_mode_match_functions(&mut scanner, self.mode_match_functions);
+        Self::apply_counting_modes(&mut scanner, &self.counting_mode_data)?;
+        scanner.nesting_depth = scanner.initial_nesting_depth(scanner.current_mode);
+        Self::apply_evaluation_orders(&mut scanner, &self.evaluation_orders)?;
+        Self::apply_disabled_dfas(&mut scanner, &self.disabled_dfas)?;
+        Self::apply_contextual_keywords(&mut scanner, &self.contextual_keyword_data)?;
+        Self::apply_case_insensitive_keywords(&mut scanner, &self.case_insensitive_keyword_data)?;
+        Ok(scanner)
+    }
 
-        ScannerBuilderWithScannerModes { scanner_modes }
+    /// Builds a scanner from its definition in JSON as exported by
+    /// [Scanner::definition_to_json], e.g. by another build or tool.
+    ///
+    /// The DFAs of the scanner know the characters of their classes, so the match function given
+    /// to the search of the scanner isn't used and generated code isn't needed.
+    ///
+    /// # Errors
+    /// Returns [BuildError::InvalidJson] if the JSON is malformed or doesn't follow the schema,
+    /// and the errors of [ScannerBuilder::build] if the definition is inconsistent.
+    pub fn build_from_json(json: &str) -> Result<Scanner, BuildError> {
+        json::scanner_from_definition(json)
     }
 
-    /// Adds DFA data to the scanner builder.
-    pub fn add_dfa_data(self, dfa_data: &[DfaData]) -> ScannerBuilderWithsDfas {
-        ScannerBuilderWithsDfas {
-            dfas: dfa_data.iter().map(|dfa| dfa.into()).collect(),
+    /// Checks that the scanner mode only references existing DFAs and scanner modes.
+    fn validate_mode(
+        mode: &ScannerModeData,
+        dfa_count: usize,
+        mode_count: usize,
+    ) -> Result<(), BuildError> {
+        if let Some((dfa_index, _)) = mode.1.iter().find(|(dfa_index, _)| *dfa_index >= dfa_count) {
+            return Err(BuildError::UnknownDfa {
+                mode: mode.0.to_string(),
+                dfa_index: *dfa_index,
+                dfa_count,
+            });
+        }
+        if let Some((_, target)) = mode
+            .2
+            .iter()
+            .find(|(_, target)| target.as_usize() >= mode_count)
+        {
+            return Err(BuildError::UnknownScannerMode {
+                mode: mode.0.to_string(),
+                target: *target,
+                mode_count,
+            });
         }
+        Ok(())
     }
 
     /// Creates a default mode for the scanner.
     /// The default mode is created if no scanner modes have been added to the scanner builder.
     /// The default mode contains all DFAs and assigns incrementing token type numbers to them.
-    fn create_default_mode(scanner: &mut Scanner) {
-        let mut token_type = 0;
-        let dfas = scanner.dfas.iter().map(|dfa| {
-            let dfa = DfaWithTokenType::new(dfa.clone(), token_type);
-            token_type += 1;
-            dfa
-        });
+    pub(crate) fn create_default_mode(scanner: &mut Scanner) {
+        let dfas = scanner
+            .dfas
+            .iter()
+            .enumerate()
+            .map(|(token_type, dfa)| DfaWithTokenType::new(Arc::clone(dfa), token_type.into()));
         let default_mode = ScannerMode {
             name: "INITIAL".to_string(),
             dfas: dfas.collect(),
             // The default mode has no transitions.
             transitions: Vec::new(),
+            start_set: None,
+            run_sets: Arc::default(),
+            counting: None,
+            evaluation_order: Vec::new(),
+            error_token_type: None,
+            matches_char_class: None,
+            generation: 0,
+            active_dfas: Vec::new(),
+            matched_dfas: Vec::new(),
         };
         scanner.scanner_modes.push(default_mode);
     }
-}
 
-/// A scanner builder with DFAs. Remember to always starts with [ScannerBuilder].
-///
-/// You can add scanner mode data to the scanner builder.
-/// Also you can call the build method to build the scanner.
-/// if no scanner mode data is added, a default mode is created in the build method.
-pub struct ScannerBuilderWithsDfas {
-    pub(crate) dfas: Vec<Dfa>,
-}
+    /// Assigns the start sets to the scanner modes with the same index.
+    fn apply_start_sets(scanner: &mut Scanner, start_set_data: &[StartSetData]) {
+        for (mode, start_set) in scanner.scanner_modes.iter_mut().zip(start_set_data) {
+            mode.start_set = Some(StartSet::from(start_set));
+        }
+    }
 
-impl ScannerBuilderWithsDfas {
-    /// Adds scanner mode data to the scanner builder.
-    pub fn add_scanner_mode_data(
-        self,
-        scanner_mode_data: &[ScannerModeData],
-    ) -> ScannerBuilderWithsDfasAndScannerModes {
-        let ScannerBuilderWithsDfas { dfas } = self;
-        let mut scanner_modes = Vec::new();
-        for mode in scanner_mode_data {
-            let scanner_mode = ScannerMode::new(&dfas, mode);
-            scanner_modes.push(scanner_mode);
+    /// Assigns the characters of the run classes to all scanner modes.
+    fn apply_run_classes(scanner: &mut Scanner, run_class_data: &[RunClassData]) {
+        if run_class_data.is_empty() {
+            return;
+        }
+        let class_count = run_class_data
+            .iter()
+            .map(|(char_class, _)| char_class + 1)
+            .max()
+            .unwrap_or_default();
+        let mut run_sets = vec![None; class_count];
+        for (char_class, char_set) in run_class_data {
+            if let Some(run_set) = run_sets.get_mut(*char_class) {
+                *run_set = Some(StartSet::from(char_set));
+            }
+        }
+        let run_sets: Arc<[Option<StartSet>]> = run_sets.into();
+        for mode in &mut scanner.scanner_modes {
+            mode.run_sets = Arc::clone(&run_sets);
         }
+    }
 
-        ScannerBuilderWithsDfasAndScannerModes {
-            dfas,
-            scanner_modes,
+    /// Assigns the evaluation orders to the scanner modes with the same index.
+    fn apply_evaluation_orders(
+        scanner: &mut Scanner,
+        evaluation_orders: &[&[usize]],
+    ) -> Result<(), BuildError> {
+        for (mode, evaluation_order) in scanner.scanner_modes.iter_mut().zip(evaluation_orders) {
+            let mut sorted = evaluation_order.to_vec();
+            sorted.sort_unstable();
+            if !sorted.iter().copied().eq(0..mode.dfas.len()) {
+                return Err(BuildError::InvalidEvaluationOrder {
+                    mode: mode.name.clone(),
+                });
+            }
+            mode.evaluation_order = evaluation_order.to_vec();
         }
+        Ok(())
     }
 
-    /// Builds the scanner.
-    /// Builds the scanner from the scanner builder.
-    pub fn build(self) -> Scanner {
-        let mut scanner = Scanner {
-            dfas: self.dfas,
-            scanner_modes: Vec::new(),
-            current_mode: 0,
-        };
-        ScannerBuilder::create_default_mode(&mut scanner);
-        scanner
+    /// Assigns the contextual keywords to the scanner.
+    fn apply_contextual_keywords(
+        scanner: &mut Scanner,
+        contextual_keyword_data: &[ContextualKeywordData],
+    ) -> Result<(), BuildError> {
+        Self::check_keyword_modes(scanner, contextual_keyword_data)?;
+        scanner.contextual_keywords = contextual_keyword_data.to_vec();
+        Ok(())
     }
-}
 
-/// A scanner builder with scanner modes. Remember to always starts with [ScannerBuilder].
-///
-/// You can add DFA data to the scanner builder.
-/// Because the scanner needs Dfas this struct has no build method.
-pub struct ScannerBuilderWithScannerModes {
-    pub(crate) scanner_modes: Vec<ScannerMode>,
-}
+    /// Assigns the case-insensitive keywords to the scanner.
+    fn apply_case_insensitive_keywords(
+        scanner: &mut Scanner,
+        case_insensitive_keyword_data: &[CaseInsensitiveKeywordData],
+    ) -> Result<(), BuildError> {
+        Self::check_keyword_modes(scanner, case_insensitive_keyword_data)?;
+        scanner.case_insensitive_keywords = case_insensitive_keyword_data.to_vec();
+        Ok(())
+    }
 
-impl ScannerBuilderWithScannerModes {
-    /// Adds DFA data to the scanner builder.
-    pub fn add_dfa_data(self, dfa_data: &[DfaData]) -> ScannerBuilderWithsDfasAndScannerModes {
-        let dfas = dfa_data.iter().map(|dfa| dfa.into()).collect();
-        ScannerBuilderWithsDfasAndScannerModes {
-            dfas,
-            scanner_modes: self.scanner_modes,
+    /// Checks that the scanner modes of the given keywords exist.
+    fn check_keyword_modes(
+        scanner: &Scanner,
+        keyword_data: &[(usize, TokenType, &'static str, TokenType)],
+    ) -> Result<(), BuildError> {
+        let mode_count = scanner.scanner_modes.len();
+        if let Some((mode_index, ..)) = keyword_data
+            .iter()
+            .find(|(mode_index, ..)| *mode_index >= mode_count)
+        {
+            return Err(BuildError::UnknownScannerMode {
+                mode: mode_index.to_string(),
+                target: ScannerModeId::new(*mode_index),
+                mode_count,
+            });
         }
+        Ok(())
     }
-}
 
-/// A scanner builder with DFAs and scanner modes. Remember to always starts with [ScannerBuilder].
-///
-/// You can call the build method to build the scanner.
-/// If the added scanner modes are empty, a default mode is created in the build method.
-pub struct ScannerBuilderWithsDfasAndScannerModes {
-    pub(crate) dfas: Vec<Dfa>,
-    pub(crate) scanner_modes: Vec<ScannerMode>,
+    /// Removes the disabled DFAs from all scanner modes. The DFAs stay in the scanner, so the
+    /// indices of the DFAs don't change.
+    fn apply_disabled_dfas(
+        scanner: &mut Scanner,
+        disabled_dfas: &[usize],
+    ) -> Result<(), BuildError> {
+        let dfa_count = scanner.dfas.len();
+        let disabled = disabled_dfas
+            .iter()
+            .map(|dfa_index| {
+                scanner
+                    .dfas
+                    .get(*dfa_index)
+                    .ok_or(BuildError::UnknownDisabledDfa {
+                        dfa_index: *dfa_index,
+                        dfa_count,
+                    })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if disabled.is_empty() {
+            return Ok(());
+        }
+        for mode in &mut scanner.scanner_modes {
+            mode.remove_dfas(|dfa| disabled.iter().any(|disabled| Arc::ptr_eq(disabled, dfa)));
+        }
+        Ok(())
+    }
+
+    /// Turns the scanner modes referenced by the counting mode data into counting modes.
+    fn apply_counting_modes(
+        scanner: &mut Scanner,
+        counting_mode_data: &[CountingModeData],
+    ) -> Result<(), BuildError> {
+        let mode_count = scanner.scanner_modes.len();
+        for (mode_index, open, close, exit) in counting_mode_data {
+            if *mode_index >= mode_count || exit.as_usize() >= mode_count {
+                return Err(BuildError::UnknownScannerMode {
+                    mode: scanner
+                        .scanner_modes
+                        .get(*mode_index)
+                        .map_or_else(|| mode_index.to_string(), |mode| mode.name.clone()),
+                    target: if *mode_index >= mode_count {
+                        ScannerModeId::new(*mode_index)
+                    } else {
+                        *exit
+                    },
+                    mode_count,
+                });
+            }
+            if let Some(mode) = scanner.scanner_modes.get_mut(*mode_index) {
+                mode.counting = Some((*open, *close, *exit));
+            }
+        }
+        Ok(())
+    }
+
+    /// Assigns the counted states to their DFAs.
+    fn apply_counted_states(
+        dfas: &mut [Dfa],
+        counted_state_data: &[CountedStateData],
+    ) -> Result<(), BuildError> {
+        for (dfa_index, state, min, max) in counted_state_data {
+            match dfas.get_mut(*dfa_index) {
+                Some(dfa) if *state < dfa.state_count() && max.is_none_or(|max| *min <= max) => {
+                    dfa.counted_states.push((*state, *min, *max));
+                }
+                _ => {
+                    return Err(BuildError::InvalidCountedState {
+                        dfa_index: *dfa_index,
+                        state: *state,
+                    })
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Assigns the match functions to the scanner modes with the same index.
+    fn apply_mode_match_functions(
+        scanner: &mut Scanner,
+        match_functions: Vec<fn(char, usize) -> bool>,
+    ) {
+        for (mode, match_function) in scanner.scanner_modes.iter_mut().zip(match_functions) {
+            mode.matches_char_class = Some(match_function);
+        }
+    }
 }
 
-impl ScannerBuilderWithsDfasAndScannerModes {
-    /// Builds the scanner.
-    /// Builds the scanner from the scanner builder.
-    pub fn build(self) -> Scanner {
-        let mut scanner = Scanner {
-            dfas: self.dfas,
-            scanner_modes: self.scanner_modes,
-            current_mode: 0,
-        };
-        if scanner.scanner_modes.is_empty() {
-            ScannerBuilder::create_default_mode(&mut scanner);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TokenType;
+
+    const DFAS: &[DfaData] = &[("a", &[1], &[(0, 1), (0, 0)], &[(0, 1)])];
+
+    #[test]
+    fn test_build_without_dfas() {
+        assert_eq!(
+            ScannerBuilder::new().build().unwrap_err(),
+            BuildError::MissingDfas
+        );
+    }
+
+    #[test]
+    fn test_build_with_unknown_dfa() {
+        const MODES: &[ScannerModeData] = &[("INITIAL", &[(1, TokenType::new(0))], &[], None)];
+        let err = ScannerBuilder::new()
+            .add_dfa_data(DFAS)
+            .add_scanner_mode_data(MODES)
+            .build()
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Scanner mode INITIAL references the unknown DFA 1, only 1 DFAs are given"
+        );
+    }
+
+    #[test]
+    fn test_build_with_unknown_scanner_mode() {
+        const MODES: &[ScannerModeData] = &[(
+            "INITIAL",
+            &[(0, TokenType::new(0))],
+            &[(TokenType::new(0), ScannerModeId::new(1))],
+            None,
+        )];
+        let err = ScannerBuilder::new()
+            .add_scanner_mode_data(MODES)
+            .add_dfa_data(DFAS)
+            .build()
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            BuildError::UnknownScannerMode { mode_count: 1, .. }
+        ));
+    }
+
+    #[test]
+    fn test_build_with_invalid_evaluation_order() {
+        let err = ScannerBuilder::new()
+            .add_dfa_data(DFAS)
+            .add_evaluation_orders(&[&[1]])
+            .build()
+            .unwrap_err();
+        assert_eq!(
+            err,
+            BuildError::InvalidEvaluationOrder {
+                mode: "INITIAL".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_disabled_dfas() {
+        fn matches_char_class(c: char, char_class: usize) -> bool {
+            c == ['a', 'b'][char_class]
+        }
+        const DFAS: &[DfaData] = &[
+            ("a", &[1], &[(0, 1), (1, 1)], &[(0, 1)]),
+            ("b", &[1], &[(0, 1), (1, 1)], &[(1, 1)]),
+        ];
+        let scanner = ScannerBuilder::new()
+            .add_dfa_data(DFAS)
+            .add_evaluation_orders(&[&[1, 0]])
+            .add_disabled_dfas(&[0])
+            .build()
+            .unwrap();
+        let matches = scanner
+            .find_iter("ab", matches_char_class)
+            .map(|m| (m.token_type(), m.span().range()))
+            .collect::<Vec<_>>();
+        // The token type of the remaining DFA doesn't change
+        assert_eq!(matches, vec![(TokenType::new(1), 1..2)]);
+        assert_eq!(scanner.scanner_modes[0].evaluation_order, vec![0]);
+        assert_eq!(scanner.dfas().count(), 2);
+
+        let err = ScannerBuilder::new()
+            .add_dfa_data(DFAS)
+            .add_disabled_dfas(&[2])
+            .build()
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "The disabled DFA 2 doesn't exist, only 2 DFAs are given"
+        );
+    }
+
+    #[test]
+    fn test_counted_states() {
+        fn matches_char_class(c: char, _char_class: usize) -> bool {
+            c.is_ascii_digit()
+        }
+        // The DFA of `[0-9]{2,3}` loops in state 1, which counts the digits
+        const DFAS: &[DfaData] = &[("[0-9]{2,3}", &[1], &[(0, 1), (1, 2)], &[(0, 1), (0, 1)])];
+        let scanner = ScannerBuilder::new()
+            .add_dfa_data(DFAS)
+            .add_counted_state_data(&[(0, 1, 2, Some(3))])
+            .build()
+            .unwrap();
+        let spans = scanner
+            .find_iter("1 12 1234", matches_char_class)
+            .map(|m| m.span().range())
+            .collect::<Vec<_>>();
+        assert_eq!(spans, vec![2..4, 5..8]);
+        let completions = scanner.longest_prefix("123", matches_char_class);
+        assert!(completions[0].is_match && !completions[0].can_continue);
+        assert!(scanner
+            .longest_prefix("1234", matches_char_class)
+            .is_empty());
+        assert_eq!(
+            scanner.dfas().next().unwrap().to_json(),
+            r#"{"pattern":"[0-9]{2,3}","accepting_states":[1],"states":[[[0,1]],[[0,1]]],"counted_states":[[1,2,3]]}"#
+        );
+
+        let err = ScannerBuilder::new()
+            .add_dfa_data(DFAS)
+            .add_counted_state_data(&[(0, 2, 2, None)])
+            .build()
+            .unwrap_err();
+        assert_eq!(
+            err,
+            BuildError::InvalidCountedState {
+                dfa_index: 0,
+                state: 2
+            }
+        );
+    }
+
+    #[test]
+    fn test_owned_dfa_data_round_trip() {
+        fn matches_char_class(c: char, _char_class: usize) -> bool {
+            c == 'a'
         }
-        scanner
+        let scanner = ScannerBuilder::new().add_dfa_data(DFAS).build().unwrap();
+        let exported = scanner.export_dfa_data();
+        assert_eq!(exported, vec![OwnedDfaData::from(&DFAS[0])]);
+        let rebuilt = ScannerBuilder::new()
+            .add_owned_dfa_data(exported)
+            .build()
+            .unwrap();
+        assert!(rebuilt
+            .find_iter("bab", matches_char_class)
+            .eq(scanner.find_iter("bab", matches_char_class)));
     }
 }
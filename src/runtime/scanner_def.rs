@@ -0,0 +1,249 @@
+use crate::{
+    CaseInsensitiveKeywordData, ContextualKeywordData, CountedStateData, CountingModeData, DfaData,
+    RunClassData, ScannerModeData, StartSetData, TokenType, TABLE_FORMAT_VERSION,
+};
+
+use super::{Scanner, ScannerBuilder};
+
+/// The static definition of a scanner, i.e. all data generated by `scangen`.
+///
+/// A scanner definition can be created in a const context, so the generated code can define it as
+/// a `static` item. Creating a scanner from the definition borrows the DFA tables instead of
+/// copying them. Only the matching states of the scanner modes are allocated.
+///
+/// The generated code states the version of `scangen` and of the table format it was generated
+/// with by [ScannerDef::generated_with]. Data of an incompatible table format is rejected when the
+/// definition is evaluated, i.e. at compile time for a `static` item.
+///
+/// ```rust
+/// use scangen::{DfaData, ScannerDef, ScannerModeData};
+/// const DFAS: &[DfaData] = &[/* ... */];
+/// const MODES: &[ScannerModeData] = &[/* ... */];
+/// static SCANNER_DEF: ScannerDef = ScannerDef::new(DFAS, MODES);
+/// let scanner = SCANNER_DEF.create_scanner();
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct ScannerDef {
+    scangen_version: Option<&'static str>,
+    dfas: &'static [DfaData],
+    modes: &'static [ScannerModeData],
+    start_sets: &'static [StartSetData],
+    run_classes: &'static [RunClassData],
+    mode_match_functions: &'static [fn(char, usize) -> bool],
+    counting_modes: &'static [CountingModeData],
+    evaluation_orders: &'static [&'static [usize]],
+    counted_states: &'static [CountedStateData],
+    disabled_dfas: &'static [usize],
+    newline_token_type: Option<TokenType>,
+    skipped_token_types: &'static [TokenType],
+    contextual_keywords: &'static [ContextualKeywordData],
+    case_insensitive_keywords: &'static [CaseInsensitiveKeywordData],
+}
+
+impl ScannerDef {
+    /// Creates a new scanner definition from the DFAs and the scanner modes.
+    /// If the scanner modes are empty, the scanner gets a default mode.
+    pub const fn new(dfas: &'static [DfaData], modes: &'static [ScannerModeData]) -> Self {
+        Self {
+            scangen_version: None,
+            dfas,
+            modes,
+            start_sets: &[],
+            run_classes: &[],
+            mode_match_functions: &[],
+            counting_modes: &[],
+            evaluation_orders: &[],
+            counted_states: &[],
+            disabled_dfas: &[],
+            newline_token_type: None,
+            skipped_token_types: &[],
+            contextual_keywords: &[],
+            case_insensitive_keywords: &[],
+        }
+    }
+
+    /// Sets the version of `scangen` and of the table format the data was generated with.
+    ///
+    /// # Panics
+    /// Panics if the table format is not [TABLE_FORMAT_VERSION]. In a const context this is a
+    /// compile error.
+    #[allow(clippy::panic)]
+    pub const fn generated_with(
+        mut self,
+        scangen_version: &'static str,
+        table_format_version: u32,
+    ) -> Self {
+        if table_format_version != TABLE_FORMAT_VERSION {
+            panic!(
+                "The scanner was generated with an incompatible version of scangen. \
+                Regenerate it with the scangen version of the runtime."
+            );
+        }
+        self.scangen_version = Some(scangen_version);
+        self
+    }
+
+    /// Returns the version of `scangen` the data was generated with, if the generated code
+    /// states it.
+    pub const fn scangen_version(&self) -> Option<&'static str> {
+        self.scangen_version
+    }
+
+    /// Sets the start sets of the scanner modes.
+    /// The start set at index `n` belongs to the scanner mode at index `n`.
+    pub const fn with_start_sets(mut self, start_sets: &'static [StartSetData]) -> Self {
+        self.start_sets = start_sets;
+        self
+    }
+
+    /// Sets the characters of the character classes the DFAs loop on. See [RunClassData].
+    pub const fn with_run_classes(mut self, run_classes: &'static [RunClassData]) -> Self {
+        self.run_classes = run_classes;
+        self
+    }
+
+    /// Sets the match functions of the scanner modes.
+    /// The match function at index `n` belongs to the scanner mode at index `n`.
+    pub const fn with_mode_match_functions(
+        mut self,
+        mode_match_functions: &'static [fn(char, usize) -> bool],
+    ) -> Self {
+        self.mode_match_functions = mode_match_functions;
+        self
+    }
+
+    /// Sets the configuration of the counting scanner modes. See [CountingModeData].
+    pub const fn with_counting_modes(
+        mut self,
+        counting_modes: &'static [CountingModeData],
+    ) -> Self {
+        self.counting_modes = counting_modes;
+        self
+    }
+
+    /// Sets the order in which the DFAs of the scanner modes are advanced.
+    /// The evaluation order at index `n` belongs to the scanner mode at index `n`.
+    pub const fn with_evaluation_orders(
+        mut self,
+        evaluation_orders: &'static [&'static [usize]],
+    ) -> Self {
+        self.evaluation_orders = evaluation_orders;
+        self
+    }
+
+    /// Sets the counted states of the DFAs. See [CountedStateData].
+    pub const fn with_counted_states(
+        mut self,
+        counted_states: &'static [CountedStateData],
+    ) -> Self {
+        self.counted_states = counted_states;
+        self
+    }
+
+    /// Sets the indices of the DFAs that are removed from all scanner modes. The generated code
+    /// lists the terminals whose features are disabled, see
+    /// [crate::ScannerBuilder::add_disabled_dfas].
+    pub const fn with_disabled_dfas(mut self, disabled_dfas: &'static [usize]) -> Self {
+        self.disabled_dfas = disabled_dfas;
+        self
+    }
+
+    /// Sets the token type of the newline terminal, see [Scanner::set_newline_token_type].
+    pub const fn with_newline_token_type(mut self, newline_token_type: TokenType) -> Self {
+        self.newline_token_type = Some(newline_token_type);
+        self
+    }
+
+    /// Sets the token types of the matches that are skipped, see
+    /// [Scanner::set_skipped_token_types].
+    pub const fn with_skipped_token_types(
+        mut self,
+        skipped_token_types: &'static [TokenType],
+    ) -> Self {
+        self.skipped_token_types = skipped_token_types;
+        self
+    }
+
+    /// Sets the contextual keywords of the scanner modes, see [Scanner::reinterpret].
+    pub const fn with_contextual_keywords(
+        mut self,
+        contextual_keywords: &'static [ContextualKeywordData],
+    ) -> Self {
+        self.contextual_keywords = contextual_keywords;
+        self
+    }
+
+    /// Sets the case-insensitive keywords of the scanner modes, see [CaseInsensitiveKeywordData].
+    pub const fn with_case_insensitive_keywords(
+        mut self,
+        case_insensitive_keywords: &'static [CaseInsensitiveKeywordData],
+    ) -> Self {
+        self.case_insensitive_keywords = case_insensitive_keywords;
+        self
+    }
+
+    /// Creates a scanner from the definition.
+    ///
+    /// # Panics
+    /// Panics if the definition is inconsistent, which can't happen for generated definitions.
+    #[allow(clippy::panic)]
+    pub fn create_scanner(&self) -> Scanner {
+        let mut scanner = ScannerBuilder::new()
+            .add_dfa_data(self.dfas)
+            .add_scanner_mode_data(self.modes)
+            .add_start_set_data(self.start_sets)
+            .add_run_class_data(self.run_classes)
+            .add_mode_match_functions(self.mode_match_functions)
+            .add_counting_mode_data(self.counting_modes)
+            .add_evaluation_orders(self.evaluation_orders)
+            .add_counted_state_data(self.counted_states)
+            .add_disabled_dfas(self.disabled_dfas)
+            .add_contextual_keyword_data(self.contextual_keywords)
+            .add_case_insensitive_keyword_data(self.case_insensitive_keywords)
+            .build()
+            .unwrap_or_else(|err| panic!("Invalid scanner definition: {}", err));
+        scanner.set_newline_token_type(self.newline_token_type);
+        scanner.set_skipped_token_types(self.skipped_token_types.iter().copied());
+        scanner
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use super::*;
+
+    const DFAS: &[DfaData] = &[("a", &[1], &[(0, 1), (1, 1)], &[(0, 1)])];
+
+    static SCANNER_DEF: ScannerDef = ScannerDef::new(DFAS, &[])
+        .generated_with("0.1.0", TABLE_FORMAT_VERSION)
+        .with_start_sets(&[(1 << 97, &[])]);
+
+    fn matches_char_class(c: char, _char_class: usize) -> bool {
+        c == 'a'
+    }
+
+    #[test]
+    fn test_scanner_def() {
+        let scanner = SCANNER_DEF.create_scanner();
+        // The DFA tables are borrowed from the static data
+        assert!(matches!(scanner.dfas[0].transitions, Cow::Borrowed(_)));
+        assert!(std::ptr::eq(
+            scanner.dfas[0].transitions.as_ptr(),
+            DFAS[0].3.as_ptr()
+        ));
+        let matches = scanner
+            .find_iter("ba", matches_char_class)
+            .collect::<Vec<_>>();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].span().start, 1);
+        assert_eq!(SCANNER_DEF.scangen_version(), Some("0.1.0"));
+    }
+
+    #[test]
+    #[should_panic(expected = "incompatible version of scangen")]
+    fn test_incompatible_table_format() {
+        let _ = ScannerDef::new(DFAS, &[]).generated_with("0.0.1", TABLE_FORMAT_VERSION + 1);
+    }
+}
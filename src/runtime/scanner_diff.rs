@@ -0,0 +1,379 @@
+use crate::{Match, TokenType};
+
+use super::Scanner;
+
+/// A pattern whose token type differs between the two scanners compared by [diff_scanners].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenRenumbering {
+    /// The name of the scanner mode.
+    pub mode: String,
+    /// The pattern of the token type.
+    pub pattern: String,
+    /// The token type in the old scanner.
+    pub old: TokenType,
+    /// The token type in the new scanner.
+    pub new: TokenType,
+}
+
+impl std::fmt::Display for TokenRenumbering {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Mode {}: {:?} changed from token type {} to {}",
+            self.mode, self.pattern, self.old, self.new
+        )
+    }
+}
+
+/// A pattern that exists in only one of the scanners compared by [diff_scanners].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatternChange {
+    /// The name of the scanner mode.
+    pub mode: String,
+    /// The pattern.
+    pub pattern: String,
+    /// The token type of the pattern in the scanner that contains it.
+    pub token_type: TokenType,
+}
+
+impl std::fmt::Display for PatternChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Mode {}: {:?} with token type {}",
+            self.mode, self.pattern, self.token_type
+        )
+    }
+}
+
+/// A pattern whose DFA has a different number of states in the scanners compared by
+/// [diff_scanners].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StateCountChange {
+    /// The pattern of the DFA.
+    pub pattern: String,
+    /// The number of states in the old scanner.
+    pub old: usize,
+    /// The number of states in the new scanner.
+    pub new: usize,
+}
+
+impl std::fmt::Display for StateCountChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:?} has {} instead of {} states",
+            self.pattern, self.new, self.old
+        )
+    }
+}
+
+/// The first match that differs when the scanners compared by [diff_scanners] scan a probe.
+///
+/// `None` means that the scanner found no further match.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BehaviorDifference {
+    /// The index of the probe.
+    pub probe: usize,
+    /// The match of the old scanner.
+    pub old: Option<Match>,
+    /// The match of the new scanner.
+    pub new: Option<Match>,
+}
+
+impl std::fmt::Display for BehaviorDifference {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let describe = |matched: Option<Match>| match matched {
+            Some(matched) => format!("token type {} at {}", matched.token_type(), matched.span()),
+            None => "no match".to_string(),
+        };
+        write!(
+            f,
+            "Probe #{}: {} instead of {}",
+            self.probe,
+            describe(self.new),
+            describe(self.old)
+        )
+    }
+}
+
+/// The differences between two scanners, e.g. a generated scanner before and after it was
+/// regenerated, created by [diff_scanners].
+///
+/// Scanner modes are identified by their names and DFAs by their patterns.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScannerDiff {
+    /// The names of the scanner modes that exist only in the new scanner.
+    pub added_modes: Vec<String>,
+    /// The names of the scanner modes that exist only in the old scanner.
+    pub removed_modes: Vec<String>,
+    /// The patterns that have another token type in the new scanner.
+    pub renumbered: Vec<TokenRenumbering>,
+    /// The patterns of a scanner mode that exist only in the new scanner.
+    pub added_patterns: Vec<PatternChange>,
+    /// The patterns of a scanner mode that exist only in the old scanner.
+    pub removed_patterns: Vec<PatternChange>,
+    /// The patterns whose DFAs have a different number of states.
+    pub state_counts: Vec<StateCountChange>,
+    /// The first differing match of each probe on which the scanners behave differently.
+    pub behavior: Vec<BehaviorDifference>,
+}
+
+impl ScannerDiff {
+    /// Returns true if no difference was found.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl std::fmt::Display for ScannerDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Added modes:")?;
+        for mode in &self.added_modes {
+            writeln!(f, "  {}", mode)?;
+        }
+        writeln!(f, "Removed modes:")?;
+        for mode in &self.removed_modes {
+            writeln!(f, "  {}", mode)?;
+        }
+        writeln!(f, "Renumbered token types:")?;
+        for renumbering in &self.renumbered {
+            writeln!(f, "  {}", renumbering)?;
+        }
+        writeln!(f, "Added patterns:")?;
+        for change in &self.added_patterns {
+            writeln!(f, "  {}", change)?;
+        }
+        writeln!(f, "Removed patterns:")?;
+        for change in &self.removed_patterns {
+            writeln!(f, "  {}", change)?;
+        }
+        writeln!(f, "Changed state counts:")?;
+        for change in &self.state_counts {
+            writeln!(f, "  {}", change)?;
+        }
+        writeln!(f, "Behavior differences:")?;
+        for difference in &self.behavior {
+            writeln!(f, "  {}", difference)?;
+        }
+        Ok(())
+    }
+}
+
+/// Compares two scanners, e.g. the scanner of a generated file before and after it was
+/// regenerated, and reports the changed token numbering, the added and removed scanner modes and
+/// patterns, the DFAs whose number of states changed and the behavior on the given probes.
+///
+/// Each probe is scanned by both scanners with their match functions. The matches are compared
+/// by their spans and the patterns of their token types, so a renumbered token type alone is no
+/// behavior difference. Token types without a pattern, like error token types, are compared by
+/// their numbers.
+pub fn diff_scanners(
+    old: &Scanner,
+    old_matches_char_class: fn(char, usize) -> bool,
+    new: &Scanner,
+    new_matches_char_class: fn(char, usize) -> bool,
+    probes: &[&str],
+) -> ScannerDiff {
+    let mut diff = ScannerDiff::default();
+    for old_mode in &old.scanner_modes {
+        let Some(new_mode) = new
+            .scanner_modes
+            .iter()
+            .find(|mode| mode.name == old_mode.name)
+        else {
+            diff.removed_modes.push(old_mode.name.clone());
+            continue;
+        };
+        for old_dfa in &old_mode.dfas {
+            let pattern = old_dfa.dfa().pattern();
+            match new_mode
+                .dfas
+                .iter()
+                .find(|new_dfa| new_dfa.dfa().pattern() == pattern)
+            {
+                Some(new_dfa) if new_dfa.token_type() != old_dfa.token_type() => {
+                    diff.renumbered.push(TokenRenumbering {
+                        mode: old_mode.name.clone(),
+                        pattern: pattern.to_string(),
+                        old: old_dfa.token_type(),
+                        new: new_dfa.token_type(),
+                    })
+                }
+                Some(_) => {}
+                None => diff.removed_patterns.push(PatternChange {
+                    mode: old_mode.name.clone(),
+                    pattern: pattern.to_string(),
+                    token_type: old_dfa.token_type(),
+                }),
+            }
+        }
+        for new_dfa in &new_mode.dfas {
+            let pattern = new_dfa.dfa().pattern();
+            if !old_mode
+                .dfas
+                .iter()
+                .any(|old_dfa| old_dfa.dfa().pattern() == pattern)
+            {
+                diff.added_patterns.push(PatternChange {
+                    mode: new_mode.name.clone(),
+                    pattern: pattern.to_string(),
+                    token_type: new_dfa.token_type(),
+                });
+            }
+        }
+    }
+    diff.added_modes = new
+        .scanner_modes
+        .iter()
+        .filter(|new_mode| !old.scanner_modes.iter().any(|m| m.name == new_mode.name))
+        .map(|new_mode| new_mode.name.clone())
+        .collect();
+
+    for old_dfa in old.dfas() {
+        if let Some(new_dfa) = new
+            .dfas()
+            .find(|new_dfa| new_dfa.pattern() == old_dfa.pattern())
+        {
+            if new_dfa.state_count() != old_dfa.state_count() {
+                diff.state_counts.push(StateCountChange {
+                    pattern: old_dfa.pattern().to_string(),
+                    old: old_dfa.state_count(),
+                    new: new_dfa.state_count(),
+                });
+            }
+        }
+    }
+
+    for (probe, input) in probes.iter().enumerate() {
+        let mut old_matches = old.find_iter(input, old_matches_char_class);
+        let mut new_matches = new.find_iter(input, new_matches_char_class);
+        loop {
+            let (old_match, new_match) = (old_matches.next(), new_matches.next());
+            let same = match (old_match, new_match) {
+                (Some(old_match), Some(new_match)) => {
+                    old_match.span() == new_match.span()
+                        && token_key(old, old_match.token_type())
+                            == token_key(new, new_match.token_type())
+                }
+                (None, None) => break,
+                _ => false,
+            };
+            if !same {
+                diff.behavior.push(BehaviorDifference {
+                    probe,
+                    old: old_match,
+                    new: new_match,
+                });
+                break;
+            }
+        }
+    }
+    diff
+}
+
+// Returns the pattern of the given token type if one exists in a mode of the scanner and the
+// number of the token type otherwise.
+fn token_key(scanner: &Scanner, token_type: TokenType) -> Result<&str, TokenType> {
+    scanner
+        .scanner_modes
+        .iter()
+        .flat_map(|mode| mode.dfas.iter())
+        .find(|dfa| dfa.token_type() == token_type)
+        .map(|dfa| dfa.dfa().pattern())
+        .ok_or(token_type)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DfaData, ScannerBuilder, ScannerModeData};
+
+    fn matches_char_class(c: char, char_class: usize) -> bool {
+        match char_class {
+            0 => c == 'a',
+            1 => c.is_ascii_digit(),
+            _ => false,
+        }
+    }
+
+    #[test]
+    fn test_diff_scanners() {
+        const OLD_DFAS: &[DfaData] = &[
+            ("a+", &[1], &[(0, 1), (1, 2)], &[(0, 1), (0, 1)]),
+            ("[0-9]", &[1], &[(0, 1), (1, 1)], &[(1, 1)]),
+        ];
+        // The digits are matched as a run and the DFA of `a+` is not minimal
+        const NEW_DFAS: &[DfaData] = &[
+            ("[0-9]+", &[1], &[(0, 1), (1, 2)], &[(1, 1), (1, 1)]),
+            (
+                "a+",
+                &[1, 2],
+                &[(0, 1), (1, 2), (2, 3)],
+                &[(0, 1), (0, 2), (0, 2)],
+            ),
+        ];
+        const NEW_MODES: &[ScannerModeData] = &[
+            (
+                "INITIAL",
+                &[(0, TokenType::new(0)), (1, TokenType::new(1))],
+                &[],
+                None,
+            ),
+            ("DIGITS", &[(0, TokenType::new(0))], &[], None),
+        ];
+        let old = ScannerBuilder::new()
+            .add_dfa_data(OLD_DFAS)
+            .build()
+            .unwrap();
+        let new = ScannerBuilder::new()
+            .add_dfa_data(NEW_DFAS)
+            .add_scanner_mode_data(NEW_MODES)
+            .build()
+            .unwrap();
+
+        let diff = diff_scanners(&old, matches_char_class, &old, matches_char_class, &["aa1"]);
+        assert!(diff.is_empty());
+
+        let diff = diff_scanners(
+            &old,
+            matches_char_class,
+            &new,
+            matches_char_class,
+            &["aa", "a12"],
+        );
+        assert_eq!(diff.added_modes, vec!["DIGITS".to_string()]);
+        assert!(diff.removed_modes.is_empty());
+        assert_eq!(
+            diff.renumbered,
+            vec![TokenRenumbering {
+                mode: "INITIAL".to_string(),
+                pattern: "a+".to_string(),
+                old: TokenType::new(0),
+                new: TokenType::new(1),
+            }]
+        );
+        assert_eq!(diff.removed_patterns[0].pattern, "[0-9]");
+        assert_eq!(diff.added_patterns[0].pattern, "[0-9]+");
+        assert_eq!(
+            diff.state_counts,
+            vec![StateCountChange {
+                pattern: "a+".to_string(),
+                old: 2,
+                new: 3,
+            }]
+        );
+        // The renumbered `a+` behaves the same, the digits don't
+        assert_eq!(
+            diff.behavior,
+            vec![BehaviorDifference {
+                probe: 1,
+                old: Some(Match::new(TokenType::new(1), (1usize..2).into())),
+                new: Some(Match::new(TokenType::new(0), (1usize..3).into())),
+            }]
+        );
+        assert!(diff
+            .to_string()
+            .contains("Probe #1: token type 0 at 1..3 instead of token type 1 at 1..2"));
+    }
+}
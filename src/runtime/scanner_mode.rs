@@ -1,11 +1,14 @@
-use crate::ScannerModeData;
+use std::sync::Arc;
 
-use super::{Dfa, DfaWithTokenType};
+use crate::{Match, ScannerModeData, ScannerModeId, TokenType};
+
+use super::{Dfa, DfaWithTokenType, StartSet};
 
 /// A ScannerMode is a set of active DFAs with their associated token type numbers.
 ///
-/// The DFAs are clones from the Scanner's `dfas` field for the sake of performance.
-/// The token type numbers are of type `usize` bundled with the DFAs.
+/// The DFAs are shared with the Scanner's `dfas` field, i.e. the tables of each pattern exist
+/// exactly once in memory regardless of how many scanner modes use it.
+/// The token type numbers are of type [TokenType] bundled with the DFAs.
 #[derive(Debug, Clone)]
 pub struct ScannerMode {
     /// The name of the mode.
@@ -15,31 +18,161 @@ pub struct ScannerMode {
     /// The transitions between the scanner modes triggered by a token type number.
     /// The entries are tuples of the token type numbers and the new scanner mode index and are
     /// sorted by token type number.
-    pub(crate) transitions: Vec<(usize, usize)>,
+    pub(crate) transitions: Vec<(TokenType, ScannerModeId)>,
+    /// The characters that can start a token in this mode, if known.
+    pub(crate) start_set: Option<StartSet>,
+    /// The characters of the character classes the DFAs loop on, indexed by character class.
+    /// They are used to consume a run of such characters without calling the match function.
+    pub(crate) run_sets: Arc<[Option<StartSet>]>,
+    /// The opening and closing token types of a counting mode along with the mode that is
+    /// entered when the nesting depth drops to zero. See [crate::CountingModeData].
+    pub(crate) counting: Option<(TokenType, TokenType, ScannerModeId)>,
+    /// The order in which the DFAs are advanced during a search given as indices into `dfas`,
+    /// e.g. by descending frequency of their token types. If empty, they are advanced in their
+    /// natural order. The order doesn't affect the priorities of the DFAs.
+    pub(crate) evaluation_order: Vec<usize>,
+    /// The token type of the tokens that cover input no DFA of this mode matches.
+    pub(crate) error_token_type: Option<TokenType>,
+    /// The match function of this mode that only knows the character classes of its DFAs. If
+    /// not set, the match function given to the search is used.
+    pub(crate) matches_char_class: Option<fn(char, usize) -> bool>,
+    /// The generation of the current search. It is incremented at the start of each search and
+    /// invalidates the matching states of all DFAs at once.
+    pub(crate) generation: usize,
+    /// Scratch space for the indices of the DFAs that are still active during a search.
+    pub(crate) active_dfas: Vec<usize>,
+    /// The indices of the DFAs that found a match in the current search in ascending order.
+    pub(crate) matched_dfas: Vec<usize>,
 }
 
 impl ScannerMode {
     /// Creates a new scanner mode from the Scanner's DFAs and the ScannerModeData.
-    pub fn new(dfas: &[Dfa], scanner_mode_data: &ScannerModeData) -> Self {
+    ///
+    /// # Panics
+    /// Panics if the scanner mode data references a DFA that is not contained in `dfas`.
+    #[allow(clippy::panic)]
+    pub fn new(dfas: &[Arc<Dfa>], scanner_mode_data: &ScannerModeData) -> Self {
         let name = scanner_mode_data.0.to_string();
         let dfas = scanner_mode_data
             .1
             .iter()
             .map(|(dfa_index, token_type)| {
-                DfaWithTokenType::new(dfas[*dfa_index].clone(), *token_type)
+                let dfa = dfas.get(*dfa_index).unwrap_or_else(|| {
+                    panic!(
+                        "Scanner mode {} references the unknown DFA {}, only {} DFAs are given",
+                        name,
+                        dfa_index,
+                        dfas.len()
+                    )
+                });
+                DfaWithTokenType::new(Arc::clone(dfa), *token_type)
             })
             .collect();
-        let mut transitions = scanner_mode_data.2.to_vec();
+        Self::from_parts(
+            name,
+            dfas,
+            scanner_mode_data.2.to_vec(),
+            scanner_mode_data.3,
+        )
+    }
+
+    /// Creates a new scanner mode from its name, its DFAs, its transitions and its error token
+    /// type. The transitions are sorted by token type.
+    pub(crate) fn from_parts(
+        name: String,
+        dfas: Vec<DfaWithTokenType>,
+        mut transitions: Vec<(TokenType, ScannerModeId)>,
+        error_token_type: Option<TokenType>,
+    ) -> Self {
         transitions.sort_by_key(|(term, _)| *term);
         Self {
             name,
             dfas,
             transitions,
+            start_set: None,
+            run_sets: Arc::default(),
+            counting: None,
+            evaluation_order: Vec::new(),
+            error_token_type,
+            matches_char_class: None,
+            generation: 0,
+            active_dfas: Vec::new(),
+            matched_dfas: Vec::new(),
+        }
+    }
+
+    /// Returns the current matches of the DFAs that found a match in the current search in the
+    /// order of the DFAs in this mode.
+    pub(crate) fn current_matches(&self) -> impl Iterator<Item = Match> + '_ {
+        self.matched_dfas
+            .iter()
+            .filter_map(|dfa_index| self.dfas.get(*dfa_index)?.current_match(self.generation))
+    }
+
+    /// Returns true if a token of this mode can start with the given character.
+    /// Without a start set every character is considered a possible start.
+    #[inline]
+    pub(crate) fn can_start_with(&self, c: char) -> bool {
+        self.start_set
+            .as_ref()
+            .is_none_or(|start_set| start_set.contains(c))
+    }
+
+    /// Removes the DFAs for which the predicate returns true, e.g. the terminals of a disabled
+    /// feature, and keeps the evaluation order of the remaining DFAs.
+    pub(crate) fn remove_dfas(&mut self, mut remove: impl FnMut(&Arc<Dfa>) -> bool) {
+        let mut kept = 0;
+        let new_indices = self
+            .dfas
+            .iter()
+            .map(|dfa| {
+                (!remove(dfa.dfa())).then(|| {
+                    kept += 1;
+                    kept - 1
+                })
+            })
+            .collect::<Vec<_>>();
+        self.evaluation_order = self
+            .evaluation_order
+            .iter()
+            .filter_map(|dfa_index| new_indices.get(*dfa_index).copied().flatten())
+            .collect();
+        let mut new_indices = new_indices.iter();
+        self.dfas
+            .retain(|_| new_indices.next().is_some_and(Option::is_some));
+    }
+
+    /// Computes the start set and the run sets of the mode from the characters of the classes of
+    /// its DFAs unless they are already known, and reserves the scratch space of the search.
+    /// Without the characters of the classes, e.g. for DFAs of generated code that use a match
+    /// function, the sets are left as they are.
+    pub(crate) fn prepare(&mut self) {
+        self.active_dfas.reserve(self.dfas.len());
+        self.matched_dfas.reserve(self.dfas.len());
+        if self.start_set.is_none() {
+            self.start_set = self
+                .dfas
+                .iter()
+                .map(|dfa| dfa.dfa().start_chars())
+                .collect::<Option<Vec<_>>>()
+                .map(|start_chars| StartSet::from_ranges(start_chars.concat()));
+        }
+        if self.run_sets.is_empty() {
+            // The run sets are indexed by character class, so the DFAs must share their classes.
+            let mut char_classes = self.dfas.iter().map(|dfa| dfa.dfa().char_classes.as_ref());
+            if let Some(Some(first)) = char_classes.next() {
+                if char_classes.all(|other| other.is_some_and(|other| Arc::ptr_eq(first, other))) {
+                    self.run_sets = first
+                        .iter()
+                        .map(|ranges| Some(StartSet::from_ranges(ranges.clone())))
+                        .collect();
+                }
+            }
         }
     }
 
     /// Check if the scanner configuration has a transition on the given terminal index
-    pub fn has_transition(&self, token_type: usize) -> Option<usize> {
+    pub fn has_transition(&self, token_type: TokenType) -> Option<ScannerModeId> {
         for (term, scanner) in &self.transitions {
             match token_type.cmp(term) {
                 std::cmp::Ordering::Less => return None,
@@ -57,8 +190,104 @@ impl ScannerMode {
     }
 }
 
-#[cfg(test)]
+/// Information about a scanner mode of a [crate::Scanner] as returned by
+/// [crate::Scanner::modes].
+///
+/// It allows generic tools like debuggers or documentation generators to introspect any scanner,
+/// regardless of whether it was generated, built with a [crate::ScannerBuilder] or loaded from
+/// JSON.
+#[derive(Debug, Clone, Copy)]
+pub struct ModeInfo<'a> {
+    id: ScannerModeId,
+    mode: &'a ScannerMode,
+}
+
+impl<'a> ModeInfo<'a> {
+    /// Creates the information about the given scanner mode.
+    pub(crate) fn new(id: ScannerModeId, mode: &'a ScannerMode) -> Self {
+        Self { id, mode }
+    }
+
+    /// Returns the id of the scanner mode.
+    #[inline]
+    pub fn id(&self) -> ScannerModeId {
+        self.id
+    }
+
+    /// Returns the name of the scanner mode.
+    #[inline]
+    pub fn name(&self) -> &'a str {
+        &self.mode.name
+    }
 
+    /// Returns the token types of the scanner mode together with their DFAs in the order of their
+    /// priority, i.e. the first one wins a tie.
+    pub fn tokens(&self) -> impl Iterator<Item = (TokenType, &'a Dfa)> + 'a {
+        self.mode
+            .dfas
+            .iter()
+            .map(|dfa| (dfa.token_type(), dfa.dfa().as_ref()))
+    }
+
+    /// Returns the token types of the scanner mode in the order of their priority.
+    pub fn token_types(&self) -> impl Iterator<Item = TokenType> + 'a {
+        self.tokens().map(|(token_type, _)| token_type)
+    }
+
+    /// Returns the token types the scanner mode can return in ascending order, i.e. the token
+    /// types of its DFAs and its error token type. The position of a token type in this list is
+    /// its mode-local index, which numbers the tokens of the mode densely, e.g. for compact parser
+    /// tables. See [ModeInfo::local_index].
+    pub fn local_token_types(&self) -> Vec<TokenType> {
+        let mut token_types = self
+            .token_types()
+            .chain(self.mode.error_token_type)
+            .collect::<Vec<_>>();
+        token_types.sort();
+        token_types.dedup();
+        token_types
+    }
+
+    /// Returns the mode-local index of the given token type, if the scanner mode can return it.
+    /// See [ModeInfo::local_token_types].
+    pub fn local_index(&self, token_type: TokenType) -> Option<usize> {
+        self.local_token_types().binary_search(&token_type).ok()
+    }
+
+    /// Returns the patterns of the DFAs of the scanner mode in the order of their priority.
+    pub fn patterns(&self) -> impl Iterator<Item = &'a str> + 'a {
+        self.tokens().map(|(_, dfa)| dfa.pattern())
+    }
+
+    /// Returns the transitions of the scanner mode as pairs of the token type that triggers the
+    /// transition and the scanner mode that is entered, sorted by token type.
+    #[inline]
+    pub fn transitions(&self) -> &'a [(TokenType, ScannerModeId)] {
+        &self.mode.transitions
+    }
+
+    /// Returns the token type of the tokens that cover input no DFA of the scanner mode matches,
+    /// if the scanner mode declares one.
+    #[inline]
+    pub fn error_token_type(&self) -> Option<TokenType> {
+        self.mode.error_token_type
+    }
+
+    /// Returns the opening and closing token types and the exit mode if this is a counting mode,
+    /// see [crate::CountingModeData].
+    #[inline]
+    pub fn counting(&self) -> Option<(TokenType, TokenType, ScannerModeId)> {
+        self.mode.counting
+    }
+}
+
+impl std::fmt::Display for ScannerMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+#[cfg(test)]
 mod tests {
     use crate::common::MatchingState;
 
@@ -66,28 +295,75 @@ mod tests {
 
     const SCANNER_MODE: ScannerModeData = (
         "test",
-        &[(0usize, 0usize)],
-        &[(0usize, 0usize), (1usize, 1usize), (3usize, 2usize)],
+        &[(0, TokenType::new(0))],
+        &[
+            (TokenType::new(0), ScannerModeId::new(0)),
+            (TokenType::new(1), ScannerModeId::new(1)),
+            (TokenType::new(3), ScannerModeId::new(2)),
+        ],
+        None,
     );
 
     #[test]
     fn test_scanner_mode() {
         let dfa = Dfa {
-            pattern: "test".to_string(),
-            accepting_states: vec![0],
-            state_ranges: vec![(0, 0), (1, 1), (2, 2), (3, 3)],
-            transitions: vec![],
+            pattern: "test".into(),
+            accepting_states: vec![0].into(),
+            state_ranges: vec![(0, 0), (1, 1), (2, 2), (3, 3)].into(),
+            transitions: vec![].into(),
+            counted_states: Vec::new(),
+            char_classes: None,
+            ascii_transitions: None,
             matching_state: MatchingState::default(),
         };
-        let dfas = vec![dfa];
+        let dfas = vec![Arc::new(dfa)];
         let scanner_mode = ScannerMode::new(&dfas, &SCANNER_MODE);
         assert_eq!(scanner_mode.name, "test");
+        assert_eq!(scanner_mode.to_string(), "test");
         assert_eq!(scanner_mode.dfas.len(), 1);
         assert_eq!(scanner_mode.transitions.len(), 3);
-        assert_eq!(scanner_mode.has_transition(0), Some(0));
-        assert_eq!(scanner_mode.has_transition(1), Some(1));
-        assert_eq!(scanner_mode.has_transition(2), None);
-        assert_eq!(scanner_mode.has_transition(3), Some(2));
-        assert_eq!(scanner_mode.has_transition(8), None);
+        assert_eq!(
+            scanner_mode.has_transition(TokenType::new(0)),
+            Some(ScannerModeId::new(0))
+        );
+        assert_eq!(
+            scanner_mode.has_transition(TokenType::new(1)),
+            Some(ScannerModeId::new(1))
+        );
+        assert_eq!(scanner_mode.has_transition(TokenType::new(2)), None);
+        assert_eq!(
+            scanner_mode.has_transition(TokenType::new(3)),
+            Some(ScannerModeId::new(2))
+        );
+        assert_eq!(scanner_mode.has_transition(TokenType::new(8)), None);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Scanner mode test references the unknown DFA 0, only 0 DFAs are given"
+    )]
+    fn test_scanner_mode_with_unknown_dfa() {
+        let _ = ScannerMode::new(&[], &SCANNER_MODE);
+    }
+
+    #[test]
+    fn test_scanner_modes_share_dfas() {
+        let dfa = Dfa {
+            pattern: "test".into(),
+            accepting_states: vec![0].into(),
+            state_ranges: vec![(0, 0)].into(),
+            transitions: vec![].into(),
+            counted_states: Vec::new(),
+            char_classes: None,
+            ascii_transitions: None,
+            matching_state: MatchingState::default(),
+        };
+        let dfas = vec![Arc::new(dfa)];
+        let mode_a = ScannerMode::new(&dfas, &SCANNER_MODE);
+        let mode_b = ScannerMode::new(&dfas, &SCANNER_MODE);
+        assert_eq!(mode_a.dfas.len(), 1);
+        assert_eq!(mode_b.dfas.len(), 1);
+        // The scanner and both modes hold a reference
+        assert_eq!(Arc::strong_count(&dfas[0]), 3);
     }
 }
@@ -0,0 +1,249 @@
+//! This module provides adapters that scan text stored in several segments.
+//!
+//! Editors often keep their text in a rope, i.e. in a tree of string chunks. Instead of
+//! materializing the whole text as a `String`, the adapters of this module iterate over the
+//! characters of the segments in order. The spans of the matches are byte offsets in the
+//! concatenated text, so a match may span several segments.
+
+use crate::common::Match;
+
+use super::{CharSource, Scanner, SeekCharSource};
+
+/// A character source over the characters of several text segments and their byte offsets in
+/// the concatenated text.
+///
+/// The segments are given by an iterator, e.g. `chunks.iter().copied()` for a `&[&str]` or the
+/// chunks iterator of a rope. The iterator must be cheap to clone, because the scanner clones the
+/// character source to look ahead.
+#[derive(Debug, Clone)]
+pub struct SegmentedCharIndices<'h, I>
+where
+    I: Iterator<Item = &'h str> + Clone,
+{
+    segments: I,
+    // The current segment and its offset in the concatenated text
+    segment: &'h str,
+    segment_start: usize,
+    // The byte offset of the next character in the current segment
+    offset: usize,
+}
+
+impl<'h, I> SegmentedCharIndices<'h, I>
+where
+    I: Iterator<Item = &'h str> + Clone,
+{
+    /// Creates a new `SegmentedCharIndices` iterator over the given segments.
+    pub fn new<S>(segments: S) -> Self
+    where
+        S: IntoIterator<IntoIter = I>,
+    {
+        SegmentedCharIndices {
+            segments: segments.into_iter(),
+            segment: "",
+            segment_start: 0,
+            offset: 0,
+        }
+    }
+
+    /// Returns the byte offset of the next character in the concatenated text.
+    #[inline]
+    pub fn byte_offset(&self) -> usize {
+        self.segment_start + self.offset
+    }
+
+    /// Moves the iterator forward to the given byte offset in the concatenated text, which must be
+    /// a character boundary. Segments before the offset are skipped as a whole.
+    pub(crate) fn set_position(&mut self, position: usize) {
+        while self.segment_start + self.segment.len() <= position {
+            let Some(segment) = self.segments.next() else {
+                self.offset = self.segment.len();
+                return;
+            };
+            self.segment_start += self.segment.len();
+            self.segment = segment;
+            self.offset = 0;
+        }
+        self.offset = self.offset.max(position - self.segment_start);
+    }
+}
+
+impl<'h, I> Iterator for SegmentedCharIndices<'h, I>
+where
+    I: Iterator<Item = &'h str> + Clone,
+{
+    type Item = (usize, char);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(c) = self
+                .segment
+                .get(self.offset..)
+                .and_then(|s| s.chars().next())
+            {
+                let position = self.segment_start + self.offset;
+                self.offset += c.len_utf8();
+                return Some((position, c));
+            }
+            // Empty segments are skipped.
+            let segment = self.segments.next()?;
+            self.segment_start += self.segment.len();
+            self.segment = segment;
+            self.offset = 0;
+        }
+    }
+}
+
+impl<'h, I> CharSource for SegmentedCharIndices<'h, I>
+where
+    I: Iterator<Item = &'h str> + Clone,
+{
+    #[inline]
+    fn encoded_len(&self, c: char) -> usize {
+        c.len_utf8()
+    }
+}
+
+impl<'h, I> SeekCharSource for SegmentedCharIndices<'h, I>
+where
+    I: Iterator<Item = &'h str> + Clone,
+{
+    #[inline]
+    fn offset(&self) -> usize {
+        self.byte_offset()
+    }
+
+    #[inline]
+    fn seek(&mut self, offset: usize) {
+        SegmentedCharIndices::set_position(self, offset);
+    }
+}
+
+/// An iterator over all non-overlapping matches in a text stored in several segments.
+///
+/// The iterator yields a [`Match`] value until no more matches could be found. The spans of the
+/// matches are byte offsets in the concatenated text.
+///
+/// This iterator can be created with the [`Scanner::find_iter_segmented`] method.
+#[derive(Debug)]
+pub struct FindSegmentedMatches<'h, I>
+where
+    I: Iterator<Item = &'h str> + Clone,
+{
+    scanner: Scanner,
+    char_indices: SegmentedCharIndices<'h, I>,
+    matches_char_class: fn(char, usize) -> bool,
+}
+
+impl<'h, I> FindSegmentedMatches<'h, I>
+where
+    I: Iterator<Item = &'h str> + Clone,
+{
+    /// Creates a new `FindSegmentedMatches` iterator.
+    pub fn new<S>(
+        scanner: Scanner,
+        segments: S,
+        matches_char_class: fn(char, usize) -> bool,
+    ) -> Self
+    where
+        S: IntoIterator<IntoIter = I>,
+    {
+        FindSegmentedMatches {
+            scanner,
+            char_indices: SegmentedCharIndices::new(segments),
+            matches_char_class,
+        }
+    }
+
+    /// Returns the scanner used by the iterator.
+    #[inline]
+    pub fn scanner(&self) -> &Scanner {
+        &self.scanner
+    }
+
+    /// Returns the next match in the text.
+    ///
+    /// If no match is found, `None` is returned.
+    /// Like [`super::FindMatches::next_match`], matches of the skipped token types are passed
+    /// over, unmatched input is reported as a match of the [`Scanner::error_token_type`] if there
+    /// is one, and `None` is returned if the step budget of the scanner is exhausted or if more
+    /// input is needed.
+    pub fn next_match(&mut self) -> Option<Match> {
+        self.scanner
+            .find_next(&mut self.char_indices, self.matches_char_class)
+            .matched()
+    }
+}
+
+impl<'h, I> Iterator for FindSegmentedMatches<'h, I>
+where
+    I: Iterator<Item = &'h str> + Clone,
+{
+    type Item = Match;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_match()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::generated::scanner_with_modes::{create_find_iter, create_scanner};
+    use crate::TokenType;
+
+    #[test]
+    fn test_segmented_char_indices() {
+        let segments = ["ab", "", "π", "😀c"];
+        let chars = SegmentedCharIndices::new(segments.iter().copied()).collect::<Vec<_>>();
+        assert_eq!(
+            chars,
+            vec![(0, 'a'), (1, 'b'), (2, 'π'), (4, '😀'), (8, 'c')]
+        );
+
+        let mut char_indices = SegmentedCharIndices::new(segments.iter().copied());
+        char_indices.set_position(4);
+        assert_eq!(char_indices.byte_offset(), 4);
+        assert_eq!(char_indices.next(), Some((4, '😀')));
+        char_indices.set_position(9);
+        assert_eq!(char_indices.next(), None);
+    }
+
+    #[test]
+    fn test_find_iter_segmented() {
+        let text = "x ä yz\nabc xyz";
+        let expected = create_find_iter(&create_scanner(), text).collect::<Vec<_>>();
+        assert!(!expected.is_empty());
+
+        // Split the text at every character boundary, so the tokens span several segments.
+        let segments = text
+            .char_indices()
+            .map(|(i, c)| &text[i..i + c.len_utf8()])
+            .collect::<Vec<_>>();
+        let chunks = [&text[..2], &text[2..9], "", &text[9..]];
+        for segments in [&segments[..], &chunks[..], &[text][..]] {
+            let scanner = create_scanner();
+            let matches_char_class = create_find_iter(&scanner, "").matches_char_class();
+            let matches = scanner
+                .find_iter_segmented(segments.iter().copied(), matches_char_class)
+                .collect::<Vec<_>>();
+            assert_eq!(matches, expected, "{:?}", segments);
+        }
+    }
+
+    #[test]
+    fn test_find_iter_segmented_skipped_token_types() {
+        let mut scanner = create_scanner();
+        scanner.set_skipped_token_types([TokenType::new(1)]);
+        let matches_char_class = create_find_iter(&scanner, "").matches_char_class();
+        let matches = scanner
+            .find_iter_segmented(["ab", " cd"], matches_char_class)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            matches,
+            vec![
+                Match::new(4.into(), (0usize..2).into()),
+                Match::new(4.into(), (3usize..5).into()),
+            ]
+        );
+    }
+}
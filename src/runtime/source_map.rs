@@ -0,0 +1,177 @@
+use crate::Match;
+
+use super::{LineIndex, Scanner};
+
+/// The id of a source file in a [SourceMap].
+///
+/// It is a newtype around `usize` to prevent accidental mixing of source ids with other indices.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SourceId(usize);
+
+impl SourceId {
+    /// Create a new source id.
+    #[inline]
+    pub const fn new(index: usize) -> Self {
+        SourceId(index)
+    }
+
+    /// Get the source id as usize.
+    #[inline]
+    pub const fn as_usize(&self) -> usize {
+        self.0
+    }
+}
+
+impl std::fmt::Display for SourceId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// A match along with the id of the source file it was found in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SourceMatch {
+    /// The source file the match was found in.
+    pub source: SourceId,
+    /// The match within the source file.
+    pub matched: Match,
+}
+
+/// A source file with its name and its text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceFile {
+    /// The name of the source file, e.g. its path.
+    pub name: String,
+    /// The text of the source file.
+    pub text: String,
+}
+
+/// A collection of source files, e.g. of a whole project, that are identified by [SourceId]s.
+///
+/// The matches found in the files carry the id of their file, see [SourceMap::find_iter], so
+/// multi-file pipelines don't need to wrap the matches themselves.
+///
+/// ```rust
+/// use scangen::SourceMap;
+/// let mut source_map = SourceMap::new();
+/// let id = source_map.add("main.txt", "let a;");
+/// assert_eq!(source_map.name(id), Some("main.txt"));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SourceMap {
+    files: Vec<SourceFile>,
+}
+
+impl SourceMap {
+    /// Creates an empty source map.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a source file and returns its id.
+    pub fn add(&mut self, name: impl Into<String>, text: impl Into<String>) -> SourceId {
+        self.files.push(SourceFile {
+            name: name.into(),
+            text: text.into(),
+        });
+        SourceId::new(self.files.len() - 1)
+    }
+
+    /// Returns the number of source files.
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    /// Returns true if the source map contains no source files.
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Returns the source file with the given id.
+    pub fn get(&self, id: SourceId) -> Option<&SourceFile> {
+        self.files.get(id.as_usize())
+    }
+
+    /// Returns the name of the source file with the given id.
+    pub fn name(&self, id: SourceId) -> Option<&str> {
+        self.get(id).map(|file| file.name.as_str())
+    }
+
+    /// Returns the text of the source file with the given id.
+    pub fn text(&self, id: SourceId) -> Option<&str> {
+        self.get(id).map(|file| file.text.as_str())
+    }
+
+    /// Returns the text covered by the given match.
+    pub fn match_text(&self, source_match: &SourceMatch) -> Option<&str> {
+        self.text(source_match.source)?
+            .get(source_match.matched.range())
+    }
+
+    /// Creates the line index of the source file with the given id.
+    pub fn line_index(&self, id: SourceId) -> Option<LineIndex<'_>> {
+        self.text(id).map(LineIndex::new)
+    }
+
+    /// Returns an iterator over the ids and the source files in the order they were added.
+    pub fn iter(&self) -> impl Iterator<Item = (SourceId, &SourceFile)> {
+        self.files
+            .iter()
+            .enumerate()
+            .map(|(index, file)| (SourceId::new(index), file))
+    }
+
+    /// Returns an iterator over the matches of all source files in the order they were added.
+    /// Each source file is scanned from the initial scanner mode.
+    pub fn find_iter<'a>(
+        &'a self,
+        scanner: &'a Scanner,
+        matches_char_class: fn(char, usize) -> bool,
+    ) -> impl Iterator<Item = SourceMatch> + 'a {
+        self.iter().flat_map(move |(source, file)| {
+            scanner
+                .find_iter(&file.text, matches_char_class)
+                .map(move |matched| SourceMatch { source, matched })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::generated::scanner_with_modes::create_scanner;
+
+    #[test]
+    fn test_source_map() {
+        let mut source_map = SourceMap::new();
+        let first = source_map.add("a.txt", "x \"s");
+        let second = source_map.add("b.txt", "y");
+        assert_eq!(source_map.len(), 2);
+        assert_eq!(source_map.name(second), Some("b.txt"));
+        assert_eq!(source_map.get(SourceId::new(2)), None);
+
+        let scanner = create_scanner();
+        // The scanner modes use their own match functions
+        let matches = source_map
+            .find_iter(&scanner, |_, _| unreachable!())
+            .collect::<Vec<_>>();
+        // The second file is scanned in the initial mode although the first one ends in a string
+        assert_eq!(
+            matches
+                .iter()
+                .map(|m| (m.source, source_map.match_text(m).unwrap()))
+                .collect::<Vec<_>>(),
+            vec![
+                (first, "x"),
+                (first, " "),
+                (first, "\""),
+                (first, "s"),
+                (second, "y"),
+            ]
+        );
+        assert_eq!(
+            matches[4].matched.token_type(),
+            matches[0].matched.token_type()
+        );
+    }
+}
@@ -0,0 +1,75 @@
+use std::fmt::{self, Write};
+
+use crate::TokenType;
+
+use super::{MatchKind, Scanner};
+
+/// Writes the specification of the scanner, i.e. its settings and its scanner modes with their
+/// tokens and transitions. The token types are named by the given names, which are indexed by
+/// token type.
+pub(crate) fn write_spec(
+    scanner: &Scanner,
+    token_names: &[&str],
+    out: &mut impl Write,
+) -> fmt::Result {
+    let token = |token_type: TokenType| match token_names.get(token_type.as_usize()) {
+        Some(name) => format!("{} {}", token_type, name),
+        None => token_type.to_string(),
+    };
+
+    if let Some(token_type) = scanner.unmatched_token_type {
+        writeln!(out, "%unmatched {}", token(token_type))?;
+    }
+    if let Some((max_len, token_type)) = scanner.max_token_length {
+        writeln!(
+            out,
+            "%max_token_length {} %error {}",
+            max_len,
+            token(token_type)
+        )?;
+    }
+    if let MatchKind::TokenAnchored { error_on_gap } = scanner.match_kind {
+        writeln!(out, "%token_anchored error_on_gap={}", error_on_gap)?;
+    }
+    // The scanner modes are separated from each other and from the settings by an empty line.
+    let mut separate = has_settings(scanner);
+    for mode in scanner.modes() {
+        if separate {
+            writeln!(out)?;
+        }
+        separate = true;
+        writeln!(out, "%scanner {} {{", mode.name())?;
+        for (token_type, dfa) in mode.tokens() {
+            writeln!(out, "    %token {} {:?}", token(token_type), dfa.pattern())?;
+        }
+        for (token_type, target) in mode.transitions() {
+            writeln!(
+                out,
+                "    %on {} %enter {}",
+                token(*token_type),
+                scanner.display_mode(*target)
+            )?;
+        }
+        if let Some(token_type) = mode.error_token_type() {
+            writeln!(out, "    %error {}", token(token_type))?;
+        }
+        if let Some((open, close, exit)) = mode.counting() {
+            writeln!(
+                out,
+                "    %counting {}, {} %exit {}",
+                token(open),
+                token(close),
+                scanner.display_mode(exit)
+            )?;
+        }
+        writeln!(out, "}}")?;
+    }
+    Ok(())
+}
+
+// Returns true if settings of the scanner precede its scanner modes.
+fn has_settings(scanner: &Scanner) -> bool {
+    scanner.unmatched_token_type.is_some()
+        || scanner.max_token_length.is_some()
+        || scanner.match_kind.is_token_anchored()
+}
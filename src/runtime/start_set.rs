@@ -0,0 +1,111 @@
+use std::borrow::Cow;
+
+use crate::StartSetData;
+
+/// The set of characters that can start a token in a scanner mode.
+///
+/// The scanner uses it to reject positions that cannot start a token without advancing any DFA.
+/// The same representation holds the characters of a run class, see [crate::RunClassData].
+#[derive(Debug, Clone)]
+pub(crate) struct StartSet {
+    /// Bit `n` is set if the ASCII character with code point `n` is in the set.
+    ascii: u128,
+    /// The sorted and disjoint ranges of the non-ASCII characters in the set.
+    ranges: Cow<'static, [(char, char)]>,
+}
+
+impl StartSet {
+    /// Returns true if the given character is in the set.
+    #[inline]
+    pub(crate) fn contains(&self, c: char) -> bool {
+        if c.is_ascii() {
+            self.ascii & (1 << c as u32) != 0
+        } else {
+            ranges_contain(&self.ranges, c)
+        }
+    }
+
+    /// Creates the set of the characters in the given ranges, which may overlap.
+    pub(crate) fn from_ranges(mut ranges: Vec<(char, char)>) -> Self {
+        ranges.sort_unstable();
+        let mut ascii = 0u128;
+        let mut non_ascii: Vec<(char, char)> = Vec::new();
+        for (start, end) in ranges {
+            for c in start..=end.min('\u{7f}') {
+                ascii |= 1 << c as u32;
+            }
+            let start = start.max('\u{80}');
+            if start > end {
+                continue;
+            }
+            match non_ascii.last_mut() {
+                Some((_, last_end)) if *last_end as u32 + 1 >= start as u32 => {
+                    *last_end = (*last_end).max(end);
+                }
+                _ => non_ascii.push((start, end)),
+            }
+        }
+        StartSet {
+            ascii,
+            ranges: Cow::Owned(non_ascii),
+        }
+    }
+}
+
+/// Returns true if one of the given sorted and disjoint ranges contains the given character.
+#[inline]
+pub(crate) fn ranges_contain(ranges: &[(char, char)], c: char) -> bool {
+    ranges
+        .binary_search_by(|(start, end)| {
+            if *end < c {
+                std::cmp::Ordering::Less
+            } else if *start > c {
+                std::cmp::Ordering::Greater
+            } else {
+                std::cmp::Ordering::Equal
+            }
+        })
+        .is_ok()
+}
+
+impl From<&StartSetData> for StartSet {
+    fn from(data: &StartSetData) -> Self {
+        StartSet {
+            ascii: data.0,
+            ranges: Cow::Borrowed(data.1),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_start_set_contains() {
+        const DATA: StartSetData = (1 << b'a' | 1 << b'\n', &[('\u{80}', '\u{ff}'), ('π', 'π')]);
+        let start_set = StartSet::from(&DATA);
+        assert!(start_set.contains('a'));
+        assert!(start_set.contains('\n'));
+        assert!(!start_set.contains('b'));
+        assert!(!start_set.contains('\u{7f}'));
+        assert!(start_set.contains('ä'));
+        assert!(start_set.contains('π'));
+        assert!(!start_set.contains('Ω'));
+    }
+
+    #[test]
+    fn test_start_set_from_ranges() {
+        let start_set =
+            StartSet::from_ranges(vec![('π', 'π'), ('a', 'c'), ('\u{7e}', 'ä'), ('b', 'b')]);
+        assert!(start_set.contains('a'));
+        assert!(start_set.contains('c'));
+        assert!(!start_set.contains('d'));
+        assert!(start_set.contains('\u{7f}'));
+        assert!(start_set.contains('\u{80}'));
+        assert!(start_set.contains('ä'));
+        assert!(!start_set.contains('å'));
+        assert!(start_set.contains('π'));
+        assert_eq!(*start_set.ranges, [('\u{80}', 'ä'), ('π', 'π')]);
+    }
+}
@@ -0,0 +1,196 @@
+use crate::{Span, TokenType};
+
+use super::Token;
+
+/// The token types that make up a string literal, typically scanned in a scanner mode of its own
+/// that is entered and left on the string delimiter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StringTokenTypes {
+    /// The token type of the delimiter that starts and ends a string, e.g. `"`.
+    pub delimiter: TokenType,
+    /// The token type of an escape sequence, e.g. `\n`. Escape sequences are decoded with
+    /// [decode_escape].
+    pub escape: TokenType,
+    /// The token type of a line continuation, i.e. a backslash at the end of a line. Line
+    /// continuations are dropped from the decoded value.
+    pub line_continuation: Option<TokenType>,
+}
+
+/// A string literal decoded from its tokens.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodedString {
+    /// The span of the string literal including its delimiters.
+    pub span: Span,
+    /// The decoded value of the string literal.
+    pub value: String,
+    /// The spans of the escape sequences that couldn't be decoded. Their text is kept verbatim
+    /// in the value.
+    pub invalid_escapes: Vec<Span>,
+    /// False if the input ended before the closing delimiter.
+    pub terminated: bool,
+}
+
+/// An item of the token stream with folded string literals.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StringItem<'h> {
+    /// A token outside of string literals.
+    Token(Token<'h>),
+    /// A string literal folded from its tokens.
+    String(DecodedString),
+}
+
+/// Decodes a single escape sequence including its leading backslash.
+///
+/// Supported are `\n`, `\r`, `\t`, `\b`, `\f`, `\0`, `\\`, `\"`, `\'`, `\/` and Unicode escapes of
+/// the form `\u{1F600}`. None is returned for other escape sequences.
+pub fn decode_escape(text: &str) -> Option<char> {
+    let escaped = text.strip_prefix('\\')?;
+    let mut chars = escaped.chars();
+    let c = match chars.next()? {
+        'n' => '\n',
+        'r' => '\r',
+        't' => '\t',
+        'b' => '\u{8}',
+        'f' => '\u{c}',
+        '0' => '\0',
+        c @ ('\\' | '"' | '\'' | '/') => c,
+        'u' => {
+            let hex = chars.as_str().strip_prefix('{')?.strip_suffix('}')?;
+            return u32::from_str_radix(hex, 16).ok().and_then(char::from_u32);
+        }
+        _ => return None,
+    };
+    chars.next().is_none().then_some(c)
+}
+
+/// An iterator adapter that folds the tokens of string literals into [DecodedString]s.
+///
+/// A string literal starts and ends with a delimiter token. The escape sequences within are
+/// decoded, line continuations are dropped, and the text of all other tokens within, e.g. the
+/// string content, is taken verbatim. Tokens outside of string literals are passed through.
+///
+/// This iterator can be created with [crate::TokenIter::fold_strings].
+#[derive(Debug)]
+pub struct FoldStrings<I> {
+    tokens: I,
+    token_types: StringTokenTypes,
+}
+
+impl<'h, I> FoldStrings<I>
+where
+    I: Iterator<Item = Token<'h>>,
+{
+    /// Creates a new `FoldStrings` iterator adapter over the given tokens.
+    pub fn new(tokens: I, token_types: StringTokenTypes) -> Self {
+        Self {
+            tokens,
+            token_types,
+        }
+    }
+
+    /// Folds the tokens of the string literal that starts with the given delimiter.
+    fn fold_string(&mut self, delimiter: Token<'h>) -> DecodedString {
+        let mut decoded = DecodedString {
+            span: delimiter.span,
+            value: String::new(),
+            invalid_escapes: Vec::new(),
+            terminated: false,
+        };
+        for token in self.tokens.by_ref() {
+            decoded.span.end = token.span.end;
+            if token.token_type == self.token_types.delimiter {
+                decoded.terminated = true;
+                break;
+            } else if token.token_type == self.token_types.escape {
+                match decode_escape(token.text) {
+                    Some(c) => decoded.value.push(c),
+                    None => {
+                        decoded.invalid_escapes.push(token.span);
+                        decoded.value.push_str(token.text);
+                    }
+                }
+            } else if Some(token.token_type) != self.token_types.line_continuation {
+                decoded.value.push_str(token.text);
+            }
+        }
+        decoded
+    }
+}
+
+impl<'h, I> Iterator for FoldStrings<I>
+where
+    I: Iterator<Item = Token<'h>>,
+{
+    type Item = StringItem<'h>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let token = self.tokens.next()?;
+        if token.token_type == self.token_types.delimiter {
+            Some(StringItem::String(self.fold_string(token)))
+        } else {
+            Some(StringItem::Token(token))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::generated::scanner_with_modes;
+
+    const STRING_TOKEN_TYPES: StringTokenTypes = StringTokenTypes {
+        delimiter: TokenType::new(8),
+        escape: TokenType::new(5),
+        line_continuation: Some(TokenType::new(6)),
+    };
+
+    fn fold(input: &str) -> Vec<StringItem<'_>> {
+        let scanner = scanner_with_modes::create_scanner();
+        scanner_with_modes::create_find_iter(&scanner, input)
+            .tokens()
+            .fold_strings(STRING_TOKEN_TYPES)
+            .collect()
+    }
+
+    #[test]
+    fn test_decode_escape() {
+        assert_eq!(decode_escape("\\n"), Some('\n'));
+        assert_eq!(decode_escape("\\\""), Some('"'));
+        assert_eq!(decode_escape("\\u{1F600}"), Some('😀'));
+        assert_eq!(decode_escape("\\u{D800}"), None);
+        assert_eq!(decode_escape("\\q"), None);
+        assert_eq!(decode_escape("\\nn"), None);
+        assert_eq!(decode_escape("n"), None);
+    }
+
+    #[test]
+    fn test_fold_strings() {
+        let items = fold("id \"a\\tb\\\n  c\" x");
+        assert_eq!(items.len(), 5);
+        assert!(matches!(&items[0], StringItem::Token(token) if token.text == "id"));
+        assert_eq!(
+            items[2],
+            StringItem::String(DecodedString {
+                span: (3usize..14).into(),
+                value: "a\tb  c".to_string(),
+                invalid_escapes: Vec::new(),
+                terminated: true,
+            })
+        );
+        assert!(matches!(&items[4], StringItem::Token(token) if token.text == "x"));
+    }
+
+    #[test]
+    fn test_fold_unterminated_string() {
+        let items = fold("\"abc");
+        assert_eq!(
+            items,
+            vec![StringItem::String(DecodedString {
+                span: (0usize..4).into(),
+                value: "abc".to_string(),
+                invalid_escapes: Vec::new(),
+                terminated: false,
+            })]
+        );
+    }
+}
@@ -0,0 +1,174 @@
+use crate::{Match, Span, TokenType};
+
+use super::{
+    ConversionError, CookRules, CookedTokens, FindMatches, FoldStrings, InternTokens, Interner,
+    StringTokenTypes, TokenValueConverters, TypedToken,
+};
+
+/// A token, i.e. a match along with the text it covers in the haystack.
+///
+/// * `'h` represents the lifetime of the haystack the token was found in.
+///
+/// Tokens are yielded by the [TokenIter] created with [crate::Scanner::token_iter]. Use
+/// [Token::to_owned_token] to keep a token beyond the lifetime of the haystack.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Token<'h> {
+    /// The token type of the token.
+    pub token_type: TokenType,
+    /// The span of the token in the haystack.
+    pub span: Span,
+    /// The text of the token.
+    pub text: &'h str,
+}
+
+impl<'h> Token<'h> {
+    /// Creates the token of the given match in the given haystack.
+    /// If the match doesn't lie within the haystack, the text of the token is empty.
+    pub fn new(ma: Match, input: &'h str) -> Self {
+        Token {
+            token_type: ma.token_type(),
+            span: ma.span(),
+            text: input.get(ma.range()).unwrap_or_default(),
+        }
+    }
+
+    /// Returns the match of the token.
+    #[inline]
+    pub fn as_match(&self) -> Match {
+        Match::new(self.token_type, self.span)
+    }
+
+    /// Returns a copy of the token that owns its text.
+    pub fn to_owned_token(&self) -> OwnedToken {
+        OwnedToken::from(*self)
+    }
+}
+
+/// A token that owns its text. It is created from a [Token] with [Token::to_owned_token].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct OwnedToken {
+    /// The token type of the token.
+    pub token_type: TokenType,
+    /// The span of the token in the haystack.
+    pub span: Span,
+    /// The text of the token.
+    pub text: String,
+}
+
+impl OwnedToken {
+    /// Returns the token that borrows its text from this one.
+    pub fn as_token(&self) -> Token<'_> {
+        Token {
+            token_type: self.token_type,
+            span: self.span,
+            text: &self.text,
+        }
+    }
+}
+
+impl From<Token<'_>> for OwnedToken {
+    fn from(token: Token<'_>) -> Self {
+        OwnedToken {
+            token_type: token.token_type,
+            span: token.span,
+            text: token.text.to_string(),
+        }
+    }
+}
+
+/// An iterator over the tokens of a haystack.
+///
+/// * `'h` represents the lifetime of the haystack being searched.
+///
+/// This iterator can be created with [crate::Scanner::token_iter] or from a [FindMatches] with
+/// [FindMatches::tokens].
+#[derive(Debug)]
+pub struct TokenIter<'h> {
+    input: &'h str,
+    find_matches: FindMatches<'h>,
+}
+
+impl<'h> TokenIter<'h> {
+    /// Creates a new `TokenIter` over the matches of the given iterator.
+    pub(crate) fn new(input: &'h str, find_matches: FindMatches<'h>) -> Self {
+        TokenIter {
+            input,
+            find_matches,
+        }
+    }
+
+    /// Returns an iterator adapter that converts the text of the tokens into values with the
+    /// given converters. See [TokenValueConverters].
+    pub fn typed<'c, T>(
+        self,
+        converters: &'c TokenValueConverters<T>,
+    ) -> impl Iterator<Item = Result<TypedToken<'h, T>, ConversionError>> + 'c
+    where
+        'h: 'c,
+    {
+        self.map(move |token| converters.convert(token))
+    }
+
+    /// Returns an iterator adapter that folds the tokens of string literals into decoded strings.
+    /// See [FoldStrings].
+    pub fn fold_strings(self, token_types: StringTokenTypes) -> FoldStrings<Self> {
+        FoldStrings::new(self, token_types)
+    }
+
+    /// Returns an iterator adapter that turns the tokens into cooked tokens with the given rules,
+    /// e.g. to merge the pieces of string literals. See [CookedTokens].
+    pub fn cook(self, rules: CookRules) -> CookedTokens<'h> {
+        let input = self.input;
+        CookedTokens::new(input, self, rules)
+    }
+
+    /// Returns an iterator adapter that interns the text of the tokens of the given token types
+    /// into the given interner and yields the matches along with their symbols. See
+    /// [InternTokens].
+    pub fn intern<'i>(
+        self,
+        interner: &'i mut dyn Interner,
+        token_types: &[TokenType],
+    ) -> InternTokens<'i, Self> {
+        InternTokens::new(self, interner, token_types)
+    }
+
+    /// Returns the iterator over the matches the tokens are created from.
+    pub fn find_matches(&mut self) -> &mut FindMatches<'h> {
+        &mut self.find_matches
+    }
+}
+
+impl<'h> Iterator for TokenIter<'h> {
+    type Item = Token<'h>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.find_matches
+            .next()
+            .map(|ma| Token::new(ma, self.input))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::runtime::generated::scanner_with_modes;
+
+    #[test]
+    fn test_token_iter() {
+        let scanner = scanner_with_modes::create_scanner();
+        let find_iter = scanner_with_modes::create_find_iter(&scanner, "Id1\nId2");
+        let matches = find_iter.collect::<Vec<_>>();
+        let tokens = scanner_with_modes::create_find_iter(&scanner, "Id1\nId2")
+            .tokens()
+            .collect::<Vec<_>>();
+        assert_eq!(tokens.len(), matches.len());
+        for (token, ma) in tokens.iter().zip(&matches) {
+            assert_eq!(token.as_match(), *ma);
+            assert_eq!(token.text, &"Id1\nId2"[ma.range()]);
+        }
+        assert_eq!(tokens[0].text, "Id1");
+        let owned = tokens[0].to_owned_token();
+        assert_eq!(owned.text, "Id1");
+        assert_eq!(owned.as_token(), tokens[0]);
+    }
+}
@@ -0,0 +1,155 @@
+use std::collections::HashMap;
+
+use crate::{Span, TokenType};
+
+use super::Token;
+
+/// A converter of the text of a token into a value. It returns an error message if the text
+/// can't be converted, e.g. if a number literal overflows.
+pub type ValueConverter<T> = Box<dyn Fn(&str) -> Result<T, String>>;
+
+/// A token along with the value converted from its text.
+///
+/// The value is `None` if no converter is registered for the token type of the token.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypedToken<'h, T> {
+    /// The token the value was converted from.
+    pub token: Token<'h>,
+    /// The converted value.
+    pub value: Option<T>,
+}
+
+/// An error that occurs if the text of a token can't be converted into a value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConversionError {
+    /// The token type of the token.
+    pub token_type: TokenType,
+    /// The span of the token in the haystack.
+    pub span: Span,
+    /// The message returned by the converter.
+    pub message: String,
+}
+
+impl std::fmt::Display for ConversionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Token of type {} at {} can't be converted: {}",
+            self.token_type, self.span, self.message
+        )
+    }
+}
+
+impl std::error::Error for ConversionError {}
+
+/// The converters of the token types whose tokens carry a value, e.g. number literals or strings.
+///
+/// The values are converted while scanning with [crate::TokenIter::typed], so a second pass over
+/// the tokens is not necessary.
+///
+/// ```rust
+/// use scangen::{TokenType, TokenValueConverters};
+/// let converters = TokenValueConverters::new()
+///     .register(TokenType::new(5), |text| text.parse::<i64>().map_err(|e| e.to_string()));
+/// ```
+pub struct TokenValueConverters<T> {
+    converters: HashMap<TokenType, ValueConverter<T>>,
+}
+
+impl<T> TokenValueConverters<T> {
+    /// Creates an empty set of converters.
+    pub fn new() -> Self {
+        Self {
+            converters: HashMap::new(),
+        }
+    }
+
+    /// Registers the converter of the given token type. An existing converter of the token type
+    /// is replaced.
+    pub fn register<F>(mut self, token_type: TokenType, converter: F) -> Self
+    where
+        F: Fn(&str) -> Result<T, String> + 'static,
+    {
+        self.converters.insert(token_type, Box::new(converter));
+        self
+    }
+
+    /// Converts the text of the given token with the converter of its token type.
+    ///
+    /// # Errors
+    /// Returns a [ConversionError] if the converter fails.
+    pub fn convert<'h>(&self, token: Token<'h>) -> Result<TypedToken<'h, T>, ConversionError> {
+        let value = self
+            .converters
+            .get(&token.token_type)
+            .map(|converter| converter(token.text))
+            .transpose()
+            .map_err(|message| ConversionError {
+                token_type: token.token_type,
+                span: token.span,
+                message,
+            })?;
+        Ok(TypedToken { token, value })
+    }
+}
+
+impl<T> Default for TokenValueConverters<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> std::fmt::Debug for TokenValueConverters<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TokenValueConverters")
+            .field("token_types", &self.converters.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::generated::scanner_with_modes;
+
+    #[derive(Debug, PartialEq)]
+    enum Value {
+        Identifier(String),
+        Escape(char),
+    }
+
+    #[test]
+    fn test_typed_tokens() {
+        let converters = TokenValueConverters::new()
+            .register(TokenType::new(4), |text| {
+                Ok(Value::Identifier(text.to_uppercase()))
+            })
+            .register(TokenType::new(5), |text| match text {
+                "\\n" => Ok(Value::Escape('\n')),
+                "\\t" => Ok(Value::Escape('\t')),
+                _ => Err(format!("unsupported escape {}", text)),
+            });
+        let scanner = scanner_with_modes::create_scanner();
+        let input = r#"id "a\tb\b""#;
+        let values = scanner_with_modes::create_find_iter(&scanner, input)
+            .tokens()
+            .typed(&converters)
+            .filter_map(|token| match token {
+                Ok(TypedToken { value, .. }) => value.map(Ok),
+                Err(err) => Some(Err(err)),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            values,
+            vec![
+                Ok(Value::Identifier("ID".to_string())),
+                Ok(Value::Escape('\t')),
+                Err(ConversionError {
+                    token_type: TokenType::new(5),
+                    span: (8usize..10).into(),
+                    message: "unsupported escape \\b".to_string(),
+                }),
+            ]
+        );
+    }
+}
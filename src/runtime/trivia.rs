@@ -0,0 +1,158 @@
+use std::iter::Peekable;
+
+use crate::{Match, TokenType};
+
+/// Determines to which significant token the trivia tokens are attached.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TriviaAttachment {
+    /// Trivia is attached as leading trivia to the following significant token.
+    #[default]
+    Leading,
+    /// Trivia is attached as trailing trivia to the preceding significant token.
+    /// Trivia before the first significant token is attached as leading trivia to it.
+    Trailing,
+}
+
+/// A significant token along with its attached trivia tokens.
+///
+/// Concatenating the leading trivia, the token and the trailing trivia of all items yields the
+/// complete token stream, i.e. the model is lossless.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenWithTrivia {
+    /// The trivia tokens before the token.
+    pub leading: Vec<Match>,
+    /// The significant token. `None` denotes the end of the input and is only used to carry
+    /// trivia that can't be attached to any significant token.
+    pub token: Option<Match>,
+    /// The trivia tokens after the token.
+    pub trailing: Vec<Match>,
+}
+
+/// An iterator adapter that attaches trivia tokens, e.g. whitespace and comments, to significant
+/// tokens.
+///
+/// The iterator yields a [TokenWithTrivia] for each significant token. This is the token model
+/// of lossless syntax trees like the ones of rowan or rust-analyzer.
+///
+/// This adapter can be created with [crate::FindMatches::attach_trivia] or [AttachTrivia::new].
+#[derive(Debug)]
+pub struct AttachTrivia<I>
+where
+    I: Iterator<Item = Match>,
+{
+    matches: Peekable<I>,
+    trivia: Vec<TokenType>,
+    attachment: TriviaAttachment,
+}
+
+impl<I> AttachTrivia<I>
+where
+    I: Iterator<Item = Match>,
+{
+    /// Creates a new adapter that attaches matches of the given trivia token types to the
+    /// significant matches.
+    pub fn new<M, T>(matches: M, trivia: T, attachment: TriviaAttachment) -> Self
+    where
+        M: IntoIterator<IntoIter = I>,
+        T: IntoIterator<Item = TokenType>,
+    {
+        AttachTrivia {
+            matches: matches.into_iter().peekable(),
+            trivia: trivia.into_iter().collect(),
+            attachment,
+        }
+    }
+
+    /// Collects the trivia tokens up to the next significant token.
+    fn collect_trivia(&mut self) -> Vec<Match> {
+        let mut trivia = Vec::new();
+        while let Some(matched) = self.matches.peek() {
+            if !self.trivia.contains(&matched.token_type()) {
+                break;
+            }
+            trivia.extend(self.matches.next());
+        }
+        trivia
+    }
+}
+
+impl<I> Iterator for AttachTrivia<I>
+where
+    I: Iterator<Item = Match>,
+{
+    type Item = TokenWithTrivia;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let leading = self.collect_trivia();
+        let token = self.matches.next();
+        if token.is_none() && leading.is_empty() {
+            return None;
+        }
+        let trailing = match (self.attachment, token) {
+            (TriviaAttachment::Trailing, Some(_)) => self.collect_trivia(),
+            _ => Vec::new(),
+        };
+        Some(TokenWithTrivia {
+            leading,
+            token,
+            trailing,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::generated::scanner_with_modes::{create_find_iter, create_scanner};
+
+    const INPUT: &str = " a  b\n";
+
+    fn texts(tokens: &[TokenWithTrivia]) -> Vec<(Vec<&str>, Option<&str>, Vec<&str>)> {
+        let text = |m: &Match| &INPUT[m.span().range()];
+        tokens
+            .iter()
+            .map(|t| {
+                (
+                    t.leading.iter().map(text).collect(),
+                    t.token.as_ref().map(text),
+                    t.trailing.iter().map(text).collect(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_leading_trivia() {
+        let scanner = create_scanner();
+        let mut find_iter = create_find_iter(&scanner, INPUT);
+        find_iter.set_trivia([TokenType::new(0), TokenType::new(1)]);
+        let tokens = find_iter
+            .attach_trivia(TriviaAttachment::Leading)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            texts(&tokens),
+            vec![
+                (vec![" "], Some("a"), vec![]),
+                (vec!["  "], Some("b"), vec![]),
+                (vec!["\n"], None, vec![]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_trailing_trivia() {
+        let scanner = create_scanner();
+        let mut find_iter = create_find_iter(&scanner, INPUT);
+        find_iter.set_trivia([TokenType::new(0), TokenType::new(1)]);
+        let tokens = find_iter
+            .attach_trivia(TriviaAttachment::Trailing)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            texts(&tokens),
+            vec![
+                (vec![" "], Some("a"), vec!["  "]),
+                (vec![], Some("b"), vec!["\n"]),
+            ]
+        );
+    }
+}